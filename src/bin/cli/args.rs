@@ -6,11 +6,41 @@ use clap::{Args, Parser, Subcommand};
 use super::helpers::*;
 use mdp::{
     commands::{
+        agenda::config::{AgendaConfig, AgendaPeriod},
+        explain::config::ExplainConfig,
+        fmt::config::FmtConfig,
+        gaps::config::GapsConfig,
+        goals::config::GoalsConfig,
+        attrs::config::AttrsConfig,
+        queries::config::RenderQueriesConfig,
+        review::config::{ReviewConfig, ReviewPeriod},
+        plan::config::{PlanConfig, PlanWeek},
+        io::OutputTarget,
+        keywords::config::{KeywordsConfig, DEFAULT_KEYWORD_LIMIT},
+        outdated::config::OutdatedConfig,
+        replace::config::ReplaceConfig,
+        sort::config::SortConfig,
         tags::config::TagsConfig,
-        search::config::{SearchTerm, SearchConfig},
-        tasks::config::TasksConfig,
+        search::config::{SearchTerm, SearchConfig, SearchTermMatching, AttributeFilter},
+        badge::config::{BadgeConfig, Thresholds},
+        cat::config::CatConfig,
+        lint::config::LintConfig,
+        projects::config::{ProjectsConfig, DEFAULT_PROJECTS_NAMESPACE},
+        waiting::config::WaitingConfig,
+        compare::config::{CompareConfig, CompareLayout},
+        backlinks::config::BacklinksConfig,
+        links::config::LinksConfig,
+        stats::config::{SectionStatsConfig, StatsConfig},
+        summarize::config::SummarizeConfig,
+        tasks::config::{
+            TaskCapacityConfig, TaskDoneConfig, TaskEditConfig, TaskShowConfig,
+            TaskSnoozeConfig, TasksConfig,
+        },
+        parse_days, InputBuilder, RelativeDate, TimeZone,
         tree::config::TreeConfig,
+        validation::Validate,
     },
+    markdown::DEFAULT_MAX_RECURSION_DEPTH,
     models::ConfigError,
 };
 
@@ -20,6 +50,113 @@ use mdp::{
 pub struct CliArgs {
     #[command(subcommand)]
     pub command: Command,
+
+    /// Pretend today is the given date (affects e.g. task urgency)
+    #[arg(long = "today", global = true)]
+    pub today: Option<NaiveDate>,
+
+    /// Time zone in which the days start: local, UTC or an offset like +02:00 (default: the
+    /// time_zone of config.toml or local)
+    #[arg(long = "time-zone", value_name = "TZ", global = true)]
+    pub time_zone: Option<TimeZone>,
+
+    /// Skip files and lines which can not be read or parsed instead of aborting
+    #[arg(long = "best-effort", global = true)]
+    pub best_effort: bool,
+
+    /// Abort on lines which can not be parsed instead of keeping them as plain text (with a
+    /// warning), and on lines which look like mistyped markup (e.g. `TODO : Task`)
+    #[arg(long = "strict", global = true)]
+    pub strict: bool,
+
+    /// Follow symlinks within input directories (every file is still read only once)
+    #[arg(long = "follow-symlinks", global = true)]
+    pub follow_symlinks: bool,
+
+    /// Ignore the limits on the number and size of input files and tokens
+    #[arg(long = "force", global = true)]
+    pub force: bool,
+
+    /// Cache the tokens of the input files in .mdp-cache, so that later runs only parse the
+    /// files which changed (can also be enabled in the [cache] of config.toml)
+    #[arg(long = "cache", global = true)]
+    pub cache: bool,
+
+    /// Maximum heading depth up to which sections are nested
+    #[arg(long = "max-section-depth", global = true, default_value_t = DEFAULT_MAX_RECURSION_DEPTH)]
+    pub max_section_depth: usize,
+
+    /// How sections are recognized in the input files
+    #[arg(long = "structure", global = true, value_enum, default_value_t = SectionStructure::Headings)]
+    pub structure: SectionStructure,
+
+    /// How top level sections without a date in their title are dated
+    #[arg(long = "date-policy", global = true, value_enum, default_value_t = DatePolicy::Require)]
+    pub date_policy: DatePolicy,
+
+    /// Which tokens are tags: at (`@tag`), hash (`#tag`, like Obsidian) or both (default:
+    /// the style in the [tags] of config.toml or at)
+    #[arg(long = "tag-style", global = true, value_enum)]
+    pub tag_style: Option<TagStyle>,
+
+    /// Shell command to run after the output file was written (the path is passed as
+    /// last argument), can be given multiple times
+    #[arg(long = "post-hook", global = true, value_name = "CMD")]
+    pub post_hooks: Vec<String>,
+
+    /// Append the output to the output file instead of replacing it
+    #[arg(long = "append", global = true)]
+    pub append: bool,
+
+    /// Print how the output would change the output file (as unified diff) instead of
+    /// writing it
+    #[arg(long = "dry-run", global = true)]
+    pub dry_run: bool,
+
+    /// Don't execute any external commands (e.g. post-write hooks)
+    #[arg(long = "no-exec", global = true)]
+    pub no_exec: bool,
+
+    /// Merge the sections of the same date of different input files into one day
+    #[arg(long = "merge-days", global = true)]
+    pub merge_days: bool,
+
+    /// Run the command in a running `mdp daemon` if available
+    #[arg(long = "daemon", global = true)]
+    pub daemon: bool,
+
+    /// What to do if the output file already exists (ignored with --append)
+    #[arg(long = "overwrite", global = true, value_enum, default_value_t = OverwritePolicy::Always)]
+    pub overwrite: OverwritePolicy,
+
+    /// What to do if the output file is inside an input directory, where later runs would
+    /// read it as input (default: the output_in_input in the [files] of config.toml or exclude)
+    #[arg(long = "output-in-input", global = true, value_enum)]
+    pub output_in_input: Option<OutputInInput>,
+
+    /// Where the output is written, a comma separated list of stdout, file:<path>, clipboard
+    /// and editor (opens a read-only copy in $EDITOR); replaces stdout and --output
+    #[arg(
+        long = "output-target",
+        global = true,
+        value_name = "TARGETS",
+        value_delimiter = ','
+    )]
+    pub output_targets: Vec<OutputTarget>,
+
+    /// Color the output on the terminal (configure the colors in the [theme] of config.toml)
+    #[arg(long = "color", global = true, value_enum, default_value_t = ColorMode::Auto)]
+    pub color: ColorMode,
+
+    /// Language of the texts of the output, e.g. table headers (default: the lang of
+    /// config.toml or English)
+    #[arg(long = "lang", global = true, value_enum)]
+    pub lang: Option<Language>,
+
+    /// Only validate the options (e.g. that `--until` is not before `--from`) without running
+    /// the command
+    #[arg(long = "check-args", global = true)]
+    pub check_args: bool,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -27,16 +164,76 @@ pub enum Command {
     Search(SearchCommandArgs),
     Tags(TagsCommandArgs),
     Tree(TreeCommandArgs),
+    Cat(CatCommandArgs),
     Tasks(TasksCommandArgs),
+    Stats(StatsCommandArgs),
+    Agenda(AgendaCommandArgs),
+    Compare(CompareCommandArgs),
+    Backlinks(BacklinksCommandArgs),
+    Links(LinksCommandArgs),
+    Lint(LintCommandArgs),
+    Waiting(WaitingCommandArgs),
+    Projects(ProjectsCommandArgs),
+    Goals(GoalsCommandArgs),
+    Attrs(AttrsCommandArgs),
+    RenderQueries(RenderQueriesCommandArgs),
+    Review(ReviewCommandArgs),
+    Plan(PlanCommandArgs),
+    Keywords(KeywordsCommandArgs),
+    Summarize(SummarizeCommandArgs),
+    Outdated(OutdatedCommandArgs),
+    Sort(SortCommandArgs),
+    Replace(ReplaceCommandArgs),
+    Fmt(FmtCommandArgs),
+    Gaps(GapsCommandArgs),
+    Badge(BadgeCommandArgs),
+    Daemon(DaemonCommandArgs),
+    Explain(ExplainCommandArgs),
+    Completions(CompletionsCommandArgs),
+    Manpage(ManpageCommandArgs),
 }
 
-/// List tags
+/// The input files of the commands reading markdown files
 #[derive(Args, Debug, Clone)]
-pub struct TagsCommandArgs {
-    /// One or multiple paths to the markdown files
+pub struct InputArgs {
+    /// One or multiple paths to the markdown files, `@FILE` reads the paths (or globs) from
+    /// FILE, one per line
     #[arg(short = 'i', long = "input")]
     pub input_path: Vec<PathBuf>,
 
+    /// Also include markdown files in subdirectories of input directories
+    #[arg(short = 'r', long = "recursive")]
+    pub recursive: bool,
+
+    /// Maximum number of directory levels to descend into (implies --recursive)
+    #[arg(long = "max-depth")]
+    pub max_depth: Option<usize>,
+}
+
+impl InputArgs {
+    /// The input paths and the number of directory levels to descend into (`None` without
+    /// limit)
+    pub fn into_paths(self) -> (Vec<PathBuf>, Option<usize>) {
+        (self.input_path, traversal_depth(self.recursive, self.max_depth))
+    }
+
+    /// Set the input files of a config builder
+    pub fn apply<B: InputBuilder>(self, builder: B) -> B {
+        let (input_path, max_depth) = self.into_paths();
+        let builder = builder.inputs(input_path);
+        match max_depth {
+            Some(depth) => builder.max_depth(depth),
+            None => builder.recursive(true),
+        }
+    }
+}
+
+/// List tags
+#[derive(Args, Debug, Clone)]
+pub struct TagsCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
     /// Export list to file
     #[arg(short = 'o', long = "output", default_value = None)]
     pub output_path: Option<PathBuf>,
@@ -49,40 +246,911 @@ pub struct TagsCommandArgs {
         default_value = "alphabetic"
     )]
     pub ordering: TagOrderingCriterion,
+
+    /// Exit with code 1 if nothing was found (errors exit with code 2)
+    #[arg(long = "fail-if-empty")]
+    pub fail_if_empty: bool,
 }
 
 impl TryFrom<TagsCommandArgs> for TagsConfig {
     type Error = ConfigError;
 
     fn try_from(args: TagsCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
         Ok(Self {
-            input_path: args.input_path,
+            input_path,
+            max_depth,
             ordering: args.ordering.into(),
             output_path: args.output_path,
         })
     }
 }
 
+/// Show statistics (sections, words, tasks, tags, date coverage) per file
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct StatsCommandArgs {
+    #[command(subcommand)]
+    pub action: Option<StatsAction>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export statistics to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+
+    /// List the sections never included in the output of commands and the ones included
+    /// most often (recorded with `record_coverage` in the [stats] settings)
+    #[arg(long = "coverage")]
+    pub coverage: bool,
+}
+
+impl TryFrom<StatsCommandArgs> for StatsConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: StatsCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            coverage: None,
+        })
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum StatsAction {
+    Sections(StatsSectionsArgs),
+}
+
+/// List the sections with the most words, tasks or links (e.g. to split them into notes)
+#[derive(Args, Debug, Clone)]
+pub struct StatsSectionsArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the sections to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+
+    /// Number of sections listed
+    #[arg(long = "top", default_value_t = 20)]
+    pub top: usize,
+
+    /// The metric the sections are ranked by (of their content without subsections)
+    #[arg(long = "by", value_enum, default_value = "words")]
+    pub by: SectionMetric,
+}
+
+impl TryFrom<StatsSectionsArgs> for SectionStatsConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: StatsSectionsArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            top: args.top,
+            by: args.by.into(),
+        })
+    }
+}
+
+/// Show the overdue tasks and the tasks and sections of a day or week
+#[derive(Args, Debug, Clone)]
+pub struct AgendaCommandArgs {
+    /// Day shown (YYYY-MM-DD or relative to today, e.g. +1d or -1w), today by default
+    #[arg(long = "day", value_name = "DATE", allow_hyphen_values = true)]
+    pub day: Option<RelativeDate>,
+
+    /// Show the whole week (Monday to Sunday) of the day
+    #[arg(long = "week")]
+    pub week: bool,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export agenda to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+
+    /// Also show the tasks snoozed until a later day
+    #[arg(long = "include-snoozed")]
+    pub include_snoozed: bool,
+
+    /// Show the events of an iCalendar file (.ics) on their days (repeat for several files)
+    #[arg(long = "ics-import", value_name = "FILE")]
+    pub calendars: Vec<PathBuf>,
+}
+
+impl TryFrom<AgendaCommandArgs> for AgendaConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: AgendaCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            day: args.day.unwrap_or(RelativeDate::Days(0)),
+            period: match args.week {
+                true => AgendaPeriod::Week,
+                false => AgendaPeriod::Day,
+            },
+            include_snoozed: args.include_snoozed,
+            calendars: args.calendars,
+        })
+    }
+}
+
+/// Compare the subsections and tasks of two days (e.g. for a weekly review)
+#[derive(Args, Debug, Clone)]
+pub struct CompareCommandArgs {
+    /// First day (YYYY-MM-DD or relative to today, e.g. -1w)
+    #[arg(value_name = "DATE1", allow_hyphen_values = true)]
+    pub first: RelativeDate,
+
+    /// Second day (YYYY-MM-DD or relative to today, e.g. 0d)
+    #[arg(value_name = "DATE2", allow_hyphen_values = true)]
+    pub second: RelativeDate,
+
+    /// Show the differences in one column (like `diff -u`) instead of side by side
+    #[arg(long = "unified")]
+    pub unified: bool,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export comparison to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<CompareCommandArgs> for CompareConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: CompareCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            first: args.first,
+            second: args.second,
+            layout: match args.unified {
+                true => CompareLayout::Unified,
+                false => CompareLayout::SideBySide,
+            },
+        })
+    }
+}
+
+/// List the sections linking to a note with a wiki link (`[[Note]]`)
+#[derive(Args, Debug, Clone)]
+pub struct BacklinksCommandArgs {
+    /// The linked note, optionally with a heading (e.g. "Project X#Budget")
+    #[arg(value_name = "NAME", required_unless_present = "graph")]
+    pub target: Option<String>,
+
+    /// List all links between the notes (`note#section -> note#heading`)
+    #[arg(long = "graph", conflicts_with = "target")]
+    pub graph: bool,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export backlinks to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<BacklinksCommandArgs> for BacklinksConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: BacklinksCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            target: args.target,
+            graph: args.graph,
+        })
+    }
+}
+
+/// List the wiki links (`[[Note#Heading]]`) and links to headings (`[Text](#anchor)`)
+#[derive(Args, Debug, Clone)]
+pub struct LinksCommandArgs {
+    /// Only list the links whose note, heading or anchor does not exist (fails if there are
+    /// any)
+    #[arg(long = "check")]
+    pub check: bool,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the links to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<LinksCommandArgs> for LinksConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: LinksCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            check: args.check,
+        })
+    }
+}
+
+/// Check the files against the conventions of mdp (fails if they are violated)
+#[derive(Args, Debug, Clone)]
+pub struct LintCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the violations to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+
+    /// Correctly spelled tag (in addition to the known tags of config.toml), other tags are
+    /// reported if any tags are known
+    #[arg(long = "known-tag", value_name = "TAG")]
+    pub known_tags: Vec<String>,
+}
+
+impl TryFrom<LintCommandArgs> for LintConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: LintCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            known_tags: args.known_tags,
+        })
+    }
+}
+
+/// List the delegated tasks (`WAITING(@person):`) grouped by person
+#[derive(Args, Debug, Clone)]
+pub struct WaitingCommandArgs {
+    /// Only list the tasks waiting for this person (e.g. "@roger")
+    #[arg(value_name = "PERSON")]
+    pub person: Option<String>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the waiting tasks to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<WaitingCommandArgs> for WaitingConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: WaitingCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            person: args.person,
+        })
+    }
+}
+
+/// Summarize the projects (the subsections of the `## Projects` headings)
+#[derive(Args, Debug, Clone)]
+pub struct ProjectsCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the summary to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+
+    /// Title of the headings containing the projects (default: the namespace of the
+    /// [projects] in config.toml or "Projects")
+    #[arg(long = "namespace", value_name = "TITLE")]
+    pub namespace: Option<String>,
+
+    /// Number of the latest sections shown per project
+    #[arg(long = "recent", value_name = "N", default_value_t = 3)]
+    pub recent: usize,
+}
+
+impl TryFrom<ProjectsCommandArgs> for ProjectsConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: ProjectsCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            namespace: args
+                .namespace
+                .unwrap_or_else(|| DEFAULT_PROJECTS_NAMESPACE.to_string()),
+            recent: args.recent,
+        })
+    }
+}
+
+/// Show the progress of the goals (`Goal::` and `Progress::` attributes) over time
+#[derive(Args, Debug, Clone)]
+pub struct GoalsCommandArgs {
+    /// Only show the progress of this goal
+    #[arg(value_name = "GOAL")]
+    pub goal: Option<String>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the progress to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<GoalsCommandArgs> for GoalsConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: GoalsCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            goal: args.goal,
+        })
+    }
+}
+
+/// List the attributes (`Key:: value`) with their values and how often they occur
+#[derive(Args, Debug, Clone)]
+pub struct AttrsCommandArgs {
+    /// Only list the values of this attribute
+    #[arg(value_name = "NAME")]
+    pub name: Option<String>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the attributes to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<AttrsCommandArgs> for AttrsConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: AttrsCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            name: args.name,
+        })
+    }
+}
+
+/// Run the queries embedded in the notes (```` ```mdp-query ```` blocks) and write their
+/// results below them (the queries search all input files)
+#[derive(Args, Debug, Clone)]
+pub struct RenderQueriesCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Only list the files whose query results are outdated (fails if there are any)
+    #[arg(long = "check")]
+    pub check: bool,
+}
+
+impl TryFrom<RenderQueriesCommandArgs> for RenderQueriesConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: RenderQueriesCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            check: args.check,
+            dry_run: false,
+        })
+    }
+}
+
+/// Report a week or month: its sections grouped by tag, its done and open tasks and the
+/// tags used for the first time
+#[derive(Args, Debug, Clone)]
+pub struct ReviewCommandArgs {
+    /// The ISO week reviewed (e.g. 2024-W12), the current week by default
+    #[arg(long = "week", value_name = "WEEK", conflicts_with = "month")]
+    pub week: Option<String>,
+
+    /// The month reviewed (e.g. 2024-03)
+    #[arg(long = "month", value_name = "MONTH")]
+    pub month: Option<String>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the review to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<ReviewCommandArgs> for ReviewConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: ReviewCommandArgs) -> Result<Self, Self::Error> {
+        let period = match (args.week, args.month) {
+            (Some(week), _) => Some(review_period(&week, true)?),
+            (None, Some(month)) => Some(review_period(&month, false)?),
+            (None, None) => None,
+        };
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            period,
+        })
+    }
+}
+
+/// Plan a week: the unfinished tasks carried over grouped by priority, the deadlines of the
+/// week and an empty subheading for every day
+#[derive(Args, Debug, Clone)]
+pub struct PlanCommandArgs {
+    /// The planned week: `this`, `next` or an ISO week (e.g. 2024-W13)
+    #[arg(long = "week", value_name = "WEEK", default_value = "next")]
+    pub week: String,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Write the plan to file (e.g. the journal together with --append)
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<PlanCommandArgs> for PlanConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: PlanCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            week: args.week.parse::<PlanWeek>()?,
+        })
+    }
+}
+
+/// List the terms characteristic for the sections of a period or with a tag (TF-IDF against
+/// all other sections)
+#[derive(Args, Debug, Clone)]
+pub struct KeywordsCommandArgs {
+    /// Only analyze sections of this day or later (YYYY-MM-DD or relative, e.g. -30d)
+    #[arg(long = "from", allow_hyphen_values = true)]
+    pub from: Option<RelativeDate>,
+
+    /// Only analyze sections of this day or earlier (YYYY-MM-DD or relative, e.g. -1d)
+    #[arg(long = "until", allow_hyphen_values = true)]
+    pub until: Option<RelativeDate>,
+
+    /// Only analyze sections with the tag (repeat it for several tags)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Maximal number of keywords shown
+    #[arg(short = 'n', long = "limit", default_value_t = DEFAULT_KEYWORD_LIMIT)]
+    pub limit: usize,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export the keywords to file
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<KeywordsCommandArgs> for KeywordsConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: KeywordsCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            from: args.from,
+            until: args.until,
+            tags: args.tags,
+            limit: args.limit,
+        })
+    }
+}
+
+/// Summarize the sections of a period or with a tag with an external command (e.g. a local
+/// language model) reading their plain text from stdin
+#[derive(Args, Debug, Clone)]
+pub struct SummarizeCommandArgs {
+    /// The summarizer, reading the text from stdin and writing the summary to stdout (by
+    /// default the `command` of the [summarize] section of config.toml)
+    #[arg(long = "command", value_name = "COMMAND")]
+    pub command: Option<String>,
+
+    /// Only summarize sections of this day or later (YYYY-MM-DD or relative, e.g. -7d)
+    #[arg(long = "from", allow_hyphen_values = true)]
+    pub from: Option<RelativeDate>,
+
+    /// Only summarize sections of this day or earlier (YYYY-MM-DD or relative, e.g. -1d)
+    #[arg(long = "until", allow_hyphen_values = true)]
+    pub until: Option<RelativeDate>,
+
+    /// Only summarize sections with the tag (repeat it for several tags)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Write the summary to file (e.g. the journal together with --append)
+    #[arg(short = 'o', long = "output", default_value = None)]
+    pub output_path: Option<PathBuf>,
+}
+
+impl TryFrom<SummarizeCommandArgs> for SummarizeConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: SummarizeCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        let config = Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            from: args.from,
+            until: args.until,
+            tags: args.tags,
+            command: args.command.unwrap_or_default(),
+            exec_policy: Default::default(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// The period of `--week` (or `--month`), which needs to be a week (or month)
+fn review_period(value: &str, week: bool) -> Result<ReviewPeriod, ConfigError> {
+    let period = value.parse::<ReviewPeriod>()?;
+    match (period, week) {
+        (ReviewPeriod::Week { .. }, true) | (ReviewPeriod::Month { .. }, false) => Ok(period),
+        (_, true) => Err(ConfigError::InvalidOptionsError(format!(
+            "--week expects a week like 2024-W12, not '{}'",
+            value
+        ))),
+        (_, false) => Err(ConfigError::InvalidOptionsError(format!(
+            "--month expects a month like 2024-03, not '{}'",
+            value
+        ))),
+    }
+}
+
+/// List the input files generated with `search --frontmatter` whose input changed since
+#[derive(Args, Debug, Clone)]
+pub struct OutdatedCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Rerun the searches of the outdated files
+    #[arg(long = "regenerate")]
+    pub regenerate: bool,
+}
+
+impl TryFrom<OutdatedCommandArgs> for OutdatedConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: OutdatedCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            regenerate: args.regenerate,
+        })
+    }
+}
+
+/// Reorder the days of files chronologically, in place (see the global --dry-run to only
+/// show the changes)
+#[derive(Args, Debug, Clone)]
+pub struct SortCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Put the latest day first
+    #[arg(long = "desc")]
+    pub descending: bool,
+}
+
+impl TryFrom<SortCommandArgs> for SortConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: SortCommandArgs) -> Result<Self, Self::Error> {
+        Ok(args
+            .input
+            .apply(SortConfig::builder())
+            .descending(args.descending)
+            .build())
+    }
+}
+
+/// Replace text only within the chosen kinds of tokens (never within code), in place (see
+/// the global --dry-run to only show the changes)
+#[derive(Args, Debug, Clone)]
+pub struct ReplaceCommandArgs {
+    /// The text to find
+    #[arg(value_name = "FIND")]
+    pub find: String,
+
+    /// The text replacing it
+    #[arg(value_name = "REPLACEMENT")]
+    pub replacement: String,
+
+    /// Comma separated kinds of tokens to replace within
+    #[arg(long = "in", value_enum, value_delimiter = ',', default_value = "text")]
+    pub scopes: Vec<ReplaceScope>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl TryFrom<ReplaceCommandArgs> for ReplaceConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: ReplaceCommandArgs) -> Result<Self, Self::Error> {
+        let mut builder = args
+            .input
+            .apply(ReplaceConfig::builder())
+            .replace(args.find, args.replacement);
+        for scope in args.scopes {
+            builder = builder.scope(scope.into());
+        }
+        let config = builder.build();
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Reformat files into the canonical style (headings, blank lines, task keywords), in
+/// place (see the global --dry-run to only show the changes)
+#[derive(Args, Debug, Clone)]
+pub struct FmtCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Only list the files which are not formatted (fails if there are any)
+    #[arg(long = "check")]
+    pub check: bool,
+}
+
+impl TryFrom<FmtCommandArgs> for FmtConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: FmtCommandArgs) -> Result<Self, Self::Error> {
+        Ok(args
+            .input
+            .apply(FmtConfig::builder())
+            .check(args.check)
+            .build())
+    }
+}
+
+/// List the days without a section, optionally adding sections for them from the daily
+/// template (see the global --dry-run to only show the changes)
+#[derive(Args, Debug, Clone)]
+pub struct GapsCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// First day to check (YYYY-MM-DD or relative, e.g. -30d), the first day with a section
+    /// by default
+    #[arg(long = "from", allow_hyphen_values = true)]
+    pub from: Option<RelativeDate>,
+
+    /// Last day to check (YYYY-MM-DD or relative, e.g. -1d), today by default
+    #[arg(long = "until", allow_hyphen_values = true)]
+    pub until: Option<RelativeDate>,
+
+    /// Add a section for every day without one, next to the closest day
+    #[arg(long = "fill")]
+    pub fill: bool,
+
+    /// File with the template of the added sections, `{date}` and `{weekday}` are replaced
+    /// (`daily_template` in the [journal] settings, `# {date}` by default)
+    #[arg(long = "template")]
+    pub template: Option<PathBuf>,
+}
+
+impl TryFrom<GapsCommandArgs> for GapsConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: GapsCommandArgs) -> Result<Self, Self::Error> {
+        let mut builder = args
+            .input
+            .apply(GapsConfig::builder())
+            .fill(args.fill);
+        if let Some(from) = args.from {
+            builder = builder.from(from);
+        }
+        if let Some(until) = args.until {
+            builder = builder.until(until);
+        }
+        if let Some(template) = args.template {
+            builder = builder.template(template);
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Create an SVG badge showing a metric (e.g. the number of open tasks)
+#[derive(Args, Debug, Clone)]
+pub struct BadgeCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Export badge to file
+    #[arg(short = 'o', long = "output", default_value = "./badge.svg")]
+    pub output_path: PathBuf,
+
+    /// Metric shown on the badge
+    #[arg(long = "metric", value_enum, default_value = "open-tasks")]
+    pub metric: BadgeMetric,
+
+    /// Text on the left side of the badge (defaults to the name of the metric)
+    #[arg(long = "label")]
+    pub label: Option<String>,
+
+    /// Value from which on the badge is yellow (below which for the streak)
+    #[arg(long = "warning", requires = "critical")]
+    pub warning: Option<usize>,
+
+    /// Value from which on the badge is red (below which for the streak)
+    #[arg(long = "critical", requires = "warning")]
+    pub critical: Option<usize>,
+}
+
+impl TryFrom<BadgeCommandArgs> for BadgeConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: BadgeCommandArgs) -> Result<Self, Self::Error> {
+        let thresholds = match (args.warning, args.critical) {
+            (Some(warning), Some(critical)) => Some(Thresholds { warning, critical }),
+            _ => None,
+        };
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            output_path: args.output_path,
+            metric: args.metric.into(),
+            label: args.label,
+            thresholds,
+        })
+    }
+}
+
+/// Keep the files in memory and answer the commands run with --daemon
+#[derive(Args, Debug, Clone)]
+pub struct DaemonCommandArgs {
+    /// Path of the socket to listen on (defaults to $MDP_SOCKET or the user's runtime directory)
+    #[arg(long = "socket")]
+    pub socket: Option<PathBuf>,
+}
+
+/// Print the completion script for a shell (e.g. `source <(mdp completions bash)`)
+#[derive(Args, Debug, Clone)]
+pub struct CompletionsCommandArgs {
+    /// The shell to complete the commands and options in
+    #[arg(name = "SHELL", value_enum)]
+    pub shell: Shell,
+}
+
+/// Print the man page (e.g. `mdp manpage > ~/.local/share/man/man1/mdp.1`)
+#[derive(Args, Debug, Clone)]
+pub struct ManpageCommandArgs {}
+
 /// Search for tags
 #[derive(Args, Debug, Clone)]
 pub struct SearchCommandArgs {
-    /// The tag(s) to look for (comma-separated)
-    #[arg(name = "TERM")]
-    pub search_string: String,
+    /// The term(s) to look for (comma-separated): `@tag`, `#hashtag` or a word of the text
+    #[arg(
+        name = "TERM",
+        required_unless_present_any = ["history", "again", "attributes"]
+    )]
+    pub search_string: Option<String>,
 
-    /// One or multiple paths to the markdown files
-    #[arg(short = 'i', long = "input")]
-    pub input_path: Vec<PathBuf>,
+    /// List the previously executed searches
+    #[arg(long = "history", conflicts_with = "again")]
+    pub history: bool,
+
+    /// Rerun the N-th most recent search of the history (see --history)
+    #[arg(long = "again", value_name = "N")]
+    pub again: Option<usize>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
 
     /// Export list to file
     #[arg(short = 'o', long = "output", default_value = "./search.md")]
     pub output_path: PathBuf,
 
+    /// Write every matched section to its own file (named by date and title) in this directory
+    /// instead of exporting one list
+    #[arg(long = "split-output", value_name = "DIR", conflicts_with = "output_path")]
+    pub split_output: Option<PathBuf>,
+
+    /// Start the output with a YAML frontmatter block describing the query and the input
+    #[arg(long = "frontmatter")]
+    pub frontmatter: bool,
+
+    /// Group the matched sections below one heading per search term
+    #[arg(long = "group-by", value_enum, conflicts_with = "split_output")]
+    pub group_by: Option<SearchGrouping>,
+
+    /// Show raw URLs as links titled like the pages they point to (fetches the pages over the
+    /// network, the titles are cached)
+    #[arg(long = "fetch-titles")]
+    pub fetch_titles: bool,
+
+    /// Mark the matched tags and words in the sections with `^^highlight^^` (colored on the
+    /// terminal)
+    #[arg(long = "highlight")]
+    pub highlight: bool,
+
+    /// Only show sections containing the attribute (`Key:: value`), given as `Key=value` or as
+    /// `Key` for any value (repeat it to require several attributes)
+    #[arg(long = "attr", value_name = "KEY[=VALUE]")]
+    pub attributes: Vec<String>,
+
+    /// Match the terms as regular expressions (case insensitive) against the tags and headings
+    #[arg(long = "regex", conflicts_with = "fuzzy")]
+    pub regex: bool,
+
+    /// Match tags and headings similar to the terms (e.g. misspelled ones), with the minimal
+    /// similarity between 0 and 1
+    #[arg(
+        long = "fuzzy",
+        value_name = "THRESHOLD",
+        num_args = 0..=1,
+        default_missing_value = "0.8"
+    )]
+    pub fuzzy: Option<f64>,
+
     /// Defines how multiple search terms are logically combined
     #[arg(long = "mode", rename_all = "UPPER", default_value = "or")]
     pub search_mode: TagSearchMode,
 
-    /// Defines the ordering of search results
+    /// Where all terms have to match to match in AND mode
+    #[arg(long = "scope", value_enum, default_value = "section")]
+    pub scope: SearchScope,
+
+    /// Defines the ordering of search results (ties are broken by relevance and date, then
+    /// the order of the input files)
     #[arg(
         long = "order",
         value_enum,
@@ -91,6 +1159,10 @@ pub struct SearchCommandArgs {
     )]
     pub ordering: SectionOrderingCriterion,
 
+    /// Reverse the ordering of the search results, e.g. newest first
+    #[arg(long = "reverse")]
+    pub reverse: bool,
+
     /// Write matched sections also to stdout
     #[clap(long = "stdout", global = true)]
     pub stdout: bool,
@@ -102,64 +1174,127 @@ pub struct SearchCommandArgs {
     /// Only consider sections before this date
     #[clap(long = "until")]
     pub until: Option<NaiveDate>,
+
+    /// Write the matched sections as Markdown or one tab separated line each (date, tags,
+    /// title and path:line), e.g. for awk or fzf
+    #[arg(
+        long = "format",
+        value_enum,
+        default_value_t = ResultFormat::Markdown,
+        conflicts_with_all = ["split_output", "group_by"]
+    )]
+    pub format: ResultFormat,
+
+    /// Exit with code 1 if nothing was found (errors exit with code 2)
+    #[arg(long = "fail-if-empty")]
+    pub fail_if_empty: bool,
 }
 
 impl TryFrom<SearchCommandArgs> for SearchConfig {
     type Error = ConfigError;
 
     fn try_from(args: SearchCommandArgs) -> Result<Self, Self::Error> {
-        Ok(Self {
-            input_path: args.input_path,
+        let matching = match (args.regex, args.fuzzy) {
+            (_, Some(threshold)) => SearchTermMatching::Fuzzy(threshold),
+            (true, None) => SearchTermMatching::Regex,
+            (false, None) => SearchTermMatching::Exact,
+        };
+        let (input_path, max_depth) = args.input.into_paths();
+        let config = Self {
+            input_path,
+            max_depth,
             output_path: args.output_path,
             ordering: args.ordering.into(),
+            reverse: args.reverse,
             search_terms: args
                 .search_string
-                .split(',')
-                .collect::<Vec<&str>>()
+                .as_deref()
+                .map_or(vec![], |s| s.split(',').collect::<Vec<&str>>())
                 .iter()
                 .map(|s| {
-                    s.trim()
-                        .to_string()
-                        .try_into()
-                        .map_err(|_| ConfigError::InvalidSearchTermError)
+                    SearchTerm::new(s.trim().to_string(), &matching)
+                        .map_err(|e| ConfigError::InvalidSearchTermError(e.term()))
                 })
                 .collect::<Result<Vec<SearchTerm>, Self::Error>>()?,
             search_mode: args.search_mode.into(),
+            scope: args.scope.into(),
             from: args.from,
             until: args.until,
-        })
+            split_output: args.split_output,
+            frontmatter: args.frontmatter,
+            group_by: args.group_by.map(Into::into),
+            fetch_titles: args.fetch_titles,
+            highlight: args.highlight,
+            attributes: args
+                .attributes
+                .iter()
+                .map(|a| a.parse())
+                .collect::<Result<Vec<AttributeFilter>, Self::Error>>()?,
+            format: args.format.into(),
+        };
+        config.validate()?;
+        Ok(config)
     }
 }
 
 /// Show tree of Markdown content/tokens
 #[derive(Args, Debug, Clone)]
 pub struct TreeCommandArgs {
-    /// One or multiple paths to the markdown files
-    #[arg(short = 'i', long = "input")]
-    pub input_path: Vec<PathBuf>,
+    #[command(flatten)]
+    pub input: InputArgs,
 
     /// Activate debug mode: Print everything using debug representation
     #[clap(long = "debug", global = false)]
     pub debug: bool,
+
+    /// Print the tree drawn as text or the section tree with outline numbers and origins as
+    /// JSON (e.g. for editors)
+    #[arg(long = "format", value_enum, default_value_t = TreeFormat::Text, conflicts_with = "debug")]
+    pub format: TreeFormat,
 }
 
 impl TryFrom<TreeCommandArgs> for TreeConfig {
     type Error = ConfigError;
 
     fn try_from(args: TreeCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
         Ok(Self {
-            input_path: args.input_path,
+            input_path,
+            max_depth,
             debug: args.debug,
+            format: args.format.into(),
+        })
+    }
+}
+
+/// Print notes, styled on the terminal (headings, tasks, links, ...)
+#[derive(Args, Debug, Clone)]
+pub struct CatCommandArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl TryFrom<CatCommandArgs> for CatConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: CatCommandArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
         })
     }
 }
 
-/// Show all tasks (TODO, TODO UNTIL <DATE>, DOING, REVIEW, DONE)
+/// Show all tasks (TODO, TODO UNTIL <DATE>, DOING, REVIEW, WAITING(@PERSON), DONE)
 #[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct TasksCommandArgs {
-    /// One or multiple paths to the markdown files
-    #[arg(short = 'i', long = "input")]
-    pub input_path: Vec<PathBuf>,
+    #[command(subcommand)]
+    pub action: Option<TasksAction>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
 
     /// Export task list to a file
     #[arg(short = 'o', long = "output", default_value = None)]
@@ -177,17 +1312,269 @@ pub struct TasksCommandArgs {
         default_value = "occurence"
     )]
     pub ordering: TaskOrderingCriterion,
+
+    /// Prefix every task with the file, line and column it was found at
+    #[arg(long = "origin")]
+    pub show_origin: bool,
+
+    /// Show the date and the headings each task is nested in
+    #[arg(long = "context")]
+    pub context: bool,
+
+    /// Prefix every task with its id (to be used with `mdp tasks done`)
+    #[arg(long = "ids")]
+    pub show_ids: bool,
+
+    /// Also show the tasks snoozed until a later day
+    #[arg(long = "include-snoozed")]
+    pub include_snoozed: bool,
+
+    /// Only show tasks of sections from this date on
+    #[arg(long = "from")]
+    pub from: Option<NaiveDate>,
+
+    /// Only show tasks of sections up to this date
+    #[arg(long = "until")]
+    pub until: Option<NaiveDate>,
+
+    /// Only show tasks due (TODO UNTIL) on or after this date
+    #[arg(long = "due-after", value_name = "DATE")]
+    pub due_after: Option<NaiveDate>,
+
+    /// Only show tasks due (TODO UNTIL) on or before this date
+    #[arg(long = "due-before", value_name = "DATE")]
+    pub due_before: Option<NaiveDate>,
+
+    /// Nest the tasks written as list items (`- TODO: Task`) below their parent list items
+    #[arg(long = "tree")]
+    pub tree: bool,
+
+    /// Add a summary: the tasks per status, the percentage done, the number of overdue
+    /// tasks and the next deadline
+    #[arg(long = "summary")]
+    pub summary: bool,
+
+    /// Write the tasks as Markdown or one tab separated line each (date, status, task and
+    /// path:line, with --ids starting with the id), e.g. for awk or fzf
+    #[arg(
+        long = "format",
+        value_enum,
+        default_value_t = ResultFormat::Markdown,
+        conflicts_with_all = ["tree", "summary"]
+    )]
+    pub format: ResultFormat,
+
+    /// Exit with code 1 if nothing was found (errors exit with code 2)
+    #[arg(long = "fail-if-empty")]
+    pub fail_if_empty: bool,
 }
 
 impl TryFrom<TasksCommandArgs> for TasksConfig {
     type Error = ConfigError;
 
     fn try_from(args: TasksCommandArgs) -> Result<Self, Self::Error> {
-        Ok(Self {
-            input_path: args.input_path,
+        let (input_path, max_depth) = args.input.into_paths();
+        let config = Self {
+            input_path,
+            max_depth,
             output_path: args.output_path,
             ordering: args.ordering.into(),
             filter: args.filter.into(),
+            show_origin: args.show_origin,
+            context: args.context,
+            show_ids: args.show_ids,
+            include_snoozed: args.include_snoozed,
+            from: args.from,
+            until: args.until,
+            due_after: args.due_after,
+            due_before: args.due_before,
+            tree: args.tree,
+            summary: args.summary,
+            format: args.format.into(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TasksAction {
+    Done(TaskDoneArgs),
+    Edit(TaskEditArgs),
+    Capacity(TaskCapacityArgs),
+    Show(TaskShowArgs),
+    Snooze(TaskSnoozeArgs),
+}
+
+/// Mark a task as done in the file it was found in
+#[derive(Args, Debug, Clone)]
+pub struct TaskDoneArgs {
+    /// The id shown by `mdp tasks --ids` or the position in the list shown by `mdp tasks`
+    #[arg(name = "ID")]
+    pub task: String,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl TryFrom<TaskDoneArgs> for TaskDoneConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: TaskDoneArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            task: args.task,
+            dry_run: false,
+        })
+    }
+}
+
+/// Show a task with the whole section it was found in
+#[derive(Args, Debug, Clone)]
+pub struct TaskShowArgs {
+    /// The id shown by `mdp tasks --ids` or the position in the list shown by `mdp tasks`
+    #[arg(name = "ID")]
+    pub task: String,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl TryFrom<TaskShowArgs> for TaskShowConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: TaskShowArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            task: args.task,
+        })
+    }
+}
+
+/// Hide a task until a day by adding `SNOOZED UNTIL <DATE>` to it
+#[derive(Args, Debug, Clone)]
+pub struct TaskSnoozeArgs {
+    /// The id shown by `mdp tasks --ids` or the position in the list shown by `mdp tasks`
+    #[arg(name = "ID")]
+    pub task: String,
+
+    /// Day the task is shown again (YYYY-MM-DD or relative to today, e.g. +3d or 1w)
+    #[arg(
+        long = "until",
+        value_name = "DATE",
+        allow_hyphen_values = true,
+        default_value = "1d"
+    )]
+    pub until: RelativeDate,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl TryFrom<TaskSnoozeArgs> for TaskSnoozeConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: TaskSnoozeArgs) -> Result<Self, Self::Error> {
+        let (input_path, max_depth) = args.input.into_paths();
+        Ok(Self {
+            input_path,
+            max_depth,
+            task: args.task,
+            until: args.until,
+            dry_run: false,
         })
     }
 }
+
+/// Change the due dates of tasks in the files they were found in
+#[derive(Args, Debug, Clone)]
+pub struct TaskEditArgs {
+    /// Ids shown by `mdp tasks --ids` or positions in the list shown by `mdp tasks`
+    #[arg(name = "ID", required_unless_present = "filter")]
+    pub tasks: Vec<String>,
+
+    /// Change all tasks of the chosen kind
+    #[arg(long = "filter", value_enum)]
+    pub filter: Option<TaskEditFilter>,
+
+    /// Set the due date (YYYY-MM-DD or relative to today, e.g. +7d or 2w)
+    #[arg(
+        long = "set-due",
+        value_name = "DATE",
+        allow_hyphen_values = true,
+        required_unless_present = "shift",
+        conflicts_with = "shift"
+    )]
+    pub set_due: Option<RelativeDate>,
+
+    /// Move the due date by a number of days or weeks (e.g. 3d, -1w)
+    #[arg(long = "shift", value_name = "OFFSET", allow_hyphen_values = true, value_parser = parse_days)]
+    pub shift: Option<i64>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl TryFrom<TaskEditArgs> for TaskEditConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: TaskEditArgs) -> Result<Self, Self::Error> {
+        let mut builder = args.input.apply(TaskEditConfig::builder());
+        for task in args.tasks {
+            builder = builder.task(task);
+        }
+        if let Some(filter) = args.filter {
+            builder = builder.filter(filter.into());
+        }
+        if let Some(date) = args.set_due {
+            builder = builder.set_due(date);
+        }
+        if let Some(days) = args.shift {
+            builder = builder.shift(days);
+        }
+        builder.build()
+    }
+}
+
+/// Show the days on which more tasks are due than the daily capacity allows
+#[derive(Args, Debug, Clone)]
+pub struct TaskCapacityArgs {
+    /// Maximum number of tasks due per day (defaults to `daily_capacity` in `config.toml`)
+    #[arg(long = "max", value_name = "N")]
+    pub capacity: Option<usize>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl TryFrom<TaskCapacityArgs> for TaskCapacityConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: TaskCapacityArgs) -> Result<Self, Self::Error> {
+        let mut builder = args.input.apply(TaskCapacityConfig::builder());
+        if let Some(capacity) = args.capacity {
+            builder = builder.capacity(capacity);
+        }
+        builder.build()
+    }
+}
+
+/// Explain an error code (e.g. MDP001) in detail
+#[derive(Args, Debug, Clone)]
+pub struct ExplainCommandArgs {
+    /// The error code shown in the error message
+    #[arg(name = "CODE")]
+    pub code: String,
+}
+
+impl TryFrom<ExplainCommandArgs> for ExplainConfig {
+    type Error = ConfigError;
+
+    fn try_from(args: ExplainCommandArgs) -> Result<Self, Self::Error> {
+        Ok(Self { code: args.code })
+    }
+}
@@ -1,6 +1,214 @@
 use clap::ValueEnum;
 
-use mdp::commands::{tags, search, tasks};
+use mdp::commands::{badge, io, locale, oneline, replace, stats, tags, search, tasks, theme, tree};
+use mdp::markdown;
+
+/// Maximum directory depth for input traversal given the CLI flags.
+///
+/// Without `--recursive` only the top level of an input directory is read, an explicit
+/// `--max-depth` always takes precedence.
+pub fn traversal_depth(recursive: bool, max_depth: Option<usize>) -> Option<usize> {
+    match (recursive, max_depth) {
+        (_, Some(depth)) => Some(depth),
+        (true, None) => None,
+        (false, None) => Some(1),
+    }
+}
+
+/// How the sections of the input files are structured.
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub enum SectionStructure {
+    /// Sections are started by headings (`#`, `##`, ...)
+    #[default]
+    Headings,
+    /// Sections are (indented) bullets of an outline
+    Outline,
+}
+
+/// How top level sections without a date in their title are dated.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum DatePolicy {
+    /// Sections without a date are an error
+    #[default]
+    Require,
+    /// The day the file was last modified
+    FileMtime,
+    /// The first date (YYYY-MM-DD) in the file name
+    Filename,
+    /// Keep the sections without a date, they only match queries without a time range
+    None,
+}
+
+impl From<DatePolicy> for markdown::DatePolicy {
+    fn from(policy: DatePolicy) -> Self {
+        match policy {
+            DatePolicy::Require => Self::Require,
+            DatePolicy::FileMtime => Self::FileMtime,
+            DatePolicy::Filename => Self::Filename,
+            DatePolicy::None => Self::None,
+        }
+    }
+}
+
+/// Shells for which `mdp completions` generates a completion script.
+#[derive(Clone, Debug, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl From<Shell> for clap_complete::Shell {
+    fn from(shell: Shell) -> Self {
+        match shell {
+            Shell::Bash => Self::Bash,
+            Shell::Zsh => Self::Zsh,
+            Shell::Fish => Self::Fish,
+        }
+    }
+}
+
+/// Which tokens are the tags of the sections.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TagStyle {
+    /// `@tag` (`#tag` is a hashtag)
+    At,
+    /// `#tag`, like in Obsidian (`@tag` is no tag)
+    Hash,
+    /// `@tag` as well as `#tag`
+    Both,
+}
+
+impl From<TagStyle> for markdown::TagStyle {
+    fn from(style: TagStyle) -> Self {
+        match style {
+            TagStyle::At => Self::At,
+            TagStyle::Hash => Self::Hash,
+            TagStyle::Both => Self::Both,
+        }
+    }
+}
+
+/// Kinds of tokens `mdp replace` changes.
+#[derive(Clone, Debug, ValueEnum)]
+pub enum ReplaceScope {
+    /// Plain text (also of headings, lists and link titles)
+    Text,
+    /// Targets of links, embeds and images
+    Links,
+    /// Names of tags
+    Tags,
+}
+
+impl From<ReplaceScope> for replace::config::ReplaceScope {
+    fn from(scope: ReplaceScope) -> Self {
+        match scope {
+            ReplaceScope::Text => Self::Text,
+            ReplaceScope::Links => Self::Links,
+            ReplaceScope::Tags => Self::Tags,
+        }
+    }
+}
+
+/// When the terminal output is colored.
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub enum ColorMode {
+    /// Only if stdout is a terminal and NO_COLOR is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<ColorMode> for theme::ColorMode {
+    fn from(mode: ColorMode) -> Self {
+        match mode {
+            ColorMode::Auto => Self::Auto,
+            ColorMode::Always => Self::Always,
+            ColorMode::Never => Self::Never,
+        }
+    }
+}
+
+/// Language of the texts of the output.
+#[derive(Clone, Debug, ValueEnum)]
+pub enum Language {
+    /// English
+    En,
+    /// German
+    De,
+}
+
+impl From<Language> for locale::Language {
+    fn from(lang: Language) -> Self {
+        match lang {
+            Language::En => Self::English,
+            Language::De => Self::German,
+        }
+    }
+}
+
+/// What happens if the output file already exists.
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub enum OverwritePolicy {
+    /// Keep the existing file and fail
+    Never,
+    /// Replace the existing file
+    #[default]
+    Always,
+    /// Ask whether the existing file should be replaced
+    Prompt,
+}
+
+impl From<OverwritePolicy> for io::OverwritePolicy {
+    fn from(policy: OverwritePolicy) -> Self {
+        match policy {
+            OverwritePolicy::Never => Self::Never,
+            OverwritePolicy::Always => Self::Always,
+            OverwritePolicy::Prompt => Self::Prompt,
+        }
+    }
+}
+
+/// What happens if the output file is inside an input directory.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputInInput {
+    /// Fail before the input is read
+    Error,
+    /// Warn and read the output file as input
+    Warn,
+    /// Skip the output file when reading the input
+    Exclude,
+}
+
+impl From<OutputInInput> for io::OutputInInput {
+    fn from(policy: OutputInInput) -> Self {
+        match policy {
+            OutputInInput::Error => Self::Error,
+            OutputInInput::Warn => Self::Warn,
+            OutputInInput::Exclude => Self::Exclude,
+        }
+    }
+}
+
+/// The metric `stats sections` ranks the sections by.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SectionMetric {
+    Words,
+    Tasks,
+    /// Wiki links, embeds, Markdown links and raw URLs
+    Links,
+}
+
+impl From<SectionMetric> for stats::config::SectionMetric {
+    fn from(metric: SectionMetric) -> Self {
+        match metric {
+            SectionMetric::Words => Self::Words,
+            SectionMetric::Tasks => Self::Tasks,
+            SectionMetric::Links => Self::Links,
+        }
+    }
+}
 
 #[derive(Clone, Debug, ValueEnum)]
 pub enum TagOrderingCriterion {
@@ -32,10 +240,74 @@ impl From<TagSearchMode> for search::config::TagSearchMode {
     }
 }
 
+#[derive(Clone, Debug, ValueEnum)]
+pub enum SearchScope {
+    Section,
+    Day,
+    File,
+}
+
+impl From<SearchScope> for search::config::SearchScope {
+    fn from(scope: SearchScope) -> Self {
+        match scope {
+            SearchScope::Section => Self::Section,
+            SearchScope::Day => Self::Day,
+            SearchScope::File => Self::File,
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum SearchGrouping {
+    /// One section per search term (e.g. `# @work`)
+    Tag,
+}
+
+impl From<SearchGrouping> for search::config::SearchGrouping {
+    fn from(grouping: SearchGrouping) -> Self {
+        match grouping {
+            SearchGrouping::Tag => Self::Tag,
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum TreeFormat {
+    Text,
+    Json,
+}
+
+impl From<TreeFormat> for tree::config::TreeFormat {
+    fn from(format: TreeFormat) -> Self {
+        match format {
+            TreeFormat::Text => Self::Text,
+            TreeFormat::Json => Self::Json,
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum ResultFormat {
+    Markdown,
+    Oneline,
+}
+
+impl From<ResultFormat> for oneline::ResultFormat {
+    fn from(format: ResultFormat) -> Self {
+        match format {
+            ResultFormat::Markdown => Self::Markdown,
+            ResultFormat::Oneline => Self::Oneline,
+        }
+    }
+}
+
 #[derive(Clone, Debug, ValueEnum)]
 pub enum SectionOrderingCriterion {
     Relevance,
     Date,
+    Title,
+    TagCount,
+    FileOrder,
 }
 
 impl From<SectionOrderingCriterion> for search::config::SectionOrderingCriterion {
@@ -43,6 +315,9 @@ impl From<SectionOrderingCriterion> for search::config::SectionOrderingCriterion
         match mode {
             SectionOrderingCriterion::Relevance => Self::Relevance,
             SectionOrderingCriterion::Date => Self::Date,
+            SectionOrderingCriterion::Title => Self::Title,
+            SectionOrderingCriterion::TagCount => Self::TagCount,
+            SectionOrderingCriterion::FileOrder => Self::FileOrder,
         }
     }
 }
@@ -80,3 +355,35 @@ impl From<TaskFilterType> for tasks::config::TaskFilterType {
         }
     }
 }
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum TaskEditFilter {
+    Unfinished,
+    Overdue,
+}
+
+impl From<TaskEditFilter> for tasks::config::TaskEditFilter {
+    fn from(filter: TaskEditFilter) -> Self {
+        match filter {
+            TaskEditFilter::Unfinished => Self::Unfinished,
+            TaskEditFilter::Overdue => Self::Overdue,
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum BadgeMetric {
+    OpenTasks,
+    OverdueTasks,
+    Streak,
+}
+
+impl From<BadgeMetric> for badge::config::BadgeMetric {
+    fn from(metric: BadgeMetric) -> Self {
+        match metric {
+            BadgeMetric::OpenTasks => Self::OpenTasks,
+            BadgeMetric::OverdueTasks => Self::OverdueTasks,
+            BadgeMetric::Streak => Self::Streak,
+        }
+    }
+}
@@ -1,86 +1,925 @@
 pub mod args;
 pub mod helpers;
 
-use anyhow::Result;
-use clap::Parser;
+use std::{path::PathBuf, process::ExitCode};
+
+use anyhow::{anyhow, Result};
+use clap::{CommandFactory, Parser};
 use simple_logger::SimpleLogger;
 
-use crate::args::{CliArgs, Command};
-use mdp::{
-    commands::{
-        io::{FileWriter, MarkdownFileReader, OutputWriter, StdoutWriter},
-        tags::{self, config::TagsConfig}, search::{self, config::SearchConfig}, tasks, tree::{self, config::TreeConfig},
+use crate::{
+    args::{
+        CliArgs, Command, DaemonCommandArgs, StatsAction, StatsCommandArgs, TasksAction,
+        TasksCommandArgs,
+    },
+    helpers::SectionStructure,
+};
+use mdp::commands::{
+    agenda::{command::AgendaCommand, config::AgendaConfig},
+    attrs::{command::AttrsCommand, config::AttrsConfig},
+    backlinks::{command::BacklinksCommand, config::BacklinksConfig},
+    badge::{command::BadgeCommand, config::BadgeConfig},
+    cache::TokenCache,
+    cat::{command::CatCommand, config::CatConfig},
+    compare::{command::CompareCommand, config::CompareConfig},
+    daemon::{self, default_socket_path, DaemonRequest, DaemonResponse, ResponseCache},
+    encryption::CacheLocation,
+    explain::{command::ExplainCommand, config::ExplainConfig},
+    fmt::{command::FmtCommand, config::FmtConfig},
+    gaps::{command::GapsCommand, config::GapsConfig},
+    goals::{command::GoalsCommand, config::GoalsConfig},
+    hooks::PostWriteHook,
+    io::{
+        CachedFileReader, FileCache, FileReader, FileWriterConfig, MarkdownFileReader, OutputSink,
+        OutputTarget, WriterRegistry,
+    },
+    keywords::{command::KeywordsCommand, config::KeywordsConfig},
+    limits::Limits,
+    links::{command::LinksCommand, config::LinksConfig},
+    lint::{command::LintCommand, config::LintConfig},
+    locale::{Language, Text},
+    outdated::{command::OutdatedCommand, config::OutdatedConfig},
+    plan::{command::PlanCommand, config::PlanConfig},
+    projects::{command::ProjectsCommand, config::ProjectsConfig},
+    queries::{command::RenderQueriesCommand, config::RenderQueriesConfig},
+    replace::{command::ReplaceCommand, config::ReplaceConfig},
+    review::{command::ReviewCommand, config::ReviewConfig},
+    search::{
+        command::SearchCommand,
+        config::SearchConfig,
+        history::{SearchHistory, SearchHistoryEntry},
+    },
+    settings::Settings,
+    sort::{command::SortCommand, config::SortConfig},
+    stats::{
+        command::StatsCommand,
+        config::{SectionStatsConfig, StatsConfig},
+        coverage::SectionCoverage,
+        sections::SectionStatsCommand,
+    },
+    summarize::{command::SummarizeCommand, config::SummarizeConfig},
+    tags::{command::TagsCommand, config::TagsConfig},
+    tasks::{
+        capacity::TaskCapacityCommand,
+        command::TasksCommand,
+        config::{
+            TaskCapacityConfig, TaskDoneConfig, TaskEditConfig, TaskShowConfig, TaskSnoozeConfig,
+            TasksConfig,
+        },
+        done::TaskDoneCommand,
+        edit::TaskEditCommand,
+        show::TaskShowCommand,
+        snooze::TaskSnoozeCommand,
     },
-    markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+    theme::ColorMode,
+    tree::{command::TreeCommand, config::TreeConfig},
+    waiting::{command::WaitingCommand, config::WaitingConfig},
+    Command as MDPCommand, FixedClock, ParseContext, SystemClock, TimeZone,
 };
+use mdp::markdown::{MDPMarkdownTokenizer, MDPSectionBuilder, OutlineSectionBuilder};
 
-fn main() -> Result<()> {
+/// A command built from the CLI arguments
+struct Invocation {
+    command: Box<dyn MDPCommand>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    /// The input paths as given (checked against the output path by `--check-args`)
+    input_path: Vec<PathBuf>,
+    /// Recorded in the search history after the command ran
+    executed_search: Option<SearchConfig>,
+    /// Commands writing files themselves (e.g. changing the input files) always run without
+    /// the daemon, which might run in another directory
+    writes_files: bool,
+    /// The results are problems (e.g. violations found by `lint`), so the program fails if
+    /// there are any
+    fails_on_results: bool,
+    /// Exit with [`EXIT_NOTHING_FOUND`] if there are no results (`--fail-if-empty`)
+    fails_if_empty: bool,
+}
+
+/// Exit code of a command without results run with `--fail-if-empty`
+const EXIT_NOTHING_FOUND: u8 = 1;
+/// Exit code of all errors, distinct from [`EXIT_NOTHING_FOUND`] so scripts can tell them
+/// apart
+const EXIT_ERROR: u8 = 2;
+
+fn main() -> ExitCode {
     SimpleLogger::new().init().unwrap();
     let cli = CliArgs::parse();
 
+    match run(&cli) {
+        Ok(code) => code,
+        Err(e) => {
+            // The settings might be the cause of the error
+            let lang = language(&cli, &load_settings().unwrap_or_default());
+            eprintln!("{}: {:?}", lang.text(Text::Error), e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn run(cli: &CliArgs) -> Result<ExitCode> {
     match &cli.command {
-        Command::Search(cmd_args) => {
-            let config = SearchConfig::try_from(cmd_args.to_owned())?;
-            let output_path = config.output_path.to_owned();
-            search::command::run(
-                config,
-                MDPMarkdownTokenizer {},
-                MDPSectionBuilder {},
-                MarkdownFileReader {},
-                vec![
-                    Box::new(StdoutWriter {}),
-                    Box::new(FileWriter { path: output_path }),
-                ],
-            )?
+        Command::Daemon(cmd_args) => {
+            run_daemon(cmd_args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Completions(cmd_args) => {
+            let shell = clap_complete::Shell::from(cmd_args.shell.clone());
+            clap_complete::generate(shell, &mut CliArgs::command(), "mdp", &mut std::io::stdout());
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Manpage(_) => {
+            clap_mangen::Man::new(CliArgs::command()).render(&mut std::io::stdout())?;
+            return Ok(ExitCode::SUCCESS);
         }
+        _ => {}
+    }
+    let settings = load_settings()?;
+    let Some(invocation) = invocation(cli, &settings)? else {
+        return Ok(ExitCode::SUCCESS);
+    };
+    // The options were validated while building the command, only the output file is
+    // checked against the input directories when the files are read
+    if cli.check_args {
+        let mut ctx = parse_context(cli, &settings, file_reader(cli, &settings, &invocation));
+        ctx.output_path = invocation.output_path.clone();
+        ctx.check_output_path(invocation.input_path.clone())?;
+        println!("{}", language(cli, &settings).text(Text::ValidOptions));
+        return Ok(ExitCode::SUCCESS);
+    }
 
-        Command::Tags(cmd_args) => {
-            let config = TagsConfig::try_from(cmd_args.to_owned())?;
+    let color = ColorMode::from(cli.color.clone()).enabled();
+    let registry = WriterRegistry::with_defaults(
+        color.then(|| settings.theme.clone()),
+        FileWriterConfig {
+            append: cli.append,
+            overwrite: cli.overwrite.clone().into(),
+            hooks: cli.post_hooks.iter().map(PostWriteHook::new).collect(),
+            exec_policy: settings.exec_policy(cli.no_exec),
+            dry_run: cli.dry_run,
+        },
+    );
+    let targets = match cli.output_targets.is_empty() {
+        // Only the diff is printed in a dry run
+        true => (!cli.dry_run || invocation.output_path.is_none())
+            .then(|| OutputTarget::new("stdout"))
+            .into_iter()
+            .chain(invocation.output_path.as_deref().map(OutputTarget::file))
+            .collect(),
+        false => cli.output_targets.clone(),
+    };
+    let mut writers = registry.writers(&targets)?;
 
-            let mut writers: Vec<Box<dyn OutputWriter>> = vec![Box::new(StdoutWriter {})];
-            if let Some(output_path) = &config.output_path {
-                writers.push(Box::new(FileWriter {
-                    path: output_path.to_owned(),
-                }));
+    let daemon_response = match cli.daemon && !invocation.writes_files {
+        true => query_daemon(),
+        false => None,
+    };
+    let (result_count, skipped, warnings, included) = match daemon_response {
+        Some(response) => {
+            if let Some(error) = response.error {
+                return Err(anyhow!(error));
             }
+            writers.write_output(&response.output)?;
+            // The daemon does not record the section coverage
+            (
+                response.result_count,
+                response.skipped,
+                response.warnings,
+                vec![],
+            )
+        }
+        None => {
+            let mut ctx = parse_context(cli, &settings, file_reader(cli, &settings, &invocation));
+            ctx.output_path = invocation.output_path.clone();
+            // The daemon keeps the files in memory instead
+            let cipher = settings.cache_cipher()?;
+            let tokenizer = ctx.tokenizer.cache_key();
+            if let Some(tokenizer) = tokenizer.filter(|_| cli.cache || settings.cache.enabled) {
+                ctx.cache = Some(TokenCache::load_from(
+                    CacheLocation {
+                        path: settings.cache.path.clone(),
+                        cipher: cipher.clone(),
+                    },
+                    &tokenizer,
+                ));
+            }
+            let fetch_titles = invocation.executed_search.as_ref().map(|c| c.fetch_titles);
+            if fetch_titles == Some(true) {
+                ctx.link_titles = Some(settings.link_titles(cipher));
+            }
+            let report = invocation.command.run(&ctx, &mut writers)?;
+            let skipped = ctx.skipped().iter().map(|s| s.to_string()).collect();
+            let warnings = ctx.warnings().iter().map(|w| w.to_string()).collect();
+            (report.result_count, skipped, warnings, ctx.included())
+        }
+    };
 
-            tags::command::run(
-                config,
-                MDPMarkdownTokenizer {},
-                MarkdownFileReader {},
-                writers,
-            )?
+    if let (Some(config), Some(history)) = (
+        invocation.executed_search,
+        SearchHistory::default_location(settings.cache_cipher()?),
+    ) {
+        let entry = SearchHistoryEntry {
+            timestamp: time_zone(cli, &settings).now(),
+            config,
+            result_count,
+        };
+        if let Err(e) = history.record(&entry) {
+            log::warn!("Could not record the search in the history: {}", e);
         }
+    }
 
-        Command::Tree(cmd_args) => {
-            let config = TreeConfig::try_from(cmd_args.to_owned())?;
-            tree::command::run(
-                config,
-                MDPMarkdownTokenizer {},
-                MDPSectionBuilder {},
-                MarkdownFileReader {},
-                vec![Box::new(StdoutWriter {})],
-            )?
+    if settings.stats.record_coverage && !cli.dry_run {
+        if let Some(coverage) = SectionCoverage::default_location(settings.cache_cipher()?) {
+            let now = time_zone(cli, &settings).now();
+            if let Err(e) = coverage.record(&included, now) {
+                log::warn!("Could not record the section coverage: {}", e);
+            }
         }
+    }
+
+    let lang = language(cli, &settings);
+    warn_about_input(lang.text(Text::KeptAsText), &warnings);
+    warn_about_input(lang.text(Text::SkippedInput), &skipped);
+
+    if invocation.fails_on_results && result_count > 0 {
+        return Err(anyhow!("Found {} problems", result_count));
+    }
+    if invocation.fails_if_empty && result_count == 0 {
+        return Ok(ExitCode::from(EXIT_NOTHING_FOUND));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn warn_about_input(title: &str, input: &[String]) {
+    if !input.is_empty() {
+        log::warn!(
+            "{}:\n{}",
+            title,
+            input
+                .iter()
+                .map(|s| format!("- {}", s))
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+    }
+}
 
-        Command::Tasks(cmd_args) => {
-            let config = tasks::config::TasksConfig::try_from(cmd_args.to_owned())?;
+/// Build the command, returns `None` if there is nothing left to run (e.g. after the
+/// search history was listed).
+fn invocation(cli: &CliArgs, settings: &Settings) -> Result<Option<Invocation>> {
+    let mut executed_search = None;
+    let mut writes_files = false;
+    let mut fails_on_results = false;
+    let mut fails_if_empty = false;
 
-            let mut writers: Vec<Box<dyn OutputWriter>> = vec![Box::new(StdoutWriter {})];
-            if let Some(output_path) = &config.output_path {
-                writers.push(Box::new(FileWriter {
-                    path: output_path.to_owned(),
-                }));
+    let (command, max_depth, output_path, input_path): (
+        Box<dyn MDPCommand>,
+        Option<usize>,
+        Option<PathBuf>,
+        Vec<PathBuf>,
+    ) = match cli.command.clone() {
+            Command::Search(cmd_args) => {
+                let history = SearchHistory::default_location(settings.cache_cipher()?)
+                    .ok_or_else(|| anyhow!("Could not determine the search history location"));
+                if cmd_args.history {
+                    print!("{}", history?.list()?);
+                    return Ok(None);
+                }
+                fails_if_empty = cmd_args.fail_if_empty;
+                let config = match cmd_args.again {
+                    Some(n) => {
+                        history?
+                            .entry(n)?
+                            .ok_or_else(|| anyhow!("There is no search {} in the history", n))?
+                            .config
+                    }
+                    None => SearchConfig::try_from(cmd_args)?,
+                };
+                executed_search = Some(config.clone());
+                // The daemon does not fetch titles
+                writes_files = config.split_output.is_some() || config.fetch_titles;
+                (
+                    Box::new(SearchCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.split_output.is_none().then_some(config.output_path),
+                    config.input_path,
+                )
+            }
+            Command::Tags(cmd_args) => {
+                fails_if_empty = cmd_args.fail_if_empty;
+                let config = TagsConfig::try_from(cmd_args)?;
+                (
+                    Box::new(TagsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Tree(cmd_args) => {
+                let config = TreeConfig::try_from(cmd_args)?;
+                (
+                    Box::new(TreeCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Cat(cmd_args) => {
+                let config = CatConfig::try_from(cmd_args)?;
+                (
+                    Box::new(CatCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Tasks(TasksCommandArgs {
+                action: Some(TasksAction::Done(cmd_args)),
+                ..
+            }) => {
+                let mut config = TaskDoneConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = !config.dry_run;
+                (
+                    Box::new(TaskDoneCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Tasks(TasksCommandArgs {
+                action: Some(TasksAction::Edit(cmd_args)),
+                ..
+            }) => {
+                let mut config = TaskEditConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = !config.dry_run;
+                (
+                    Box::new(TaskEditCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Tasks(TasksCommandArgs {
+                action: Some(TasksAction::Capacity(mut cmd_args)),
+                ..
+            }) => {
+                cmd_args.capacity = cmd_args.capacity.or(settings.tasks.daily_capacity);
+                if cmd_args.capacity.is_none() {
+                    return Err(anyhow!(
+                        "No daily capacity given, use --max or set daily_capacity in config.toml"
+                    ));
+                }
+                let config = TaskCapacityConfig::try_from(cmd_args)?;
+                (
+                    Box::new(TaskCapacityCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Tasks(TasksCommandArgs {
+                action: Some(TasksAction::Show(cmd_args)),
+                ..
+            }) => {
+                let config = TaskShowConfig::try_from(cmd_args)?;
+                (
+                    Box::new(TaskShowCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Tasks(TasksCommandArgs {
+                action: Some(TasksAction::Snooze(cmd_args)),
+                ..
+            }) => {
+                let mut config = TaskSnoozeConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = !config.dry_run;
+                (
+                    Box::new(TaskSnoozeCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Tasks(cmd_args) => {
+                fails_if_empty = cmd_args.fail_if_empty;
+                let config = TasksConfig::try_from(cmd_args)?;
+                (
+                    Box::new(TasksCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Stats(StatsCommandArgs {
+                action: Some(StatsAction::Sections(cmd_args)),
+                ..
+            }) => {
+                let config = SectionStatsConfig::try_from(cmd_args)?;
+                (
+                    Box::new(SectionStatsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Stats(cmd_args) => {
+                let coverage = cmd_args.coverage;
+                let mut config = StatsConfig::try_from(cmd_args)?;
+                if coverage {
+                    let coverage = SectionCoverage::default_location(settings.cache_cipher()?)
+                        .ok_or_else(|| {
+                            anyhow!("Could not determine the section coverage location")
+                        })?;
+                    config.coverage = Some(coverage.location);
+                }
+                (
+                    Box::new(StatsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Agenda(cmd_args) => {
+                let config = AgendaConfig::try_from(cmd_args)?;
+                (
+                    Box::new(AgendaCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Compare(cmd_args) => {
+                let config = CompareConfig::try_from(cmd_args)?;
+                (
+                    Box::new(CompareCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Backlinks(cmd_args) => {
+                let config = BacklinksConfig::try_from(cmd_args)?;
+                (
+                    Box::new(BacklinksCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Links(cmd_args) => {
+                let config = LinksConfig::try_from(cmd_args)?;
+                fails_on_results = config.check;
+                (
+                    Box::new(LinksCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Lint(cmd_args) => {
+                let mut config = LintConfig::try_from(cmd_args)?;
+                config
+                    .known_tags
+                    .extend(settings.tags.known.iter().cloned());
+                fails_on_results = true;
+                (
+                    Box::new(LintCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
             }
+            Command::Waiting(cmd_args) => {
+                let config = WaitingConfig::try_from(cmd_args)?;
+                (
+                    Box::new(WaitingCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Projects(mut cmd_args) => {
+                cmd_args.namespace = cmd_args.namespace.or(settings.projects.namespace.clone());
+                let config = ProjectsConfig::try_from(cmd_args)?;
+                (
+                    Box::new(ProjectsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Goals(cmd_args) => {
+                let config = GoalsConfig::try_from(cmd_args)?;
+                (
+                    Box::new(GoalsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Attrs(cmd_args) => {
+                let config = AttrsConfig::try_from(cmd_args)?;
+                (
+                    Box::new(AttrsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::RenderQueries(cmd_args) => {
+                let mut config = RenderQueriesConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = !config.check && !config.dry_run;
+                fails_on_results = config.check;
+                (
+                    Box::new(RenderQueriesCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Review(cmd_args) => {
+                let config = ReviewConfig::try_from(cmd_args)?;
+                (
+                    Box::new(ReviewCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Plan(cmd_args) => {
+                let config = PlanConfig::try_from(cmd_args)?;
+                (
+                    Box::new(PlanCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Keywords(cmd_args) => {
+                let config = KeywordsConfig::try_from(cmd_args)?;
+                (
+                    Box::new(KeywordsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Summarize(mut cmd_args) => {
+                cmd_args.command = cmd_args.command.or(settings.summarize.command.clone());
+                let mut config = SummarizeConfig::try_from(cmd_args)?;
+                config.exec_policy = settings.exec_policy(cli.no_exec);
+                // The summarizer runs in the working directory of the client
+                writes_files = true;
+                (
+                    Box::new(SummarizeCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    config.output_path,
+                    config.input_path,
+                )
+            }
+            Command::Sort(cmd_args) => {
+                let mut config = SortConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = !config.dry_run;
+                (
+                    Box::new(SortCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Replace(cmd_args) => {
+                let mut config = ReplaceConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = !config.dry_run;
+                (
+                    Box::new(ReplaceCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Fmt(cmd_args) => {
+                let mut config = FmtConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = !config.check && !config.dry_run;
+                fails_on_results = config.check;
+                (
+                    Box::new(FmtCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Gaps(mut cmd_args) => {
+                cmd_args.template = cmd_args
+                    .template
+                    .or(settings.journal.daily_template.clone());
+                let mut config = GapsConfig::try_from(cmd_args)?;
+                config.dry_run = cli.dry_run;
+                writes_files = config.fill && !config.dry_run;
+                (
+                    Box::new(GapsCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Outdated(cmd_args) => {
+                let config = OutdatedConfig::try_from(cmd_args)?;
+                writes_files = config.regenerate;
+                (
+                    Box::new(OutdatedCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    None,
+                    config.input_path,
+                )
+            }
+            Command::Badge(cmd_args) => {
+                let config = BadgeConfig::try_from(cmd_args)?;
+                (
+                    Box::new(BadgeCommand {
+                        config: config.clone(),
+                    }),
+                    config.max_depth,
+                    Some(config.output_path),
+                    config.input_path,
+                )
+            }
+            Command::Daemon(_) => return Err(anyhow!("The daemon can not run itself")),
+            // Generated from the arguments without reading any files
+            Command::Completions(_) | Command::Manpage(_) => return Ok(None),
+            Command::Explain(cmd_args) => {
+                let config = ExplainConfig::try_from(cmd_args)?;
+                (Box::new(ExplainCommand { config }), None, None, vec![])
+            }
+        };
 
-            tasks::command::run(
-                config,
-                MDPMarkdownTokenizer {},
-                MarkdownFileReader {},
-                writers,
-            )?
-        }
+    Ok(Some(Invocation {
+        command,
+        max_depth,
+        output_path,
+        input_path,
+        executed_search,
+        writes_files,
+        fails_on_results,
+        fails_if_empty,
+    }))
+}
+
+fn file_reader(cli: &CliArgs, settings: &Settings, invocation: &Invocation) -> MarkdownFileReader {
+    MarkdownFileReader {
+        max_depth: invocation.max_depth,
+        extensions: settings.files.extensions.clone(),
+        follow_symlinks: cli.follow_symlinks,
+    }
+}
+
+fn load_settings() -> Result<Settings> {
+    match Settings::default_path() {
+        Some(path) => Ok(Settings::load(&path)?),
+        None => Ok(Settings::default()),
+    }
+}
+
+/// The language of `--lang`, or else of the settings
+fn language(cli: &CliArgs, settings: &Settings) -> Language {
+    match &cli.lang {
+        Some(lang) => lang.clone().into(),
+        None => settings.lang,
+    }
+}
+
+/// The time zone of `--time-zone`, or else of the settings
+fn time_zone(cli: &CliArgs, settings: &Settings) -> TimeZone {
+    cli.time_zone.unwrap_or(settings.time_zone)
+}
+
+fn parse_context<R: FileReader + 'static>(
+    cli: &CliArgs,
+    settings: &Settings,
+    reader: R,
+) -> ParseContext {
+    let mut ctx = ParseContext::with_reader(reader);
+    ctx.clock = match cli.today {
+        Some(today) => Box::new(FixedClock(today)),
+        None => Box::new(SystemClock {
+            time_zone: time_zone(cli, settings),
+        }),
+    };
+    ctx.best_effort = cli.best_effort;
+    ctx.lenient = !cli.strict;
+    ctx.strict = cli.strict;
+    ctx.limits = match cli.force {
+        true => Limits::none(),
+        false => settings.limits.clone(),
     };
+    ctx.merge_days = cli.merge_days;
+    ctx.lang = language(cli, settings);
+    ctx.output_in_input = cli
+        .output_in_input
+        .map(Into::into)
+        .unwrap_or(settings.files.output_in_input);
+    let tag_style = cli.tag_style.map(Into::into).unwrap_or(settings.tags.style);
+    ctx.tags = settings.tag_normalizer().with_style(tag_style);
+    ctx.tokenizer = Box::new(MDPMarkdownTokenizer {
+        any_case_task_keywords: settings.tasks.any_case_keywords,
+        today: Some(ctx.clock.today()),
+        single_colon_attributes: settings.attributes.single_colon,
+    });
+    ctx.section_builder = match cli.structure {
+        SectionStructure::Headings => Box::new(MDPSectionBuilder {
+            max_recursion_depth: cli.max_section_depth,
+            date_policy: cli.date_policy.into(),
+            tag_style,
+        }),
+        SectionStructure::Outline => Box::new(OutlineSectionBuilder {
+            date_policy: cli.date_policy.into(),
+            tag_style,
+        }),
+    };
+    ctx
+}
+
+/// Runs the command in the daemon, returns `None` if no daemon is available
+fn query_daemon() -> Option<DaemonResponse> {
+    let socket = default_socket_path()?;
+    let request = DaemonRequest {
+        cwd: std::env::current_dir().ok()?,
+        args: std::env::args().collect(),
+    };
+    match daemon::query(&socket, &request) {
+        Ok(response) => Some(response),
+        Err(e) => {
+            log::info!("Running without the daemon: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn run_daemon(cmd_args: &DaemonCommandArgs) -> Result<()> {
+    let socket = cmd_args
+        .socket
+        .clone()
+        .or_else(default_socket_path)
+        .ok_or_else(|| anyhow!("Could not determine the daemon socket location"))?;
+    let server = daemon::DaemonServer::bind(&socket)?;
+    log::info!("Listening on {}", socket.display());
 
+    let cache = FileCache::default();
+    let mut responses = ResponseCache::default();
+    server.serve(|request| daemon_response(request, &cache, &mut responses))?;
     Ok(())
 }
+
+#[cfg(not(unix))]
+fn run_daemon(_cmd_args: &DaemonCommandArgs) -> Result<()> {
+    Err(anyhow!("The daemon is only supported on unix"))
+}
+
+/// Run the command of a request, reading the files through the daemon's cache. The response
+/// of a previous identical request is reused if its input did not change since.
+#[cfg(unix)]
+fn daemon_response(
+    request: DaemonRequest,
+    cache: &FileCache,
+    responses: &mut ResponseCache,
+) -> DaemonResponse {
+    let mut run = || -> Result<DaemonResponse> {
+        let cli = CliArgs::try_parse_from(&request.args)?;
+        // Read for every request, so changes are picked up without restarting the daemon
+        let settings = load_settings()?;
+        let Some(invocation) = invocation(&cli, &settings)? else {
+            return Ok(DaemonResponse::default());
+        };
+        if invocation.writes_files {
+            return Err(anyhow!("Commands writing files can not run in the daemon"));
+        }
+        let context = || {
+            let mut ctx = parse_context(
+                &cli,
+                &settings,
+                CachedFileReader {
+                    cache: cache.clone(),
+                    base_dir: request.cwd.clone(),
+                    max_depth: invocation.max_depth,
+                    extensions: settings.files.extensions.clone(),
+                    follow_symlinks: cli.follow_symlinks,
+                },
+            );
+            ctx.output_path = invocation.output_path.clone();
+            ctx
+        };
+        // Besides the files, the output depends on the settings, the date of today and
+        // the search (`search --again` reads it from the history)
+        let revision = |ctx: &ParseContext| {
+            ctx.revision().combine(&[
+                &ctx.clock.today().to_string(),
+                &format!("{:?}", settings),
+                &format!("{:?}", invocation.executed_search),
+            ])
+        };
+        let cached = responses.get(&request, |inputs| {
+            let ctx = context();
+            ctx.read(inputs.to_vec()).ok()?;
+            Some(revision(&ctx))
+        });
+        if let Some(response) = cached {
+            log::info!("Reused the response of a previous request");
+            return Ok(response);
+        }
+
+        let ctx = context();
+        let mut output = String::new();
+        let report = invocation.command.run(&ctx, &mut output)?;
+        let response = DaemonResponse {
+            output,
+            result_count: report.result_count,
+            skipped: ctx.skipped().iter().map(|s| s.to_string()).collect(),
+            warnings: ctx.warnings().iter().map(|w| w.to_string()).collect(),
+            error: None,
+        };
+        responses.insert(
+            request.clone(),
+            ctx.revision().inputs,
+            revision(&ctx),
+            response.clone(),
+        );
+        Ok(response)
+    };
+
+    run().unwrap_or_else(|e| DaemonResponse {
+        error: Some(e.to_string()),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,268 @@
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+
+use super::config::{AgendaConfig, AgendaPeriod};
+use crate::{
+    commands::{
+        add_days, ics::parse_calendar, io::OutputSink, locale::Text, transclusion::Transcluder,
+        Command, ParseContext, Report,
+    },
+    models::{ConfigError, TaskStatus, Token},
+};
+
+/// Shows the overdue tasks followed by the events (of calendar files), the tasks due and the
+/// sections written on every day of the period.
+pub struct AgendaCommand {
+    pub config: AgendaConfig,
+}
+
+impl Command for AgendaCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let today = ctx.clock.today();
+        let day = config
+            .day
+            .resolve(today)
+            .ok_or_else(|| ConfigError::InvalidDateError(format!("{:?}", config.day)))?;
+
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+        let transcluder = Transcluder::new(&sections);
+        let due_tasks: Vec<(NaiveDate, Token)> = ctx
+            .tokens(&files)?
+            .into_iter()
+            .filter_map(|(t, _)| match &t {
+                Token::Task {
+                    status: TaskStatus::TodoUntil(due),
+                    ..
+                } if config.include_snoozed
+                    || t.snoozed_until().is_none_or(|until| until <= today) =>
+                {
+                    Some((due.date, t))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let calendars = ctx.read(config.calendars.clone())?;
+        let events = calendars
+            .iter()
+            .flat_map(|c| parse_calendar(&c.content))
+            .collect::<Vec<_>>();
+        let time_zone = ctx.clock.time_zone();
+
+        let mut result_count = 0;
+        let mut parts = vec![];
+
+        let overdue: Vec<String> = due_tasks
+            .iter()
+            .filter(|(due, _)| *due < today)
+            .map(|(_, t)| t.to_markdown_string())
+            .collect();
+        if !overdue.is_empty() {
+            result_count += overdue.len();
+            parts.push(format!(
+                "# {}\n\n{}",
+                ctx.lang.text(Text::Overdue),
+                overdue.join("\n")
+            ));
+        }
+
+        for date in period_days(day, &config.period) {
+            let mut day_parts = vec![format!("# {} {}", date, ctx.lang.weekday(date.weekday()))];
+
+            // All-day events first
+            let mut occurrences = events
+                .iter()
+                .filter_map(|e| e.on(date, &time_zone))
+                .collect::<Vec<_>>();
+            occurrences.sort_by_key(|o| (o.start, o.end));
+            if !occurrences.is_empty() {
+                result_count += occurrences.len();
+                day_parts.push(
+                    occurrences
+                        .iter()
+                        .map(|o| o.to_string())
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+            }
+
+            // Overdue tasks are already listed above
+            let due: Vec<String> = due_tasks
+                .iter()
+                .filter(|(due, _)| *due == date && *due >= today)
+                .map(|(_, t)| t.to_markdown_string())
+                .collect();
+            if !due.is_empty() {
+                result_count += due.len();
+                day_parts.push(due.join("\n"));
+            }
+
+            for section in sections.iter().filter(|s| s.date == Some(date)) {
+                let body = transcluder.render_body(section);
+                if !body.trim().is_empty() {
+                    result_count += 1;
+                    day_parts.push(body.trim().to_string());
+                }
+            }
+            parts.push(day_parts.join("\n\n"));
+        }
+
+        out.write_output(&parts.join("\n\n"))?;
+        Ok(Report { result_count })
+    }
+}
+
+/// The days of the period containing `day`
+fn period_days(day: NaiveDate, period: &AgendaPeriod) -> Vec<NaiveDate> {
+    match period {
+        AgendaPeriod::Day => vec![day],
+        AgendaPeriod::Week => {
+            let monday = add_days(day, -(day.weekday().num_days_from_monday() as i64));
+            (0..7)
+                .filter_map(|n| monday.and_then(|m| add_days(m, n)))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder, RelativeDate};
+
+    const DIARY: &str = "\
+# 2023-10-09
+
+TODO UNTIL 2023-10-05: Pay the bill
+TODO UNTIL 2023-10-11: Book flight
+
+# 2023-10-10
+
+## Meeting
+
+Discussed the roadmap.
+TODO UNTIL 2023-10-10: Send minutes
+";
+
+    #[test]
+    fn test_agenda() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(&path, DIARY)?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap()));
+        let run = |config: AgendaConfig| -> Result<String> {
+            let mut output = String::new();
+            AgendaCommand { config }.run(&ctx, &mut output)?;
+            Ok(output)
+        };
+
+        assert_eq!(
+            run(AgendaConfig::builder().input(&path).build())?,
+            "\
+# Overdue
+
+TODO UNTIL 2023-10-05: Pay the bill
+
+# 2023-10-10 Tuesday
+
+TODO UNTIL 2023-10-10: Send minutes
+
+## Meeting
+
+Discussed the roadmap.
+TODO UNTIL 2023-10-10: Send minutes"
+        );
+
+        let week = run(AgendaConfig::builder()
+            .input(&path)
+            .day(RelativeDate::Days(1))
+            .period(AgendaPeriod::Week)
+            .build())?;
+        let headings: Vec<&str> = week.lines().filter(|l| l.starts_with("# ")).collect();
+        assert_eq!(
+            headings,
+            vec![
+                "# Overdue",
+                "# 2023-10-09 Monday",
+                "# 2023-10-10 Tuesday",
+                "# 2023-10-11 Wednesday",
+                "# 2023-10-12 Thursday",
+                "# 2023-10-13 Friday",
+                "# 2023-10-14 Saturday",
+                "# 2023-10-15 Sunday",
+            ]
+        );
+        assert!(week.contains("# 2023-10-11 Wednesday\n\nTODO UNTIL 2023-10-11: Book flight"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_agenda_snoozed_tasks() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2023-10-09\n\nTODO UNTIL 2023-10-05: Pay the bill @snooze(2023-10-12)\n\
+             TODO UNTIL 2023-10-06: Call Anna SNOOZED UNTIL 2023-10-10\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap()));
+        let run = |include_snoozed: bool| -> Result<String> {
+            let config = AgendaConfig::builder()
+                .input(&path)
+                .include_snoozed(include_snoozed)
+                .build();
+            let mut output = String::new();
+            AgendaCommand { config }.run(&ctx, &mut output)?;
+            Ok(output)
+        };
+
+        // Tasks reappear on the day their snooze ends
+        assert_eq!(
+            run(false)?,
+            "# Overdue\n\nTODO UNTIL 2023-10-06: Call Anna SNOOZED UNTIL 2023-10-10\n\n\
+             # 2023-10-10 Tuesday"
+        );
+        assert!(run(true)?.contains("Pay the bill @snooze(2023-10-12)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_agenda_calendar() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(&path, DIARY)?;
+        let calendar = dir.path().join("work.ics");
+        fs::write(
+            &calendar,
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Review\nDTSTART:20231010T140000\n\
+             DTEND:20231010T150000\nEND:VEVENT\nBEGIN:VEVENT\nSUMMARY:Holiday\n\
+             DTSTART;VALUE=DATE:20231010\nEND:VEVENT\nEND:VCALENDAR\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap()));
+        let config = AgendaConfig::builder()
+            .input(&path)
+            .calendar(&calendar)
+            .build();
+        let mut output = String::new();
+        let report = AgendaCommand { config }.run(&ctx, &mut output)?;
+
+        assert!(output.contains(
+            "# 2023-10-10 Tuesday\n\nHoliday\n14:00-15:00 Review\n\n\
+             TODO UNTIL 2023-10-10: Send minutes"
+        ));
+        assert_eq!(report.result_count, 5);
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use crate::commands::{InputBuilder, RelativeDate};
+
+#[derive(Clone, Debug)]
+pub struct AgendaConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// The day shown (or a day of the week shown)
+    pub day: RelativeDate,
+    pub period: AgendaPeriod,
+    /// Also show tasks snoozed until a day after today
+    pub include_snoozed: bool,
+    /// iCalendar files (`.ics`) whose events are shown on their days (they are only read)
+    pub calendars: Vec<PathBuf>,
+}
+
+impl AgendaConfig {
+    pub fn builder() -> AgendaConfigBuilder {
+        AgendaConfigBuilder::default()
+    }
+}
+
+/// Builder for [`AgendaConfig`]
+#[derive(Clone, Debug)]
+pub struct AgendaConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    day: RelativeDate,
+    period: AgendaPeriod,
+    include_snoozed: bool,
+    calendars: Vec<PathBuf>,
+}
+
+impl Default for AgendaConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            day: RelativeDate::Days(0),
+            period: AgendaPeriod::Day,
+            include_snoozed: false,
+            calendars: vec![],
+        }
+    }
+}
+
+impl InputBuilder for AgendaConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl AgendaConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Show the agenda of this day (or of its week), today by default
+    pub fn day(mut self, day: RelativeDate) -> Self {
+        self.day = day;
+        self
+    }
+
+    pub fn period(mut self, period: AgendaPeriod) -> Self {
+        self.period = period;
+        self
+    }
+
+    pub fn include_snoozed(mut self, include_snoozed: bool) -> Self {
+        self.include_snoozed = include_snoozed;
+        self
+    }
+
+    /// Also show the events of the iCalendar file
+    pub fn calendar<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.calendars.push(path.into());
+        self
+    }
+
+    pub fn build(self) -> AgendaConfig {
+        AgendaConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            day: self.day,
+            period: self.period,
+            include_snoozed: self.include_snoozed,
+            calendars: self.calendars,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AgendaPeriod {
+    Day,
+    /// The week (starting on Monday) containing the day
+    Week,
+}
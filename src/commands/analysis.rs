@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+/// Words without meaning of their own, left out of the analysis (English and German, the
+/// languages of the output)
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "all", "also", "and", "any", "are", "because", "been", "before",
+    "being", "but", "can", "could", "did", "does", "doing", "done", "down", "each", "for", "from",
+    "had", "has", "have", "her", "here", "him", "his", "how", "into", "its", "just", "more",
+    "most", "not", "now", "off", "once", "only", "other", "our", "out", "over", "own", "same",
+    "she", "should", "some", "such", "than", "that", "the", "their", "them", "then", "there",
+    "these", "they", "this", "those", "through", "too", "under", "until", "very", "was", "were",
+    "what", "when", "where", "which", "while", "who", "why", "will", "with", "would", "you",
+    "your", "aber", "alle", "als", "auch", "auf", "aus", "bei", "bin", "bis", "das", "dass", "dem",
+    "den", "der", "des", "die", "doch", "ein", "eine", "einem", "einen", "einer", "eines", "für",
+    "hat", "hatte", "ich", "ihr", "ist", "mit", "nach", "nicht", "noch", "nur", "oder", "ohne",
+    "sich", "sie", "sind", "über", "und", "uns", "vom", "von", "vor", "war", "waren", "wie", "wir",
+    "wird", "zum", "zur",
+];
+
+/// The significant words of a text: lowercase, at least three characters long, containing
+/// a letter and no stopword
+pub fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .map(|w| w.trim_matches(['-', '_']).to_lowercase())
+        .filter(|w| w.chars().count() >= 3 && w.chars().any(char::is_alphabetic))
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// A term significant for a part of the documents
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyword {
+    pub term: String,
+    /// Occurrences within the part
+    pub count: usize,
+    /// TF-IDF of the term, the higher the more characteristic for the part
+    pub score: f64,
+}
+
+/// In how many documents (e.g. sections) every term occurs, to weigh terms by how specific
+/// they are
+#[derive(Clone, Debug, Default)]
+pub struct Corpus {
+    documents: usize,
+    document_frequencies: HashMap<String, usize>,
+}
+
+impl Corpus {
+    /// The corpus of the documents, given as their text
+    pub fn new<'t, I: IntoIterator<Item = &'t str>>(documents: I) -> Self {
+        let mut corpus = Self::default();
+        for document in documents {
+            corpus.documents += 1;
+            for term in terms(document).into_iter().collect::<HashSet<_>>() {
+                *corpus.document_frequencies.entry(term).or_default() += 1;
+            }
+        }
+        corpus
+    }
+
+    /// The (smoothed) inverse document frequency of a term, a term occurring in every
+    /// document has the lowest one
+    fn idf(&self, term: &str) -> f64 {
+        let frequency = self.document_frequencies.get(term).copied().unwrap_or(0);
+        ((1 + self.documents) as f64 / (1 + frequency) as f64).ln() + 1.0
+    }
+
+    /// The at most `limit` terms of the documents (a part of the corpus) with the highest
+    /// TF-IDF, ties ordered alphabetically
+    pub fn keywords<'t, I: IntoIterator<Item = &'t str>>(
+        &self,
+        documents: I,
+        limit: usize,
+    ) -> Vec<Keyword> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for term in documents.into_iter().flat_map(terms) {
+            *counts.entry(term).or_default() += 1;
+        }
+        let mut keywords = counts
+            .into_iter()
+            .map(|(term, count)| Keyword {
+                score: count as f64 * self.idf(&term),
+                term,
+                count,
+            })
+            .collect::<Vec<_>>();
+        keywords.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.term.cmp(&b.term)));
+        keywords.truncate(limit);
+        keywords
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(
+            terms("The Rust-Meetup: 2024 was über-fun, and I liked it."),
+            vec!["rust-meetup", "über-fun", "liked"]
+        );
+    }
+
+    #[test]
+    fn test_keywords() {
+        let vault = [
+            "Rust meetup about async rust",
+            "Planning the garden",
+            "Garden work: planting tomatoes",
+            "Weekly planning",
+        ];
+        let corpus = Corpus::new(vault);
+        let keywords = corpus.keywords(vault[..2].iter().copied(), 3);
+        let terms = keywords.iter().map(|k| k.term.as_str()).collect::<Vec<_>>();
+
+        // `rust` occurs twice, `planning` is common in the vault
+        assert_eq!(terms, vec!["rust", "async", "meetup"]);
+        assert_eq!(keywords[0].count, 2);
+        assert!(corpus.keywords(["the and"], 3).is_empty());
+    }
+}
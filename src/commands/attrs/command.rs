@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use super::config::AttrsConfig;
+use crate::{
+    commands::{io::OutputSink, Command, ParseContext, Report},
+    models::Section,
+};
+
+/// Lists every attribute (`Key:: value`) with its values and how often they occur. Names
+/// and values are compared case-insensitively, the first spelling found is shown.
+pub struct AttrsCommand {
+    pub config: AttrsConfig,
+}
+
+/// The values of an attribute
+#[derive(Debug, Default)]
+struct AttributeValues {
+    name: String,
+    /// Spelling and count of every value, by lowercase value
+    counts: BTreeMap<String, (String, usize)>,
+}
+
+impl Command for AttrsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+
+        let mut attributes: BTreeMap<String, AttributeValues> = BTreeMap::new();
+        for (name, value) in all_attributes(&sections) {
+            let entry = attributes
+                .entry(name.to_lowercase())
+                .or_insert_with(|| AttributeValues {
+                    name: name.clone(),
+                    ..Default::default()
+                });
+            entry
+                .counts
+                .entry(value.to_lowercase())
+                .or_insert_with(|| (value, 0))
+                .1 += 1;
+        }
+        if let Some(name) = &config.name {
+            attributes.retain(|key, _| *key == name.trim().to_lowercase());
+        }
+        if attributes.is_empty() {
+            log::warn!("No attributes found!");
+            return Ok(Report::default());
+        }
+
+        let parts = attributes
+            .values()
+            .map(attribute_to_string)
+            .collect::<Vec<String>>();
+        out.write_output(&parts.join("\n\n"))?;
+
+        Ok(Report {
+            result_count: attributes.len(),
+        })
+    }
+}
+
+/// The attributes (name, value) of the sections and their subsections
+fn all_attributes(sections: &[Section]) -> Vec<(String, String)> {
+    let mut attributes = vec![];
+    for section in sections {
+        attributes.extend(section.attributes());
+        attributes.extend(all_attributes(&section.subsections));
+    }
+    attributes
+}
+
+/// The name of the attribute with its total count, followed by its values, the most
+/// frequent first
+fn attribute_to_string(attribute: &AttributeValues) -> String {
+    let mut values = attribute.counts.values().collect::<Vec<_>>();
+    values.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let total: usize = values.iter().map(|(_, count)| count).sum();
+
+    let mut lines = vec![format!("# {} ({})", attribute.name, total), String::new()];
+    lines.extend(
+        values
+            .iter()
+            .map(|(value, count)| format!("- {}: {}", value, count)),
+    );
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_attrs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2024-06-08\n\nSource:: blog\n\n## Reading\n\nsource:: Book\nRating:: 4\n\n\
+             # 2024-06-01\n\nSource:: Blog\n",
+        )?;
+
+        let ctx = ParseContext::default();
+        let run = |config: AttrsConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = AttrsCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let (output, count) = run(AttrsConfig::builder().input(&path).build())?;
+        assert_eq!(
+            output,
+            "# Rating (1)\n\n- 4: 1\n\n# Source (3)\n\n- blog: 2\n- Book: 1"
+        );
+        assert_eq!(count, 2);
+
+        let (output, _) = run(AttrsConfig::builder().input(&path).name("rating").build())?;
+        assert_eq!(output, "# Rating (1)\n\n- 4: 1");
+        Ok(())
+    }
+}
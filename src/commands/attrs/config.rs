@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct AttrsConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Only show the values of this attribute (compared case-insensitively)
+    pub name: Option<String>,
+}
+
+impl AttrsConfig {
+    pub fn builder() -> AttrsConfigBuilder {
+        AttrsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`AttrsConfig`]
+#[derive(Clone, Debug)]
+pub struct AttrsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    name: Option<String>,
+}
+
+impl Default for AttrsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            name: None,
+        }
+    }
+}
+
+impl InputBuilder for AttrsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl AttrsConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Only show the values of this attribute
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn build(self) -> AttrsConfig {
+        AttrsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            name: self.name,
+        }
+    }
+}
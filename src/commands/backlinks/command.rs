@@ -0,0 +1,147 @@
+use anyhow::Result;
+
+use super::config::BacklinksConfig;
+use crate::{
+    commands::{
+        io::{MarkdownFile, OutputSink},
+        links::{LinkIndex, LinkTarget, SectionLink},
+        Command, ParseContext, Report,
+    },
+    models::ConfigError,
+};
+
+/// Shows the sections linking to a note (`[[Note]]` or `[[Note#Heading]]`) together with
+/// the line containing the link, or all links between the notes with `graph`.
+pub struct BacklinksCommand {
+    pub config: BacklinksConfig,
+}
+
+impl Command for BacklinksCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+        let index = LinkIndex::new(&sections);
+
+        let lines = match (&config.target, config.graph) {
+            (_, true) => link_pairs(&index.links.iter().collect::<Vec<_>>()),
+            (Some(target), false) => index
+                .backlinks(&LinkTarget::parse(target))
+                .iter()
+                .map(|l| backlink_to_string(l, &files))
+                .collect(),
+            (None, false) => return Err(ConfigError::IncompatibleConfigError.into()),
+        };
+
+        if lines.is_empty() {
+            log::warn!("No links found!");
+            return Ok(Report::default());
+        }
+        let separator = match config.graph {
+            true => "\n",
+            false => "\n\n",
+        };
+        out.write_output(&lines.join(separator))?;
+
+        Ok(Report {
+            result_count: lines.len(),
+        })
+    }
+}
+
+/// The origin and the titles of the sections containing the link, followed by the line
+/// containing it (or the token containing the link if the line is unknown)
+fn backlink_to_string(link: &SectionLink, files: &[MarkdownFile]) -> String {
+    let line = files
+        .iter()
+        .find(|f| Some(&f.path) == link.origin.file.as_ref())
+        .and_then(|f| f.content.lines().nth(link.origin.line.checked_sub(1)?))
+        .map(str::to_string)
+        .unwrap_or_else(|| link.token.to_markdown_string());
+    format!(
+        "{}: {}\n{}",
+        link.origin,
+        link.path.join(" > "),
+        line.trim()
+    )
+}
+
+/// `Note#Section -> Target#Heading` for every link (without duplicates)
+fn link_pairs(links: &[&SectionLink]) -> Vec<String> {
+    let mut pairs: Vec<String> = vec![];
+    for link in links {
+        let source = format!(
+            "{}#{}",
+            link.source_note().unwrap_or_default(),
+            link.section.title_string()
+        );
+        let target = match &link.target.heading {
+            Some(heading) => format!("{}#{}", link.target_note().unwrap_or_default(), heading),
+            None => link.target_note().unwrap_or_default(),
+        };
+        let pair = format!("{} -> {}", source, target);
+        if !pairs.contains(&pair) {
+            pairs.push(pair);
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_backlinks() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let diary = dir.path().join("diary.md");
+        std::fs::write(
+            &diary,
+            "# 2024-06-01\n\n## Meeting\nDiscussed [[Project X]] with @roger\n\n\
+             - Budget: [[Project X#Budget]]\n",
+        )?;
+        std::fs::write(
+            dir.path().join("Project X.md"),
+            "# 2024-05-01\n\n## Budget\nSee [[diary]] and [[#Budget]]\n",
+        )?;
+
+        let run = |config: BacklinksConfig| -> Result<String> {
+            let mut out = String::new();
+            BacklinksCommand { config }.run(&ParseContext::default(), &mut out)?;
+            Ok(out)
+        };
+
+        assert_eq!(
+            run(BacklinksConfig::builder()
+                .input(&diary)
+                .target("project x")
+                .build())?,
+            format!(
+                "{0}:4:11: 2024-06-01 > Meeting\nDiscussed [[Project X]] with @roger\n\n\
+                 {0}:6:1: 2024-06-01 > Meeting\n- Budget: [[Project X#Budget]]",
+                diary.display()
+            )
+        );
+
+        let graph = run(BacklinksConfig::builder()
+            .input(dir.path())
+            .graph(true)
+            .build())?;
+        let mut pairs = graph.lines().collect::<Vec<_>>();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                "Project X#Budget -> Project X#Budget",
+                "Project X#Budget -> diary",
+                "diary#Meeting -> Project X",
+                "diary#Meeting -> Project X#Budget",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct BacklinksConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// The note (optionally with a heading, e.g. `Note#Heading`) whose backlinks are shown
+    pub target: Option<String>,
+    /// List all links (`source -> target`) instead of the backlinks of one note
+    pub graph: bool,
+}
+
+impl BacklinksConfig {
+    pub fn builder() -> BacklinksConfigBuilder {
+        BacklinksConfigBuilder::default()
+    }
+}
+
+/// Builder for [`BacklinksConfig`]
+#[derive(Clone, Debug)]
+pub struct BacklinksConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    target: Option<String>,
+    graph: bool,
+}
+
+impl Default for BacklinksConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            target: None,
+            graph: false,
+        }
+    }
+}
+
+impl InputBuilder for BacklinksConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl BacklinksConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Show the backlinks of this note (e.g. `Note` or `Note#Heading`)
+    pub fn target<S: Into<String>>(mut self, target: S) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn graph(mut self, graph: bool) -> Self {
+        self.graph = graph;
+        self
+    }
+
+    pub fn build(self) -> BacklinksConfig {
+        BacklinksConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            target: self.target,
+            graph: self.graph,
+        }
+    }
+}
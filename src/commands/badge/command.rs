@@ -0,0 +1,158 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+
+use super::{
+    config::{BadgeConfig, BadgeMetric, Thresholds},
+    svg::{badge_svg, BadgeColor},
+};
+use crate::{
+    commands::{io::OutputSink, Command, ParseContext, Report},
+    models::{Origin, TaskStatus, Token},
+};
+
+pub struct BadgeCommand {
+    pub config: BadgeConfig,
+}
+
+impl Command for BadgeCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let today = ctx.clock.today();
+
+        let value = match config.metric {
+            BadgeMetric::OpenTasks => open_tasks(&task_statuses(ctx.tokens(&files)?)),
+            BadgeMetric::OverdueTasks => overdue_tasks(&task_statuses(ctx.tokens(&files)?), today),
+            BadgeMetric::Streak => streak(
+                &ctx.sections(&files)?
+                    .iter()
+                    .filter_map(|s| s.date)
+                    .collect(),
+                today,
+            ),
+        };
+
+        let color = badge_color(
+            value,
+            &config.thresholds(),
+            config.metric.higher_is_better(),
+        );
+        out.write_output(&badge_svg(&config.label(), &value.to_string(), color))?;
+
+        Ok(Report {
+            result_count: value,
+        })
+    }
+}
+
+fn task_statuses(tokens: Vec<(Token, Origin)>) -> Vec<TaskStatus> {
+    tokens
+        .into_iter()
+        .filter_map(|(t, _)| match t {
+            Token::Task { status, .. } => Some(status),
+            _ => None,
+        })
+        .collect()
+}
+
+fn open_tasks(statuses: &[TaskStatus]) -> usize {
+    statuses.iter().filter(|s| **s != TaskStatus::Done).count()
+}
+
+fn overdue_tasks(statuses: &[TaskStatus], today: NaiveDate) -> usize {
+    statuses
+        .iter()
+        .filter(|s| matches!(s, TaskStatus::TodoUntil(due) if due.date < today))
+        .count()
+}
+
+/// Number of consecutive days with an entry, ending today (or yesterday if there is no
+/// entry for today yet)
+fn streak(dates: &BTreeSet<NaiveDate>, today: NaiveDate) -> usize {
+    let mut day = match dates.contains(&today) {
+        true => today,
+        false => today - Duration::days(1),
+    };
+    let mut streak = 0;
+    while dates.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+fn badge_color(value: usize, thresholds: &Thresholds, higher_is_better: bool) -> BadgeColor {
+    let reaches = |threshold: usize| match higher_is_better {
+        true => value < threshold,
+        false => value >= threshold,
+    };
+    if reaches(thresholds.critical) {
+        BadgeColor::Red
+    } else if reaches(thresholds.warning) {
+        BadgeColor::Yellow
+    } else {
+        BadgeColor::Green
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 10, day).unwrap()
+    }
+
+    #[test]
+    fn test_task_metrics() {
+        let task = |status| {
+            (
+                Token::Task {
+                    content: vec![],
+                    status,
+                    checkbox: None,
+                },
+                Origin::default(),
+            )
+        };
+        let statuses = task_statuses(vec![
+            task(TaskStatus::Todo),
+            task(TaskStatus::Done),
+            task(TaskStatus::TodoUntil(date(9).into())),
+            task(TaskStatus::TodoUntil(date(10).into())),
+            (Token::Text("TODO"), Origin::default()),
+        ]);
+
+        assert_eq!(open_tasks(&statuses), 3);
+        assert_eq!(overdue_tasks(&statuses, date(10)), 1);
+    }
+
+    #[test]
+    fn test_streak() {
+        let dates = BTreeSet::from([date(1), date(3), date(4), date(5)]);
+
+        assert_eq!(streak(&dates, date(5)), 3);
+        assert_eq!(streak(&dates, date(6)), 3);
+        assert_eq!(streak(&dates, date(7)), 0);
+    }
+
+    #[test]
+    fn test_badge_color() {
+        let thresholds = Thresholds {
+            warning: 5,
+            critical: 10,
+        };
+        assert_eq!(badge_color(4, &thresholds, false), BadgeColor::Green);
+        assert_eq!(badge_color(5, &thresholds, false), BadgeColor::Yellow);
+        assert_eq!(badge_color(10, &thresholds, false), BadgeColor::Red);
+
+        let thresholds = BadgeMetric::Streak.default_thresholds();
+        assert_eq!(badge_color(0, &thresholds, true), BadgeColor::Red);
+        assert_eq!(badge_color(3, &thresholds, true), BadgeColor::Yellow);
+        assert_eq!(badge_color(7, &thresholds, true), BadgeColor::Green);
+    }
+}
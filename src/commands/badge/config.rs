@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct BadgeConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: PathBuf,
+    pub metric: BadgeMetric,
+    /// Text on the left side of the badge (defaults to the name of the metric)
+    pub label: Option<String>,
+    /// Thresholds for the color of the badge (defaults depend on the metric)
+    pub thresholds: Option<Thresholds>,
+}
+
+impl BadgeConfig {
+    pub fn builder() -> BadgeConfigBuilder {
+        BadgeConfigBuilder::default()
+    }
+
+    pub fn label(&self) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| self.metric.default_label().to_string())
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        self.thresholds
+            .clone()
+            .unwrap_or_else(|| self.metric.default_thresholds())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BadgeMetric {
+    /// Number of tasks which are not done
+    OpenTasks,
+    /// Number of `TODO UNTIL` tasks whose date has passed
+    OverdueTasks,
+    /// Number of consecutive days with an entry up to today (or yesterday)
+    Streak,
+}
+
+impl BadgeMetric {
+    pub fn default_label(&self) -> &'static str {
+        match self {
+            BadgeMetric::OpenTasks => "open tasks",
+            BadgeMetric::OverdueTasks => "overdue tasks",
+            BadgeMetric::Streak => "streak",
+        }
+    }
+
+    pub fn default_thresholds(&self) -> Thresholds {
+        match self {
+            BadgeMetric::OpenTasks => Thresholds {
+                warning: 10,
+                critical: 25,
+            },
+            BadgeMetric::OverdueTasks => Thresholds {
+                warning: 1,
+                critical: 5,
+            },
+            BadgeMetric::Streak => Thresholds {
+                warning: 7,
+                critical: 1,
+            },
+        }
+    }
+
+    /// Whether a higher value is better (e.g. a longer streak)
+    pub fn higher_is_better(&self) -> bool {
+        matches!(self, BadgeMetric::Streak)
+    }
+}
+
+/// Values from which on a badge is colored as warning or critical.
+///
+/// For metrics where a higher value is better, values below the thresholds are colored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thresholds {
+    pub warning: usize,
+    pub critical: usize,
+}
+
+/// Builder for [`BadgeConfig`]
+#[derive(Clone, Debug)]
+pub struct BadgeConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: PathBuf,
+    metric: BadgeMetric,
+    label: Option<String>,
+    thresholds: Option<Thresholds>,
+}
+
+impl Default for BadgeConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: PathBuf::from("./badge.svg"),
+            metric: BadgeMetric::OpenTasks,
+            label: None,
+            thresholds: None,
+        }
+    }
+}
+
+impl InputBuilder for BadgeConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl BadgeConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = path.into();
+        self
+    }
+
+    pub fn metric(mut self, metric: BadgeMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.thresholds = Some(thresholds);
+        self
+    }
+
+    pub fn build(self) -> BadgeConfig {
+        BadgeConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            metric: self.metric,
+            label: self.label,
+            thresholds: self.thresholds,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+/// Color of the value side of a badge
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BadgeColor {
+    Green,
+    Yellow,
+    Red,
+}
+
+impl BadgeColor {
+    fn hex(&self) -> &'static str {
+        match self {
+            BadgeColor::Green => "#4c1",
+            BadgeColor::Yellow => "#dfb317",
+            BadgeColor::Red => "#e05d44",
+        }
+    }
+}
+
+/// Approximate width of a text in the badge font (11px Verdana) including padding
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders a flat badge in the style of shields.io: the label on a gray background on the
+/// left, the value on a colored background on the right.
+pub fn badge_svg(label: &str, value: &str, color: BadgeColor) -> String {
+    let label_width = text_width(label);
+    let value_width = text_width(value);
+    let width = label_width + value_width;
+    let label = escape_xml(label);
+    let value = escape_xml(value);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}">
+  <title>{label}: {value}</title>
+  <linearGradient id="s" x2="0" y2="100%">
+    <stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
+    <stop offset="1" stop-opacity=".1"/>
+  </linearGradient>
+  <clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="#555"/>
+    <rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/>
+    <rect width="{width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{value_x}" y="14">{value}</text>
+  </g>
+</svg>
+"##,
+        color = color.hex(),
+        label_x = label_width / 2,
+        value_x = label_width + value_width / 2,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge_svg() {
+        let svg = badge_svg("open <tasks>", "12", BadgeColor::Yellow);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"aria-label="open &lt;tasks&gt;: 12""#));
+        assert!(svg.contains(r##"fill="#dfb317""##));
+        assert!(svg.contains(r#"width="118""#));
+    }
+}
@@ -0,0 +1,494 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{encryption::CacheLocation, io::MarkdownFile, util::stable_hash};
+use crate::models::{MDPError, TaskStatus, Token};
+
+/// Default location of the cache (relative to the working directory)
+pub const DEFAULT_CACHE_PATH: &str = ".mdp-cache";
+
+/// On-disk cache of the tokens of the input files, so that repeated invocations only
+/// tokenize the files whose content changed.
+///
+/// The tokens borrow from the content of the files, which is why they are stored as spans
+/// of the content and the files are still read on every invocation (to check their hash).
+/// Only files which could be tokenized without errors are cached.
+#[derive(Debug)]
+pub struct TokenCache {
+    location: CacheLocation,
+    /// The key of the tokenizer the tokens are created by, see
+    /// [`crate::models::MarkdownTokenizer::cache_key`]
+    tokenizer: String,
+    entries: RefCell<HashMap<PathBuf, CacheEntry>>,
+    changed: Cell<bool>,
+}
+
+/// Content of the cache file
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    /// The cache of a different version of mdp is discarded, as the tokens may differ
+    version: String,
+    /// The cache of a tokenizer with other settings is discarded as well
+    #[serde(default)]
+    tokenizer: String,
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+/// The tokens of a file with the hash of the content they were created from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    hash: u64,
+    tokens: Vec<CachedToken>,
+}
+
+/// Start and end (byte offsets) of a string within the content of the file
+type Span = (usize, usize);
+
+/// A [`Token`] whose strings are replaced by their span within the content of the file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum CachedToken {
+    Blank,
+    HRule,
+    Newline,
+
+    BlockRef(Span),
+    Email(Span),
+    Embed(Span),
+    FootnoteRef(Span),
+    Hashtag(Span),
+    Latex(Span),
+    Link(Span),
+    Text(Span),
+    RawHyperlink(Span),
+    SingleBacktick(Span),
+    Tag(Span),
+    TripleBacktick(Span),
+
+    Date(NaiveDate),
+
+    BlockQuote(Vec<CachedToken>),
+    Bold(Vec<CachedToken>),
+    Highlight(Vec<CachedToken>),
+    Italic(Vec<CachedToken>),
+    Strike(Vec<CachedToken>),
+    HeadingH1(Vec<CachedToken>),
+    HeadingH2(Vec<CachedToken>),
+    HeadingH3(Vec<CachedToken>),
+    HeadingH4(Vec<CachedToken>),
+    HeadingH5(Vec<CachedToken>),
+    HeadingH6(Vec<CachedToken>),
+    Table(Vec<CachedToken>),
+    TableRow(Vec<CachedToken>),
+    TableCell(Vec<CachedToken>),
+
+    ListItem {
+        prefix: Span,
+        level: usize,
+        content: Vec<CachedToken>,
+    },
+    Attribute {
+        name: Span,
+        separator: Span,
+        value: Vec<CachedToken>,
+    },
+    FootnoteDefinition {
+        label: Span,
+        content: Vec<CachedToken>,
+    },
+    Snooze {
+        markup: Span,
+        date: NaiveDate,
+    },
+    Image {
+        alt: Span,
+        url: Span,
+    },
+    MarkdownInternalLink {
+        label: Span,
+        link: Span,
+    },
+    MarkdownExternalLink {
+        title: Span,
+        url: Span,
+    },
+    Task {
+        content: Vec<CachedToken>,
+        status: TaskStatus,
+        checkbox: Option<Span>,
+    },
+}
+
+impl TokenCache {
+    /// Load the cache of the tokens created by the tokenizer with the given `tokenizer`
+    /// key, a missing, unreadable or outdated cache file (or the one of a tokenizer with
+    /// other settings) results in an empty cache
+    pub fn load(path: &Path, tokenizer: &str) -> Self {
+        Self::load_from(path.into(), tokenizer)
+    }
+
+    /// Load the cache from a (possibly encrypted) file, see [`TokenCache::load`]
+    pub fn load_from(location: CacheLocation, tokenizer: &str) -> Self {
+        let entries = location
+            .read()
+            .and_then(|s| serde_json::from_str::<CacheFile>(&s).ok())
+            .filter(|c| c.version == env!("CARGO_PKG_VERSION") && c.tokenizer == tokenizer)
+            .map(|c| c.entries)
+            .unwrap_or_default();
+        Self {
+            location,
+            tokenizer: tokenizer.to_string(),
+            entries: RefCell::new(entries),
+            changed: Cell::new(false),
+        }
+    }
+
+    pub fn entries(&self) -> Ref<'_, HashMap<PathBuf, CacheEntry>> {
+        self.entries.borrow()
+    }
+
+    pub fn insert(&self, path: PathBuf, entry: CacheEntry) {
+        self.entries.borrow_mut().insert(path, entry);
+        self.changed.set(true);
+    }
+
+    /// Write the cache file if entries were added, entries of files which no longer exist
+    /// are removed
+    pub fn save(&self) -> Result<(), MDPError> {
+        if !self.changed.replace(false) {
+            return Ok(());
+        }
+
+        let mut entries = self.entries.borrow_mut();
+        entries.retain(|path, _| path.exists());
+        let cache_file = CacheFile {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            tokenizer: self.tokenizer.clone(),
+            entries: std::mem::take(&mut *entries),
+        };
+        let json = serde_json::to_string(&cache_file).unwrap_or_default();
+        *entries = cache_file.entries;
+
+        self.location.write(&json)
+    }
+}
+
+impl CacheEntry {
+    /// `None` if a string of the tokens is not part of the content (e.g. created by a
+    /// custom tokenizer), such tokens can not be cached. Neither can relative deadlines
+    /// (`TODO UNTIL tomorrow:`), which might depend on the day the file is read.
+    pub fn new(file: &MarkdownFile, tokens: &[Token]) -> Option<Self> {
+        Some(Self {
+            hash: content_hash(&file.content),
+            tokens: Spans(&file.content).cache_tokens(tokens)?,
+        })
+    }
+
+    /// The cached tokens if the content of the file did not change
+    pub fn tokens<'a>(&self, file: &'a MarkdownFile) -> Option<Vec<Token<'a>>> {
+        if self.hash != content_hash(&file.content) {
+            return None;
+        }
+        Spans(&file.content).restore_tokens(&self.tokens)
+    }
+}
+
+/// A hash of the content (see [`stable_hash`])
+pub(crate) fn content_hash(content: &str) -> u64 {
+    stable_hash(content.bytes())
+}
+
+/// Converts between the strings of the tokens and their spans within the content
+struct Spans<'c>(&'c str);
+
+impl<'c> Spans<'c> {
+    fn span(&self, s: &str) -> Option<Span> {
+        let start = (s.as_ptr() as usize).checked_sub(self.0.as_ptr() as usize)?;
+        let end = start + s.len();
+        (end <= self.0.len()).then_some((start, end))
+    }
+
+    fn str(&self, (start, end): Span) -> Option<&'c str> {
+        self.0.get(start..end)
+    }
+
+    fn cache_tokens(&self, tokens: &[Token]) -> Option<Vec<CachedToken>> {
+        tokens.iter().map(|t| self.cache_token(t)).collect()
+    }
+
+    fn restore_tokens(&self, tokens: &[CachedToken]) -> Option<Vec<Token<'c>>> {
+        tokens.iter().map(|t| self.restore_token(t)).collect()
+    }
+
+    fn cache_token(&self, token: &Token) -> Option<CachedToken> {
+        let span = |s| self.span(s);
+        let tokens = |t| self.cache_tokens(t);
+        Some(match token {
+            Token::Blank => CachedToken::Blank,
+            Token::HRule => CachedToken::HRule,
+            Token::Newline => CachedToken::Newline,
+
+            Token::BlockRef(s) => CachedToken::BlockRef(span(s)?),
+            Token::Email(s) => CachedToken::Email(span(s)?),
+            Token::Embed(s) => CachedToken::Embed(span(s)?),
+            Token::FootnoteRef(s) => CachedToken::FootnoteRef(span(s)?),
+            Token::Hashtag(s) => CachedToken::Hashtag(span(s)?),
+            Token::Latex(s) => CachedToken::Latex(span(s)?),
+            Token::Link(s) => CachedToken::Link(span(s)?),
+            Token::Text(s) => CachedToken::Text(span(s)?),
+            Token::RawHyperlink(s) => CachedToken::RawHyperlink(span(s)?),
+            Token::SingleBacktick(s) => CachedToken::SingleBacktick(span(s)?),
+            Token::Tag(s) => CachedToken::Tag(span(s)?),
+            Token::TripleBacktick(s) => CachedToken::TripleBacktick(span(s)?),
+
+            Token::Date(date) => CachedToken::Date(*date),
+
+            Token::BlockQuote(t) => CachedToken::BlockQuote(tokens(t)?),
+            Token::Bold(t) => CachedToken::Bold(tokens(t)?),
+            Token::Highlight(t) => CachedToken::Highlight(tokens(t)?),
+            Token::Italic(t) => CachedToken::Italic(tokens(t)?),
+            Token::Strike(t) => CachedToken::Strike(tokens(t)?),
+            Token::HeadingH1(t) => CachedToken::HeadingH1(tokens(t)?),
+            Token::HeadingH2(t) => CachedToken::HeadingH2(tokens(t)?),
+            Token::HeadingH3(t) => CachedToken::HeadingH3(tokens(t)?),
+            Token::HeadingH4(t) => CachedToken::HeadingH4(tokens(t)?),
+            Token::HeadingH5(t) => CachedToken::HeadingH5(tokens(t)?),
+            Token::HeadingH6(t) => CachedToken::HeadingH6(tokens(t)?),
+            Token::Table(t) => CachedToken::Table(tokens(t)?),
+            Token::TableRow(t) => CachedToken::TableRow(tokens(t)?),
+            Token::TableCell(t) => CachedToken::TableCell(tokens(t)?),
+
+            Token::ListItem {
+                prefix,
+                level,
+                content,
+            } => CachedToken::ListItem {
+                prefix: span(prefix)?,
+                level: *level,
+                content: tokens(content)?,
+            },
+            Token::Attribute {
+                name,
+                separator,
+                value,
+            } => CachedToken::Attribute {
+                name: span(name)?,
+                separator: span(separator)?,
+                value: tokens(value)?,
+            },
+            Token::FootnoteDefinition { label, content } => CachedToken::FootnoteDefinition {
+                label: span(label)?,
+                content: tokens(content)?,
+            },
+            Token::Snooze { markup, date } => CachedToken::Snooze {
+                markup: span(markup)?,
+                date: *date,
+            },
+            Token::Image { alt, url } => CachedToken::Image {
+                alt: span(alt)?,
+                url: span(url)?,
+            },
+            Token::MarkdownInternalLink { label, link } => CachedToken::MarkdownInternalLink {
+                label: span(label)?,
+                link: span(link)?,
+            },
+            Token::MarkdownExternalLink { title, url } => CachedToken::MarkdownExternalLink {
+                title: span(title)?,
+                url: span(url)?,
+            },
+            Token::Task {
+                status: TaskStatus::TodoUntil(due),
+                ..
+            } if due.expression.is_some() => return None,
+            Token::Task {
+                content,
+                status,
+                checkbox,
+            } => CachedToken::Task {
+                content: tokens(content)?,
+                status: status.clone(),
+                checkbox: match checkbox {
+                    Some(c) => Some(span(c)?),
+                    None => None,
+                },
+            },
+        })
+    }
+
+    fn restore_token(&self, token: &CachedToken) -> Option<Token<'c>> {
+        let str = |s| self.str(s);
+        let tokens = |t| self.restore_tokens(t);
+        Some(match token {
+            CachedToken::Blank => Token::Blank,
+            CachedToken::HRule => Token::HRule,
+            CachedToken::Newline => Token::Newline,
+
+            CachedToken::BlockRef(s) => Token::BlockRef(str(*s)?),
+            CachedToken::Email(s) => Token::Email(str(*s)?),
+            CachedToken::Embed(s) => Token::Embed(str(*s)?),
+            CachedToken::FootnoteRef(s) => Token::FootnoteRef(str(*s)?),
+            CachedToken::Hashtag(s) => Token::Hashtag(str(*s)?),
+            CachedToken::Latex(s) => Token::Latex(str(*s)?),
+            CachedToken::Link(s) => Token::Link(str(*s)?),
+            CachedToken::Text(s) => Token::Text(str(*s)?),
+            CachedToken::RawHyperlink(s) => Token::RawHyperlink(str(*s)?),
+            CachedToken::SingleBacktick(s) => Token::SingleBacktick(str(*s)?),
+            CachedToken::Tag(s) => Token::Tag(str(*s)?),
+            CachedToken::TripleBacktick(s) => Token::TripleBacktick(str(*s)?),
+
+            CachedToken::Date(date) => Token::Date(*date),
+
+            CachedToken::BlockQuote(t) => Token::BlockQuote(tokens(t)?),
+            CachedToken::Bold(t) => Token::Bold(tokens(t)?),
+            CachedToken::Highlight(t) => Token::Highlight(tokens(t)?),
+            CachedToken::Italic(t) => Token::Italic(tokens(t)?),
+            CachedToken::Strike(t) => Token::Strike(tokens(t)?),
+            CachedToken::HeadingH1(t) => Token::HeadingH1(tokens(t)?),
+            CachedToken::HeadingH2(t) => Token::HeadingH2(tokens(t)?),
+            CachedToken::HeadingH3(t) => Token::HeadingH3(tokens(t)?),
+            CachedToken::HeadingH4(t) => Token::HeadingH4(tokens(t)?),
+            CachedToken::HeadingH5(t) => Token::HeadingH5(tokens(t)?),
+            CachedToken::HeadingH6(t) => Token::HeadingH6(tokens(t)?),
+            CachedToken::Table(t) => Token::Table(tokens(t)?),
+            CachedToken::TableRow(t) => Token::TableRow(tokens(t)?),
+            CachedToken::TableCell(t) => Token::TableCell(tokens(t)?),
+
+            CachedToken::ListItem {
+                prefix,
+                level,
+                content,
+            } => Token::ListItem {
+                prefix: str(*prefix)?,
+                level: *level,
+                content: tokens(content)?,
+            },
+            CachedToken::Attribute {
+                name,
+                separator,
+                value,
+            } => Token::Attribute {
+                name: str(*name)?,
+                separator: str(*separator)?,
+                value: tokens(value)?,
+            },
+            CachedToken::FootnoteDefinition { label, content } => Token::FootnoteDefinition {
+                label: str(*label)?,
+                content: tokens(content)?,
+            },
+            CachedToken::Snooze { markup, date } => Token::Snooze {
+                markup: str(*markup)?,
+                date: *date,
+            },
+            CachedToken::Image { alt, url } => Token::Image {
+                alt: str(*alt)?,
+                url: str(*url)?,
+            },
+            CachedToken::MarkdownInternalLink { label, link } => Token::MarkdownInternalLink {
+                label: str(*label)?,
+                link: str(*link)?,
+            },
+            CachedToken::MarkdownExternalLink { title, url } => Token::MarkdownExternalLink {
+                title: str(*title)?,
+                url: str(*url)?,
+            },
+            CachedToken::Task {
+                content,
+                status,
+                checkbox,
+            } => Token::Task {
+                content: tokens(content)?,
+                status: status.clone(),
+                checkbox: match checkbox {
+                    Some(c) => Some(str(*c)?),
+                    None => None,
+                },
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{markdown::MDPMarkdownTokenizer, models::MarkdownTokenizer};
+
+    #[test]
+    fn test_cache_entry() {
+        let content =
+            "# 2024-06-01\n\n## Call @roger\n- [ ] Send [minutes](https://x.org) **now**\n\
+                       | a | b |\n|---|---|\n> quote \"with\" \\escapes\n";
+        let file = MarkdownFile::new(PathBuf::from("diary.md"), content.to_string());
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize(&file.content)
+            .unwrap();
+
+        let entry = CacheEntry::new(&file, &tokens).unwrap();
+        assert_eq!(entry.tokens(&file), Some(tokens));
+
+        let changed = MarkdownFile::new(PathBuf::from("diary.md"), format!("{}\n", content));
+        assert_eq!(entry.tokens(&changed), None);
+
+        // Strings which are not part of the content can not be cached
+        assert_eq!(CacheEntry::new(&file, &[Token::Text("elsewhere")]), None);
+
+        let relative = MarkdownFile::new(
+            PathBuf::from("diary.md"),
+            "TODO UNTIL tomorrow: Call".to_string(),
+        );
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize(&relative.content)
+            .unwrap();
+        assert_eq!(CacheEntry::new(&relative, &tokens), None);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join(DEFAULT_CACHE_PATH);
+        let note = dir.path().join("note.md");
+        fs::write(&note, "# 2024-06-01\n").unwrap();
+        let file = MarkdownFile::new(note.clone(), "# 2024-06-01\n".to_string());
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize(&file.content)
+            .unwrap();
+
+        let key = MDPMarkdownTokenizer::default().cache_key().unwrap();
+        let cache = TokenCache::load(&cache_path, &key);
+        assert!(cache.entries().is_empty());
+        cache.insert(note.clone(), CacheEntry::new(&file, &tokens).unwrap());
+        cache.insert(
+            dir.path().join("deleted.md"),
+            CacheEntry::new(&file, &tokens).unwrap(),
+        );
+        cache.save().unwrap();
+
+        let loaded = TokenCache::load(&cache_path, &key);
+        assert_eq!(loaded.entries().len(), 1);
+        assert_eq!(loaded.entries()[&note].tokens(&file), Some(tokens));
+
+        // The cache of a tokenizer with other settings is discarded
+        let single_colon = MDPMarkdownTokenizer {
+            single_colon_attributes: true,
+            ..Default::default()
+        };
+        assert_ne!(single_colon.cache_key(), Some(key.clone()));
+        assert!(TokenCache::load(&cache_path, &single_colon.cache_key().unwrap())
+            .entries()
+            .is_empty());
+
+        // The cache of another version is discarded
+        let json = fs::read_to_string(&cache_path).unwrap();
+        let version = format!("\"version\":\"{}\"", env!("CARGO_PKG_VERSION"));
+        fs::write(&cache_path, json.replace(&version, "\"version\":\"0.0.1\"")).unwrap();
+        assert!(TokenCache::load(&cache_path, &key).entries().is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+use std::slice;
+
+use anyhow::Result;
+
+use super::config::CatConfig;
+use crate::{
+    commands::{
+        io::{MarkdownFile, OutputSink},
+        Command, ParseContext, Report,
+    },
+    models::{Origin, Token},
+};
+
+/// Prints the files as rendered from their tokens, the output on the terminal is styled
+/// with the theme like the output of all other commands.
+pub struct CatCommand {
+    pub config: CatConfig,
+}
+
+impl Command for CatCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let files = ctx.read(self.config.input_path.clone())?;
+        let rendered = files
+            .iter()
+            .map(|f| Ok(render_file(f, &ctx.tokens(slice::from_ref(f))?)))
+            .collect::<Result<Vec<String>>>()?;
+
+        let output_string = match rendered.as_slice() {
+            [single] => single.to_owned(),
+            _ => files
+                .iter()
+                .zip(rendered)
+                .map(|(f, r)| format!("==> {} <==\n\n{}", f.path.display(), r))
+                .collect::<Vec<String>>()
+                .join("\n\n"),
+        };
+        out.write_output(&output_string)?;
+
+        Ok(Report {
+            result_count: files.len(),
+        })
+    }
+}
+
+/// The front matter (as is) followed by the Markdown of the tokens, without the trailing
+/// newline of the last line
+fn render_file(file: &MarkdownFile, tokens: &[(Token, Origin)]) -> String {
+    let (body, _) = file.body();
+    let front_matter = &file.content[..file.content.len() - body.len()];
+    let markdown: String = tokens.iter().map(|(t, _)| t.to_markdown_string()).collect();
+    format!("{}{}", front_matter, markdown)
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_cat() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.md");
+        std::fs::write(&a, "# 2024-06-01\n\n- [ ] Call @roger\n")?;
+        std::fs::write(&b, "Source:: [[Blog]]\n")?;
+
+        let run = |config: CatConfig| -> Result<String> {
+            let mut out = String::new();
+            CatCommand { config }.run(&ParseContext::default(), &mut out)?;
+            Ok(out)
+        };
+
+        assert_eq!(
+            run(CatConfig::builder().input(&a).build())?,
+            "# 2024-06-01\n\n- [ ] Call @roger"
+        );
+        assert_eq!(
+            run(CatConfig::builder().input(&a).input(&b).build())?,
+            format!(
+                "==> {} <==\n\n# 2024-06-01\n\n- [ ] Call @roger\n\n==> {} <==\n\nSource:: [[Blog]]",
+                a.display(),
+                b.display()
+            )
+        );
+
+        // The front matter is printed as is
+        std::fs::write(&b, "---\ntags: [blog]\n---\nSource:: [[Blog]]\n")?;
+        assert_eq!(
+            run(CatConfig::builder().input(&b).build())?,
+            "---\ntags: [blog]\n---\nSource:: [[Blog]]"
+        );
+        Ok(())
+    }
+}
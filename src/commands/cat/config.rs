@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct CatConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+}
+
+impl CatConfig {
+    pub fn builder() -> CatConfigBuilder {
+        CatConfigBuilder::default()
+    }
+}
+
+/// Builder for [`CatConfig`]
+#[derive(Clone, Debug)]
+pub struct CatConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+}
+
+impl Default for CatConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+        }
+    }
+}
+
+impl InputBuilder for CatConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl CatConfigBuilder {
+    pub fn build(self) -> CatConfig {
+        CatConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+        }
+    }
+}
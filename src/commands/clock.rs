@@ -0,0 +1,195 @@
+use std::str::FromStr;
+
+use chrono::{Days, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone as _, Utc};
+use serde::Deserialize;
+
+use crate::{markdown::day_offset, models::ConfigError};
+
+/// Source of the current date
+///
+/// Everything that depends on "today" (e.g. task urgency) should ask a `Clock` instead
+/// of the system time, so results are reproducible and can be computed for other days.
+pub trait Clock {
+    fn today(&self) -> NaiveDate;
+
+    /// The current date and time (in the time zone of the clock)
+    fn now(&self) -> NaiveDateTime;
+
+    /// The time zone times given in UTC (e.g. of calendar events) are shown in
+    fn time_zone(&self) -> TimeZone {
+        TimeZone::Local
+    }
+}
+
+/// Clock using the system time, the days start at midnight of its time zone
+#[derive(Clone, Debug, Default)]
+pub struct SystemClock {
+    pub time_zone: TimeZone,
+}
+
+impl Clock for SystemClock {
+    fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+
+    fn now(&self) -> NaiveDateTime {
+        self.time_zone.now()
+    }
+
+    fn time_zone(&self) -> TimeZone {
+        self.time_zone
+    }
+}
+
+/// Time zone in which the days start and end, given as `local` (the time zone of the
+/// system), `UTC` or a fixed offset from UTC (e.g. `+02:00`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TimeZone {
+    #[default]
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl TimeZone {
+    pub fn now(&self) -> NaiveDateTime {
+        match self {
+            TimeZone::Local => Local::now().naive_local(),
+            TimeZone::Fixed(offset) => Utc::now().with_timezone(offset).naive_local(),
+        }
+    }
+
+    /// The time in this time zone of a time given in UTC
+    pub fn from_utc(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            TimeZone::Local => Local.from_utc_datetime(&utc).naive_local(),
+            TimeZone::Fixed(offset) => offset.from_utc_datetime(&utc).naive_local(),
+        }
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(TimeZone::Local);
+        }
+        if s.eq_ignore_ascii_case("utc") || s == "Z" {
+            return Ok(TimeZone::Fixed(FixedOffset::east_opt(0).unwrap()));
+        }
+        s.parse::<FixedOffset>().map(TimeZone::Fixed).map_err(|_| {
+            ConfigError::InvalidOptionsError(format!(
+                "The time zone '{}' is neither `local`, `UTC` nor an offset like +02:00",
+                s
+            ))
+        })
+    }
+}
+
+impl TryFrom<String> for TimeZone {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Clock which always returns the same date (at midnight)
+#[derive(Clone, Debug)]
+pub struct FixedClock(pub NaiveDate);
+
+impl Clock for FixedClock {
+    fn today(&self) -> NaiveDate {
+        self.0
+    }
+
+    fn now(&self) -> NaiveDateTime {
+        self.0.and_time(chrono::NaiveTime::MIN)
+    }
+}
+
+/// A date given either as `YYYY-MM-DD` or relative to today (e.g. `+7d`, `-1w`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelativeDate {
+    Date(NaiveDate),
+    /// Number of days from today
+    Days(i64),
+}
+
+impl RelativeDate {
+    pub fn resolve(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            RelativeDate::Date(date) => Some(*date),
+            RelativeDate::Days(days) => add_days(today, *days),
+        }
+    }
+}
+
+impl FromStr for RelativeDate {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(date) => Ok(RelativeDate::Date(date)),
+            Err(_) => parse_days(s).map(RelativeDate::Days),
+        }
+    }
+}
+
+/// Parse a number of days (`7d`, `-3d`) or weeks (`+1w`), a missing unit means days.
+pub fn parse_days(s: &str) -> Result<i64, ConfigError> {
+    day_offset(s.trim()).ok_or_else(|| ConfigError::InvalidDateError(s.to_string()))
+}
+
+/// Add a (possibly negative) number of days to a date, `None` if out of range
+pub fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    match days >= 0 {
+        true => date.checked_add_days(Days::new(days as u64)),
+        false => date.checked_sub_days(Days::new(days.unsigned_abs())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_relative_date() {
+        let today = NaiveDate::from_ymd_opt(2023, 10, 10).unwrap();
+        let resolve = |s: &str| s.parse::<RelativeDate>().unwrap().resolve(today).unwrap();
+
+        assert_eq!(resolve("2023-12-24").to_string(), "2023-12-24");
+        assert_eq!(resolve("+7d").to_string(), "2023-10-17");
+        assert_eq!(resolve("-1w").to_string(), "2023-10-03");
+        assert_eq!(resolve("2").to_string(), "2023-10-12");
+
+        assert_eq!(parse_days("2w"), Ok(14));
+        assert_eq!(parse_days(" -2w "), Ok(-14));
+        assert!(parse_days("w").is_err());
+        assert!(parse_days("++3d").is_err());
+        assert!("2023-02-30".parse::<RelativeDate>().is_err());
+    }
+
+    #[test]
+    fn test_time_zone() {
+        let offset = |seconds| TimeZone::Fixed(FixedOffset::east_opt(seconds).unwrap());
+        assert_eq!("local".parse::<TimeZone>(), Ok(TimeZone::Local));
+        assert_eq!("UTC".parse::<TimeZone>(), Ok(offset(0)));
+        assert_eq!("+02:00".parse::<TimeZone>(), Ok(offset(7200)));
+        assert_eq!("-05:30".parse::<TimeZone>(), Ok(offset(-19800)));
+        assert!("Europe/Zurich".parse::<TimeZone>().is_err());
+
+        // Days start at midnight of the time zone, not of UTC
+        let utc = Utc::now().naive_utc();
+        let ahead = SystemClock {
+            time_zone: offset(14 * 3600),
+        };
+        let hours = (ahead.now() - utc).num_minutes() as f64 / 60.0;
+        assert!((hours - 14.0).abs() < 0.1);
+        assert_eq!(ahead.today(), ahead.now().date());
+    }
+}
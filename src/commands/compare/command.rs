@@ -0,0 +1,251 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::config::{CompareConfig, CompareLayout};
+use crate::{
+    commands::{io::OutputSink, Command, ParseContext, RelativeDate, Report},
+    models::{ConfigError, Section, Token},
+};
+
+/// Compares the structure of two days: their subsections (matched by title) and their tasks
+/// (matched by content, so that status changes of the same task are shown).
+pub struct CompareCommand {
+    pub config: CompareConfig,
+}
+
+impl Command for CompareCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let today = ctx.clock.today();
+        let resolve = |day: &RelativeDate| {
+            day.resolve(today)
+                .ok_or_else(|| ConfigError::InvalidDateError(format!("{:?}", day)))
+        };
+        let (first, second) = (resolve(&config.first)?, resolve(&config.second)?);
+
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+        let left = day_entries(&sections, first);
+        let right = day_entries(&sections, second);
+        for (date, entries) in [(first, &left), (second, &right)] {
+            if entries.is_empty() {
+                log::warn!("No subsections or tasks found on {}", date);
+            }
+        }
+
+        let rows = align(&left, &right);
+        let output_string = match config.layout {
+            CompareLayout::SideBySide => side_by_side(first, second, &rows),
+            CompareLayout::Unified => unified(first, second, &rows),
+        };
+        out.write_output(&output_string)?;
+
+        Ok(Report {
+            result_count: rows.iter().filter(|r| !matches!(r, Row::Same(_))).count(),
+        })
+    }
+}
+
+/// A heading or task of a day
+#[derive(Debug)]
+struct Entry {
+    /// Identifies the same heading or task on both days
+    key: String,
+    line: String,
+}
+
+#[derive(Debug)]
+enum Row<'e> {
+    Same(&'e Entry),
+    /// Same heading or task, which differs (e.g. the status of a task)
+    Changed(&'e Entry, &'e Entry),
+    First(&'e Entry),
+    Second(&'e Entry),
+}
+
+/// The headings of the subsections and the tasks of all sections of the day, in order
+fn day_entries(sections: &[Section], date: NaiveDate) -> Vec<Entry> {
+    fn collect(section: &Section, path: &str, entries: &mut Vec<Entry>) {
+        for token in &section.content {
+            if let Token::Task { content, .. } = token {
+                let content: String = content.iter().map(|t| t.to_markdown_string()).collect();
+                entries.push(Entry {
+                    key: format!("task:{}", content.trim()),
+                    line: token.to_markdown_string().trim().to_string(),
+                });
+            }
+        }
+        for subsection in &section.subsections {
+            let path = format!("{}/{}", path, subsection.title_string());
+            entries.push(Entry {
+                key: format!("section:{}", path),
+                line: subsection.title.to_markdown_string().trim().to_string(),
+            });
+            collect(subsection, &path, entries);
+        }
+    }
+
+    let mut entries = vec![];
+    for section in sections.iter().filter(|s| s.date == Some(date)) {
+        collect(section, "", &mut entries);
+    }
+    entries
+}
+
+/// Align the entries of both days along their longest common subsequence of keys
+fn align<'e>(first: &'e [Entry], second: &'e [Entry]) -> Vec<Row<'e>> {
+    let (n, m) = (first.len(), second.len());
+    let mut lcs = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = match first[i].key == second[j].key {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut rows = vec![];
+    while i < n || j < m {
+        if i < n && j < m && first[i].key == second[j].key {
+            rows.push(match first[i].line == second[j].line {
+                true => Row::Same(&first[i]),
+                false => Row::Changed(&first[i], &second[j]),
+            });
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            rows.push(Row::First(&first[i]));
+            i += 1;
+        } else {
+            rows.push(Row::Second(&second[j]));
+            j += 1;
+        }
+    }
+    rows
+}
+
+fn side_by_side(first: NaiveDate, second: NaiveDate, rows: &[Row]) -> String {
+    let columns: Vec<(&str, char, &str)> = rows
+        .iter()
+        .map(|row| match row {
+            Row::Same(e) => (e.line.as_str(), ' ', e.line.as_str()),
+            Row::Changed(a, b) => (a.line.as_str(), '|', b.line.as_str()),
+            Row::First(e) => (e.line.as_str(), '<', ""),
+            Row::Second(e) => ("", '>', e.line.as_str()),
+        })
+        .collect();
+    let (first, second) = (first.to_string(), second.to_string());
+    let width = columns
+        .iter()
+        .map(|(l, _, _)| l.chars().count())
+        .chain([first.len()])
+        .max()
+        .unwrap_or_default();
+
+    [(first.as_str(), ' ', second.as_str())]
+        .iter()
+        .chain(&columns)
+        .map(|(l, marker, r)| {
+            format!("{:<width$} {} {}", l, marker, r)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn unified(first: NaiveDate, second: NaiveDate, rows: &[Row]) -> String {
+    let mut lines = vec![format!("--- {}", first), format!("+++ {}", second)];
+    for row in rows {
+        match row {
+            Row::Same(e) => lines.push(format!(" {}", e.line)),
+            Row::Changed(a, b) => {
+                lines.push(format!("-{}", a.line));
+                lines.push(format!("+{}", b.line));
+            }
+            Row::First(e) => lines.push(format!("-{}", e.line)),
+            Row::Second(e) => lines.push(format!("+{}", e.line)),
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    const DIARY: &str = "\
+# 2024-06-01
+
+## Planning
+TODO: Book flight
+TODO: Pay the bill
+
+## Gym
+
+# 2024-06-08
+
+## Planning
+TODO: Book flight
+DONE: Pay the bill
+TODO: Call roger
+
+## Review
+";
+
+    #[test]
+    fn test_compare() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(&path, DIARY)?;
+
+        let run = |layout| -> Result<(String, usize)> {
+            let config = CompareConfig::builder()
+                .input(&path)
+                .days(
+                    RelativeDate::Date(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()),
+                    RelativeDate::Date(NaiveDate::from_ymd_opt(2024, 6, 8).unwrap()),
+                )
+                .layout(layout)
+                .build();
+            let mut out = String::new();
+            let report = CompareCommand { config }.run(&ParseContext::default(), &mut out)?;
+            Ok((out, report.result_count))
+        };
+
+        assert_eq!(
+            run(CompareLayout::SideBySide)?,
+            (
+                "\
+2024-06-01           2024-06-08
+## Planning          ## Planning
+TODO: Book flight    TODO: Book flight
+TODO: Pay the bill | DONE: Pay the bill
+## Gym             <
+                   > TODO: Call roger
+                   > ## Review"
+                    .to_string(),
+                4
+            )
+        );
+        assert_eq!(
+            run(CompareLayout::Unified)?.0,
+            "\
+--- 2024-06-01
++++ 2024-06-08
+ ## Planning
+ TODO: Book flight
+-TODO: Pay the bill
++DONE: Pay the bill
+-## Gym
++TODO: Call roger
++## Review"
+        );
+        Ok(())
+    }
+}
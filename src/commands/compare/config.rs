@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use crate::commands::{InputBuilder, RelativeDate};
+
+#[derive(Clone, Debug)]
+pub struct CompareConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// The day shown on the left (or with `-` in the unified layout)
+    pub first: RelativeDate,
+    /// The day shown on the right (or with `+` in the unified layout)
+    pub second: RelativeDate,
+    pub layout: CompareLayout,
+}
+
+impl CompareConfig {
+    pub fn builder() -> CompareConfigBuilder {
+        CompareConfigBuilder::default()
+    }
+}
+
+/// Builder for [`CompareConfig`]
+///
+/// The days default to a week ago and today.
+#[derive(Clone, Debug)]
+pub struct CompareConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    first: RelativeDate,
+    second: RelativeDate,
+    layout: CompareLayout,
+}
+
+impl Default for CompareConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            first: RelativeDate::Days(-7),
+            second: RelativeDate::Days(0),
+            layout: CompareLayout::SideBySide,
+        }
+    }
+}
+
+impl InputBuilder for CompareConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl CompareConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Compare the sections of these two days
+    pub fn days(mut self, first: RelativeDate, second: RelativeDate) -> Self {
+        self.first = first;
+        self.second = second;
+        self
+    }
+
+    pub fn layout(mut self, layout: CompareLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn build(self) -> CompareConfig {
+        CompareConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            first: self.first,
+            second: self.second,
+            layout: self.layout,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompareLayout {
+    /// Two columns like `sdiff`: `|` marks changed lines, `<` and `>` lines of one day only
+    SideBySide,
+    /// One column like `diff -u`: lines of the first day only start with `-`, lines of the
+    /// second day only with `+`
+    Unified,
+}
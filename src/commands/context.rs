@@ -0,0 +1,1003 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use crate::{
+    commands::{
+        cache::{CacheEntry, TokenCache},
+        io::{FileReader, MarkdownFile, MarkdownFileReader, OutputInInput},
+        limits::Limits,
+        locale::Language,
+        tags::normalize::TagNormalizer,
+        titles::LinkTitles,
+        util::stable_hash,
+        validation::{normalize, output_inside_input},
+        Clock, SystemClock,
+    },
+    markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+    models::{
+        annotate_origins, merge_days, ConfigError, MDPError, MarkdownTokenizer, Origin,
+        OriginTracker, Section, SectionBuilder, Token,
+    },
+};
+
+/// Everything a command needs to turn input paths into tokens and sections.
+pub struct ParseContext {
+    pub reader: Box<dyn FileReader>,
+    pub tokenizer: Box<dyn MarkdownTokenizer>,
+    pub section_builder: Box<dyn SectionBuilder>,
+    /// Determines what "today" is for date dependent logic
+    pub clock: Box<dyn Clock>,
+    /// Skip input which can not be read or parsed instead of failing
+    pub best_effort: bool,
+    /// Keep lines which can not be parsed as text instead of failing (or skipping them in
+    /// best effort mode), their errors are recorded as warnings
+    pub lenient: bool,
+    /// Fail on lines which look like mistyped markup as well (e.g. `TODO : Task`), unless
+    /// they are skipped in best effort mode
+    pub strict: bool,
+    /// Merge the sections of the same day of different files (see [`merge_days`])
+    pub merge_days: bool,
+    /// Aliases of tags, the tags of sections are replaced by their canonical name
+    pub tags: TagNormalizer,
+    /// Number of threads the files are tokenized and hierarchized with
+    pub threads: usize,
+    /// Limits on the size of the input
+    pub limits: Limits,
+    /// Tokens of the files from previous invocations, only changed files are tokenized
+    pub cache: Option<TokenCache>,
+    /// Titles of the pages raw URLs point to, fetched by `search --fetch-titles`
+    pub link_titles: Option<LinkTitles>,
+    /// Language of the texts of the output
+    pub lang: Language,
+    /// The file the output is written to, see [`OutputInInput`]
+    pub output_path: Option<PathBuf>,
+    /// What happens if the output file is inside an input directory
+    pub output_in_input: OutputInInput,
+    skipped: RefCell<Vec<SkippedInput>>,
+    warnings: RefCell<Vec<InputWarning>>,
+    revision: RefCell<InputRevision>,
+    included: RefCell<Vec<String>>,
+}
+
+/// Input which was skipped in best effort mode, for exceeding the file size limit or for
+/// being a binary file
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkippedInput {
+    pub path: PathBuf,
+    pub error: MDPError,
+}
+
+impl Display for SkippedInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+/// A line which could not be parsed and was kept as text in lenient mode
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputWarning {
+    pub path: PathBuf,
+    pub error: MDPError,
+}
+
+impl Display for InputWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error {
+            MDPError::MarkdownParseError { msg, line_number } => write!(
+                f,
+                "{}:{}: [{}] {}",
+                self.path.display(),
+                line_number,
+                self.error.code(),
+                msg
+            ),
+            error => write!(f, "{}: {}", self.path.display(), error),
+        }
+    }
+}
+
+/// The input paths read by a command together with a hash of the files found at them (their
+/// paths and contents), which changes whenever one of the files is changed, added or removed.
+///
+/// Input lists (`@files.txt`) are kept as inputs and the paths they were expanded to are
+/// part of the hash, so changing a list or adding a file matched by one of its patterns
+/// changes the revision as well.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputRevision {
+    pub inputs: Vec<PathBuf>,
+    pub hash: u64,
+}
+
+impl InputRevision {
+    fn add(
+        &mut self,
+        inputs: Vec<PathBuf>,
+        expanded: &[PathBuf],
+        found: &[PathBuf],
+        files: &[MarkdownFile],
+    ) {
+        let files = files.iter().flat_map(|file| {
+            path_bytes(&file.path).chain(length_prefixed(file.content.as_bytes()))
+        });
+        let bytes = self.hash.to_le_bytes().into_iter();
+        let bytes = bytes.chain(paths_bytes(expanded)).chain(paths_bytes(found));
+        self.hash = stable_hash(bytes.chain(files));
+        self.inputs.extend(inputs);
+    }
+
+    /// The revision combined with anything else an output depends on (e.g. the settings),
+    /// stable across releases like the revision itself
+    pub fn combine(&self, parts: &[&str]) -> u64 {
+        let parts = parts.iter().flat_map(|part| length_prefixed(part.as_bytes()));
+        stable_hash(self.hash.to_le_bytes().into_iter().chain(parts))
+    }
+}
+
+/// The bytes with their length in front, so consecutive values can not be confused
+fn length_prefixed(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let length = (bytes.len() as u64).to_le_bytes();
+    length.into_iter().chain(bytes.iter().copied())
+}
+
+fn path_bytes(path: &Path) -> impl Iterator<Item = u8> + '_ {
+    length_prefixed(path.as_os_str().as_encoded_bytes())
+}
+
+fn paths_bytes(paths: &[PathBuf]) -> impl Iterator<Item = u8> + '_ {
+    let count = (paths.len() as u64).to_le_bytes();
+    count.into_iter().chain(paths.iter().flat_map(|path| path_bytes(path)))
+}
+
+impl ParseContext {
+    /// Create a context using the MDP tokenizer and section builder
+    pub fn with_reader<R: FileReader + 'static>(reader: R) -> Self {
+        Self {
+            reader: Box::new(reader),
+            ..Default::default()
+        }
+    }
+
+    /// Returns all input which was skipped so far (see [`SkippedInput`])
+    pub fn skipped(&self) -> Vec<SkippedInput> {
+        self.skipped.borrow().clone()
+    }
+
+    /// Returns the warnings about lines which were kept as text so far (lenient mode)
+    pub fn warnings(&self) -> Vec<InputWarning> {
+        self.warnings.borrow().clone()
+    }
+
+    /// Returns the revision of all input read so far (see [`InputRevision`])
+    pub fn revision(&self) -> InputRevision {
+        self.revision.borrow().clone()
+    }
+
+    /// Records that the section is part of the output (for the section coverage of
+    /// `stats --coverage`)
+    pub fn include(&self, section: &Section) {
+        self.included.borrow_mut().push(section.id());
+    }
+
+    /// Returns the ids of the sections included in the output so far (see [`Section::id`])
+    pub fn included(&self) -> Vec<String> {
+        self.included.borrow().clone()
+    }
+
+    /// Read all files found at the given paths, input lists (`@files.txt`) are replaced by
+    /// the paths they contain (see [`FileReader::expand_inputs`]).
+    ///
+    /// Fails if more files than allowed by the limits are found, files larger than the
+    /// limit and binary files are skipped. In best effort mode, files which can not be read
+    /// are skipped. The output file is handled according to [`Self::output_in_input`] if
+    /// it is inside an input directory.
+    pub fn read(&self, inputs: Vec<PathBuf>) -> Result<Vec<MarkdownFile>, MDPError> {
+        let expanded = self.reader.expand_inputs(inputs.clone())?;
+        let mut paths = self.reader.find_files(expanded.clone())?;
+        if let Some(output) = &self.output_path {
+            self.check_output(output, &expanded, &mut paths)?;
+        }
+        self.limits.check_file_count(paths.len())?;
+
+        let mut files = vec![];
+        for path in paths.iter().cloned() {
+            let size = self.reader.file_size(&path).unwrap_or_default();
+            if let Err(error) = self.limits.check_file_size(size) {
+                self.skip(path, vec![error]);
+                continue;
+            }
+            match self.reader.read_file(path.clone()) {
+                Ok(file) => files.push(file),
+                Err(error @ MDPError::BinaryFileError(_)) => self.skip(path, vec![error]),
+                Err(error) if self.best_effort => self.skip(path, vec![error]),
+                Err(error) => return Err(error),
+            }
+        }
+        self.revision.borrow_mut().add(inputs, &expanded, &paths, &files);
+        Ok(files)
+    }
+
+    /// Fails or warns like [`Self::read`] if the output file is inside an input directory,
+    /// without reading any file (e.g. to check the options with `--check-args`)
+    pub fn check_output_path(&self, inputs: Vec<PathBuf>) -> Result<(), MDPError> {
+        let Some(output) = &self.output_path else {
+            return Ok(());
+        };
+        let inputs = self.reader.expand_inputs(inputs)?;
+        self.check_output(output, &inputs, &mut vec![])
+    }
+
+    /// Fails, warns or removes the output file from the found paths if it is inside an input
+    /// directory (it might not exist yet)
+    fn check_output(
+        &self,
+        output: &Path,
+        inputs: &[PathBuf],
+        paths: &mut Vec<PathBuf>,
+    ) -> Result<(), MDPError> {
+        let Some(input) = output_inside_input(output, inputs, self.reader.max_depth()) else {
+            return Ok(());
+        };
+        match self.output_in_input {
+            OutputInInput::Error => {
+                let message = format!(
+                    "The output file {} is inside the input directory {} and would be read as \
+                     input by later runs, write it to another directory with --output or \
+                     exclude it with --output-in-input exclude",
+                    output.display(),
+                    input.display()
+                );
+                Err(MDPError::ConfigError(ConfigError::InvalidOptionsError(
+                    message,
+                )))
+            }
+            OutputInInput::Warn => {
+                log::warn!(
+                    "The output file {} is inside the input directory {} and is read as input",
+                    output.display(),
+                    input.display()
+                );
+                Ok(())
+            }
+            OutputInInput::Exclude => {
+                let output = normalize(output);
+                paths.retain(|path| normalize(path) != output);
+                Ok(())
+            }
+        }
+    }
+
+    /// Tokenize all files, annotating each token with its origin.
+    ///
+    /// Parse errors of all files are collected and returned together. In lenient mode, lines
+    /// which can not be parsed are kept as text, in best effort mode they are skipped.
+    pub fn tokens<'a>(
+        &self,
+        files: &'a [MarkdownFile],
+    ) -> Result<Vec<(Token<'a>, Origin)>, MDPError> {
+        let tokenizer = &*self.tokenizer;
+        let recovery = self.line_recovery();
+        let entries = self.cache.as_ref().map(|c| c.entries());
+        let cached = entries.as_deref();
+        let parsed = self.map_files(files, |file| {
+            tokenize_file(tokenizer, file, recovery, cached)
+        });
+        drop(entries);
+
+        let mut tokens = vec![];
+        let mut errors = vec![];
+        let mut new_entries = vec![];
+        for (file, tokenized) in files.iter().zip(parsed) {
+            self.report_lines(file.path.clone(), tokenized.line_errors);
+            new_entries.extend(tokenized.cache_entry.map(|e| (file.path.clone(), e)));
+            match tokenized.tokens {
+                Ok(t) => tokens.extend(t),
+                Err(MDPError::MultiError(e)) => errors.extend(e),
+                Err(e) => errors.push(e),
+            }
+        }
+        self.update_cache(new_entries);
+        self.limits.check_token_count(tokens.len())?;
+
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(MDPError::MultiError(errors))
+        }
+    }
+
+    /// Tokenize the files one after another while the tokens are consumed, so that only the
+    /// tokens of the current line are kept in memory (for commands only looking at single
+    /// tokens, e.g. the tags or tasks, of inputs too large for [`Self::tokens`]).
+    ///
+    /// The parse errors are returned by [`TokenStream::finish`]. Lines which can not be
+    /// parsed are skipped in lenient mode as well, as they only consist of text. With the
+    /// cache or in strict mode, all files are tokenized at once like by [`Self::tokens`].
+    pub fn token_stream<'a>(&'a self, files: &'a [MarkdownFile]) -> TokenStream<'a> {
+        let mut stream = TokenStream {
+            ctx: self,
+            files: files.iter(),
+            current: Box::new(std::iter::empty()),
+            path: PathBuf::new(),
+            errors: vec![],
+            token_count: 0,
+        };
+        if self.cache.is_some() || self.strict {
+            stream.files = [].iter();
+            stream.current = match self.tokens(files) {
+                Ok(tokens) => Box::new(tokens.into_iter().map(Ok)),
+                Err(e) => {
+                    stream.errors.push(e);
+                    Box::new(std::iter::empty())
+                }
+            };
+        }
+        stream
+    }
+
+    /// Build the sections of all files (every file is hierarchized on its own).
+    ///
+    /// In best effort mode, files whose sections can not be built are skipped.
+    pub fn sections<'a>(&self, files: &'a [MarkdownFile]) -> Result<Vec<Section<'a>>, MDPError> {
+        let tokenizer = &*self.tokenizer;
+        let section_builder = &*self.section_builder;
+        let recovery = self.line_recovery();
+        let entries = self.cache.as_ref().map(|c| c.entries());
+        let cached = entries.as_deref();
+        let parsed = self.map_files(files, |file| {
+            let tokenized = tokenize_file(tokenizer, file, recovery, cached);
+            let token_count = tokenized.tokens.as_ref().map_or(0, |t| t.len());
+            let mut sections = tokenized
+                .tokens
+                .and_then(|t| section_builder.sections_from_tokens_with_origins(t));
+            if let (Ok(sections), Some(front_matter)) = (&mut sections, &file.front_matter) {
+                front_matter.tag_sections(sections);
+            }
+            (
+                sections,
+                tokenized.line_errors,
+                tokenized.cache_entry,
+                token_count,
+            )
+        });
+        drop(entries);
+
+        let mut sections = vec![];
+        let mut new_entries = vec![];
+        let mut total_token_count = 0;
+        for (file, (result, line_errors, cache_entry, token_count)) in files.iter().zip(parsed) {
+            self.report_lines(file.path.clone(), line_errors);
+            new_entries.extend(cache_entry.map(|e| (file.path.clone(), e)));
+            total_token_count += token_count;
+            self.limits.check_token_count(total_token_count)?;
+            match result {
+                Ok(s) => sections.extend(s),
+                Err(error) if self.best_effort => self.skip(file.path.clone(), vec![error]),
+                Err(error) => return Err(error),
+            }
+        }
+        self.update_cache(new_entries);
+        self.tags.normalize_sections(&mut sections);
+        match self.merge_days {
+            true => Ok(merge_days(sections)),
+            false => Ok(sections),
+        }
+    }
+
+    /// Apply `f` to every file, the files are split into chunks processed by separate
+    /// threads. The results are in the order of the files.
+    fn map_files<'a, T, F>(&self, files: &'a [MarkdownFile], f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(&'a MarkdownFile) -> T + Sync,
+    {
+        let threads = self.threads.clamp(1, files.len().max(1));
+        if threads == 1 {
+            return files.iter().map(f).collect();
+        }
+
+        let chunk_size = files.len().div_ceil(threads);
+        thread::scope(|scope| {
+            let handles = files
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(|| chunk.iter().map(&f).collect::<Vec<T>>()))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        })
+    }
+
+    /// Adds the tokens of files which were not cached yet and writes the cache file
+    fn update_cache(&self, entries: Vec<(PathBuf, CacheEntry)>) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        for (path, entry) in entries {
+            cache.insert(path, entry);
+        }
+        if let Err(e) = cache.save() {
+            log::warn!("Could not write the cache: {}", e);
+        }
+    }
+
+    fn line_recovery(&self) -> LineRecovery {
+        match (self.lenient, self.best_effort) {
+            (true, _) => LineRecovery::KeepAsText,
+            (false, true) => LineRecovery::Skip,
+            (false, false) if self.strict => LineRecovery::FailStrict,
+            (false, false) => LineRecovery::Fail,
+        }
+    }
+
+    /// Records the errors of lines which were kept as text (lenient mode) or skipped.
+    fn report_lines(&self, path: PathBuf, errors: Vec<MDPError>) {
+        if !self.lenient {
+            return self.skip(path, errors);
+        }
+
+        let mut warnings = self.warnings.borrow_mut();
+        for error in errors {
+            let warning = InputWarning {
+                path: path.clone(),
+                error,
+            };
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+
+    /// Records skipped input (only once if the same file is processed repeatedly).
+    fn skip(&self, path: PathBuf, errors: Vec<MDPError>) {
+        let mut skipped = self.skipped.borrow_mut();
+        for error in errors {
+            let input = SkippedInput {
+                path: path.clone(),
+                error,
+            };
+            if !skipped.contains(&input) {
+                skipped.push(input);
+            }
+        }
+    }
+}
+
+/// The tokens of files with their origins, tokenized while they are consumed (see
+/// [`ParseContext::token_stream`])
+pub struct TokenStream<'a> {
+    ctx: &'a ParseContext,
+    files: std::slice::Iter<'a, MarkdownFile>,
+    current: Box<dyn Iterator<Item = Result<(Token<'a>, Origin), MDPError>> + 'a>,
+    /// Path of the file currently tokenized
+    path: PathBuf,
+    errors: Vec<MDPError>,
+    token_count: usize,
+}
+
+impl<'a> TokenStream<'a> {
+    /// The errors of the files tokenized so far, to be called once all tokens were consumed
+    pub fn finish(self) -> Result<(), MDPError> {
+        let mut errors = vec![];
+        for error in self.errors {
+            match error {
+                MDPError::MultiError(e) => errors.extend(e),
+                e @ MDPError::LimitExceededError(_) => return Err(e),
+                e => errors.push(e),
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(MDPError::MultiError(errors)),
+        }
+    }
+
+    fn start_file(&mut self, file: &'a MarkdownFile) {
+        // The front matter is not tokenized, the line numbers are shifted by its length
+        let (body, first_line) = file.body();
+        let offset = first_line - 1;
+        let mut origins = OriginTracker::new(Some(&file.path));
+        self.path = file.path.clone();
+        self.current = Box::new(self.ctx.tokenizer.tokenize_iter(body).map(move |t| {
+            let (token, mut origin) = origins.annotate(t.map_err(|e| e.offset_lines(offset))?);
+            origin.line += offset;
+            Ok((token, origin))
+        }));
+    }
+}
+
+impl<'a> Iterator for TokenStream<'a> {
+    type Item = (Token<'a>, Origin);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.current.next() {
+                Some(Ok(token)) => {
+                    self.token_count += 1;
+                    if let Err(e) = self.ctx.limits.check_token_count(self.token_count) {
+                        self.errors.push(e);
+                        self.files = [].iter();
+                        self.current = Box::new(std::iter::empty());
+                        return None;
+                    }
+                    return Some(token);
+                }
+                Some(Err(e)) => match self.ctx.line_recovery() {
+                    LineRecovery::Fail | LineRecovery::FailStrict => self.errors.push(e),
+                    _ => self.ctx.report_lines(self.path.clone(), vec![e]),
+                },
+                None => {
+                    let file = self.files.next()?;
+                    self.start_file(file);
+                }
+            }
+        }
+    }
+}
+
+/// What happens with lines which can not be parsed
+#[derive(Clone, Copy)]
+enum LineRecovery {
+    Fail,
+    /// Fail on lines which look like mistyped markup as well
+    FailStrict,
+    Skip,
+    KeepAsText,
+}
+
+/// The result of tokenizing a file
+struct TokenizedFile<'a> {
+    tokens: Result<Vec<(Token<'a>, Origin)>, MDPError>,
+    /// Errors of the lines which were skipped or kept as text
+    line_errors: Vec<MDPError>,
+    /// The tokens to cache, if the file was tokenized (without errors) instead of cached
+    cache_entry: Option<CacheEntry>,
+}
+
+fn tokenize_file<'a>(
+    tokenizer: &dyn MarkdownTokenizer,
+    file: &'a MarkdownFile,
+    recovery: LineRecovery,
+    cached: Option<&HashMap<PathBuf, CacheEntry>>,
+) -> TokenizedFile<'a> {
+    // The front matter is not tokenized, the line numbers are shifted by its length
+    let (body, first_line) = file.body();
+    let offset = first_line - 1;
+    let annotate = |tokens| {
+        annotate_origins(tokens, Some(&file.path))
+            .into_iter()
+            .map(|(token, mut origin)| {
+                origin.line += offset;
+                (token, origin)
+            })
+            .collect()
+    };
+
+    // Cached files are not checked for mistyped markup
+    let cached = cached.filter(|_| !matches!(recovery, LineRecovery::FailStrict));
+    let cached_tokens = cached.and_then(|c| c.get(&file.path)?.tokens(file));
+    if let Some(tokens) = cached_tokens {
+        return TokenizedFile {
+            tokens: Ok(annotate(tokens)),
+            line_errors: vec![],
+            cache_entry: None,
+        };
+    }
+
+    let (tokens, errors) = match recovery {
+        LineRecovery::Fail | LineRecovery::FailStrict => {
+            let tokenized = match recovery {
+                LineRecovery::FailStrict => tokenizer.tokenize_strict(body),
+                _ => tokenizer.tokenize(body),
+            };
+            match tokenized {
+                Ok(tokens) => (tokens, vec![]),
+                Err(e) => {
+                    return TokenizedFile {
+                        tokens: Err(e.offset_lines(offset)),
+                        line_errors: vec![],
+                        cache_entry: None,
+                    }
+                }
+            }
+        }
+        LineRecovery::Skip => tokenizer.tokenize_best_effort(body),
+        LineRecovery::KeepAsText => tokenizer.tokenize_lenient(body),
+    };
+    let cache_entry = match cached.is_some() && errors.is_empty() {
+        true => CacheEntry::new(file, &tokens),
+        false => None,
+    };
+    TokenizedFile {
+        tokens: Ok(annotate(tokens)),
+        line_errors: errors.into_iter().map(|e| e.offset_lines(offset)).collect(),
+        cache_entry,
+    }
+}
+
+impl Default for ParseContext {
+    fn default() -> Self {
+        Self {
+            reader: Box::new(MarkdownFileReader::default()),
+            tokenizer: Box::new(MDPMarkdownTokenizer::default()),
+            section_builder: Box::new(MDPSectionBuilder::default()),
+            clock: Box::new(SystemClock::default()),
+            best_effort: false,
+            lenient: false,
+            strict: false,
+            merge_days: false,
+            tags: TagNormalizer::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            limits: Limits::default(),
+            cache: None,
+            link_titles: None,
+            lang: Language::default(),
+            output_path: None,
+            output_in_input: OutputInInput::default(),
+            skipped: RefCell::new(vec![]),
+            warnings: RefCell::new(vec![]),
+            revision: RefCell::new(InputRevision::default()),
+            included: RefCell::new(vec![]),
+        }
+    }
+}
+
+/// Summary of a finished command run
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Number of results (e.g. matched sections, tags or tasks) the command produced
+    pub result_count: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn file(path: &str, content: &str) -> MarkdownFile {
+        MarkdownFile::new(PathBuf::from(path), content.to_string())
+    }
+
+    #[test]
+    fn test_sections_best_effort() {
+        let files = vec![
+            file("good.md", "# 2023-01-01\n\n## Meeting\n"),
+            file("bad.md", "# Without date\n"),
+        ];
+
+        let ctx = ParseContext::default();
+        assert!(ctx.sections(&files).is_err());
+
+        let ctx = ParseContext {
+            best_effort: true,
+            ..Default::default()
+        };
+        let sections = ctx.sections(&files).unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(
+            ctx.skipped()
+                .iter()
+                .map(|s| s.path.clone())
+                .collect::<Vec<_>>(),
+            vec![PathBuf::from("bad.md")]
+        );
+    }
+
+    #[test]
+    fn test_parse_files_in_parallel() {
+        let files = (1..=9)
+            .map(|day| {
+                let content = match day {
+                    4 => "# Without date\n".to_string(),
+                    _ => format!("# 2023-01-0{}\n\n## Meeting\n@work\n", day),
+                };
+                file(&format!("{}.md", day), &content)
+            })
+            .collect::<Vec<_>>();
+        let ctx = |threads| ParseContext {
+            best_effort: true,
+            threads,
+            ..Default::default()
+        };
+
+        let serial = ctx(1);
+        let parallel = ctx(4);
+        assert_eq!(serial.tokens(&files), parallel.tokens(&files));
+        assert_eq!(serial.sections(&files), parallel.sections(&files));
+        assert_eq!(parallel.sections(&files).unwrap().len(), 8);
+        assert_eq!(serial.skipped(), parallel.skipped());
+        assert_eq!(parallel.skipped()[0].path, PathBuf::from("4.md"));
+    }
+
+    #[test]
+    fn test_limits() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("small.md"), "# 2023-01-01\n")?;
+        std::fs::write(dir.path().join("large.md"), "# 2023-01-02\n".repeat(10))?;
+        let input = vec![dir.path().to_path_buf()];
+
+        let ctx = |limits| ParseContext {
+            limits,
+            ..Default::default()
+        };
+        let files_limit = Limits {
+            max_files: Some(1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ctx(files_limit).read(input.clone()),
+            Err(MDPError::LimitExceededError(_))
+        ));
+
+        let size_limit = ctx(Limits {
+            max_file_size: Some(20),
+            ..Default::default()
+        });
+        let files = size_limit.read(input.clone())?;
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path.ends_with("small.md"));
+        assert!(size_limit.skipped()[0].path.ends_with("large.md"));
+
+        let token_limit = ctx(Limits {
+            max_tokens: Some(5),
+            ..Default::default()
+        });
+        let files = token_limit.read(input.clone())?;
+        assert!(token_limit.tokens(&files).is_err());
+        assert!(token_limit.sections(&files).is_err());
+
+        let forced = ctx(Limits::none());
+        let files = forced.read(input)?;
+        assert_eq!(files.len(), 2);
+        assert_eq!(forced.sections(&files)?.len(), 11);
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_files_are_skipped() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("note.md"), "# 2023-01-01\n")?;
+        std::fs::write(dir.path().join("image.md"), b"\x89PNG\r\n\x1a\n\0\0")?;
+
+        let ctx = ParseContext::default();
+        let files = ctx.read(vec![dir.path().to_path_buf()])?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            ctx.skipped()[0].error,
+            MDPError::BinaryFileError(dir.path().join("image.md"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_in_input() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("note.md"), "# 2023-01-01\n")?;
+        let output = dir.path().join("tags.md");
+        let ctx = |policy| ParseContext {
+            output_path: Some(output.clone()),
+            output_in_input: policy,
+            ..Default::default()
+        };
+        let read = |policy| {
+            ctx(policy)
+                .read(vec![dir.path().to_path_buf()])
+                .map(|files| files.len())
+                .map_err(|e| e.code())
+        };
+        let check = |policy| {
+            ctx(policy)
+                .check_output_path(vec![dir.path().to_path_buf()])
+                .map_err(|e| e.code())
+        };
+
+        // Even before the output file was written
+        assert_eq!(read(OutputInInput::Error), Err("MDP105"));
+        assert_eq!(check(OutputInInput::Error), Err("MDP105"));
+        assert_eq!(check(OutputInInput::Exclude), Ok(()));
+        std::fs::write(&output, "# Tags\n")?;
+        assert_eq!(read(OutputInInput::Error), Err("MDP105"));
+        assert_eq!(read(OutputInInput::Warn), Ok(2));
+        assert_eq!(read(OutputInInput::Exclude), Ok(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_revision() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let note = dir.path().join("note.md");
+        let revision = || -> anyhow::Result<InputRevision> {
+            let ctx = ParseContext::default();
+            ctx.read(vec![dir.path().to_path_buf()])?;
+            Ok(ctx.revision())
+        };
+
+        std::fs::write(&note, "# 2023-01-01\n")?;
+        let first = revision()?;
+        assert_eq!(revision()?, first);
+        assert_eq!(first.inputs, vec![dir.path().to_path_buf()]);
+        std::fs::write(&note, "# 2023-01-02\n")?;
+        assert_ne!(revision()?.hash, first.hash);
+        std::fs::write(&note, "# 2023-01-01\n")?;
+        assert_eq!(revision()?, first);
+        std::fs::write(dir.path().join("other.md"), "")?;
+        assert_ne!(revision()?.hash, first.hash);
+
+        assert_eq!(first.combine(&["a"]), first.combine(&["a"]));
+        assert_ne!(first.combine(&["ab", "c"]), first.combine(&["a", "bc"]));
+        assert_ne!(first.combine(&[]), first.hash);
+        Ok(())
+    }
+
+    /// Fails on the second line, which is kept as text in lenient mode
+    struct BrokenSecondLine;
+
+    impl MarkdownTokenizer for BrokenSecondLine {
+        /// Claims the settings of the default tokenizer, to check that its cached tokens
+        /// are used
+        fn cache_key(&self) -> Option<String> {
+            MDPMarkdownTokenizer::default().cache_key()
+        }
+
+        fn tokenize<'a>(&self, _: &'a str) -> Result<Vec<Token<'a>>, MDPError> {
+            Err(MDPError::MultiError(vec![self.error()]))
+        }
+
+        fn tokenize_lenient<'a>(&self, s: &'a str) -> (Vec<Token<'a>>, Vec<MDPError>) {
+            (vec![Token::Text(s)], vec![self.error()])
+        }
+    }
+
+    impl BrokenSecondLine {
+        fn error(&self) -> MDPError {
+            MDPError::MarkdownParseError {
+                msg: "broken".to_string(),
+                line_number: 2,
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokens_lenient() {
+        let files = vec![file("notes.md", "# 2023-01-01\n[broken")];
+        let ctx = |lenient| ParseContext {
+            tokenizer: Box::new(BrokenSecondLine),
+            lenient,
+            ..Default::default()
+        };
+
+        assert!(ctx(false).tokens(&files).is_err());
+
+        let lenient = ctx(true);
+        let tokens = lenient.tokens(&files).unwrap();
+        assert_eq!(tokens[0].0, Token::Text("# 2023-01-01\n[broken"));
+        assert!(lenient.skipped().is_empty());
+        assert_eq!(
+            lenient
+                .warnings()
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>(),
+            vec!["notes.md:2: [MDP001] broken"]
+        );
+    }
+
+    #[test]
+    fn test_token_stream() {
+        let files = vec![
+            file("a.md", "---\ntags: [work]\n---\n# 2023-01-01\n@roger\n"),
+            file("b.md", "# 2023-01-02\n| @a | b |\n|---|---|\nTODO: Task"),
+        ];
+        let ctx = ParseContext::default();
+        let mut stream = ctx.token_stream(&files);
+        let tokens = stream.by_ref().collect::<Vec<_>>();
+        assert!(stream.finish().is_ok());
+        assert_eq!(tokens, ctx.tokens(&files).unwrap());
+        assert_eq!(tokens[2].1, Origin::new(Some(&PathBuf::from("a.md")), 5, 1));
+
+        let broken = vec![file("notes.md", "# 2023-01-01\n[broken")];
+        let ctx = |best_effort| ParseContext {
+            tokenizer: Box::new(BrokenSecondLine),
+            best_effort,
+            ..Default::default()
+        };
+        let strict = ctx(false);
+        let mut stream = strict.token_stream(&broken);
+        assert_eq!(stream.by_ref().count(), 0);
+        assert!(stream.finish().is_err());
+        let best_effort = ctx(true);
+        let mut stream = best_effort.token_stream(&broken);
+        assert_eq!(stream.by_ref().count(), 0);
+        assert!(stream.finish().is_ok());
+        assert_eq!(best_effort.skipped()[0].path, PathBuf::from("notes.md"));
+
+        let limited = ParseContext {
+            limits: Limits {
+                max_tokens: Some(3),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut stream = limited.token_stream(&files);
+        assert_eq!(stream.by_ref().count(), 3);
+        assert!(matches!(
+            stream.finish(),
+            Err(MDPError::LimitExceededError(_))
+        ));
+    }
+
+    #[test]
+    fn test_front_matter() {
+        let content = "---\ntags: [work]\n---\n# 2023-01-01\n\n## Meeting\n@roger\n";
+        let files = vec![file("notes.md", content)];
+        let ctx = ParseContext::default();
+
+        let tokens = ctx.tokens(&files).unwrap();
+        assert_eq!(tokens[0].1.line, 4);
+        let sections = ctx.sections(&files).unwrap();
+        assert_eq!(sections[0].tags, vec!["work"]);
+        assert_eq!(sections[0].origin.line, 4);
+        assert_eq!(sections[0].subsections[0].tags, vec!["roger"]);
+
+        // The line numbers of parse errors refer to the whole file as well
+        let files = vec![file(
+            "notes.md",
+            "---\ntitle: Notes\n---\n# 2023-01-01\n[broken",
+        )];
+        let lenient = ParseContext {
+            tokenizer: Box::new(BrokenSecondLine),
+            lenient: true,
+            ..Default::default()
+        };
+        lenient.tokens(&files).unwrap();
+        assert_eq!(
+            lenient.warnings()[0].to_string(),
+            "notes.md:5: [MDP001] broken"
+        );
+    }
+
+    #[test]
+    fn test_cached_tokens() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_path = dir.path().join(".mdp-cache");
+        let path = dir.path().join("notes.md");
+        std::fs::write(&path, "# 2023-01-01\n\n## Meeting\nPlace: Bern\n")?;
+        let ctx = |tokenizer: Box<dyn MarkdownTokenizer>| ParseContext {
+            cache: Some(TokenCache::load(
+                &cache_path,
+                &tokenizer.cache_key().unwrap(),
+            )),
+            tokenizer,
+            ..Default::default()
+        };
+
+        let first = ctx(Box::<MDPMarkdownTokenizer>::default());
+        let files = first.read(vec![path.clone()])?;
+        let sections = first.sections(&files)?;
+        assert!(cache_path.exists());
+
+        // The cached tokens are used, the broken tokenizer is not even called
+        let cached = ctx(Box::new(BrokenSecondLine));
+        assert_eq!(cached.sections(&files)?, sections);
+        assert_eq!(cached.tokens(&files)?, first.tokens(&files)?);
+
+        // A tokenizer with other settings does not get the cached tokens
+        let single_colon = ctx(Box::new(MDPMarkdownTokenizer {
+            single_colon_attributes: true,
+            ..Default::default()
+        }));
+        assert_ne!(single_colon.tokens(&files)?, first.tokens(&files)?);
+
+        // Changed files are tokenized again
+        std::fs::write(&path, "# 2023-01-02\n")?;
+        let files = cached.read(vec![path])?;
+        assert!(cached.tokens(&files).is_err());
+        Ok(())
+    }
+}
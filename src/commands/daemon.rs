@@ -0,0 +1,367 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::models::MDPError;
+
+/// Environment variable overriding the location of the daemon's socket
+pub const DAEMON_SOCKET_ENV: &str = "MDP_SOCKET";
+
+/// Number of responses kept by a [`ResponseCache`], the oldest are dropped first
+const MAX_CACHED_RESPONSES: usize = 100;
+
+/// A command to run in the daemon, given as command line arguments
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DaemonRequest {
+    /// Relative input paths are resolved against this directory
+    pub cwd: PathBuf,
+    pub args: Vec<String>,
+}
+
+/// The output of a command run in the daemon (the client writes it to stdout/files)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonResponse {
+    pub output: String,
+    pub result_count: usize,
+    /// Input which was skipped in best effort mode
+    pub skipped: Vec<String>,
+    /// Lines which were kept as text in lenient mode
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Responses of the daemon by request, so a repeated command (e.g. the same search run by
+/// an editor on every keystroke) is answered without parsing the files again.
+///
+/// A response is only reused while its revision is unchanged, a hash of the files read at
+/// its input paths (see [`crate::commands::InputRevision`]) and of anything else its
+/// output depends on (e.g. the date of today).
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    entries: HashMap<DaemonRequest, CachedResponse>,
+    /// The cached requests from the oldest to the newest
+    order: VecDeque<DaemonRequest>,
+}
+
+#[derive(Debug)]
+struct CachedResponse {
+    inputs: Vec<PathBuf>,
+    revision: u64,
+    response: DaemonResponse,
+}
+
+impl ResponseCache {
+    /// The cached response of the request, if its revision is unchanged. `revision`
+    /// computes the current revision of the input paths of the response (`None` if they
+    /// can not be read).
+    pub fn get<F>(&self, request: &DaemonRequest, revision: F) -> Option<DaemonResponse>
+    where
+        F: FnOnce(&[PathBuf]) -> Option<u64>,
+    {
+        let cached = self.entries.get(request)?;
+        (revision(&cached.inputs)? == cached.revision).then(|| cached.response.clone())
+    }
+
+    /// Caches the response of the request, computed from the input paths at the revision
+    pub fn insert(
+        &mut self,
+        request: DaemonRequest,
+        inputs: Vec<PathBuf>,
+        revision: u64,
+        response: DaemonResponse,
+    ) {
+        let cached = CachedResponse {
+            inputs,
+            revision,
+            response,
+        };
+        if self.entries.insert(request.clone(), cached).is_none() {
+            self.order.push_back(request);
+        }
+        while self.order.len() > MAX_CACHED_RESPONSES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// The socket in the user's runtime (or data) directory, or at `$MDP_SOCKET` if set
+pub fn default_socket_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(DAEMON_SOCKET_ENV) {
+        return Some(path.into());
+    }
+    ProjectDirs::from("", "", "mdp").map(|dirs| {
+        dirs.runtime_dir()
+            .unwrap_or_else(|| dirs.data_dir())
+            .join("mdp.sock")
+    })
+}
+
+fn io_error<E: ToString>(socket: &Path, e: E) -> MDPError {
+    MDPError::IOError(format!(
+        "daemon socket {}: {}",
+        socket.display(),
+        e.to_string()
+    ))
+}
+
+#[cfg(unix)]
+pub use unix::*;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        path::{Path, PathBuf},
+    };
+
+    use super::{io_error, DaemonRequest, DaemonResponse};
+    use crate::models::MDPError;
+
+    /// Listens for requests on a Unix socket, one JSON request and response per connection.
+    pub struct DaemonServer {
+        pub path: PathBuf,
+        pub(super) listener: UnixListener,
+    }
+
+    impl DaemonServer {
+        /// Listen on the socket, a stale socket of a daemon which is no longer running is
+        /// replaced.
+        pub fn bind(path: &Path) -> Result<Self, MDPError> {
+            if path.exists() {
+                if UnixStream::connect(path).is_ok() {
+                    return Err(io_error(path, "a daemon is already running"));
+                }
+                fs::remove_file(path).map_err(|e| io_error(path, e))?;
+            }
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| io_error(path, e))?;
+            }
+            let listener = UnixListener::bind(path).map_err(|e| io_error(path, e))?;
+            Ok(Self {
+                path: path.to_path_buf(),
+                listener,
+            })
+        }
+
+        /// Answer requests one after another until an IO error occurs.
+        pub fn serve<F>(&self, mut handler: F) -> Result<(), MDPError>
+        where
+            F: FnMut(DaemonRequest) -> DaemonResponse,
+        {
+            for stream in self.listener.incoming() {
+                let stream = stream.map_err(|e| io_error(&self.path, e))?;
+                if let Err(e) = self.answer(stream, &mut handler) {
+                    log::warn!("Could not answer a request: {}", e);
+                }
+            }
+            Ok(())
+        }
+
+        /// Answer a single request
+        pub fn answer<F>(&self, stream: UnixStream, handler: &mut F) -> Result<(), MDPError>
+        where
+            F: FnMut(DaemonRequest) -> DaemonResponse,
+        {
+            let mut line = String::new();
+            let read = BufReader::new(&stream)
+                .read_line(&mut line)
+                .map_err(|e| io_error(&self.path, e))?;
+            // E.g. another daemon checking whether this one is still running
+            if read == 0 {
+                return Ok(());
+            }
+            let response = match serde_json::from_str(&line) {
+                Ok(request) => handler(request),
+                Err(e) => DaemonResponse {
+                    error: Some(format!("invalid request: {}", e)),
+                    ..Default::default()
+                },
+            };
+            write_line(&stream, &response).map_err(|e| io_error(&self.path, e))
+        }
+    }
+
+    impl Drop for DaemonServer {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// Send a request to the daemon listening on the socket
+    pub fn query(socket: &Path, request: &DaemonRequest) -> Result<DaemonResponse, MDPError> {
+        let stream = UnixStream::connect(socket).map_err(|e| io_error(socket, e))?;
+        write_line(&stream, request).map_err(|e| io_error(socket, e))?;
+
+        let mut line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut line)
+            .map_err(|e| io_error(socket, e))?;
+        serde_json::from_str(&line).map_err(|e| io_error(socket, e))
+    }
+
+    fn write_line<T: serde::Serialize>(mut stream: &UnixStream, value: &T) -> std::io::Result<()> {
+        let json = serde_json::to_string(value)?;
+        writeln!(stream, "{}", json)?;
+        stream.flush()
+    }
+}
+
+#[cfg(not(unix))]
+pub fn query(socket: &Path, _request: &DaemonRequest) -> Result<DaemonResponse, MDPError> {
+    Err(io_error(socket, "the daemon is only supported on unix"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{fs, thread};
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{
+        io::{default_extensions, CachedFileReader},
+        ParseContext,
+    };
+
+    #[test]
+    fn test_daemon_query() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let socket = dir.path().join("mdp.sock");
+        let server = DaemonServer::bind(&socket)?;
+        assert!(DaemonServer::bind(&socket).is_err());
+
+        let handle = thread::spawn(move || {
+            let mut handler = |request: DaemonRequest| DaemonResponse {
+                output: request.args.join(" "),
+                result_count: request.args.len(),
+                ..Default::default()
+            };
+            // The first connection is the one of the second `bind`
+            for _ in 0..2 {
+                let (stream, _) = server.listener.accept().unwrap();
+                server.answer(stream, &mut handler).unwrap();
+            }
+        });
+
+        let request = DaemonRequest {
+            cwd: dir.path().to_path_buf(),
+            args: vec!["mdp".to_string(), "tags".to_string()],
+        };
+        let response = query(&socket, &request)?;
+        handle.join().unwrap();
+
+        assert_eq!(response.output, "mdp tags");
+        assert_eq!(response.result_count, 2);
+        // The socket is removed when the server is dropped
+        assert!(!socket.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_cache() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(&path, "# 2024-06-01\n\n## Call\n")?;
+        let revision = |inputs: &[PathBuf]| {
+            let ctx = ParseContext::default();
+            ctx.read(inputs.to_vec()).ok()?;
+            Some(ctx.revision().hash)
+        };
+        let request = |arg: &str| DaemonRequest {
+            cwd: dir.path().to_path_buf(),
+            args: vec!["mdp".to_string(), arg.to_string()],
+        };
+        let response = DaemonResponse {
+            output: "@roger".to_string(),
+            ..Default::default()
+        };
+
+        let mut cache = ResponseCache::default();
+        let inputs = vec![dir.path().to_path_buf()];
+        cache.insert(
+            request("tags"),
+            inputs.clone(),
+            revision(&inputs).unwrap(),
+            response.clone(),
+        );
+        assert_eq!(cache.get(&request("tags"), revision), Some(response));
+        assert_eq!(cache.get(&request("tasks"), revision), None);
+
+        // Changed and added files invalidate the response
+        fs::write(&path, "# 2024-06-01\n\n## Call\n@roger\n")?;
+        assert_eq!(cache.get(&request("tags"), revision), None);
+        cache.insert(
+            request("tags"),
+            inputs.clone(),
+            revision(&inputs).unwrap(),
+            DaemonResponse::default(),
+        );
+        assert!(cache.get(&request("tags"), revision).is_some());
+        fs::write(dir.path().join("notes.md"), "# 2024-06-02\n")?;
+        assert_eq!(cache.get(&request("tags"), revision), None);
+
+        for i in 0..MAX_CACHED_RESPONSES {
+            let response = DaemonResponse::default();
+            cache.insert(request(&i.to_string()), vec![], 0, response);
+        }
+        assert_eq!(cache.entries.len(), MAX_CACHED_RESPONSES);
+        assert!(!cache.entries.contains_key(&request("tags")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_cache_input_list() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("diary.md"), "# 2024-06-01\n")?;
+        fs::write(dir.path().join("files.txt"), "*.md\n")?;
+        // Resolved against the working directory of the client, not of the daemon
+        let revision = |inputs: &[PathBuf]| {
+            let ctx = ParseContext::with_reader(CachedFileReader {
+                cache: Default::default(),
+                base_dir: dir.path().to_path_buf(),
+                max_depth: Some(1),
+                extensions: default_extensions(),
+                follow_symlinks: false,
+            });
+            ctx.read(inputs.to_vec()).ok()?;
+            Some(ctx.revision())
+        };
+        let request = DaemonRequest {
+            cwd: dir.path().to_path_buf(),
+            args: vec!["mdp".to_string(), "tags".to_string()],
+        };
+        let hash = |inputs: &[PathBuf]| revision(inputs).map(|r| r.hash);
+
+        let read = revision(&[PathBuf::from("@files.txt")]).unwrap();
+        assert_eq!(read.inputs, vec![PathBuf::from("@files.txt")]);
+        let mut cache = ResponseCache::default();
+        cache.insert(
+            request.clone(),
+            read.inputs.clone(),
+            read.hash,
+            DaemonResponse::default(),
+        );
+        assert!(cache.get(&request, hash).is_some());
+
+        // A new file matching a pattern of the list invalidates the response
+        fs::write(dir.path().join("notes.md"), "# 2024-06-02\n")?;
+        assert_eq!(cache.get(&request, hash), None);
+        let read = revision(&read.inputs).unwrap();
+        cache.insert(request.clone(), read.inputs, read.hash, DaemonResponse::default());
+        assert!(cache.get(&request, hash).is_some());
+
+        // And so does a changed list
+        fs::write(dir.path().join("files.txt"), "diary.md\n")?;
+        assert_eq!(cache.get(&request, hash), None);
+        Ok(())
+    }
+}
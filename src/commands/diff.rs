@@ -0,0 +1,131 @@
+/// Unchanged lines shown around every change
+const CONTEXT_LINES: usize = 3;
+
+/// A line of the diff
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Line<'t> {
+    Same(&'t str),
+    Removed(&'t str),
+    Added(&'t str),
+}
+
+impl Line<'_> {
+    fn is_change(&self) -> bool {
+        !matches!(self, Line::Same(_))
+    }
+
+    fn in_old(&self) -> bool {
+        !matches!(self, Line::Added(_))
+    }
+
+    fn in_new(&self) -> bool {
+        !matches!(self, Line::Removed(_))
+    }
+}
+
+/// The changes from `old` to `new` as unified diff (like `diff -u`) with the given file
+/// names in the header, empty if both are equal
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines = old.lines().collect::<Vec<&str>>();
+    let new_lines = new.lines().collect::<Vec<&str>>();
+    let lines = diff_lines(&old_lines, &new_lines);
+    if !lines.iter().any(Line::is_change) {
+        return String::new();
+    }
+
+    let mut s = format!("--- {}\n+++ {}\n", old_name, new_name);
+    for (start, end) in hunks(&lines) {
+        // The number of lines of both files before the hunk
+        let old_before = lines[..start].iter().filter(|l| l.in_old()).count();
+        let new_before = lines[..start].iter().filter(|l| l.in_new()).count();
+        let old_count = lines[start..end].iter().filter(|l| l.in_old()).count();
+        let new_count = lines[start..end].iter().filter(|l| l.in_new()).count();
+        // An empty range starts at the line before it
+        let first = |before: usize, count: usize| before + usize::from(count > 0);
+        s += &format!(
+            "@@ -{},{} +{},{} @@\n",
+            first(old_before, old_count),
+            old_count,
+            first(new_before, new_count),
+            new_count
+        );
+        for line in &lines[start..end] {
+            s += &match line {
+                Line::Same(l) => format!(" {}\n", l),
+                Line::Removed(l) => format!("-{}\n", l),
+                Line::Added(l) => format!("+{}\n", l),
+            };
+        }
+    }
+    s.trim_end_matches('\n').to_string()
+}
+
+/// Align the lines of both texts along their longest common subsequence
+fn diff_lines<'t>(old: &[&'t str], new: &[&'t str]) -> Vec<Line<'t>> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// The ranges of the lines shown in hunks: the changes with their context, merged if they
+/// overlap or touch
+fn hunks(lines: &[Line]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for (i, _) in lines.iter().enumerate().filter(|(_, l)| l.is_change()) {
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + CONTEXT_LINES + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "# 2024-05-01\n\na\nb\nc\nd\ne\nf\ng\nh\ni";
+        let new = "# 2024-05-01\n\na\nB\nc\nd\ne\nf\ng\nh\ni\nj";
+        assert_eq!(
+            unified_diff(old, new, "search.md", "search.md"),
+            "--- search.md\n+++ search.md\n\
+             @@ -1,7 +1,7 @@\n # 2024-05-01\n \n a\n-b\n+B\n c\n d\n e\n\
+             @@ -9,3 +9,4 @@\n g\n h\n i\n+j"
+        );
+        assert_eq!(
+            unified_diff("", "new", "a.md", "a.md"),
+            "--- a.md\n+++ a.md\n@@ -0,0 +1,1 @@\n+new"
+        );
+        assert_eq!(unified_diff(old, old, "a.md", "a.md"), "");
+    }
+}
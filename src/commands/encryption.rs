@@ -0,0 +1,234 @@
+use std::{
+    fmt, fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::models::MDPError;
+
+/// Environment variable holding the passphrase the cache files are encrypted with
+pub const CACHE_PASSPHRASE_ENV: &str = "MDP_CACHE_PASSPHRASE";
+
+/// Start of every encrypted file, followed by the salt, the nonce and the ciphertext
+const MAGIC: &[u8] = b"MDPENC1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Encrypts the files derived from the notes (the token cache, the cache of link titles,
+/// the search history and the section coverage) with a passphrase or the content of a key file, so their content is not stored in plain
+/// text.
+///
+/// Every file is encrypted with XChaCha20-Poly1305 using a key derived from the secret with
+/// Argon2 and a random salt stored in the file.
+#[derive(Clone)]
+pub struct CacheCipher {
+    secret: Vec<u8>,
+}
+
+impl fmt::Debug for CacheCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CacheCipher { .. }")
+    }
+}
+
+impl CacheCipher {
+    pub fn new<S: Into<Vec<u8>>>(secret: S) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+
+    /// A cipher using the content of the file (without surrounding whitespace) as secret
+    pub fn from_key_file(path: &Path) -> Result<Self, MDPError> {
+        let content = fs::read(path).map_err(|e| MDPError::IOReadError {
+            path: path.to_path_buf(),
+            details: e.to_string(),
+        })?;
+        let secret = content.trim_ascii();
+        if secret.is_empty() {
+            return Err(MDPError::IOReadError {
+                path: path.to_path_buf(),
+                details: "The key file is empty".to_string(),
+            });
+        }
+        Ok(Self::new(secret))
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.try_fill_bytes(&mut salt).map_err(|e| e.to_string())?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .aead(&salt)?
+            .encrypt(&nonce, plaintext)
+            .map_err(|e| e.to_string())?;
+
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&salt);
+        data.extend_from_slice(&nonce);
+        data.extend(ciphertext);
+        Ok(data)
+    }
+
+    /// Fails if the data was not encrypted by [`CacheCipher::encrypt`] with the same secret
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let rest = data
+            .strip_prefix(MAGIC)
+            .filter(|rest| rest.len() >= SALT_LEN + NONCE_LEN)
+            .ok_or("The file is not encrypted")?;
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        self.aead(salt)?
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "The passphrase or key file is wrong".to_string())
+    }
+
+    fn aead(&self, salt: &[u8]) -> Result<XChaCha20Poly1305, String> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(&self.secret, salt, &mut key)
+            .map_err(|e| e.to_string())?;
+        Ok(XChaCha20Poly1305::new(&key.into()))
+    }
+}
+
+/// A cache file, which is encrypted if a cipher is given
+#[derive(Clone, Debug)]
+pub struct CacheLocation {
+    pub path: PathBuf,
+    pub cipher: Option<CacheCipher>,
+}
+
+impl CacheLocation {
+    /// The content of the file, `None` if it is missing or can not be decrypted
+    pub fn read(&self) -> Option<String> {
+        let data = fs::read(&self.path).ok()?;
+        let data = match (&self.cipher, data.starts_with(MAGIC)) {
+            (Some(cipher), _) => match cipher.decrypt(&data) {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!("Ignoring the cache {}: {}", self.path.display(), e);
+                    return None;
+                }
+            },
+            (None, true) => {
+                log::warn!(
+                    "Ignoring the encrypted cache {}: no passphrase or key file is set",
+                    self.path.display()
+                );
+                return None;
+            }
+            (None, false) => data,
+        };
+        String::from_utf8(data).ok()
+    }
+
+    /// The content of the file, `None` if it is missing. Unlike [`CacheLocation::read`], a
+    /// file which can not be decrypted is an error, for files which must not be rebuilt
+    /// (like the search history). An unencrypted file is read as is, so it is encrypted
+    /// when it is written the next time.
+    pub fn read_existing(&self) -> Result<Option<String>, MDPError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let read_error = |details: String| MDPError::IOReadError {
+            path: self.path.clone(),
+            details,
+        };
+        let data = fs::read(&self.path).map_err(|e| read_error(e.to_string()))?;
+        let data = match (&self.cipher, data.starts_with(MAGIC)) {
+            (Some(cipher), true) => cipher.decrypt(&data).map_err(read_error)?,
+            (None, true) => {
+                let details = "The file is encrypted, set the passphrase or key file";
+                return Err(read_error(details.to_string()));
+            }
+            (_, false) => data,
+        };
+        String::from_utf8(data)
+            .map(Some)
+            .map_err(|e| read_error(e.to_string()))
+    }
+
+    /// Write the content, an encrypted file is never replaced by an unencrypted one
+    pub fn write(&self, content: &str) -> Result<(), MDPError> {
+        let write_error = |details: String| MDPError::IOWriteError {
+            path: self.path.clone(),
+            details,
+        };
+        let data = match &self.cipher {
+            Some(cipher) => cipher.encrypt(content.as_bytes()).map_err(write_error)?,
+            None if self.is_encrypted() => {
+                let details = "The cache is encrypted, set the passphrase or key file";
+                return Err(write_error(details.to_string()));
+            }
+            None => content.as_bytes().to_vec(),
+        };
+        fs::write(&self.path, data).map_err(|e| write_error(e.to_string()))
+    }
+
+    fn is_encrypted(&self) -> bool {
+        let mut magic = [0u8; MAGIC.len()];
+        fs::File::open(&self.path)
+            .and_then(|mut f| f.read_exact(&mut magic))
+            .is_ok_and(|_| magic == MAGIC)
+    }
+}
+
+impl From<&Path> for CacheLocation {
+    /// An unencrypted cache file
+    fn from(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            cipher: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_encrypted_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = CacheLocation {
+            path: dir.path().join(".mdp-cache"),
+            cipher: Some(CacheCipher::new("correct horse")),
+        };
+
+        location.write("{\"entries\":{}}").unwrap();
+        let data = fs::read(&location.path).unwrap();
+        assert!(data.starts_with(MAGIC));
+        assert!(!String::from_utf8_lossy(&data).contains("entries"));
+        assert_eq!(location.read(), Some("{\"entries\":{}}".to_string()));
+
+        let wrong = CacheLocation {
+            cipher: Some(CacheCipher::new("battery staple")),
+            ..location.clone()
+        };
+        assert_eq!(wrong.read(), None);
+        let unencrypted = CacheLocation::from(location.path.as_path());
+        assert_eq!(unencrypted.read(), None);
+        assert!(unencrypted.write("{}").is_err());
+
+        // Files which must not be rebuilt fail instead
+        assert_eq!(location.read_existing().unwrap(), Some("{\"entries\":{}}".to_string()));
+        assert!(wrong.read_existing().is_err());
+        assert!(unencrypted.read_existing().is_err());
+        let plain = dir.path().join("plain.json");
+        fs::write(&plain, "{}").unwrap();
+        let plain = CacheLocation {
+            path: plain,
+            ..location.clone()
+        };
+        assert_eq!(plain.read_existing().unwrap(), Some("{}".to_string()));
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::{anyhow, Result};
+
+use super::config::ExplainConfig;
+use crate::{
+    commands::{io::OutputSink, Command, ParseContext, Report},
+    models::explain_error,
+};
+
+pub struct ExplainCommand {
+    pub config: ExplainConfig,
+}
+
+impl Command for ExplainCommand {
+    fn run(&self, _ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let explanation = explain_error(&self.config.code)
+            .ok_or_else(|| anyhow!("Unknown error code '{}'", self.config.code))?;
+
+        out.write_output(&explanation.to_text())?;
+
+        Ok(Report { result_count: 1 })
+    }
+}
@@ -0,0 +1,5 @@
+#[derive(Clone, Debug)]
+pub struct ExplainConfig {
+    /// Error code to explain, e.g. `MDP001`
+    pub code: String,
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+
+use super::config::FmtConfig;
+use crate::{
+    commands::{
+        diff::unified_diff,
+        io::{MarkdownFile, OutputSink},
+        rewrite::replace_content,
+        Command, ParseContext, Report,
+    },
+    markdown::{format_markdown, MDPMarkdownTokenizer},
+};
+
+/// Reformats files into the canonical style of mdp (see [`format_markdown`]), in place. The
+/// front matter is kept as it is. Task keywords are read in any case, so e.g. `todo:`
+/// becomes `TODO:`.
+pub struct FmtCommand {
+    pub config: FmtConfig,
+}
+
+impl Command for FmtCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let tokenizer = MDPMarkdownTokenizer {
+            any_case_task_keywords: true,
+            today: Some(ctx.clock.today()),
+            ..Default::default()
+        };
+
+        let mut lines = vec![];
+        for file in &files {
+            let formatted = format_file(file, &tokenizer);
+            if formatted == file.content {
+                continue;
+            }
+            let name = file.path.display().to_string();
+            match config.dry_run {
+                true => lines.push(unified_diff(&file.content, &formatted, &name, &name)),
+                false => {
+                    if !config.check {
+                        replace_content(&file.path, &file.content, formatted)?;
+                    }
+                    lines.push(name);
+                }
+            }
+        }
+
+        out.write_output(&lines.join("\n"))?;
+        Ok(Report {
+            result_count: lines.len(),
+        })
+    }
+}
+
+/// The content of the file with its body formatted
+fn format_file(file: &MarkdownFile, tokenizer: &MDPMarkdownTokenizer) -> String {
+    let (body, _) = file.body();
+    let front_matter = &file.content[..file.content.len() - body.len()];
+    format!("{}{}", front_matter, format_markdown(body, tokenizer))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_fmt() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("journal.md");
+        let content = "---\ntags: [diary]\n---\n#  2024-05-01\ntodo: Water plants\n\n\n\
+                       # 2024-05-02\n- [X] Gardening";
+        fs::write(&path, content)?;
+        let formatted = dir.path().join("formatted.md");
+        fs::write(&formatted, "# 2024-05-01\n\nTODO: Water plants\n")?;
+
+        let ctx = ParseContext::default();
+        let run = |config: FmtConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = FmtCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+        let builder = || FmtConfig::builder().input(dir.path());
+
+        let (diff, count) = run(builder().dry_run(true).build())?;
+        assert_eq!(count, 1);
+        assert!(diff.contains("+TODO: Water plants"));
+        assert_eq!(
+            run(builder().check(true).build())?,
+            (path.display().to_string(), 1)
+        );
+        assert_eq!(fs::read_to_string(&path)?, content);
+
+        run(builder().build())?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "---\ntags: [diary]\n---\n# 2024-05-01\nTODO: Water plants\n\n\
+             # 2024-05-02\n- [x] Gardening\n"
+        );
+        assert_eq!(run(builder().check(true).build())?, (String::new(), 0));
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct FmtConfig {
+    /// Paths of the files to format (or of directories containing them)
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// Only list the files which are not formatted instead of formatting them
+    pub check: bool,
+    /// Only show the changes (as unified diff) instead of writing them to the files
+    pub dry_run: bool,
+}
+
+impl FmtConfig {
+    pub fn builder() -> FmtConfigBuilder {
+        FmtConfigBuilder::default()
+    }
+}
+
+/// Builder for [`FmtConfig`]
+#[derive(Clone, Debug)]
+pub struct FmtConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    check: bool,
+    dry_run: bool,
+}
+
+impl Default for FmtConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            check: false,
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for FmtConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl FmtConfigBuilder {
+    pub fn check(mut self, check: bool) -> Self {
+        self.check = check;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> FmtConfig {
+        FmtConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            check: self.check,
+            dry_run: self.dry_run,
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::{collections::BTreeSet, fs};
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+
+use super::config::{GapsConfig, DEFAULT_DAILY_TEMPLATE};
+use crate::{
+    commands::{
+        diff::unified_diff,
+        io::{MarkdownFile, OutputSink},
+        locale::{Language, Text},
+        rewrite::replace_content,
+        Command, ParseContext, Report,
+    },
+    models::MDPError,
+};
+
+/// Lists the days of a period without a section, e.g. to fill in the days forgotten to
+/// write about. With `--fill`, a section created from the daily template is added for
+/// each of them next to the closest day, so the days of a file stay in order.
+pub struct GapsCommand {
+    pub config: GapsConfig,
+}
+
+/// A top level section: the file and the line it starts at
+#[derive(Clone, Debug)]
+struct Day {
+    file: usize,
+    line: usize,
+    date: Option<NaiveDate>,
+}
+
+impl Command for GapsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let today = ctx.clock.today();
+        let files = ctx.read(config.input_path.clone())?;
+
+        let mut days = vec![];
+        let mut covered = BTreeSet::new();
+        for (i, file) in files.iter().enumerate() {
+            let sections = ctx.sections(std::slice::from_ref(file))?;
+            for section in sections.iter().filter(|s| s.section_type.is_top_level()) {
+                if let (Some(first), Some(last)) = (section.date, section.last_date()) {
+                    covered.extend(first.iter_days().take_while(|d| *d <= last));
+                }
+                days.push(Day {
+                    file: i,
+                    line: section.origin.line,
+                    date: section.date,
+                });
+            }
+        }
+        // Merged days (`--merge-days`) keep the position of their first section
+        days.sort_by_key(|d| (d.file, d.line));
+        days.dedup_by_key(|d| (d.file, d.line));
+
+        let from = config
+            .from
+            .and_then(|d| d.resolve(today))
+            .or(covered.first().copied());
+        let until = config.until.and_then(|d| d.resolve(today)).unwrap_or(today);
+        let gaps = match from {
+            Some(from) => from
+                .iter_days()
+                .take_while(|d| *d <= until)
+                .filter(|d| !covered.contains(d))
+                .collect::<Vec<NaiveDate>>(),
+            None => vec![],
+        };
+
+        if !config.fill || files.is_empty() {
+            let lines = gaps
+                .iter()
+                .map(|d| format!("{} {}", d, ctx.lang.weekday(d.weekday())))
+                .collect::<Vec<String>>();
+            out.write_output(&lines.join("\n"))?;
+            return Ok(Report {
+                result_count: gaps.len(),
+            });
+        }
+
+        let template = match &config.template_path {
+            Some(path) => fs::read_to_string(path).map_err(|e| MDPError::IOReadError {
+                path: path.clone(),
+                details: e.to_string(),
+            })?,
+            None => DEFAULT_DAILY_TEMPLATE.to_string(),
+        };
+        let mut stubs = vec![vec![]; files.len()];
+        for gap in &gaps {
+            // Without any day, the sections are appended to the first file
+            let (file, offset) =
+                insertion_point(&days, &files[..], *gap).unwrap_or((0, files[0].content.len()));
+            stubs[file].push((offset, *gap));
+        }
+
+        let mut lines = vec![];
+        for (i, (file, mut stubs)) in files.iter().zip(stubs).enumerate() {
+            if stubs.is_empty() {
+                continue;
+            }
+            match is_descending(&days, i) {
+                true => stubs.sort_by_key(|(offset, date)| (*offset, std::cmp::Reverse(*date))),
+                false => stubs.sort(),
+            }
+            let filled = insert_stubs(&file.content, &stubs, &template, ctx.lang);
+            let name = file.path.display().to_string();
+            match config.dry_run {
+                true => lines.push(unified_diff(&file.content, &filled, &name, &name)),
+                false => {
+                    replace_content(&file.path, &file.content, filled)?;
+                    lines.push(format!(
+                        "{}: {}",
+                        name,
+                        ctx.lang.format(Text::SectionsAdded, &[&stubs.len()])
+                    ));
+                }
+            }
+        }
+
+        out.write_output(&lines.join("\n"))?;
+        Ok(Report {
+            result_count: gaps.len(),
+        })
+    }
+}
+
+/// Whether the days of the file are written latest first
+fn is_descending(days: &[Day], file: usize) -> bool {
+    let mut dates = days
+        .iter()
+        .filter(|d| d.file == file)
+        .filter_map(|d| d.date);
+    match (dates.next(), dates.next_back()) {
+        (Some(first), Some(last)) => first > last,
+        _ => false,
+    }
+}
+
+/// The file and byte offset the section of the date is inserted at: next to the latest
+/// earlier day (or, if there is none, the earliest later day), on the side keeping the
+/// order of the days of its file
+fn insertion_point(
+    days: &[Day],
+    files: &[MarkdownFile],
+    date: NaiveDate,
+) -> Option<(usize, usize)> {
+    let dated = || days.iter().filter_map(|d| d.date.map(|date| (d, date)));
+    let (anchor, after) = match dated().filter(|(_, d)| *d < date).max_by_key(|(_, d)| *d) {
+        Some((anchor, _)) => (anchor, true),
+        None => (
+            dated()
+                .filter(|(_, d)| *d > date)
+                .min_by_key(|(_, d)| *d)?
+                .0,
+            false,
+        ),
+    };
+
+    let content = &files[anchor.file].content;
+    let offset = match after != is_descending(days, anchor.file) {
+        // At the start of the next section of the file
+        true => days
+            .iter()
+            .filter(|d| d.file == anchor.file && d.line > anchor.line)
+            .map(|d| d.line)
+            .min()
+            .map_or(content.len(), |line| line_start(content, line)),
+        false => line_start(content, anchor.line),
+    };
+    Some((anchor.file, offset))
+}
+
+/// Byte offset of the start of a line (starting at 1)
+fn line_start(content: &str, line: usize) -> usize {
+    match line {
+        0 | 1 => 0,
+        _ => content
+            .match_indices('\n')
+            .nth(line - 2)
+            .map_or(content.len(), |(i, _)| i + 1),
+    }
+}
+
+/// The content with a section created from the template inserted at each offset (which are
+/// sorted), separated from the following section by a blank line
+fn insert_stubs(
+    content: &str,
+    stubs: &[(usize, NaiveDate)],
+    template: &str,
+    lang: Language,
+) -> String {
+    let line_ending = match content.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    };
+    let mut filled = String::with_capacity(content.len());
+    let mut position = 0;
+    for (offset, date) in stubs {
+        filled += &content[position..*offset];
+        position = *offset;
+        if position == content.len() && !filled.trim().is_empty() {
+            if !filled.ends_with('\n') {
+                filled += line_ending;
+            }
+            let trailing = &filled[filled.trim_end_matches(['\r', '\n']).len()..];
+            if trailing.matches('\n').count() < 2 {
+                filled += line_ending;
+            }
+        }
+        let stub = template
+            .replace("{date}", &date.to_string())
+            .replace("{weekday}", lang.weekday(date.weekday()));
+        for line in stub.lines() {
+            filled += line;
+            filled += line_ending;
+        }
+        if position < content.len() {
+            filled += line_ending;
+        }
+    }
+    filled += &content[position..];
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder, RelativeDate};
+
+    #[test]
+    fn test_gaps() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("journal.md");
+        let content = "---\ntags: [diary]\n---\n# 2024-05-01\nGardening\n\n\
+                       # 2024-05-03 - 2024-05-04 Trip\n\n# 2024-05-07\n";
+        fs::write(&path, content)?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()));
+        let run = |config: GapsConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = GapsCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        assert_eq!(
+            run(GapsConfig::builder().input(&path).build())?,
+            (
+                "2024-05-02 Thursday\n2024-05-05 Sunday\n2024-05-06 Monday\n\
+                 2024-05-08 Wednesday"
+                    .to_string(),
+                4
+            )
+        );
+        let builder = || {
+            GapsConfig::builder()
+                .input(&path)
+                .from(RelativeDate::Date(
+                    NaiveDate::from_ymd_opt(2024, 4, 30).unwrap(),
+                ))
+                .until(RelativeDate::Days(-2))
+        };
+        assert_eq!(run(builder().build())?.1, 4);
+
+        let (diff, count) = run(builder().fill(true).dry_run(true).build())?;
+        assert_eq!(count, 4);
+        assert!(diff.contains("+# 2024-04-30"));
+        assert_eq!(fs::read_to_string(&path)?, content);
+
+        let template = dir.path().join("daily.md");
+        fs::write(&template, "# {date}\n\n## {weekday}\n")?;
+        assert_eq!(
+            run(builder().fill(true).template(&template).build())?,
+            (format!("{}: Sections added: 4", path.display()), 4)
+        );
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "---\ntags: [diary]\n---\n# 2024-04-30\n\n## Tuesday\n\n# 2024-05-01\n\
+             Gardening\n\n# 2024-05-02\n\n## Thursday\n\n\
+             # 2024-05-03 - 2024-05-04 Trip\n\n# 2024-05-05\n\n## Sunday\n\n\
+             # 2024-05-06\n\n## Monday\n\n# 2024-05-07\n"
+        );
+        assert_eq!(run(builder().fill(true).build())?.1, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_stubs() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let content = "# 2024-05-03\r\nc\r\n\r\n# 2024-05-01\r\na";
+        let days = [
+            Day {
+                file: 0,
+                line: 1,
+                date: Some(date(3)),
+            },
+            Day {
+                file: 0,
+                line: 4,
+                date: Some(date(1)),
+            },
+        ];
+        let files = [MarkdownFile::new("journal.md".into(), content.to_string())];
+        let stubs = [date(4), date(2)]
+            .map(|d| insertion_point(&days, &files, d).unwrap().1)
+            .into_iter()
+            .zip([date(4), date(2)])
+            .collect::<Vec<_>>();
+        assert_eq!(stubs, vec![(0, date(4)), (19, date(2))]);
+        assert_eq!(
+            insert_stubs(content, &stubs, "# {date}", Language::English),
+            "# 2024-05-04\r\n\r\n# 2024-05-03\r\nc\r\n\r\n# 2024-05-02\r\n\r\n\
+             # 2024-05-01\r\na"
+        );
+        assert_eq!(
+            insert_stubs("a", &[(1, date(2))], "# {date}\n", Language::English),
+            "a\n\n# 2024-05-02\n"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use crate::commands::{InputBuilder, RelativeDate};
+
+/// Template of the sections added by `--fill` if none is configured
+pub const DEFAULT_DAILY_TEMPLATE: &str = "# {date}\n";
+
+#[derive(Clone, Debug)]
+pub struct GapsConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// First day checked for a section (the first day with a section if `None`)
+    pub from: Option<RelativeDate>,
+    /// Last day checked for a section (today if `None`)
+    pub until: Option<RelativeDate>,
+    /// Add a section created from the template for every day without one
+    pub fill: bool,
+    /// File with the template of the added sections (see [`DEFAULT_DAILY_TEMPLATE`])
+    pub template_path: Option<PathBuf>,
+    /// Only show the sections `fill` would add (as unified diff) instead of writing them
+    pub dry_run: bool,
+}
+
+impl GapsConfig {
+    pub fn builder() -> GapsConfigBuilder {
+        GapsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`GapsConfig`]
+#[derive(Clone, Debug)]
+pub struct GapsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    from: Option<RelativeDate>,
+    until: Option<RelativeDate>,
+    fill: bool,
+    template_path: Option<PathBuf>,
+    dry_run: bool,
+}
+
+impl Default for GapsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            from: None,
+            until: None,
+            fill: false,
+            template_path: None,
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for GapsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl GapsConfigBuilder {
+    pub fn from(mut self, date: RelativeDate) -> Self {
+        self.from = Some(date);
+        self
+    }
+
+    pub fn until(mut self, date: RelativeDate) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    pub fn fill(mut self, fill: bool) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn template<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.template_path = Some(path.into());
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> GapsConfig {
+        GapsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            from: self.from,
+            until: self.until,
+            fill: self.fill,
+            template_path: self.template_path,
+            dry_run: self.dry_run,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::config::GoalsConfig;
+use crate::{
+    commands::{io::OutputSink, locale::Text, Command, ParseContext, Report},
+    models::{Section, Token},
+};
+
+/// Shows the progress of every goal over time: a goal is named with `Goal:: <name>`, the
+/// `Progress:: <percent>` attributes following it (in the same section or its subsections)
+/// are its progress on the date of the section.
+pub struct GoalsCommand {
+    pub config: GoalsConfig,
+}
+
+/// The attributes of a section describing a goal
+#[derive(Clone, Debug, PartialEq)]
+enum GoalAttribute {
+    /// `Goal:: <name>`
+    Goal(String),
+    /// `Progress:: 40%` (the `%` is optional)
+    Progress(f64),
+}
+
+impl GoalAttribute {
+    /// `None` if the token is no goal attribute, an error if the progress is no number
+    fn from_token(token: &Token) -> Option<Result<Self, String>> {
+        let Token::Attribute { name, value, .. } = token else {
+            return None;
+        };
+        let value: String = value.iter().map(|t| t.to_plain_string()).collect();
+        let value = value.trim();
+        match name.trim().to_lowercase().as_str() {
+            "goal" => Some(Ok(GoalAttribute::Goal(value.to_string()))),
+            "progress" => {
+                let number = value.strip_suffix('%').unwrap_or(value).trim();
+                Some(match number.parse::<f64>() {
+                    Ok(progress) if progress.is_finite() => Ok(GoalAttribute::Progress(progress)),
+                    _ => Err(format!("The progress '{}' is no percentage", value)),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Command for GoalsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+
+        let mut goals: BTreeMap<String, Vec<(NaiveDate, f64)>> = BTreeMap::new();
+        for (goal, date, progress) in progress_entries(&sections, None) {
+            goals.entry(goal).or_default().push((date, progress));
+        }
+        if let Some(goal) = &config.goal {
+            goals.retain(|g, _| g.eq_ignore_ascii_case(goal.trim()));
+        }
+        if goals.is_empty() {
+            log::warn!("No progress of goals found!");
+            return Ok(Report::default());
+        }
+
+        let parts = goals
+            .iter_mut()
+            .map(|(goal, entries)| {
+                // Entries of the same day keep their order
+                entries.sort_by_key(|(date, _)| *date);
+                goal_to_string(goal, entries, ctx)
+            })
+            .collect::<Vec<String>>();
+        out.write_output(&parts.join("\n\n"))?;
+
+        Ok(Report {
+            result_count: goals.len(),
+        })
+    }
+}
+
+/// The progress entries (goal, date, progress) of the sections, a section without a goal
+/// of its own continues the goal of its parent. Progress in sections without a date is left
+/// out.
+fn progress_entries(
+    sections: &[Section],
+    parent_goal: Option<&str>,
+) -> Vec<(String, NaiveDate, f64)> {
+    let mut entries = vec![];
+    for section in sections {
+        let mut goal = parent_goal.map(str::to_string);
+        for (token, origin) in section.content_with_origins() {
+            match GoalAttribute::from_token(token) {
+                Some(Ok(GoalAttribute::Goal(name))) => goal = Some(name),
+                Some(Ok(GoalAttribute::Progress(progress))) => match &goal {
+                    Some(goal) => {
+                        entries.extend(section.date.map(|date| (goal.clone(), date, progress)))
+                    }
+                    None => log::warn!("{}: The progress belongs to no goal", origin),
+                },
+                Some(Err(message)) => log::warn!("{}: {}", origin, message),
+                None => {}
+            }
+        }
+        entries.extend(progress_entries(&section.subsections, goal.as_deref()));
+    }
+    entries
+}
+
+/// The entries of the goal followed by its latest progress and the change since the first
+/// entry
+fn goal_to_string(goal: &str, entries: &[(NaiveDate, f64)], ctx: &ParseContext) -> String {
+    let mut lines = vec![format!("# {}", goal), String::new()];
+    lines.extend(
+        entries
+            .iter()
+            .map(|(date, progress)| format!("- {}: {}%", date, progress)),
+    );
+
+    if let (Some((first_date, first)), Some((_, latest))) = (entries.first(), entries.last()) {
+        let latest_text = ctx
+            .lang
+            .format(Text::GoalLatest, &[&format!("{}%", latest)]);
+        let line = match entries.len() {
+            1 => latest_text,
+            _ => {
+                let change = latest - first;
+                let arrow = match change {
+                    c if c > 0.0 => '↑',
+                    c if c < 0.0 => '↓',
+                    _ => '→',
+                };
+                let trend = ctx.lang.format(
+                    Text::GoalTrend,
+                    &[&format!("{} {:+}%", arrow, change), first_date],
+                );
+                format!("{} ({})", latest_text, trend)
+            }
+        };
+        lines.push(String::new());
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_goal_attribute() {
+        let attribute = |name, value| Token::Attribute {
+            name,
+            separator: "::",
+            value: vec![Token::Text(value)],
+        };
+        assert_eq!(
+            GoalAttribute::from_token(&attribute("Goal", " Marathon")),
+            Some(Ok(GoalAttribute::Goal("Marathon".to_string())))
+        );
+        assert_eq!(
+            GoalAttribute::from_token(&attribute("progress", " 42.5 %")),
+            Some(Ok(GoalAttribute::Progress(42.5)))
+        );
+        assert!(matches!(
+            GoalAttribute::from_token(&attribute("Progress", " a lot")),
+            Some(Err(_))
+        ));
+        assert_eq!(GoalAttribute::from_token(&attribute("Source", " x")), None);
+    }
+
+    #[test]
+    fn test_goals() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2024-06-08\n\n## Running\n\nGoal:: Marathon\n\n### Training\n\nProgress:: 60%\n\n\
+             # 2024-06-01\n\nGoal:: Marathon\nProgress:: 40%\nGoal:: Spanish\nProgress:: 10%\n",
+        )?;
+
+        let ctx = ParseContext::default();
+        let run = |config: GoalsConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = GoalsCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let (output, count) = run(GoalsConfig::builder().input(&path).build())?;
+        assert_eq!(
+            output,
+            "# Marathon\n\n- 2024-06-01: 40%\n- 2024-06-08: 60%\n\n\
+             Latest: 60% (↑ +20% since 2024-06-01)\n\n\
+             # Spanish\n\n- 2024-06-01: 10%\n\nLatest: 10%"
+        );
+        assert_eq!(count, 2);
+
+        let (output, _) = run(GoalsConfig::builder().input(&path).goal("spanish").build())?;
+        assert!(output.starts_with("# Spanish"));
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct GoalsConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Only show the progress of this goal (compared case-insensitively)
+    pub goal: Option<String>,
+}
+
+impl GoalsConfig {
+    pub fn builder() -> GoalsConfigBuilder {
+        GoalsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`GoalsConfig`]
+#[derive(Clone, Debug)]
+pub struct GoalsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    goal: Option<String>,
+}
+
+impl Default for GoalsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            goal: None,
+        }
+    }
+}
+
+impl InputBuilder for GoalsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl GoalsConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Only show the progress of this goal
+    pub fn goal<S: Into<String>>(mut self, goal: S) -> Self {
+        self.goal = Some(goal.into());
+        self
+    }
+
+    pub fn build(self) -> GoalsConfig {
+        GoalsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            goal: self.goal,
+        }
+    }
+}
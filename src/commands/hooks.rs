@@ -0,0 +1,239 @@
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::models::MDPError;
+
+/// Shell command which is run after an output file was written.
+///
+/// The path of the written file is passed as last argument, e.g. `prettier --write`
+/// runs `prettier --write <path>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PostWriteHook {
+    pub command: String,
+}
+
+impl PostWriteHook {
+    pub fn new<S: Into<String>>(command: S) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// Run the hook if the policy permits it, a non-zero exit status is reported as
+    /// `MDPError::HookError`.
+    pub fn run(&self, path: &Path, policy: &ExecPolicy) -> Result<(), MDPError> {
+        policy.check(&self.command)?;
+
+        let output = shell_command(&self.command, path)
+            .output()
+            .map_err(|e| self.error(e.to_string()))?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(self.error(format!("{} {}", output.status, stderr.trim())))
+    }
+
+    fn error(&self, details: String) -> MDPError {
+        MDPError::HookError {
+            command: self.command.clone(),
+            details: details.trim().to_string(),
+        }
+    }
+}
+
+/// Shell command reading its input from stdin and writing its result to stdout, e.g. a
+/// summarizer like `ollama run llama3 "Summarize:"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilterCommand {
+    pub command: String,
+}
+
+impl FilterCommand {
+    pub fn new<S: Into<String>>(command: S) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// Pipe the input through the command if the policy permits it and return its output,
+    /// a non-zero exit status is reported as `MDPError::HookError`.
+    pub fn run(&self, input: &str, policy: &ExecPolicy) -> Result<String, MDPError> {
+        policy.check(&self.command)?;
+        let error = |details: String| MDPError::HookError {
+            command: self.command.clone(),
+            details: details.trim().to_string(),
+        };
+
+        let mut child = shell(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| error(e.to_string()))?;
+        // Written in another thread, so a command writing before it read everything can't
+        // block both processes
+        let stdin = child.stdin.take();
+        let input = input.to_string();
+        let writer = thread::spawn(move || stdin.map(|mut s| s.write_all(input.as_bytes())));
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| error(e.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(error(format!("{} {}", output.status, stderr.trim())));
+        }
+        // The command may exit without reading everything (e.g. `head`)
+        let _ = writer.join();
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// The shell running `command`
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// The shell running `command`
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// The shell command running `command` with the path as last argument
+#[cfg(unix)]
+pub(crate) fn shell_command(command: &str, path: &Path) -> Command {
+    // The path is passed as positional parameter so that it doesn't need quoting
+    let mut shell = Command::new("sh");
+    shell
+        .arg("-c")
+        .arg(format!("{} \"$@\"", command))
+        .arg("sh")
+        .arg(path);
+    shell
+}
+
+/// The shell command running `command` with the path as last argument
+#[cfg(windows)]
+pub(crate) fn shell_command(command: &str, path: &Path) -> Command {
+    let mut shell = Command::new("cmd");
+    shell
+        .arg("/C")
+        .arg(format!("{} \"{}\"", command, path.display()));
+    shell
+}
+
+/// Which external commands may be executed (e.g. by hooks)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ExecPolicy {
+    #[default]
+    AllowAll,
+    DenyAll,
+    /// Only commands starting with one of the listed executables (names or paths) are
+    /// permitted. Shell operators are rejected so that no other commands can be chained.
+    Allowlist(Vec<String>),
+}
+
+/// Characters which would let a shell run further commands
+const SHELL_OPERATORS: &[char] = &[';', '&', '|', '$', '`', '<', '>', '(', ')', '\n'];
+
+impl ExecPolicy {
+    /// Returns `MDPError::ExecNotPermittedError` if the command must not be executed.
+    pub fn check(&self, command: &str) -> Result<(), MDPError> {
+        let reason = match self {
+            ExecPolicy::AllowAll => return Ok(()),
+            ExecPolicy::DenyAll => "execution of external commands is disabled".to_string(),
+            ExecPolicy::Allowlist(_) if command.contains(SHELL_OPERATORS) => {
+                "shell operators are not allowed together with an allowlist".to_string()
+            }
+            ExecPolicy::Allowlist(allowed) => match command.split_whitespace().next() {
+                Some(executable) if allowed.iter().any(|a| a == executable) => return Ok(()),
+                Some(executable) => format!("'{}' is not an allowed executable", executable),
+                None => "the command is empty".to_string(),
+            },
+        };
+        Err(MDPError::ExecNotPermittedError {
+            command: command.to_string(),
+            reason,
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_post_write_hook() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("out put.md");
+        fs::write(&path, "# 2024-01-01\n")?;
+
+        PostWriteHook::new("sed -i.bak s/2024/2025/").run(&path, &ExecPolicy::AllowAll)?;
+        assert_eq!(fs::read_to_string(&path)?, "# 2025-01-01\n");
+
+        let res = PostWriteHook::new("false").run(&path, &ExecPolicy::AllowAll);
+        assert!(matches!(res, Err(MDPError::HookError { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_command() -> Result<()> {
+        let upper = FilterCommand::new("tr a-z A-Z").run("Week 12\nrust", &ExecPolicy::AllowAll)?;
+        assert_eq!(upper, "WEEK 12\nRUST");
+
+        let long_input = "line\n".repeat(100_000);
+        assert_eq!(
+            FilterCommand::new("head -n 1").run(&long_input, &ExecPolicy::AllowAll)?,
+            "line\n"
+        );
+        let res = FilterCommand::new("false").run("", &ExecPolicy::AllowAll);
+        assert!(matches!(res, Err(MDPError::HookError { .. })));
+        let res = FilterCommand::new("cat").run("", &ExecPolicy::DenyAll);
+        assert!(matches!(res, Err(MDPError::ExecNotPermittedError { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exec_policy() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("out.md");
+        fs::write(&path, "# 2024-01-01\n")?;
+        let allowlist = ExecPolicy::Allowlist(vec!["sed".to_string()]);
+
+        PostWriteHook::new("sed -i s/2024/2025/").run(&path, &allowlist)?;
+        assert_eq!(fs::read_to_string(&path)?, "# 2025-01-01\n");
+
+        for (command, policy) in [
+            ("sed -i s/2025/2026/", &ExecPolicy::DenyAll),
+            ("touch", &allowlist),
+            ("sed -i s/2025/2026/; touch", &allowlist),
+            ("sed -i s/2025/$(date +%Y)/", &allowlist),
+        ] {
+            let res = PostWriteHook::new(command).run(&path, policy);
+            assert!(
+                matches!(res, Err(MDPError::ExecNotPermittedError { .. })),
+                "{} was permitted",
+                command
+            );
+        }
+        assert_eq!(fs::read_to_string(&path)?, "# 2025-01-01\n");
+        Ok(())
+    }
+}
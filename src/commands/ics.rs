@@ -0,0 +1,470 @@
+use std::fmt;
+
+use chrono::{Days, Months, NaiveDate, NaiveDateTime, NaiveTime};
+
+use super::TimeZone;
+
+/// An event of an iCalendar file (`.ics`, e.g. exported from a calendar application), shown
+/// by `mdp agenda --ics-import` next to the tasks of the notes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalendarEvent {
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: EventTime,
+    /// The end (exclusive for all-day events), `None` if the event has no duration
+    pub end: Option<EventTime>,
+    pub recurrence: Option<Recurrence>,
+    /// Starts of occurrences of a recurring event which do not take place
+    pub exceptions: Vec<EventTime>,
+}
+
+/// The start or end of an event
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EventTime {
+    /// All-day events only have a date
+    Date(NaiveDate),
+    /// A time as written in the calendar (its `TZID` is not applied)
+    Floating(NaiveDateTime),
+    Utc(NaiveDateTime),
+}
+
+/// A (simplified) `RRULE`: the event repeats every `interval` days, weeks, months or years,
+/// at most `count` times or until a day. Other parts of the rule (e.g. `BYDAY`) are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// An occurrence of an event on a day, with the times in the time zone of the agenda
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Occurrence<'e> {
+    pub event: &'e CalendarEvent,
+    /// `None` for all-day events
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+}
+
+impl EventTime {
+    fn parse(value: &str, is_date: bool) -> Option<Self> {
+        if is_date || value.len() == 8 {
+            return NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .map(Self::Date);
+        }
+        match value.strip_suffix('Z') {
+            Some(utc) => NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+                .ok()
+                .map(Self::Utc),
+            None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+                .ok()
+                .map(Self::Floating),
+        }
+    }
+
+    /// The time in the time zone, `None` for dates
+    fn local(&self, time_zone: &TimeZone) -> Option<NaiveDateTime> {
+        match self {
+            Self::Date(_) => None,
+            Self::Floating(t) => Some(*t),
+            Self::Utc(t) => Some(time_zone.from_utc(*t)),
+        }
+    }
+
+    fn date(&self, time_zone: &TimeZone) -> NaiveDate {
+        match self {
+            Self::Date(d) => *d,
+            t => t.local(time_zone).map_or(NaiveDate::MIN, |t| t.date()),
+        }
+    }
+
+    /// The time shifted by the difference between the days
+    fn shift(&self, days: i64) -> Option<Self> {
+        let shift = |d: NaiveDate| match days >= 0 {
+            true => d.checked_add_days(Days::new(days as u64)),
+            false => d.checked_sub_days(Days::new(days.unsigned_abs())),
+        };
+        match self {
+            Self::Date(d) => shift(*d).map(Self::Date),
+            Self::Floating(t) => shift(t.date()).map(|d| Self::Floating(d.and_time(t.time()))),
+            Self::Utc(t) => shift(t.date()).map(|d| Self::Utc(d.and_time(t.time()))),
+        }
+    }
+}
+
+impl Recurrence {
+    /// Parses the value of an `RRULE` (e.g. `FREQ=WEEKLY;INTERVAL=2;COUNT=10`)
+    fn parse(value: &str) -> Option<Self> {
+        let mut frequency = None;
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+        };
+        for part in value.split(';') {
+            let (name, value) = part.split_once('=')?;
+            match name.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => {
+                    rule.until = EventTime::parse(value, false).map(|t| match t {
+                        EventTime::Date(d) => d,
+                        EventTime::Floating(t) | EventTime::Utc(t) => t.date(),
+                    })
+                }
+                _ => {}
+            }
+        }
+        rule.frequency = frequency?;
+        Some(rule)
+    }
+
+    /// The n-th start date (starting at 0) of the event starting on `first`
+    fn nth(&self, first: NaiveDate, n: u32) -> Option<NaiveDate> {
+        let steps = n.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => first.checked_add_days(Days::new(steps.into())),
+            Frequency::Weekly => first.checked_add_days(Days::new(u64::from(steps) * 7)),
+            Frequency::Monthly => first.checked_add_months(Months::new(steps)),
+            Frequency::Yearly => first.checked_add_months(Months::new(steps.checked_mul(12)?)),
+        }
+    }
+}
+
+impl CalendarEvent {
+    /// The occurrence of the event on the day (the event might start on an earlier day and
+    /// last until the day), `None` if it does not take place on the day
+    pub fn on(&self, day: NaiveDate, time_zone: &TimeZone) -> Option<Occurrence<'_>> {
+        let first = self.start.date(time_zone);
+        let starts = match self.recurrence {
+            None => vec![first],
+            Some(rule) => {
+                let mut starts = vec![];
+                for n in 0.. {
+                    let Some(start) = rule.nth(first, n) else {
+                        break;
+                    };
+                    if start > day
+                        || rule.count.is_some_and(|count| n >= count)
+                        || rule.until.is_some_and(|until| start > until)
+                    {
+                        break;
+                    }
+                    // Events lasting several days might start before the day
+                    if (day - start).num_days() <= self.days() {
+                        starts.push(start);
+                    }
+                }
+                starts
+            }
+        };
+
+        starts
+            .into_iter()
+            .filter(|start| !self.exceptions.iter().any(|e| e.date(time_zone) == *start))
+            .find_map(|start| {
+                let shift = (start - first).num_days();
+                let begin = self.start.shift(shift)?;
+                let end = self.end.and_then(|e| e.shift(shift));
+                self.occurrence(day, begin, end, time_zone)
+            })
+    }
+
+    /// The number of days the event lasts after the day it starts on (at most, a day more
+    /// than written since the days of UTC times depend on the time zone)
+    fn days(&self) -> i64 {
+        let date = |t: EventTime| match t {
+            EventTime::Date(d) => d,
+            EventTime::Floating(t) | EventTime::Utc(t) => t.date(),
+        };
+        let days = self
+            .end
+            .map_or(0, |end| (date(end) - date(self.start)).num_days());
+        days.max(0) + 1
+    }
+
+    fn occurrence(
+        &self,
+        day: NaiveDate,
+        start: EventTime,
+        end: Option<EventTime>,
+        time_zone: &TimeZone,
+    ) -> Option<Occurrence<'_>> {
+        if let EventTime::Date(first) = start {
+            // The end of all-day events is exclusive
+            let last = match end {
+                Some(EventTime::Date(end)) if end > first => end.pred_opt()?,
+                _ => first,
+            };
+            return (first..=last).contains(&day).then_some(Occurrence {
+                event: self,
+                start: None,
+                end: None,
+            });
+        }
+
+        let start = start.local(time_zone)?;
+        let end = end.and_then(|e| e.local(time_zone)).unwrap_or(start);
+        if day < start.date() || end.date() < day {
+            return None;
+        }
+        Some(Occurrence {
+            event: self,
+            start: (start.date() == day).then_some(start.time()),
+            end: (end.date() == day && end != start).then_some(end.time()),
+        })
+    }
+}
+
+impl fmt::Display for Occurrence<'_> {
+    /// The times (`10:00-11:00`, `-11:00` if it started on an earlier day) followed by the
+    /// summary and the location, all-day events only with the summary and the location
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = |t: Option<NaiveTime>| t.map(|t| t.format("%H:%M").to_string());
+        match (time(self.start), time(self.end)) {
+            (Some(start), Some(end)) => write!(f, "{}-{} ", start, end)?,
+            (Some(start), None) => write!(f, "{} ", start)?,
+            (None, Some(end)) => write!(f, "-{} ", end)?,
+            (None, None) => {}
+        }
+        write!(f, "{}", self.event.summary)?;
+        if let Some(location) = &self.event.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the events of an iCalendar file. Events without a (valid) start are skipped, as
+/// well as the properties of components nested in events (e.g. alarms).
+pub fn parse_calendar(content: &str) -> Vec<CalendarEvent> {
+    let mut events = vec![];
+    let mut event: Option<EventBuilder> = None;
+    let mut nested = 0;
+
+    for line in unfold(content) {
+        let Some((name, params, value)) = content_line(&line) else {
+            continue;
+        };
+        match (name.as_str(), value.to_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => event = Some(EventBuilder::default()),
+            ("END", "VEVENT") => {
+                match event.take().and_then(EventBuilder::build) {
+                    Some(e) => events.push(e),
+                    None => log::warn!("Skipped a calendar event without a valid start"),
+                }
+                nested = 0;
+            }
+            ("BEGIN", _) if event.is_some() => nested += 1,
+            ("END", _) if event.is_some() => nested -= 1,
+            _ => {
+                if let (Some(event), 0) = (&mut event, nested) {
+                    event.set(&name, &params, value);
+                }
+            }
+        }
+    }
+    events
+}
+
+#[derive(Default)]
+struct EventBuilder {
+    summary: Option<String>,
+    location: Option<String>,
+    start: Option<EventTime>,
+    end: Option<EventTime>,
+    recurrence: Option<Recurrence>,
+    exceptions: Vec<EventTime>,
+}
+
+impl EventBuilder {
+    fn set(&mut self, name: &str, params: &str, value: String) {
+        let is_date = params.to_uppercase().contains("VALUE=DATE")
+            && !params.to_uppercase().contains("VALUE=DATE-TIME");
+        match name {
+            "SUMMARY" => self.summary = Some(unescape(&value)),
+            "LOCATION" => self.location = Some(unescape(&value)).filter(|l| !l.is_empty()),
+            "DTSTART" => self.start = EventTime::parse(&value, is_date),
+            "DTEND" => self.end = EventTime::parse(&value, is_date),
+            "RRULE" => self.recurrence = Recurrence::parse(&value),
+            "EXDATE" => self.exceptions.extend(
+                value
+                    .split(',')
+                    .filter_map(|v| EventTime::parse(v, is_date)),
+            ),
+            _ => {}
+        }
+    }
+
+    fn build(self) -> Option<CalendarEvent> {
+        Some(CalendarEvent {
+            summary: self.summary.unwrap_or_default(),
+            location: self.location,
+            start: self.start?,
+            end: self.end,
+            recurrence: self.recurrence,
+            exceptions: self.exceptions,
+        })
+    }
+}
+
+/// The lines of the content, lines starting with a space or tab continue the previous one
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits a line like `DTSTART;TZID=Europe/Zurich:20240318T100000` into the (uppercase)
+/// name, the parameters and the value. Colons in quoted parameters are no separators.
+fn content_line(line: &str) -> Option<(String, String, String)> {
+    let mut quoted = false;
+    let separator = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..separator], &line[separator + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((
+        name.trim().to_uppercase(),
+        params.to_string(),
+        value.to_string(),
+    ))
+}
+
+/// Replaces the escaped characters of a text value (`\,`, `\;`, `\\` and `\n`)
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') | Some('N') => text.push('\n'),
+                Some(c) => text.push(c),
+                None => {}
+            },
+            (c, false) => text.push(c),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CALENDAR: &str = "\
+BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+SUMMARY:Standup\r
+DTSTART;TZID=Europe/Zurich:20231009T093000\r
+DTEND;TZID=Europe/Zurich:20231009T094500\r
+RRULE:FREQ=WEEKLY;INTERVAL=1;COUNT=3\r
+EXDATE;TZID=Europe/Zurich:20231016T093000\r
+BEGIN:VALARM\r
+SUMMARY:Reminder\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Conference\\, day\r
+  one and two\r
+LOCATION:Bern\r
+DTSTART;VALUE=DATE:20231010\r
+DTEND;VALUE=DATE:20231012\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Flight\r
+DTSTART:20231010T220000Z\r
+DTEND:20231011T020000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Without start\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn test_parse_calendar() {
+        let events = parse_calendar(CALENDAR);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].summary, "Standup");
+        assert_eq!(
+            events[0].recurrence,
+            Some(Recurrence {
+                frequency: Frequency::Weekly,
+                interval: 1,
+                count: Some(3),
+                until: None,
+            })
+        );
+        assert_eq!(events[1].summary, "Conference, day one and two");
+        assert_eq!(events[1].location.as_deref(), Some("Bern"));
+        assert!(matches!(events[2].start, EventTime::Utc(_)));
+    }
+
+    #[test]
+    fn test_event_occurrences() {
+        let events = parse_calendar(CALENDAR);
+        let utc = "UTC".parse::<TimeZone>().unwrap();
+        let on = |day: u32| {
+            let date = NaiveDate::from_ymd_opt(2023, 10, day).unwrap();
+            events
+                .iter()
+                .filter_map(|e| e.on(date, &utc))
+                .map(|o| o.to_string())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(on(9), vec!["09:30-09:45 Standup"]);
+        assert_eq!(
+            on(10),
+            vec!["Conference, day one and two (Bern)", "22:00 Flight"]
+        );
+        assert_eq!(
+            on(11),
+            vec!["Conference, day one and two (Bern)", "-02:00 Flight"]
+        );
+        assert!(on(12).is_empty());
+        // Excluded
+        assert!(on(16).is_empty());
+        assert_eq!(on(23), vec!["09:30-09:45 Standup"]);
+        // After the third occurrence
+        assert!(on(30).is_empty());
+
+        let offset = "+02:00".parse::<TimeZone>().unwrap();
+        let flight = events[2].on(NaiveDate::from_ymd_opt(2023, 10, 11).unwrap(), &offset);
+        assert_eq!(flight.unwrap().to_string(), "00:00-04:00 Flight");
+    }
+}
@@ -1,86 +1,663 @@
-use std::{fs, path::{PathBuf, Path}};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+    rc::Rc,
+    str::FromStr,
+    time::SystemTime,
+};
 
-use crate::models::MDPError;
+use serde::Deserialize;
+
+use super::{
+    diff::unified_diff,
+    hooks::{shell_command, ExecPolicy, PostWriteHook},
+    theme::Theme,
+};
+use crate::{
+    markdown::parse_front_matter,
+    models::{ConfigError, FrontMatter, MDPError},
+};
 
 pub trait FileReader {
-    fn read(&self, paths: Vec<PathBuf>) -> Result<String, MDPError>;
+    /// Returns the paths of all files found at the given paths (e.g. inside directories)
+    fn find_files(&self, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, MDPError>;
+
+    /// Read a single file
+    fn read_file(&self, path: PathBuf) -> Result<MarkdownFile, MDPError>;
+
+    /// Replace the input lists among the inputs (`@files.txt`) by the paths they contain,
+    /// see [`expand_input_lists`]
+    fn expand_inputs(&self, inputs: Vec<PathBuf>) -> Result<Vec<PathBuf>, MDPError> {
+        expand_input_lists(inputs, Path::new(""))
+    }
+
+    /// How many directory levels are descended into input directories (`None` means no
+    /// limit)
+    fn max_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// Size of a file in bytes (`None` if it can not be determined)
+    fn file_size(&self, path: &Path) -> Option<u64> {
+        fs::metadata(long_path(path)).ok().map(|m| m.len())
+    }
+
+    /// Read all files found at the given paths
+    fn read(&self, paths: Vec<PathBuf>) -> Result<Vec<MarkdownFile>, MDPError> {
+        self.find_files(paths)?
+            .into_iter()
+            .map(|p| self.read_file(p))
+            .collect()
+    }
 }
 
-pub struct MarkdownFileReader {}
+/// Content of a single markdown file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarkdownFile {
+    pub path: PathBuf,
+    pub content: String,
+    pub front_matter: Option<FrontMatter>,
+}
+
+impl MarkdownFile {
+    /// Create a file, parsing its front matter (a file with invalid front matter is read
+    /// as if it had none)
+    pub fn new(path: PathBuf, content: String) -> Self {
+        let front_matter = parse_front_matter(&content).unwrap_or_else(|e| {
+            log::warn!("Ignored the front matter of {}: {}", path.display(), e);
+            None
+        });
+        Self {
+            path,
+            content,
+            front_matter,
+        }
+    }
+
+    /// Returns the content following the front matter and the number of its first line
+    pub fn body(&self) -> (&str, usize) {
+        match &self.front_matter {
+            Some(f) => (f.body(&self.content), f.line_count + 1),
+            None => (&self.content, 1),
+        }
+    }
+
+    /// Returns the tags of the front matter
+    pub fn front_matter_tags(&self) -> &[String] {
+        self.front_matter.as_ref().map_or(&[], |f| &f.tags)
+    }
+}
+
+/// Extensions of the files read from input directories (compared case-insensitively)
+pub const DEFAULT_EXTENSIONS: [&str; 3] = ["md", "markdown", "mdown"];
+
+pub fn default_extensions() -> Vec<String> {
+    DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect()
+}
+
+pub struct MarkdownFileReader {
+    /// How many directory levels are descended into (`None` means no limit)
+    pub max_depth: Option<usize>,
+    /// Files in input directories with one of these extensions are read
+    pub extensions: Vec<String>,
+    /// Follow symlinks within input directories instead of skipping them
+    pub follow_symlinks: bool,
+}
+
+impl Default for MarkdownFileReader {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(1),
+            extensions: default_extensions(),
+            follow_symlinks: false,
+        }
+    }
+}
 
 impl FileReader for MarkdownFileReader {
-    fn read(&self, paths: Vec<PathBuf>) -> Result<String, MDPError> {
-        let mut s = String::new();
+    fn find_files(&self, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, MDPError> {
+        let walker = DirWalker::new(&self.extensions, self.follow_symlinks);
+        walker.find(paths, self.max_depth)
+    }
 
-        for path in all_md_files(paths)? {
-            s = format!("{}\n\n{}", s, fs::read_to_string(path.as_path()).map_err(|e| {
-                MDPError::IOReadError{
-                    path,
-                    details: e.to_string(),
-                }
-            })?);
+    fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    fn read_file(&self, path: PathBuf) -> Result<MarkdownFile, MDPError> {
+        let bytes = fs::read(long_path(&path)).map_err(|e| {
+            MDPError::IOReadError{
+                path: path.clone(),
+                details: e.to_string(),
+            }
+        })?;
+        let content = decode_text(&path, bytes)?;
+        Ok(MarkdownFile::new(path, content))
+    }
+
+}
+
+/// Contents of files kept in memory together with their modification time
+#[derive(Clone, Debug, Default)]
+pub struct FileCache(Rc<RefCell<HashMap<PathBuf, (SystemTime, String)>>>);
+
+/// Reads markdown files like [`MarkdownFileReader`] but keeps their content in a
+/// [`FileCache`], files are only read again if they were modified since.
+pub struct CachedFileReader {
+    pub cache: FileCache,
+    /// Relative paths are resolved against this directory
+    pub base_dir: PathBuf,
+    /// How many directory levels are descended into (`None` means no limit)
+    pub max_depth: Option<usize>,
+    /// Files in input directories with one of these extensions are read
+    pub extensions: Vec<String>,
+    /// Follow symlinks within input directories instead of skipping them
+    pub follow_symlinks: bool,
+}
+
+impl FileReader for CachedFileReader {
+    fn find_files(&self, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, MDPError> {
+        let mut files = vec![];
+        for path in paths {
+            let full_path = self.base_dir.join(&path);
+            let walker = DirWalker::new(&self.extensions, self.follow_symlinks);
+            for file in walker.find(vec![full_path.clone()], self.max_depth)? {
+                // Keep the paths as given, like `MarkdownFileReader` does
+                files.push(match file.strip_prefix(&full_path) {
+                    Ok(rest) if rest.as_os_str().is_empty() => path.clone(),
+                    Ok(rest) => path.join(rest),
+                    Err(_) => file,
+                });
+            }
+        }
+        Ok(files)
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    fn read_file(&self, path: PathBuf) -> Result<MarkdownFile, MDPError> {
+        let full_path = self.base_dir.join(&path);
+        let read_error = |e: std::io::Error| MDPError::IOReadError {
+            path: path.clone(),
+            details: e.to_string(),
+        };
+        let modified = fs::metadata(long_path(&full_path))
+            .and_then(|m| m.modified())
+            .map_err(read_error)?;
+
+        let mut cache = self.cache.0.borrow_mut();
+        if let Some((cached_modified, content)) = cache.get(&full_path) {
+            if *cached_modified == modified {
+                return Ok(MarkdownFile::new(path, content.clone()));
+            }
         }
 
-        Ok(s)
+        let bytes = fs::read(long_path(&full_path)).map_err(read_error)?;
+        let content = decode_text(&path, bytes)?;
+        cache.insert(full_path, (modified, content.clone()));
+        Ok(MarkdownFile::new(path, content))
+    }
+
+    fn file_size(&self, path: &Path) -> Option<u64> {
+        let full_path = self.base_dir.join(path);
+        fs::metadata(long_path(&full_path)).ok().map(|m| m.len())
+    }
+
+    fn expand_inputs(&self, inputs: Vec<PathBuf>) -> Result<Vec<PathBuf>, MDPError> {
+        expand_input_lists(inputs, &self.base_dir)
     }
+}
+
+/// Number of bytes at the start of a file which are inspected to detect binary files
+const SNIFF_LENGTH: usize = 8192;
 
+/// Whether the content looks like a binary file: its start contains a NUL byte or is no
+/// valid UTF-8 (a character cut off at the end of the inspected bytes is fine).
+fn is_binary(bytes: &[u8]) -> bool {
+    let start = &bytes[..bytes.len().min(SNIFF_LENGTH)];
+    start.contains(&0) || std::str::from_utf8(start).is_err_and(|e| e.error_len().is_some())
+}
+
+/// Decode the content of a text file, binary files are rejected
+fn decode_text(path: &Path, bytes: Vec<u8>) -> Result<String, MDPError> {
+    if is_binary(&bytes) {
+        return Err(MDPError::BinaryFileError(path.to_path_buf()));
+    }
+    String::from_utf8(bytes).map_err(|e| MDPError::IOReadError {
+        path: path.to_path_buf(),
+        details: e.to_string(),
+    })
 }
 
-/// Returns all markdown files, i.e. find all markdown files in provided directories.
-fn all_md_files(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, MDPError> {
-    let mut res: Vec<PathBuf> = vec![];
+/// Finds the markdown files in directories.
+///
+/// Every directory and file is only visited once (identified by its inode on Unix), so
+/// files reached by several symlinks are read once and directories linked into themselves
+/// (e.g. by a symlink to a parent directory) don't end in an endless loop.
+struct DirWalker<'e> {
+    extensions: &'e [String],
+    follow_symlinks: bool,
+    visited: HashSet<FileId>,
+    files: Vec<PathBuf>,
+}
+
+impl<'e> DirWalker<'e> {
+    fn new(extensions: &'e [String], follow_symlinks: bool) -> Self {
+        Self {
+            extensions,
+            follow_symlinks,
+            visited: HashSet::new(),
+            files: vec![],
+        }
+    }
+
+    /// Returns all markdown files, i.e. the given files and the markdown files in the given
+    /// directories (descending up to `max_depth` levels, `None` means no limit).
+    fn find(
+        mut self,
+        paths: Vec<PathBuf>,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<PathBuf>, MDPError> {
+        for path in paths {
+            if long_path(&path).is_dir() {
+                self.collect(&path, max_depth)?;
+            } else {
+                self.files.push(path);
+            }
+        }
+        Ok(self.files)
+    }
+
+    fn collect(&mut self, dir: &Path, max_depth: Option<usize>) -> Result<(), MDPError> {
+        if max_depth == Some(0) {
+            return Ok(());
+        }
+
+        let dir_iter_err = |e: std::io::Error| {
+            MDPError::IOError(format!(
+                "error while traversing the directory {}: {}",
+                dir.to_string_lossy(),
+                e
+            ))
+        };
+        if !self.visited.insert(file_id(dir).map_err(dir_iter_err)?) {
+            log::warn!("Skipped {}: the directory was already read", dir.display());
+            return Ok(());
+        }
+
+        // Joined to `dir` instead of using the entry paths, which are long paths on Windows
+        let mut entries = fs::read_dir(long_path(dir))
+            .map_err(dir_iter_err)?
+            .map(|entry| entry.map(|e| dir.join(e.file_name())).map_err(dir_iter_err))
+            .collect::<Result<Vec<PathBuf>, MDPError>>()?;
+        entries.sort();
 
-    for path in paths {
-        if path.is_dir() {
-            let dir_iter_err = MDPError::IOError(
-                format!("error while traversing the directory {}", path.to_string_lossy().into_owned())
-            );
-            for entry in fs::read_dir(path).map_err(|_| dir_iter_err.clone())? {
-                let entry = entry.map_err(|_| dir_iter_err.clone())?;
-                let p = entry.path();
-                if is_md_file(&p) {
-                    res.push(p);
+        for p in entries {
+            let is_symlink = fs::symlink_metadata(long_path(&p)).is_ok_and(|m| m.is_symlink());
+            if is_symlink && !self.follow_symlinks {
+                log::info!("Skipped {}: symlinks are not followed", p.display());
+            } else if long_path(&p).is_dir() {
+                self.collect(&p, max_depth.map(|d| d - 1))?;
+            } else if is_md_file(&p, self.extensions) {
+                // E.g. linked into several directories
+                match file_id(&p) {
+                    Ok(id) if !self.visited.insert(id) => {
+                        log::info!("Skipped {}: the file was already read", p.display())
+                    }
+                    _ => self.files.push(p),
                 }
             }
-        } else {
-            res.push(path);
         }
+
+        Ok(())
     }
+}
+
+#[cfg(unix)]
+type FileId = (u64, u64);
 
-    Ok(res)
+/// The device and inode of a file (following symlinks)
+#[cfg(unix)]
+fn file_id(path: &Path) -> std::io::Result<FileId> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
 }
 
-fn is_md_file<P: AsRef<Path>>(path: &P) -> bool {
+#[cfg(not(unix))]
+type FileId = PathBuf;
+
+/// The canonical path of a file (following symlinks)
+#[cfg(not(unix))]
+fn file_id(path: &Path) -> std::io::Result<FileId> {
+    fs::canonicalize(long_path(path))
+}
+
+fn is_md_file<P: AsRef<Path>>(path: &P, extensions: &[String]) -> bool {
     let path = path.as_ref();
-    path.is_file() && path.extension().map_or(false, |ext| ext == "md")
+    let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    extensions
+        .iter()
+        .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+        && long_path(path).is_file()
 }
 
+/// Prefix of an input naming a file which lists the inputs (`-i @files.txt`)
+pub const INPUT_LIST_PREFIX: char = '@';
+
+/// Replace the inputs naming an input list (`@files.txt`) by the paths listed in the file.
+///
+/// An input list contains one path or glob pattern (`*` and `?` within a name, `**` for any
+/// number of directories) per line, relative to the working directory like the paths given
+/// on the command line. Relative lists and entries are resolved against `base_dir` (the
+/// working directory for an empty path), the listed paths are returned as written. Blank
+/// lines and lines starting with `#` are ignored. Fails with all entries which don't exist
+/// or match no file, so a stale list is noticed right away. Files matched by several
+/// entries are listed once.
+pub fn expand_input_lists(
+    inputs: Vec<PathBuf>,
+    base_dir: &Path,
+) -> Result<Vec<PathBuf>, MDPError> {
+    let mut paths = vec![];
+    for input in inputs {
+        match input
+            .to_str()
+            .and_then(|s| s.strip_prefix(INPUT_LIST_PREFIX))
+        {
+            Some(list) => paths.extend(read_input_list(Path::new(list), base_dir)?),
+            None => paths.push(input),
+        }
+    }
+    Ok(paths)
+}
+
+fn read_input_list(list: &Path, base_dir: &Path) -> Result<Vec<PathBuf>, MDPError> {
+    let content = fs::read_to_string(long_path(&within(base_dir, list))).map_err(|e| {
+        MDPError::IOReadError {
+            path: list.to_path_buf(),
+            details: e.to_string(),
+        }
+    })?;
+
+    let mut paths = vec![];
+    let mut listed = HashSet::new();
+    let mut missing = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let entry = line.trim();
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+        let found = match is_glob(entry) {
+            true => glob(entry, base_dir),
+            false => Vec::from_iter(
+                Some(PathBuf::from(entry)).filter(|p| long_path(&within(base_dir, p)).exists()),
+            ),
+        };
+        if found.is_empty() {
+            missing.push(format!("  {}:{}: {}", list.display(), i + 1, entry));
+        }
+        // A file matched by several entries is read once
+        paths.extend(found.into_iter().filter(|p| listed.insert(p.clone())));
+    }
+
+    match missing.is_empty() {
+        true => Ok(paths),
+        false => Err(MDPError::IOError(format!(
+            "The input list {} contains paths which don't exist or patterns matching no \
+             file:\n{}",
+            list.display(),
+            missing.join("\n")
+        ))),
+    }
+}
+
+/// The path resolved against `base_dir` (the working directory for an empty path)
+fn within(base_dir: &Path, path: &Path) -> PathBuf {
+    match path.as_os_str().is_empty() {
+        true if base_dir.as_os_str().is_empty() => PathBuf::from("."),
+        true => base_dir.to_path_buf(),
+        false => base_dir.join(path),
+    }
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// The existing paths matching a glob pattern relative to `base_dir` (sorted, as written
+/// in the pattern), hidden files and directories only match names starting with `.` like
+/// in the shell
+fn glob(pattern: &str, base_dir: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let name = component.as_os_str().to_string_lossy();
+        paths = match name.as_ref() {
+            "**" => paths
+                .into_iter()
+                .flat_map(|p| {
+                    let mut dirs = vec![p.clone()];
+                    collect_subdirectories(&p, base_dir, &mut dirs);
+                    dirs
+                })
+                .collect(),
+            name if is_glob(name) => {
+                let regex = glob_regex(name);
+                paths
+                    .iter()
+                    .flat_map(|dir| dir_entries(dir, base_dir))
+                    .filter(|p| {
+                        let file_name = p.file_name().unwrap_or_default().to_string_lossy();
+                        regex.is_match(&file_name)
+                            && (name.starts_with('.') || !file_name.starts_with('.'))
+                    })
+                    .collect()
+            }
+            _ => paths.into_iter().map(|p| p.join(component)).collect(),
+        };
+    }
+    paths.retain(|p| long_path(&within(base_dir, p)).exists());
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// A regex matching the names matched by a glob pattern
+fn glob_regex(pattern: &str) -> regex::Regex {
+    let regex: String = pattern
+        .chars()
+        .map(|c| match c {
+            '*' => ".*".to_string(),
+            '?' => ".".to_string(),
+            c => regex::escape(&c.to_string()),
+        })
+        .collect();
+    regex::Regex::new(&format!("^{}$", regex)).expect("an escaped pattern is a valid regex")
+}
+
+/// The paths of the entries of a directory relative to `base_dir` (`base_dir` itself for
+/// an empty path), none if it is no readable directory
+fn dir_entries(dir: &Path, base_dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(long_path(&within(base_dir, dir)))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| dir.join(e.file_name()))
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// All (non hidden) directories below `dir`, symlinks are not followed
+fn collect_subdirectories(dir: &Path, base_dir: &Path, dirs: &mut Vec<PathBuf>) {
+    for p in dir_entries(dir, base_dir) {
+        let hidden = p
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        let full_path = long_path(&within(base_dir, &p));
+        let is_symlink = fs::symlink_metadata(&full_path).is_ok_and(|m| m.is_symlink());
+        if !hidden && !is_symlink && full_path.is_dir() {
+            dirs.push(p.clone());
+            collect_subdirectories(&p, base_dir, dirs);
+        }
+    }
+}
+
+/// The path in the extended-length form (`\\?\C:\...` or `\\?\UNC\server\share\...`),
+/// which is not limited to 260 characters on Windows
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    let s = absolute.as_os_str().to_string_lossy();
+    if s.starts_with(r"\\?\") {
+        absolute
+    } else if let Some(unc) = s.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", unc))
+    } else {
+        PathBuf::from(format!(r"\\?\{}", s))
+    }
+}
+
+/// The path as it is, paths are not limited in length on other platforms
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
 
 pub trait OutputWriter {
     fn write_output(&self, output: &str) -> Result<(), MDPError>;
 }
 
-pub struct StdoutWriter {}
+/// Destination of the output of a command
+pub trait OutputSink {
+    fn write_output(&mut self, output: &str) -> Result<(), MDPError>;
+}
+
+impl OutputSink for Vec<Box<dyn OutputWriter>> {
+    fn write_output(&mut self, output: &str) -> Result<(), MDPError> {
+        for writer in self.iter() {
+            writer.write_output(output)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the output in memory
+impl OutputSink for String {
+    fn write_output(&mut self, output: &str) -> Result<(), MDPError> {
+        self.push_str(output);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct StdoutWriter {
+    /// Colors the output if set
+    pub theme: Option<Theme>,
+}
 
 impl OutputWriter for StdoutWriter {
     fn write_output(&self, output: &str) -> Result<(), MDPError> {
-        println!("{}", output);
+        match &self.theme {
+            Some(theme) => println!("{}", theme.style_markdown(output)),
+            None => println!("{}", output),
+        }
         Ok(())
     }
 }
 
+/// What happens if the output file already exists (and is not appended to)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Keep the existing file and fail
+    Never,
+    /// Replace the existing file
+    #[default]
+    Always,
+    /// Ask on the terminal whether the existing file should be replaced
+    Prompt,
+}
+
+/// What happens if the output file is inside an input directory, where it would be read as
+/// input by later runs (e.g. skewing the counts of `mdp tags`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputInInput {
+    /// Fail before the input is read
+    Error,
+    /// Log a warning and read the output file like any other input file
+    Warn,
+    /// Skip the output file when reading the input
+    #[default]
+    Exclude,
+}
+
+/// Configuration of a [`FileWriter`]
+#[derive(Clone, Debug, Default)]
+pub struct FileWriterConfig {
+    /// Append the output to an existing file instead of replacing it
+    pub append: bool,
+    pub overwrite: OverwritePolicy,
+    /// Run after the file was written (but before it is made read-only)
+    pub hooks: Vec<PostWriteHook>,
+    /// Which of the hooks may be executed
+    pub exec_policy: ExecPolicy,
+    /// Print the changes to the file as unified diff instead of writing it
+    pub dry_run: bool,
+}
+
 pub struct FileWriter {
     pub path: PathBuf,
+    pub config: FileWriterConfig,
 }
 
 impl OutputWriter for FileWriter {
     fn write_output(&self, output: &str) -> Result<(), MDPError> {
-        if self.file_exists() {
-            self.delete_file()?;
+        let write_error = |e: std::io::Error| MDPError::IOWriteError {
+            path: self.path.clone(),
+            details: e.to_string(),
+        };
+
+        if self.config.dry_run {
+            return self.print_diff(output);
         }
 
-        fs::write(self.path.clone(), output)
-            .map_err(|e| MDPError::IOReadError { path: self.path.clone(), details: e.to_string()})?;
+        // Fail before the file is touched if a hook is not permitted to run
+        for hook in &self.config.hooks {
+            self.config.exec_policy.check(&hook.command)?;
+        }
+
+        if self.file_exists() && self.config.append {
+            self.make_writable()?;
+            let mut file = fs::OpenOptions::new()
+                .append(true)
+                .open(long_path(&self.path))
+                .map_err(write_error)?;
+            write!(file, "\n{}", output).map_err(write_error)?;
+        } else {
+            if self.file_exists() {
+                if !self.may_overwrite()? {
+                    log::warn!("Kept the existing file {}", self.path.display());
+                    return Ok(());
+                }
+                self.delete_file()?;
+            }
+            fs::write(long_path(&self.path), output).map_err(write_error)?;
+        }
+
+        for hook in &self.config.hooks {
+            hook.run(&self.path, &self.config.exec_policy)?;
+        }
 
         self.make_read_only()?;
 
@@ -89,6 +666,67 @@ impl OutputWriter for FileWriter {
 }
 
 impl FileWriter {
+    /// Print how the output would change the file (without running the hooks)
+    fn print_diff(&self, output: &str) -> Result<(), MDPError> {
+        let existing = match self.file_exists() {
+            true => {
+                fs::read_to_string(long_path(&self.path)).map_err(|e| MDPError::IOReadError {
+                    path: self.path.clone(),
+                    details: e.to_string(),
+                })?
+            }
+            false => String::new(),
+        };
+        let written = match self.file_exists() && self.config.append {
+            true => format!("{}\n{}", existing, output),
+            false => output.to_string(),
+        };
+        let name = self.path.display().to_string();
+        match unified_diff(&existing, &written, &name, &name) {
+            diff if diff.is_empty() => log::info!("{} would not change", name),
+            diff => println!("{}", diff),
+        }
+        Ok(())
+    }
+
+    /// Whether the existing file may be replaced according to the overwrite policy
+    fn may_overwrite(&self) -> Result<bool, MDPError> {
+        match self.config.overwrite {
+            OverwritePolicy::Always => Ok(true),
+            OverwritePolicy::Never => Err(MDPError::IOWriteError {
+                path: self.path.clone(),
+                details: "the file already exists and must not be overwritten".to_string(),
+            }),
+            OverwritePolicy::Prompt => {
+                eprint!("Overwrite {}? [y/N] ", self.path.display());
+                let mut answer = String::new();
+                std::io::stdin()
+                    .read_line(&mut answer)
+                    .map_err(|e| MDPError::IOError(e.to_string()))?;
+                Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    /// Set file permissions to writable for the owner.
+    fn make_writable(&self) -> Result<(), MDPError> {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(self.path.clone(), fs::Permissions::from_mode(0o644))
+            .map_err(|_| MDPError::IOError("could not make file writable".to_string()))
+    }
+
+    #[cfg(windows)]
+    /// Remove the read-only flag of the file.
+    fn make_writable(&self) -> Result<(), MDPError> {
+        let err = MDPError::IOError("could not remove read only flag from file".to_string());
+        let metadata = fs::metadata(long_path(&self.path)).map_err(|_| err.clone())?;
+        let mut permissions = metadata.permissions();
+        permissions.set_readonly(false);
+        fs::set_permissions(long_path(&self.path), permissions).map_err(|_| err)
+    }
+
     #[cfg(unix)]
     /// Set file permissions to read-only.
     fn make_read_only(&self) -> Result<(), MDPError> {
@@ -108,10 +746,10 @@ impl FileWriter {
     /// Set file permissions to read-only.
     fn make_read_only(&self) -> Result<(), MDPError> {
         let err = MDPError::IOError("could not remove read only flag from file".to_string());
-        let metadata = fs::metadata(self.path.as_path()).map_err(|_| err.clone())?;
+        let metadata = fs::metadata(long_path(&self.path)).map_err(|_| err.clone())?;
         let mut permissions = metadata.permissions();
         permissions.set_readonly(true);
-        fs::set_permissions(self.path.as_path(), permissions).map_err(|_| err)
+        fs::set_permissions(long_path(&self.path), permissions).map_err(|_| err)
     }
 
     #[cfg(unix)]
@@ -122,15 +760,697 @@ impl FileWriter {
     #[cfg(windows)]
     fn delete_file(&self) -> Result<(), MDPError> {
         let err = MDPError::IOError("could not remove read only flag from file".to_string());
-        let metadata = fs::metadata(self.path.as_path()).map_err(|_| err.clone())?;
+        let metadata = fs::metadata(long_path(&self.path)).map_err(|_| err.clone())?;
         let mut permissions = metadata.permissions();
         permissions.set_readonly(false);
-        fs::set_permissions(self.path.as_path(), permissions).map_err(|_| err)?;
+        fs::set_permissions(long_path(&self.path), permissions).map_err(|_| err)?;
 
-        fs::remove_file(&self.path).map_err(|_| MDPError::IOError("could not delete file".to_string()))
+        fs::remove_file(long_path(&self.path)).map_err(|_| MDPError::IOError("could not delete file".to_string()))
     }
 
     fn file_exists(&self) -> bool {
-        Path::new(&self.path).exists()
+        long_path(&self.path).exists()
+    }
+}
+
+/// Writes multiple documents into a directory, each to its own file which is written like
+/// by a [`FileWriter`] with the same configuration.
+pub struct DirectoryWriter {
+    pub dir: PathBuf,
+    pub config: FileWriterConfig,
+}
+
+impl DirectoryWriter {
+    /// Write `output` to the file `name` within the directory (which is created if it does
+    /// not exist yet), returns the path of the file.
+    pub fn write_file(&self, name: &str, output: &str) -> Result<PathBuf, MDPError> {
+        if !self.config.dry_run {
+            fs::create_dir_all(&self.dir).map_err(|e| MDPError::IOWriteError {
+                path: self.dir.clone(),
+                details: e.to_string(),
+            })?;
+        }
+        let writer = FileWriter {
+            path: self.dir.join(name),
+            config: self.config.clone(),
+        };
+        writer.write_output(output)?;
+        Ok(writer.path)
+    }
+}
+
+/// Commands copying their standard input to the clipboard, the first one installed is used
+#[cfg(target_os = "macos")]
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[&["pbcopy"]];
+#[cfg(windows)]
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[&["clip"]];
+#[cfg(not(any(target_os = "macos", windows)))]
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Copies the output to the clipboard (with `pbcopy`, `clip`, `wl-copy`, `xclip` or `xsel`)
+#[derive(Default)]
+pub struct ClipboardWriter {
+    pub exec_policy: ExecPolicy,
+}
+
+impl OutputWriter for ClipboardWriter {
+    fn write_output(&self, output: &str) -> Result<(), MDPError> {
+        let mut not_permitted = None;
+        for args in CLIPBOARD_COMMANDS {
+            let command = args.join(" ");
+            if let Err(e) = self.exec_policy.check(&command) {
+                not_permitted = Some(e);
+                continue;
+            }
+            let error = |details: String| MDPError::HookError {
+                command: command.clone(),
+                details,
+            };
+            let child = process::Command::new(args[0])
+                .args(&args[1..])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(error(e.to_string())),
+            };
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin
+                    .write_all(output.as_bytes())
+                    .map_err(|e| error(e.to_string()))?;
+            }
+            let status = child.wait().map_err(|e| error(e.to_string()))?;
+            return match status.success() {
+                true => Ok(()),
+                false => Err(error(status.to_string())),
+            };
+        }
+        Err(not_permitted.unwrap_or_else(|| {
+            MDPError::IOError(format!(
+                "Could not copy the output, none of the clipboard commands is installed ({})",
+                CLIPBOARD_COMMANDS
+                    .iter()
+                    .map(|args| args[0])
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ))
+        }))
+    }
+}
+
+/// Writes the output to a read-only file and opens it in an editor, waiting until the
+/// editor is closed
+pub struct EditorWriter {
+    /// The editor, the path is passed as last argument. Editors which return immediately
+    /// have to be told to wait until the file is closed (e.g. `code --wait`), as a
+    /// temporary file is removed as soon as the command exits.
+    pub command: String,
+    /// The file the output is written to, a new (read-only) temporary file if `None`
+    pub path: Option<PathBuf>,
+    pub exec_policy: ExecPolicy,
+}
+
+impl Default for EditorWriter {
+    /// The editor of `$VISUAL` or `$EDITOR` opening a new temporary file
+    fn default() -> Self {
+        let fallback = match cfg!(windows) {
+            true => "notepad",
+            false => "vi",
+        };
+        let command = ["VISUAL", "EDITOR"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| fallback.to_string());
+        Self {
+            command,
+            path: None,
+            exec_policy: ExecPolicy::default(),
+        }
+    }
+}
+
+impl OutputWriter for EditorWriter {
+    fn write_output(&self, output: &str) -> Result<(), MDPError> {
+        self.exec_policy.check(&self.command)?;
+        // Kept until the editor exits
+        let mut temp_file = None;
+        let path = match &self.path {
+            Some(path) => {
+                let file = FileWriter {
+                    path: path.clone(),
+                    config: FileWriterConfig {
+                        exec_policy: self.exec_policy.clone(),
+                        ..Default::default()
+                    },
+                };
+                file.write_output(output)?;
+                path.clone()
+            }
+            None => {
+                let write_error = |e: std::io::Error| MDPError::IOWriteError {
+                    path: std::env::temp_dir(),
+                    details: e.to_string(),
+                };
+                let mut file = tempfile::Builder::new()
+                    .prefix("mdp-output-")
+                    .suffix(".md")
+                    .tempfile()
+                    .map_err(write_error)?;
+                file.write_all(output.as_bytes()).map_err(write_error)?;
+                let path = temp_file.insert(file).path().to_path_buf();
+                temp_file_writer(&path).make_read_only()?;
+                path
+            }
+        };
+
+        let error = |details: String| MDPError::HookError {
+            command: self.command.clone(),
+            details,
+        };
+        let status = shell_command(&self.command, &path)
+            .status()
+            .map_err(|e| error(e.to_string()));
+        // Read-only files can not be removed on Windows
+        if let Some(file) = &temp_file {
+            temp_file_writer(file.path()).make_writable()?;
+        }
+        let status = status?;
+        match status.success() {
+            true => Ok(()),
+            false => Err(error(status.to_string())),
+        }
+    }
+}
+
+/// Sets the permissions of the temporary file of the [`EditorWriter`] like the ones of
+/// written files
+fn temp_file_writer(path: &Path) -> FileWriter {
+    FileWriter {
+        path: path.to_path_buf(),
+        config: FileWriterConfig::default(),
+    }
+}
+
+/// A destination of the output (`--output-target`), the name of a writer registered in the
+/// [`WriterRegistry`] followed by its argument, e.g. `stdout` or `file:results.md`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputTarget {
+    pub name: String,
+    pub argument: Option<String>,
+}
+
+impl OutputTarget {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            argument: None,
+        }
+    }
+
+    pub fn file(path: &Path) -> Self {
+        Self {
+            name: "file".to_string(),
+            argument: Some(path.to_string_lossy().to_string()),
+        }
+    }
+}
+
+impl FromStr for OutputTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match s.split_once(':') {
+            Some((name, argument)) => (name, Some(argument.to_string())),
+            None => (s, None),
+        };
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return Err(format!("The output target '{}' has no name", s));
+        }
+        Ok(Self { name, argument })
+    }
+}
+
+/// Creates the writer of a target from its argument
+pub type WriterFactory = Box<dyn Fn(Option<&str>) -> Result<Box<dyn OutputWriter>, MDPError>>;
+
+/// The writers which can be chosen as output targets by their name
+#[derive(Default)]
+pub struct WriterRegistry {
+    factories: BTreeMap<String, WriterFactory>,
+}
+
+impl WriterRegistry {
+    /// The writers `stdout`, `file:<path>`, `clipboard` and `editor`. External commands
+    /// (hooks, the clipboard and the editor) are run according to the policy of the file
+    /// configuration.
+    pub fn with_defaults(theme: Option<Theme>, file_config: FileWriterConfig) -> Self {
+        let mut registry = Self::default();
+        registry.register("stdout", move |argument| {
+            no_argument("stdout", argument)?;
+            Ok(Box::new(StdoutWriter {
+                theme: theme.clone(),
+            }))
+        });
+        let exec_policy = file_config.exec_policy.clone();
+        registry.register("file", move |argument| {
+            let path = argument.filter(|a| !a.trim().is_empty()).ok_or_else(|| {
+                invalid_target("The output target file needs a path (file:<path>)".to_string())
+            })?;
+            Ok(Box::new(FileWriter {
+                path: PathBuf::from(path),
+                config: file_config.clone(),
+            }))
+        });
+        let policy = exec_policy.clone();
+        registry.register("clipboard", move |argument| {
+            no_argument("clipboard", argument)?;
+            Ok(Box::new(ClipboardWriter {
+                exec_policy: policy.clone(),
+            }))
+        });
+        registry.register("editor", move |argument| {
+            no_argument("editor", argument)?;
+            Ok(Box::new(EditorWriter {
+                exec_policy: exec_policy.clone(),
+                ..Default::default()
+            }))
+        });
+        registry
+    }
+
+    /// Makes the writer available as target `name`, replacing a writer of the same name
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(Option<&str>) -> Result<Box<dyn OutputWriter>, MDPError> + 'static,
+    {
+        self.factories
+            .insert(name.to_lowercase(), Box::new(factory));
+    }
+
+    /// The writers of the targets (in the given order)
+    pub fn writers(
+        &self,
+        targets: &[OutputTarget],
+    ) -> Result<Vec<Box<dyn OutputWriter>>, MDPError> {
+        targets
+            .iter()
+            .map(|target| match self.factories.get(&target.name) {
+                Some(factory) => factory(target.argument.as_deref()),
+                None => Err(invalid_target(format!(
+                    "Unknown output target '{}', choose one of {}",
+                    target.name,
+                    self.factories
+                        .keys()
+                        .map(String::as_str)
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ))),
+            })
+            .collect()
+    }
+}
+
+fn no_argument(name: &str, argument: Option<&str>) -> Result<(), MDPError> {
+    match argument {
+        Some(argument) => Err(invalid_target(format!(
+            "The output target {} has no argument, remove ':{}'",
+            name, argument
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn invalid_target(message: String) -> MDPError {
+    MDPError::ConfigError(ConfigError::InvalidOptionsError(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_all_md_files_max_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        for p in [
+            dir.path().join("top.md"),
+            dir.path().join("a").join("mid.md"),
+            nested.join("deep.md"),
+            nested.join("ignored.txt"),
+        ] {
+            fs::write(p, "# 2023-01-01").unwrap();
+        }
+
+        let names = |max_depth| {
+            DirWalker::new(&default_extensions(), false)
+                .find(vec![dir.path().to_path_buf()], max_depth)
+                .unwrap()
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(names(Some(1)), vec!["top.md"]);
+        assert_eq!(names(Some(2)), vec!["mid.md", "top.md"]);
+        assert_eq!(names(None), vec!["deep.md", "mid.md", "top.md"]);
+    }
+
+    #[test]
+    fn test_all_md_files_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.md", "b.MD", "c.markdown", "d.Mdown", "e.txt", "f"] {
+            fs::write(dir.path().join(name), "# 2023-01-01").unwrap();
+        }
+
+        let names = |extensions: &[String]| {
+            DirWalker::new(extensions, false)
+                .find(vec![dir.path().to_path_buf()], None)
+                .unwrap()
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            names(&default_extensions()),
+            vec!["a.md", "b.MD", "c.markdown", "d.Mdown"]
+        );
+        assert_eq!(
+            names(&[".txt".to_string(), "md".to_string()]),
+            vec!["a.md", "b.MD", "e.txt"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_all_md_files_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a");
+        let shared = dir.path().join("shared");
+        fs::create_dir(&nested).unwrap();
+        fs::create_dir(&shared).unwrap();
+        fs::write(nested.join("note.md"), "# 2023-01-01").unwrap();
+        fs::write(shared.join("shared.md"), "# 2023-01-01").unwrap();
+        std::os::unix::fs::symlink(dir.path(), nested.join("loop")).unwrap();
+        std::os::unix::fs::symlink(&shared, nested.join("linked")).unwrap();
+        std::os::unix::fs::symlink(shared.join("shared.md"), nested.join("link.md")).unwrap();
+
+        let files = |follow_symlinks| {
+            DirWalker::new(&default_extensions(), follow_symlinks)
+                .find(vec![nested.clone()], None)
+                .unwrap()
+        };
+        assert_eq!(files(false), vec![nested.join("note.md")]);
+        // `shared.md` only once (also reachable via `linked` and `loop`), without looping
+        assert_eq!(
+            files(true),
+            vec![nested.join("link.md"), nested.join("note.md")]
+        );
+    }
+
+    #[test]
+    fn test_expand_input_lists() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("2024").join("06");
+        fs::create_dir_all(&nested).unwrap();
+        for p in [
+            dir.path().join("notes.md"),
+            dir.path().join("todo.md"),
+            dir.path().join(".draft.md"),
+            nested.join("01.md"),
+            nested.join("02.md"),
+        ] {
+            fs::write(p, "# 2024-06-01").unwrap();
+        }
+        let list = dir.path().join("files.txt");
+        let input_list = PathBuf::from(format!("@{}", list.display()));
+        let base = dir.path().display();
+        fs::write(
+            &list,
+            format!(
+                "# Selected notes\n{0}/notes.md\n\n{0}/**/0?.md\r\n{0}/*.md\n",
+                base
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            expand_input_lists(vec![nested.clone(), input_list.clone()], Path::new("")).unwrap(),
+            vec![
+                nested.clone(),
+                dir.path().join("notes.md"),
+                nested.join("01.md"),
+                nested.join("02.md"),
+                dir.path().join("todo.md"),
+            ]
+        );
+
+        fs::write(
+            &list,
+            format!("{0}/notes.md\n{0}/missing.md\n{0}/*.txt.md\n", base),
+        )
+        .unwrap();
+        assert_eq!(
+            expand_input_lists(vec![input_list], Path::new("")),
+            Err(MDPError::IOError(format!(
+                "The input list {0} contains paths which don't exist or patterns matching no \
+                 file:\n  {0}:2: {1}/missing.md\n  {0}:3: {1}/*.txt.md",
+                list.display(),
+                base
+            )))
+        );
+        assert!(matches!(
+            expand_input_lists(vec![PathBuf::from("@missing.txt")], Path::new("")),
+            Err(MDPError::IOReadError { .. })
+        ));
+
+        // A relative list and its entries are resolved against the base directory
+        fs::write(&list, "notes.md
+**/0?.md
+").unwrap();
+        assert_eq!(
+            expand_input_lists(vec![PathBuf::from("@files.txt")], dir.path()).unwrap(),
+            vec![
+                PathBuf::from("notes.md"),
+                PathBuf::from("2024/06/01.md"),
+                PathBuf::from("2024/06/02.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binary_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("text.md");
+        let image = dir.path().join("image.md");
+        fs::write(&text, "# 2023-01-01\n\nÄpfel 🍎").unwrap();
+        fs::write(&image, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+
+        let reader = MarkdownFileReader::default();
+        assert!(reader.read_file(text).is_ok());
+        assert_eq!(
+            reader.read_file(image.clone()),
+            Err(MDPError::BinaryFileError(image))
+        );
+
+        // A multi-byte character cut off at the end of the inspected bytes is no sign of a
+        // binary file
+        let mut bytes = "a".repeat(SNIFF_LENGTH - 1).into_bytes();
+        bytes.extend("ä".as_bytes());
+        assert!(!is_binary(&bytes));
+        assert!(is_binary(b"\xff\xfeLatin-1"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_long_path() {
+        assert_eq!(
+            long_path(Path::new(r"C:\notes\diary.md")),
+            PathBuf::from(r"\\?\C:\notes\diary.md")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\server\share\diary.md")),
+            PathBuf::from(r"\\?\UNC\server\share\diary.md")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\?\C:\diary.md")),
+            PathBuf::from(r"\\?\C:\diary.md")
+        );
+    }
+
+    #[test]
+    fn test_cached_file_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("diary.md");
+        fs::write(&path, "# 2023-01-01").unwrap();
+        let reader = CachedFileReader {
+            cache: FileCache::default(),
+            base_dir: dir.path().to_path_buf(),
+            max_depth: Some(1),
+            extensions: default_extensions(),
+            follow_symlinks: false,
+        };
+
+        let files = reader.read(vec![PathBuf::from(".")]).unwrap();
+        assert_eq!(
+            files,
+            vec![MarkdownFile::new(
+                PathBuf::from("./diary.md"),
+                "# 2023-01-01".to_string()
+            )]
+        );
+
+        // Changing the cached content shows that the file is not read again
+        let cached = reader.cache.0.borrow().get(&path).unwrap().0;
+        reader.cache.0.borrow_mut().insert(path.clone(), (cached, "cached".to_string()));
+        assert_eq!(reader.read_file("diary.md".into()).unwrap().content, "cached");
+
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(cached + std::time::Duration::from_secs(1)).unwrap();
+        assert_eq!(reader.read_file("diary.md".into()).unwrap().content, "# 2023-01-01");
+    }
+
+    #[test]
+    fn test_file_writer_append_and_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("search.md");
+        let writer = |append, overwrite| FileWriter {
+            path: path.clone(),
+            config: FileWriterConfig {
+                append,
+                overwrite,
+                hooks: vec![],
+                exec_policy: ExecPolicy::AllowAll,
+                dry_run: false,
+            },
+        };
+
+        writer(false, OverwritePolicy::Never).write_output("first").unwrap();
+        writer(true, OverwritePolicy::Never).write_output("second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond");
+
+        assert!(writer(false, OverwritePolicy::Never)
+            .write_output("third")
+            .is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond");
+
+        writer(false, OverwritePolicy::Always).write_output("third").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "third");
+        assert!(fs::metadata(&path).unwrap().permissions().readonly());
+    }
+
+    #[test]
+    fn test_file_writer_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FileWriterConfig {
+            dry_run: true,
+            ..Default::default()
+        };
+        let writer = FileWriter {
+            path: dir.path().join("search.md"),
+            config: config.clone(),
+        };
+
+        writer.write_output("first").unwrap();
+        assert!(!writer.path.exists());
+        fs::write(&writer.path, "first").unwrap();
+        writer.write_output("second").unwrap();
+        assert_eq!(fs::read_to_string(&writer.path).unwrap(), "first");
+
+        let writer = DirectoryWriter {
+            dir: dir.path().join("results"),
+            config,
+        };
+        writer.write_file("2023-10-10.md", "# 2023-10-10").unwrap();
+        assert!(!writer.dir.exists());
+    }
+
+    #[test]
+    fn test_directory_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = DirectoryWriter {
+            dir: dir.path().join("results"),
+            config: FileWriterConfig::default(),
+        };
+
+        let path = writer.write_file("2023-10-10.md", "# 2023-10-10").unwrap();
+        assert_eq!(path, dir.path().join("results").join("2023-10-10.md"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# 2023-10-10");
+    }
+
+    #[test]
+    fn test_writer_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.md");
+        struct NullWriter;
+        impl OutputWriter for NullWriter {
+            fn write_output(&self, _output: &str) -> Result<(), MDPError> {
+                Ok(())
+            }
+        }
+        let mut registry = WriterRegistry::with_defaults(None, FileWriterConfig::default());
+        registry.register("null", |_| Ok(Box::new(NullWriter)));
+
+        let targets = ["stdout", &format!("FILE:{}", path.display()), "null"]
+            .iter()
+            .map(|t| t.parse())
+            .collect::<Result<Vec<OutputTarget>, String>>()
+            .unwrap();
+        assert_eq!(targets[1], OutputTarget::file(&path));
+        let mut writers = registry.writers(&targets).unwrap();
+        assert_eq!(writers.len(), 3);
+        writers.write_output("# 2023-10-10").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# 2023-10-10");
+
+        let error = |target: &str| {
+            registry
+                .writers(&[target.parse().unwrap()])
+                .err()
+                .map(|e| e.code())
+        };
+        assert_eq!(error("printer"), Some("MDP105"));
+        assert_eq!(error("file"), Some("MDP105"));
+        assert_eq!(error("stdout:x"), Some("MDP105"));
+        assert!(":x".parse::<OutputTarget>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_editor_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = |command: &str| EditorWriter {
+            command: command.to_string(),
+            path: Some(dir.path().join("mdp-output.md")),
+            exec_policy: ExecPolicy::AllowAll,
+        };
+
+        writer("test -r").write_output("first").unwrap();
+        writer("test -r").write_output("second").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("mdp-output.md")).unwrap(),
+            "second"
+        );
+        assert!(writer("false").write_output("third").is_err());
+
+        // The temporary file exists (read-only, with the output) while the editor runs
+        let writer = EditorWriter {
+            command: "f() { grep -q fourth \"$1\" && test -z \"$(find \"$1\" -perm -u+w)\"; }; f"
+                .to_string(),
+            path: None,
+            exec_policy: ExecPolicy::AllowAll,
+        };
+        writer.write_output("fourth").unwrap();
+        assert!(writer.write_output("fifth").is_err());
     }
 }
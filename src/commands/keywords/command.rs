@@ -0,0 +1,109 @@
+use anyhow::Result;
+
+use super::config::KeywordsConfig;
+use crate::{
+    commands::{
+        analysis::Corpus,
+        io::OutputSink,
+        query::{DateRange, Query},
+        tags::normalize::TagNormalizer,
+        Command, ParseContext, Report,
+    },
+    models::{Section, Token},
+};
+
+/// Lists the terms most characteristic for the sections of a period or with a tag, i.e.
+/// the terms occurring often in them but rarely in the other sections (TF-IDF of the
+/// stopword-filtered words), to summarize what the period was about.
+pub struct KeywordsCommand {
+    pub config: KeywordsConfig,
+}
+
+impl Command for KeywordsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let dates = DateRange::relative(config.from, config.until, ctx.clock.today());
+
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+        let texts = |query: Query| {
+            query
+                .sections(&sections, &ctx.tags)
+                .iter()
+                .map(|m| section_text(&m.section, &ctx.tags))
+                .collect::<Vec<String>>()
+        };
+        let selected = texts(Query::tagged(&config.tags, dates));
+        if selected.is_empty() {
+            log::warn!("No sections match!");
+            return Ok(Report::default());
+        }
+
+        let corpus = Corpus::new(texts(Query::default()).iter().map(String::as_str));
+        let keywords = corpus.keywords(selected.iter().map(String::as_str), config.limit);
+        let lines = keywords
+            .iter()
+            .map(|k| format!("- {} ({})", k.term, k.count))
+            .collect::<Vec<String>>();
+        out.write_output(&lines.join("\n"))?;
+
+        Ok(Report {
+            result_count: keywords.len(),
+        })
+    }
+}
+
+/// The text of the section (without its subsections) without the tags, which are no
+/// keywords
+fn section_text(section: &Section, tags: &TagNormalizer) -> String {
+    std::iter::once(&section.title)
+        .chain(section.content.iter())
+        .filter(|t| tags.tag(t).is_none())
+        .map(Token::to_plain_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder, RelativeDate};
+
+    #[test]
+    fn test_keywords() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2024-05-01\n\n## Garden\n\n@home\nPlanted tomatoes and weekly watering.\n\n\
+             # 2024-05-20\n\n## Meetup\n\n@work\nTalk about async Rust, then weekly planning.\n\n\
+             ## Review\n\n@work\nRust release notes.\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 5, 25).unwrap()));
+        let run = |config: KeywordsConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = KeywordsCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let recent = KeywordsConfig::builder()
+            .input(&path)
+            .from(RelativeDate::Days(-7))
+            .limit(3)
+            .build();
+        assert_eq!(
+            run(recent)?,
+            ("- rust (2)\n- async (1)\n- meetup (1)".to_string(), 3)
+        );
+        let (output, _) = run(KeywordsConfig::builder().input(&path).tag("home").build())?;
+        assert!(output.starts_with("- garden (1)\n- planted (1)"));
+        assert!(!output.contains("rust"));
+        Ok(())
+    }
+}
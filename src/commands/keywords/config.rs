@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use crate::commands::{InputBuilder, RelativeDate};
+
+/// How many keywords are shown by default
+pub const DEFAULT_KEYWORD_LIMIT: usize = 20;
+
+#[derive(Clone, Debug)]
+pub struct KeywordsConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Only the sections of these days are analyzed (`None` for an open end)
+    pub from: Option<RelativeDate>,
+    pub until: Option<RelativeDate>,
+    /// Only the sections with one of these tags are analyzed (all sections if empty)
+    pub tags: Vec<String>,
+    pub limit: usize,
+}
+
+impl KeywordsConfig {
+    pub fn builder() -> KeywordsConfigBuilder {
+        KeywordsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`KeywordsConfig`]
+#[derive(Clone, Debug)]
+pub struct KeywordsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    from: Option<RelativeDate>,
+    until: Option<RelativeDate>,
+    tags: Vec<String>,
+    limit: usize,
+}
+
+impl Default for KeywordsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            from: None,
+            until: None,
+            tags: vec![],
+            limit: DEFAULT_KEYWORD_LIMIT,
+        }
+    }
+}
+
+impl InputBuilder for KeywordsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl KeywordsConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Analyze the sections of this day and later
+    pub fn from(mut self, date: RelativeDate) -> Self {
+        self.from = Some(date);
+        self
+    }
+
+    /// Analyze the sections of this day and earlier
+    pub fn until(mut self, date: RelativeDate) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    /// Analyze the sections with the tag (given without `@`, repeat for several tags)
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Show at most `limit` keywords
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn build(self) -> KeywordsConfig {
+        KeywordsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            from: self.from,
+            until: self.until,
+            tags: self.tags,
+            limit: self.limit,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use serde::Deserialize;
+
+use crate::models::MDPError;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Guardrails against unexpectedly large input (e.g. `-i /` or a huge exported log file),
+/// configured in the `[limits]` section of the settings and disabled with `--force`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Files larger than this (in bytes) are skipped
+    pub max_file_size: Option<u64>,
+    /// Maximum number of input files
+    pub max_files: Option<usize>,
+    /// Maximum number of tokens of all input files together
+    pub max_tokens: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_file_size: Some(10 * MIB),
+            max_files: Some(10_000),
+            max_tokens: Some(10_000_000),
+        }
+    }
+}
+
+impl Limits {
+    /// No limits at all
+    pub fn none() -> Self {
+        Self {
+            max_file_size: None,
+            max_files: None,
+            max_tokens: None,
+        }
+    }
+
+    pub fn check_file_size(&self, size: u64) -> Result<(), MDPError> {
+        match self.max_file_size {
+            Some(max) if size > max => Err(MDPError::LimitExceededError(format!(
+                "The file is larger than {} bytes ({} bytes)",
+                max, size
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_file_count(&self, count: usize) -> Result<(), MDPError> {
+        match self.max_files {
+            Some(max) if count > max => Err(MDPError::LimitExceededError(format!(
+                "Found {} input files, more than the maximum of {}",
+                count, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_token_count(&self, count: usize) -> Result<(), MDPError> {
+        match self.max_tokens {
+            Some(max) if count > max => Err(MDPError::LimitExceededError(format!(
+                "The input consists of {} tokens, more than the maximum of {}",
+                count, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use super::{config::LinksConfig, heading_anchor, heading_anchors, LinkTarget};
+use crate::{
+    commands::{
+        io::{MarkdownFile, OutputSink},
+        Command, ParseContext, Report,
+    },
+    models::{Origin, Token},
+};
+
+/// Lists the links between the notes (wiki links `[[Note#Heading]]`, embeds and links to
+/// the anchor of a heading `[Budget](#budget)`) with their location, with `check` only the
+/// ones which can not be resolved within the input files together with the reason. The
+/// number of listed links is the result count (so the CLI fails with `check` if any link
+/// is broken).
+pub struct LinksCommand {
+    pub config: LinksConfig,
+}
+
+impl Command for LinksCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let tokens = ctx.tokens(&files)?;
+        let notes = NoteIndex::new(&files, &tokens);
+
+        let mut lines = vec![];
+        for (token, origin) in &tokens {
+            for link in internal_links(token) {
+                let markup = link.to_markdown_string();
+                match (config.check, notes.broken(link, origin.file.as_deref())) {
+                    (false, _) => lines.push(format!("{}: {}", origin, markup)),
+                    (true, Some(reason)) => {
+                        lines.push(format!("{}: {}: {}", origin, markup, reason))
+                    }
+                    (true, None) => {}
+                }
+            }
+        }
+
+        if lines.is_empty() {
+            match config.check {
+                true => log::info!("No broken links found"),
+                false => log::warn!("No links found!"),
+            }
+            return Ok(Report::default());
+        }
+        out.write_output(&lines.join("\n"))?;
+
+        Ok(Report {
+            result_count: lines.len(),
+        })
+    }
+}
+
+/// The headings of a file
+struct Headings {
+    /// Titles (as Markdown, in lowercase) the headings of wiki links are compared with
+    titles: Vec<String>,
+    anchors: Vec<String>,
+}
+
+/// The headings of all input files, by file and by note name
+struct NoteIndex {
+    files: HashMap<Option<PathBuf>, Headings>,
+    /// Files by their name without extension (in lowercase)
+    notes: HashMap<String, Vec<Option<PathBuf>>>,
+}
+
+impl NoteIndex {
+    fn new(files: &[MarkdownFile], tokens: &[(Token, Origin)]) -> Self {
+        let mut titles: HashMap<Option<PathBuf>, Vec<(String, String)>> = HashMap::new();
+        for file in files {
+            titles.entry(Some(file.path.clone())).or_default();
+        }
+        for (token, origin) in tokens {
+            if !token.is_heading() {
+                continue;
+            }
+            let markdown = token
+                .children()
+                .iter()
+                .map(|t| t.to_markdown_string())
+                .collect::<String>();
+            titles
+                .entry(origin.file.clone())
+                .or_default()
+                .push((markdown.trim().to_lowercase(), token.to_plain_string()));
+        }
+
+        let mut notes: HashMap<String, Vec<Option<PathBuf>>> = HashMap::new();
+        for file in titles.keys().flatten() {
+            if let Some(stem) = file.file_stem() {
+                let note = stem.to_string_lossy().to_lowercase();
+                notes.entry(note).or_default().push(Some(file.clone()));
+            }
+        }
+        let files = titles
+            .into_iter()
+            .map(|(file, titles)| {
+                let anchors = heading_anchors(titles.iter().map(|(_, plain)| plain.as_str()));
+                let titles = titles.into_iter().map(|(title, _)| title).collect();
+                (file, Headings { titles, anchors })
+            })
+            .collect();
+        Self { files, notes }
+    }
+
+    /// Why the link (contained in the file `from`) can not be resolved, `None` if it can
+    fn broken(&self, link: &Token, from: Option<&Path>) -> Option<String> {
+        let from = from.map(Path::to_path_buf);
+        match link {
+            Token::Link(target) | Token::Embed(target) => {
+                let target = LinkTarget::parse(target);
+                let files = match &target.note {
+                    Some(note) => match self.notes.get(&note.to_lowercase()) {
+                        Some(files) => files.clone(),
+                        None => return Some(format!("note '{}' not found", note)),
+                    },
+                    None => vec![from],
+                };
+                let heading = target.heading?;
+                let title = heading.to_lowercase();
+                let found = files
+                    .iter()
+                    .filter_map(|f| self.files.get(f))
+                    .any(|h| h.titles.contains(&title));
+                match found {
+                    true => None,
+                    false => Some(format!("heading '{}' not found", heading)),
+                }
+            }
+            Token::MarkdownInternalLink { link, .. } => {
+                let anchor = link.trim_start_matches('#');
+                let found = self
+                    .files
+                    .get(&from)
+                    .is_some_and(|h| h.anchors.contains(&heading_anchor(anchor)));
+                match found {
+                    true => None,
+                    false => Some(format!("anchor '#{}' not found", anchor)),
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The wiki links, embeds and anchor links within the token (and its children)
+fn internal_links<'t, 'a>(token: &'t Token<'a>) -> Vec<&'t Token<'a>> {
+    match token {
+        Token::Link(_) | Token::Embed(_) | Token::MarkdownInternalLink { .. } => vec![token],
+        t => t.children().iter().flat_map(internal_links).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_links() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let diary = dir.path().join("diary.md");
+        fs::write(
+            &diary,
+            "# 2024-06-01\n\n## Meeting\nDiscussed [[Project X]] and [[Project Y]]\n\
+             - Budget: [[project x#budget]], [[Project X#Costs]]\n\
+             - see [above](#meeting), [below](#todo) and [[#Meeting]]\n\n\
+             ## Notes!\n![[Project X#Budget|Budget]] [[#Notes]]\n",
+        )?;
+        fs::write(
+            dir.path().join("Project X.md"),
+            "# 2024-05-01\n\n## Budget\nSee [[diary]] and [the budget](#budget)\n",
+        )?;
+
+        let run = |check: bool| -> Result<(String, usize)> {
+            let mut out = String::new();
+            let config = LinksConfig::builder()
+                .input(dir.path())
+                .check(check)
+                .build();
+            let report = LinksCommand { config }.run(&ParseContext::default(), &mut out)?;
+            Ok((out, report.result_count))
+        };
+
+        let (all, count) = run(false)?;
+        assert_eq!(count, 11);
+        assert!(all.contains(&format!("{}:4:11: [[Project X]]", diary.display())));
+
+        let (broken, count) = run(true)?;
+        assert_eq!(
+            broken,
+            format!(
+                "{0}:4:29: [[Project Y]]: note 'Project Y' not found\n\
+                 {0}:5:1: [[Project X#Costs]]: heading 'Costs' not found\n\
+                 {0}:6:1: [below](#todo): anchor '#todo' not found\n\
+                 {0}:9:30: [[#Notes]]: heading 'Notes' not found",
+                diary.display()
+            )
+        );
+        assert_eq!(count, 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct LinksConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Only list the links whose note, heading or anchor does not exist
+    pub check: bool,
+}
+
+impl LinksConfig {
+    pub fn builder() -> LinksConfigBuilder {
+        LinksConfigBuilder::default()
+    }
+}
+
+/// Builder for [`LinksConfig`]
+#[derive(Clone, Debug)]
+pub struct LinksConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    check: bool,
+}
+
+impl Default for LinksConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            check: false,
+        }
+    }
+}
+
+impl InputBuilder for LinksConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl LinksConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    pub fn check(mut self, check: bool) -> Self {
+        self.check = check;
+        self
+    }
+
+    pub fn build(self) -> LinksConfig {
+        LinksConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            check: self.check,
+        }
+    }
+}
@@ -0,0 +1,281 @@
+pub mod command;
+pub mod config;
+
+use std::{collections::HashMap, path::Path};
+
+use crate::models::{Origin, Section, Token};
+
+/// The target of a wiki link (`[[Note#Heading]]`) or an embed (`![[Note#Heading]]`).
+///
+/// `Note` is the name of a file without its extension, `Heading` the title of a section
+/// within it. Both parts are optional, an alias (`[[Note|Alias]]`) is ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkTarget {
+    pub note: Option<String>,
+    pub heading: Option<String>,
+}
+
+impl LinkTarget {
+    pub fn parse(link: &str) -> Self {
+        let link = link.split('|').next().unwrap_or_default();
+        let (note, heading) = match link.split_once('#') {
+            Some((note, heading)) => (note, Some(heading)),
+            None => (link, None),
+        };
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+
+        Self {
+            note: non_empty(note),
+            heading: heading.and_then(non_empty),
+        }
+    }
+}
+
+/// Resolves links to the sections of the parsed files
+pub struct LinkResolver<'s, 'a> {
+    sections: &'s [Section<'a>],
+}
+
+impl<'s, 'a> LinkResolver<'s, 'a> {
+    pub fn new(sections: &'s [Section<'a>]) -> Self {
+        Self { sections }
+    }
+
+    /// The sections a link points to: the first section with the given heading or all top
+    /// level sections of the note if the link has no heading.
+    ///
+    /// Links without a note point into `from`, the file containing the link. Notes and
+    /// headings are compared case insensitively.
+    pub fn resolve(&self, target: &LinkTarget, from: Option<&Path>) -> Vec<&'s Section<'a>> {
+        let in_note = self.sections.iter().filter(|s| match &target.note {
+            Some(note) => s
+                .origin
+                .file
+                .as_deref()
+                .and_then(Path::file_stem)
+                .is_some_and(|stem| stem.to_string_lossy().to_lowercase() == note.to_lowercase()),
+            None => s.origin.file.as_deref() == from,
+        });
+
+        match &target.heading {
+            Some(heading) => in_note
+                .filter_map(|s| find_heading(s, &heading.to_lowercase()))
+                .take(1)
+                .collect(),
+            None => in_note.collect(),
+        }
+    }
+}
+
+/// A wiki link (`[[Note#Heading]]`) within a section
+#[derive(Clone, Debug)]
+pub struct SectionLink<'s, 'a> {
+    /// The innermost section containing the link
+    pub section: &'s Section<'a>,
+    /// Titles of the sections from the top level section down to `section`
+    pub path: Vec<String>,
+    pub target: LinkTarget,
+    /// The top level token containing the link (e.g. a list item or the section title)
+    pub token: &'s Token<'a>,
+    pub origin: Origin,
+}
+
+impl<'s, 'a> SectionLink<'s, 'a> {
+    /// The name of the note containing the link
+    pub fn source_note(&self) -> Option<String> {
+        let file = self.origin.file.as_deref()?;
+        Some(file.file_stem()?.to_string_lossy().to_string())
+    }
+
+    /// The note the link points to, links without a note point into the note containing them
+    pub fn target_note(&self) -> Option<String> {
+        self.target.note.clone().or_else(|| self.source_note())
+    }
+}
+
+/// Index of the wiki links of all sections, e.g. to find the backlinks of a note
+pub struct LinkIndex<'s, 'a> {
+    pub links: Vec<SectionLink<'s, 'a>>,
+}
+
+impl<'s, 'a> LinkIndex<'s, 'a> {
+    pub fn new(sections: &'s [Section<'a>]) -> Self {
+        let mut links = vec![];
+        for section in sections {
+            index_section(section, &mut vec![], &mut links);
+        }
+        Self { links }
+    }
+
+    /// The links pointing to the note of `target` (and to its heading if given). Notes and
+    /// headings are compared case insensitively.
+    pub fn backlinks(&self, target: &LinkTarget) -> Vec<&SectionLink<'s, 'a>> {
+        let equal = |a: Option<&str>, b: Option<&str>| match (a, b) {
+            (Some(a), Some(b)) => a.to_lowercase() == b.to_lowercase(),
+            _ => false,
+        };
+
+        self.links
+            .iter()
+            .filter(|l| equal(l.target_note().as_deref(), target.note.as_deref()))
+            .filter(|l| {
+                target.heading.is_none()
+                    || equal(l.target.heading.as_deref(), target.heading.as_deref())
+            })
+            .collect()
+    }
+}
+
+fn index_section<'s, 'a>(
+    section: &'s Section<'a>,
+    path: &mut Vec<String>,
+    links: &mut Vec<SectionLink<'s, 'a>>,
+) {
+    path.push(section.title_string());
+
+    let title = (&section.title, section.origin.clone());
+    for (token, origin) in [title].into_iter().chain(section.content_with_origins()) {
+        for link in link_targets(token) {
+            links.push(SectionLink {
+                section,
+                path: path.clone(),
+                target: LinkTarget::parse(link),
+                token,
+                origin: origin.clone(),
+            });
+        }
+    }
+    for subsection in &section.subsections {
+        index_section(subsection, path, links);
+    }
+
+    path.pop();
+}
+
+/// The targets of the wiki links within the token (and its children)
+fn link_targets<'t>(token: &'t Token) -> Vec<&'t str> {
+    match token {
+        Token::Link(link) => vec![link],
+        t => t.children().iter().flat_map(link_targets).collect(),
+    }
+}
+
+/// The first section (depth first) titled `heading` (in lowercase)
+fn find_heading<'s, 'a>(section: &'s Section<'a>, heading: &str) -> Option<&'s Section<'a>> {
+    if section.title_string().to_lowercase() == heading {
+        return Some(section);
+    }
+    section
+        .subsections
+        .iter()
+        .find_map(|s| find_heading(s, heading))
+}
+
+/// The anchor of a heading as GitHub and most Markdown renderers generate it, e.g.
+/// `budget-2024` for `## Budget 2024!`: the title in lowercase without punctuation, spaces
+/// replaced by `-`
+pub fn heading_anchor(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// The anchors of the headings of a file (see [`heading_anchor`]) in order, a repeated
+/// anchor gets the number of its previous occurrences as suffix (`budget`, `budget-1`, ...)
+pub fn heading_anchors<'t>(titles: impl IntoIterator<Item = &'t str>) -> Vec<String> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    titles
+        .into_iter()
+        .map(|title| {
+            let anchor = heading_anchor(title);
+            let count = occurrences.entry(anchor.clone()).or_default();
+            *count += 1;
+            match *count {
+                1 => anchor,
+                n => format!("{}-{}", anchor, n - 1),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_link_target() {
+        let target = |note: Option<&str>, heading: Option<&str>| LinkTarget {
+            note: note.map(String::from),
+            heading: heading.map(String::from),
+        };
+
+        assert_eq!(
+            LinkTarget::parse("Note#Heading"),
+            target(Some("Note"), Some("Heading"))
+        );
+        assert_eq!(LinkTarget::parse("Note|Alias"), target(Some("Note"), None));
+        assert_eq!(LinkTarget::parse("#Heading"), target(None, Some("Heading")));
+        assert_eq!(LinkTarget::parse("Note#"), target(Some("Note"), None));
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        assert_eq!(heading_anchor(" Budget 2024! "), "budget-2024");
+        assert_eq!(
+            heading_anchor("2024-06-01 Meeting @work"),
+            "2024-06-01-meeting-work"
+        );
+        assert_eq!(heading_anchor("Über uns"), "über-uns");
+        assert_eq!(
+            heading_anchors(["Notes", "Budget", "notes", "Notes"]),
+            vec!["notes", "budget", "notes-1", "notes-2"]
+        );
+    }
+
+    #[test]
+    fn test_link_index() -> anyhow::Result<()> {
+        use crate::{
+            markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+            models::{MarkdownTokenizer, SectionBuilder},
+        };
+
+        let markdown = "# 2024-06-01\n\n## Meeting\nDiscussed **[[Project X]]**\n\
+                        - see [[project x#Budget]]\n\n## Notes [[#Meeting]]\n";
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize_with_origins(markdown, Some(Path::new("diary.md")))?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens_with_origins(tokens)?;
+        let index = LinkIndex::new(&sections);
+        assert_eq!(index.links.len(), 3);
+
+        let lines = |target: &str| {
+            index
+                .backlinks(&LinkTarget::parse(target))
+                .iter()
+                .map(|l| (l.origin.line, l.path.join(" > ")))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            lines("Project X"),
+            vec![
+                (4, "2024-06-01 > Meeting".to_string()),
+                (5, "2024-06-01 > Meeting".to_string())
+            ]
+        );
+        assert_eq!(
+            lines("Project X#budget"),
+            vec![(5, "2024-06-01 > Meeting".to_string())]
+        );
+        // Links without a note point into the file containing them
+        assert_eq!(
+            lines("diary"),
+            vec![(7, "2024-06-01 > Notes [[#Meeting]]".to_string())]
+        );
+        Ok(())
+    }
+}
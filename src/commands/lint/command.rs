@@ -0,0 +1,349 @@
+use std::{collections::HashMap, fmt::Display, path::PathBuf};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::config::LintConfig;
+use crate::{
+    commands::{
+        io::{MarkdownFile, OutputSink},
+        tags::normalize::TagNormalizer,
+        Command, ParseContext, Report,
+    },
+    markdown::{dates_from_title, misspelled_task_keyword},
+    models::{Origin, TaskStatus, Token},
+};
+
+/// Deepest heading level of markdown, deeper "headings" are read as text
+const MAX_HEADING_LEVEL: usize = 6;
+
+/// Minimal similarity of an unknown tag to a known one to suggest the known tag
+const TAG_SIMILARITY: f64 = 0.7;
+
+/// Checks the files against the conventions mdp relies on and reports every violation with
+/// its location. The number of violations is the result count (so the CLI fails if there
+/// are any).
+pub struct LintCommand {
+    pub config: LintConfig,
+}
+
+/// The conventions checked by [`LintCommand`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintRule {
+    /// Every H1 contains exactly one date
+    H1Date,
+    /// No two H1 of a file contain the same date
+    DuplicateDate,
+    /// No headings deeper than H6
+    HeadingDepth,
+    /// No unfinished tasks whose due date (`TODO UNTIL`) passed
+    OverdueTask,
+    /// No tags missing in the list of known tags
+    UnknownTag,
+    /// No task keywords in another case (`todo:`), which are no tasks
+    TaskKeyword,
+}
+
+impl LintRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintRule::H1Date => "h1-date",
+            LintRule::DuplicateDate => "duplicate-date",
+            LintRule::HeadingDepth => "heading-depth",
+            LintRule::OverdueTask => "overdue-task",
+            LintRule::UnknownTag => "unknown-tag",
+            LintRule::TaskKeyword => "task-keyword",
+        }
+    }
+}
+
+/// A violation of a [`LintRule`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub origin: Origin,
+    pub rule: LintRule,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: [{}] {}",
+            self.origin,
+            self.rule.name(),
+            self.message
+        )
+    }
+}
+
+impl Command for LintCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let tokens = ctx.tokens(&files)?;
+
+        let linter = Linter {
+            today: ctx.clock.today(),
+            tags: &ctx.tags,
+            known_tags: config
+                .known_tags
+                .iter()
+                .map(|t| ctx.tags.normalize(t.trim_start_matches('@')))
+                .collect(),
+        };
+        let mut diagnostics = linter.lint_tokens(&tokens);
+        diagnostics.extend(files.iter().flat_map(heading_depth));
+        diagnostics.extend(files.iter().flat_map(task_keywords));
+        diagnostics.sort_by_key(|d| (d.origin.file.clone(), d.origin.line, d.origin.column));
+
+        if diagnostics.is_empty() {
+            log::info!("No problems found");
+            return Ok(Report::default());
+        }
+        let lines = diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>();
+        out.write_output(&lines.join("\n"))?;
+
+        Ok(Report {
+            result_count: diagnostics.len(),
+        })
+    }
+}
+
+struct Linter<'c> {
+    today: NaiveDate,
+    tags: &'c TagNormalizer,
+    /// Canonical names of the known tags
+    known_tags: Vec<String>,
+}
+
+impl Linter<'_> {
+    fn lint_tokens(&self, tokens: &[(Token, Origin)]) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        let mut dates: HashMap<(Option<PathBuf>, NaiveDate), usize> = HashMap::new();
+        let diagnostic = |origin: &Origin, rule, message| Diagnostic {
+            origin: origin.clone(),
+            rule,
+            message,
+        };
+
+        for (token, origin) in tokens {
+            if let Token::HeadingH1(title) = token {
+                match dates_from_title(token, title) {
+                    // Every day of a range counts as used
+                    Ok((first, last)) => {
+                        let mut duplicate = None;
+                        for date in first.iter_days().take_while(|d| *d <= last.unwrap_or(first)) {
+                            let key = (origin.file.clone(), date);
+                            match dates.get(&key) {
+                                Some(line) => {
+                                    duplicate.get_or_insert((date, *line));
+                                }
+                                None => {
+                                    dates.insert(key, origin.line);
+                                }
+                            }
+                        }
+                        if let Some((date, line)) = duplicate {
+                            diagnostics.push(diagnostic(
+                                origin,
+                                LintRule::DuplicateDate,
+                                format!("The date {} is already used on line {}", date, line),
+                            ));
+                        }
+                    }
+                    Err(_) => diagnostics.push(diagnostic(
+                        origin,
+                        LintRule::H1Date,
+                        format!(
+                            "The heading '{}' contains {} dates instead of one (or a range)",
+                            token.to_markdown_string().trim(),
+                            title.iter().filter(|t| matches!(t, Token::Date(_))).count()
+                        ),
+                    )),
+                }
+            }
+
+            visit(token, &mut |t| match t {
+                Token::Task {
+                    status: TaskStatus::TodoUntil(due),
+                    ..
+                } if due.date < self.today => diagnostics.push(diagnostic(
+                    origin,
+                    LintRule::OverdueTask,
+                    format!(
+                        "The task is open although it was due {} days ago ({})",
+                        (self.today - due.date).num_days(),
+                        due.date
+                    ),
+                )),
+                t => {
+                    if let Some(message) = self.tags.tag(t).and_then(|t| self.unknown_tag(t)) {
+                        diagnostics.push(diagnostic(origin, LintRule::UnknownTag, message));
+                    }
+                }
+            });
+        }
+        diagnostics
+    }
+
+    /// A message if the tag (and all its parents) is not known, suggesting the most similar
+    /// known tag
+    fn unknown_tag(&self, tag: &str) -> Option<String> {
+        if self.known_tags.is_empty()
+            || self
+                .tags
+                .with_parents(tag)
+                .iter()
+                .any(|t| self.known_tags.contains(t))
+        {
+            return None;
+        }
+        let tag = self.tags.normalize(tag);
+        let suggestion = self
+            .known_tags
+            .iter()
+            .map(|known| {
+                let similarity = strsim::normalized_damerau_levenshtein(
+                    &tag.to_lowercase(),
+                    &known.to_lowercase(),
+                );
+                (known, similarity)
+            })
+            .filter(|(_, similarity)| *similarity >= TAG_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        Some(match suggestion {
+            Some((known, _)) => format!("Unknown tag @{}, did you mean @{}?", tag, known),
+            None => format!("Unknown tag @{}", tag),
+        })
+    }
+}
+
+/// Calls `f` for the token and all tokens nested within it
+fn visit<'t, 'a>(token: &'t Token<'a>, f: &mut impl FnMut(&'t Token<'a>)) {
+    f(token);
+    for child in token.children() {
+        visit(child, f);
+    }
+}
+
+/// The lines of the file outside of code blocks with their origin
+fn text_lines(file: &MarkdownFile) -> Vec<(Origin, &str)> {
+    let (body, first_line) = file.body();
+    let mut in_code_block = false;
+    let mut lines = vec![];
+    for (i, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if !in_code_block {
+            lines.push((Origin::new(Some(&file.path), first_line + i, 1), line));
+        }
+    }
+    lines
+}
+
+/// Lines starting like a heading deeper than H6 (outside of code blocks), which are read as
+/// text instead of a heading
+fn heading_depth(file: &MarkdownFile) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for (origin, line) in text_lines(file) {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if level > MAX_HEADING_LEVEL && line[level..].starts_with(' ') {
+            diagnostics.push(Diagnostic {
+                origin,
+                rule: LintRule::HeadingDepth,
+                message: format!(
+                    "Headings can be nested at most {} levels deep, this one is read as text",
+                    MAX_HEADING_LEVEL
+                ),
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Lines starting with a task keyword in another case (see [`misspelled_task_keyword`]),
+/// which are missing in the task list
+fn task_keywords(file: &MarkdownFile) -> Vec<Diagnostic> {
+    text_lines(file)
+        .into_iter()
+        .filter_map(|(origin, line)| {
+            let (written, keyword) = misspelled_task_keyword(line)?;
+            Some(Diagnostic {
+                origin,
+                rule: LintRule::TaskKeyword,
+                message: format!(
+                    "'{}' is no task, write the keyword as {} (followed by ':')",
+                    written, keyword
+                ),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder};
+
+    #[test]
+    fn test_lint() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(
+            &path,
+            "# 2024-06-01\n\nTODO UNTIL 2024-06-02: Pay the bill\n\
+             DONE: Call @roger about @wrok\n\n\
+             # Notes\n\n####### Details\n\n\
+             # 2024-06-02 - 2024-06-03\n\n\
+             # 2024-06-01\n\n- Meeting @work/projectX\n  - todo: Book a room\n\n\
+             # 2024-06-04 2024-06-05\n\n# 2024-06-03\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 6, 5).unwrap()));
+        let run = |config: LintConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = LintCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let config = LintConfig::builder()
+            .input(&path)
+            .known_tags(["@work", "roger"])
+            .build();
+        let (output, count) = run(config)?;
+        assert_eq!(
+            output,
+            format!(
+                "{0}:3:1: [overdue-task] The task is open although it was due 3 days ago \
+                 (2024-06-02)\n\
+                 {0}:4:1: [unknown-tag] Unknown tag @wrok, did you mean @work?\n\
+                 {0}:6:1: [h1-date] The heading '# Notes' contains 0 dates instead of one (or \
+                 a range)\n\
+                 {0}:8:1: [heading-depth] Headings can be nested at most 6 levels deep, this \
+                 one is read as text\n\
+                 {0}:12:1: [duplicate-date] The date 2024-06-01 is already used on line 1\n\
+                 {0}:15:1: [task-keyword] 'todo:' is no task, write the keyword as TODO \
+                 (followed by ':')\n\
+                 {0}:17:1: [h1-date] The heading '# 2024-06-04 2024-06-05' contains 2 dates \
+                 instead of one (or a range)\n\
+                 {0}:19:1: [duplicate-date] The date 2024-06-03 is already used on line 10",
+                path.display()
+            )
+        );
+        assert_eq!(count, 8);
+
+        // Tags are only checked against a list of known tags
+        let (_, count) = run(LintConfig::builder().input(&path).build())?;
+        assert_eq!(count, 7);
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct LintConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Correctly spelled tags, other tags (except children of these) are reported. Tags are
+    /// not checked if the list is empty.
+    pub known_tags: Vec<String>,
+}
+
+impl LintConfig {
+    pub fn builder() -> LintConfigBuilder {
+        LintConfigBuilder::default()
+    }
+}
+
+/// Builder for [`LintConfig`]
+#[derive(Clone, Debug)]
+pub struct LintConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    known_tags: Vec<String>,
+}
+
+impl Default for LintConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            known_tags: vec![],
+        }
+    }
+}
+
+impl InputBuilder for LintConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl LintConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    pub fn known_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.known_tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build(self) -> LintConfig {
+        LintConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            known_tags: self.known_tags,
+        }
+    }
+}
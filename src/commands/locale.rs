@@ -0,0 +1,261 @@
+use std::fmt::Display;
+
+use chrono::Weekday;
+use serde::Deserialize;
+
+/// Language of the user-facing texts of the output (table headers, summaries, banners and
+/// error prefaces). The markdown syntax (e.g. `TODO:`) is the same in all languages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+}
+
+/// A user-facing text, looked up in the bundle of the [`Language`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Text {
+    /// Preface of the error the program fails with
+    Error,
+    KeptAsText,
+    SkippedInput,
+    /// Result of `--check-args`
+    ValidOptions,
+    TagHeader,
+    CountHeader,
+    /// `{0}`: path of the file
+    StatsFile,
+    /// `{0}`: number of files
+    StatsTotal,
+    Sections,
+    Words,
+    Tasks,
+    Links,
+    /// Header of a column of sections
+    SectionHeader,
+    FirstEntry,
+    LastEntry,
+    MissingDays,
+    NeverIncluded,
+    MostIncluded,
+    Tags,
+    SearchedFor,
+    SearchMode,
+    SearchScope,
+    SearchFrom,
+    SearchTo,
+    SearchOrdering,
+    Overdue,
+    /// Percentage of the tasks done
+    Completed,
+    NextDeadline,
+    /// `{0}`: number of due tasks, `{1}`: daily capacity
+    TasksDue,
+    /// `{0}`: task
+    AlreadyDone,
+    /// `{0}`: date of the generated file
+    InputChanged,
+    /// `{0}`: error
+    InputUnreadable,
+    Regenerated,
+    /// `{0}`: number of moved sections, `{1}`: number of sections
+    SectionsMoved,
+    /// `{0}`: number of replacements
+    Replacements,
+    /// `{0}`: number of added sections
+    SectionsAdded,
+    /// `{0}`: number of open tasks, `{1}`: number of done tasks
+    ProjectTasks,
+    LastActivity,
+    RecentSections,
+    /// `{0}`: latest progress of a goal
+    GoalLatest,
+    /// `{0}`: change of the progress, `{1}`: date of the first progress
+    GoalTrend,
+    /// Result of an embedded query without matches
+    NoQueryResults,
+    /// Group of the sections without tags
+    Untagged,
+    /// Tags used for the first time in a period
+    NewTags,
+    /// `{0}`: planned week
+    Plan,
+    /// Unfinished tasks of the previous weeks
+    CarriedOver,
+    Deadlines,
+    InProgress,
+    OpenTasks,
+    WaitingTasks,
+}
+
+impl Language {
+    pub fn text(&self, text: Text) -> &'static str {
+        match self {
+            Language::English => english(text),
+            Language::German => german(text),
+        }
+    }
+
+    /// The text with its placeholders (`{0}`, `{1}`, ...) replaced by the arguments
+    pub fn format(&self, text: Text, args: &[&dyn Display]) -> String {
+        let mut s = self.text(text).to_string();
+        for (i, arg) in args.iter().enumerate() {
+            s = s.replace(&format!("{{{}}}", i), &arg.to_string());
+        }
+        s
+    }
+
+    pub fn weekday(&self, day: Weekday) -> &'static str {
+        let names = match self {
+            Language::English => [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ],
+            Language::German => [
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ],
+        };
+        names[day.num_days_from_monday() as usize]
+    }
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::Error => "Error",
+        Text::KeptAsText => "Kept the following lines as text",
+        Text::SkippedInput => "Skipped the following input",
+        Text::ValidOptions => "The options are valid",
+        Text::TagHeader => "Tag",
+        Text::CountHeader => "Count",
+        Text::StatsFile => "FILE: {0}",
+        Text::StatsTotal => "TOTAL ({0} files)",
+        Text::Sections => "Sections",
+        Text::Words => "Words",
+        Text::Tasks => "Tasks",
+        Text::Links => "Links",
+        Text::SectionHeader => "Section",
+        Text::FirstEntry => "First entry",
+        Text::LastEntry => "Last entry",
+        Text::MissingDays => "Missing days",
+        Text::NeverIncluded => "NEVER INCLUDED: {0} of {1} sections",
+        Text::MostIncluded => "MOST INCLUDED:",
+        Text::Tags => "Tags",
+        Text::SearchedFor => "SEARCHED FOR",
+        Text::SearchMode => "MODE",
+        Text::SearchScope => "SCOPE",
+        Text::SearchFrom => "FROM",
+        Text::SearchTo => "TO",
+        Text::SearchOrdering => "ORDERING",
+        Text::Overdue => "Overdue",
+        Text::Completed => "Completed",
+        Text::NextDeadline => "Next deadline",
+        Text::TasksDue => "{0} tasks due (capacity {1})",
+        Text::AlreadyDone => "Already done: {0}",
+        Text::InputChanged => "input changed since {0}",
+        Text::InputUnreadable => "input can not be read: {0}",
+        Text::Regenerated => "regenerated",
+        Text::SectionsMoved => "{0} of {1} sections moved",
+        Text::Replacements => "Replacements: {0}",
+        Text::SectionsAdded => "Sections added: {0}",
+        Text::ProjectTasks => "{0} open, {1} done",
+        Text::LastActivity => "Last activity",
+        Text::RecentSections => "Recent",
+        Text::GoalLatest => "Latest: {0}",
+        Text::GoalTrend => "{0} since {1}",
+        Text::NoQueryResults => "No matching sections",
+        Text::Untagged => "Without tags",
+        Text::NewTags => "New tags",
+        Text::Plan => "Plan {0}",
+        Text::CarriedOver => "Carried over",
+        Text::Deadlines => "Deadlines",
+        Text::InProgress => "In progress",
+        Text::OpenTasks => "Open",
+        Text::WaitingTasks => "Waiting",
+    }
+}
+
+fn german(text: Text) -> &'static str {
+    match text {
+        Text::Error => "Fehler",
+        Text::KeptAsText => "Die folgenden Zeilen wurden als Text übernommen",
+        Text::SkippedInput => "Die folgende Eingabe wurde übersprungen",
+        Text::ValidOptions => "Die Optionen sind gültig",
+        Text::TagHeader => "Tag",
+        Text::CountHeader => "Anzahl",
+        Text::StatsFile => "DATEI: {0}",
+        Text::StatsTotal => "TOTAL ({0} Dateien)",
+        Text::Sections => "Abschnitte",
+        Text::Words => "Wörter",
+        Text::Tasks => "Aufgaben",
+        Text::Links => "Links",
+        Text::SectionHeader => "Abschnitt",
+        Text::FirstEntry => "Erster Eintrag",
+        Text::LastEntry => "Letzter Eintrag",
+        Text::MissingDays => "Fehlende Tage",
+        Text::NeverIncluded => "NIE VERWENDET: {0} von {1} Abschnitten",
+        Text::MostIncluded => "AM HÄUFIGSTEN VERWENDET:",
+        Text::Tags => "Tags",
+        Text::SearchedFor => "GESUCHT NACH",
+        Text::SearchMode => "MODUS",
+        Text::SearchScope => "BEREICH",
+        Text::SearchFrom => "VON",
+        Text::SearchTo => "BIS",
+        Text::SearchOrdering => "SORTIERUNG",
+        Text::Overdue => "Überfällig",
+        Text::Completed => "Erledigt",
+        Text::NextDeadline => "Nächste Frist",
+        Text::TasksDue => "{0} Aufgaben fällig (Kapazität {1})",
+        Text::AlreadyDone => "Bereits erledigt: {0}",
+        Text::InputChanged => "Eingabe geändert seit {0}",
+        Text::InputUnreadable => "Eingabe kann nicht gelesen werden: {0}",
+        Text::Regenerated => "neu generiert",
+        Text::SectionsMoved => "{0} von {1} Abschnitten verschoben",
+        Text::Replacements => "Ersetzungen: {0}",
+        Text::SectionsAdded => "Abschnitte hinzugefügt: {0}",
+        Text::ProjectTasks => "{0} offen, {1} erledigt",
+        Text::LastActivity => "Letzte Aktivität",
+        Text::RecentSections => "Zuletzt",
+        Text::GoalLatest => "Aktuell: {0}",
+        Text::GoalTrend => "{0} seit {1}",
+        Text::NoQueryResults => "Keine passenden Abschnitte",
+        Text::Untagged => "Ohne Tags",
+        Text::NewTags => "Neue Tags",
+        Text::Plan => "Planung {0}",
+        Text::CarriedOver => "Übertragen",
+        Text::Deadlines => "Fristen",
+        Text::InProgress => "In Arbeit",
+        Text::OpenTasks => "Offen",
+        Text::WaitingTasks => "Wartend",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_language() {
+        assert_eq!(Language::default().text(Text::Overdue), "Overdue");
+        assert_eq!(Language::German.text(Text::Overdue), "Überfällig");
+        assert_eq!(
+            Language::German.format(Text::TasksDue, &[&3, &2]),
+            "3 Aufgaben fällig (Kapazität 2)"
+        );
+        assert_eq!(Language::German.weekday(Weekday::Sun), "Sonntag");
+    }
+}
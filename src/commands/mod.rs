@@ -1,5 +1,54 @@
+mod clock;
+mod context;
+mod traits;
+mod util;
+
+pub mod agenda;
+pub mod analysis;
+pub mod attrs;
+pub mod backlinks;
+pub mod badge;
+pub mod cache;
+pub mod cat;
+pub mod compare;
+pub mod daemon;
+pub mod diff;
+pub mod encryption;
+pub mod explain;
+pub mod fmt;
+pub mod gaps;
+pub mod goals;
+pub mod hooks;
+pub mod ics;
 pub mod io;
-pub mod tags;
+pub mod keywords;
+pub mod limits;
+pub mod links;
+pub mod lint;
+pub mod locale;
+pub mod oneline;
+pub mod outdated;
+pub mod plan;
+pub mod projects;
+pub mod queries;
+pub mod query;
+pub mod replace;
+pub mod review;
+pub mod rewrite;
 pub mod search;
+pub mod settings;
+pub mod sort;
+pub mod stats;
+pub mod summarize;
+pub mod tags;
 pub mod tasks;
+pub mod theme;
+pub mod titles;
+pub mod transclusion;
 pub mod tree;
+pub mod validation;
+pub mod waiting;
+
+pub use clock::*;
+pub use context::*;
+pub use traits::*;
@@ -0,0 +1,58 @@
+//! Results written as one tab separated line each (`--format oneline`), to be processed by
+//! e.g. `awk -F'\t'`, `cut` or `fzf` without parsing Markdown or JSON.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Origin;
+
+/// How the results of a command are written
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultFormat {
+    /// As Markdown
+    #[default]
+    Markdown,
+    /// One line per result with tab separated fields
+    Oneline,
+}
+
+/// The fields joined by tabs. Whitespace within a field (including tabs and line breaks)
+/// is collapsed to single spaces, so every field stays in its column and every result on
+/// its line.
+pub fn oneline<S: AsRef<str>>(fields: &[S]) -> String {
+    fields
+        .iter()
+        .map(|f| f.as_ref().split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<String>>()
+        .join("\t")
+}
+
+/// The location of a result as `path:line` (only the line if the file is not known)
+pub fn location(origin: &Origin) -> String {
+    match &origin.file {
+        Some(file) => format!("{}:{}", file.display(), origin.line),
+        None => origin.line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_oneline() {
+        assert_eq!(
+            oneline(&["2024-05-01", "work,meeting", " Plan\tthe\n trip ", ""]),
+            "2024-05-01\twork,meeting\tPlan the trip\t"
+        );
+        assert_eq!(
+            location(&Origin::new(Some(Path::new("notes/a.md")), 3, 5)),
+            "notes/a.md:3"
+        );
+        assert_eq!(location(&Origin::new(None, 3, 5)), "3");
+    }
+}
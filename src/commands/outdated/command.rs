@@ -0,0 +1,144 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use anyhow::Result;
+
+use super::config::OutdatedConfig;
+use crate::commands::{
+    io::{FileReader, FileWriter, FileWriterConfig, MarkdownFileReader, OutputSink, OutputWriter},
+    locale::Text,
+    search::command::{input_hash, Provenance, SearchCommand},
+    Command, ParseContext, Report,
+};
+
+/// Lists the files generated by `mdp search --frontmatter` whose input changed since they
+/// were generated, and optionally reruns their searches.
+///
+/// Files without such a frontmatter are ignored. The input paths recorded in the
+/// frontmatter are resolved relative to the current working directory, as they were when
+/// the file was generated.
+pub struct OutdatedCommand {
+    pub config: OutdatedConfig,
+}
+
+impl Command for OutdatedCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let files = ctx.read(self.config.input_path.clone())?;
+
+        let mut lines = vec![];
+        // The files of a split output are regenerated together
+        let mut regenerated_dirs: HashSet<PathBuf> = HashSet::new();
+        for file in &files {
+            let Some(provenance) = Provenance::parse(&file.content) else {
+                continue;
+            };
+            let search = SearchCommand {
+                config: provenance.config,
+            };
+            let reader = MarkdownFileReader {
+                max_depth: search.config.max_depth,
+                ..Default::default()
+            };
+            let inputs = match reader.read(search.config.input_path.clone()) {
+                Ok(inputs) if input_hash(&inputs) == provenance.input_hash => continue,
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    lines.push(format!(
+                        "{}: {}",
+                        file.path.display(),
+                        ctx.lang.format(Text::InputUnreadable, &[&e])
+                    ));
+                    continue;
+                }
+            };
+
+            let mut line = format!(
+                "{}: {}",
+                file.path.display(),
+                ctx.lang
+                    .format(Text::InputChanged, &[&provenance.generated])
+            );
+            if self.config.regenerate {
+                match &search.config.split_output {
+                    Some(dir) if !regenerated_dirs.insert(dir.clone()) => {}
+                    Some(_) => {
+                        search.run_on_files(ctx, &inputs, &mut String::new())?;
+                    }
+                    None => {
+                        let mut writers: Vec<Box<dyn OutputWriter>> = vec![Box::new(FileWriter {
+                            path: file.path.clone(),
+                            config: FileWriterConfig::default(),
+                        })];
+                        search.run_on_files(ctx, &inputs, &mut writers)?;
+                    }
+                }
+                line += &format!(" ({})", ctx.lang.text(Text::Regenerated));
+            }
+            lines.push(line);
+        }
+
+        out.write_output(&lines.join("\n"))?;
+        Ok(Report {
+            result_count: lines.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{search::config::SearchConfig, InputBuilder};
+
+    const DIARY: &str = "# 2023-10-10\n\n## Meeting\n\n@roger was late\n";
+
+    #[test]
+    fn test_outdated() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let diary = dir.path().join("diary.md");
+        let generated = dir.path().join("out").join("roger.md");
+        fs::write(&diary, DIARY)?;
+        fs::create_dir(dir.path().join("out"))?;
+
+        let ctx = ParseContext::default();
+        let search = SearchCommand {
+            config: SearchConfig::builder()
+                .input(&diary)
+                .terms(["@roger"])
+                .output(&generated)
+                .frontmatter(true)
+                .build()?,
+        };
+        let mut report = String::new();
+        search.run(&ctx, &mut report)?;
+        fs::write(&generated, &report)?;
+
+        let run = |regenerate: bool| -> Result<(String, usize)> {
+            let config = OutdatedConfig::builder()
+                .input(dir.path().join("out"))
+                .regenerate(regenerate)
+                .build();
+            let mut output = String::new();
+            let report = OutdatedCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        assert_eq!(run(false)?, (String::new(), 0));
+
+        fs::write(
+            &diary,
+            format!("{}\n# 2023-10-11\n\n@roger called\n", DIARY),
+        )?;
+        let (output, count) = run(false)?;
+        assert!(output.starts_with(&format!("{}: input changed since", generated.display())));
+        assert_eq!(count, 1);
+
+        let (output, _) = run(true)?;
+        assert!(output.ends_with("(regenerated)"));
+        assert!(fs::read_to_string(&generated)?.contains("@roger called"));
+        assert_eq!(run(false)?, (String::new(), 0));
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct OutdatedConfig {
+    /// Paths of the generated files (or of directories containing them)
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// Rerun the searches of the outdated files
+    pub regenerate: bool,
+}
+
+impl OutdatedConfig {
+    pub fn builder() -> OutdatedConfigBuilder {
+        OutdatedConfigBuilder::default()
+    }
+}
+
+/// Builder for [`OutdatedConfig`]
+#[derive(Clone, Debug)]
+pub struct OutdatedConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    regenerate: bool,
+}
+
+impl Default for OutdatedConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            regenerate: false,
+        }
+    }
+}
+
+impl InputBuilder for OutdatedConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl OutdatedConfigBuilder {
+    pub fn regenerate(mut self, regenerate: bool) -> Self {
+        self.regenerate = regenerate;
+        self
+    }
+
+    pub fn build(self) -> OutdatedConfig {
+        OutdatedConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            regenerate: self.regenerate,
+        }
+    }
+}
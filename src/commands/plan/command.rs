@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+
+use super::config::PlanConfig;
+use crate::{
+    commands::{
+        io::OutputSink,
+        locale::{Language, Text},
+        Command, ParseContext, Report,
+    },
+    models::{ConfigError, TaskStatus, Token},
+};
+
+/// A planning section for a week: the unfinished tasks carried over grouped by priority, the
+/// deadlines within the week and an empty subheading for every day. Written to a file with
+/// `--output` (and `--append` to add it to the journal).
+pub struct PlanCommand {
+    pub config: PlanConfig,
+}
+
+/// Groups of the carried over tasks, most urgent first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+    Overdue,
+    InProgress,
+    Open,
+    Waiting,
+}
+
+impl Priority {
+    /// The group of an unfinished task, `None` for finished tasks and tasks due within the
+    /// week starting at `first` (which are listed as deadlines)
+    fn of(status: &TaskStatus, first: NaiveDate, last: NaiveDate) -> Option<Self> {
+        match status {
+            TaskStatus::Done => None,
+            TaskStatus::TodoUntil(due) if due.date < first => Some(Self::Overdue),
+            TaskStatus::TodoUntil(due) if due.date <= last => None,
+            TaskStatus::Todo | TaskStatus::TodoUntil(_) => Some(Self::Open),
+            TaskStatus::Doing => Some(Self::InProgress),
+            TaskStatus::Review | TaskStatus::Waiting(_) => Some(Self::Waiting),
+        }
+    }
+
+    fn text(&self) -> Text {
+        match self {
+            Self::Overdue => Text::Overdue,
+            Self::InProgress => Text::InProgress,
+            Self::Open => Text::OpenTasks,
+            Self::Waiting => Text::WaitingTasks,
+        }
+    }
+}
+
+/// The tasks of the plan as Markdown
+#[derive(Debug, Default)]
+struct Plan {
+    carried_over: BTreeMap<Priority, Vec<String>>,
+    deadlines: Vec<(NaiveDate, String)>,
+}
+
+impl Command for PlanCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let week = config
+            .week
+            .resolve(ctx.clock.today())
+            .ok_or_else(|| ConfigError::InvalidDateError(format!("{:?}", config.week)))?;
+        let dates = week.dates();
+        let (Some(first), Some(last)) = (dates.from, dates.until) else {
+            return Err(ConfigError::InvalidDateError(week.to_string()).into());
+        };
+
+        let files = ctx.read(config.input_path.clone())?;
+        let mut plan = Plan::default();
+        for (token, _) in ctx.tokens(&files)? {
+            let Token::Task {
+                content, status, ..
+            } = &token
+            else {
+                continue;
+            };
+            // Tasks snoozed beyond the week are left for later
+            if token.snoozed_until().is_some_and(|until| last < until) {
+                continue;
+            }
+            // Written without checkbox, as a checkbox can not have every status
+            let task = Token::Task {
+                content: content.clone(),
+                status: status.clone(),
+                checkbox: None,
+            }
+            .to_markdown_string();
+            match (Priority::of(status, first, last), status) {
+                (Some(priority), _) => plan.carried_over.entry(priority).or_default().push(task),
+                (None, TaskStatus::TodoUntil(due)) => plan.deadlines.push((due.date, task)),
+                _ => {}
+            }
+        }
+        plan.deadlines.sort_by_key(|(due, _)| *due);
+
+        let days = dates_between(first, last);
+        out.write_output(&plan_to_string(&plan, &week.to_string(), &days, ctx.lang))?;
+        Ok(Report {
+            result_count: plan.carried_over.values().map(Vec::len).sum::<usize>()
+                + plan.deadlines.len(),
+        })
+    }
+}
+
+fn dates_between(first: NaiveDate, last: NaiveDate) -> Vec<NaiveDate> {
+    first.iter_days().take_while(|d| *d <= last).collect()
+}
+
+fn plan_to_string(plan: &Plan, week: &str, days: &[NaiveDate], lang: Language) -> String {
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        return String::new();
+    };
+    let mut parts = vec![format!(
+        "## {} ({} - {})",
+        lang.format(Text::Plan, &[&week]),
+        first,
+        last
+    )];
+
+    if !plan.carried_over.is_empty() {
+        parts.push(format!("### {}", lang.text(Text::CarriedOver)));
+        for (priority, tasks) in &plan.carried_over {
+            parts.push(format!(
+                "#### {}\n\n{}",
+                lang.text(priority.text()),
+                tasks.join("\n")
+            ));
+        }
+    }
+    if !plan.deadlines.is_empty() {
+        let tasks = plan
+            .deadlines
+            .iter()
+            .map(|(_, t)| t.as_str())
+            .collect::<Vec<&str>>();
+        parts.push(format!(
+            "### {}\n\n{}",
+            lang.text(Text::Deadlines),
+            tasks.join("\n")
+        ));
+    }
+    parts.extend(
+        days.iter()
+            .map(|d| format!("### {} {}", d, lang.weekday(d.weekday()))),
+    );
+    parts.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{plan::config::PlanWeek, FixedClock, InputBuilder};
+
+    #[test]
+    fn test_plan() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2024-03-18\n\nTODO: Book flight\nDONE: Send minutes\nWAITING(@roger): Slides\n\
+             - [ ] Water plants\n\n# 2024-03-20\n\nDOING: Write report\n\
+             TODO UNTIL 2024-03-29: Submit talk\nTODO UNTIL 2024-03-26: Pay bill\n\
+             TODO UNTIL 2024-03-19: Call plumber\nTODO: Renew passport @snooze(2024-05-01)\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 3, 21).unwrap()));
+        let run = |config: PlanConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = PlanCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let (output, count) = run(PlanConfig::builder().input(&path).build())?;
+        assert_eq!(
+            output,
+            "## Plan 2024-W13 (2024-03-25 - 2024-03-31)\n\n### Carried over\n\n\
+             #### Overdue\n\nTODO UNTIL 2024-03-19: Call plumber\n\n\
+             #### In progress\n\nDOING: Write report\n\n\
+             #### Open\n\nTODO: Book flight\nTODO: Water plants\n\n\
+             #### Waiting\n\nWAITING(@roger): Slides\n\n\
+             ### Deadlines\n\nTODO UNTIL 2024-03-26: Pay bill\nTODO UNTIL 2024-03-29: Submit talk\n\n\
+             ### 2024-03-25 Monday\n\n### 2024-03-26 Tuesday\n\n### 2024-03-27 Wednesday\n\n\
+             ### 2024-03-28 Thursday\n\n### 2024-03-29 Friday\n\n### 2024-03-30 Saturday\n\n\
+             ### 2024-03-31 Sunday"
+        );
+        assert_eq!(count, 7);
+
+        // The deadline of the following week is an open task of this one
+        let this_week = PlanConfig::builder()
+            .input(&path)
+            .week("this".parse()?)
+            .build();
+        let (output, _) = run(this_week)?;
+        assert!(output.starts_with("## Plan 2024-W12 (2024-03-18 - 2024-03-24)"));
+        assert!(output.contains(
+            "#### Open\n\nTODO: Book flight\nTODO: Water plants\n\
+                                 TODO UNTIL 2024-03-29: Submit talk"
+        ));
+        assert!(output.contains("### Deadlines\n\nTODO UNTIL 2024-03-19: Call plumber"));
+        assert!("2024-03".parse::<PlanWeek>().is_err());
+        Ok(())
+    }
+}
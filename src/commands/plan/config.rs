@@ -0,0 +1,112 @@
+use std::{path::PathBuf, str::FromStr};
+
+use chrono::NaiveDate;
+
+use crate::{
+    commands::{add_days, review::config::ReviewPeriod, InputBuilder},
+    models::ConfigError,
+};
+
+#[derive(Clone, Debug)]
+pub struct PlanConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// The planned week
+    pub week: PlanWeek,
+}
+
+impl PlanConfig {
+    pub fn builder() -> PlanConfigBuilder {
+        PlanConfigBuilder::default()
+    }
+}
+
+/// Builder for [`PlanConfig`]
+#[derive(Clone, Debug)]
+pub struct PlanConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    week: PlanWeek,
+}
+
+impl Default for PlanConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            week: PlanWeek::Next,
+        }
+    }
+}
+
+impl InputBuilder for PlanConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl PlanConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Plan this week instead of the next one
+    pub fn week(mut self, week: PlanWeek) -> Self {
+        self.week = week;
+        self
+    }
+
+    pub fn build(self) -> PlanConfig {
+        PlanConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            week: self.week,
+        }
+    }
+}
+
+/// The planned week: `this`, `next` (relative to today) or an ISO week like `2024-W13`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanWeek {
+    This,
+    Next,
+    Week(ReviewPeriod),
+}
+
+impl PlanWeek {
+    /// The ISO week, relative weeks resolved against `today`
+    pub fn resolve(&self, today: NaiveDate) -> Option<ReviewPeriod> {
+        match self {
+            Self::This => Some(ReviewPeriod::week_of(today)),
+            Self::Next => add_days(today, 7).map(ReviewPeriod::week_of),
+            Self::Week(week) => Some(*week),
+        }
+    }
+}
+
+impl FromStr for PlanWeek {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "this" => Ok(Self::This),
+            "next" => Ok(Self::Next),
+            _ => match s.parse::<ReviewPeriod>() {
+                Ok(week @ ReviewPeriod::Week { .. }) => Ok(Self::Week(week)),
+                _ => Err(ConfigError::InvalidOptionsError(format!(
+                    "--week expects `this`, `next` or a week like 2024-W13, not '{}'",
+                    s
+                ))),
+            },
+        }
+    }
+}
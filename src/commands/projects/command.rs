@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::config::ProjectsConfig;
+use crate::{
+    commands::{io::OutputSink, locale::Text, Command, ParseContext, Report},
+    models::{Section, TaskStatus, Token},
+};
+
+/// Summarizes the projects, i.e. the subsections of the headings titled like the namespace
+/// (e.g. `### Website` below `## Projects`) of all days: their open and done tasks, the
+/// day of their last activity, their tags and their latest sections.
+pub struct ProjectsCommand {
+    pub config: ProjectsConfig,
+}
+
+/// All sections of a project
+#[derive(Debug, Default)]
+struct Project<'s, 'a> {
+    sections: Vec<&'s Section<'a>>,
+    open_tasks: usize,
+    done_tasks: usize,
+    tags: BTreeSet<String>,
+}
+
+impl Command for ProjectsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+
+        let mut projects: BTreeMap<String, Project> = BTreeMap::new();
+        for section in project_sections(&sections, &config.namespace) {
+            let project = projects.entry(section.title_string()).or_default();
+            project.add(section);
+        }
+        if projects.is_empty() {
+            log::warn!(
+                "No projects found (subsections of headings titled '{}')!",
+                config.namespace
+            );
+            return Ok(Report::default());
+        }
+
+        let mut projects = projects.into_iter().collect::<Vec<_>>();
+        // Most recently active first
+        projects.sort_by_key(|(_, p)| std::cmp::Reverse(p.last_activity()));
+        let parts = projects
+            .iter()
+            .map(|(name, project)| project_to_string(name, project, config.recent, ctx))
+            .collect::<Vec<String>>();
+        out.write_output(&parts.join("\n\n"))?;
+
+        Ok(Report {
+            result_count: projects.len(),
+        })
+    }
+}
+
+impl<'s, 'a> Project<'s, 'a> {
+    fn add(&mut self, section: &'s Section<'a>) {
+        fn add_section(project: &mut Project, section: &Section) {
+            project.tags.extend(section.tags.iter().cloned());
+            for token in &section.content {
+                match token {
+                    Token::Task {
+                        status: TaskStatus::Done,
+                        ..
+                    } => project.done_tasks += 1,
+                    Token::Task { .. } => project.open_tasks += 1,
+                    _ => {}
+                }
+            }
+            for subsection in &section.subsections {
+                add_section(project, subsection);
+            }
+        }
+
+        add_section(self, section);
+        self.sections.push(section);
+    }
+
+    fn last_activity(&self) -> Option<NaiveDate> {
+        self.sections.iter().filter_map(|s| s.date).max()
+    }
+}
+
+/// The subsections of all sections titled like the namespace (compared case-insensitively)
+fn project_sections<'s, 'a>(sections: &'s [Section<'a>], namespace: &str) -> Vec<&'s Section<'a>> {
+    let mut projects = vec![];
+    for section in sections {
+        match section.title_string().eq_ignore_ascii_case(namespace) {
+            true => projects.extend(section.subsections.iter()),
+            false => projects.extend(project_sections(&section.subsections, namespace)),
+        }
+    }
+    projects
+}
+
+fn project_to_string(name: &str, project: &Project, recent: usize, ctx: &ParseContext) -> String {
+    let lang = ctx.lang;
+    let mut lines = vec![
+        format!("# {}", name),
+        String::new(),
+        format!(
+            "{}: {}",
+            lang.text(Text::Tasks),
+            lang.format(
+                Text::ProjectTasks,
+                &[&project.open_tasks, &project.done_tasks]
+            )
+        ),
+    ];
+    if let Some(date) = project.last_activity() {
+        lines.push(format!("{}: {}", lang.text(Text::LastActivity), date));
+    }
+    if !project.tags.is_empty() {
+        let tags = project
+            .tags
+            .iter()
+            .map(|t| format!("@{}", t))
+            .collect::<Vec<String>>();
+        lines.push(format!("{}: {}", lang.text(Text::Tags), tags.join(", ")));
+    }
+
+    let mut sections = project.sections.clone();
+    sections.sort_by_key(|s| std::cmp::Reverse(s.date));
+    if recent > 0 {
+        lines.push(format!("{}:", lang.text(Text::RecentSections)));
+    }
+    for section in sections.iter().take(recent) {
+        match (section.date, section.paragraphs().first()) {
+            (Some(date), Some(paragraph)) => lines.push(format!("- {}: {}", date, paragraph)),
+            (Some(date), None) => lines.push(format!("- {}", date)),
+            (None, Some(paragraph)) => lines.push(format!("- {}", paragraph)),
+            (None, None) => lines.push(format!("- {}", section.title_string())),
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_projects() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2024-06-01\n\n## Projects\n\n### Website\n\nKickoff with @roger.\n\
+             TODO: Draft the layout\n\n### Garden\n\nDONE: Buy seeds\n\n\
+             ## Gym\n\n# 2024-06-08\n\n## projects\n\n### Website\n\n\
+             DONE: Draft the layout\n\n#### Budget\n\nApproved @work\nTODO: Order hosting\n",
+        )?;
+
+        let ctx = ParseContext::default();
+        let run = |config: ProjectsConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = ProjectsCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let (output, count) = run(ProjectsConfig::builder().input(&path).build())?;
+        assert_eq!(
+            output,
+            "# Website\n\nTasks: 2 open, 1 done\nLast activity: 2024-06-08\n\
+             Tags: @roger, @work\nRecent:\n- 2024-06-08: Draft the layout\n\
+             - 2024-06-01: Kickoff with roger. Draft the layout\n\n\
+             # Garden\n\nTasks: 0 open, 1 done\nLast activity: 2024-06-01\nRecent:\n\
+             - 2024-06-01: Buy seeds"
+        );
+        assert_eq!(count, 2);
+
+        let (_, count) = run(ProjectsConfig::builder()
+            .input(&path)
+            .namespace("Clients")
+            .build())?;
+        assert_eq!(count, 0);
+        Ok(())
+    }
+}
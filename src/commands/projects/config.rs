@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+/// Title of the heading whose subsections are the projects
+pub const DEFAULT_PROJECTS_NAMESPACE: &str = "Projects";
+
+#[derive(Clone, Debug)]
+pub struct ProjectsConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Title of the heading (e.g. `## Projects`) whose subsections (e.g. `### Website`) are
+    /// the projects, compared case-insensitively
+    pub namespace: String,
+    /// Number of the latest sections shown per project
+    pub recent: usize,
+}
+
+impl ProjectsConfig {
+    pub fn builder() -> ProjectsConfigBuilder {
+        ProjectsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`ProjectsConfig`]
+#[derive(Clone, Debug)]
+pub struct ProjectsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    namespace: String,
+    recent: usize,
+}
+
+impl Default for ProjectsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            namespace: DEFAULT_PROJECTS_NAMESPACE.to_string(),
+            recent: 3,
+        }
+    }
+}
+
+impl InputBuilder for ProjectsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl ProjectsConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Title of the heading containing the projects (default `Projects`)
+    pub fn namespace<S: Into<String>>(mut self, namespace: S) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    pub fn recent(mut self, recent: usize) -> Self {
+        self.recent = recent;
+        self
+    }
+
+    pub fn build(self) -> ProjectsConfig {
+        ProjectsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            namespace: self.namespace,
+            recent: self.recent,
+        }
+    }
+}
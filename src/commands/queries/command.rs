@@ -0,0 +1,394 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::config::RenderQueriesConfig;
+use crate::{
+    commands::{
+        io::{MarkdownFile, OutputSink},
+        locale::{Language, Text},
+        query::{DateRange, Query, QueryMatch},
+        rewrite::{diff, replace_all, Replacement},
+        search::config::{AttributeFilter, SearchTerm, TagSearchMode},
+        Command, ParseContext, RelativeDate, Report,
+    },
+    models::Origin,
+};
+
+/// Language of the fenced code blocks containing a query
+const QUERY_LANGUAGE: &str = "mdp-query";
+/// The lines enclosing the results of a query, which directly follow its code block
+const RESULT_START: &str = "<!-- mdp-query-result -->";
+const RESULT_END: &str = "<!-- /mdp-query-result -->";
+
+/// Runs the queries embedded in the notes and writes their results below them, replacing
+/// the results of previous runs. A query is a fenced code block with the language
+/// `mdp-query`:
+///
+/// ````markdown
+/// ```mdp-query
+/// tags: work, meeting
+/// from: -7d
+/// ```
+/// ````
+///
+/// The queries search all input files (without the results of queries). The updated files
+/// are listed (or their changes shown as unified diff with `--dry-run`), their number is
+/// the result count.
+pub struct RenderQueriesCommand {
+    pub config: RenderQueriesConfig,
+}
+
+/// The options of an embedded query, one `key: value` per line
+#[derive(Clone, Debug, Default)]
+struct EmbeddedQuery {
+    /// `terms: roger, #project` (search terms as on the command line)
+    terms: Vec<String>,
+    /// `tags: work` (tags, the `@` is optional)
+    tags: Vec<String>,
+    /// `attr: Source=blog`
+    attributes: Vec<String>,
+    /// `mode: and`
+    mode: Option<TagSearchMode>,
+    /// `from: -7d`
+    from: Option<RelativeDate>,
+    /// `until: 2024-06-30`
+    until: Option<RelativeDate>,
+}
+
+impl EmbeddedQuery {
+    fn parse(lines: &[&str]) -> Result<Self, String> {
+        let list = |value: &str| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<String>>()
+        };
+        let date = |value: &str| value.parse::<RelativeDate>().map_err(|e| e.to_string());
+
+        let mut query = Self::default();
+        for line in lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("'{}' is no option of the query (`key: value`)", line))?;
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "terms" => query.terms.extend(list(value)),
+                "tags" => query.tags.extend(list(value)),
+                "attr" => query.attributes.extend(list(value)),
+                "mode" => {
+                    query.mode = Some(match value.to_lowercase().as_str() {
+                        "and" => TagSearchMode::And,
+                        "or" => TagSearchMode::Or,
+                        _ => return Err(format!("The mode '{}' is neither 'and' nor 'or'", value)),
+                    })
+                }
+                "from" => query.from = Some(date(value)?),
+                "until" => query.until = Some(date(value)?),
+                key => return Err(format!("Unknown option '{}' of the query", key)),
+            }
+        }
+        Ok(query)
+    }
+
+    fn query(&self, today: NaiveDate) -> Result<Query, String> {
+        let resolve = |date: &Option<RelativeDate>| match date {
+            Some(d) => d
+                .resolve(today)
+                .map(Some)
+                .ok_or_else(|| format!("The date {:?} is out of range", d)),
+            None => Ok(None),
+        };
+        let tags = self
+            .tags
+            .iter()
+            .map(|t| format!("@{}", t.trim_start_matches('@')));
+
+        let query = Query {
+            terms: self
+                .terms
+                .iter()
+                .cloned()
+                .chain(tags)
+                .map(SearchTerm::try_from)
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?,
+            mode: self.mode.clone().unwrap_or(TagSearchMode::Or),
+            attributes: self
+                .attributes
+                .iter()
+                .map(|a| a.parse::<AttributeFilter>())
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?,
+            dates: DateRange::new(resolve(&self.from)?, resolve(&self.until)?),
+            ..Default::default()
+        };
+        if query.is_unrestricted() {
+            return Err("The query has neither terms, tags nor attributes".to_string());
+        }
+        Ok(query)
+    }
+}
+
+/// A query code block within the lines of a file
+#[derive(Clone, Debug)]
+struct QueryBlock<'c> {
+    /// Index of the line of the opening fence
+    start: usize,
+    /// Index of the line of the closing fence
+    end: usize,
+    /// Index of the last line of the results following the block (`end` if there are none)
+    result_end: usize,
+    query: Vec<&'c str>,
+}
+
+impl Command for RenderQueriesCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        // Results of queries are not searched, so that they do not find themselves
+        let searched = files
+            .iter()
+            .map(|f| MarkdownFile::new(f.path.clone(), without_results(&f.content)))
+            .collect::<Vec<MarkdownFile>>();
+        let sections = ctx.sections(&searched)?;
+
+        let mut updated = vec![];
+        for file in &files {
+            let lines = file.content.split('\n').collect::<Vec<&str>>();
+            let mut replacements = vec![];
+            for block in query_blocks(&lines) {
+                let origin = Origin::new(Some(&file.path), block.start + 1, 1);
+                let query =
+                    EmbeddedQuery::parse(&block.query).and_then(|q| q.query(ctx.clock.today()));
+                let query = match query {
+                    Ok(query) => query,
+                    Err(message) => {
+                        log::warn!("{}: Skipped the query: {}", origin, message);
+                        continue;
+                    }
+                };
+                let results = query.sections(&sections, &ctx.tags);
+                let replacement =
+                    result_replacement(&lines, &block, &results_to_lines(results, ctx.lang));
+                if replacement.old != replacement.new {
+                    replacements.push(replacement);
+                }
+            }
+
+            if replacements.is_empty() {
+                continue;
+            }
+            match (config.check, config.dry_run) {
+                (true, _) => updated.push(file.path.display().to_string()),
+                (false, true) => updated.push(diff(&file.path, &replacements)?),
+                (false, false) => {
+                    replace_all(&file.path, &replacements)?;
+                    updated.push(file.path.display().to_string());
+                }
+            }
+        }
+
+        if updated.is_empty() {
+            log::info!("All query results are up to date");
+            return Ok(Report::default());
+        }
+        out.write_output(&updated.join("\n"))?;
+        Ok(Report {
+            result_count: updated.len(),
+        })
+    }
+}
+
+/// The query code blocks (and the results following them) within the lines
+fn query_blocks<'c>(lines: &[&'c str]) -> Vec<QueryBlock<'c>> {
+    let trimmed = |i: usize| lines.get(i).map(|l| l.trim());
+    let mut blocks = vec![];
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(language) = trimmed(i).and_then(|l| l.strip_prefix("```")) else {
+            i += 1;
+            continue;
+        };
+        let Some(end) = (i + 1..lines.len()).find(|j| trimmed(*j) == Some("```")) else {
+            break;
+        };
+        if language.trim() == QUERY_LANGUAGE {
+            let result_end = match trimmed(end + 1) == Some(RESULT_START) {
+                true => (end + 2..lines.len()).find(|j| trimmed(*j) == Some(RESULT_END)),
+                false => Some(end),
+            };
+            match result_end {
+                Some(result_end) => blocks.push(QueryBlock {
+                    start: i,
+                    end,
+                    result_end,
+                    query: lines[i + 1..end].to_vec(),
+                }),
+                None => log::warn!(
+                    "Line {}: Skipped the query, its results are missing the line {}",
+                    i + 1,
+                    RESULT_END
+                ),
+            }
+        }
+        i = end + 1;
+    }
+    blocks
+}
+
+/// The content with the results of queries replaced by blank lines (keeping the line
+/// numbers)
+fn without_results(content: &str) -> String {
+    let mut in_results = false;
+    content
+        .split('\n')
+        .map(|line| {
+            match line.trim() {
+                RESULT_START => in_results = true,
+                RESULT_END if in_results => {
+                    in_results = false;
+                    return "";
+                }
+                _ => {}
+            }
+            match in_results {
+                true => "",
+                false => line,
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// The results as list of links to the sections, the most recent first
+fn results_to_lines(mut results: Vec<QueryMatch>, lang: Language) -> Vec<String> {
+    if results.is_empty() {
+        return vec![format!("_{}_", lang.text(Text::NoQueryResults))];
+    }
+    results.sort_by_key(|r| std::cmp::Reverse(r.section.date));
+    results
+        .iter()
+        .map(|r| {
+            let note = r
+                .section
+                .origin
+                .file
+                .as_ref()
+                .and_then(|f| f.file_stem())
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let link = format!("[[{}#{}]]", note, r.section.title_string());
+            match r.section.date {
+                Some(date) => format!("- {}: {}", date, link),
+                None => format!("- {}", link),
+            }
+        })
+        .collect()
+}
+
+/// Replaces the closing fence of the block and the previous results by the closing fence
+/// followed by the new results (keeping the line endings of the file)
+fn result_replacement(lines: &[&str], block: &QueryBlock, results: &[String]) -> Replacement {
+    let old = lines[block.end..=block.result_end].join("\n");
+    let line_ending = match lines[block.end].ends_with('\r') {
+        true => "\r\n",
+        false => "\n",
+    };
+
+    let mut new_lines = vec![lines[block.end].trim_end_matches('\r'), RESULT_START];
+    new_lines.extend(results.iter().map(String::as_str));
+    new_lines.push(RESULT_END);
+    let mut new = new_lines.join(line_ending);
+    if old.ends_with('\r') {
+        new.push('\r');
+    }
+
+    Replacement {
+        line: block.end + 1,
+        column: 1,
+        old,
+        new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder};
+
+    #[test]
+    fn test_embedded_query() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 8).unwrap();
+        let query = EmbeddedQuery::parse(&["tags: work, @rega", "", "from: -7d", "mode: AND"]);
+        let query = query.unwrap().query(today).unwrap();
+        assert_eq!(
+            query.terms.iter().map(|t| t.inner()).collect::<Vec<_>>(),
+            vec!["@work", "@rega"]
+        );
+        assert_eq!(query.dates.from, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert!(matches!(query.mode, TagSearchMode::And));
+
+        assert!(EmbeddedQuery::parse(&["limit: 3"]).is_err());
+        assert!(EmbeddedQuery::parse(&["from: yesterday"]).is_err());
+        assert!(EmbeddedQuery::parse(&["from: -7d"])
+            .unwrap()
+            .query(today)
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_queries() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let diary = dir.path().join("diary.md");
+        let index = dir.path().join("index.md");
+        fs::write(
+            &diary,
+            "# 2024-06-07\n\n## Standup\n\n@work\n\n# 2024-06-01\n\n## Planning\n\n@work\n",
+        )?;
+        fs::write(
+            &index,
+            "# 2024-06-08\r\n\r\n```mdp-query\r\ntags: work\r\nfrom: -3d\r\n```\r\n\r\nEnd\r\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 6, 8).unwrap()));
+        let run_with = |check: bool, dry_run: bool| -> Result<(String, usize)> {
+            let config = RenderQueriesConfig::builder()
+                .input(dir.path())
+                .check(check)
+                .dry_run(dry_run)
+                .build();
+            let mut output = String::new();
+            let report = RenderQueriesCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+        let run = |check: bool| run_with(check, false);
+
+        assert_eq!(run(true)?, (index.display().to_string(), 1));
+        let (diff, count) = run_with(false, true)?;
+        assert_eq!(count, 1);
+        assert!(diff.contains("+<!-- mdp-query-result -->"));
+        assert!(!fs::read_to_string(&index)?.contains("mdp-query-result"));
+        assert_eq!(run(false)?.1, 1);
+        let rendered = "# 2024-06-08\r\n\r\n```mdp-query\r\ntags: work\r\nfrom: -3d\r\n```\r\n\
+                        <!-- mdp-query-result -->\r\n- 2024-06-07: [[diary#Standup]]\r\n\
+                        <!-- /mdp-query-result -->\r\n\r\nEnd\r\n";
+        assert_eq!(fs::read_to_string(&index)?, rendered);
+
+        // The results are replaced, unchanged results are not written again
+        assert_eq!(run(false)?.1, 0);
+        fs::write(&index, rendered.replace("-3d", "-30d"))?;
+        run(false)?;
+        assert!(fs::read_to_string(&index)?.contains(
+            "- 2024-06-07: [[diary#Standup]]\r\n- 2024-06-01: [[diary#Planning]]\r\n\
+             <!-- /mdp-query-result -->\r\n\r\nEnd"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct RenderQueriesConfig {
+    /// Paths of the notes (the queries search all of them)
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// Only list the files whose query results changed, without updating them
+    pub check: bool,
+    /// Only show the changes (as unified diff) instead of writing them to the files
+    pub dry_run: bool,
+}
+
+impl RenderQueriesConfig {
+    pub fn builder() -> RenderQueriesConfigBuilder {
+        RenderQueriesConfigBuilder::default()
+    }
+}
+
+/// Builder for [`RenderQueriesConfig`]
+#[derive(Clone, Debug)]
+pub struct RenderQueriesConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    check: bool,
+    dry_run: bool,
+}
+
+impl Default for RenderQueriesConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            check: false,
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for RenderQueriesConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl RenderQueriesConfigBuilder {
+    /// Only list the files whose query results changed
+    pub fn check(mut self, check: bool) -> Self {
+        self.check = check;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> RenderQueriesConfig {
+        RenderQueriesConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            check: self.check,
+            dry_run: self.dry_run,
+        }
+    }
+}
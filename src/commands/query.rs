@@ -0,0 +1,410 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+
+use super::{
+    search::config::{AttributeFilter, SearchScope, SearchTerm, SearchTermKind, TagSearchMode},
+    tags::normalize::TagNormalizer,
+    tasks::config::TaskFilterType,
+    RelativeDate,
+};
+use crate::models::{Section, TaskStatus};
+
+/// The filters selecting sections (search terms, attributes and dates) and tasks (status,
+/// due and section dates). `search`, the queries embedded in notes (`render-queries`),
+/// `review`, `keywords` and `summarize` select their sections with [`Query::sections`], and
+/// `tasks` selects its tasks with [`Query::matches_task`], so a filter means the same in all
+/// of them.
+#[derive(Clone, Debug)]
+pub struct Query {
+    pub terms: Vec<SearchTerm>,
+    pub mode: TagSearchMode,
+    /// Where all terms have to match in [`TagSearchMode::And`]
+    pub scope: SearchScope,
+    /// Only sections containing all these attributes (`Key:: value`) match
+    pub attributes: Vec<AttributeFilter>,
+    pub dates: DateRange,
+    /// Only tasks of this status match
+    pub status: TaskFilterType,
+    /// Only tasks due (`TODO UNTIL`) within the range match
+    pub due: DateRange,
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self {
+            terms: vec![],
+            mode: TagSearchMode::Or,
+            scope: SearchScope::Section,
+            attributes: vec![],
+            dates: DateRange::default(),
+            status: TaskFilterType::All,
+            due: DateRange::default(),
+        }
+    }
+}
+
+/// A section matching a query
+#[derive(Clone, Debug)]
+pub struct QueryMatch<'a> {
+    /// The search terms matched by the section itself
+    pub matched_terms: Vec<String>,
+    pub section: Section<'a>,
+}
+
+/// An inclusive range of dates, open at the unset ends
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn new(from: Option<NaiveDate>, until: Option<NaiveDate>) -> Self {
+        Self { from, until }
+    }
+
+    /// The range between dates relative to `today`, dates which can not be resolved are
+    /// left open
+    pub fn relative(
+        from: Option<RelativeDate>,
+        until: Option<RelativeDate>,
+        today: NaiveDate,
+    ) -> Self {
+        Self::new(
+            from.and_then(|d| d.resolve(today)),
+            until.and_then(|d| d.resolve(today)),
+        )
+    }
+
+    /// Whether the date is within the range, a missing date (e.g. of a task read without
+    /// its section) is only within an unrestricted range
+    pub fn contains(&self, date: Option<NaiveDate>) -> bool {
+        match date {
+            Some(d) => self.from.is_none_or(|f| f <= d) && self.until.is_none_or(|u| d <= u),
+            None => self.from.is_none() && self.until.is_none(),
+        }
+    }
+
+    /// Whether any day from `first` to `last` (both inclusive, e.g. the days of a section
+    /// spanning several days) is within the range
+    pub fn overlaps(&self, first: NaiveDate, last: NaiveDate) -> bool {
+        self.from.is_none_or(|f| f <= last) && self.until.is_none_or(|u| first <= u)
+    }
+
+    /// Whether any day of the section is within the range, a section without a date is
+    /// only within an unrestricted range
+    pub fn contains_section(&self, section: &Section) -> bool {
+        match (section.date, section.last_date()) {
+            (Some(first), Some(last)) => self.overlaps(first, last),
+            _ => self.contains(None),
+        }
+    }
+}
+
+impl Query {
+    /// The sections with one of the tags (given with or without `@`, all sections if there
+    /// are none) within the dates
+    pub fn tagged(tags: &[String], dates: DateRange) -> Self {
+        Self {
+            terms: tags
+                .iter()
+                .map(|t| SearchTerm::Exact(format!("@{}", t.trim_start_matches('@'))))
+                .collect(),
+            dates,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the query has neither terms nor attributes, so it would match every section
+    pub fn is_unrestricted(&self) -> bool {
+        self.terms.is_empty() && self.attributes.is_empty()
+    }
+
+    /// Whether a task with the status, written in a section of the date (`None` if unknown),
+    /// matches the status, due dates and dates of the query. A task without a due date is
+    /// only within an unrestricted due range (see [`DateRange::contains`]).
+    pub fn matches_task(&self, status: &TaskStatus, date: Option<NaiveDate>) -> bool {
+        let matches_status = match self.status {
+            TaskFilterType::All => true,
+            TaskFilterType::Finished => matches!(status, TaskStatus::Done),
+            TaskFilterType::Unfinished => !matches!(status, TaskStatus::Done),
+        };
+        let due = match status {
+            TaskStatus::TodoUntil(due) => Some(due.date),
+            _ => None,
+        };
+        matches_status && self.dates.contains(date) && self.due.contains(due)
+    }
+
+    /// The sections (and subsections) matching the query, each section before its
+    /// subsections
+    pub fn sections<'a>(
+        &self,
+        sections: &[Section<'a>],
+        tags: &TagNormalizer,
+    ) -> Vec<QueryMatch<'a>> {
+        let scoped_matches = ScopedMatches::new(sections, self.scope.clone(), &self.terms, tags);
+        let mut results = vec![];
+        self.collect(sections, &scoped_matches, tags, &mut results);
+        results
+    }
+
+    fn collect<'a>(
+        &self,
+        sections: &[Section<'a>],
+        scoped_matches: &ScopedMatches,
+        tags: &TagNormalizer,
+        results: &mut Vec<QueryMatch<'a>>,
+    ) {
+        for s in sections {
+            let matched = matched_terms(s, &self.terms, tags);
+            // Without search terms, all sections match (and are only filtered by attributes)
+            let matches = self.terms.is_empty()
+                || match self.mode {
+                    TagSearchMode::Or => !matched.is_empty(),
+                    TagSearchMode::And => {
+                        !matched.is_empty()
+                            && scoped_matches.matched_terms_of(s, &matched).len()
+                                == self.terms.len()
+                    }
+                };
+            if matches && self.dates.contains_section(s) && has_attributes(s, &self.attributes) {
+                results.push(QueryMatch {
+                    section: s.clone(),
+                    matched_terms: matched.iter().map(|i| self.terms[*i].inner()).collect(),
+                });
+            }
+            self.collect(&s.subsections, scoped_matches, tags, results);
+        }
+    }
+}
+
+/// Whether the section (without its subsections) contains all the attributes
+fn has_attributes(section: &Section, filters: &[AttributeFilter]) -> bool {
+    if filters.is_empty() {
+        return true;
+    }
+    let attributes = section.attributes();
+    filters.iter().all(|f| {
+        attributes
+            .iter()
+            .any(|(name, value)| f.matches(name, value))
+    })
+}
+
+/// The search terms matched by all sections within the same scope (e.g. of the same day),
+/// stored as indices into the search terms.
+struct ScopedMatches {
+    scope: SearchScope,
+    matches: HashMap<String, HashSet<usize>>,
+}
+
+impl ScopedMatches {
+    fn new(
+        sections: &[Section],
+        scope: SearchScope,
+        search_terms: &[SearchTerm],
+        tags: &TagNormalizer,
+    ) -> Self {
+        let mut scoped_matches = Self {
+            scope,
+            matches: HashMap::new(),
+        };
+        scoped_matches.collect(sections, search_terms, tags);
+        scoped_matches
+    }
+
+    fn collect(&mut self, sections: &[Section], search_terms: &[SearchTerm], tags: &TagNormalizer) {
+        for s in sections {
+            if let Some(key) = self.key(s) {
+                self.matches
+                    .entry(key)
+                    .or_default()
+                    .extend(matched_terms(s, search_terms, tags));
+            }
+            self.collect(&s.subsections, search_terms, tags);
+        }
+    }
+
+    fn key(&self, section: &Section) -> Option<String> {
+        match self.scope {
+            SearchScope::Section => None,
+            // Sections without a date are their own scope
+            SearchScope::Day => section.date.map(|d| d.to_string()),
+            SearchScope::File => Some(match &section.origin.file {
+                Some(file) => file.display().to_string(),
+                None => String::new(),
+            }),
+        }
+    }
+
+    /// Search terms matched within the scope of the section (`matched` are the ones
+    /// matched by the section itself)
+    fn matched_terms_of(&self, section: &Section, matched: &[usize]) -> HashSet<usize> {
+        match self.key(section).and_then(|key| self.matches.get(&key)) {
+            Some(scoped) => scoped.clone(),
+            None => matched.iter().copied().collect(),
+        }
+    }
+}
+
+/// Indices of the search terms matched by the section (without its subsections), tag terms
+/// match aliases and child tags as well.
+fn matched_terms(
+    section: &Section,
+    search_terms: &[SearchTerm],
+    tags: &TagNormalizer,
+) -> Vec<usize> {
+    let kinds = search_terms.iter().map(|t| t.kind()).collect::<Vec<_>>();
+    let has_patterns = search_terms
+        .iter()
+        .any(|t| !matches!(t, SearchTerm::Exact(_)));
+    let heading = match has_patterns {
+        true => section.title_string(),
+        false => String::new(),
+    };
+
+    let has_hashtags = kinds
+        .iter()
+        .any(|k| matches!(k, SearchTermKind::Hashtag(_)));
+    let has_words = kinds.iter().any(|k| matches!(k, SearchTermKind::Text(_)));
+
+    let hashtags = match has_hashtags {
+        true => section.hashtags(),
+        false => vec![],
+    };
+    let words = match has_words {
+        true => words(&section.plain_text()),
+        false => HashSet::new(),
+    };
+
+    kinds
+        .iter()
+        .zip(search_terms)
+        .enumerate()
+        .filter(|(_, (kind, term))| match (kind, term) {
+            (SearchTermKind::Tag(tag), SearchTerm::Exact(_)) => {
+                section.tags.iter().any(|t| tags.matches(t, tag))
+            }
+            (SearchTermKind::Hashtag(hashtag), SearchTerm::Exact(_)) => hashtags.contains(hashtag),
+            (SearchTermKind::Text(word), SearchTerm::Exact(_)) => {
+                words.contains(&word.to_lowercase())
+            }
+            (SearchTermKind::Tag(_), term) => section.tags.iter().any(|t| term.matches_pattern(t)),
+            (SearchTermKind::Hashtag(_), term) => hashtags.iter().any(|h| term.matches_pattern(h)),
+            (SearchTermKind::Text(_), term) => {
+                section.tags.iter().any(|t| term.matches_pattern(t))
+                    || matches_heading(term, &heading)
+            }
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Whether the heading matches the regular expression, or the heading or one of its words
+/// is similar to the fuzzy term
+fn matches_heading(term: &SearchTerm, heading: &str) -> bool {
+    match term {
+        SearchTerm::Fuzzy { .. } => {
+            term.matches_pattern(heading) || words(heading).iter().any(|w| term.matches_pattern(w))
+        }
+        term => term.matches_pattern(heading),
+    }
+}
+
+/// The lowercase words of a text, split at everything but alphanumeric characters, `-`
+/// and `_`
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+        models::{MarkdownTokenizer, SectionBuilder},
+    };
+
+    #[test]
+    fn test_date_range() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 6, d);
+        let range = DateRange::new(date(2), date(4));
+        assert!(range.contains(date(2)));
+        assert!(range.contains(date(4)));
+        assert!(!range.contains(date(5)));
+        assert!(!range.contains(None));
+        assert!(DateRange::new(None, date(4)).contains(date(1)));
+        assert!(DateRange::default().contains(None));
+
+        assert!(range.overlaps(date(1).unwrap(), date(2).unwrap()));
+        assert!(range.overlaps(date(3).unwrap(), date(9).unwrap()));
+        assert!(!range.overlaps(date(5).unwrap(), date(9).unwrap()));
+        assert!(DateRange::new(None, date(4)).overlaps(date(1).unwrap(), date(1).unwrap()));
+    }
+
+    #[test]
+    fn test_query_matches_task() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 6, d);
+        let due = |d| TaskStatus::TodoUntil(date(d).unwrap().into());
+        let open = Query {
+            status: TaskFilterType::Unfinished,
+            dates: DateRange::new(date(2), None),
+            ..Default::default()
+        };
+        assert!(open.matches_task(&TaskStatus::Todo, date(2)));
+        assert!(!open.matches_task(&TaskStatus::Done, date(2)));
+        assert!(!open.matches_task(&TaskStatus::Todo, date(1)));
+        assert!(!open.matches_task(&TaskStatus::Todo, None));
+
+        let due_soon = Query {
+            due: DateRange::new(None, date(5)),
+            ..Default::default()
+        };
+        assert!(due_soon.matches_task(&due(5), None));
+        assert!(!due_soon.matches_task(&due(6), None));
+        assert!(!due_soon.matches_task(&TaskStatus::Todo, None));
+        assert!(Query::default().matches_task(&TaskStatus::Done, None));
+    }
+
+    #[test]
+    fn test_query_sections() {
+        let diary = "# 2024-06-01\n\n## Post\nSource:: blog\n\n# 2024-06-03\n\n## Book\n\
+                     Source:: book\n\n## Call\n@roger\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary).unwrap();
+        let sections = MDPSectionBuilder::default()
+            .sections_from_tokens(tokens)
+            .unwrap();
+        let titles = |query: Query| {
+            query
+                .sections(&sections, &TagNormalizer::default())
+                .iter()
+                .map(|m| m.section.title_string())
+                .collect::<Vec<String>>()
+        };
+
+        let attributes = vec!["Source".parse::<AttributeFilter>().unwrap()];
+        let sourced = Query {
+            attributes: attributes.clone(),
+            ..Default::default()
+        };
+        assert!(!sourced.is_unrestricted());
+        assert_eq!(titles(sourced), vec!["Post", "Book"]);
+        let recent = Query {
+            attributes,
+            dates: DateRange::new(NaiveDate::from_ymd_opt(2024, 6, 2), None),
+            ..Default::default()
+        };
+        assert_eq!(titles(recent), vec!["Book"]);
+        assert_eq!(
+            titles(Query::default()),
+            vec!["2024-06-01", "Post", "2024-06-03", "Book", "Call"]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::ops::Range;
+
+use anyhow::Result;
+
+use super::config::{ReplaceConfig, ReplaceScope};
+use crate::{
+    commands::{
+        diff::unified_diff, io::OutputSink, locale::Text, rewrite::replace_content,
+        tags::normalize::TagNormalizer, Command, ParseContext, Report,
+    },
+    models::Token,
+};
+
+/// Replaces a string in the input files, but only within the chosen kinds of tokens (e.g.
+/// only in tags), never within code. Unlike `sed`, a replacement can't break markup, e.g.
+/// the `@` of a tag or the brackets of a link.
+pub struct ReplaceCommand {
+    pub config: ReplaceConfig,
+}
+
+impl Command for ReplaceCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+
+        let mut lines = vec![];
+        let mut result_count = 0;
+        for file in &files {
+            let mut parts = vec![];
+            for (token, _) in ctx.tokens(std::slice::from_ref(file))? {
+                collect_parts(&token, &config.scopes, &ctx.tags, &mut parts);
+            }
+            let ranges = parts
+                .into_iter()
+                .filter_map(|part| span(&file.content, part))
+                .collect::<Vec<_>>();
+            let (replaced, count) = replace_in(&file.content, &ranges, config);
+            if count == 0 {
+                continue;
+            }
+            result_count += count;
+            let name = file.path.display().to_string();
+            match config.dry_run {
+                true => lines.push(unified_diff(&file.content, &replaced, &name, &name)),
+                false => {
+                    replace_content(&file.path, &file.content, replaced)?;
+                    lines.push(format!(
+                        "{}: {}",
+                        name,
+                        ctx.lang.format(Text::Replacements, &[&count])
+                    ));
+                }
+            }
+        }
+
+        out.write_output(&lines.join("\n"))?;
+        Ok(Report { result_count })
+    }
+}
+
+/// Collects the strings of the token (and the tokens nested within it) which are of the
+/// given kinds
+fn collect_parts<'a>(
+    token: &Token<'a>,
+    scopes: &[ReplaceScope],
+    tags: &TagNormalizer,
+    parts: &mut Vec<&'a str>,
+) {
+    let in_scope = |scope| scopes.contains(&scope);
+    if let Some(tag) = tags.tag(token) {
+        if in_scope(ReplaceScope::Tags) {
+            parts.push(tag);
+        }
+        return;
+    }
+    match token {
+        Token::Text(s) if in_scope(ReplaceScope::Text) => parts.push(s),
+        Token::MarkdownExternalLink {
+            title: text,
+            url: link,
+        }
+        | Token::MarkdownInternalLink { label: text, link }
+        | Token::Image {
+            alt: text,
+            url: link,
+        } => {
+            if in_scope(ReplaceScope::Text) {
+                parts.push(text);
+            }
+            if in_scope(ReplaceScope::Links) {
+                parts.push(link);
+            }
+        }
+        Token::Link(s) | Token::Embed(s) | Token::RawHyperlink(s)
+            if in_scope(ReplaceScope::Links) =>
+        {
+            parts.push(s)
+        }
+        t => t
+            .children()
+            .iter()
+            .for_each(|t| collect_parts(t, scopes, tags, parts)),
+    }
+}
+
+/// Byte range of a string within the content it was sliced from, `None` if it is not part
+/// of the content
+fn span(content: &str, part: &str) -> Option<Range<usize>> {
+    let start = (part.as_ptr() as usize).checked_sub(content.as_ptr() as usize)?;
+    let end = start + part.len();
+    (end <= content.len()).then_some(start..end)
+}
+
+/// The content with all occurrences of the string within the ranges replaced, and the
+/// number of replacements
+fn replace_in(content: &str, ranges: &[Range<usize>], config: &ReplaceConfig) -> (String, usize) {
+    let mut matches = ranges
+        .iter()
+        .flat_map(|range| {
+            content[range.clone()]
+                .match_indices(&config.find)
+                .map(move |(i, _)| range.start + i)
+        })
+        .collect::<Vec<usize>>();
+    matches.sort();
+    matches.dedup();
+
+    let mut replaced = String::with_capacity(content.len());
+    let mut end = 0;
+    let mut count = 0;
+    for start in matches {
+        // Nested tokens might overlap
+        if start < end {
+            continue;
+        }
+        replaced += &content[end..start];
+        replaced += &config.replacement;
+        end = start + config.find.len();
+        count += 1;
+    }
+    replaced += &content[end..];
+    (replaced, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_replace() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("notes.md");
+        let content = "---\ntags: [rust]\n---\n# 2024-05-20 rust\n\n@rust talk about rust, \
+                       see [[rust]] and `rust`\n- [rust](https://rust-lang.org) #rust\n\
+                       ```\nrust\n```\n";
+        fs::write(&path, content)?;
+
+        let ctx = ParseContext::default();
+        let run = |config: ReplaceConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = ReplaceCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+        let builder = || {
+            ReplaceConfig::builder()
+                .input(&path)
+                .replace("rust", "Rust")
+        };
+
+        let (diff, count) = run(builder().dry_run(true).build())?;
+        assert_eq!(count, 3);
+        assert!(diff.contains("+@rust talk about Rust, see [[rust]] and `rust`"));
+        assert_eq!(fs::read_to_string(&path)?, content);
+
+        assert_eq!(
+            run(builder().scope(ReplaceScope::Tags).build())?,
+            (format!("{}: Replacements: 1", path.display()), 1)
+        );
+        run(builder()
+            .scope(ReplaceScope::Links)
+            .scope(ReplaceScope::Text)
+            .build())?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "---\ntags: [rust]\n---\n# 2024-05-20 Rust\n\n@Rust talk about Rust, see \
+             [[Rust]] and `rust`\n- [Rust](https://Rust-lang.org) #rust\n```\nrust\n```\n"
+        );
+        assert_eq!(run(builder().build())?.1, 0);
+        Ok(())
+    }
+}
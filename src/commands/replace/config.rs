@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct ReplaceConfig {
+    /// Paths of the files to change (or of directories containing them)
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub find: String,
+    pub replacement: String,
+    /// Kinds of tokens the replacements are made in
+    pub scopes: Vec<ReplaceScope>,
+    /// Only show the changes (as unified diff per file) instead of writing them to the files
+    pub dry_run: bool,
+}
+
+impl ReplaceConfig {
+    pub fn builder() -> ReplaceConfigBuilder {
+        ReplaceConfigBuilder::default()
+    }
+}
+
+/// Kinds of tokens text is replaced in, code (`` `code` `` and code blocks) is never
+/// changed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplaceScope {
+    /// Plain text, also within headings, lists, tasks, tables and the titles of links
+    Text,
+    /// The targets of links (`[[Note]]`, `![[Note]]`, `[title](url)`, images and raw URLs)
+    Links,
+    /// The names of tags (in the tag style of the notes, see `--tag-style`)
+    Tags,
+}
+
+/// Builder for [`ReplaceConfig`]
+#[derive(Clone, Debug)]
+pub struct ReplaceConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    find: String,
+    replacement: String,
+    scopes: Vec<ReplaceScope>,
+    dry_run: bool,
+}
+
+impl Default for ReplaceConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            find: String::new(),
+            replacement: String::new(),
+            scopes: vec![],
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for ReplaceConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl ReplaceConfigBuilder {
+    /// Replace `find` by `replacement`
+    pub fn replace<F: Into<String>, R: Into<String>>(mut self, find: F, replacement: R) -> Self {
+        self.find = find.into();
+        self.replacement = replacement.into();
+        self
+    }
+
+    /// Replace within this kind of tokens as well (only within text if none is given)
+    pub fn scope(mut self, scope: ReplaceScope) -> Self {
+        if !self.scopes.contains(&scope) {
+            self.scopes.push(scope);
+        }
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> ReplaceConfig {
+        ReplaceConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            find: self.find,
+            replacement: self.replacement,
+            scopes: match self.scopes.is_empty() {
+                true => vec![ReplaceScope::Text],
+                false => self.scopes,
+            },
+            dry_run: self.dry_run,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+
+use super::config::{ReviewConfig, ReviewPeriod};
+use crate::{
+    commands::{
+        add_days,
+        io::OutputSink,
+        locale::{Language, Text},
+        query::{DateRange, Query},
+        tasks::stats::TaskStats,
+        Command, ParseContext, Report,
+    },
+    models::{Section, TaskStatus, Token},
+};
+
+/// A report of a week or month for a review: the sections of the period grouped by tag,
+/// the done and open tasks written in the period and the tags used for the first time.
+pub struct ReviewCommand {
+    pub config: ReviewConfig,
+}
+
+/// What was written in the period
+#[derive(Debug, Default)]
+struct Review<'s, 'a> {
+    /// The sections by tag, sections without tags have the key `None`
+    sections: BTreeMap<Option<String>, Vec<&'s Section<'a>>>,
+    done_tasks: Vec<String>,
+    open_tasks: Vec<String>,
+    task_stats: TaskStats,
+    new_tags: BTreeSet<String>,
+}
+
+impl Command for ReviewCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let period = config
+            .period
+            .unwrap_or_else(|| ReviewPeriod::week_of(ctx.clock.today()));
+        let dates = period.dates();
+
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+        let in_period = Query {
+            dates,
+            ..Default::default()
+        }
+        .sections(&sections, &ctx.tags);
+        let before = Query {
+            dates: DateRange::new(None, dates.from.and_then(|d| add_days(d, -1))),
+            ..Default::default()
+        }
+        .sections(&sections, &ctx.tags);
+        if in_period.is_empty() {
+            log::warn!("No sections written in {}!", period);
+            return Ok(Report::default());
+        }
+
+        let known_tags = before
+            .iter()
+            .flat_map(|m| m.section.tags.iter())
+            .collect::<BTreeSet<_>>();
+        let mut review = Review::default();
+        for section in in_period.iter().map(|m| &m.section) {
+            review.add(section);
+            review.new_tags.extend(
+                section
+                    .tags
+                    .iter()
+                    .filter(|t| !known_tags.contains(t))
+                    .cloned(),
+            );
+        }
+
+        out.write_output(&review_to_string(&review, &period, &dates, ctx.lang))?;
+        Ok(Report {
+            result_count: in_period.len(),
+        })
+    }
+}
+
+impl<'s, 'a> Review<'s, 'a> {
+    /// Adds the section and its tasks (without the ones of its subsections)
+    fn add(&mut self, section: &'s Section<'a>) {
+        if section.tags.is_empty() {
+            // E.g. the heading of a day which only contains subsections
+            if !section.paragraphs().is_empty() {
+                self.sections.entry(None).or_default().push(section);
+            }
+        }
+        for tag in &section.tags {
+            self.sections
+                .entry(Some(tag.clone()))
+                .or_default()
+                .push(section);
+        }
+        for token in &section.content {
+            if let Token::Task { status, .. } = token {
+                self.task_stats.add(status);
+                match status {
+                    TaskStatus::Done => self.done_tasks.push(token.to_markdown_string()),
+                    _ => self.open_tasks.push(token.to_markdown_string()),
+                }
+            }
+        }
+    }
+}
+
+fn review_to_string(
+    review: &Review,
+    period: &ReviewPeriod,
+    dates: &DateRange,
+    lang: Language,
+) -> String {
+    let (Some(first), Some(last)) = (dates.from, dates.until) else {
+        return String::new();
+    };
+    let mut parts = vec![format!("# {} ({} - {})", period, first, last)];
+
+    let mut lines = vec![format!("## {}", lang.text(Text::Sections))];
+    // Sections without tags last
+    let groups = review
+        .sections
+        .iter()
+        .filter(|(tag, _)| tag.is_some())
+        .chain(review.sections.iter().filter(|(tag, _)| tag.is_none()));
+    for (tag, sections) in groups {
+        lines.push(String::new());
+        lines.push(match tag {
+            Some(tag) => format!("### @{}", tag),
+            None => format!("### {}", lang.text(Text::Untagged)),
+        });
+        lines.push(String::new());
+        let mut sections = sections.clone();
+        sections.sort_by_key(|s| s.date);
+        lines.extend(
+            sections
+                .iter()
+                .map(|s| match (s.section_type.is_top_level(), s.date) {
+                    (true, Some(date)) => format!("- {}", date),
+                    (false, Some(date)) => format!("- {}: {}", date, s.title_string()),
+                    (_, None) => format!("- {}", s.title_string()),
+                }),
+        );
+    }
+    parts.push(lines.join("\n"));
+
+    let mut lines = vec![
+        format!("## {}", lang.text(Text::Tasks)),
+        String::new(),
+        lang.format(
+            Text::ProjectTasks,
+            &[&review.task_stats.unfinished(), &review.task_stats.done],
+        ),
+    ];
+    if review.task_stats.total() > 0 {
+        lines.push(String::new());
+        lines.extend(review.done_tasks.iter().cloned());
+        lines.extend(review.open_tasks.iter().cloned());
+    }
+    parts.push(lines.join("\n"));
+
+    if !review.new_tags.is_empty() {
+        let tags = review
+            .new_tags
+            .iter()
+            .map(|t| format!("@{}", t))
+            .collect::<Vec<String>>();
+        parts.push(format!(
+            "## {}\n\n{}",
+            lang.text(Text::NewTags),
+            tags.join(", ")
+        ));
+    }
+    parts.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder};
+
+    #[test]
+    fn test_review_period() {
+        let period = |s: &str| s.parse::<ReviewPeriod>();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+
+        let week = period("2024-W12").unwrap();
+        assert_eq!(week.to_string(), "2024-W12");
+        assert_eq!(
+            week.dates(),
+            DateRange::new(date(2024, 3, 18), date(2024, 3, 24))
+        );
+        assert_eq!(
+            period("2024-02").unwrap().dates(),
+            DateRange::new(date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(period("2023-12").unwrap().dates().until, date(2023, 12, 31));
+        assert_eq!(
+            ReviewPeriod::week_of(date(2024, 12, 30).unwrap()).to_string(),
+            "2025-W01"
+        );
+        assert!(period("2024-W54").is_err());
+        assert!(period("2024-13").is_err());
+        assert!(period("March").is_err());
+    }
+
+    #[test]
+    fn test_review() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2024-03-15\n\n## Standup\n\n@work\n\n\
+             # 2024-03-18\n\n## Standup\n\n@work\nDONE: Send minutes\n\n\
+             ## Call\n\n@work @roger\nTODO: Book flight\n\n\
+             # 2024-03-20\n\nGym\n\n# 2024-03-25\n\n## Planning\n\n@work\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 3, 21).unwrap()));
+        let run = |config: ReviewConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = ReviewCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let (output, count) = run(ReviewConfig::builder().input(&path).build())?;
+        assert_eq!(
+            output,
+            "# 2024-W12 (2024-03-18 - 2024-03-24)\n\n## Sections\n\n\
+             ### @roger\n\n- 2024-03-18: Call\n\n\
+             ### @work\n\n- 2024-03-18: Standup\n- 2024-03-18: Call\n\n\
+             ### Without tags\n\n- 2024-03-20\n\n\
+             ## Tasks\n\n1 open, 1 done\n\nDONE: Send minutes\nTODO: Book flight\n\n\
+             ## New tags\n\n@roger"
+        );
+        assert_eq!(count, 4);
+
+        let month = ReviewConfig::builder()
+            .input(&path)
+            .period("2024-02".parse()?)
+            .build();
+        assert_eq!(run(month)?.1, 0);
+        Ok(())
+    }
+}
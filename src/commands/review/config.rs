@@ -0,0 +1,148 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::{
+    commands::{add_days, query::DateRange, InputBuilder},
+    models::ConfigError,
+};
+
+#[derive(Clone, Debug)]
+pub struct ReviewConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// The reviewed period, `None` for the week containing today
+    pub period: Option<ReviewPeriod>,
+}
+
+impl ReviewConfig {
+    pub fn builder() -> ReviewConfigBuilder {
+        ReviewConfigBuilder::default()
+    }
+}
+
+/// Builder for [`ReviewConfig`]
+#[derive(Clone, Debug)]
+pub struct ReviewConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    period: Option<ReviewPeriod>,
+}
+
+impl Default for ReviewConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            period: None,
+        }
+    }
+}
+
+impl InputBuilder for ReviewConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl ReviewConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Review this period instead of the current week
+    pub fn period(mut self, period: ReviewPeriod) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    pub fn build(self) -> ReviewConfig {
+        ReviewConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            period: self.period,
+        }
+    }
+}
+
+/// A reviewed week or month, written like `2024-W12` (ISO week) or `2024-03`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReviewPeriod {
+    Week { year: i32, week: u32 },
+    Month { year: i32, month: u32 },
+}
+
+impl ReviewPeriod {
+    /// The ISO week containing the day
+    pub fn week_of(day: NaiveDate) -> Self {
+        let week = day.iso_week();
+        Self::Week {
+            year: week.year(),
+            week: week.week(),
+        }
+    }
+
+    /// The days of the period (both inclusive)
+    pub fn dates(&self) -> DateRange {
+        let (first, last) = match *self {
+            Self::Week { year, week } => (
+                NaiveDate::from_isoywd_opt(year, week, Weekday::Mon),
+                NaiveDate::from_isoywd_opt(year, week, Weekday::Sun),
+            ),
+            Self::Month { year, month } => {
+                let next = match month {
+                    12 => NaiveDate::from_ymd_opt(year + 1, 1, 1),
+                    _ => NaiveDate::from_ymd_opt(year, month + 1, 1),
+                };
+                (
+                    NaiveDate::from_ymd_opt(year, month, 1),
+                    next.and_then(|d| add_days(d, -1)),
+                )
+            }
+        };
+        DateRange::new(first, last)
+    }
+}
+
+impl FromStr for ReviewPeriod {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ConfigError::InvalidDateError(s.to_string());
+        let (year, rest) = s.trim().split_once('-').ok_or_else(error)?;
+        let year = year.parse::<i32>().map_err(|_| error())?;
+        let period = match rest.strip_prefix(['W', 'w']) {
+            Some(week) => Self::Week {
+                year,
+                week: week.parse().map_err(|_| error())?,
+            },
+            None => Self::Month {
+                year,
+                month: rest.parse().map_err(|_| error())?,
+            },
+        };
+        let dates = period.dates();
+        match dates.from.is_some() && dates.until.is_some() {
+            true => Ok(period),
+            false => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for ReviewPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Week { year, week } => write!(f, "{}-W{:02}", year, week),
+            Self::Month { year, month } => write!(f, "{}-{:02}", year, month),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{fs, path::Path};
+
+use crate::{commands::diff::unified_diff, models::MDPError};
+
+/// Replacement of `old` by `new` at a line and column (both starting at 1, the column
+/// counted in characters like [`crate::models::Origin`])
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replacement {
+    pub line: usize,
+    pub column: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// A line changed by replacements
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineChange {
+    pub line: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// Replaces `old` by `new` at the given position of a file, see [`replace_all`].
+pub fn replace_at(
+    path: &Path,
+    line: usize,
+    column: usize,
+    old: &str,
+    new: &str,
+) -> Result<(), MDPError> {
+    let replacement = Replacement {
+        line,
+        column,
+        old: old.to_string(),
+        new: new.to_string(),
+    };
+    replace_all(path, &[replacement])
+}
+
+/// Applies all replacements to a file at once.
+///
+/// All other bytes of the file (e.g. line endings) are kept as they are. Fails without
+/// touching the file if the `old` text of a replacement is not found at its position, e.g.
+/// because the file was changed since it was parsed. The file is replaced atomically by
+/// writing a temporary file next to it first.
+pub fn replace_all(path: &Path, replacements: &[Replacement]) -> Result<(), MDPError> {
+    let content = read(path)?;
+    let rewritten = apply(path, &content, replacements)?;
+    write_atomically(path, rewritten)
+}
+
+/// Replaces the whole content of a file, e.g. after reordering its sections.
+///
+/// Fails without touching the file if its content is not `old` anymore. The file is
+/// replaced atomically like by [`replace_all`].
+pub fn replace_content(path: &Path, old: &str, new: String) -> Result<(), MDPError> {
+    if read(path)? != old {
+        return Err(write_error(
+            path,
+            "the file was changed since it was read".to_string(),
+        ));
+    }
+    write_atomically(path, new)
+}
+
+/// The lines the replacements would change, without touching the file
+pub fn preview(path: &Path, replacements: &[Replacement]) -> Result<Vec<LineChange>, MDPError> {
+    let content = read(path)?;
+    let rewritten = apply(path, &content, replacements)?;
+
+    Ok(content
+        .split('\n')
+        .zip(rewritten.split('\n'))
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(i, (old, new))| LineChange {
+            line: i + 1,
+            old: old.trim_end_matches('\r').to_string(),
+            new: new.trim_end_matches('\r').to_string(),
+        })
+        .collect())
+}
+
+/// How the replacements would change the file (as unified diff), without touching it
+pub fn diff(path: &Path, replacements: &[Replacement]) -> Result<String, MDPError> {
+    let content = read(path)?;
+    let rewritten = apply(path, &content, replacements)?;
+    let name = path.display().to_string();
+    Ok(unified_diff(&content, &rewritten, &name, &name))
+}
+
+fn write_error(path: &Path, details: String) -> MDPError {
+    MDPError::IOWriteError {
+        path: path.to_path_buf(),
+        details,
+    }
+}
+
+fn read(path: &Path) -> Result<String, MDPError> {
+    fs::read_to_string(path).map_err(|e| MDPError::IOReadError {
+        path: path.to_path_buf(),
+        details: e.to_string(),
+    })
+}
+
+fn apply(path: &Path, content: &str, replacements: &[Replacement]) -> Result<String, MDPError> {
+    let mut offsets = vec![];
+    for r in replacements {
+        let offset = byte_offset(content, r.line, r.column)
+            .filter(|offset| content[*offset..].starts_with(&r.old))
+            .ok_or_else(|| {
+                write_error(
+                    path,
+                    format!(
+                        "'{}' was not found at line {}, column {} (was the file changed?)",
+                        r.old, r.line, r.column
+                    ),
+                )
+            })?;
+        offsets.push((offset, r));
+    }
+
+    // Replace from the end, so the offsets of the remaining replacements stay valid
+    offsets.sort_by_key(|(offset, _)| std::cmp::Reverse(*offset));
+    let mut rewritten = content.to_string();
+    let mut previous_start = content.len();
+    for (offset, r) in offsets {
+        if offset + r.old.len() > previous_start {
+            return Err(write_error(path, "overlapping replacements".to_string()));
+        }
+        rewritten.replace_range(offset..offset + r.old.len(), &r.new);
+        previous_start = offset;
+    }
+    Ok(rewritten)
+}
+
+fn write_atomically(path: &Path, content: String) -> Result<(), MDPError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| write_error(path, "not a file".to_string()))?;
+    let tmp_path = path.with_file_name(format!(".{}.mdp-tmp", file_name.to_string_lossy()));
+    let permissions = fs::metadata(path)
+        .map_err(|e| write_error(path, e.to_string()))?
+        .permissions();
+
+    fs::write(&tmp_path, content)
+        .and_then(|_| fs::set_permissions(&tmp_path, permissions))
+        .and_then(|_| fs::rename(&tmp_path, path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            write_error(path, e.to_string())
+        })
+}
+
+/// Byte offset of the given line and column (in characters), if the position exists
+fn byte_offset(content: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => content.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let line_content = content[line_start..].split('\n').next()?;
+
+    match column {
+        0 => None,
+        _ => line_content
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(line_content.len()))
+            .nth(column - 1)
+            .map(|i| line_start + i),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_replace_at() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        let content = "# 2023-10-10\r\n\r\nÄ TODO: Süß\r\nTODO: Pay the bill\r\n";
+        fs::write(&path, content)?;
+
+        replace_at(&path, 3, 3, "TODO:", "DONE:")?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "# 2023-10-10\r\n\r\nÄ DONE: Süß\r\nTODO: Pay the bill\r\n"
+        );
+
+        assert!(replace_at(&path, 3, 3, "TODO:", "DONE:").is_err());
+        assert!(replace_at(&path, 9, 1, "TODO:", "DONE:").is_err());
+        assert_eq!(fs::read_dir(dir.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_all_and_preview() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(&path, "TODO: A\r\nText\r\nTODO: B\r\n")?;
+        let replacement = |line| Replacement {
+            line,
+            column: 1,
+            old: "TODO:".to_string(),
+            new: "TODO UNTIL 2023-10-10:".to_string(),
+        };
+        let replacements = [replacement(3), replacement(1)];
+
+        assert_eq!(
+            preview(&path, &replacements)?,
+            vec![
+                LineChange {
+                    line: 1,
+                    old: "TODO: A".to_string(),
+                    new: "TODO UNTIL 2023-10-10: A".to_string(),
+                },
+                LineChange {
+                    line: 3,
+                    old: "TODO: B".to_string(),
+                    new: "TODO UNTIL 2023-10-10: B".to_string(),
+                },
+            ]
+        );
+        assert_eq!(fs::read_to_string(&path)?, "TODO: A\r\nText\r\nTODO: B\r\n");
+
+        replace_all(&path, &replacements)?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "TODO UNTIL 2023-10-10: A\r\nText\r\nTODO UNTIL 2023-10-10: B\r\n"
+        );
+
+        // Overlapping replacements are rejected as a whole
+        fs::write(&path, "TODO: A\r\n")?;
+        assert!(replace_all(&path, &[replacement(1), replacement(1)]).is_err());
+        assert_eq!(fs::read_to_string(&path)?, "TODO: A\r\n");
+        Ok(())
+    }
+}
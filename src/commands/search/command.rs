@@ -1,146 +1,326 @@
-use std::cmp::Ordering;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 
 use anyhow::Result;
 
-use super::config::{SearchTerm, SectionOrderingCriterion, SearchConfig, TagSearchMode};
+use super::{
+    config::{SearchConfig, SearchGrouping, SearchTerm, SectionOrderingCriterion, TagSearchMode},
+    highlight::MatchHighlighter,
+};
 use crate::{
-    commands::io::{FileReader, OutputWriter},
-    models::{MarkdownTokenizer, Section, SectionBuilder, SectionType},
+    commands::{
+        io::{DirectoryWriter, FileWriterConfig, MarkdownFile, OutputSink},
+        locale::{Language, Text},
+        oneline::{location, oneline, ResultFormat},
+        query::QueryMatch,
+        titles::section_urls,
+        transclusion::Transcluder,
+        util::stable_hash,
+        Command, ParseContext, Report,
+    },
+    models::{MDPError, PlainStyler, Section, TokenStyler},
 };
 
-pub fn run<T, S, R>(
-    config: SearchConfig,
-    tokenizer: T,
-    section_builder: S,
-    reader: R,
-    writers: Vec<Box<dyn OutputWriter>>,
-) -> Result<()>
-where
-    T: MarkdownTokenizer,
-    S: SectionBuilder,
-    R: FileReader,
-{
-    let markdown_string = reader.read(config.input_path.clone())?;
-    let tokens = tokenizer.tokenize(&markdown_string)?;
-    let sections = section_builder.sections_from_tokens(tokens)?;
-
-    let results = search(
-        sections,
-        config.search_terms.clone(),
-        config.search_mode.clone(),
-        config.from,
-        config.until,
-    );
-
-    let search_result_string = search_results_to_string(results, config.ordering.clone());
-    let search_summary = search_summary(config.clone());
-    let output_string = format!("{}\n\n{}", search_result_string, search_summary);
-
-    for writer in writers {
-        writer.write_output(&output_string)?;
-    }
-
-    Ok(())
+pub struct SearchCommand {
+    pub config: SearchConfig,
 }
 
-#[derive(Clone, Debug)]
-pub struct SearchResultSection<'a> {
-    pub matched_tags: Vec<String>,
-    pub section: Section<'a>,
-}
-
-fn search(
-    sections: Vec<Section>,
-    search_terms: Vec<SearchTerm>,
-    mode: TagSearchMode,
-    from: Option<NaiveDate>,
-    until: Option<NaiveDate>,
-) -> Vec<SearchResultSection> {
-    let mut results = vec![];
-    for s in sections {
-        if tags_match(&s.tags, &search_terms, &mode) && in_date_range(s.date, from, until) {
-            results.push(SearchResultSection {
-                section: s.clone(),
-                matched_tags: matched_tags(&s.tags, &search_terms),
-            });
-        }
-        results.append(&mut search(
-            s.subsections,
-            search_terms.clone(),
-            mode.clone(),
-            from,
-            until,
-        ))
+impl Command for SearchCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let files = ctx.read(self.config.input_path.clone())?;
+        self.run_on_files(ctx, &files, out)
     }
-    results
 }
 
-fn tags_match(tags: &[String], tag_search_terms: &[SearchTerm], mode: &TagSearchMode) -> bool {
-    match mode {
-        TagSearchMode::Or => tag_search_terms.iter().any(|t| tags.contains(&t.inner())),
-        TagSearchMode::And => tag_search_terms.iter().all(|t| tags.contains(&t.inner())),
-    }
-}
-
-fn matched_tags(tags: &[String], tag_search_terms: &[SearchTerm]) -> Vec<String> {
-    tag_search_terms
-        .iter()
-        .filter_map(|t| {
-            if tags.contains(&t.inner()) {
-                Some(t.inner())
-            } else {
-                None
+impl SearchCommand {
+    /// Search the given files instead of the files at the input paths of the config
+    pub fn run_on_files(
+        &self,
+        ctx: &ParseContext,
+        files: &[MarkdownFile],
+        out: &mut dyn OutputSink,
+    ) -> Result<Report> {
+        let config = &self.config;
+        let sections = ctx.sections(files)?;
+        let results = config.query().sections(&sections, &ctx.tags);
+        let results = ordered_search_result_sections(results, &config.ordering, config.reverse);
+        let result_count = results.len();
+        for r in &results {
+            ctx.include(&r.section);
+        }
+        let mut styler: &dyn TokenStyler = &PlainStyler;
+        if config.fetch_titles {
+            match &ctx.link_titles {
+                Some(titles) => {
+                    titles.resolve(results.iter().flat_map(|r| section_urls(&r.section)));
+                    styler = titles;
+                }
+                None => log::warn!("Titles can not be fetched here, the URLs are kept as they are"),
             }
-        })
-        .collect()
-}
-
-fn in_date_range(date: NaiveDate, from: Option<NaiveDate>, until: Option<NaiveDate>) -> bool {
-    if let Some(from) = from {
-        if date < from {
-            return false;
         }
-    }
-    if let Some(until) = until {
-        if date > until {
-            return false;
+        let highlighter = MatchHighlighter::new(&config.search_terms, &ctx.tags, styler);
+        if config.highlight {
+            styler = &highlighter;
+        }
+        if config.format == ResultFormat::Oneline {
+            let lines = results
+                .iter()
+                .map(|r| result_line(&r.section))
+                .collect::<Vec<String>>();
+            out.write_output(&lines.join("\n"))?;
+            return Ok(Report { result_count });
         }
+        let transcluder = Transcluder::new(&sections).with_styler(styler);
+        let frontmatter = match config.frontmatter {
+            true => frontmatter(config, files, ctx.clock.now()),
+            false => String::new(),
+        };
+
+        if let Some(dir) = &config.split_output {
+            let paths = write_split_results(results, &transcluder, &frontmatter, dir)?;
+            let path_strings: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+            out.write_output(&path_strings.join("\n"))?;
+            return Ok(Report { result_count });
+        }
+
+        let search_result_string = search_results_to_string(
+            results,
+            config.group_by.as_ref(),
+            &config.search_terms,
+            &transcluder,
+        );
+        let search_summary = search_summary(config.clone(), ctx.lang);
+        let output_string = format!(
+            "{}{}\n\n{}",
+            frontmatter, search_result_string, search_summary
+        );
+
+        out.write_output(&output_string)?;
+
+        Ok(Report { result_count })
     }
-    true
 }
 
+/// Render the results as markdown, embeds within them are expanded using `transcluder`.
+///
+/// Grouped by tag, every search term gets a `# {term}` section containing the results
+/// which matched it (with their headings nested one level deeper), terms without results
+/// are left out.
 fn search_results_to_string(
-    results: Vec<SearchResultSection>,
-    ordering: SectionOrderingCriterion,
+    results: Vec<QueryMatch>,
+    grouping: Option<&SearchGrouping>,
+    search_terms: &[SearchTerm],
+    transcluder: &Transcluder,
 ) -> String {
-    let ordered_results = ordered_search_result_sections(results, ordering);
+    match grouping {
+        None => ordered_results_to_string(results, transcluder),
+        Some(SearchGrouping::Tag) => search_terms
+            .iter()
+            .filter_map(|term| {
+                let group = results
+                    .iter()
+                    .filter(|r| r.matched_terms.contains(&term.inner()))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if group.is_empty() {
+                    return None;
+                }
+                let group_string = ordered_results_to_string(group, transcluder);
+                Some(format!(
+                    "# {}\n\n{}",
+                    term.inner(),
+                    demote_headings(&group_string)
+                ))
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n"),
+    }
+}
 
+/// Render the (already ordered) results as a list, consecutive results of the same day share
+/// one date heading.
+fn ordered_results_to_string(results: Vec<QueryMatch>, transcluder: &Transcluder) -> String {
     let mut section_strings = Vec::<String>::new();
-    let mut previous_section_date: Option<NaiveDate> = None;
+    let mut previous_section_date: Option<String> = None;
 
-    for r in ordered_results.iter() {
+    for r in results.iter() {
         let mut s = String::new();
 
-        if r.section.section_type != SectionType::H1 {
-            if previous_section_date.is_none() || previous_section_date.unwrap() != r.section.date {
-                s += &format!("# {}\n\n", r.section.date);
+        // Sections without a date have no date heading
+        if !r.section.section_type.is_top_level() && r.section.date.is_some() {
+            let date = r.section.date_string();
+            if previous_section_date.as_ref() != Some(&date) {
+                s += &format!("# {}\n\n", date);
             } else {
                 s += &format!("{}\n\n", section_strings.pop().unwrap().to_owned());
             }
         }
-        s += r.section.to_string().trim();
+        s += transcluder.render(&r.section).trim();
         section_strings.push(s);
 
-        previous_section_date = Some(r.section.date);
+        previous_section_date = Some(r.section.date_string());
     }
 
     section_strings.join("\n\n---\n\n")
 }
 
-fn search_summary(config: SearchConfig) -> String {
-    let tags = config.search_terms.iter().map(|t| t.inner()).collect::<Vec<_>>().join(", ");
+/// The section as line of `--format oneline`: its date, tags (comma separated), title and
+/// location
+fn result_line(section: &Section) -> String {
+    oneline(&[
+        section.date_string(),
+        section.tags.join(","),
+        section.plain_title(),
+        location(&section.origin),
+    ])
+}
+
+/// Nest all headings one level deeper (`#` becomes `##`, H6 stays H6)
+fn demote_headings(markdown: &str) -> String {
+    markdown
+        .split('\n')
+        .map(|line| {
+            let level = line.chars().take_while(|c| *c == '#').count();
+            let is_heading = (1..6).contains(&level) && line[level..].starts_with(' ');
+            match is_heading {
+                true => format!("#{}", line),
+                false => line.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Write every result as a standalone markdown document (starting with `frontmatter` and
+/// its date heading) to its own file in `dir`, named by the date and the title of the
+/// section.
+fn write_split_results(
+    results: Vec<QueryMatch>,
+    transcluder: &Transcluder,
+    frontmatter: &str,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, MDPError> {
+    let writer = DirectoryWriter {
+        dir: dir.to_path_buf(),
+        config: FileWriterConfig::default(),
+    };
+    let mut names = HashSet::new();
+    let mut paths = vec![];
+
+    for r in results {
+        let mut document = frontmatter.to_string();
+        let mut stem_parts = Vec::from_iter(r.section.date.map(|d| d.to_string()));
+        if !r.section.section_type.is_top_level() && r.section.date.is_some() {
+            document += &format!("# {}\n\n", r.section.date_string());
+        }
+        // The title of a section without a date names its file
+        if !r.section.section_type.is_top_level() || r.section.date.is_none() {
+            stem_parts.push(slug(&r.section.title_string()));
+        }
+        stem_parts.retain(|p| !p.is_empty());
+        let stem = match stem_parts.is_empty() {
+            true => "section".to_string(),
+            false => stem_parts.join("-"),
+        };
+        document += transcluder.render(&r.section).trim();
+        document.push('\n');
+
+        // Sections with the same date and title get a running number
+        let mut name = format!("{}.md", stem);
+        let mut n = 1;
+        while !names.insert(name.clone()) {
+            n += 1;
+            name = format!("{}-{}.md", stem, n);
+        }
+        paths.push(writer.write_file(&name, &document)?);
+    }
+
+    Ok(paths)
+}
+
+/// The lowercase alphanumeric words of `title` joined by `-`
+fn slug(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// YAML frontmatter describing the query and the input the results were generated from
+fn frontmatter(config: &SearchConfig, files: &[MarkdownFile], generated: NaiveDateTime) -> String {
+    let terms: Vec<String> = config.search_terms.iter().map(|t| t.inner()).collect();
+    let date = |d: Option<NaiveDate>| d.map_or("null".to_string(), |d| d.to_string());
+    let mode = match config.search_mode {
+        TagSearchMode::Or => "or",
+        TagSearchMode::And => "and",
+    };
+
+    format!(
+        "---\ngenerator: mdp {}\nterms: {}\nmode: {}\nscope: {}\nfrom: {}\nuntil: {}\n\
+         ordering: {}\ngenerated: {}\ninput_hash: {}\nconfig: {}\n---\n\n",
+        env!("CARGO_PKG_VERSION"),
+        // A JSON array is a valid YAML flow sequence
+        serde_json::to_string(&terms).unwrap_or_default(),
+        mode,
+        config.scope,
+        date(config.from),
+        date(config.until),
+        ordering_string(config),
+        generated.format("%Y-%m-%dT%H:%M:%S"),
+        input_hash(files),
+        // Allows `mdp outdated` to check and regenerate the output
+        serde_json::to_string(config).unwrap_or_default(),
+    )
+}
+
+/// A hash of the paths and contents of the files, which changes as soon as any of them does
+pub(crate) fn input_hash(files: &[MarkdownFile]) -> String {
+    let hash = stable_hash(files.iter().flat_map(|file| {
+        format!("{}\0{}\0", file.path.display(), file.content).into_bytes()
+    }));
+    format!("{:016x}", hash)
+}
+
+/// The origin of a generated file as recorded in its frontmatter
+#[derive(Clone, Debug)]
+pub struct Provenance {
+    pub generated: String,
+    pub input_hash: String,
+    pub config: SearchConfig,
+}
+
+impl Provenance {
+    /// Read the frontmatter written by `mdp search --frontmatter`, `None` if the content does
+    /// not start with one
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+        if lines.next()? != "---" {
+            return None;
+        }
+        let fields: HashMap<&str, &str> = lines
+            .take_while(|l| *l != "---")
+            .filter_map(|l| l.split_once(": "))
+            .collect();
+        if !fields.get("generator")?.starts_with("mdp ") {
+            return None;
+        }
+        Some(Self {
+            generated: fields.get("generated")?.to_string(),
+            input_hash: fields.get("input_hash")?.to_string(),
+            config: serde_json::from_str(fields.get("config")?).ok()?,
+        })
+    }
+}
+
+fn search_summary(config: SearchConfig, lang: Language) -> String {
+    let terms = config.search_terms.iter().map(|t| t.inner()).collect::<Vec<_>>().join(", ");
     let mode = match config.search_mode {
         TagSearchMode::Or => "OR",
         TagSearchMode::And => "AND",
@@ -153,39 +333,574 @@ fn search_summary(config: SearchConfig) -> String {
         Some(d) => d.to_string(),
         None => "".to_string(),
     };
-    let ordering = match config.ordering {
-        SectionOrderingCriterion::Date => "date",
-        SectionOrderingCriterion::Relevance => "relevance",
-    };
+    let ordering = ordering_string(&config);
 
     format!(
-        "SEARCHED FOR TAGS: {}\nMODE: {}\nFROM: {}\nTO: {}\nORDERING: {}\n",
-        tags,
+        "{}: {}\n{}: {}\n{}: {}\n{}: {}\n{}: {}\n{}: {}\n",
+        lang.text(Text::SearchedFor),
+        terms,
+        lang.text(Text::SearchMode),
         mode,
+        lang.text(Text::SearchScope),
+        config.scope,
+        lang.text(Text::SearchFrom),
         from,
+        lang.text(Text::SearchTo),
         until,
+        lang.text(Text::SearchOrdering),
         ordering,
     )
 }
 
-fn ordered_search_result_sections(
-    results: Vec<SearchResultSection>,
-    ordering: SectionOrderingCriterion,
-) -> Vec<SearchResultSection> {
-    let mut ordered_result = results.clone();
-    match ordering {
-        SectionOrderingCriterion::Relevance => ordered_result.sort_by(|a, b| {
-            match a.matched_tags.len().cmp(&b.matched_tags.len()).reverse() {
-                Ordering::Equal => a.section.date.cmp(&b.section.date),
-                other => other,
-            }
-        }),
-        SectionOrderingCriterion::Date => {
-            ordered_result.sort_by(|a, b| match a.section.date.cmp(&b.section.date) {
-                Ordering::Equal => a.matched_tags.len().cmp(&b.matched_tags.len()).reverse(),
-                other => other,
-            })
-        }
+/// The ordering of the results as shown in the summary and the frontmatter
+fn ordering_string(config: &SearchConfig) -> String {
+    match config.reverse {
+        true => format!("{} (reversed)", config.ordering),
+        false => config.ordering.to_string(),
+    }
+}
+
+/// The results ordered by `ordering`, ties are broken by the criteria following it in
+/// [`SectionOrderingCriterion::keys`] and results equal in all of them keep their order.
+/// `reverse` reverses the resulting order as a whole.
+fn ordered_search_result_sections<'a>(
+    results: Vec<QueryMatch<'a>>,
+    ordering: &SectionOrderingCriterion,
+    reverse: bool,
+) -> Vec<QueryMatch<'a>> {
+    let keys = ordering.keys();
+    let mut ordered_result = results;
+    ordered_result.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| compare_by(key, a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    if reverse {
+        ordered_result.reverse();
     }
     ordered_result
 }
+
+/// Compare two results by a single criterion
+fn compare_by(criterion: &SectionOrderingCriterion, a: &QueryMatch, b: &QueryMatch) -> Ordering {
+    match criterion {
+        SectionOrderingCriterion::Relevance => {
+            a.matched_terms.len().cmp(&b.matched_terms.len()).reverse()
+        }
+        SectionOrderingCriterion::Date => a.section.date.cmp(&b.section.date),
+        SectionOrderingCriterion::Title => a
+            .section
+            .plain_title()
+            .to_lowercase()
+            .cmp(&b.section.plain_title().to_lowercase()),
+        SectionOrderingCriterion::TagCount => {
+            a.section.tags.len().cmp(&b.section.tags.len()).reverse()
+        }
+        SectionOrderingCriterion::FileOrder => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        commands::{
+            query::Query,
+            search::config::{SearchConfigBuilder, SearchScope, SearchTermMatching},
+            tags::normalize::TagNormalizer,
+        },
+        markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+        models::{MarkdownTokenizer, Section, SectionBuilder},
+    };
+
+    const DIARY: &str = "\
+# 2024-01-01
+
+## Standup
+@work
+
+## Lunch
+@meeting
+
+# 2024-01-02
+
+## Planning
+@work
+";
+
+    fn search_query(terms: &[SearchTerm]) -> Query {
+        Query {
+            terms: terms.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn search_titles(
+        sections: &[Section],
+        terms: &[&str],
+        mode: TagSearchMode,
+        scope: SearchScope,
+    ) -> Vec<String> {
+        let terms = terms
+            .iter()
+            .map(|t| SearchTerm::try_from(t.to_string()).unwrap())
+            .collect::<Vec<_>>();
+        let tags = TagNormalizer::default();
+        let query = Query {
+            terms,
+            mode,
+            scope,
+            ..Default::default()
+        };
+        query
+            .sections(sections, &tags)
+            .iter()
+            .map(|r| r.section.title_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_and_scope() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize(DIARY)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let titles =
+            |scope| search_titles(&sections, &["@work", "@meeting"], TagSearchMode::And, scope);
+
+        assert!(titles(SearchScope::Section).is_empty());
+        assert_eq!(titles(SearchScope::Day), vec!["Standup", "Lunch"]);
+        assert_eq!(
+            titles(SearchScope::File),
+            vec!["Standup", "Lunch", "Planning"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_mixed_terms() -> Result<()> {
+        let diary = "\
+# 2024-01-01
+
+## Budget
+Discussed the #finance plan with @roger.
+
+## Review
+The budget was approved.
+
+## Roadmap
+#finance @roger
+";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let titles =
+            |terms: &[&str], mode| search_titles(&sections, terms, mode, SearchScope::Section);
+
+        assert_eq!(
+            titles(&["#finance"], TagSearchMode::Or),
+            vec!["Budget", "Roadmap"]
+        );
+        assert_eq!(
+            titles(&["BUDGET"], TagSearchMode::Or),
+            vec!["Budget", "Review"]
+        );
+        assert_eq!(
+            titles(&["@roger", "budget"], TagSearchMode::And),
+            vec!["Budget"]
+        );
+        assert_eq!(
+            titles(&["approved", "#finance"], TagSearchMode::Or),
+            vec!["Budget", "Review", "Roadmap"]
+        );
+        // Tags and hashtags are part of the text as well
+        assert_eq!(
+            titles(&["roger"], TagSearchMode::Or),
+            vec!["Budget", "Roadmap"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_results_expand_embeds() -> Result<()> {
+        let diary = "\
+# 2024-01-01
+
+## Standup
+@work
+![[#Notes]]
+
+## Notes
+Deploy on Friday.
+";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let terms = vec![SearchTerm::try_from("@work".to_string())?];
+        let tags = TagNormalizer::default();
+        let results = search_query(&terms).sections(&sections, &tags);
+
+        assert_eq!(
+            ordered_results_to_string(results, &Transcluder::new(&sections)),
+            "# 2024-01-01\n\n## Standup\n@work\nDeploy on Friday."
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_regex_and_fuzzy() -> Result<()> {
+        let diary = "# 2024-01-01\n\n## Standup\n@projectX\n\n## Meeting with roger\n\
+                     #budget\n\n## Lunch\n@project\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let titles = |term: &str, matching| -> Result<Vec<String>> {
+            let terms = vec![SearchTerm::new(term.to_string(), &matching)?];
+            let tags = TagNormalizer::default();
+            Ok(search_query(&terms)
+                .sections(&sections, &tags)
+                .iter()
+                .map(|r| r.section.title_string())
+                .collect())
+        };
+
+        assert_eq!(
+            titles("proj.*", SearchTermMatching::Regex)?,
+            vec!["Standup", "Lunch"]
+        );
+        assert_eq!(
+            titles("@^project$", SearchTermMatching::Regex)?,
+            vec!["Lunch"]
+        );
+        assert_eq!(
+            titles("^meeting", SearchTermMatching::Regex)?,
+            vec!["Meeting with roger"]
+        );
+        assert_eq!(
+            titles("#budg", SearchTermMatching::Regex)?,
+            vec!["Meeting with roger"]
+        );
+        assert!(SearchTerm::new("(proj".to_string(), &SearchTermMatching::Regex).is_err());
+
+        assert_eq!(
+            titles("@projcet", SearchTermMatching::Fuzzy(0.8))?,
+            vec!["Lunch"]
+        );
+        assert_eq!(
+            titles("meting", SearchTermMatching::Fuzzy(0.8))?,
+            vec!["Meeting with roger"]
+        );
+        assert_eq!(
+            titles("meting", SearchTermMatching::Fuzzy(0.9))?,
+            Vec::<String>::new()
+        );
+        // Exact terms are still words of the text
+        assert_eq!(
+            titles("meeting", SearchTermMatching::Exact)?,
+            vec!["Meeting with roger"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordered_search_result_sections() -> Result<()> {
+        let diary = "# 2024-01-02\n\n## Zeta\n@work @meeting\n\n## alpha\n@work\n\n\
+                     # 2024-01-01\n\n## Beta\n@work @meeting @private\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let terms = vec![
+            SearchTerm::try_from("@work".to_string())?,
+            SearchTerm::try_from("@meeting".to_string())?,
+        ];
+        let results = search_query(&terms).sections(&sections, &TagNormalizer::default());
+        let titles = |ordering: SectionOrderingCriterion, reverse: bool| {
+            ordered_search_result_sections(results.clone(), &ordering, reverse)
+                .iter()
+                .map(|r| r.section.title_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            titles(SectionOrderingCriterion::Relevance, false),
+            vec!["Beta", "Zeta", "alpha"]
+        );
+        assert_eq!(
+            titles(SectionOrderingCriterion::Date, false),
+            vec!["Beta", "Zeta", "alpha"]
+        );
+        assert_eq!(
+            titles(SectionOrderingCriterion::Date, true),
+            vec!["alpha", "Zeta", "Beta"]
+        );
+        assert_eq!(
+            titles(SectionOrderingCriterion::Title, false),
+            vec!["alpha", "Beta", "Zeta"]
+        );
+        assert_eq!(
+            titles(SectionOrderingCriterion::TagCount, false),
+            vec!["Beta", "Zeta", "alpha"]
+        );
+        assert_eq!(
+            titles(SectionOrderingCriterion::FileOrder, false),
+            vec!["Zeta", "alpha", "Beta"]
+        );
+        assert_eq!(
+            titles(SectionOrderingCriterion::FileOrder, true),
+            vec!["Beta", "alpha", "Zeta"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_results_grouped_by_tag() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize(DIARY)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let terms = ["@work", "@meeting", "@private"]
+            .iter()
+            .map(|t| SearchTerm::try_from(t.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tags = TagNormalizer::default();
+        let results = search_query(&terms).sections(&sections, &tags);
+
+        assert_eq!(
+            search_results_to_string(
+                results,
+                Some(&SearchGrouping::Tag),
+                &terms,
+                &Transcluder::new(&sections)
+            ),
+            "# @work\n\n## 2024-01-01\n\n### Standup\n@work\n\n---\n\n## 2024-01-02\n\n\
+             ### Planning\n@work\n\n# @meeting\n\n## 2024-01-01\n\n### Lunch\n@meeting"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_results_keep_tables() -> Result<()> {
+        let diary = "# 2024-01-01\n\n## Budget\n| Item | Cost |\n|------|-----:|\n| Rent | 900 |\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let terms = vec![SearchTerm::try_from("rent".to_string())?];
+        let tags = TagNormalizer::default();
+        let results = search_query(&terms).sections(&sections, &tags);
+
+        assert_eq!(
+            ordered_results_to_string(results, &Transcluder::new(&sections)),
+            "# 2024-01-01\n\n## Budget\n| Item | Cost |\n|------|-----:|\n| Rent | 900 |"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_frontmatter() -> Result<()> {
+        let config = SearchConfig::builder()
+            .terms(["@roger", "budget"])
+            .mode(TagSearchMode::And)
+            .from(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+            .frontmatter(true)
+            .build()?;
+        let files = vec![MarkdownFile::new(
+            PathBuf::from("diary.md"),
+            DIARY.to_string(),
+        )];
+        let generated = NaiveDate::from_ymd_opt(2024, 1, 3)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap();
+
+        let frontmatter = frontmatter(&config, &files, generated);
+        assert_eq!(
+            frontmatter,
+            format!(
+                "---\ngenerator: mdp {}\nterms: [\"@roger\",\"budget\"]\nmode: and\n\
+                 scope: section\nfrom: 2024-01-01\nuntil: null\nordering: date\n\
+                 generated: 2024-01-03T12:30:00\ninput_hash: {}\nconfig: {}\n---\n\n",
+                env!("CARGO_PKG_VERSION"),
+                input_hash(&files),
+                serde_json::to_string(&config)?
+            )
+        );
+
+        let provenance = Provenance::parse(&format!("{}# 2024-01-02\n", frontmatter)).unwrap();
+        assert_eq!(provenance.generated, "2024-01-03T12:30:00");
+        assert_eq!(provenance.input_hash, input_hash(&files));
+        assert_eq!(provenance.config.from, config.from);
+        assert!(Provenance::parse("---\ntitle: Notes\n---\n").is_none());
+        assert!(Provenance::parse(DIARY).is_none());
+
+        let mut changed = files.clone();
+        changed[0].content.push_str("@work\n");
+        assert_ne!(input_hash(&files), input_hash(&changed));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_split_results() -> Result<()> {
+        let diary = format!("{}\n## Planning\n@work\n", DIARY);
+        let tokens = MDPMarkdownTokenizer::default().tokenize(&diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let terms = vec![SearchTerm::try_from("@work".to_string())?];
+        let tags = TagNormalizer::default();
+        let results = search_query(&terms).sections(&sections, &tags);
+
+        let dir = tempfile::tempdir()?;
+        let paths = write_split_results(results, &Transcluder::new(&sections), "", dir.path())?;
+
+        let names: Vec<String> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "2024-01-01-standup.md",
+                "2024-01-02-planning.md",
+                "2024-01-02-planning-2.md"
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&paths[0])?,
+            "# 2024-01-01\n\n## Standup\n@work\n"
+        );
+        assert_eq!(
+            slug("Q3 Planning: Budget & Hiring"),
+            "q3-planning-budget-hiring"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_tag_aliases_and_children() -> Result<()> {
+        let diary = "\
+# 2024-01-01
+
+## Design
+@work/projectX
+
+## Call
+@Rega
+
+## Gym
+@workout
+";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+        let tags = TagNormalizer::new(&HashMap::from([(
+            "rega".to_string(),
+            vec!["Rega".to_string()],
+        )]));
+        let titles = |term: &str| {
+            let terms = vec![SearchTerm::try_from(term.to_string()).unwrap()];
+            search_query(&terms)
+                .sections(&sections, &tags)
+                .iter()
+                .map(|r| r.section.title_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(titles("@work"), vec!["Design"]);
+        assert_eq!(titles("@work/projectX"), vec!["Design"]);
+        assert_eq!(titles("@rega"), vec!["Call"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_front_matter_tags() -> Result<()> {
+        let files = vec![MarkdownFile::new(
+            PathBuf::from("projectX.md"),
+            format!("---\ntags: projectX\n---\n{}", DIARY),
+        )];
+        let sections = ParseContext::default().sections(&files)?;
+
+        let titles = search_titles(
+            &sections,
+            &["@projectX"],
+            TagSearchMode::Or,
+            SearchScope::Section,
+        );
+        assert_eq!(titles.len(), sections.len());
+        assert_eq!(titles[0], "2024-01-01");
+        Ok(())
+    }
+    #[test]
+    fn test_search_attributes() -> Result<()> {
+        let files = vec![MarkdownFile::new(
+            PathBuf::from("reading.md"),
+            "# 2024-06-01\n\n## Post\n\n@rust\nSource:: blog\n\n\
+             ## Book\n\n@rust\nSource:: Book\nRating:: 5\n"
+                .to_string(),
+        )];
+        let ctx = ParseContext::default();
+        let titles = |builder: SearchConfigBuilder| -> Result<Vec<String>> {
+            let mut output = String::new();
+            let command = SearchCommand {
+                config: builder.build()?,
+            };
+            command.run_on_files(&ctx, &files, &mut output)?;
+            Ok(output
+                .lines()
+                .filter(|l| l.starts_with("## "))
+                .map(str::to_string)
+                .collect())
+        };
+
+        let blog = SearchConfig::builder().attributes(["source=BLOG"]);
+        assert_eq!(titles(blog)?, vec!["## Post"]);
+        let rated = SearchConfig::builder()
+            .terms(["@rust"])
+            .attributes(["Source", "Rating"]);
+        assert_eq!(titles(rated)?, vec!["## Book"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_highlight() -> Result<()> {
+        let files = vec![MarkdownFile::new(
+            PathBuf::from("diary.md"),
+            "# 2024-06-01\n\n## Rust meetup\n\n@rust @meetup\nTalked about #async rust, not Rusty.\n"
+                .to_string(),
+        )];
+        let ctx = ParseContext::default();
+        let search = |highlight| -> Result<String> {
+            let mut output = String::new();
+            let command = SearchCommand {
+                config: SearchConfig::builder()
+                    .terms(["@rust", "#async", "rust"])
+                    .highlight(highlight)
+                    .build()?,
+            };
+            command.run_on_files(&ctx, &files, &mut output)?;
+            Ok(output)
+        };
+
+        assert!(search(true)?.starts_with(
+            "# 2024-06-01\n\n## ^^Rust^^ meetup\n\n^^@rust^^ @meetup\n\
+             Talked about ^^#async^^ ^^rust^^, not Rusty.\n"
+        ));
+        assert!(!search(false)?.contains("^^"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_oneline() -> Result<()> {
+        let files = vec![MarkdownFile::new(
+            PathBuf::from("notes/diary.md"),
+            "# 2024-06-01 - 2024-06-02 Trip @travel\n\n## Rust  *meetup*\n\n@rust @meetup\n\
+             Talked about rust.\n\n# 2024-05-30\n\n## Review\n\n@rust\n"
+                .to_string(),
+        )];
+        let config = SearchConfig::builder()
+            .terms(["@rust"])
+            .format(ResultFormat::Oneline)
+            .build()?;
+        let mut output = String::new();
+        let ctx = ParseContext::default();
+        let report = SearchCommand { config }.run_on_files(&ctx, &files, &mut output)?;
+        assert_eq!(
+            output,
+            "2024-05-30\trust\tReview\tnotes/diary.md:10\n\
+             2024-06-01 - 2024-06-02\trust,meetup\tRust meetup\tnotes/diary.md:3"
+        );
+        assert_eq!(report.result_count, 2);
+        Ok(())
+    }
+}
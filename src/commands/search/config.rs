@@ -1,43 +1,432 @@
-use std::{error::Error, fmt, path::PathBuf};
+use std::{error::Error, fmt, path::PathBuf, str::FromStr};
 
 use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+use crate::{
+    commands::{
+        oneline::ResultFormat,
+        query::{DateRange, Query},
+        InputBuilder,
+    },
+    models::ConfigError,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SearchConfig {
     pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
     pub output_path: PathBuf,
     pub ordering: SectionOrderingCriterion,
+    /// Reverse the order of the results given by `ordering`
+    #[serde(default)]
+    pub reverse: bool,
     pub search_terms: Vec<SearchTerm>,
     pub search_mode: TagSearchMode,
+    pub scope: SearchScope,
     pub from: Option<NaiveDate>,
     pub until: Option<NaiveDate>,
+    /// Write every matched section to its own file in this directory instead of writing one
+    /// report to `output_path`
+    #[serde(default)]
+    pub split_output: Option<PathBuf>,
+    /// Start the output with a YAML frontmatter block describing the query and the input
+    #[serde(default)]
+    pub frontmatter: bool,
+    /// Group the results (ignored with `split_output`)
+    #[serde(default)]
+    pub group_by: Option<SearchGrouping>,
+    /// Render raw URLs in the results as links titled like the pages they point to, which
+    /// are fetched over the network (and cached)
+    #[serde(default)]
+    pub fetch_titles: bool,
+    /// Wrap the tags, hashtags and words matched by the search terms in `^^highlight^^`
+    #[serde(default)]
+    pub highlight: bool,
+    /// Only sections containing all these attributes (`Key:: value`) match
+    #[serde(default)]
+    pub attributes: Vec<AttributeFilter>,
+    /// Write the results as Markdown or one line each (date, tags, title and location)
+    #[serde(default)]
+    pub format: ResultFormat,
 }
 
+impl SearchConfig {
+    pub fn builder() -> SearchConfigBuilder {
+        SearchConfigBuilder::default()
+    }
+
+    /// The filters selecting the sections found by the search
+    pub fn query(&self) -> Query {
+        Query {
+            terms: self.search_terms.clone(),
+            mode: self.search_mode.clone(),
+            scope: self.scope.clone(),
+            attributes: self.attributes.clone(),
+            dates: DateRange::new(self.from, self.until),
+            ..Default::default()
+        }
+    }
+}
+
+/// Builder for [`SearchConfig`]
 #[derive(Clone, Debug)]
-pub struct SearchTerm(String);
+pub struct SearchConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: PathBuf,
+    ordering: SectionOrderingCriterion,
+    reverse: bool,
+    search_terms: Vec<String>,
+    search_mode: TagSearchMode,
+    scope: SearchScope,
+    from: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    split_output: Option<PathBuf>,
+    frontmatter: bool,
+    group_by: Option<SearchGrouping>,
+    fetch_titles: bool,
+    highlight: bool,
+    attributes: Vec<String>,
+    matching: SearchTermMatching,
+    format: ResultFormat,
+}
+
+impl Default for SearchConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: PathBuf::from("./search.md"),
+            ordering: SectionOrderingCriterion::Date,
+            reverse: false,
+            search_terms: vec![],
+            search_mode: TagSearchMode::Or,
+            scope: SearchScope::Section,
+            from: None,
+            until: None,
+            split_output: None,
+            frontmatter: false,
+            group_by: None,
+            fetch_titles: false,
+            highlight: false,
+            attributes: vec![],
+            matching: SearchTermMatching::Exact,
+            format: ResultFormat::Markdown,
+        }
+    }
+}
+
+impl InputBuilder for SearchConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl SearchConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = path.into();
+        self
+    }
+
+    pub fn ordering(mut self, ordering: SectionOrderingCriterion) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Reverse the order of the results (e.g. newest or least relevant first)
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    pub fn terms<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.search_terms.extend(terms.into_iter().map(Into::into));
+        self
+    }
+
+    /// How all search terms are matched
+    pub fn matching(mut self, matching: SearchTermMatching) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    pub fn mode(mut self, mode: TagSearchMode) -> Self {
+        self.search_mode = mode;
+        self
+    }
+
+    pub fn scope(mut self, scope: SearchScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn from(mut self, date: NaiveDate) -> Self {
+        self.from = Some(date);
+        self
+    }
+
+    pub fn until(mut self, date: NaiveDate) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    /// Write every matched section to its own file in `dir`
+    pub fn split_output<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.split_output = Some(dir.into());
+        self
+    }
+
+    /// Start the output (or every file written with `split_output`) with a YAML frontmatter
+    /// block describing the query and the input
+    pub fn frontmatter(mut self, frontmatter: bool) -> Self {
+        self.frontmatter = frontmatter;
+        self
+    }
+
+    /// Group the results in the output instead of listing them ordered by date
+    pub fn group_by(mut self, grouping: SearchGrouping) -> Self {
+        self.group_by = Some(grouping);
+        self
+    }
+
+    /// Fetch the titles of the pages raw URLs in the results point to
+    pub fn fetch_titles(mut self, fetch_titles: bool) -> Self {
+        self.fetch_titles = fetch_titles;
+        self
+    }
+
+    /// Highlight what the sections matched (see [`SearchConfig::highlight`])
+    pub fn highlight(mut self, highlight: bool) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    /// Only match sections containing the attributes, given as `Key=value` or as `Key` to
+    /// accept any value
+    pub fn attributes<I, S>(mut self, filters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.attributes.extend(filters.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn format(mut self, format: ResultFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Build the config, validating all search terms and attribute filters.
+    pub fn build(self) -> Result<SearchConfig, ConfigError> {
+        Ok(SearchConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            ordering: self.ordering,
+            reverse: self.reverse,
+            search_terms: self
+                .search_terms
+                .into_iter()
+                .map(|s| {
+                    SearchTerm::new(s, &self.matching)
+                        .map_err(|e| ConfigError::InvalidSearchTermError(e.term()))
+                })
+                .collect::<Result<Vec<SearchTerm>, ConfigError>>()?,
+            search_mode: self.search_mode,
+            scope: self.scope,
+            from: self.from,
+            until: self.until,
+            split_output: self.split_output,
+            frontmatter: self.frontmatter,
+            group_by: self.group_by,
+            fetch_titles: self.fetch_titles,
+            highlight: self.highlight,
+            attributes: self
+                .attributes
+                .iter()
+                .map(|f| f.parse())
+                .collect::<Result<Vec<AttributeFilter>, ConfigError>>()?,
+            format: self.format,
+        })
+    }
+}
+
+/// How the search terms are matched
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SearchTermMatching {
+    /// The term itself (see [`SearchTermKind`])
+    #[default]
+    Exact,
+    /// The term is a regular expression (case insensitive) matched against the tags and the
+    /// heading of a section
+    Regex,
+    /// Tags and the words of the heading at least as similar to the term as the threshold
+    /// (between 0 and 1, the normalized Damerau-Levenshtein similarity)
+    Fuzzy(f64),
+}
+
+/// A single search term, its prefix determines what it is matched against (see
+/// [`SearchTermKind`]).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "SearchTermRepr", into = "SearchTermRepr")]
+pub enum SearchTerm {
+    Exact(String),
+    Regex { term: String, regex: Regex },
+    Fuzzy { term: String, threshold: f64 },
+}
 
 impl TryFrom<String> for SearchTerm {
     type Error = InvalidSearchTermError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        // REVIEW: Check if more restrictions are neccessary for
-        // a String to be a valid search term.
-        if value.contains(char::is_whitespace) {
-            return Err(InvalidSearchTermError(value));
+        SearchTerm::new(value, &SearchTermMatching::Exact)
+    }
+}
+
+impl From<SearchTerm> for String {
+    fn from(term: SearchTerm) -> Self {
+        term.inner()
+    }
+}
+
+/// Serialized form of a search term, a plain string for exact terms (as before regular
+/// expressions and fuzzy terms existed)
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SearchTermRepr {
+    Exact(String),
+    Regex { regex: String },
+    Fuzzy { fuzzy: String, threshold: f64 },
+}
+
+impl TryFrom<SearchTermRepr> for SearchTerm {
+    type Error = InvalidSearchTermError;
+
+    fn try_from(repr: SearchTermRepr) -> Result<Self, Self::Error> {
+        match repr {
+            SearchTermRepr::Exact(term) => SearchTerm::new(term, &SearchTermMatching::Exact),
+            SearchTermRepr::Regex { regex } => SearchTerm::new(regex, &SearchTermMatching::Regex),
+            SearchTermRepr::Fuzzy { fuzzy, threshold } => {
+                SearchTerm::new(fuzzy, &SearchTermMatching::Fuzzy(threshold))
+            }
+        }
+    }
+}
+
+impl From<SearchTerm> for SearchTermRepr {
+    fn from(term: SearchTerm) -> Self {
+        match term {
+            SearchTerm::Exact(term) => SearchTermRepr::Exact(term),
+            SearchTerm::Regex { term, .. } => SearchTermRepr::Regex { regex: term },
+            SearchTerm::Fuzzy { term, threshold } => SearchTermRepr::Fuzzy {
+                fuzzy: term,
+                threshold,
+            },
         }
-        Ok(Self(value))
     }
 }
 
 impl SearchTerm {
+    /// A term matched as given by `matching`, fails for terms containing whitespace, terms
+    /// consisting of a prefix only and invalid regular expressions
+    pub fn new(
+        term: String,
+        matching: &SearchTermMatching,
+    ) -> Result<Self, InvalidSearchTermError> {
+        let value = SearchTerm::Exact(term.clone()).kind().value().to_string();
+        if term.contains(char::is_whitespace) || value.is_empty() {
+            return Err(InvalidSearchTermError(term));
+        }
+        Ok(match matching {
+            SearchTermMatching::Exact => SearchTerm::Exact(term),
+            SearchTermMatching::Regex => {
+                match RegexBuilder::new(&value).case_insensitive(true).build() {
+                    Ok(regex) => SearchTerm::Regex { term, regex },
+                    Err(_) => return Err(InvalidSearchTermError(term)),
+                }
+            }
+            SearchTermMatching::Fuzzy(threshold) => SearchTerm::Fuzzy {
+                term,
+                threshold: *threshold,
+            },
+        })
+    }
+
+    /// The term as given (including its prefix)
     pub fn inner(&self) -> String {
-        self.0.clone()
+        match self {
+            SearchTerm::Exact(term)
+            | SearchTerm::Regex { term, .. }
+            | SearchTerm::Fuzzy { term, .. } => term.clone(),
+        }
+    }
+
+    pub fn kind(&self) -> SearchTermKind {
+        let term = self.inner();
+        if let Some(tag) = term.strip_prefix('@') {
+            SearchTermKind::Tag(tag.to_string())
+        } else if let Some(hashtag) = term.strip_prefix('#') {
+            SearchTermKind::Hashtag(hashtag.to_string())
+        } else {
+            SearchTermKind::Text(term)
+        }
+    }
+
+    /// Whether a tag, hashtag or heading matches the regular expression or is similar enough
+    /// (`false` for exact terms, which are matched by [`SearchTermKind`])
+    pub fn matches_pattern(&self, s: &str) -> bool {
+        match self {
+            SearchTerm::Exact(_) => false,
+            SearchTerm::Regex { regex, .. } => regex.is_match(s),
+            SearchTerm::Fuzzy { threshold, .. } => {
+                let value = self.kind().value().to_lowercase();
+                strsim::normalized_damerau_levenshtein(&value, &s.to_lowercase()) >= *threshold
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchTermKind {
+    /// `@tag`: one of the tags of the section
+    Tag(String),
+    /// `#topic`: one of the hashtags within the section
+    Hashtag(String),
+    /// Any other word: a word of the section's text (case insensitive), regular expressions
+    /// and fuzzy terms match the tags and the heading of the section instead
+    Text(String),
+}
+
+impl SearchTermKind {
+    /// The term without its prefix
+    pub fn value(&self) -> &str {
+        match self {
+            SearchTermKind::Tag(s) | SearchTermKind::Hashtag(s) | SearchTermKind::Text(s) => s,
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct InvalidSearchTermError(String);
 
+impl InvalidSearchTermError {
+    /// The rejected search term
+    pub fn term(&self) -> String {
+        self.0.clone()
+    }
+}
+
 impl fmt::Display for InvalidSearchTermError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "The provided search term '{}' is invalid.", self.0)
@@ -46,14 +435,210 @@ impl fmt::Display for InvalidSearchTermError {
 
 impl Error for InvalidSearchTermError {}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TagSearchMode {
     And,
     Or,
 }
 
-#[derive(Clone, Debug)]
+/// Where all search terms have to match for a section to match in `TagSearchMode::And`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    /// All terms in the section itself
+    Section,
+    /// All terms anywhere within the same day (the section needs to match at least one)
+    Day,
+    /// All terms anywhere within the same file (the section needs to match at least one)
+    File,
+}
+
+impl fmt::Display for SearchScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchScope::Section => write!(f, "section"),
+            SearchScope::Day => write!(f, "day"),
+            SearchScope::File => write!(f, "file"),
+        }
+    }
+}
+
+/// The order of the search results (see [`SectionOrderingCriterion::keys`] for how ties are
+/// broken)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SectionOrderingCriterion {
+    /// Sections matching the most search terms first
     Relevance,
+    /// Oldest sections first, sections without a date before all others
     Date,
+    /// Alphabetically by title (case insensitive)
+    Title,
+    /// Sections with the most tags first
+    TagCount,
+    /// As the sections appear in the input files
+    FileOrder,
+}
+
+impl SectionOrderingCriterion {
+    /// The criterion followed by the criteria breaking its ties, sections equal in all of them
+    /// keep the order of the input files
+    pub fn keys(&self) -> Vec<Self> {
+        use SectionOrderingCriterion::*;
+        match self {
+            Relevance => vec![Relevance, Date],
+            Date => vec![Date, Relevance],
+            Title => vec![Title, Date, Relevance],
+            TagCount => vec![TagCount, Relevance, Date],
+            FileOrder => vec![FileOrder],
+        }
+    }
+}
+
+impl fmt::Display for SectionOrderingCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SectionOrderingCriterion::Relevance => write!(f, "relevance"),
+            SectionOrderingCriterion::Date => write!(f, "date"),
+            SectionOrderingCriterion::Title => write!(f, "title"),
+            SectionOrderingCriterion::TagCount => write!(f, "tag-count"),
+            SectionOrderingCriterion::FileOrder => write!(f, "file-order"),
+        }
+    }
+}
+
+/// How the search results are grouped in the output
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchGrouping {
+    /// One `# @tag` section per search term containing the sections matching the term
+    Tag,
+}
+
+/// An attribute (`Key:: value`) a section has to contain, names and values are compared
+/// case-insensitively
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeFilter {
+    pub name: String,
+    /// Any value matches if not set
+    pub value: Option<String>,
+}
+
+impl AttributeFilter {
+    pub fn matches(&self, name: &str, value: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name.trim())
+            && self
+                .value
+                .as_ref()
+                .is_none_or(|v| v.to_lowercase() == value.trim().to_lowercase())
+    }
+}
+
+impl FromStr for AttributeFilter {
+    type Err = ConfigError;
+
+    /// Parses `Key=value` or `Key`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim().to_string())),
+            None => (s.trim(), None),
+        };
+        if name.is_empty() || name.contains(':') {
+            return Err(ConfigError::InvalidOptionsError(format!(
+                "The attribute filter '{}' is invalid, use `Key=value` or `Key`",
+                s
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_search_config_builder() {
+        let from = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        let config = SearchConfig::builder()
+            .terms(["work", "meeting"])
+            .input("diary.md")
+            .from(from)
+            .group_by(SearchGrouping::Tag)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            config
+                .search_terms
+                .iter()
+                .map(|t| t.inner())
+                .collect::<Vec<_>>(),
+            vec!["work", "meeting"]
+        );
+        assert_eq!(config.input_path, vec![PathBuf::from("diary.md")]);
+        assert_eq!(config.output_path, PathBuf::from("./search.md"));
+        assert_eq!(config.from, Some(from));
+        assert_eq!(config.until, None);
+        assert_eq!(config.group_by, Some(SearchGrouping::Tag));
+    }
+
+    #[test]
+    fn test_search_config_builder_invalid_term() {
+        let res = SearchConfig::builder().terms(["two words"]).build();
+        assert_eq!(
+            res.unwrap_err(),
+            ConfigError::InvalidSearchTermError("two words".to_string())
+        );
+        let res = SearchConfig::builder().terms(["#"]).build();
+        assert_eq!(
+            res.unwrap_err(),
+            ConfigError::InvalidSearchTermError("#".to_string())
+        );
+    }
+
+    #[test]
+    fn test_attribute_filter() {
+        let filter: AttributeFilter = "Source = Blog".parse().unwrap();
+        assert!(filter.matches("source", " blog"));
+        assert!(!filter.matches("Source", "book"));
+        let filter: AttributeFilter = "Source".parse().unwrap();
+        assert!(filter.matches("Source", "book"));
+        assert!("=blog".parse::<AttributeFilter>().is_err());
+    }
+
+    #[test]
+    fn test_search_term_serialization() {
+        let config = SearchConfig::builder()
+            .terms(["@proj.*"])
+            .matching(SearchTermMatching::Regex)
+            .build()
+            .unwrap();
+        let json = serde_json::to_string(&config.search_terms).unwrap();
+        assert_eq!(json, r#"[{"regex":"@proj.*"}]"#);
+        let terms: Vec<SearchTerm> = serde_json::from_str(&json).unwrap();
+        assert!(terms[0].matches_pattern("projectX"));
+
+        let terms: Vec<SearchTerm> =
+            serde_json::from_str(r#"["@work",{"fuzzy":"meting","threshold":0.8}]"#).unwrap();
+        assert_eq!(terms[0].kind(), SearchTermKind::Tag("work".to_string()));
+        assert!(terms[1].matches_pattern("Meeting"));
+        assert!(!terms[1].matches_pattern("Lunch"));
+    }
+
+    #[test]
+    fn test_search_term_kind() {
+        let kind = |s: &str| SearchTerm::try_from(s.to_string()).unwrap().kind();
+
+        assert_eq!(kind("@work"), SearchTermKind::Tag("work".to_string()));
+        assert_eq!(kind("#topic"), SearchTermKind::Hashtag("topic".to_string()));
+        assert_eq!(kind("budget"), SearchTermKind::Text("budget".to_string()));
+    }
 }
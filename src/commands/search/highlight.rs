@@ -0,0 +1,92 @@
+use super::config::{SearchTerm, SearchTermKind};
+use crate::{
+    commands::tags::normalize::TagNormalizer,
+    models::{TaskStatus, Token, TokenStyler},
+};
+
+/// Wraps the tags, hashtags and words matched by the search terms in `^^highlight^^`
+/// markup, so the output shows why a section matched. Everything else is decorated by the
+/// inner styler (e.g. the titles of links).
+pub struct MatchHighlighter<'s> {
+    terms: &'s [SearchTerm],
+    tags: &'s TagNormalizer,
+    inner: &'s dyn TokenStyler,
+}
+
+impl<'s> MatchHighlighter<'s> {
+    pub fn new(
+        terms: &'s [SearchTerm],
+        tags: &'s TagNormalizer,
+        inner: &'s dyn TokenStyler,
+    ) -> Self {
+        Self { terms, tags, inner }
+    }
+
+    fn matches_tag(&self, tag: &str) -> bool {
+        self.terms.iter().any(|term| match (term.kind(), term) {
+            (SearchTermKind::Tag(query), SearchTerm::Exact(_)) => self.tags.matches(tag, &query),
+            (SearchTermKind::Tag(_), term) | (SearchTermKind::Text(_), term) => {
+                term.matches_pattern(tag)
+            }
+            _ => false,
+        })
+    }
+
+    fn matches_hashtag(&self, hashtag: &str) -> bool {
+        self.terms.iter().any(|term| match (term.kind(), term) {
+            (SearchTermKind::Hashtag(query), SearchTerm::Exact(_)) => query == hashtag,
+            (SearchTermKind::Hashtag(_), term) => term.matches_pattern(hashtag),
+            _ => false,
+        })
+    }
+
+    /// Whether a word of a text matches, exact terms match it case insensitively (like the
+    /// search), regular expressions and fuzzy terms like a heading
+    fn matches_word(&self, word: &str) -> bool {
+        self.terms.iter().any(|term| match (term.kind(), term) {
+            (SearchTermKind::Text(query), SearchTerm::Exact(_)) => {
+                query.to_lowercase() == word.to_lowercase()
+            }
+            (SearchTermKind::Text(_), term) => term.matches_pattern(word),
+            _ => false,
+        })
+    }
+
+    /// Wraps the matched words of a text, keeping everything between them as it is
+    fn highlight_words(&self, text: &str) -> String {
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        let mut s = String::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let start = rest.find(is_word_char).unwrap_or(rest.len());
+            s += &rest[..start];
+            rest = &rest[start..];
+            let end = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+            let word = &rest[..end];
+            match !word.is_empty() && self.matches_word(word) {
+                true => s += &format!("^^{}^^", word),
+                false => s += word,
+            }
+            rest = &rest[end..];
+        }
+        s
+    }
+}
+
+impl TokenStyler for MatchHighlighter<'_> {
+    fn style_token(&self, token: &Token, markdown: String) -> String {
+        let markdown = match token {
+            Token::Tag(tag) if self.matches_tag(tag) => format!("^^{}^^", markdown),
+            Token::Hashtag(hashtag) if self.matches_hashtag(hashtag) => {
+                format!("^^{}^^", markdown)
+            }
+            Token::Text(text) => self.highlight_words(text),
+            _ => markdown,
+        };
+        self.inner.style_token(token, markdown)
+    }
+
+    fn style_task_status(&self, status: &TaskStatus, markdown: String) -> String {
+        self.inner.style_task_status(status, markdown)
+    }
+}
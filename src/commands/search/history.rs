@@ -0,0 +1,201 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+use chrono::NaiveDateTime;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::config::{SearchConfig, SearchScope, TagSearchMode};
+use crate::{
+    commands::encryption::{CacheCipher, CacheLocation},
+    models::MDPError,
+};
+
+/// Environment variable to override the location of the search history file
+pub const SEARCH_HISTORY_ENV: &str = "MDP_SEARCH_HISTORY";
+
+/// An executed search as recorded in the [`SearchHistory`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchHistoryEntry {
+    pub timestamp: NaiveDateTime,
+    pub config: SearchConfig,
+    pub result_count: usize,
+}
+
+impl Display for SearchHistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = &self.config;
+        let terms = config
+            .search_terms
+            .iter()
+            .map(|t| t.inner())
+            .collect::<Vec<_>>()
+            .join(",");
+        let mode = match config.search_mode {
+            TagSearchMode::And => "AND",
+            TagSearchMode::Or => "OR",
+        };
+
+        write!(
+            f,
+            "{}  {}  {}",
+            self.timestamp.format("%Y-%m-%d %H:%M"),
+            terms,
+            mode
+        )?;
+        if config.scope != SearchScope::Section {
+            write!(f, "  scope={}", config.scope)?;
+        }
+        if let Some(from) = config.from {
+            write!(f, "  from={}", from)?;
+        }
+        if let Some(until) = config.until {
+            write!(f, "  until={}", until)?;
+        }
+        write!(f, "  ({} results)", self.result_count)
+    }
+}
+
+/// Executed searches, stored as one JSON object per line (encrypted like the cache files,
+/// as the search terms are taken from the notes).
+pub struct SearchHistory {
+    pub location: CacheLocation,
+}
+
+impl SearchHistory {
+    /// The history in the user's data directory (or at `$MDP_SEARCH_HISTORY` if set),
+    /// encrypted with `cipher`
+    pub fn default_location(cipher: Option<CacheCipher>) -> Option<Self> {
+        let path = match std::env::var_os(SEARCH_HISTORY_ENV) {
+            Some(path) => path.into(),
+            None => ProjectDirs::from("", "", "mdp")?
+                .data_dir()
+                .join("search_history.jsonl"),
+        };
+        Some(Self {
+            location: CacheLocation { path, cipher },
+        })
+    }
+
+    /// All recorded searches, the most recent first
+    pub fn entries(&self) -> Result<Vec<SearchHistoryEntry>, MDPError> {
+        let path = &self.location.path;
+        let content = self.location.read_existing()?.unwrap_or_default();
+
+        let mut entries = content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).map_err(|e| read_error(path, e)))
+            .collect::<Result<Vec<SearchHistoryEntry>, MDPError>>()?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// The `n`-th most recent search (starting at 1)
+    pub fn entry(&self, n: usize) -> Result<Option<SearchHistoryEntry>, MDPError> {
+        let entries = self.entries()?;
+        Ok(n.checked_sub(1).and_then(|i| entries.get(i).cloned()))
+    }
+
+    /// Adds the search to the history (the file is rewritten, as an encrypted file can not
+    /// be appended to)
+    pub fn record(&self, entry: &SearchHistoryEntry) -> Result<(), MDPError> {
+        let path = &self.location.path;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| write_error(path, e))?;
+        }
+        let line = serde_json::to_string(entry).map_err(|e| write_error(path, e))?;
+
+        let mut content = self.location.read_existing()?.unwrap_or_default();
+        content.push_str(&line);
+        content.push('\n');
+        self.location.write(&content)
+    }
+
+    /// Numbered list of all recorded searches, the most recent first
+    pub fn list(&self) -> Result<String, MDPError> {
+        Ok(self
+            .entries()?
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| format!("{:>4}  {}\n", i + 1, entry))
+            .collect())
+    }
+}
+
+fn read_error<E: Display>(path: &Path, error: E) -> MDPError {
+    MDPError::IOReadError {
+        path: path.to_path_buf(),
+        details: error.to_string(),
+    }
+}
+
+fn write_error<E: Display>(path: &Path, error: E) -> MDPError {
+    MDPError::IOWriteError {
+        path: path.to_path_buf(),
+        details: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn entry(term: &str, result_count: usize) -> Result<SearchHistoryEntry> {
+        Ok(SearchHistoryEntry {
+            timestamp: NaiveDate::from_ymd_opt(2024, 1, 2)
+                .unwrap()
+                .and_hms_opt(8, 30, 0)
+                .unwrap(),
+            config: SearchConfig::builder()
+                .terms([term])
+                .mode(TagSearchMode::And)
+                .build()?,
+            result_count,
+        })
+    }
+
+    #[test]
+    fn test_search_history() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let history = SearchHistory {
+            location: dir.path().join("history").join("search_history.jsonl").as_path().into(),
+        };
+        assert!(history.entries()?.is_empty());
+
+        history.record(&entry("work", 3)?)?;
+        history.record(&entry("home", 1)?)?;
+
+        let last = history.entry(1)?.unwrap();
+        assert_eq!(last.config.search_terms[0].inner(), "home");
+        assert_eq!(history.entry(2)?.unwrap().result_count, 3);
+        assert!(history.entry(3)?.is_none());
+        assert!(history.entry(0)?.is_none());
+
+        assert_eq!(
+            history.list()?,
+            "   1  2024-01-02 08:30  home  AND  (1 results)\n   \
+                2  2024-01-02 08:30  work  AND  (3 results)\n"
+        );
+
+        // The search terms are not written in plain text if a cipher is given
+        let encrypted = SearchHistory {
+            location: CacheLocation {
+                cipher: Some(CacheCipher::new("secret")),
+                ..history.location.clone()
+            },
+        };
+        encrypted.record(&entry("private", 2)?)?;
+        assert!(!String::from_utf8_lossy(&fs::read(&history.location.path)?).contains("private"));
+        assert_eq!(encrypted.entries()?.len(), 3);
+        assert!(history.entries().is_err());
+        Ok(())
+    }
+}
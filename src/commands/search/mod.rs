@@ -1,2 +1,4 @@
 pub mod command;
 pub mod config;
+pub mod highlight;
+pub mod history;
@@ -0,0 +1,387 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use directories::ProjectDirs;
+use serde::Deserialize;
+
+use super::{
+    cache::DEFAULT_CACHE_PATH,
+    encryption::{CacheCipher, CacheLocation, CACHE_PASSPHRASE_ENV},
+    hooks::ExecPolicy,
+    io::{default_extensions, OutputInInput},
+    limits::Limits,
+    locale::Language,
+    tags::normalize::TagNormalizer,
+    theme::Theme,
+    titles, TimeZone,
+};
+use crate::{markdown::TagStyle, models::MDPError};
+
+/// Environment variable overriding the location of the configuration file
+pub const SETTINGS_ENV: &str = "MDP_CONFIG";
+
+/// User settings, read from a TOML file, e.g.
+///
+/// ```toml
+/// lang = "de"
+/// time_zone = "+02:00"
+///
+/// [attributes]
+/// single_colon = true
+///
+/// [cache]
+/// enabled = true
+/// key_file = "/home/me/.config/mdp/cache.key"
+///
+/// [exec]
+/// allowed_executables = ["prettier", "git"]
+///
+/// [files]
+/// extensions = ["md", "txt"]
+/// output_in_input = "error"  # or "warn", default "exclude"
+///
+/// [journal]
+/// daily_template = "/home/me/notes/templates/daily.md"
+///
+/// [limits]
+/// max_file_size = 52428800
+/// max_files = 50000
+///
+/// [projects]
+/// namespace = "Clients"
+///
+/// [stats]
+/// record_coverage = true
+///
+/// [summarize]
+/// command = "ollama run llama3 'Summarize these notes:'"
+///
+/// [tags]
+/// known = ["work", "rega"]
+/// style = "at"  # or "hash" (`#tag`, like Obsidian) or "both"
+///
+/// [tags.aliases]
+/// rega = ["Rega", "REGA"]
+///
+/// [tasks]
+/// daily_capacity = 5
+/// any_case_keywords = true
+///
+/// [theme]
+/// tag = { color = "magenta", bold = true }
+///
+/// [titles]
+/// timeout = 10
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub attributes: AttributeSettings,
+    pub cache: CacheSettings,
+    pub exec: ExecSettings,
+    pub files: FileSettings,
+    pub journal: JournalSettings,
+    /// Language of the texts of the output (like `--lang`)
+    pub lang: Language,
+    /// Limits on the size of the input (ignored with `--force`)
+    pub limits: Limits,
+    pub projects: ProjectSettings,
+    pub stats: StatsSettings,
+    pub summarize: SummarizeSettings,
+    pub tags: TagSettings,
+    pub tasks: TaskSettings,
+    /// Colors of the terminal output
+    pub theme: Theme,
+    pub titles: TitleSettings,
+    /// Time zone in which the days start (like `--time-zone`)
+    pub time_zone: TimeZone,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AttributeSettings {
+    /// Read `Name: value` lines (with a single colon) directly below a heading as attributes
+    /// (in addition to `Name:: value`)
+    pub single_colon: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheSettings {
+    /// Cache the tokens of the input files (like `--cache`)
+    pub enabled: bool,
+    /// Location of the cache file, relative paths are relative to the working directory
+    pub path: PathBuf,
+    /// File whose content is the secret the cache files are encrypted with (unless the
+    /// passphrase is set in `$MDP_CACHE_PASSPHRASE`)
+    pub key_file: Option<PathBuf>,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from(DEFAULT_CACHE_PATH),
+            key_file: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecSettings {
+    /// Executables which external commands (e.g. hooks) may run, all if not set
+    pub allowed_executables: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileSettings {
+    /// Extensions of the files read from input directories (compared case-insensitively)
+    pub extensions: Vec<String>,
+    /// What happens if the output file is inside an input directory (like
+    /// `--output-in-input`)
+    pub output_in_input: OutputInInput,
+}
+
+impl Default for FileSettings {
+    fn default() -> Self {
+        Self {
+            extensions: default_extensions(),
+            output_in_input: OutputInInput::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JournalSettings {
+    /// Template of the sections added by `mdp gaps --fill` (like `--template`), `{date}` and
+    /// `{weekday}` are replaced by the day
+    pub daily_template: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectSettings {
+    /// Title of the headings containing the projects (like `mdp projects --namespace`)
+    pub namespace: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatsSettings {
+    /// Record which sections are included in the output of commands (e.g. search results)
+    /// for `stats --coverage`
+    pub record_coverage: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SummarizeSettings {
+    /// The summarizer of `mdp summarize` (like `--command`)
+    pub command: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagSettings {
+    /// Canonical tag names and their aliases (e.g. other spellings)
+    pub aliases: HashMap<String, Vec<String>>,
+    /// Correctly spelled tags, `mdp lint` reports all other tags
+    pub known: Vec<String>,
+    /// Which tokens are tags (like `--tag-style`)
+    pub style: TagStyle,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TaskSettings {
+    /// Maximum number of tasks which should be due on the same day
+    pub daily_capacity: Option<usize>,
+    /// Read task keywords in any case (e.g. `todo:` or `Done:`) as tasks
+    pub any_case_keywords: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TitleSettings {
+    /// Location of the cache of fetched page titles (`search --fetch-titles`), relative
+    /// paths are relative to the working directory
+    pub path: PathBuf,
+    /// Seconds fetching the title of a page may take
+    pub timeout: u64,
+}
+
+impl Default for TitleSettings {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from(titles::DEFAULT_TITLE_CACHE_PATH),
+            timeout: titles::DEFAULT_FETCH_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl Settings {
+    /// The configuration file in the user's config directory (or at `$MDP_CONFIG` if set)
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(SETTINGS_ENV) {
+            return Some(path.into());
+        }
+        ProjectDirs::from("", "", "mdp").map(|dirs| dirs.config_dir().join("config.toml"))
+    }
+
+    /// Read the settings from the given file, a missing file results in the defaults.
+    pub fn load(path: &Path) -> Result<Self, MDPError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let read_error = |details: String| MDPError::IOReadError {
+            path: path.to_path_buf(),
+            details,
+        };
+        let content = fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+        toml::from_str(&content).map_err(|e| read_error(e.to_string()))
+    }
+
+    /// The execution policy, `no_exec` disables the execution of external commands
+    /// completely.
+    pub fn exec_policy(&self, no_exec: bool) -> ExecPolicy {
+        match (no_exec, &self.exec.allowed_executables) {
+            (true, _) => ExecPolicy::DenyAll,
+            (false, Some(allowed)) => ExecPolicy::Allowlist(allowed.clone()),
+            (false, None) => ExecPolicy::AllowAll,
+        }
+    }
+
+    pub fn tag_normalizer(&self) -> TagNormalizer {
+        TagNormalizer::new(&self.tags.aliases).with_style(self.tags.style)
+    }
+
+    /// The cipher the cache files are encrypted with: the passphrase in
+    /// `$MDP_CACHE_PASSPHRASE` or the content of the key file, `None` if neither is set
+    pub fn cache_cipher(&self) -> Result<Option<CacheCipher>, MDPError> {
+        if let Some(passphrase) = std::env::var_os(CACHE_PASSPHRASE_ENV) {
+            if !passphrase.is_empty() {
+                return Ok(Some(CacheCipher::new(passphrase.into_encoded_bytes())));
+            }
+        }
+        self.cache
+            .key_file
+            .as_deref()
+            .map(CacheCipher::from_key_file)
+            .transpose()
+    }
+
+    /// Titles fetched over HTTP and cached as configured (encrypted with `cipher`)
+    pub fn link_titles(&self, cipher: Option<CacheCipher>) -> titles::LinkTitles {
+        let fetcher = titles::HttpTitleFetcher {
+            timeout: Duration::from_secs(self.titles.timeout),
+        };
+        let location = CacheLocation {
+            path: self.titles.path.clone(),
+            cipher,
+        };
+        titles::LinkTitles::load(Some(location), Box::new(fetcher))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::theme::{Color, Style};
+
+    #[test]
+    fn test_load_settings() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+
+        let settings = Settings::load(&path)?;
+        assert_eq!(settings.exec_policy(false), ExecPolicy::AllowAll);
+
+        fs::write(&path, "[exec]\nallowed_executables = [\"prettier\"]\n")?;
+        let settings = Settings::load(&path)?;
+        assert_eq!(
+            settings.exec_policy(false),
+            ExecPolicy::Allowlist(vec!["prettier".to_string()])
+        );
+        assert_eq!(settings.exec_policy(true), ExecPolicy::DenyAll);
+
+        fs::write(&path, "[tags.aliases]\nrega = [\"Rega\"]\n")?;
+        let settings = Settings::load(&path)?;
+        assert_eq!(settings.tag_normalizer().normalize("Rega"), "rega");
+
+        fs::write(
+            &path,
+            "[tags]\nknown = [\"work\"]\n[tags.aliases]\nrega = []\n",
+        )?;
+        let tags = Settings::load(&path)?.tags;
+        assert_eq!(tags.known, vec!["work"]);
+        assert!(tags.aliases.contains_key("rega"));
+        assert_eq!(tags.style, TagStyle::At);
+        fs::write(&path, "[tags]\nstyle = \"hash\"\n")?;
+        assert_eq!(Settings::load(&path)?.tags.style, TagStyle::Hash);
+
+        assert_eq!(
+            Settings::default().files.extensions,
+            vec!["md", "markdown", "mdown"]
+        );
+        fs::write(&path, "[files]\nextensions = [\"md\", \"txt\"]\n")?;
+        assert_eq!(Settings::load(&path)?.files.extensions, vec!["md", "txt"]);
+        assert!(!Settings::default().attributes.single_colon);
+        fs::write(&path, "[attributes]\nsingle_colon = true\n")?;
+        assert!(Settings::load(&path)?.attributes.single_colon);
+        assert_eq!(
+            Settings::default().files.output_in_input,
+            OutputInInput::Exclude
+        );
+        fs::write(&path, "[files]\noutput_in_input = \"error\"\n")?;
+        assert_eq!(
+            Settings::load(&path)?.files.output_in_input,
+            OutputInInput::Error
+        );
+
+        fs::write(&path, "[journal]\ndaily_template = \"daily.md\"\n")?;
+        assert_eq!(
+            Settings::load(&path)?.journal.daily_template,
+            Some(PathBuf::from("daily.md"))
+        );
+
+        fs::write(&path, "[limits]\nmax_files = 5\n")?;
+        let limits = Settings::load(&path)?.limits;
+        assert_eq!(limits.max_files, Some(5));
+        assert_eq!(limits.max_tokens, Limits::default().max_tokens);
+
+        fs::write(&path, "[cache]\nenabled = true\n")?;
+        let cache = Settings::load(&path)?.cache;
+        assert!(cache.enabled);
+        assert_eq!(cache.path, PathBuf::from(".mdp-cache"));
+
+        fs::write(&path, "lang = \"de\"\n")?;
+        assert_eq!(Settings::load(&path)?.lang, Language::German);
+
+        fs::write(&path, "time_zone = \"UTC\"\n")?;
+        assert_eq!(Settings::load(&path)?.time_zone, "+00:00".parse()?);
+        fs::write(&path, "time_zone = \"Europe/Zurich\"\n")?;
+        assert!(Settings::load(&path).is_err());
+
+        fs::write(&path, "[tasks]\ndaily_capacity = 5\n")?;
+        assert_eq!(Settings::load(&path)?.tasks.daily_capacity, Some(5));
+
+        fs::write(&path, "[theme]\ntag = { color = \"magenta\" }\n")?;
+        let theme = Settings::load(&path)?.theme;
+        assert_eq!(theme.tag, Style::color(Color::Magenta));
+        assert_eq!(theme.todo, Theme::default().todo);
+
+        fs::write(&path, "[exec]\nallowed = []\n")?;
+        assert!(Settings::load(&path).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use std::cmp::Ordering;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::config::SortConfig;
+use crate::commands::{
+    diff::unified_diff, io::OutputSink, locale::Text, rewrite::replace_content, Command,
+    ParseContext, Report,
+};
+
+/// Reorders the top level sections (the days) of files chronologically, in place.
+///
+/// A day is moved with all its lines up to the next day, nothing of the file is reparsed or
+/// reformatted. Everything before the first day (e.g. a front matter) stays at the top, and
+/// the blank lines between the days stay where they were. Days without a date (see
+/// `--date-policy`) are put last, days of the same date keep their order.
+pub struct SortCommand {
+    pub config: SortConfig,
+}
+
+impl Command for SortCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+
+        let mut lines = vec![];
+        let mut result_count = 0;
+        for file in &files {
+            let mut days = ctx
+                .sections(std::slice::from_ref(file))?
+                .iter()
+                .filter(|s| s.section_type.is_top_level())
+                .map(|s| (s.origin.line, s.date))
+                .collect::<Vec<_>>();
+            // Merged days (`--merge-days`) keep the position of their first section
+            days.sort_by_key(|(line, _)| *line);
+            days.dedup_by_key(|(line, _)| *line);
+
+            let (sorted, moved) = sort_days(&file.content, &days, config.descending);
+            if moved == 0 {
+                continue;
+            }
+            result_count += moved;
+            let name = file.path.display().to_string();
+            match config.dry_run {
+                true => lines.push(unified_diff(&file.content, &sorted, &name, &name)),
+                false => {
+                    replace_content(&file.path, &file.content, sorted)?;
+                    lines.push(format!(
+                        "{}: {}",
+                        name,
+                        ctx.lang.format(Text::SectionsMoved, &[&moved, &days.len()])
+                    ));
+                }
+            }
+        }
+
+        out.write_output(&lines.join("\n"))?;
+        Ok(Report { result_count })
+    }
+}
+
+/// The content with the days (given by the line they start at and their date) sorted, and
+/// the number of days which were moved
+fn sort_days(
+    content: &str,
+    days: &[(usize, Option<NaiveDate>)],
+    descending: bool,
+) -> (String, usize) {
+    let line_starts = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect::<Vec<usize>>();
+    let starts = days
+        .iter()
+        .filter_map(|(line, _)| line_starts.get(line - 1).copied())
+        .collect::<Vec<usize>>();
+    if starts.len() < 2 || starts.len() != days.len() {
+        return (content.to_string(), 0);
+    }
+
+    // Every day is split into its lines and the blank lines following them, which stay in
+    // place as separators
+    let ends = starts.iter().skip(1).copied().chain([content.len()]);
+    let (bodies, separators): (Vec<&str>, Vec<&str>) = starts
+        .iter()
+        .zip(ends)
+        .map(|(start, end)| split_trailing_blank_lines(&content[*start..end]))
+        .unzip();
+
+    let mut order = (0..days.len()).collect::<Vec<usize>>();
+    order.sort_by(|a, b| match (days[*a].1, days[*b].1) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    let moved = order.iter().enumerate().filter(|(i, d)| i != *d).count();
+    if moved == 0 {
+        return (content.to_string(), 0);
+    }
+
+    let line_ending = match content.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    };
+    let mut sorted = content[..starts[0]].to_string();
+    for (day, separator) in order.iter().zip(separators) {
+        sorted += bodies[*day];
+        // The last day of a file might not end with a line break
+        if !bodies[*day].ends_with('\n') {
+            sorted += line_ending;
+        }
+        sorted += separator;
+    }
+    if !content.ends_with('\n') {
+        sorted.truncate(sorted.trim_end_matches(['\r', '\n']).len());
+    }
+    (sorted, moved)
+}
+
+/// Splits the text after its last line which is not blank
+fn split_trailing_blank_lines(text: &str) -> (&str, &str) {
+    let mut end = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        offset += line.len();
+        if !line.trim().is_empty() {
+            end = offset;
+        }
+    }
+    text.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_sort() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("journal.md");
+        let content = "---\ntags: [diary]\n---\nJournal\n\n# 2024-05-20 Meetup\n\n## Talk\n\
+                       ```\n# 2024-01-01\n```\n\n\n# 2024-05-01\nGardening\n\n\
+                       # 2024-05-10\n\nTODO: Call";
+        fs::write(&path, content)?;
+
+        let ctx = ParseContext::default();
+        let run = |config: SortConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = SortCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let (diff, moved) = run(SortConfig::builder().input(&path).dry_run(true).build())?;
+        assert_eq!(moved, 3);
+        assert!(diff.contains("-# 2024-05-20 Meetup"));
+        assert_eq!(fs::read_to_string(&path)?, content);
+
+        assert_eq!(
+            run(SortConfig::builder().input(&path).build())?,
+            (format!("{}: 3 of 3 sections moved", path.display()), 3)
+        );
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "---\ntags: [diary]\n---\nJournal\n\n# 2024-05-01\nGardening\n\n\n\
+             # 2024-05-10\n\nTODO: Call\n\n# 2024-05-20 Meetup\n\n## Talk\n\
+             ```\n# 2024-01-01\n```"
+        );
+        assert_eq!(run(SortConfig::builder().input(&path).build())?.1, 0);
+
+        run(SortConfig::builder().input(&path).descending(true).build())?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "---\ntags: [diary]\n---\nJournal\n\n# 2024-05-20 Meetup\n\n## Talk\n\
+             ```\n# 2024-01-01\n```\n\n\n# 2024-05-10\n\nTODO: Call\n\n# 2024-05-01\n\
+             Gardening"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_days() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 5, d);
+        let content = "# B\r\nb\r\n\r\n# 2024-05-02\r\n\r\n# 2024-05-01\r\na\r\n";
+        assert_eq!(
+            sort_days(content, &[(1, None), (4, date(2)), (6, date(1))], false),
+            (
+                "# 2024-05-01\r\na\r\n\r\n# 2024-05-02\r\n\r\n# B\r\nb\r\n".to_string(),
+                2
+            )
+        );
+        assert_eq!(sort_days(content, &[(1, None)], false).1, 0);
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct SortConfig {
+    /// Paths of the files to sort (or of directories containing them)
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// Put the latest day first
+    pub descending: bool,
+    /// Only show the changes (as unified diff) instead of writing them to the files
+    pub dry_run: bool,
+}
+
+impl SortConfig {
+    pub fn builder() -> SortConfigBuilder {
+        SortConfigBuilder::default()
+    }
+}
+
+/// Builder for [`SortConfig`]
+#[derive(Clone, Debug)]
+pub struct SortConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    descending: bool,
+    dry_run: bool,
+}
+
+impl Default for SortConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            descending: false,
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for SortConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl SortConfigBuilder {
+    pub fn descending(mut self, descending: bool) -> Self {
+        self.descending = descending;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> SortConfig {
+        SortConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            descending: self.descending,
+            dry_run: self.dry_run,
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+    slice,
+};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::{
+    config::StatsConfig,
+    coverage::{CoverageEntry, SectionCoverage},
+    format::{coverage_to_string, stats_to_string},
+};
+use crate::{
+    commands::{
+        io::OutputSink,
+        tags::{command::count_tags, normalize::TagNormalizer},
+        tasks::stats::TaskStats,
+        Command, ParseContext, Report,
+    },
+    models::{Section, Token},
+};
+
+pub struct StatsCommand {
+    pub config: StatsConfig,
+}
+
+impl Command for StatsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        if let Some(location) = &config.coverage {
+            let entries = SectionCoverage {
+                location: location.clone(),
+            }
+            .entries()?;
+            let sections = ctx.sections(&files)?;
+            let mut counts = vec![];
+            collect_coverage(&sections, &entries, &mut counts);
+            out.write_output(&coverage_to_string(&counts, ctx.lang))?;
+            return Ok(Report {
+                result_count: counts.len(),
+            });
+        }
+
+        let mut file_stats = vec![];
+        for file in &files {
+            let file = slice::from_ref(file);
+            let tokens = ctx.tokens(file)?.into_iter().map(|(t, _)| t).collect();
+            let sections = ctx.sections(file)?;
+            let front_matter_tags = file[0].front_matter_tags();
+            let stats = Stats::new(tokens, front_matter_tags, &sections, &ctx.tags);
+            file_stats.push((file[0].path.clone(), stats));
+        }
+
+        let output_string = stats_to_string(&file_stats, ctx.lang);
+        out.write_output(&output_string)?;
+
+        Ok(Report {
+            result_count: file_stats.len(),
+        })
+    }
+}
+
+/// Metrics of one or (aggregated) multiple files
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub files: usize,
+    /// Number of sections including all subsections
+    pub sections: usize,
+    pub words: usize,
+    pub tasks: TaskStats,
+    pub tags: HashMap<String, usize>,
+    /// Days covered by the top level sections (all days of a section spanning several)
+    pub dates: BTreeSet<NaiveDate>,
+}
+
+impl Stats {
+    pub fn new(
+        tokens: Vec<Token>,
+        front_matter_tags: &[String],
+        sections: &[Section],
+        tags: &TagNormalizer,
+    ) -> Self {
+        let mut stats = Self {
+            files: 1,
+            tasks: TaskStats::new(tokens.iter().filter_map(|t| match t {
+                Token::Task { status, .. } => Some(status),
+                _ => None,
+            })),
+            dates: sections
+                .iter()
+                .filter_map(|s| Some((s.date?, s.last_date()?)))
+                .flat_map(|(first, last)| first.iter_days().take_while(move |d| *d <= last))
+                .collect(),
+            ..Default::default()
+        };
+        stats.tags = count_tags(tokens, front_matter_tags, tags);
+        stats.add_sections(sections);
+
+        stats
+    }
+
+    fn add_sections(&mut self, sections: &[Section]) {
+        for section in sections {
+            self.sections += 1;
+            self.words += section.plain_text().split_whitespace().count();
+            self.add_sections(&section.subsections);
+        }
+    }
+
+    /// Aggregate the stats of all files
+    pub fn total<'a, I: IntoIterator<Item = &'a Stats>>(stats: I) -> Self {
+        let mut total = Self::default();
+        for s in stats {
+            total.files += s.files;
+            total.sections += s.sections;
+            total.words += s.words;
+            total.tasks.merge(&s.tasks);
+            for (tag, count) in &s.tags {
+                *total.tags.entry(tag.to_owned()).or_insert(0) += count;
+            }
+            total.dates.extend(s.dates.iter().cloned());
+        }
+        total
+    }
+
+    pub fn first_entry(&self) -> Option<NaiveDate> {
+        self.dates.first().cloned()
+    }
+
+    pub fn last_entry(&self) -> Option<NaiveDate> {
+        self.dates.last().cloned()
+    }
+
+    /// Days between the first and the last entry without an entry
+    pub fn missing_days(&self) -> Vec<NaiveDate> {
+        match (self.first_entry(), self.last_entry()) {
+            (Some(first), Some(last)) => first
+                .iter_days()
+                .take_while(|d| *d <= last)
+                .filter(|d| !self.dates.contains(d))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// How often the sections with content of their own (e.g. not the days only containing
+/// subsections) were included in the output, by section id
+fn collect_coverage(
+    sections: &[Section],
+    entries: &BTreeMap<String, CoverageEntry>,
+    counts: &mut Vec<(String, usize)>,
+) {
+    for section in sections {
+        let has_content = section
+            .content
+            .iter()
+            .any(|t| !matches!(t, Token::Newline | Token::Blank));
+        if has_content {
+            let id = section.id();
+            let count = entries.get(&id).map_or(0, |e| e.count);
+            counts.push((id, count));
+        }
+        collect_coverage(&section.subsections, entries, counts);
+    }
+}
+
+/// Stats per file, see [`stats_to_string`]
+pub type FileStats = (PathBuf, Stats);
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        commands::InputBuilder,
+        markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+        models::{MarkdownTokenizer, SectionBuilder},
+    };
+
+    const DIARY: &str = "\
+# 2024-01-01
+
+## Work
+@work Wrote the report.
+
+TODO: Send the report
+
+# 2024-01-04
+
+DONE: Water the plants
+@home @work
+";
+
+    #[test]
+    fn test_stats() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize(DIARY)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens.clone())?;
+        let stats = Stats::new(tokens, &[], &sections, &TagNormalizer::default());
+
+        assert_eq!(stats.sections, 3);
+        assert_eq!(stats.words, 15);
+        assert_eq!(
+            stats.tasks,
+            TaskStats {
+                todo: 1,
+                done: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(stats.tags.get("work"), Some(&2));
+        assert_eq!(stats.tags.get("home"), Some(&1));
+        assert_eq!(
+            stats.missing_days(),
+            vec![
+                NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 3).unwrap()
+            ]
+        );
+
+        let total = Stats::total([&stats, &stats]);
+        assert_eq!(total.files, 2);
+        assert_eq!(total.words, 30);
+        assert_eq!(total.tags.get("work"), Some(&4));
+        assert_eq!(total.dates, stats.dates);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats_date_range() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize("# 2024-01-01\n\n# 2024-01-03 - 2024-01-05 Trip\n\nHiking\n")?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens.clone())?;
+        let stats = Stats::new(tokens, &[], &sections, &TagNormalizer::default());
+
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        assert_eq!(stats.last_entry(), Some(date(5)));
+        assert_eq!(stats.missing_days(), vec![date(2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats_coverage() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(&path, DIARY)?;
+        let coverage = SectionCoverage {
+            location: dir.path().join("section_coverage.json").as_path().into(),
+        };
+        let now = NaiveDate::from_ymd_opt(2024, 1, 5)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        coverage.record(&["diary.md:2024-01-01 Work".to_string()], now)?;
+
+        let ctx = ParseContext::default();
+        let config = StatsConfig::builder()
+            .input(&path)
+            .coverage(coverage.location.clone())
+            .build();
+        let mut output = String::new();
+        let report = StatsCommand { config }.run(&ctx, &mut output)?;
+
+        // The first day only contains its subsection
+        assert_eq!(
+            output,
+            "NEVER INCLUDED: 1 of 2 sections\n  diary.md:2024-01-04\n\n\
+             MOST INCLUDED:\n    1  diary.md:2024-01-01 Work\n"
+        );
+        assert_eq!(report.result_count, 2);
+        Ok(())
+    }
+}
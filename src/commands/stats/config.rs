@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+
+use crate::commands::{encryption::CacheLocation, InputBuilder};
+
+#[derive(Clone, Debug)]
+pub struct StatsConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Show how often the sections were included in the output according to the section
+    /// coverage file at this (possibly encrypted) location (see [`SectionCoverage`]) instead
+    /// of the stats
+    ///
+    /// [`SectionCoverage`]: super::coverage::SectionCoverage
+    pub coverage: Option<CacheLocation>,
+}
+
+impl StatsConfig {
+    pub fn builder() -> StatsConfigBuilder {
+        StatsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`StatsConfig`]
+#[derive(Clone, Debug)]
+pub struct StatsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    coverage: Option<CacheLocation>,
+}
+
+impl Default for StatsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            coverage: None,
+        }
+    }
+}
+
+impl InputBuilder for StatsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl StatsConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Show the section coverage recorded in the file instead of the stats
+    pub fn coverage<L: Into<CacheLocation>>(mut self, location: L) -> Self {
+        self.coverage = Some(location.into());
+        self
+    }
+
+    pub fn build(self) -> StatsConfig {
+        StatsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            coverage: self.coverage,
+        }
+    }
+}
+
+/// The metric sections are ranked by, see [`SectionStatsConfig`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SectionMetric {
+    #[default]
+    Words,
+    Tasks,
+    /// Wiki links, embeds, Markdown links and raw URLs
+    Links,
+}
+
+#[derive(Clone, Debug)]
+pub struct SectionStatsConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Number of sections listed
+    pub top: usize,
+    pub by: SectionMetric,
+}
+
+impl SectionStatsConfig {
+    pub fn builder() -> SectionStatsConfigBuilder {
+        SectionStatsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`SectionStatsConfig`]
+#[derive(Clone, Debug)]
+pub struct SectionStatsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    top: usize,
+    by: SectionMetric,
+}
+
+impl Default for SectionStatsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            top: 20,
+            by: SectionMetric::default(),
+        }
+    }
+}
+
+impl InputBuilder for SectionStatsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl SectionStatsConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    pub fn top(mut self, top: usize) -> Self {
+        self.top = top;
+        self
+    }
+
+    pub fn by(mut self, by: SectionMetric) -> Self {
+        self.by = by;
+        self
+    }
+
+    pub fn build(self) -> SectionStatsConfig {
+        SectionStatsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            top: self.top,
+            by: self.by,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::{collections::BTreeMap, fmt::Display, fs, path::Path};
+
+use chrono::NaiveDateTime;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::encryption::{CacheCipher, CacheLocation},
+    models::MDPError,
+};
+
+/// Environment variable to override the location of the section coverage file
+pub const SECTION_COVERAGE_ENV: &str = "MDP_SECTION_COVERAGE";
+
+/// How often a section was included in the output
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageEntry {
+    pub count: usize,
+    pub last_included: NaiveDateTime,
+}
+
+/// How often the sections were included in the output of commands (e.g. search results),
+/// by section id (see [`crate::models::Section::id`]). Only recorded if enabled in the
+/// settings, stored as JSON object (encrypted like the cache files, as the ids contain
+/// the titles of the sections).
+pub struct SectionCoverage {
+    pub location: CacheLocation,
+}
+
+impl SectionCoverage {
+    /// The coverage in the user's data directory (or at `$MDP_SECTION_COVERAGE` if set),
+    /// encrypted with `cipher`
+    pub fn default_location(cipher: Option<CacheCipher>) -> Option<Self> {
+        let path = match std::env::var_os(SECTION_COVERAGE_ENV) {
+            Some(path) => path.into(),
+            None => ProjectDirs::from("", "", "mdp")?
+                .data_dir()
+                .join("section_coverage.json"),
+        };
+        Some(Self {
+            location: CacheLocation { path, cipher },
+        })
+    }
+
+    /// The recorded entries by section id
+    pub fn entries(&self) -> Result<BTreeMap<String, CoverageEntry>, MDPError> {
+        let path = &self.location.path;
+        match self.location.read_existing()? {
+            Some(content) => serde_json::from_str(&content).map_err(|e| read_error(path, e)),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    /// Counts the sections (given by their ids) as included at `timestamp`, a section
+    /// included multiple times in one output counts once
+    pub fn record(&self, ids: &[String], timestamp: NaiveDateTime) -> Result<(), MDPError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut entries = self.entries()?;
+        let mut ids = ids.to_vec();
+        ids.sort();
+        ids.dedup();
+        for id in ids {
+            let entry = entries.entry(id).or_insert(CoverageEntry {
+                count: 0,
+                last_included: timestamp,
+            });
+            entry.count += 1;
+            entry.last_included = timestamp;
+        }
+
+        let path = &self.location.path;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| write_error(path, e))?;
+        }
+        let json = serde_json::to_string_pretty(&entries).map_err(|e| write_error(path, e))?;
+        self.location.write(&json)
+    }
+}
+
+fn read_error<E: Display>(path: &Path, error: E) -> MDPError {
+    MDPError::IOReadError {
+        path: path.to_path_buf(),
+        details: error.to_string(),
+    }
+}
+
+fn write_error<E: Display>(path: &Path, error: E) -> MDPError {
+    MDPError::IOWriteError {
+        path: path.to_path_buf(),
+        details: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_section_coverage() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let coverage = SectionCoverage {
+            location: dir.path().join("data").join("section_coverage.json").as_path().into(),
+        };
+        let at = |hour| {
+            NaiveDate::from_ymd_opt(2024, 5, 20)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        assert!(coverage.entries()?.is_empty());
+
+        let ids = |ids: &[&str]| ids.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        coverage.record(&ids(&["a.md:2024-05-20 Meetup", "a.md:2024-05-01"]), at(8))?;
+        coverage.record(&ids(&["a.md:2024-05-20 Meetup"; 2]), at(9))?;
+        coverage.record(&[], at(10))?;
+
+        let entries = coverage.entries()?;
+        assert_eq!(
+            entries["a.md:2024-05-20 Meetup"],
+            CoverageEntry {
+                count: 2,
+                last_included: at(9),
+            }
+        );
+        assert_eq!(entries["a.md:2024-05-01"].count, 1);
+
+        // The titles are not written in plain text if a cipher is given
+        let encrypted = SectionCoverage {
+            location: CacheLocation {
+                cipher: Some(CacheCipher::new("secret")),
+                ..coverage.location.clone()
+            },
+        };
+        encrypted.record(&ids(&["a.md:2024-05-01"]), at(11))?;
+        assert!(!String::from_utf8_lossy(&fs::read(&coverage.location.path)?).contains("Meetup"));
+        assert_eq!(encrypted.entries()?["a.md:2024-05-20 Meetup"].count, 2);
+        assert!(coverage.entries().is_err());
+        Ok(())
+    }
+}
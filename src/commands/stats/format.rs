@@ -0,0 +1,176 @@
+use std::cmp::Ordering;
+
+use super::{
+    command::{FileStats, Stats},
+    sections::SectionStats,
+};
+use crate::commands::locale::{Language, Text};
+
+/// Formats the stats of every file followed by the aggregated stats (if there are
+/// multiple files).
+pub fn stats_to_string(file_stats: &[FileStats], lang: Language) -> String {
+    let mut blocks = file_stats
+        .iter()
+        .map(|(path, stats)| {
+            format!(
+                "{}\n{}",
+                lang.format(Text::StatsFile, &[&path.display()]),
+                stats_block(stats, lang)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    if file_stats.len() > 1 {
+        let total = Stats::total(file_stats.iter().map(|(_, s)| s));
+        blocks.push(format!(
+            "{}\n{}",
+            lang.format(Text::StatsTotal, &[&total.files]),
+            stats_block(&total, lang)
+        ));
+    }
+
+    blocks.join("\n")
+}
+
+fn stats_block(stats: &Stats, lang: Language) -> String {
+    let date_or_dash = |d: Option<chrono::NaiveDate>| match d {
+        Some(d) => d.to_string(),
+        None => "-".to_string(),
+    };
+
+    let mut s = String::new();
+    s += &line(lang.text(Text::Sections), stats.sections);
+    s += &line(lang.text(Text::Words), stats.words);
+    s += &line(lang.text(Text::Tasks), stats.tasks.total());
+    s += &line("  TODO", stats.tasks.todo);
+    s += &line("  DOING", stats.tasks.doing);
+    s += &line("  REVIEW", stats.tasks.review);
+    s += &line("  WAITING", stats.tasks.waiting);
+    s += &line("  DONE", stats.tasks.done);
+    s += &line(
+        &format!("  {}", lang.text(Text::Completed)),
+        match stats.tasks.completion() {
+            Some(percent) => format!("{}%", percent),
+            None => "-".to_string(),
+        },
+    );
+    s += &line(
+        lang.text(Text::FirstEntry),
+        date_or_dash(stats.first_entry()),
+    );
+    s += &line(lang.text(Text::LastEntry), date_or_dash(stats.last_entry()));
+    s += &line(lang.text(Text::MissingDays), stats.missing_days().len());
+    s += &line(lang.text(Text::Tags), stats.tags.len());
+
+    let mut tags = stats.tags.iter().collect::<Vec<_>>();
+    tags.sort_by(|a, b| match a.1.cmp(b.1).reverse() {
+        Ordering::Equal => a.0.cmp(b.0),
+        other => other,
+    });
+    for (tag, count) in tags {
+        s += &line(&format!("  {}", tag), count);
+    }
+    s
+}
+
+/// Number of sections listed as most included
+const MOST_INCLUDED: usize = 10;
+
+/// Lists the sections never included in the output, followed by the ones included most
+/// often. `counts` are the inclusion counts by section id.
+pub fn coverage_to_string(counts: &[(String, usize)], lang: Language) -> String {
+    let never = counts
+        .iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(id, _)| id.to_string())
+        .collect::<Vec<String>>();
+    let mut s = lang.format(Text::NeverIncluded, &[&never.len(), &counts.len()]);
+    s += "\n";
+    for id in &never {
+        s += &format!("  {}\n", id);
+    }
+
+    let mut most = counts
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .collect::<Vec<_>>();
+    most.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    if !most.is_empty() {
+        s += &format!("\n{}\n", lang.text(Text::MostIncluded));
+        for (id, count) in most.into_iter().take(MOST_INCLUDED) {
+            s += &format!("{:>5}  {}\n", count, id);
+        }
+    }
+    s
+}
+
+/// A table of the metrics of the sections, one row per section with its location and the
+/// titles of the sections containing it
+pub fn section_stats_to_string(stats: &[SectionStats], lang: Language) -> String {
+    let mut lines = vec![format!(
+        "{:>5}  {:>5}  {:>5}  {}",
+        lang.text(Text::Words),
+        lang.text(Text::Tasks),
+        lang.text(Text::Links),
+        lang.text(Text::SectionHeader)
+    )];
+    for s in stats {
+        lines.push(format!(
+            "{:>5}  {:>5}  {:>5}  {}  {}",
+            s.words,
+            s.tasks,
+            s.links,
+            s.origin,
+            s.path.join(" > ")
+        ));
+    }
+    lines.join("\n")
+}
+
+fn line<T: ToString>(label: &str, value: T) -> String {
+    format!("{:<20} {:>10}\n", label, value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_stats_to_string() {
+        let stats = Stats {
+            files: 1,
+            sections: 2,
+            words: 10,
+            tags: [("work".to_string(), 1), ("home".to_string(), 3)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            stats_to_string(&[(PathBuf::from("diary.md"), stats)], Language::English),
+            "\
+FILE: diary.md
+Sections                      2
+Words                        10
+Tasks                         0
+  TODO                        0
+  DOING                       0
+  REVIEW                      0
+  WAITING                     0
+  DONE                        0
+  Completed                   -
+First entry                   -
+Last entry                    -
+Missing days                  0
+Tags                          2
+  home                        3
+  work                        1
+"
+        );
+    }
+}
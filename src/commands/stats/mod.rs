@@ -0,0 +1,5 @@
+pub mod command;
+pub mod config;
+pub mod coverage;
+pub mod format;
+pub mod sections;
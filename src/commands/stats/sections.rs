@@ -0,0 +1,156 @@
+use anyhow::Result;
+
+use super::{
+    config::{SectionMetric, SectionStatsConfig},
+    format::section_stats_to_string,
+};
+use crate::{
+    commands::{io::OutputSink, Command, ParseContext, Report},
+    models::{Origin, Section, Token},
+};
+
+/// Lists the sections with the most words, tasks or links (see [`SectionMetric`]) with their
+/// location, e.g. to find sections which could become notes of their own.
+pub struct SectionStatsCommand {
+    pub config: SectionStatsConfig,
+}
+
+impl Command for SectionStatsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+
+        let mut stats = vec![];
+        collect_section_stats(&sections, &mut vec![], &mut stats);
+        // Stable, so sections with the same metric stay in the order of the input
+        stats.sort_by_key(|s| std::cmp::Reverse(s.metric(config.by)));
+        stats.truncate(config.top);
+
+        if stats.is_empty() {
+            log::warn!("No sections found!");
+            return Ok(Report::default());
+        }
+        out.write_output(&section_stats_to_string(&stats, ctx.lang))?;
+
+        Ok(Report {
+            result_count: stats.len(),
+        })
+    }
+}
+
+/// Metrics of the content of a section (without its subsections)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionStats {
+    pub origin: Origin,
+    /// Titles of the sections from the top level section down to this one
+    pub path: Vec<String>,
+    /// Words of the title and the paragraphs (see [`Section::plain_text`])
+    pub words: usize,
+    pub tasks: usize,
+    pub links: usize,
+}
+
+impl SectionStats {
+    pub fn new(section: &Section, path: Vec<String>) -> Self {
+        let count = |is_counted: fn(&Token) -> bool| {
+            section
+                .content
+                .iter()
+                .map(|t| count_tokens(t, is_counted))
+                .sum()
+        };
+        Self {
+            origin: section.origin.clone(),
+            path,
+            words: section.plain_text().split_whitespace().count(),
+            tasks: count(|t| matches!(t, Token::Task { .. })),
+            links: count(|t| {
+                matches!(
+                    t,
+                    Token::Link(_)
+                        | Token::Embed(_)
+                        | Token::MarkdownExternalLink { .. }
+                        | Token::MarkdownInternalLink { .. }
+                        | Token::RawHyperlink(_)
+                )
+            }),
+        }
+    }
+
+    pub fn metric(&self, metric: SectionMetric) -> usize {
+        match metric {
+            SectionMetric::Words => self.words,
+            SectionMetric::Tasks => self.tasks,
+            SectionMetric::Links => self.links,
+        }
+    }
+}
+
+/// Number of tokens within the token (itself included) which are counted
+fn count_tokens(token: &Token, is_counted: fn(&Token) -> bool) -> usize {
+    let children: usize = token
+        .children()
+        .iter()
+        .map(|t| count_tokens(t, is_counted))
+        .sum();
+    children + usize::from(is_counted(token))
+}
+
+fn collect_section_stats(
+    sections: &[Section],
+    path: &mut Vec<String>,
+    stats: &mut Vec<SectionStats>,
+) {
+    for section in sections {
+        path.push(section.title_string());
+        stats.push(SectionStats::new(section, path.clone()));
+        collect_section_stats(&section.subsections, path, stats);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_section_stats() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(
+            &path,
+            "# 2024-06-01\nShort day\n\n## Project X\nSee [[Budget]] and https://x.org\n\
+             - [docs](https://docs.x.org)\n\n## Chores\nTODO: Laundry\n- TODO: Groceries\n\
+             DONE: Dishes\n",
+        )?;
+
+        let run = |by: SectionMetric, top: usize| -> Result<String> {
+            let mut out = String::new();
+            let config = SectionStatsConfig::builder()
+                .input(&path)
+                .by(by)
+                .top(top)
+                .build();
+            SectionStatsCommand { config }.run(&ParseContext::default(), &mut out)?;
+            Ok(out)
+        };
+
+        assert_eq!(
+            run(SectionMetric::Tasks, 2)?,
+            format!(
+                "Words  Tasks  Links  Section\n    \
+                 4      3      0  {0}:8:1  2024-06-01 > Chores\n    \
+                 3      0      0  {0}:1:1  2024-06-01",
+                path.display()
+            )
+        );
+        let by_links = run(SectionMetric::Links, 1)?;
+        assert!(by_links.ends_with(":4:1  2024-06-01 > Project X"));
+        assert!(by_links.contains("     3  "));
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+
+use super::config::SummarizeConfig;
+use crate::{
+    commands::{
+        hooks::FilterCommand,
+        io::OutputSink,
+        query::{DateRange, Query},
+        Command, ParseContext, Report,
+    },
+    models::Section,
+};
+
+/// Pipes the plain text of the selected sections through an external summarizer (e.g. a
+/// local language model) and returns its summary. mdp bundles no summarizer, any command
+/// reading stdin and writing stdout works.
+pub struct SummarizeCommand {
+    pub config: SummarizeConfig,
+}
+
+impl Command for SummarizeCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let dates = DateRange::relative(config.from, config.until, ctx.clock.today());
+
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+        let mut texts = vec![];
+        for m in Query::tagged(&config.tags, dates).sections(&sections, &ctx.tags) {
+            let text = section_text(&m.section);
+            if !text.is_empty() {
+                ctx.include(&m.section);
+                texts.push(text);
+            }
+        }
+        if texts.is_empty() {
+            log::warn!("No sections match!");
+            return Ok(Report::default());
+        }
+
+        let summary =
+            FilterCommand::new(&config.command).run(&texts.join("\n\n"), &config.exec_policy)?;
+        if summary.trim().is_empty() {
+            log::warn!("The summarizer '{}' returned no summary", config.command);
+        }
+        out.write_output(summary.trim())?;
+
+        Ok(Report {
+            result_count: texts.len(),
+        })
+    }
+}
+
+/// The plain text of the section (without its subsections) headed by its day and title,
+/// empty if there is no text
+fn section_text(section: &Section) -> String {
+    let paragraphs = section.paragraphs();
+    if paragraphs.is_empty() {
+        return String::new();
+    }
+    let heading = match (section.section_type.is_top_level(), section.date) {
+        (true, Some(date)) => date.to_string(),
+        (false, Some(date)) => format!("{} {}", date, section.title_string()),
+        (_, None) => section.title_string(),
+    };
+    std::iter::once(heading)
+        .chain(paragraphs)
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs;
+
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{hooks::ExecPolicy, FixedClock, InputBuilder, RelativeDate};
+
+    #[test]
+    fn test_summarize() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2024-05-01\n\nGardening\n\n# 2024-05-20\n\n## Meetup\n\n@work\nTalk about Rust.\n\n\
+             ## Empty\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 5, 25).unwrap()));
+        let run = |config: SummarizeConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = SummarizeCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        // `cat` returns the text passed to the summarizer
+        let recent = SummarizeConfig::builder()
+            .input(&path)
+            .from(RelativeDate::Days(-7))
+            .command("cat");
+        assert_eq!(
+            run(recent.clone().build())?,
+            ("2024-05-20 Meetup\n\nwork Talk about Rust.".to_string(), 1)
+        );
+        let (output, _) = run(SummarizeConfig::builder()
+            .input(&path)
+            .command("wc -l")
+            .build())?;
+        assert_eq!(output, "6");
+
+        let denied = recent.exec_policy(ExecPolicy::DenyAll).build();
+        assert!(run(denied).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use crate::commands::{hooks::ExecPolicy, InputBuilder, RelativeDate};
+
+#[derive(Clone, Debug)]
+pub struct SummarizeConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Only the sections of these days are summarized (`None` for an open end)
+    pub from: Option<RelativeDate>,
+    pub until: Option<RelativeDate>,
+    /// Only the sections with one of these tags are summarized (all sections if empty)
+    pub tags: Vec<String>,
+    /// The summarizer, reading the plain text of the sections from stdin and writing the
+    /// summary to stdout (e.g. `ollama run llama3 "Summarize:"`)
+    pub command: String,
+    pub exec_policy: ExecPolicy,
+}
+
+impl SummarizeConfig {
+    pub fn builder() -> SummarizeConfigBuilder {
+        SummarizeConfigBuilder::default()
+    }
+}
+
+/// Builder for [`SummarizeConfig`]
+///
+/// The summarizer has to be set.
+#[derive(Clone, Debug)]
+pub struct SummarizeConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    from: Option<RelativeDate>,
+    until: Option<RelativeDate>,
+    tags: Vec<String>,
+    command: String,
+    exec_policy: ExecPolicy,
+}
+
+impl Default for SummarizeConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            from: None,
+            until: None,
+            tags: vec![],
+            command: String::new(),
+            exec_policy: ExecPolicy::default(),
+        }
+    }
+}
+
+impl InputBuilder for SummarizeConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl SummarizeConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Summarize the sections of this day and later
+    pub fn from(mut self, date: RelativeDate) -> Self {
+        self.from = Some(date);
+        self
+    }
+
+    /// Summarize the sections of this day and earlier
+    pub fn until(mut self, date: RelativeDate) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    /// Summarize the sections with the tag (given without `@`, repeat for several tags)
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// The summarizer, see [`SummarizeConfig::command`]
+    pub fn command<S: Into<String>>(mut self, command: S) -> Self {
+        self.command = command.into();
+        self
+    }
+
+    /// Which summarizers may be run
+    pub fn exec_policy(mut self, policy: ExecPolicy) -> Self {
+        self.exec_policy = policy;
+        self
+    }
+
+    pub fn build(self) -> SummarizeConfig {
+        SummarizeConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            from: self.from,
+            until: self.until,
+            tags: self.tags,
+            command: self.command,
+            exec_policy: self.exec_policy,
+        }
+    }
+}
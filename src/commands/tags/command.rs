@@ -1,57 +1,80 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{borrow::Borrow, cmp::Ordering, collections::HashMap};
 
 use anyhow::Result;
 
-use super::config::{TagsConfig, TagOrderingCriterion};
+use super::{
+    config::{TagsConfig, TagOrderingCriterion},
+    normalize::TagNormalizer,
+};
 use crate::{
-    commands::io::{FileReader, OutputWriter},
-    models::{MarkdownTokenizer, Token},
+    commands::{
+        io::OutputSink,
+        locale::{Language, Text},
+        Command, ParseContext, Report,
+    },
+    models::Token,
 };
 
-pub fn run<T, R>(
-    config: TagsConfig,
-    tokenizer: T,
-    reader: R,
-    writers: Vec<Box<dyn OutputWriter>>,
-) -> Result<()>
-where
-    T: MarkdownTokenizer,
-    R: FileReader,
-{
-    let markdown_string = reader.read(config.input_path.clone())?;
-    let tokens = tokenizer.tokenize(&markdown_string)?;
-    let count = count_tags(tokens);
-
-    if count.is_empty() {
-        log::warn!("No tags found!");
-        return Ok(());
-    }
+pub struct TagsCommand {
+    pub config: TagsConfig,
+}
 
-    let output_string = count_to_string(&count, &config.ordering);
-    for writer in writers {
-        writer.write_output(&output_string)?;
-    }
+impl Command for TagsCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let mut tokens = ctx.token_stream(&files);
+        let count = count_tags(
+            tokens.by_ref().map(|(t, _)| t),
+            files.iter().flat_map(|f| f.front_matter_tags()),
+            &ctx.tags,
+        );
+        tokens.finish()?;
+
+        if count.is_empty() {
+            log::warn!("No tags found!");
+            return Ok(Report::default());
+        }
 
-    Ok(())
+        let output_string = count_to_string(&count, &config.ordering, ctx.lang);
+        out.write_output(&output_string)?;
+
+        Ok(Report {
+            result_count: count.len(),
+        })
+    }
 }
 
-fn count_tags(tokens: Vec<Token>) -> HashMap<String, usize> {
+/// Count the tags of the tokens (in the tag style of `tags`) and of the front matter by
+/// their canonical name, occurences of child tags (e.g. `@work/projectX`) count for their
+/// parents (`@work`) as well.
+pub(crate) fn count_tags<'t, 'a, T: Borrow<Token<'a>>>(
+    tokens: impl IntoIterator<Item = T>,
+    front_matter_tags: impl IntoIterator<Item = &'t String>,
+    tags: &TagNormalizer,
+) -> HashMap<String, usize> {
     let mut count: HashMap<String, usize> = HashMap::new();
+    let mut add = |s: &str| {
+        for tag in tags.with_parents(s) {
+            *count.entry(tag).or_insert(0) += 1;
+        }
+    };
     for token in tokens {
-        match &token {
-            Token::Tag(s) => *count
-                .entry(s.to_string())
-                .and_modify(|x| *x += 1)
-                .or_insert(1),
-            _ => continue,
-        };
+        for s in tags.tags(token.borrow()) {
+            add(s);
+        }
     }
+    front_matter_tags.into_iter().for_each(|s| add(s));
     count
 }
 
-fn count_to_string(count: &HashMap<String, usize>, ordering: &TagOrderingCriterion) -> String {
+fn count_to_string(
+    count: &HashMap<String, usize>,
+    ordering: &TagOrderingCriterion,
+    lang: Language,
+) -> String {
     let mut counts = count
-        .to_owned()
+        .clone()
         .into_iter()
         .collect::<Vec<(String, usize)>>();
     match ordering {
@@ -67,6 +90,81 @@ fn count_to_string(count: &HashMap<String, usize>, ordering: &TagOrderingCriteri
         .map(|c| format!("{:<20} {:>10}\n", c.0, c.1,))
         .collect::<String>();
 
-    s.insert_str(0, &format!("{:<20} {:>10}\n", "Tag", "Count"));
+    s.insert_str(
+        0,
+        &format!(
+            "{:<20} {:>10}\n",
+            lang.text(Text::TagHeader),
+            lang.text(Text::CountHeader)
+        ),
+    );
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        markdown::{MDPMarkdownTokenizer, TagStyle},
+        models::MarkdownTokenizer,
+    };
+
+    #[test]
+    fn test_count_tags() {
+        let tags = TagNormalizer::new(&HashMap::from([(
+            "work".to_string(),
+            vec!["Work".to_string()],
+        )]));
+        let count = count_tags(
+            vec![
+                Token::Tag("work"),
+                Token::Tag("Work/projectX"),
+                Token::Tag("work/projectY"),
+                Token::Text("@work"),
+            ],
+            &["work/projectY".to_string()],
+            &tags,
+        );
+
+        assert_eq!(count.get("work"), Some(&4));
+        assert_eq!(count.get("work/projectX"), Some(&1));
+        assert_eq!(count.get("work/projectY"), Some(&2));
+        assert_eq!(count.get("Work/projectX"), None);
+        assert_eq!(count.len(), 3);
+
+        let tokens = vec![Token::Tag("work"), Token::Hashtag("topic")];
+        let count = count_tags(
+            tokens.clone(),
+            &[],
+            &tags.clone().with_style(TagStyle::Hash),
+        );
+        assert_eq!(count.keys().collect::<Vec<_>>(), vec!["topic"]);
+        let count = count_tags(tokens, &[], &tags.with_style(TagStyle::Both));
+        assert_eq!(count.len(), 2);
+    }
+
+    #[test]
+    fn test_count_tags_list_items() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize("# 2024-05-20 @day\n\n- item @b\n- [ ] task @b\n  - TODO: nested @c\n")?;
+        let count = count_tags(tokens, &[], &TagNormalizer::default());
+
+        assert_eq!(count.get("b"), Some(&2));
+        assert_eq!(count.get("c"), Some(&1));
+        assert_eq!(count.get("day"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_tags_table() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize("# 2024-05-20\n\n| @x | y |\n|---|---|\n| @x | @z |\n")?;
+        let count = count_tags(tokens, &[], &TagNormalizer::default());
+
+        assert_eq!(count.get("x"), Some(&2));
+        assert_eq!(count.get("z"), Some(&1));
+        Ok(())
+    }
+}
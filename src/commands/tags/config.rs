@@ -1,12 +1,72 @@
 use std::path::PathBuf;
 
+use crate::commands::InputBuilder;
+
 #[derive(Clone, Debug)]
 pub struct TagsConfig {
     pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
     pub ordering: TagOrderingCriterion,
     pub output_path: Option<PathBuf>,
 }
 
+impl TagsConfig {
+    pub fn builder() -> TagsConfigBuilder {
+        TagsConfigBuilder::default()
+    }
+}
+
+/// Builder for [`TagsConfig`]
+#[derive(Clone, Debug)]
+pub struct TagsConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    ordering: TagOrderingCriterion,
+    output_path: Option<PathBuf>,
+}
+
+impl Default for TagsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            ordering: TagOrderingCriterion::Alphabetic,
+            output_path: None,
+        }
+    }
+}
+
+impl InputBuilder for TagsConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TagsConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    pub fn ordering(mut self, ordering: TagOrderingCriterion) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    pub fn build(self) -> TagsConfig {
+        TagsConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            ordering: self.ordering,
+            output_path: self.output_path,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum TagOrderingCriterion {
     Count,
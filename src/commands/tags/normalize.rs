@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use crate::{
+    markdown::TagStyle,
+    models::{Section, Token},
+};
+
+/// Separates the levels of hierarchical tags, e.g. `@work/projectX` is a child of `@work`
+pub const TAG_HIERARCHY_SEPARATOR: char = '/';
+
+/// Maps tags to their canonical name, shared by all commands dealing with tags.
+///
+/// Aliases are resolved for the whole tag as well as for its parents (e.g. with the alias
+/// `Work` for `work`, `Work/projectX` becomes `work/projectX`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagNormalizer {
+    /// Alias to canonical name
+    aliases: HashMap<String, String>,
+    /// Which tokens are tags
+    style: TagStyle,
+}
+
+impl TagNormalizer {
+    /// `aliases` maps every canonical name to its aliases
+    pub fn new(aliases: &HashMap<String, Vec<String>>) -> Self {
+        Self {
+            aliases: aliases
+                .iter()
+                .flat_map(|(tag, aliases)| aliases.iter().map(move |a| (a.clone(), tag.clone())))
+                .collect(),
+            style: TagStyle::default(),
+        }
+    }
+
+    /// Read the tokens of the given style as tags (e.g. `#tag` for Obsidian vaults)
+    pub fn with_style(mut self, style: TagStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// The name of the tag if the token is a tag (in the style of the notes)
+    pub fn tag<'a>(&self, token: &Token<'a>) -> Option<&'a str> {
+        self.style.tag(token)
+    }
+
+    /// The names of the tags within the token (see [`TagStyle::tags`])
+    pub fn tags<'a>(&self, token: &Token<'a>) -> Vec<&'a str> {
+        self.style.tags(token)
+    }
+
+    /// The canonical name of a tag (without the leading `@`)
+    pub fn normalize(&self, tag: &str) -> String {
+        let prefixes = tag
+            .match_indices(TAG_HIERARCHY_SEPARATOR)
+            .map(|(i, _)| i)
+            .chain(std::iter::once(tag.len()))
+            .rev();
+
+        for end in prefixes {
+            if let Some(canonical) = self.aliases.get(&tag[..end]) {
+                return format!("{}{}", canonical, &tag[end..]);
+            }
+        }
+        tag.to_string()
+    }
+
+    /// The canonical name of a tag followed by the ones of all its parents, e.g.
+    /// `work/projectX/design`, `work/projectX` and `work`.
+    pub fn with_parents(&self, tag: &str) -> Vec<String> {
+        let tag = self.normalize(tag);
+        let mut tags = vec![tag.clone()];
+        let mut current = tag.as_str();
+        while let Some((parent, _)) = current.rsplit_once(TAG_HIERARCHY_SEPARATOR) {
+            tags.push(parent.to_string());
+            current = parent;
+        }
+        tags
+    }
+
+    /// Whether `tag` is `query` (or an alias of it) or one of its children
+    pub fn matches(&self, tag: &str, query: &str) -> bool {
+        self.with_parents(tag).contains(&self.normalize(query))
+    }
+
+    /// Replace the tags of the sections (and their subsections) by their canonical names
+    pub fn normalize_sections(&self, sections: &mut [Section]) {
+        for section in sections {
+            for tag in section.tags.iter_mut() {
+                *tag = self.normalize(tag);
+            }
+            self.normalize_sections(&mut section.subsections);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn normalizer() -> TagNormalizer {
+        TagNormalizer::new(&HashMap::from([
+            ("rega".to_string(), vec!["Rega".to_string()]),
+            ("work".to_string(), vec!["Work".to_string(), "job".to_string()]),
+        ]))
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = normalizer();
+
+        assert_eq!(tags.normalize("Rega"), "rega");
+        assert_eq!(tags.normalize("rega"), "rega");
+        assert_eq!(tags.normalize("job/projectX"), "work/projectX");
+        assert_eq!(tags.normalize("home"), "home");
+        assert_eq!(
+            tags.with_parents("Work/projectX/design"),
+            vec!["work/projectX/design", "work/projectX", "work"]
+        );
+    }
+
+    #[test]
+    fn test_match_tags() {
+        let tags = normalizer();
+
+        assert!(tags.matches("work/projectX", "work"));
+        assert!(tags.matches("job/projectX", "Work"));
+        assert!(tags.matches("Rega", "rega"));
+        assert!(!tags.matches("work", "work/projectX"));
+        assert!(!tags.matches("workshop", "work"));
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::{
+    command::{tasks_from_sections, Task},
+    config::TaskCapacityConfig,
+};
+use crate::{
+    commands::{add_days, io::OutputSink, locale::Text, Command, ParseContext, Report},
+    models::{TaskStatus, Token},
+};
+
+/// Lists the days (from today on) on which more tasks are due than the daily capacity
+/// allows, together with suggestions which tasks to move to which day.
+///
+/// The tasks written down first keep their due date, the ones added later are suggested to
+/// be moved to the next day with free capacity.
+pub struct TaskCapacityCommand {
+    pub config: TaskCapacityConfig,
+}
+
+impl Command for TaskCapacityCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let capacity = self.config.capacity;
+        let files = ctx.read(self.config.input_path.clone())?;
+        let tasks = tasks_from_sections(&ctx.sections(&files)?);
+
+        let mut due = due_dates(&tasks, ctx.clock.today());
+        let overloaded: Vec<NaiveDate> = due
+            .iter()
+            .filter(|(_, tasks)| tasks.len() > capacity)
+            .map(|(date, _)| *date)
+            .collect();
+
+        let mut days = vec![];
+        for date in &overloaded {
+            let mut day_tasks = due.get(date).cloned().unwrap_or_default();
+            // Stable, so tasks of the same section keep their order
+            day_tasks.sort_by_key(|t| t.date);
+
+            let mut lines = vec![format!(
+                "{}: {}",
+                date,
+                ctx.lang
+                    .format(Text::TasksDue, &[&day_tasks.len(), &capacity])
+            )];
+            for task in day_tasks.split_off(capacity) {
+                let Some(target) = next_free_day(&due, *date, capacity) else {
+                    continue;
+                };
+                due.entry(target).or_default().push(task);
+                lines.push(format!(
+                    "  [{}] {} -> {}",
+                    task.id,
+                    Token::from(task).to_markdown_string(),
+                    target
+                ));
+            }
+            days.push(lines.join("\n"));
+        }
+
+        out.write_output(&days.join("\n"))?;
+        Ok(Report {
+            result_count: overloaded.len(),
+        })
+    }
+}
+
+/// The unfinished tasks due today or later, grouped by their due date
+fn due_dates<'t, 'a>(
+    tasks: &'t [Task<'a>],
+    today: NaiveDate,
+) -> BTreeMap<NaiveDate, Vec<&'t Task<'a>>> {
+    let mut due: BTreeMap<NaiveDate, Vec<&Task>> = BTreeMap::new();
+    for task in tasks {
+        if let TaskStatus::TodoUntil(date) = &task.status {
+            if date.date >= today {
+                due.entry(date.date).or_default().push(task);
+            }
+        }
+    }
+    due
+}
+
+/// The first day after `date` with less than `capacity` tasks due
+fn next_free_day(
+    due: &BTreeMap<NaiveDate, Vec<&Task>>,
+    date: NaiveDate,
+    capacity: usize,
+) -> Option<NaiveDate> {
+    let mut day = add_days(date, 1)?;
+    while due.get(&day).is_some_and(|tasks| tasks.len() >= capacity) {
+        day = add_days(day, 1)?;
+    }
+    Some(day)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{tasks::ids::task_id, FixedClock, InputBuilder};
+
+    const DIARY: &str = "\
+# 2023-10-09
+
+TODO UNTIL 2023-10-12: Pay the bill
+TODO UNTIL 2023-10-13: Call Anna
+
+# 2023-10-10
+
+TODO UNTIL 2023-10-12: Book flight
+TODO UNTIL 2023-10-12: Send minutes
+DONE: Write report
+TODO UNTIL 2023-10-01: Water plants
+";
+
+    #[test]
+    fn test_capacity_suggestions() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(&path, DIARY)?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap()));
+        let run = |capacity: usize| -> Result<(String, usize)> {
+            let config = TaskCapacityConfig::builder()
+                .input(&path)
+                .capacity(capacity)
+                .build()?;
+            let mut output = String::new();
+            let report = TaskCapacityCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let tokens = ctx
+            .tokenizer
+            .tokenize_with_origins(DIARY, Some(path.as_path()))?;
+        let id = |line: usize| {
+            let (token, origin) = tokens.iter().find(|(_, o)| o.line == line).unwrap();
+            match token {
+                Token::Task { content, .. } => task_id(origin, content),
+                _ => unreachable!(),
+            }
+        };
+
+        assert_eq!(
+            run(2)?,
+            (
+                format!(
+                    "2023-10-12: 3 tasks due (capacity 2)\n  \
+                     [{}] TODO UNTIL 2023-10-12: Send minutes -> 2023-10-13",
+                    id(9)
+                ),
+                1
+            )
+        );
+        assert_eq!(
+            run(1)?,
+            (
+                format!(
+                    "2023-10-12: 3 tasks due (capacity 1)\n  \
+                     [{}] TODO UNTIL 2023-10-12: Book flight -> 2023-10-14\n  \
+                     [{}] TODO UNTIL 2023-10-12: Send minutes -> 2023-10-15",
+                    id(8),
+                    id(9)
+                ),
+                1
+            )
+        );
+        assert_eq!(run(3)?, (String::new(), 0));
+        assert!(TaskCapacityConfig::builder().capacity(0).build().is_err());
+        Ok(())
+    }
+}
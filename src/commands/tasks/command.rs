@@ -1,65 +1,180 @@
+use std::{borrow::Borrow, collections::HashSet};
+
 use anyhow::Result;
-use chrono::{NaiveDate, Utc};
+use chrono::NaiveDate;
 
-use super::config::{TasksConfig, TaskFilterType, TaskOrderingCriterion};
+use super::{
+    config::{TasksConfig, TaskFilterType, TaskOrderingCriterion},
+    ids::assign_ids,
+    stats::TaskStats,
+};
 use crate::{
-    commands::io::{FileReader, OutputWriter},
-    models::{MarkdownTokenizer, TaskStatus, Token},
+    commands::{
+        io::OutputSink,
+        oneline::{location, oneline, ResultFormat},
+        query::Query,
+        rewrite::Replacement,
+        Command, ParseContext, Report,
+    },
+    markdown::indentation_width,
+    models::{Origin, Section, TaskStatus, Token},
 };
 
-pub fn run<T, R>(
-    config: TasksConfig,
-    tokenizer: T,
-    reader: R,
-    writers: Vec<Box<dyn OutputWriter>>,
-) -> Result<()>
-where
-    T: MarkdownTokenizer,
-    R: FileReader,
-{
-    let markdown_string = reader.read(config.input_path.clone())?;
-    let tokens = tokenizer.tokenize(&markdown_string)?;
+pub struct TasksCommand {
+    pub config: TasksConfig,
+}
 
-    let tasks = tasks_from_tokens(tokens);
-    let tasks = filter_tasks(tasks, config.filter);
-    let tasks = order_tasks(tasks, config.ordering);
-    let task_strings = tasks_as_strings(tasks);
+impl Command for TasksCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
 
-    let output_string = task_strings.join("\n");
-    for writer in writers {
-        writer.write_output(&output_string)?;
-    }
+        // The date of the section a task belongs to is only known if built from sections
+        let with_dates = config.context
+            || config.format == ResultFormat::Oneline
+            || config.from.is_some()
+            || config.until.is_some();
+        let mut tasks = match with_dates {
+            true => tasks_from_sections(&ctx.sections(&files)?),
+            false => {
+                let mut tokens = ctx.token_stream(&files);
+                let tasks = tasks_from_tokens(tokens.by_ref());
+                tokens.finish()?;
+                tasks
+            }
+        };
+        let today = ctx.clock.today();
+        let query = config.query();
+        // The summary covers the tasks of all statuses
+        let all_statuses = Query {
+            status: TaskFilterType::All,
+            ..query.clone()
+        };
+        tasks.retain(|t| all_statuses.matches_task(&t.status, t.date));
+        let stats = TaskStats::new(tasks.iter().map(|t| &t.status));
+        tasks.retain(|t| query.matches_task(&t.status, t.date));
+        if !config.include_snoozed {
+            tasks.retain(|t| !t.is_snoozed(today));
+        }
+        let tasks = order_tasks(tasks, config.ordering.clone(), today);
+        if config.format == ResultFormat::Oneline {
+            let lines = tasks
+                .iter()
+                .map(|t| task_line(t, config.show_ids))
+                .collect::<Vec<String>>();
+            out.write_output(&lines.join("\n"))?;
+            return Ok(Report {
+                result_count: lines.len(),
+            });
+        }
+        let task_strings = tasks_as_strings(
+            tasks.clone(),
+            config.show_origin,
+            config.context,
+            config.show_ids,
+        );
 
-    Ok(())
+        let mut output_string = match config.tree {
+            true => tasks_as_tree(&tasks, &task_strings).join("\n"),
+            false => task_strings.join("\n"),
+        };
+        if config.summary {
+            if !output_string.is_empty() {
+                output_string += "\n\n";
+            }
+            output_string += &stats.summary(today, ctx.lang);
+        }
+        out.write_output(&output_string)?;
+
+        Ok(Report {
+            result_count: task_strings.len(),
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
-struct Task<'a> {
-    content: Vec<Token<'a>>,
-    status: TaskStatus,
+pub(super) struct Task<'a> {
+    pub(super) content: Vec<Token<'a>>,
+    pub(super) status: TaskStatus,
+    /// List item prefix of a task written as checkbox (see [`Token::Task`])
+    pub(super) checkbox: Option<&'a str>,
+    pub(super) origin: Origin,
+    /// Date of the section the task belongs to (only known if built from sections)
+    pub(super) date: Option<NaiveDate>,
+    /// Titles of the headings (below the dated H1) the task is nested in
+    pub(super) headings: Vec<String>,
+    /// Unique among the tasks read, see [`assign_ids`]
+    pub(super) id: String,
+    /// Marker of a task written as list item without its indentation (e.g. `- ` or `1. `)
+    pub(super) list_marker: Option<&'a str>,
+    /// The list items the task is nested in, starting with the outermost
+    pub(super) parents: Vec<ListParent>,
+}
+
+/// A list item a task is nested in, which might be a task itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct ListParent {
+    pub(super) origin: Origin,
+    /// The item without its indentation (e.g. `- Shopping` or `- TODO: Trip`)
+    pub(super) text: String,
 }
 
 impl<'a> Task<'a> {
-    fn is_finished(&self) -> bool {
-        match self.status {
-            TaskStatus::Done => true,
-            _ => false,
-        }
+    pub(super) fn is_finished(&self) -> bool {
+        matches!(self.status, TaskStatus::Done)
+    }
+
+    /// Whether the task is hidden by a snooze annotation until a day after `today`
+    pub(super) fn is_snoozed(&self, today: NaiveDate) -> bool {
+        Token::from(self)
+            .snoozed_until()
+            .is_some_and(|until| today < until)
+    }
+
+    /// The task with its list marker, as shown by `--tree`
+    fn list_item_string(&self) -> String {
+        format!(
+            "{}{}",
+            self.list_marker.unwrap_or_default(),
+            Token::from(self).to_markdown_string()
+        )
     }
 
-    fn is_unfinished(&self) -> bool {
+    pub(super) fn is_unfinished(&self) -> bool {
         !self.is_finished()
     }
 
-    fn urgency(&self) -> usize {
-        match self.status {
+    /// Replacement of the status of the task in its file (`TODO:` or the `[ ]` of a
+    /// checkbox), `None` if a checkbox can not have the new status (e.g. a due date)
+    pub(super) fn status_replacement(&self, status: &TaskStatus) -> Option<Replacement> {
+        let (column, old, new) = match self.checkbox {
+            Some(prefix) => (
+                self.origin.column + prefix.chars().count(),
+                self.status.checkbox(),
+                status.checkbox_mark().map(|_| status.checkbox())?,
+            ),
+            None => (
+                self.origin.column,
+                format!("{}:", self.status),
+                format!("{}:", status),
+            ),
+        };
+        Some(Replacement {
+            line: self.origin.line,
+            column,
+            old,
+            new,
+        })
+    }
+
+    fn urgency(&self, today: NaiveDate) -> usize {
+        match &self.status {
             TaskStatus::Done => 0,
-            TaskStatus::Review => 10,
+            TaskStatus::Review | TaskStatus::Waiting(_) => 10,
             TaskStatus::Doing => 20,
             TaskStatus::Todo => 30,
-            TaskStatus::TodoUntil(d) => {
-                let today: NaiveDate = Utc::now().naive_utc().into();
-                let days_until = (d - today).num_days();
+            TaskStatus::TodoUntil(due) => {
+                let days_until = (due.date - today).num_days();
                 let urgency = if days_until > 0 {
                     days_until * 10
                 } else {
@@ -76,49 +191,455 @@ impl<'a> From<&Task<'a>> for Token<'a> {
         Token::Task {
             content: value.content.clone(),
             status: value.status.clone(),
+            checkbox: None,
         }
     }
 }
 
-fn tasks_from_tokens(tokens: Vec<Token>) -> Vec<Task> {
-    tokens
-        .iter()
-        .filter_map(|t| match t {
-            Token::Task { content, status } => Some(Task {
-                content: content.to_owned(),
-                status: status.to_owned(),
-            }),
-            _ => None,
-        })
-        .collect()
+pub(super) fn tasks_from_tokens<'a, I>(tokens: I) -> Vec<Task<'a>>
+where
+    I: IntoIterator<Item = (Token<'a>, Origin)>,
+{
+    let mut tasks = collect_tasks(tokens, None, &[]);
+    assign_ids(&mut tasks);
+    tasks
 }
 
-fn filter_tasks(tasks: Vec<Task>, filter: TaskFilterType) -> Vec<Task> {
-    match filter {
-        TaskFilterType::All => tasks,
-        TaskFilterType::Finished => tasks.iter().filter(|t| t.is_finished()).cloned().collect(),
-        TaskFilterType::Unfinished => tasks
-            .iter()
-            .filter(|t| t.is_unfinished())
-            .cloned()
-            .collect(),
+/// Collect the tasks of all sections (and their subsections) including their context.
+pub(super) fn tasks_from_sections<'a>(sections: &[Section<'a>]) -> Vec<Task<'a>> {
+    let mut tasks = section_tasks(sections, &[]);
+    assign_ids(&mut tasks);
+    tasks
+}
+
+fn section_tasks<'a>(sections: &[Section<'a>], parent_headings: &[String]) -> Vec<Task<'a>> {
+    let mut tasks = vec![];
+
+    for section in sections {
+        let mut headings = parent_headings.to_vec();
+        if !section.section_type.is_top_level() {
+            headings.push(section.title_string());
+        }
+
+        tasks.extend(collect_tasks(
+            section.content_with_origins(),
+            section.date,
+            &headings,
+        ));
+
+        tasks.extend(section_tasks(&section.subsections, &headings));
     }
+
+    tasks
 }
 
-fn order_tasks(tasks: Vec<Task>, ordering: TaskOrderingCriterion) -> Vec<Task> {
+/// The tasks of the tokens (of a section or file), tasks written as list items with the list
+/// items they are nested in
+fn collect_tasks<'a, T, I>(tokens: I, date: Option<NaiveDate>, headings: &[String]) -> Vec<Task<'a>>
+where
+    T: Borrow<Token<'a>>,
+    I: IntoIterator<Item = (T, Origin)>,
+{
+    let mut tasks = vec![];
+    let mut list = ListStack::default();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some((item, origin)) = tokens.next() {
+        let token = item.borrow();
+        let (item, origin, prefix) = match token {
+            // A task written as list item with a keyword (`- TODO: Task`) follows its
+            // empty list item
+            Token::ListItem { prefix, .. } => {
+                match tokens.next_if(|(t, _)| matches!(t.borrow(), Token::Task { .. })) {
+                    Some((task, task_origin)) => (task, task_origin, Some(*prefix)),
+                    None => {
+                        let text = token.to_markdown_string().trim_start().to_string();
+                        list.push(prefix, ListParent { origin, text });
+                        continue;
+                    }
+                }
+            }
+            Token::Task { checkbox, .. } => {
+                let checkbox = *checkbox;
+                (item, origin, checkbox)
+            }
+            Token::Newline | Token::Blank => continue,
+            // An unindented line which is no list item ends the list
+            token => {
+                if origin.column == 1 && !token.to_markdown_string().starts_with([' ', '\t']) {
+                    list.clear();
+                }
+                continue;
+            }
+        };
+        let Token::Task {
+            content,
+            status,
+            checkbox,
+        } = item.borrow()
+        else {
+            continue;
+        };
+
+        let mut task = Task {
+            content: content.to_owned(),
+            status: status.to_owned(),
+            checkbox: *checkbox,
+            origin,
+            date,
+            headings: headings.to_vec(),
+            id: String::new(),
+            list_marker: prefix.map(str::trim_start),
+            parents: vec![],
+        };
+        match prefix {
+            Some(prefix) => {
+                let parent = ListParent {
+                    origin: task.origin.clone(),
+                    text: task.list_item_string(),
+                };
+                task.parents = list.push(prefix, parent);
+            }
+            None if task.origin.column == 1 => list.clear(),
+            None => task.parents = list.parents(),
+        }
+        tasks.push(task);
+    }
+    tasks
+}
+
+/// The list items enclosing the current line together with their indentation
+#[derive(Default)]
+struct ListStack(Vec<(usize, ListParent)>);
+
+impl ListStack {
+    /// Adds a list item, returns the list items it is nested in
+    fn push(&mut self, prefix: &str, item: ListParent) -> Vec<ListParent> {
+        let indentation = indentation_width(prefix);
+        while self.0.last().is_some_and(|(i, _)| *i >= indentation) {
+            self.0.pop();
+        }
+        let parents = self.parents();
+        self.0.push((indentation, item));
+        parents
+    }
+
+    fn parents(&self) -> Vec<ListParent> {
+        self.0.iter().map(|(_, item)| item.clone()).collect()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+fn order_tasks(tasks: Vec<Task>, ordering: TaskOrderingCriterion, today: NaiveDate) -> Vec<Task> {
     match ordering {
         TaskOrderingCriterion::Occurence => tasks,
         TaskOrderingCriterion::Urgency => {
             let mut ordered_tasks = tasks.clone();
-            ordered_tasks.sort_by_key(|t| t.urgency());
+            ordered_tasks.sort_by_key(|t| t.urgency(today));
             ordered_tasks
         }
     }
 }
 
-fn tasks_as_strings(tasks: Vec<Task>) -> Vec<String> {
+fn tasks_as_strings(
+    tasks: Vec<Task>,
+    show_origin: bool,
+    show_context: bool,
+    show_ids: bool,
+) -> Vec<String> {
     tasks
         .iter()
-        .map(|t| Token::from(t).to_markdown_string())
+        .map(|t| {
+            let mut s = Token::from(t).to_markdown_string();
+            if show_context {
+                s = task_context(t)
+                    .into_iter()
+                    .chain(std::iter::once(s))
+                    .collect::<Vec<String>>()
+                    .join(" > ");
+            }
+            if show_origin {
+                s = format!("{}: {}", t.origin, s);
+            }
+            if show_ids {
+                s = format!("[{}] {}", t.id, s);
+            }
+            s
+        })
         .collect()
 }
+
+/// The task as line of `--format oneline`: the date of its section, its status (e.g.
+/// `TODO UNTIL 2024-06-10`, also for relative deadlines), the task as plain text and its
+/// location, starting with its id if `show_id` is set
+fn task_line(task: &Task, show_id: bool) -> String {
+    let status = match &task.status {
+        TaskStatus::TodoUntil(due) => TaskStatus::TodoUntil(due.date.into()).to_string(),
+        status => status.to_string(),
+    };
+    let mut fields = vec![
+        task.date.map(|d| d.to_string()).unwrap_or_default(),
+        status,
+        task.content.iter().map(Token::to_plain_string).collect(),
+        location(&task.origin),
+    ];
+    if show_id {
+        fields.insert(0, task.id.clone());
+    }
+    oneline(&fields)
+}
+
+/// The tasks (already formatted as `strings`) nested like the list items they were written
+/// as. The list items a task is nested in are shown above it, also if they are no tasks or
+/// not shown themselves (e.g. a done task with unfinished subtasks).
+fn tasks_as_tree(tasks: &[Task], strings: &[String]) -> Vec<String> {
+    let mut lines = vec![];
+    let mut shown = HashSet::new();
+    for (task, s) in tasks.iter().zip(strings) {
+        for (depth, parent) in task.parents.iter().enumerate() {
+            if shown.insert(&parent.origin) {
+                lines.push(format!("{}{}", "  ".repeat(depth), parent.text));
+            }
+        }
+        shown.insert(&task.origin);
+        lines.push(format!(
+            "{}{}{}",
+            "  ".repeat(task.parents.len()),
+            task.list_marker.unwrap_or_default(),
+            s
+        ));
+    }
+    lines
+}
+
+/// The date and the headings of the section the task was found in
+pub(super) fn task_context(task: &Task) -> Vec<String> {
+    task.date
+        .iter()
+        .map(|d| d.to_string())
+        .chain(task.headings.iter().cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        commands::{tasks::config::TasksConfigBuilder, Clock, FixedClock, InputBuilder},
+        markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+        models::{MarkdownTokenizer, SectionBuilder},
+    };
+
+    fn task(status: TaskStatus) -> Task<'static> {
+        Task {
+            content: vec![Token::Text("task")],
+            status,
+            checkbox: None,
+            origin: Origin::default(),
+            date: None,
+            headings: vec![],
+            id: String::new(),
+            list_marker: None,
+            parents: vec![],
+        }
+    }
+
+    #[test]
+    fn test_urgency_depends_on_today() {
+        let due = NaiveDate::from_ymd_opt(2023, 10, 10).unwrap();
+        let t = task(TaskStatus::TodoUntil(due.into()));
+
+        let before = FixedClock(NaiveDate::from_ymd_opt(2023, 10, 8).unwrap());
+        let after = FixedClock(NaiveDate::from_ymd_opt(2023, 10, 12).unwrap());
+
+        assert_eq!(t.urgency(before.today()), 50);
+        assert_eq!(t.urgency(after.today()), 230);
+    }
+
+    #[test]
+    fn test_is_snoozed() {
+        let date = |d| NaiveDate::from_ymd_opt(2023, 10, d).unwrap();
+        let mut t = task(TaskStatus::Todo);
+        assert!(!t.is_snoozed(date(10)));
+
+        t.content.push(Token::Snooze {
+            markup: "@snooze(2023-10-12)",
+            date: date(12),
+        });
+        assert!(t.is_snoozed(date(11)));
+        assert!(!t.is_snoozed(date(12)));
+    }
+
+    #[test]
+    fn test_tasks_query() {
+        let date = |d| NaiveDate::from_ymd_opt(2023, 10, d).unwrap();
+        let mut t = task(TaskStatus::TodoUntil(date(12).into()));
+        t.date = Some(date(5));
+        let undated = task(TaskStatus::Todo);
+        let passes = |t: &Task, config: TasksConfigBuilder| {
+            config.build().query().matches_task(&t.status, t.date)
+        };
+        let config = TasksConfig::builder;
+
+        assert!(passes(&t, config()));
+        assert!(passes(&undated, config()));
+        assert!(passes(&t, config().from(date(5)).until(date(5))));
+        assert!(!passes(&t, config().from(date(6))));
+        assert!(!passes(&undated, config().until(date(6))));
+        assert!(passes(&t, config().due_before(date(12))));
+        assert!(!passes(&t, config().due_after(date(13))));
+        assert!(!passes(&undated, config().due_before(date(12))));
+        assert!(!passes(&task(TaskStatus::Done), config()));
+        assert!(passes(&task(TaskStatus::Done), config().filter(TaskFilterType::All)));
+    }
+
+    #[test]
+    fn test_order_tasks_by_urgency() {
+        let today = NaiveDate::from_ymd_opt(2023, 10, 10).unwrap();
+        let overdue = NaiveDate::from_ymd_opt(2023, 10, 1).unwrap();
+        let tasks = vec![
+            task(TaskStatus::TodoUntil(overdue.into())),
+            task(TaskStatus::Todo),
+            task(TaskStatus::Review),
+        ];
+
+        let ordered = order_tasks(tasks, TaskOrderingCriterion::Urgency, today);
+        assert_eq!(
+            ordered.iter().map(|t| t.status.clone()).collect::<Vec<_>>(),
+            vec![
+                TaskStatus::Review,
+                TaskStatus::Todo,
+                TaskStatus::TodoUntil(overdue.into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tasks_from_sections_with_context() {
+        let date = NaiveDate::from_ymd_opt(2023, 10, 10).unwrap();
+        let tokens = vec![
+            Token::HeadingH1(vec![Token::Date(date)]),
+            Token::Newline,
+            Token::HeadingH2(vec![Token::Text("Meeting")]),
+            Token::Newline,
+            Token::Task {
+                content: vec![Token::Text("Send minutes")],
+                status: TaskStatus::Todo,
+                checkbox: None,
+            },
+            Token::Newline,
+        ];
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens).unwrap();
+
+        let tasks = tasks_from_sections(&sections);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].origin.line, 3);
+        assert_eq!(
+            tasks_as_strings(tasks, false, true, false),
+            vec!["2023-10-10 > Meeting > TODO: Send minutes"]
+        );
+    }
+
+    #[test]
+    fn test_tasks_as_tree() -> Result<()> {
+        let diary = "# 2023-10-10\n\n- DONE: Trip\n  - TODO: Book flight\n    - [ ] Window seat\n\
+                     \t- TODO: Pack\n- Shopping\n  1. TODO: Milk\nTODO: Call roger\n  - TODO: Gift\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(diary)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+
+        let query = Query {
+            status: TaskFilterType::Unfinished,
+            ..Default::default()
+        };
+        let mut tasks = tasks_from_sections(&sections);
+        tasks.retain(|t| query.matches_task(&t.status, t.date));
+        assert_eq!(tasks[0].origin.column, 5);
+        let strings = tasks_as_strings(tasks.clone(), false, false, false);
+        assert_eq!(
+            tasks_as_tree(&tasks, &strings),
+            vec![
+                "- DONE: Trip",
+                "  - TODO: Book flight",
+                "    - TODO: Window seat",
+                "    - TODO: Pack",
+                "- Shopping",
+                "  1. TODO: Milk",
+                "TODO: Call roger",
+                "- TODO: Gift",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_summary() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(
+            &path,
+            "# 2024-06-01\n\nDONE: Book flight\nTODO UNTIL 2024-06-03: Pack\n\
+             TODO UNTIL 2024-06-10: Send postcards\nWAITING(@roger): Keys\n",
+        )?;
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2024, 6, 5).unwrap()));
+
+        let config = TasksConfig::builder()
+            .input(&path)
+            .filter(TaskFilterType::Finished)
+            .summary(true)
+            .build();
+        let mut output = String::new();
+        let report = TasksCommand { config }.run(&ctx, &mut output)?;
+        assert_eq!(
+            output,
+            "DONE: Book flight\n\n\
+             TODO: 2, DOING: 0, REVIEW: 0, WAITING: 1, DONE: 1\n\
+             Completed: 25% (1/4)\n\
+             Overdue: 1\n\
+             Next deadline: 2024-06-10"
+        );
+        assert_eq!(report.result_count, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_oneline() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(
+            &path,
+            "# 2024-06-01\n\nTODO UNTIL +2d: Pack the **bags**\n\n## Trip\n\n\
+             - [ ] Call\troger\n",
+        )?;
+        let config = TasksConfig::builder()
+            .input(&path)
+            .format(ResultFormat::Oneline)
+            .show_ids(true)
+            .build();
+        let mut output = String::new();
+        let report = TasksCommand { config }.run(&ParseContext::default(), &mut output)?;
+        let lines = output
+            .lines()
+            .map(|l| l.split('\t').skip(1).collect::<Vec<&str>>())
+            .collect::<Vec<_>>();
+        let location = |line| format!("{}:{}", path.display(), line);
+        assert_eq!(
+            lines,
+            vec![
+                vec![
+                    "2024-06-01",
+                    "TODO UNTIL 2024-06-03",
+                    "Pack the bags",
+                    &location(3)
+                ],
+                vec!["2024-06-01", "TODO", "Call roger", &location(7)],
+            ]
+        );
+        assert_eq!(report.result_count, 2);
+        Ok(())
+    }
+}
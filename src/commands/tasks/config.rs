@@ -1,11 +1,410 @@
 use std::path::PathBuf;
 
+use chrono::NaiveDate;
+
+use crate::{
+    commands::{
+        oneline::ResultFormat,
+        query::{DateRange, Query},
+        InputBuilder, RelativeDate,
+    },
+    models::ConfigError,
+};
+
 #[derive(Clone, Debug)]
 pub struct TasksConfig {
     pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
     pub output_path: Option<PathBuf>,
     pub ordering: TaskOrderingCriterion,
     pub filter: TaskFilterType,
+    /// Prefix every task with the location it was found at
+    pub show_origin: bool,
+    /// Show the date and the headings each task is nested in
+    pub context: bool,
+    /// Prefix every task with its id (see [`super::ids::task_id`])
+    pub show_ids: bool,
+    /// Also show tasks snoozed until a day after today
+    pub include_snoozed: bool,
+    /// Only show tasks of sections from this date on
+    pub from: Option<NaiveDate>,
+    /// Only show tasks of sections up to this date
+    pub until: Option<NaiveDate>,
+    /// Only show tasks due (`TODO UNTIL`) on or after this date
+    pub due_after: Option<NaiveDate>,
+    /// Only show tasks due (`TODO UNTIL`) on or before this date
+    pub due_before: Option<NaiveDate>,
+    /// Nest the tasks written as list items like the list items
+    pub tree: bool,
+    /// Add a summary of the tasks (see [`super::stats::TaskStats::summary`]) after them
+    pub summary: bool,
+    /// Write the tasks as Markdown or one line each (date, status, task and location)
+    pub format: ResultFormat,
+}
+
+impl TasksConfig {
+    pub fn builder() -> TasksConfigBuilder {
+        TasksConfigBuilder::default()
+    }
+
+    /// The filters selecting the tasks shown
+    pub fn query(&self) -> Query {
+        Query {
+            dates: DateRange::new(self.from, self.until),
+            status: self.filter.clone(),
+            due: DateRange::new(self.due_after, self.due_before),
+            ..Default::default()
+        }
+    }
+}
+
+/// Builder for [`TasksConfig`]
+#[derive(Clone, Debug)]
+pub struct TasksConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    ordering: TaskOrderingCriterion,
+    filter: TaskFilterType,
+    show_origin: bool,
+    context: bool,
+    show_ids: bool,
+    include_snoozed: bool,
+    from: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    due_after: Option<NaiveDate>,
+    due_before: Option<NaiveDate>,
+    tree: bool,
+    summary: bool,
+    format: ResultFormat,
+}
+
+impl Default for TasksConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            ordering: TaskOrderingCriterion::Occurence,
+            filter: TaskFilterType::Unfinished,
+            show_origin: false,
+            context: false,
+            show_ids: false,
+            include_snoozed: false,
+            from: None,
+            until: None,
+            due_after: None,
+            due_before: None,
+            tree: false,
+            summary: false,
+            format: ResultFormat::Markdown,
+        }
+    }
+}
+
+impl InputBuilder for TasksConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TasksConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    pub fn ordering(mut self, ordering: TaskOrderingCriterion) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    pub fn filter(mut self, filter: TaskFilterType) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn show_origin(mut self, show_origin: bool) -> Self {
+        self.show_origin = show_origin;
+        self
+    }
+
+    pub fn context(mut self, context: bool) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn show_ids(mut self, show_ids: bool) -> Self {
+        self.show_ids = show_ids;
+        self
+    }
+
+    pub fn include_snoozed(mut self, include_snoozed: bool) -> Self {
+        self.include_snoozed = include_snoozed;
+        self
+    }
+
+    pub fn from(mut self, date: NaiveDate) -> Self {
+        self.from = Some(date);
+        self
+    }
+
+    pub fn until(mut self, date: NaiveDate) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    pub fn due_after(mut self, date: NaiveDate) -> Self {
+        self.due_after = Some(date);
+        self
+    }
+
+    pub fn due_before(mut self, date: NaiveDate) -> Self {
+        self.due_before = Some(date);
+        self
+    }
+
+    pub fn tree(mut self, tree: bool) -> Self {
+        self.tree = tree;
+        self
+    }
+
+    pub fn summary(mut self, summary: bool) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub fn format(mut self, format: ResultFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> TasksConfig {
+        TasksConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            ordering: self.ordering,
+            filter: self.filter,
+            show_origin: self.show_origin,
+            context: self.context,
+            show_ids: self.show_ids,
+            include_snoozed: self.include_snoozed,
+            from: self.from,
+            until: self.until,
+            due_after: self.due_after,
+            due_before: self.due_before,
+            tree: self.tree,
+            summary: self.summary,
+            format: self.format,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TaskDoneConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// The id of the task or its position (starting at 1) in the default task list
+    pub task: String,
+    /// Only show the change (as unified diff) instead of writing it to the file
+    pub dry_run: bool,
+}
+
+impl TaskDoneConfig {
+    pub fn builder() -> TaskDoneConfigBuilder {
+        TaskDoneConfigBuilder::default()
+    }
+}
+
+/// Builder for [`TaskDoneConfig`]
+#[derive(Clone, Debug)]
+pub struct TaskDoneConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    task: String,
+    dry_run: bool,
+}
+
+impl Default for TaskDoneConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            task: String::new(),
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for TaskDoneConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TaskDoneConfigBuilder {
+    pub fn task<S: Into<String>>(mut self, task: S) -> Self {
+        self.task = task.into();
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> TaskDoneConfig {
+        TaskDoneConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            task: self.task,
+            dry_run: self.dry_run,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TaskShowConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// The id of the task or its position (starting at 1) in the default task list
+    pub task: String,
+}
+
+impl TaskShowConfig {
+    pub fn builder() -> TaskShowConfigBuilder {
+        TaskShowConfigBuilder::default()
+    }
+}
+
+/// Builder for [`TaskShowConfig`]
+#[derive(Clone, Debug)]
+pub struct TaskShowConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    task: String,
+}
+
+impl Default for TaskShowConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            task: String::new(),
+        }
+    }
+}
+
+impl InputBuilder for TaskShowConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TaskShowConfigBuilder {
+    pub fn task<S: Into<String>>(mut self, task: S) -> Self {
+        self.task = task.into();
+        self
+    }
+
+    pub fn build(self) -> TaskShowConfig {
+        TaskShowConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            task: self.task,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TaskSnoozeConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// The id of the task or its position (starting at 1) in the default task list
+    pub task: String,
+    /// The task is hidden until this day
+    pub until: RelativeDate,
+    /// Only show the change (as unified diff) instead of writing it to the file
+    pub dry_run: bool,
+}
+
+impl TaskSnoozeConfig {
+    pub fn builder() -> TaskSnoozeConfigBuilder {
+        TaskSnoozeConfigBuilder::default()
+    }
+}
+
+/// Builder for [`TaskSnoozeConfig`]
+#[derive(Clone, Debug)]
+pub struct TaskSnoozeConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    task: String,
+    until: RelativeDate,
+    dry_run: bool,
+}
+
+impl Default for TaskSnoozeConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            task: String::new(),
+            until: RelativeDate::Days(1),
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for TaskSnoozeConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TaskSnoozeConfigBuilder {
+    pub fn task<S: Into<String>>(mut self, task: S) -> Self {
+        self.task = task.into();
+        self
+    }
+
+    /// Hide the task until this day, tomorrow by default
+    pub fn until(mut self, until: RelativeDate) -> Self {
+        self.until = until;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> TaskSnoozeConfig {
+        TaskSnoozeConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            task: self.task,
+            until: self.until,
+            dry_run: self.dry_run,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -20,3 +419,177 @@ pub enum TaskFilterType {
     Unfinished,
     Finished,
 }
+
+#[derive(Clone, Debug)]
+pub struct TaskEditConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// Ids of the tasks to change or their positions (starting at 1) in the default task list
+    pub tasks: Vec<String>,
+    /// Change all tasks of this kind (in addition to `tasks`)
+    pub filter: Option<TaskEditFilter>,
+    pub change: DueDateChange,
+    /// Only show the changes instead of writing them to the files
+    pub dry_run: bool,
+}
+
+impl TaskEditConfig {
+    pub fn builder() -> TaskEditConfigBuilder {
+        TaskEditConfigBuilder::default()
+    }
+}
+
+/// Builder for [`TaskEditConfig`]
+#[derive(Clone, Debug)]
+pub struct TaskEditConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    tasks: Vec<String>,
+    filter: Option<TaskEditFilter>,
+    change: Option<DueDateChange>,
+    dry_run: bool,
+}
+
+impl Default for TaskEditConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            tasks: vec![],
+            filter: None,
+            change: None,
+            dry_run: false,
+        }
+    }
+}
+
+impl InputBuilder for TaskEditConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TaskEditConfigBuilder {
+    pub fn task<S: Into<String>>(mut self, task: S) -> Self {
+        self.tasks.push(task.into());
+        self
+    }
+
+    pub fn filter(mut self, filter: TaskEditFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Set the due date of the tasks (which makes `TODO` tasks `TODO UNTIL` tasks)
+    pub fn set_due(mut self, date: RelativeDate) -> Self {
+        self.change = Some(DueDateChange::Set(date));
+        self
+    }
+
+    /// Move the due date of the tasks by the given number of days
+    pub fn shift(mut self, days: i64) -> Self {
+        self.change = Some(DueDateChange::Shift(days));
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Build the config, a change and the tasks to change (or a filter) are required.
+    pub fn build(self) -> Result<TaskEditConfig, ConfigError> {
+        let change = self.change.ok_or(ConfigError::IncompatibleConfigError)?;
+        if self.tasks.is_empty() && self.filter.is_none() {
+            return Err(ConfigError::IncompatibleConfigError);
+        }
+        Ok(TaskEditConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            tasks: self.tasks,
+            filter: self.filter,
+            change,
+            dry_run: self.dry_run,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskEditFilter {
+    Unfinished,
+    /// `TODO UNTIL` tasks whose due date has passed
+    Overdue,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DueDateChange {
+    Set(RelativeDate),
+    /// Number of days
+    Shift(i64),
+}
+
+#[derive(Clone, Debug)]
+pub struct TaskCapacityConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    /// Maximum number of tasks which should be due on the same day
+    pub capacity: usize,
+}
+
+impl TaskCapacityConfig {
+    pub fn builder() -> TaskCapacityConfigBuilder {
+        TaskCapacityConfigBuilder::default()
+    }
+}
+
+/// Builder for [`TaskCapacityConfig`]
+#[derive(Clone, Debug)]
+pub struct TaskCapacityConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    capacity: Option<usize>,
+}
+
+impl Default for TaskCapacityConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            capacity: None,
+        }
+    }
+}
+
+impl InputBuilder for TaskCapacityConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TaskCapacityConfigBuilder {
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Build the config, a capacity of at least one task per day is required.
+    pub fn build(self) -> Result<TaskCapacityConfig, ConfigError> {
+        let capacity = self
+            .capacity
+            .filter(|c| *c > 0)
+            .ok_or(ConfigError::IncompatibleConfigError)?;
+        Ok(TaskCapacityConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            capacity,
+        })
+    }
+}
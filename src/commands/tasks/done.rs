@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+
+use super::{command::tasks_from_tokens, config::TaskDoneConfig, ids::find_task};
+use crate::{
+    commands::{
+        io::OutputSink,
+        locale::Text,
+        rewrite::{diff, replace_all},
+        Command, ParseContext, Report,
+    },
+    models::{TaskStatus, Token},
+};
+
+/// Marks a task as done in the file it was found in
+pub struct TaskDoneCommand {
+    pub config: TaskDoneConfig,
+}
+
+impl Command for TaskDoneCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let tasks = tasks_from_tokens(ctx.tokens(&files)?);
+
+        let task = find_task(&tasks, &config.task)?;
+        let task_string = Token::from(task).to_markdown_string();
+        if task.is_finished() {
+            out.write_output(&ctx.lang.format(Text::AlreadyDone, &[&task_string]))?;
+            return Ok(Report { result_count: 0 });
+        }
+
+        let path = task
+            .origin
+            .file
+            .as_ref()
+            .ok_or_else(|| anyhow!("The file of task '{}' is unknown", config.task))?;
+        let replacement = task.status_replacement(&TaskStatus::Done);
+        if config.dry_run {
+            out.write_output(&diff(path, replacement.as_slice())?)?;
+            return Ok(Report { result_count: 1 });
+        }
+        if let Some(replacement) = replacement {
+            replace_all(path, &[replacement])?;
+        }
+
+        let done = Token::Task {
+            content: task.content.clone(),
+            status: TaskStatus::Done,
+            checkbox: None,
+        };
+        out.write_output(&format!("{}: {}", task.origin, done.to_markdown_string()))?;
+        Ok(Report { result_count: 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{tasks::ids::task_id, InputBuilder};
+
+    const DIARY: &str = "# 2023-10-10\r\n\r\nTODO: Send minutes\r\nDONE: Pay the bill\r\n\
+                         TODO UNTIL 2023-10-12: Book flight  \r\n";
+
+    #[test]
+    fn test_task_done() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(&path, DIARY)?;
+
+        let ctx = ParseContext::default();
+        let run = |task: &str| -> Result<String> {
+            let command = TaskDoneCommand {
+                config: TaskDoneConfig::builder().input(&path).task(task).build(),
+            };
+            let mut output = String::new();
+            command.run(&ctx, &mut output)?;
+            Ok(output)
+        };
+
+        let files = ctx.read(vec![path.clone()])?;
+        let id = task_id(
+            &tasks_from_tokens(ctx.tokens(&files)?)[2].origin,
+            &[Token::Text("Book flight")],
+        );
+        run(&id)?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            DIARY.replace("TODO UNTIL 2023-10-12:", "DONE:")
+        );
+
+        // The first unfinished task
+        run("1")?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            DIARY
+                .replace("TODO UNTIL 2023-10-12:", "DONE:")
+                .replace("TODO:", "DONE:")
+        );
+
+        assert!(run(&id)?.starts_with("Already done"));
+        assert!(run("1").is_err());
+        assert!(run("unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_checkbox_task_done() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2023-10-10\n\n- [ ] Send minutes\n  - [/] Pay the bill\n",
+        )?;
+
+        let ctx = ParseContext::default();
+        let mut output = String::new();
+        let config = TaskDoneConfig::builder()
+            .input(&path)
+            .task("1")
+            .dry_run(true)
+            .build();
+        TaskDoneCommand { config }.run(&ctx, &mut output)?;
+        assert!(output.contains("-- [ ] Send minutes\n+- [x] Send minutes"));
+        assert!(fs::read_to_string(&path)?.contains("- [ ] Send minutes"));
+
+        for _ in 0..2 {
+            let config = TaskDoneConfig::builder().input(&path).task("1").build();
+            TaskDoneCommand { config }.run(&ctx, &mut output)?;
+        }
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "# 2023-10-10\n\n- [x] Send minutes\n  - [x] Pay the bill\n"
+        );
+        Ok(())
+    }
+}
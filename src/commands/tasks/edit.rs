@@ -0,0 +1,196 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::{
+    command::{tasks_from_tokens, Task},
+    config::{DueDateChange, TaskEditConfig, TaskEditFilter},
+    ids::find_task,
+};
+use crate::{
+    commands::{
+        add_days,
+        io::OutputSink,
+        rewrite::{preview, replace_all, Replacement},
+        Command, ParseContext, Report,
+    },
+    models::TaskStatus,
+};
+
+/// Changes the due dates of tasks in the files they were found in
+pub struct TaskEditCommand {
+    pub config: TaskEditConfig,
+}
+
+impl Command for TaskEditCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let today = ctx.clock.today();
+        let files = ctx.read(config.input_path.clone())?;
+        let tasks = tasks_from_tokens(ctx.tokens(&files)?);
+
+        let mut selected: Vec<&Task> = vec![];
+        for id in &config.tasks {
+            selected.push(find_task(&tasks, id)?);
+        }
+        if let Some(filter) = &config.filter {
+            selected.extend(tasks.iter().filter(|t| matches_filter(t, filter, today)));
+        }
+
+        let mut replacements: BTreeMap<PathBuf, Vec<Replacement>> = BTreeMap::new();
+        for task in selected {
+            let (Some(path), Some(replacement)) = (
+                &task.origin.file,
+                changed_status(&task.status, &config.change, today)
+                    .and_then(|status| task.status_replacement(&status)),
+            ) else {
+                log::info!(
+                    "Skipped {}: only TODO tasks (no checkboxes) have a due date",
+                    task.origin
+                );
+                continue;
+            };
+            let file_replacements = replacements.entry(path.clone()).or_default();
+            // The same task might be selected by its id and the filter
+            if file_replacements.iter().any(|r| r.line == task.origin.line) {
+                continue;
+            }
+            file_replacements.push(replacement);
+        }
+
+        let mut changes = vec![];
+        for (path, file_replacements) in &replacements {
+            for change in preview(path, file_replacements)? {
+                changes.push(format!(
+                    "{}:{}\n- {}\n+ {}",
+                    path.display(),
+                    change.line,
+                    change.old,
+                    change.new
+                ));
+            }
+        }
+        if !config.dry_run {
+            for (path, file_replacements) in &replacements {
+                replace_all(path, file_replacements)?;
+            }
+        }
+
+        out.write_output(&changes.join("\n"))?;
+        Ok(Report {
+            result_count: changes.len(),
+        })
+    }
+}
+
+fn matches_filter(task: &Task, filter: &TaskEditFilter, today: NaiveDate) -> bool {
+    match filter {
+        TaskEditFilter::Unfinished => task.is_unfinished(),
+        TaskEditFilter::Overdue => {
+            matches!(&task.status, TaskStatus::TodoUntil(due) if due.date < today)
+        }
+    }
+}
+
+/// The status of a task after changing its due date, `None` if the task has no due date
+/// (and can't get one)
+fn changed_status(
+    status: &TaskStatus,
+    change: &DueDateChange,
+    today: NaiveDate,
+) -> Option<TaskStatus> {
+    match (status, change) {
+        (TaskStatus::Todo | TaskStatus::TodoUntil(_), DueDateChange::Set(date)) => {
+            date.resolve(today).map(|d| TaskStatus::TodoUntil(d.into()))
+        }
+        (TaskStatus::TodoUntil(due), DueDateChange::Shift(days)) => {
+            add_days(due.date, *days).map(|d| TaskStatus::TodoUntil(d.into()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder, RelativeDate};
+
+    const DIARY: &str = "\
+# 2023-10-10
+
+TODO UNTIL 2023-10-01: Pay the bill
+TODO: Send minutes
+DOING: Write report
+TODO UNTIL 2023-10-20: Book flight
+";
+
+    #[test]
+    fn test_edit_due_dates() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(&path, DIARY)?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap()));
+        let run = |config: TaskEditConfig| -> Result<String> {
+            let mut output = String::new();
+            TaskEditCommand { config }.run(&ctx, &mut output)?;
+            Ok(output)
+        };
+
+        let shift_overdue = TaskEditConfig::builder()
+            .input(&path)
+            .filter(TaskEditFilter::Overdue)
+            .shift(14)
+            .dry_run(true)
+            .build()?;
+        assert_eq!(
+            run(shift_overdue.clone())?,
+            format!(
+                "{}:3\n- TODO UNTIL 2023-10-01: Pay the bill\n+ TODO UNTIL 2023-10-15: Pay the bill",
+                path.display()
+            )
+        );
+        assert_eq!(fs::read_to_string(&path)?, DIARY);
+
+        run(TaskEditConfig {
+            dry_run: false,
+            ..shift_overdue
+        })?;
+        // The tasks without due date are skipped when shifting
+        run(TaskEditConfig::builder()
+            .input(&path)
+            .filter(TaskEditFilter::Unfinished)
+            .shift(-1)
+            .build()?)?;
+        run(TaskEditConfig::builder()
+            .input(&path)
+            .task("2")
+            .set_due(RelativeDate::Days(7))
+            .build()?)?;
+
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "\
+# 2023-10-10
+
+TODO UNTIL 2023-10-14: Pay the bill
+TODO UNTIL 2023-10-17: Send minutes
+DOING: Write report
+TODO UNTIL 2023-10-19: Book flight
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_config_requires_tasks_and_change() {
+        assert!(TaskEditConfig::builder().shift(1).build().is_err());
+        assert!(TaskEditConfig::builder().task("1").build().is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use super::command::Task;
+use crate::{
+    commands::util::stable_hash,
+    models::{Origin, Token},
+};
+
+/// A short id of a task, which stays the same as long as neither the name of its file nor
+/// its content (without the status) changes.
+pub fn task_id(origin: &Origin, content: &[Token]) -> String {
+    let file_name = origin
+        .file
+        .as_ref()
+        .and_then(|f| f.file_name())
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let content: String = content.iter().map(|t| t.to_markdown_string()).collect();
+
+    let hash = stable_hash(
+        file_name
+            .bytes()
+            .chain(std::iter::once(0))
+            .chain(content.trim().bytes()),
+    );
+    // Folded to 32 bit to keep the ids short
+    format!("{:08x}", (hash ^ (hash >> 32)) as u32)
+}
+
+/// Assigns the ids of the tasks (given in the order they were found): the [`task_id`],
+/// followed by `-2`, `-3`, ... for further tasks with the same content in the same file.
+pub(super) fn assign_ids(tasks: &mut [Task]) {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for task in tasks {
+        let id = task_id(&task.origin, &task.content);
+        let count = counts.entry(id.clone()).or_default();
+        *count += 1;
+        task.id = match *count {
+            1 => id,
+            n => format!("{}-{}", id, n),
+        };
+    }
+}
+
+/// Find a task by its id or by its position (starting at 1) among the unfinished tasks
+pub(super) fn find_task<'t, 'a>(tasks: &'t [Task<'a>], id: &str) -> Result<&'t Task<'a>> {
+    if let Some(task) = tasks.iter().find(|t| t.id == id) {
+        return Ok(task);
+    }
+    match id.parse::<usize>() {
+        Ok(n) if n > 0 => tasks
+            .iter()
+            .filter(|t| t.is_unfinished())
+            .nth(n - 1)
+            .ok_or_else(|| anyhow!("There are less than {} unfinished tasks", n)),
+        _ => Err(anyhow!("There is no task with id '{}'", id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        commands::tasks::command::tasks_from_tokens, markdown::MDPMarkdownTokenizer,
+        models::MarkdownTokenizer,
+    };
+
+    #[test]
+    fn test_task_id() {
+        let origin = |file: &str, line| Origin::new(Some(Path::new(file)), line, 1);
+        let content = vec![Token::Text("Send minutes")];
+        let id = task_id(&origin("diary/2023.md", 3), &content);
+
+        assert_eq!(id.len(), 8);
+        // Neither the directory nor the position affect the id
+        assert_eq!(id, task_id(&origin("2023.md", 12), &content));
+        assert_ne!(id, task_id(&origin("2024.md", 3), &content));
+        assert_ne!(id, task_id(&origin("2023.md", 3), &[Token::Text("Pay")]));
+    }
+
+    #[test]
+    fn test_assign_ids() -> Result<()> {
+        let markdown = "TODO: Call roger\nDONE: Call roger\nTODO: Pay the bill\n";
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize_with_origins(markdown, Some(Path::new("diary.md")))?;
+        let tasks = tasks_from_tokens(tokens);
+        let ids = tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+
+        let call = task_id(&tasks[0].origin, &tasks[0].content);
+        assert_eq!(ids[0], call);
+        assert_eq!(ids[1], format!("{}-2", call));
+        assert_eq!(ids[2], task_id(&tasks[2].origin, &tasks[2].content));
+
+        assert_eq!(find_task(&tasks, &format!("{}-2", call))?.origin.line, 2);
+        assert_eq!(find_task(&tasks, "2")?.origin.line, 3);
+        assert!(find_task(&tasks, "3").is_err());
+        assert!(find_task(&tasks, "unknown").is_err());
+        Ok(())
+    }
+}
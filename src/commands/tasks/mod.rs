@@ -1,2 +1,9 @@
+pub mod capacity;
 pub mod command;
 pub mod config;
+pub mod done;
+pub mod edit;
+pub mod ids;
+pub mod show;
+pub mod snooze;
+pub mod stats;
@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Result};
+
+use super::{
+    command::{task_context, tasks_from_sections, Task},
+    config::TaskShowConfig,
+    ids::find_task,
+};
+use crate::{
+    commands::{io::OutputSink, Command, ParseContext, Report},
+    models::{Section, Token},
+};
+
+/// Shows a single task with its id, origin, the headings it is nested in and the whole
+/// section it was found in.
+pub struct TaskShowCommand {
+    pub config: TaskShowConfig,
+}
+
+impl Command for TaskShowCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+        let tasks = tasks_from_sections(&sections);
+
+        let task = find_task(&tasks, &config.task)?;
+        let section = section_of(&sections, task)
+            .ok_or_else(|| anyhow!("The section of task '{}' is unknown", config.task))?;
+
+        let mut lines = vec![
+            format!("[{}] {}", task.id, Token::from(task).to_markdown_string()),
+            task.origin.to_string(),
+        ];
+        let context = task_context(task);
+        if !context.is_empty() {
+            lines.push(context.join(" > "));
+        }
+        lines.push(String::new());
+        lines.push(section_markdown(section));
+        out.write_output(&lines.join("\n"))?;
+
+        Ok(Report { result_count: 1 })
+    }
+}
+
+/// The innermost section whose content contains the task
+fn section_of<'s, 'a>(sections: &'s [Section<'a>], task: &Task) -> Option<&'s Section<'a>> {
+    sections.iter().find_map(|section| {
+        let contains_task = section
+            .content_with_origins()
+            .iter()
+            .any(|(token, origin)| matches!(token, Token::Task { .. }) && origin == &task.origin);
+        match contains_task {
+            true => Some(section),
+            false => section_of(&section.subsections, task),
+        }
+    })
+}
+
+/// The title and the content of the section (without its subsections)
+fn section_markdown(section: &Section) -> String {
+    std::iter::once(&section.title)
+        .chain(section.content.iter())
+        .map(|t| t.to_markdown_string())
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+
+    #[test]
+    fn test_task_show() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        std::fs::write(
+            &path,
+            "# 2023-10-10\n\nTODO: Pay the bill\n\n## Meeting with @roger\n\
+             Discussed the budget\nTODO: Send minutes\n\n### Notes\nNone\n",
+        )?;
+
+        let ctx = ParseContext::default();
+        let run = |task: &str| -> Result<String> {
+            let config = TaskShowConfig::builder().input(&path).task(task).build();
+            let mut output = String::new();
+            TaskShowCommand { config }.run(&ctx, &mut output)?;
+            Ok(output)
+        };
+
+        let output = run("2")?;
+        let (id, rest) = output[1..].split_once("] ").unwrap();
+        assert_eq!(
+            rest,
+            format!(
+                "TODO: Send minutes\n{}:7:1\n2023-10-10 > Meeting with @roger\n\n\
+                 ## Meeting with @roger\nDiscussed the budget\nTODO: Send minutes",
+                path.display()
+            )
+        );
+        assert_eq!(run(id)?, output);
+        assert!(run("3").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+
+use super::{
+    command::{tasks_from_tokens, Task},
+    config::TaskSnoozeConfig,
+    ids::find_task,
+};
+use crate::{
+    commands::{
+        io::{MarkdownFile, OutputSink},
+        locale::Text,
+        rewrite::{diff, preview, replace_all, Replacement},
+        Command, ParseContext, Report,
+    },
+    models::{ConfigError, Token},
+};
+
+/// Hides a task until a day by adding a snooze annotation (`SNOOZED UNTIL <DATE>`) to it
+/// in the file it was found in, an existing annotation is replaced.
+pub struct TaskSnoozeCommand {
+    pub config: TaskSnoozeConfig,
+}
+
+impl Command for TaskSnoozeCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let until = config
+            .until
+            .resolve(ctx.clock.today())
+            .ok_or_else(|| ConfigError::InvalidDateError(format!("{:?}", config.until)))?;
+        let files = ctx.read(config.input_path.clone())?;
+        let tasks = tasks_from_tokens(ctx.tokens(&files)?);
+
+        let task = find_task(&tasks, &config.task)?;
+        if task.is_finished() {
+            let task_string = Token::from(task).to_markdown_string();
+            out.write_output(&ctx.lang.format(Text::AlreadyDone, &[&task_string]))?;
+            return Ok(Report { result_count: 0 });
+        }
+
+        let (path, line) = files
+            .iter()
+            .find(|f| Some(&f.path) == task.origin.file.as_ref())
+            .and_then(|f| Some((&f.path, task_line(f, task)?)))
+            .ok_or_else(|| anyhow!("The file of task '{}' is unknown", config.task))?;
+        let replacement = snooze_replacement(task, line, until);
+        if config.dry_run {
+            out.write_output(&diff(path, std::slice::from_ref(&replacement))?)?;
+            return Ok(Report { result_count: 1 });
+        }
+
+        let snoozed_line = preview(path, std::slice::from_ref(&replacement))?
+            .into_iter()
+            .map(|change| change.new)
+            .next()
+            .unwrap_or_default();
+        replace_all(path, &[replacement])?;
+
+        out.write_output(&format!("{}: {}", task.origin, snoozed_line.trim()))?;
+        Ok(Report { result_count: 1 })
+    }
+}
+
+fn task_line<'f>(file: &'f MarkdownFile, task: &Task) -> Option<&'f str> {
+    file.content.lines().nth(task.origin.line.checked_sub(1)?)
+}
+
+/// Changes the date of the snooze annotation of the task (keeping its style), or appends
+/// `SNOOZED UNTIL <DATE>` to the line of the task if it has none
+fn snooze_replacement(task: &Task, line: &str, until: NaiveDate) -> Replacement {
+    let existing = task.content.iter().find_map(|t| match t {
+        Token::Snooze { markup, .. } => line.find(markup).map(|i| (i, *markup)),
+        _ => None,
+    });
+    match existing {
+        Some((i, old)) => Replacement {
+            line: task.origin.line,
+            column: line[..i].chars().count() + 1,
+            old: old.to_string(),
+            new: match old.starts_with('@') {
+                true => format!("@snooze({})", until),
+                false => format!("SNOOZED UNTIL {}", until),
+            },
+        },
+        None => Replacement {
+            line: task.origin.line,
+            column: line.trim_end().chars().count() + 1,
+            old: String::new(),
+            new: format!(" SNOOZED UNTIL {}", until),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder, RelativeDate};
+
+    #[test]
+    fn test_task_snooze() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2023-10-10\r\n\r\nTODO: Send minutes  \r\n- [ ] Call @snooze(2023-10-11)\r\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap()));
+        let run = |task: &str, until: RelativeDate| -> Result<String> {
+            let config = TaskSnoozeConfig::builder()
+                .input(&path)
+                .task(task)
+                .until(until)
+                .build();
+            let mut output = String::new();
+            TaskSnoozeCommand { config }.run(&ctx, &mut output)?;
+            Ok(output)
+        };
+
+        assert_eq!(
+            run("1", RelativeDate::Days(7))?,
+            format!(
+                "{}:3:1: TODO: Send minutes SNOOZED UNTIL 2023-10-17",
+                path.display()
+            )
+        );
+        run("2", RelativeDate::Days(2))?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "# 2023-10-10\r\n\r\nTODO: Send minutes SNOOZED UNTIL 2023-10-17  \r\n\
+             - [ ] Call @snooze(2023-10-12)\r\n"
+        );
+        Ok(())
+    }
+}
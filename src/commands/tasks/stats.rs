@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+
+use crate::{
+    commands::locale::{Language, Text},
+    models::TaskStatus,
+};
+
+/// Number of tasks per status and the due dates of the unfinished ones
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskStats {
+    /// Includes `TODO UNTIL` tasks
+    pub todo: usize,
+    pub doing: usize,
+    pub review: usize,
+    pub waiting: usize,
+    pub done: usize,
+    /// Due dates of the `TODO UNTIL` tasks
+    pub due_dates: Vec<NaiveDate>,
+}
+
+impl TaskStats {
+    pub fn new<'s, I: IntoIterator<Item = &'s TaskStatus>>(statuses: I) -> Self {
+        let mut stats = Self::default();
+        for status in statuses {
+            stats.add(status);
+        }
+        stats
+    }
+
+    pub fn add(&mut self, status: &TaskStatus) {
+        match status {
+            TaskStatus::Todo => self.todo += 1,
+            TaskStatus::TodoUntil(due) => {
+                self.todo += 1;
+                self.due_dates.push(due.date);
+            }
+            TaskStatus::Doing => self.doing += 1,
+            TaskStatus::Review => self.review += 1,
+            TaskStatus::Waiting(_) => self.waiting += 1,
+            TaskStatus::Done => self.done += 1,
+        }
+    }
+
+    /// Adds the tasks counted by `other`
+    pub fn merge(&mut self, other: &Self) {
+        self.todo += other.todo;
+        self.doing += other.doing;
+        self.review += other.review;
+        self.waiting += other.waiting;
+        self.done += other.done;
+        self.due_dates.extend(&other.due_dates);
+    }
+
+    pub fn total(&self) -> usize {
+        self.unfinished() + self.done
+    }
+
+    pub fn unfinished(&self) -> usize {
+        self.todo + self.doing + self.review + self.waiting
+    }
+
+    /// Percentage of the tasks done (rounded down), `None` without tasks
+    pub fn completion(&self) -> Option<usize> {
+        match self.total() {
+            0 => None,
+            total => Some(self.done * 100 / total),
+        }
+    }
+
+    /// Number of tasks due before `today`
+    pub fn overdue(&self, today: NaiveDate) -> usize {
+        self.due_dates.iter().filter(|d| **d < today).count()
+    }
+
+    /// The earliest due date from `today` on
+    pub fn next_deadline(&self, today: NaiveDate) -> Option<NaiveDate> {
+        self.due_dates
+            .iter()
+            .filter(|d| **d >= today)
+            .min()
+            .copied()
+    }
+
+    /// The completion with the number of done tasks, e.g. `50% (1/2)`
+    fn completion_string(&self) -> String {
+        match self.completion() {
+            Some(percent) => format!("{}% ({}/{})", percent, self.done, self.total()),
+            None => "-".to_string(),
+        }
+    }
+
+    /// Summary shown by `mdp tasks --summary`: the tasks per status, the completion, the
+    /// number of overdue tasks and the next deadline
+    pub fn summary(&self, today: NaiveDate, lang: Language) -> String {
+        let next_deadline = self
+            .next_deadline(today)
+            .map(|d| d.to_string())
+            .unwrap_or("-".to_string());
+        [
+            format!(
+                "TODO: {}, DOING: {}, REVIEW: {}, WAITING: {}, DONE: {}",
+                self.todo, self.doing, self.review, self.waiting, self.done
+            ),
+            format!(
+                "{}: {}",
+                lang.text(Text::Completed),
+                self.completion_string()
+            ),
+            format!("{}: {}", lang.text(Text::Overdue), self.overdue(today)),
+            format!("{}: {}", lang.text(Text::NextDeadline), next_deadline),
+        ]
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_task_stats() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+        let mut stats = TaskStats::new(&[
+            TaskStatus::Todo,
+            TaskStatus::TodoUntil(date(3).into()),
+            TaskStatus::TodoUntil(date(12).into()),
+            TaskStatus::Waiting("roger".to_string()),
+        ]);
+        stats.merge(&TaskStats::new(&[
+            TaskStatus::Done,
+            TaskStatus::TodoUntil(date(8).into()),
+        ]));
+
+        assert_eq!(stats.total(), 6);
+        assert_eq!(stats.unfinished(), 5);
+        assert_eq!(stats.completion(), Some(16));
+        assert_eq!(stats.overdue(date(5)), 1);
+        assert_eq!(stats.next_deadline(date(5)), Some(date(8)));
+        assert_eq!(stats.next_deadline(date(13)), None);
+        assert_eq!(TaskStats::default().completion(), None);
+        assert_eq!(
+            stats.summary(date(5), Language::English),
+            "TODO: 4, DOING: 0, REVIEW: 0, WAITING: 1, DONE: 1\n\
+             Completed: 16% (1/6)\n\
+             Overdue: 1\n\
+             Next deadline: 2024-06-08"
+        );
+    }
+}
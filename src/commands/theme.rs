@@ -0,0 +1,281 @@
+use std::io::IsTerminal;
+
+use serde::Deserialize;
+
+use crate::{
+    markdown::MDPMarkdownTokenizer,
+    models::{MarkdownTokenizer, TaskStatus, Token, TokenStyler},
+};
+
+const RESET: &str = "\x1b[0m";
+
+/// Environment variable disabling colors (see https://no-color.org)
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// Whether the output on the terminal is colored
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colored if stdout is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorMode::Auto => {
+                std::io::stdout().is_terminal() && std::env::var_os(NO_COLOR_ENV).is_none()
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn ansi_code(&self) -> u8 {
+        match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+        }
+    }
+}
+
+/// How a part of the output is displayed in the terminal
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    pub fn color(color: Color) -> Self {
+        Self {
+            color: Some(color),
+            ..Default::default()
+        }
+    }
+
+    /// Wrap `s` in the ANSI escape codes of the style, the style is restored after styled
+    /// parts nested within `s`.
+    pub fn apply(&self, s: String) -> String {
+        let mut codes = vec![];
+        if self.bold {
+            codes.push(1);
+        }
+        if self.dim {
+            codes.push(2);
+        }
+        if self.underline {
+            codes.push(4);
+        }
+        codes.extend(self.color.map(|c| c.ansi_code()));
+        if codes.is_empty() || s.is_empty() {
+            return s;
+        }
+
+        let codes = codes
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
+            .join(";");
+        let start = format!("\x1b[{}m", codes);
+        format!(
+            "{}{}{}",
+            start,
+            s.replace(RESET, &format!("{}{}", RESET, start)),
+            RESET
+        )
+    }
+}
+
+/// Styles of the terminal output, configured in the `[theme]` section of the settings, e.g.
+///
+/// ```toml
+/// [theme]
+/// tag = { color = "magenta" }
+/// done = { color = "green", dim = true }
+/// link = { color = "blue", underline = true }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub heading: Style,
+    pub tag: Style,
+    /// Status of unfinished tasks (`TODO`, `DOING`, ...)
+    pub todo: Style,
+    /// Status of finished tasks
+    pub done: Style,
+    pub date: Style,
+    /// Links, embeds, URLs and email addresses
+    pub link: Style,
+    /// Attributes (`Name:: value`)
+    pub metadata: Style,
+    /// Highlighted text (`^^text^^`), e.g. the matches of `search --highlight`
+    pub highlight: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            heading: Style {
+                bold: true,
+                ..Default::default()
+            },
+            tag: Style::color(Color::Cyan),
+            todo: Style::color(Color::Red),
+            done: Style::color(Color::Green),
+            date: Style {
+                dim: true,
+                ..Default::default()
+            },
+            link: Style {
+                underline: true,
+                ..Default::default()
+            },
+            metadata: Style {
+                dim: true,
+                ..Default::default()
+            },
+            highlight: Style {
+                color: Some(Color::Yellow),
+                bold: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl TokenStyler for Theme {
+    fn style_token(&self, token: &Token, markdown: String) -> String {
+        match token {
+            Token::HeadingH1(_)
+            | Token::HeadingH2(_)
+            | Token::HeadingH3(_)
+            | Token::HeadingH4(_)
+            | Token::HeadingH5(_)
+            | Token::HeadingH6(_) => self.heading.apply(markdown),
+            Token::Tag(_) => self.tag.apply(markdown),
+            Token::Highlight(_) => self.highlight.apply(markdown),
+            Token::Date(_) => self.date.apply(markdown),
+            Token::Link(_)
+            | Token::Embed(_)
+            | Token::FootnoteRef(_)
+            | Token::RawHyperlink(_)
+            | Token::Email(_)
+            | Token::MarkdownExternalLink { .. }
+            | Token::MarkdownInternalLink { .. } => self.link.apply(markdown),
+            Token::Attribute { .. } | Token::FootnoteDefinition { .. } | Token::Snooze { .. } => {
+                self.metadata.apply(markdown)
+            }
+            _ => markdown,
+        }
+    }
+
+    fn style_task_status(&self, status: &TaskStatus, markdown: String) -> String {
+        match status {
+            TaskStatus::Done => self.done.apply(markdown),
+            _ => self.todo.apply(markdown),
+        }
+    }
+}
+
+impl Theme {
+    /// Style Markdown text line by line. Lines which can not be parsed or not be rendered
+    /// exactly as they were (e.g. due to redundant whitespace) are kept as they are, just
+    /// like the lines of fenced code blocks.
+    pub fn style_markdown(&self, markdown: &str) -> String {
+        let mut in_code_block = false;
+        markdown
+            .split('\n')
+            .map(|line| {
+                let fence = line.trim_start().strip_prefix("```");
+                if in_code_block || fence.is_some_and(|info| !info.contains("```")) {
+                    if fence.is_some() {
+                        in_code_block = !in_code_block;
+                    }
+                    return line.to_string();
+                }
+                self.style_line(line).unwrap_or_else(|| line.to_string())
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn style_line(&self, line: &str) -> Option<String> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize(line).ok()?;
+        let tokens = tokens.iter().filter(|t| **t != Token::Newline);
+        let plain: String = tokens.clone().map(|t| t.to_markdown_string()).collect();
+        match plain == line {
+            true => Some(tokens.map(|t| t.render(self)).collect()),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_style_markdown() {
+        let theme = Theme::default();
+
+        assert_eq!(
+            theme.style_markdown("## Call @roger today\nTODO: Pay\nDONE: Write"),
+            "\x1b[1m## Call \x1b[36m@roger\x1b[0m\x1b[1m today\x1b[0m\n\
+             \x1b[31mTODO\x1b[0m: Pay\n\
+             \x1b[32mDONE\x1b[0m: Write"
+        );
+        assert_eq!(
+            theme.style_markdown("# 2023-10-10"),
+            "\x1b[1m# \x1b[2m2023-10-10\x1b[0m\x1b[1m\x1b[0m"
+        );
+        // Unstyled text stays exactly the same
+        assert_eq!(theme.style_markdown("- a **b**\n\nc"), "- a **b**\n\nc");
+        assert_eq!(theme.style_markdown("TODO:   @roger"), "TODO:   @roger");
+        assert_eq!(
+            theme.style_markdown("See [[Blog]]\nSource:: x"),
+            "See \x1b[4m[[Blog]]\x1b[0m\n\x1b[2mSource:: x\x1b[0m"
+        );
+        assert_eq!(
+            theme.style_markdown("^^rust^^ book"),
+            "\x1b[1;33m^^rust^^\x1b[0m book"
+        );
+        assert_eq!(
+            theme.style_markdown("```python\n# TODO: x\n```\nTODO: y"),
+            "```python\n# TODO: x\n```\n\x1b[31mTODO\x1b[0m: y"
+        );
+    }
+
+    #[test]
+    fn test_style_without_codes() {
+        assert_eq!(Style::default().apply("text".to_string()), "text");
+    }
+}
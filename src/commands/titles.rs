@@ -0,0 +1,240 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io::Read,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+
+use super::encryption::CacheLocation;
+use crate::models::{MDPError, Section, Token, TokenStyler};
+
+/// Default location of the cache of fetched titles (relative to the working directory)
+pub const DEFAULT_TITLE_CACHE_PATH: &str = ".mdp-titles";
+
+/// Seconds fetching the title of a page may take
+pub const DEFAULT_FETCH_TIMEOUT_SECS: u64 = 5;
+
+/// Only the start of a page is read, the title is part of its head
+const MAX_PAGE_SIZE: u64 = 512 * 1024;
+
+/// Fetches the title of the page at a URL
+pub trait TitleFetcher {
+    fn fetch(&self, url: &str) -> Result<String>;
+}
+
+/// Fetches titles over HTTP(S), following redirects
+pub struct HttpTitleFetcher {
+    pub timeout: Duration,
+}
+
+impl Default for HttpTitleFetcher {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_FETCH_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl TitleFetcher for HttpTitleFetcher {
+    fn fetch(&self, url: &str) -> Result<String> {
+        let agent = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        let response = agent.get(url).call()?;
+        if !response.content_type().contains("html") {
+            return Err(anyhow!("{} is no HTML page", url));
+        }
+        let mut page = vec![];
+        response
+            .into_reader()
+            .take(MAX_PAGE_SIZE)
+            .read_to_end(&mut page)?;
+        html_title(&String::from_utf8_lossy(&page))
+            .ok_or_else(|| anyhow!("The page at {} has no title", url))
+    }
+}
+
+/// The titles of the pages raw URLs point to, cached in a file so that every page is only
+/// fetched once. Renders raw URLs with a known title as markdown links (`[Title](url)`).
+pub struct LinkTitles {
+    /// `None` if the titles are not cached
+    location: Option<CacheLocation>,
+    fetcher: Box<dyn TitleFetcher>,
+    titles: RefCell<HashMap<String, String>>,
+    changed: Cell<bool>,
+}
+
+impl LinkTitles {
+    /// Titles fetched by `fetcher` and cached at `location`, a missing or invalid cache file
+    /// is ignored
+    pub fn load(location: Option<CacheLocation>, fetcher: Box<dyn TitleFetcher>) -> Self {
+        let titles = location
+            .as_ref()
+            .and_then(CacheLocation::read)
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self {
+            location,
+            fetcher,
+            titles: RefCell::new(titles),
+            changed: Cell::new(false),
+        }
+    }
+
+    /// Fetches the titles of the URLs which are not cached yet. URLs whose title can not be
+    /// fetched are logged and kept as they are.
+    pub fn resolve<'u, I: IntoIterator<Item = &'u str>>(&self, urls: I) {
+        for url in urls {
+            if self.titles.borrow().contains_key(url) {
+                continue;
+            }
+            match self.fetcher.fetch(url) {
+                Ok(title) => {
+                    self.titles.borrow_mut().insert(url.to_string(), title);
+                    self.changed.set(true);
+                }
+                Err(e) => log::warn!("Could not fetch the title of {}: {}", url, e),
+            }
+        }
+        if let Err(e) = self.save() {
+            log::warn!("Could not cache the titles: {}", e);
+        }
+    }
+
+    pub fn title(&self, url: &str) -> Option<String> {
+        self.titles.borrow().get(url).cloned()
+    }
+
+    fn save(&self) -> Result<(), MDPError> {
+        let Some(location) = &self.location else {
+            return Ok(());
+        };
+        if !self.changed.replace(false) {
+            return Ok(());
+        }
+        let json = serde_json::to_string(&*self.titles.borrow()).unwrap_or_default();
+        location.write(&json)
+    }
+}
+
+impl TokenStyler for LinkTitles {
+    fn style_token(&self, token: &Token, markdown: String) -> String {
+        match token {
+            Token::RawHyperlink(url) => match self.title(url) {
+                Some(title) => format!("[{}]({})", escape_link_text(&title), url),
+                None => markdown,
+            },
+            _ => markdown,
+        }
+    }
+}
+
+/// The raw URLs within the tokens (and the tokens nested in them)
+pub fn raw_urls<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    let mut urls = vec![];
+    for token in tokens {
+        match token {
+            Token::RawHyperlink(url) => urls.push(*url),
+            t => urls.extend(raw_urls(t.children())),
+        }
+    }
+    urls
+}
+
+/// The raw URLs within the section and its subsections
+pub fn section_urls<'a>(section: &Section<'a>) -> Vec<&'a str> {
+    let mut urls = raw_urls(std::slice::from_ref(&section.title));
+    urls.extend(raw_urls(&section.content));
+    for sub in &section.subsections {
+        urls.extend(section_urls(sub));
+    }
+    urls
+}
+
+/// The content of the `<title>` element with the common entities decoded and whitespace
+/// collapsed, `None` if it is missing or empty
+fn html_title(html: &str) -> Option<String> {
+    // ASCII lowercasing keeps the byte offsets
+    let lowercase = html.to_ascii_lowercase();
+    let start = lowercase.find("<title")?;
+    let start = start + lowercase[start..].find('>')? + 1;
+    let end = start + lowercase[start..].find("</title")?;
+
+    let title = html[start..end]
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    let title = title.split_whitespace().collect::<Vec<&str>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// Escapes the brackets of the text of a markdown link
+fn escape_link_text(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct FakeFetcher;
+
+    impl TitleFetcher for FakeFetcher {
+        fn fetch(&self, url: &str) -> Result<String> {
+            match url {
+                "https://example.com" => Ok("Example [Domain]".to_string()),
+                _ => Err(anyhow!("timeout")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_html_title() {
+        assert_eq!(
+            html_title("<html><head><TITLE lang=\"en\">\n  Rust &amp; Cargo\n</TITLE>"),
+            Some("Rust & Cargo".to_string())
+        );
+        assert_eq!(html_title("<title> </title>"), None);
+        assert_eq!(html_title("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_link_titles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DEFAULT_TITLE_CACHE_PATH);
+        let tokens = vec![
+            Token::Text("See "),
+            Token::Bold(vec![Token::RawHyperlink("https://example.com")]),
+            Token::Text(" and "),
+            Token::RawHyperlink("https://example.org"),
+        ];
+
+        let titles = LinkTitles::load(Some(path.as_path().into()), Box::new(FakeFetcher));
+        titles.resolve(raw_urls(&tokens));
+        let rendered: String = tokens.iter().map(|t| t.render(&titles)).collect();
+        assert_eq!(
+            rendered,
+            "See **[Example \\[Domain\\]](https://example.com)** and https://example.org"
+        );
+
+        // Cached titles are not fetched again
+        struct FailingFetcher;
+        impl TitleFetcher for FailingFetcher {
+            fn fetch(&self, _url: &str) -> Result<String> {
+                Err(anyhow!("offline"))
+            }
+        }
+        let cached = LinkTitles::load(Some(path.as_path().into()), Box::new(FailingFetcher));
+        cached.resolve(["https://example.com"]);
+        assert_eq!(
+            cached.title("https://example.com"),
+            Some("Example [Domain]".to_string())
+        );
+    }
+}
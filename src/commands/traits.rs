@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use super::{io::OutputSink, ParseContext, Report};
+
+pub trait Command {
+    /// Run the command using the given parsing pipeline and write its output to `out`
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report>;
+}
+
+/// Setters for the input files of the command config builders
+///
+/// The builders use the same defaults as the CLI for all fields not explicitly set, e.g. only
+/// the files directly in the input directories are read.
+pub trait InputBuilder: Sized {
+    /// The paths of the input files and directories
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf>;
+
+    /// The number of directory levels to descend into input directories (`None` without limit)
+    fn max_depth_mut(&mut self) -> &mut Option<usize>;
+
+    fn input<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.input_path_mut().push(path.into());
+        self
+    }
+
+    fn inputs<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.input_path_mut().extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Descend into subdirectories of input directories without depth limit
+    fn recursive(mut self, recursive: bool) -> Self {
+        *self.max_depth_mut() = if recursive { None } else { Some(1) };
+        self
+    }
+
+    /// Descend at most `depth` directory levels into input directories
+    fn max_depth(mut self, depth: usize) -> Self {
+        *self.max_depth_mut() = Some(depth);
+        self
+    }
+}
@@ -0,0 +1,192 @@
+use super::links::{LinkResolver, LinkTarget};
+use crate::models::{Origin, PlainStyler, Section, Token, TokenStyler};
+
+/// How many levels of embeds within embedded sections are expanded by default
+pub const DEFAULT_MAX_TRANSCLUSION_DEPTH: usize = 5;
+
+/// Renders sections as markdown, replacing embeds (`![[Note#Heading]]`) by the content of
+/// the sections they reference.
+///
+/// Embeds which can not be resolved, which would embed a section into itself or which are
+/// nested deeper than `max_depth` are kept as they are.
+pub struct Transcluder<'s, 'a> {
+    resolver: LinkResolver<'s, 'a>,
+    pub max_depth: usize,
+    /// Decorates the rendered tokens
+    styler: &'s dyn TokenStyler,
+}
+
+impl<'s, 'a> Transcluder<'s, 'a> {
+    /// Embeds are resolved against the given (top level) sections
+    pub fn new(sections: &'s [Section<'a>]) -> Self {
+        Self {
+            resolver: LinkResolver::new(sections),
+            max_depth: DEFAULT_MAX_TRANSCLUSION_DEPTH,
+            styler: &PlainStyler,
+        }
+    }
+
+    /// Renders the tokens with the given styler instead of as they are
+    pub fn with_styler(mut self, styler: &'s dyn TokenStyler) -> Self {
+        self.styler = styler;
+        self
+    }
+
+    /// Renders the section like its `Display` implementation, but with embeds expanded
+    pub fn render(&self, section: &Section) -> String {
+        self.render_section(section, &mut vec![], 0)
+    }
+
+    /// Renders the content and the subsections of the section without its title
+    pub fn render_body(&self, section: &Section) -> String {
+        self.render_content(section, &mut vec![], 0)
+    }
+
+    /// `stack` holds the sections currently being rendered, `depth` is the number of
+    /// embeds they are nested in.
+    fn render_section(&self, section: &Section, stack: &mut Vec<Origin>, depth: usize) -> String {
+        section.title.render(self.styler) + &self.render_content(section, stack, depth)
+    }
+
+    fn render_content(&self, section: &Section, stack: &mut Vec<Origin>, depth: usize) -> String {
+        stack.push(section.origin.clone());
+
+        let mut s = String::new();
+        for token in &section.content {
+            match token {
+                Token::Embed(link) => s += &self.render_embed(link, section, stack, depth),
+                t => s += &t.render(self.styler),
+            }
+        }
+        for sub in &section.subsections {
+            s += &self.render_section(sub, stack, depth);
+        }
+
+        stack.pop();
+        s
+    }
+
+    fn render_embed(
+        &self,
+        link: &str,
+        section: &Section,
+        stack: &mut Vec<Origin>,
+        depth: usize,
+    ) -> String {
+        let unexpanded = Token::Embed(link).to_markdown_string();
+        if depth >= self.max_depth {
+            log::warn!(
+                "Did not expand {} at {}: embeds are nested more than {} levels deep",
+                unexpanded,
+                section.origin,
+                self.max_depth
+            );
+            return unexpanded;
+        }
+
+        let target = LinkTarget::parse(link);
+        let sections = self
+            .resolver
+            .resolve(&target, section.origin.file.as_deref());
+        if sections.is_empty() {
+            log::warn!(
+                "Did not expand {} at {}: no such section",
+                unexpanded,
+                section.origin
+            );
+            return unexpanded;
+        }
+        if sections.iter().any(|s| stack.contains(&s.origin)) {
+            log::warn!(
+                "Did not expand {} at {}: the section would embed itself",
+                unexpanded,
+                section.origin
+            );
+            return unexpanded;
+        }
+
+        sections
+            .iter()
+            .map(|s| match target.heading {
+                // Only the content replaces the embed, whole notes keep their headings
+                Some(_) => self.render_content(s, stack, depth + 1),
+                None => self.render_section(s, stack, depth + 1),
+            })
+            .map(|s| s.trim().to_string())
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+        models::{MarkdownTokenizer, SectionBuilder},
+    };
+
+    const DIARY: &str = "\
+# 2023-10-10
+
+## Meeting
+
+Agenda:
+![[projects#Roadmap]]
+
+## Loop
+
+![[#Loop]]
+";
+
+    const PROJECTS: &str = "\
+# 2023-10-01
+
+## Roadmap
+
+- Release 1.0
+![[#Details]]
+
+## Details
+
+Due in November.
+![[diary#Meeting]]
+";
+
+    fn sections<'a>(markdown: &'a str, file: &str) -> Result<Vec<Section<'a>>> {
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize_with_origins(markdown, Some(Path::new(file)))?;
+        Ok(MDPSectionBuilder::default().sections_from_tokens_with_origins(tokens)?)
+    }
+
+    #[test]
+    fn test_render_embeds() -> Result<()> {
+        let mut all = sections(DIARY, "notes/diary.md")?;
+        all.extend(sections(PROJECTS, "notes/projects.md")?);
+        let transcluder = Transcluder::new(&all);
+
+        let meeting = &all[0].subsections[0];
+        assert_eq!(
+            transcluder.render(meeting),
+            "## Meeting\n\nAgenda:\n- Release 1.0\nDue in November.\n![[diary#Meeting]]\n\n"
+        );
+
+        let looping = &all[0].subsections[1];
+        assert_eq!(transcluder.render(looping), "## Loop\n\n![[#Loop]]\n\n");
+
+        let shallow = Transcluder {
+            max_depth: 1,
+            ..Transcluder::new(&all)
+        };
+        assert_eq!(
+            shallow.render(meeting),
+            "## Meeting\n\nAgenda:\n- Release 1.0\n![[#Details]]\n\n"
+        );
+        Ok(())
+    }
+}
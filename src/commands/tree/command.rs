@@ -1,36 +1,36 @@
-use std::io::BufWriter;
+use std::{io::BufWriter, path::PathBuf};
 
 use anyhow::Result;
+use chrono::NaiveDate;
 use ptree::{write_tree, TreeBuilder};
+use serde::Serialize;
 
-use super::config::TreeConfig;
+use super::config::{TreeConfig, TreeFormat};
 use crate::{
-    commands::io::{FileReader, OutputWriter},
-    models::{MarkdownTokenizer, Section, SectionBuilder, Token, TokenType},
+    commands::{io::OutputSink, Command, ParseContext, Report},
+    models::{Section, Token, TokenType},
 };
 
-pub fn run<T, S, R>(
-    config: TreeConfig,
-    tokenizer: T,
-    section_builder: S,
-    reader: R,
-    writers: Vec<Box<dyn OutputWriter>>,
-) -> Result<()>
-where
-    T: MarkdownTokenizer,
-    S: SectionBuilder,
-    R: FileReader,
-{
-    let markdown_string = reader.read(config.input_path.clone())?;
-    let tokens = tokenizer.tokenize(&markdown_string)?;
-    let sections = section_builder.sections_from_tokens(tokens)?;
-
-    let output_string = sections_as_ptree_string(&sections, config.debug);
-    for writer in writers {
-        writer.write_output(&output_string)?;
-    }
+pub struct TreeCommand {
+    pub config: TreeConfig,
+}
+
+impl Command for TreeCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+
+        let output_string = match config.format {
+            TreeFormat::Text => sections_as_ptree_string(&sections, config.debug),
+            TreeFormat::Json => serde_json::to_string_pretty(&outline(&sections, ""))?,
+        };
+        out.write_output(&output_string)?;
 
-    Ok(())
+        Ok(Report {
+            result_count: sections.len(),
+        })
+    }
 }
 
 fn sections_as_ptree_string(sections: &[Section], debug: bool) -> String {
@@ -50,14 +50,49 @@ fn sections_as_ptree_string(sections: &[Section], debug: bool) -> String {
 fn add_section_to_tree(section: &Section, tb: &mut TreeBuilder, debug: bool) {
     tb.begin_child(match debug {
         true => section.title.to_debug_string(),
-        false => section.title.to_markdown_string(),
+        false => section.title.to_markdown_string().trim_start().to_string(),
     });
 
-    for c in &section.content {
+    // Nested list items are added as children of their parent item
+    let mut open_list_items = 0;
+    let mut tokens = section.content.iter().peekable();
+    while let Some(c) = tokens.next() {
         match c.token_type() {
             TokenType::Newline | TokenType::Blankline => continue,
+            TokenType::ListItem => {
+                let level = match c {
+                    Token::ListItem { level, .. } => *level,
+                    _ => 0,
+                };
+                while open_list_items > level {
+                    tb.end_child();
+                    open_list_items -= 1;
+                }
+                let mut label = match debug {
+                    true => c.to_debug_string(),
+                    false => c.to_markdown_string().trim_start().to_string(),
+                };
+                // A task written as list item (`- TODO: Task`) follows its list item
+                if let Some(task) = tokens.next_if(|t| matches!(t, Token::Task { .. })) {
+                    label.push_str(&match debug {
+                        true => task.to_debug_string(),
+                        false => task.to_markdown_string(),
+                    });
+                }
+                tb.begin_child(label);
+                open_list_items += 1;
+            }
             _ => {
-                if !token_is_empty(c) {
+                for _ in 0..open_list_items {
+                    tb.end_child();
+                }
+                open_list_items = 0;
+                // Every row on its own line, so the table stays aligned
+                if let (Token::Table(rows), false) = (c, debug) {
+                    for row in rows {
+                        tb.add_empty_child(row.to_markdown_string());
+                    }
+                } else if !token_is_empty(c) {
                     tb.add_empty_child(match debug {
                         true => c.to_debug_string(),
                         false => c.to_markdown_string(),
@@ -66,6 +101,9 @@ fn add_section_to_tree(section: &Section, tb: &mut TreeBuilder, debug: bool) {
             }
         };
     }
+    for _ in 0..open_list_items {
+        tb.end_child();
+    }
 
     for s in &section.subsections {
         if s.subsections.is_empty() && s.content.is_empty() {
@@ -76,13 +114,132 @@ fn add_section_to_tree(section: &Section, tb: &mut TreeBuilder, debug: bool) {
                 });
             };
         } else {
-            add_section_to_tree(&s, tb, debug);
+            add_section_to_tree(s, tb, debug);
         }
     }
 
     tb.end_child();
 }
 
+/// A section of the tree emitted with `--format json`
+#[derive(Debug, PartialEq, Serialize)]
+struct OutlineNode {
+    /// Outline number, e.g. `2.1` for the first subsection of the second section
+    id: String,
+    title: String,
+    /// Nesting depth, 1 for the top level sections
+    depth: usize,
+    date: Option<NaiveDate>,
+    tags: Vec<String>,
+    file: Option<PathBuf>,
+    line: usize,
+    child_count: usize,
+    children: Vec<OutlineNode>,
+}
+
+fn outline(sections: &[Section], parent_id: &str) -> Vec<OutlineNode> {
+    sections
+        .iter()
+        .enumerate()
+        .map(|(i, section)| {
+            let id = match parent_id {
+                "" => (i + 1).to_string(),
+                parent => format!("{}.{}", parent, i + 1),
+            };
+            OutlineNode {
+                title: section.title_string(),
+                depth: id.split('.').count(),
+                date: section.date,
+                tags: section.tags.clone(),
+                file: section.origin.file.clone(),
+                line: section.origin.line,
+                child_count: section.subsections.len(),
+                children: outline(&section.subsections, &id),
+                id,
+            }
+        })
+        .collect()
+}
+
 fn token_is_empty(token: &Token) -> bool {
     token.to_markdown_string().trim().is_empty()
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        markdown::{MDPMarkdownTokenizer, MDPSectionBuilder},
+        models::{MarkdownTokenizer, SectionBuilder},
+    };
+
+    #[test]
+    fn test_sections_as_ptree_string_nested_list() -> Result<()> {
+        let markdown = "# 2024-01-01\n- Groceries\n  - Apples\n  - Pears\n- Laundry\nDone.\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+
+        assert_eq!(
+            sections_as_ptree_string(&sections, false),
+            "\n\
+            └─ # 2024-01-01\n   \
+               ├─ - Groceries\n   \
+               │  ├─ - Apples\n   \
+               │  └─ - Pears\n   \
+               ├─ - Laundry\n   \
+               └─ Done.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sections_as_ptree_string_table() -> Result<()> {
+        let markdown = "# 2024-01-01\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+
+        assert_eq!(
+            sections_as_ptree_string(&sections, false),
+            "\n\
+            └─ # 2024-01-01\n   \
+               ├─ | a | b |\n   \
+               ├─ |---|---|\n   \
+               └─ | 1 | 2 |\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_outline() -> Result<()> {
+        let markdown =
+            "# 2024-01-01\n\n## Meeting\n@work\n\n### Notes\n\n## Lunch\n\n# 2024-01-02\n";
+        let tokens = MDPMarkdownTokenizer::default().tokenize_with_origins(markdown, None)?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens_with_origins(tokens)?;
+
+        let outline = outline(&sections, "");
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].child_count, 2);
+        let meeting = &outline[0].children[0];
+        assert_eq!(
+            (
+                meeting.id.as_str(),
+                meeting.title.as_str(),
+                meeting.depth,
+                meeting.line
+            ),
+            ("1.1", "Meeting", 2, 3)
+        );
+        assert_eq!(meeting.tags, vec!["work"]);
+        assert_eq!(meeting.children[0].id, "1.1.1");
+        assert_eq!(outline[0].children[1].id, "1.2");
+        assert_eq!(outline[1].id, "2");
+
+        let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&outline)?)?;
+        assert_eq!(json[0]["children"][0]["children"][0]["title"], "Notes");
+        assert_eq!(json[1]["date"], "2024-01-02");
+        Ok(())
+    }
+}
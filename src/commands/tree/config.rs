@@ -1,7 +1,77 @@
 use std::path::PathBuf;
 
+use crate::commands::InputBuilder;
+
 #[derive(Clone, Debug)]
 pub struct TreeConfig {
     pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
     pub debug: bool,
+    pub format: TreeFormat,
+}
+
+impl TreeConfig {
+    pub fn builder() -> TreeConfigBuilder {
+        TreeConfigBuilder::default()
+    }
+}
+
+/// Builder for [`TreeConfig`]
+#[derive(Clone, Debug)]
+pub struct TreeConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    debug: bool,
+    format: TreeFormat,
+}
+
+impl Default for TreeConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            debug: false,
+            format: TreeFormat::Text,
+        }
+    }
+}
+
+impl InputBuilder for TreeConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl TreeConfigBuilder {
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    pub fn format(mut self, format: TreeFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> TreeConfig {
+        TreeConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            debug: self.debug,
+            format: self.format,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeFormat {
+    /// Tree of the sections and their content drawn with box characters
+    Text,
+    /// The section tree with outline numbers, depths, dates, tags and origins (e.g. for
+    /// editors building a navigable outline)
+    Json,
 }
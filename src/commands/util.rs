@@ -0,0 +1,21 @@
+/// A 64 bit FNV-1a hash of the bytes, which (unlike the std hasher) is stable across
+/// releases and can therefore be stored (e.g. in caches or generated files)
+pub(crate) fn stable_hash(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_hash() {
+        // Test vectors of the FNV-1a reference implementation
+        assert_eq!(stable_hash("".bytes()), 0xcbf29ce484222325);
+        assert_eq!(stable_hash("a".bytes()), 0xaf63dc4c8601ec8c);
+        assert_eq!(stable_hash("foobar".bytes()), 0x85944171f73967e8);
+        assert_ne!(stable_hash("ab".bytes()), stable_hash("ba".bytes()));
+    }
+}
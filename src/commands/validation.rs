@@ -0,0 +1,279 @@
+use std::path::{Component, Path, PathBuf};
+
+use chrono::NaiveDate;
+
+use super::{
+    io::default_extensions,
+    oneline::ResultFormat,
+    replace::config::ReplaceConfig,
+    search::config::{SearchConfig, SearchTerm},
+    summarize::config::SummarizeConfig,
+    tasks::config::{TaskFilterType, TaskOrderingCriterion, TasksConfig},
+};
+use crate::models::ConfigError;
+
+/// Semantic checks of a configuration, catching combinations of options which are valid on
+/// their own but would produce confusing results together.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ConfigError>;
+}
+
+impl Validate for SearchConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        check_date_range(self.from, self.until)?;
+        if self.query().is_unrestricted() {
+            return Err(ConfigError::InvalidOptionsError(
+                "Nothing to search for, give a search term or an attribute (--attr)".to_string(),
+            ));
+        }
+        for term in &self.search_terms {
+            if let SearchTerm::Fuzzy { threshold, .. } = term {
+                if !(0.0..=1.0).contains(threshold) {
+                    return Err(ConfigError::InvalidOptionsError(format!(
+                        "The threshold of --fuzzy is the minimal similarity between 0 and 1 \
+                         (e.g. 0.8), not {}",
+                        threshold
+                    )));
+                }
+            }
+        }
+        if self.format == ResultFormat::Oneline
+            && (self.split_output.is_some() || self.group_by.is_some())
+        {
+            return Err(ConfigError::InvalidOptionsError(
+                "--format oneline writes one line per result, it can't be combined with \
+                 --split-output or --group-by"
+                    .to_string(),
+            ));
+        }
+        match &self.split_output {
+            Some(dir) => check_output_dir(dir, &self.input_path, self.max_depth),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Validate for ReplaceConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.find.is_empty() {
+            return Err(ConfigError::InvalidOptionsError(
+                "Nothing to replace, the text to find is empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SummarizeConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.command.trim().is_empty() {
+            return Err(ConfigError::InvalidOptionsError(
+                "There is no summarizer, pass it with --command or set `command` in the \
+                 [summarize] section of config.toml"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for TasksConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if matches!(self.ordering, TaskOrderingCriterion::Urgency)
+            && matches!(self.filter, TaskFilterType::Finished)
+        {
+            return Err(ConfigError::InvalidOptionsError(
+                "Finished tasks have no urgency, order them with `--order occurence` or show \
+                 the unfinished tasks with `--show unfinished`"
+                    .to_string(),
+            ));
+        }
+        if matches!(self.ordering, TaskOrderingCriterion::Urgency) && self.tree {
+            return Err(ConfigError::InvalidOptionsError(
+                "The tree keeps the order of the tasks, use `--order occurence` with `--tree`"
+                    .to_string(),
+            ));
+        }
+        if self.format == ResultFormat::Oneline && (self.tree || self.summary) {
+            return Err(ConfigError::InvalidOptionsError(
+                "--format oneline writes one line per task, it can't be combined with --tree \
+                 or --summary"
+                    .to_string(),
+            ));
+        }
+        check_date_range(self.from, self.until)?;
+        check_due_date_range(self.due_after, self.due_before)
+    }
+}
+
+fn check_date_range(from: Option<NaiveDate>, until: Option<NaiveDate>) -> Result<(), ConfigError> {
+    match (from, until) {
+        (Some(from), Some(until)) if until < from => {
+            Err(ConfigError::InvalidOptionsError(format!(
+                "--until {} is before --from {}, so no section can match, swap the dates",
+                until, from
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_due_date_range(
+    after: Option<NaiveDate>,
+    before: Option<NaiveDate>,
+) -> Result<(), ConfigError> {
+    match (after, before) {
+        (Some(after), Some(before)) if before < after => {
+            Err(ConfigError::InvalidOptionsError(format!(
+                "--due-before {} is before --due-after {}, so no task can match, swap the dates",
+                before, after
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The input directory from which the output file would be read by later runs (as it is a
+/// markdown file within the traversal depth of the directory)
+pub(crate) fn output_inside_input<'i>(
+    output: &Path,
+    inputs: &'i [PathBuf],
+    max_depth: Option<usize>,
+) -> Option<&'i PathBuf> {
+    let extensions = default_extensions();
+    let is_markdown = output
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| extensions.contains(&e));
+    match is_markdown {
+        true => input_containing(output, inputs, max_depth),
+        false => None,
+    }
+}
+
+/// Fails if the files written to the output directory would be read as input by later runs
+fn check_output_dir(
+    dir: &Path,
+    inputs: &[PathBuf],
+    max_depth: Option<usize>,
+) -> Result<(), ConfigError> {
+    match input_containing(&dir.join("file.md"), inputs, max_depth) {
+        Some(input) => Err(ConfigError::InvalidOptionsError(format!(
+            "The output directory {} is inside the input directory {} and its files would be \
+             read as input by later runs, choose another directory",
+            dir.display(),
+            input.display()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The input directory from which `path` is read (with the traversal depth `max_depth`)
+fn input_containing<'i>(
+    path: &Path,
+    inputs: &'i [PathBuf],
+    max_depth: Option<usize>,
+) -> Option<&'i PathBuf> {
+    let path = normalize(path);
+    inputs.iter().find(|input| {
+        // Input files are read explicitly, only directories are traversed
+        if input.is_file() {
+            return false;
+        }
+        match path.strip_prefix(normalize(input)) {
+            Ok(rest) => max_depth.is_none_or(|depth| rest.components().count() <= depth),
+            Err(_) => false,
+        }
+    })
+}
+
+/// The absolute path without `.` and `..` components (the path does not need to exist)
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::InputBuilder;
+    use crate::commands::search::config::{SearchConfigBuilder, SearchTermMatching};
+
+    #[test]
+    fn test_validate() {
+        fn search(
+            builder: impl FnOnce(SearchConfigBuilder) -> SearchConfigBuilder,
+        ) -> Result<(), &'static str> {
+            let config = builder(SearchConfig::builder().input("diary").terms(["@work"]));
+            config.build().unwrap().validate().map_err(|e| e.code())
+        }
+        assert_eq!(search(|b| b.split_output("diary")), Err("MDP105"));
+        assert_eq!(search(|b| b.split_output("results")), Ok(()));
+        assert_eq!(
+            search(|b| b.split_output("results").format(ResultFormat::Oneline)),
+            Err("MDP105")
+        );
+
+        let date = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+        assert_eq!(search(|b| b.from(date(1)).until(date(1))), Ok(()));
+        assert_eq!(search(|b| b.from(date(2)).until(date(1))), Err("MDP105"));
+        assert_eq!(
+            search(|b| b.matching(SearchTermMatching::Fuzzy(0.8))),
+            Ok(())
+        );
+        assert_eq!(
+            search(|b| b.matching(SearchTermMatching::Fuzzy(80.0))),
+            Err("MDP105")
+        );
+
+        let tasks = |ordering, filter| {
+            TasksConfig::builder()
+                .ordering(ordering)
+                .filter(filter)
+                .build()
+                .validate()
+                .map_err(|e| e.code())
+        };
+        assert_eq!(
+            tasks(TaskOrderingCriterion::Urgency, TaskFilterType::Unfinished),
+            Ok(())
+        );
+        assert_eq!(
+            tasks(TaskOrderingCriterion::Urgency, TaskFilterType::Finished),
+            Err("MDP105")
+        );
+        let due = TasksConfig::builder().due_after(date(2));
+        assert_eq!(due.clone().build().validate().map_err(|e| e.code()), Ok(()));
+        let due = due.due_before(date(1)).build();
+        assert_eq!(due.validate().map_err(|e| e.code()), Err("MDP105"));
+    }
+
+    #[test]
+    fn test_output_inside_input() {
+        let inputs = [PathBuf::from("diary")];
+        let inside =
+            |output: &str, max_depth| output_inside_input(Path::new(output), &inputs, max_depth);
+        assert_eq!(inside("search.md", Some(1)), None);
+        assert_eq!(inside("diary/search.md", Some(1)), Some(&inputs[0]));
+        assert_eq!(
+            inside("./diary/../diary/search.md", Some(1)),
+            Some(&inputs[0])
+        );
+        // Not read as input (no markdown file or not within the traversal depth)
+        assert_eq!(inside("diary/search.txt", Some(1)), None);
+        assert_eq!(inside("diary/out/search.md", Some(1)), None);
+        assert_eq!(inside("diary/out/search.md", None), Some(&inputs[0]));
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::config::WaitingConfig;
+use crate::{
+    commands::{io::OutputSink, Command, ParseContext, Report},
+    models::{Origin, Section, TaskStatus, Token},
+};
+
+/// Shows the delegated tasks (`WAITING(@person):`) grouped by the person they wait for,
+/// together with the number of days since they were delegated (the date of their section).
+pub struct WaitingCommand {
+    pub config: WaitingConfig,
+}
+
+/// A task waiting for a person
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct WaitingTask {
+    /// The task without its status
+    description: String,
+    /// Date of the section the task was written in
+    since: Option<NaiveDate>,
+    origin: Origin,
+}
+
+impl Command for WaitingCommand {
+    fn run(&self, ctx: &ParseContext, out: &mut dyn OutputSink) -> Result<Report> {
+        let config = &self.config;
+        let today = ctx.clock.today();
+        let files = ctx.read(config.input_path.clone())?;
+        let sections = ctx.sections(&files)?;
+
+        let mut groups: BTreeMap<String, Vec<WaitingTask>> = BTreeMap::new();
+        for (person, task) in waiting_tasks(&sections) {
+            groups
+                .entry(ctx.tags.normalize(&person))
+                .or_default()
+                .push(task);
+        }
+        if let Some(person) = &config.person {
+            let person = ctx.tags.normalize(person.trim_start_matches('@'));
+            groups.retain(|p, _| *p == person);
+        }
+        if groups.is_empty() {
+            log::warn!("No waiting tasks found!");
+            return Ok(Report::default());
+        }
+
+        let mut result_count = 0;
+        let mut parts = vec![];
+        for (person, mut tasks) in groups {
+            // Longest waiting first, tasks of sections without a date last
+            tasks.sort_by_key(|t| (t.since.is_none(), t.since, t.origin.line));
+            result_count += tasks.len();
+            let lines = tasks
+                .iter()
+                .map(|t| match t.since {
+                    Some(since) => format!("- {} ({}d)", t.description, (today - since).num_days()),
+                    None => format!("- {}", t.description),
+                })
+                .collect::<Vec<String>>();
+            parts.push(format!("# @{}\n\n{}", person, lines.join("\n")));
+        }
+
+        out.write_output(&parts.join("\n\n"))?;
+        Ok(Report { result_count })
+    }
+}
+
+/// The waiting tasks of all sections (and their subsections) with the person they wait for
+fn waiting_tasks(sections: &[Section]) -> Vec<(String, WaitingTask)> {
+    let mut tasks = vec![];
+    for section in sections {
+        for (token, origin) in section.content_with_origins() {
+            if let Token::Task {
+                content,
+                status: TaskStatus::Waiting(person),
+                ..
+            } = token
+            {
+                let description: String = content.iter().map(|t| t.to_markdown_string()).collect();
+                tasks.push((
+                    person.clone(),
+                    WaitingTask {
+                        description: description.trim().to_string(),
+                        since: section.date,
+                        origin,
+                    },
+                ));
+            }
+        }
+        tasks.extend(waiting_tasks(&section.subsections));
+    }
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::commands::{FixedClock, InputBuilder};
+
+    #[test]
+    fn test_waiting() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("diary.md");
+        fs::write(
+            &path,
+            "# 2023-10-02\n\nWAITING(@roger): Send the contract\nTODO: Call @roger\n\n\
+             # 2023-10-09\n\n## Meeting\n\nWAITING(@anna): Review the slides\n\
+             WAITING(@roger): Book the room\n",
+        )?;
+
+        let mut ctx = ParseContext::default();
+        ctx.clock = Box::new(FixedClock(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap()));
+        let run = |config: WaitingConfig| -> Result<(String, usize)> {
+            let mut output = String::new();
+            let report = WaitingCommand { config }.run(&ctx, &mut output)?;
+            Ok((output, report.result_count))
+        };
+
+        let (output, count) = run(WaitingConfig::builder().input(&path).build())?;
+        assert_eq!(
+            output,
+            "# @anna\n\n- Review the slides (1d)\n\n\
+             # @roger\n\n- Send the contract (8d)\n- Book the room (1d)"
+        );
+        assert_eq!(count, 3);
+
+        let (output, count) = run(WaitingConfig::builder()
+            .input(&path)
+            .person("@anna")
+            .build())?;
+        assert_eq!(output, "# @anna\n\n- Review the slides (1d)");
+        assert_eq!(count, 1);
+        Ok(())
+    }
+}
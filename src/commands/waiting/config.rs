@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use crate::commands::InputBuilder;
+
+#[derive(Clone, Debug)]
+pub struct WaitingConfig {
+    pub input_path: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+    pub output_path: Option<PathBuf>,
+    /// Only show the tasks waiting for this person (given with or without `@`)
+    pub person: Option<String>,
+}
+
+impl WaitingConfig {
+    pub fn builder() -> WaitingConfigBuilder {
+        WaitingConfigBuilder::default()
+    }
+}
+
+/// Builder for [`WaitingConfig`]
+#[derive(Clone, Debug)]
+pub struct WaitingConfigBuilder {
+    input_path: Vec<PathBuf>,
+    max_depth: Option<usize>,
+    output_path: Option<PathBuf>,
+    person: Option<String>,
+}
+
+impl Default for WaitingConfigBuilder {
+    fn default() -> Self {
+        Self {
+            input_path: vec![],
+            max_depth: Some(1),
+            output_path: None,
+            person: None,
+        }
+    }
+}
+
+impl InputBuilder for WaitingConfigBuilder {
+    fn input_path_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.input_path
+    }
+
+    fn max_depth_mut(&mut self) -> &mut Option<usize> {
+        &mut self.max_depth
+    }
+}
+
+impl WaitingConfigBuilder {
+    pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Only show the tasks waiting for this person (e.g. `@roger`)
+    pub fn person<S: Into<String>>(mut self, person: S) -> Self {
+        self.person = Some(person.into());
+        self
+    }
+
+    pub fn build(self) -> WaitingConfig {
+        WaitingConfig {
+            input_path: self.input_path,
+            max_depth: self.max_depth,
+            output_path: self.output_path,
+            person: self.person,
+        }
+    }
+}
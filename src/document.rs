@@ -0,0 +1,263 @@
+use std::{collections::HashMap, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::{
+    commands::{
+        io::MarkdownFile,
+        tags::{command::count_tags, normalize::TagNormalizer},
+    },
+    markdown::{parse_front_matter, MDPMarkdownTokenizer, MDPSectionBuilder},
+    models::{
+        FrontMatter, MDPError, MarkdownTokenizer, Origin, Section, SectionBuilder, TaskStatus,
+        Token,
+    },
+};
+
+/// A parsed markdown document, the entry point for using mdp as a library.
+///
+/// The document borrows the markdown string it was parsed from:
+///
+/// ```
+/// let markdown = "# 2023-10-10\n\n## Meeting\n@work\n\nTODO: Send minutes\n";
+/// let document = mdp::Document::parse(markdown).unwrap();
+///
+/// assert_eq!(document.find_sections_by_tag("work")[0].title_string(), "Meeting");
+/// assert_eq!(document.tasks()[0].content_string(), "Send minutes");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Document<'a> {
+    front_matter: Option<FrontMatter>,
+    tokens: Vec<(Token<'a>, Origin)>,
+    sections: Vec<Section<'a>>,
+}
+
+/// A task of a [`Document`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Task<'a> {
+    pub content: Vec<Token<'a>>,
+    pub status: TaskStatus,
+    pub origin: Origin,
+    /// Date of the section the task belongs to (if it has one)
+    pub date: Option<NaiveDate>,
+}
+
+impl<'a> Task<'a> {
+    /// The content of the task as markdown (without the status)
+    pub fn content_string(&self) -> String {
+        self.content
+            .iter()
+            .map(|t| t.to_markdown_string())
+            .collect()
+    }
+}
+
+impl<'a> Document<'a> {
+    /// Parse a markdown string using the MDP tokenizer and section builder
+    pub fn parse(markdown: &'a str) -> Result<Self, MDPError> {
+        Self::parse_with(
+            markdown,
+            None,
+            &MDPMarkdownTokenizer::default(),
+            &MDPSectionBuilder::default(),
+        )
+    }
+
+    /// Parse a markdown file, the origins of tokens and sections refer to its path.
+    pub fn from_file(file: &'a MarkdownFile) -> Result<Self, MDPError> {
+        Self::parse_with(
+            &file.content,
+            Some(&file.path),
+            &MDPMarkdownTokenizer::default(),
+            &MDPSectionBuilder::default(),
+        )
+    }
+
+    /// Parse a markdown string using the given tokenizer and section builder (e.g. the
+    /// [`crate::markdown::OutlineSectionBuilder`]). The tags of the front matter (if any)
+    /// are added to the top level sections.
+    pub fn parse_with(
+        markdown: &'a str,
+        file: Option<&Path>,
+        tokenizer: &dyn MarkdownTokenizer,
+        section_builder: &dyn SectionBuilder,
+    ) -> Result<Self, MDPError> {
+        let front_matter = parse_front_matter(markdown)?;
+        let (body, offset) = match &front_matter {
+            Some(f) => (f.body(markdown), f.line_count),
+            None => (markdown, 0),
+        };
+
+        let mut tokens = tokenizer
+            .tokenize_with_origins(body, file)
+            .map_err(|e| e.offset_lines(offset))?;
+        for (_, origin) in &mut tokens {
+            origin.line += offset;
+        }
+        let mut sections = section_builder.sections_from_tokens_with_origins(tokens.clone())?;
+        if let Some(f) = &front_matter {
+            f.tag_sections(&mut sections);
+        }
+        Ok(Self {
+            front_matter,
+            tokens,
+            sections,
+        })
+    }
+
+    /// The metadata given in the YAML block at the start of the document
+    pub fn front_matter(&self) -> Option<&FrontMatter> {
+        self.front_matter.as_ref()
+    }
+
+    /// All tokens together with their origin
+    pub fn tokens(&self) -> &[(Token<'a>, Origin)] {
+        &self.tokens
+    }
+
+    /// The top level (dated) sections
+    pub fn sections(&self) -> &[Section<'a>] {
+        &self.sections
+    }
+
+    /// All tasks within the sections (in order of occurence)
+    pub fn tasks(&self) -> Vec<Task<'a>> {
+        fn collect<'a>(sections: &[Section<'a>], tasks: &mut Vec<Task<'a>>) {
+            for section in sections {
+                for (token, origin) in section.content_with_origins() {
+                    if let Token::Task {
+                        content, status, ..
+                    } = token
+                    {
+                        tasks.push(Task {
+                            content: content.clone(),
+                            status: status.clone(),
+                            origin,
+                            date: section.date,
+                        });
+                    }
+                }
+                collect(&section.subsections, tasks);
+            }
+        }
+
+        let mut tasks = vec![];
+        collect(&self.sections, &mut tasks);
+        tasks
+    }
+
+    /// All tags and how often they occur
+    pub fn tags(&self) -> HashMap<String, usize> {
+        let front_matter_tags = self.front_matter.iter().flat_map(|f| &f.tags);
+        count_tags(
+            self.tokens.iter().map(|(t, _)| t),
+            front_matter_tags,
+            &TagNormalizer::default(),
+        )
+    }
+
+    /// The top level sections dated between `from` and `until` (both inclusive), sections
+    /// spanning several days if one of their days is
+    pub fn sections_between(&self, from: NaiveDate, until: NaiveDate) -> Vec<&Section<'a>> {
+        self.sections
+            .iter()
+            .filter(|s| s.date.is_some_and(|d| d <= until))
+            .filter(|s| s.last_date().is_some_and(|d| from <= d))
+            .collect()
+    }
+
+    /// All sections (at any level) tagged with `tag` (without the leading `@`)
+    pub fn find_sections_by_tag(&self, tag: &str) -> Vec<&Section<'a>> {
+        fn collect<'s, 'a>(sections: &'s [Section<'a>], tag: &str, res: &mut Vec<&'s Section<'a>>) {
+            for section in sections {
+                if section.tags.iter().any(|t| t == tag) {
+                    res.push(section);
+                }
+                collect(&section.subsections, tag, res);
+            }
+        }
+
+        let mut res = vec![];
+        collect(&self.sections, tag, &mut res);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const DIARY: &str = "\
+# 2023-10-09
+
+## Meeting
+@work @roger
+
+TODO: Send minutes
+
+# 2023-10-10
+
+## Lunch
+@roger
+
+DONE: Pay the bill
+";
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 10, day).unwrap()
+    }
+
+    #[test]
+    fn test_document() -> Result<()> {
+        let document = Document::parse(DIARY)?;
+
+        assert_eq!(document.sections().len(), 2);
+        assert_eq!(document.tags().get("roger"), Some(&2));
+
+        let tasks = document.tasks();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].status, TaskStatus::Todo);
+        assert_eq!(tasks[0].origin.line, 6);
+        assert_eq!(tasks[1].date, Some(date(10)));
+        assert_eq!(tasks[1].content_string(), "Pay the bill");
+
+        let between = document.sections_between(date(10), date(31));
+        assert_eq!(between.len(), 1);
+        assert_eq!(between[0].date, Some(date(10)));
+
+        let titles = |tag| {
+            document
+                .find_sections_by_tag(tag)
+                .iter()
+                .map(|s| s.title_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles("roger"), vec!["Meeting", "Lunch"]);
+        assert_eq!(titles("work"), vec!["Meeting"]);
+        assert!(titles("school").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_document_with_front_matter() -> Result<()> {
+        let markdown = format!(
+            "---\ntitle: October\ntags: [diary, roger]\n---\n\n{}",
+            DIARY
+        );
+        let document = Document::parse(&markdown)?;
+
+        assert_eq!(
+            document.front_matter().unwrap().title.as_deref(),
+            Some("October")
+        );
+        assert_eq!(document.tags().get("diary"), Some(&1));
+        assert_eq!(document.tags().get("roger"), Some(&3));
+        assert_eq!(document.tasks()[0].origin.line, 11);
+        assert_eq!(document.sections()[1].tags, vec!["diary", "roger"]);
+        assert_eq!(document.find_sections_by_tag("diary").len(), 2);
+        Ok(())
+    }
+}
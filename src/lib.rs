@@ -1,3 +1,7 @@
 pub mod commands;
 pub mod markdown;
 pub mod models;
+
+mod document;
+
+pub use document::*;
@@ -12,6 +12,8 @@ pub(super) enum MarkdownParseError<I> {
     InvalidISO8601Date,
     UnbalancedBracketCount,
     IncompleteInput,
+    /// The line could be parsed, but looks like mistyped markup (in strict mode)
+    MalformedMarkup(String),
     Nom(I, nom::error::ErrorKind),
 }
 
@@ -40,6 +42,9 @@ impl Display for MarkdownParseError<&str> {
             Self::InvalidRawURL => "The input could not be interpreted as an URL".to_string(),
             Self::UnbalancedBracketCount => "The input contains unbalanced brackets".to_string(),
             Self::IncompleteInput => "Not enough input was given.".to_string(),
+            Self::MalformedMarkup(reason) => {
+                format!("The line looks like malformed markup: {}", reason)
+            }
             Self::Nom(i, errorkind) => {
                 format!("A Nom error occured for input '{}': {:?}", i, errorkind)
             }
@@ -0,0 +1,134 @@
+use crate::models::{MarkdownTokenizer, Token};
+
+/// Splits the tokens of a Markdown string into the tokens of each of its lines (at the
+/// `Token::Newline`s), the inverse of [`render_lines`].
+///
+/// Tables and code blocks are a single token spanning several lines, the underline of a
+/// setext heading has no tokens of its own.
+pub fn token_lines<'a>(tokens: &[Token<'a>]) -> Vec<Vec<Token<'a>>> {
+    let mut lines = vec![];
+    let mut line = vec![];
+    for token in tokens {
+        match token {
+            Token::Newline => lines.push(std::mem::take(&mut line)),
+            token => line.push(token.clone()),
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Renders the lines of tokens as Markdown, one line per element (without a line break
+/// after the last one).
+///
+/// The tokens of a Markdown string are rendered back to the same string (apart from the
+/// spacing normalized by the tokenizer, see [`format_markdown`]):
+///
+/// ```
+/// use mdp::markdown::{render_lines, token_lines, MDPMarkdownTokenizer};
+/// use mdp::models::MarkdownTokenizer;
+///
+/// let markdown = "# 2024-05-01 Trip @travel\n\n- [ ] Pack\n";
+/// let tokens = MDPMarkdownTokenizer::default().tokenize(markdown).unwrap();
+/// assert_eq!(render_lines(&token_lines(&tokens)), markdown);
+/// ```
+pub fn render_lines(lines: &[Vec<Token<'_>>]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|t| t.to_markdown_string())
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Reformats a Markdown string (without front matter) into the canonical style of mdp by
+/// rendering its tokens back to Markdown:
+///
+/// - headings are written with `#` and a single space after it (setext headings as well)
+/// - task keywords are upper case, followed by a colon and a single space (e.g. `TODO: `),
+///   checked checkboxes are written as `[x]`, if the tokenizer reads keywords in any case
+/// - every heading is preceded by a blank line, other blank lines are collapsed into one
+/// - blank lines at the start and the end are removed, the last line ends with a line break
+///
+/// Lines which can not be parsed and code blocks are kept as they are, as are the line
+/// endings (`\r\n` if the string contains any).
+pub fn format_markdown(markdown: &str, tokenizer: &dyn MarkdownTokenizer) -> String {
+    let line_ending = match markdown.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    };
+    let markdown = markdown.replace("\r\n", "\n");
+    let (tokens, _) = tokenizer.tokenize_lenient(&markdown);
+
+    let mut formatted: Vec<String> = vec![];
+    let mut blank = false;
+    for line in token_lines(&tokens) {
+        match line.first() {
+            // The underline of a setext heading
+            None => continue,
+            Some(Token::Blank) => {
+                blank = true;
+                continue;
+            }
+            Some(first) => {
+                if !formatted.is_empty() && (blank || first.is_heading()) {
+                    formatted.push(String::new());
+                }
+                blank = false;
+            }
+        }
+        formatted.push(render_lines(&[line]));
+    }
+
+    match formatted.is_empty() {
+        true => String::new(),
+        false => formatted.join(line_ending) + line_ending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::markdown::MDPMarkdownTokenizer;
+
+    #[test]
+    fn test_render_lines() {
+        let tokenizer = MDPMarkdownTokenizer::default();
+        let markdown = "# 2024-05-01 Title @work\nSome *italic* and **bold** text, see \
+                        [[Note]]\n\n- [x] Done\n  - nested item\n1. TODO: Call\n> quote\n\
+                        ```rust\nfn main() {}\n```\n| a | b |\n|---|---|\n\
+                        key:: value\nend without line break";
+        let tokens = tokenizer.tokenize(markdown).unwrap();
+        assert_eq!(render_lines(&token_lines(&tokens)), markdown);
+    }
+
+    #[test]
+    fn test_format_markdown() {
+        let tokenizer = MDPMarkdownTokenizer {
+            any_case_task_keywords: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_markdown(
+                "\n\n#   2024-05-01 Trip\nPack @travel\n##Not a heading\n\n\n\
+                 todo:  Book hotel\n- [X] Buy tickets\nPacking list\n---\n- Socks\n\
+                 ```\n\n\n```\n\n",
+                &tokenizer
+            ),
+            "# 2024-05-01 Trip\nPack @travel\n##Not a heading\n\nTODO: Book hotel\n\
+             - [x] Buy tickets\n\n## Packing list\n- Socks\n```\n\n\n```\n"
+        );
+        assert_eq!(
+            format_markdown("# A\r\ntext\r\n## B\r\n", &tokenizer),
+            "# A\r\ntext\r\n\r\n## B\r\n"
+        );
+        assert_eq!(format_markdown("\n\n", &tokenizer), "");
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::NaiveDate;
+use yaml_rust::{Yaml, YamlLoader};
+
+use crate::models::{FrontMatter, MDPError};
+
+/// Parse the YAML front matter at the start of a file (delimited by `---` lines, the closing
+/// delimiter may also be `...`).
+///
+/// Returns `None` if the file has no front matter or if the block is no YAML mapping (e.g. a
+/// horizontal rule followed by text). `tags` and `aliases` are either lists or comma separated
+/// strings, a leading `@` or `#` of the tags is removed.
+pub fn parse_front_matter(content: &str) -> Result<Option<FrontMatter>, MDPError> {
+    let mut lines = content.split('\n').map(|l| l.trim_end_matches('\r'));
+    if lines.next() != Some("---") {
+        return Ok(None);
+    }
+    let Some(length) = lines.position(|l| l == "---" || l == "...") else {
+        return Ok(None);
+    };
+    let yaml = content
+        .split('\n')
+        .skip(1)
+        .take(length)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let documents = YamlLoader::load_from_str(&yaml).map_err(|e| MDPError::MarkdownParseError {
+        msg: format!("Invalid front matter: {}", e),
+        line_number: e.marker().line() + 1,
+    })?;
+    let mapping = match documents.into_iter().next() {
+        None => Yaml::Hash(Default::default()),
+        Some(m @ Yaml::Hash(_)) => m,
+        Some(_) => return Ok(None),
+    };
+
+    let date = match scalar(&mapping["date"]) {
+        Some(s) => Some(NaiveDate::parse_from_str(&s, "%Y-%m-%d").map_err(|_| {
+            MDPError::MDPSyntaxError(format!(
+                "The date of the front matter is no valid date (YYYY-MM-DD): {}",
+                s
+            ))
+        })?),
+        None => None,
+    };
+    let tags = list(&mapping["tags"])
+        .iter()
+        .map(|t| t.trim_start_matches(['@', '#']).to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    Ok(Some(FrontMatter {
+        title: scalar(&mapping["title"]),
+        date,
+        tags,
+        aliases: list(&mapping["aliases"]),
+        line_count: length + 2,
+    }))
+}
+
+fn scalar(yaml: &Yaml) -> Option<String> {
+    match yaml {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.to_owned()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn list(yaml: &Yaml) -> Vec<String> {
+    let values: Vec<String> = match yaml {
+        Yaml::Array(values) => values.iter().filter_map(scalar).collect(),
+        yaml => scalar(yaml)
+            .map(|s| s.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+    };
+    values
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let content = "---\ntitle: Project X\ndate: 2024-03-01\ntags: [work, \"@projectX\"]\n\
+                       aliases: px, project x\n---\n\n# 2024-03-01\n";
+        let front_matter = parse_front_matter(content).unwrap().unwrap();
+        assert_eq!(
+            front_matter,
+            FrontMatter {
+                title: Some("Project X".to_string()),
+                date: NaiveDate::from_ymd_opt(2024, 3, 1),
+                tags: vec!["work".to_string(), "projectX".to_string()],
+                aliases: vec!["px".to_string(), "project x".to_string()],
+                line_count: 6,
+            }
+        );
+        assert_eq!(front_matter.body(content), "\n# 2024-03-01\n");
+
+        let empty = parse_front_matter("---\n...\n").unwrap().unwrap();
+        assert_eq!(
+            empty,
+            FrontMatter {
+                line_count: 2,
+                ..Default::default()
+            }
+        );
+
+        // No front matter at all or only horizontal rules around text
+        assert_eq!(parse_front_matter("# 2024-03-01\n---\n").unwrap(), None);
+        assert_eq!(parse_front_matter("---\nSome text\n---\n").unwrap(), None);
+        assert_eq!(parse_front_matter("---\ntitle: unclosed\n").unwrap(), None);
+
+        assert_eq!(
+            parse_front_matter("---\ntags: [work\n---\n")
+                .unwrap_err()
+                .code(),
+            "MDP001"
+        );
+        assert_eq!(
+            parse_front_matter("---\ndate: tomorrow\n---\n")
+                .unwrap_err()
+                .code(),
+            "MDP002"
+        );
+    }
+}
@@ -1,7 +1,16 @@
 mod errors;
+mod format;
+mod front_matter;
+mod near_miss;
+mod outline;
 mod parsers;
 mod sections;
 mod tokenize;
 
+pub use format::*;
+pub use front_matter::*;
+pub use near_miss::misspelled_task_keyword;
+pub use outline::*;
+pub use parsers::day_offset;
 pub use sections::*;
 pub use tokenize::*;
@@ -0,0 +1,185 @@
+use crate::models::Token;
+
+/// Keywords of tasks which have to be followed by a colon
+const TASK_KEYWORDS: &[&str] = &["TODO", "DOING", "REVIEW", "WAITING", "DONE"];
+
+/// Delimiters of inline markup, left in the text if they are not closed
+const MARKUP_DELIMITERS: &[&str] = &["**", "~~", "^^"];
+
+/// Checks a line which was parsed without errors for markup which is probably mistyped, as
+/// it ended up as plain text (e.g. `TODO : Call` is no task). Returns why the line looks
+/// malformed.
+pub(super) fn near_miss(line: &str, tokens: &[Token]) -> Option<String> {
+    task_near_miss(line, tokens)
+        .or_else(|| heading_near_miss(tokens))
+        .or_else(|| markup_near_miss(tokens))
+}
+
+/// The start of a line (after a list marker) written like a task keyword, but in another
+/// case (e.g. `todo:` or `Done:`) or with a fullwidth colon (`TODO：`), together with the
+/// keyword it should be. The tokenizer does not read such lines as tasks (unless it accepts
+/// keywords in any case).
+pub fn misspelled_task_keyword(line: &str) -> Option<(&str, &'static str)> {
+    let line = without_list_marker(line);
+    let end = line.find([':', '：'])?;
+    let (head, colon) = (&line[..end], &line[end..]);
+    let fullwidth = colon.starts_with('：');
+    let misspelled = |keyword: &str, head: &str| {
+        head.eq_ignore_ascii_case(keyword) && (head != keyword || fullwidth)
+    };
+    let keyword = if let Some(keyword) = ["TODO", "DOING", "REVIEW", "DONE"]
+        .into_iter()
+        .find(|k| misspelled(k, head))
+    {
+        keyword
+    } else if head.get(..11).is_some_and(|h| misspelled("TODO UNTIL ", h)) {
+        "TODO UNTIL"
+    } else if head.ends_with(')') && head.get(..8).is_some_and(|h| misspelled("WAITING(", h)) {
+        "WAITING"
+    } else {
+        return None;
+    };
+    Some((&line[..end + colon.chars().next()?.len_utf8()], keyword))
+}
+
+/// The line without indentation and list marker (`- `, `* ` or `+ `)
+fn without_list_marker(line: &str) -> &str {
+    let line = line.trim_start();
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|p| line.strip_prefix(p))
+        .unwrap_or(line)
+        .trim_start()
+}
+
+fn task_near_miss(line: &str, tokens: &[Token]) -> Option<String> {
+    if tokens.iter().any(|t| matches!(t, Token::Task { .. })) {
+        return None;
+    }
+    let line = without_list_marker(line);
+    let (keyword, rest) = TASK_KEYWORDS
+        .iter()
+        .find_map(|k| line.strip_prefix(k).map(|rest| (k, rest)))?;
+    if rest.starts_with(char::is_whitespace) && rest.trim_start().starts_with(':') {
+        return Some(format!(
+            "`{}` is not followed directly by `:`, so it is no task",
+            keyword
+        ));
+    }
+    if *keyword == "TODO" && rest.starts_with(" UNTIL ") && rest.contains(':') {
+        return Some(
+            "the due date of `TODO UNTIL` is no date (YYYY-MM-DD) or day like `tomorrow`, \
+             `friday` or `+3d`"
+                .to_string(),
+        );
+    }
+    if *keyword == "WAITING" && rest.starts_with([':', '(']) {
+        return Some("`WAITING` needs a person, e.g. `WAITING(@roger):`".to_string());
+    }
+    None
+}
+
+fn heading_near_miss(tokens: &[Token]) -> Option<String> {
+    let title = match tokens.first()? {
+        Token::HeadingH1(t)
+        | Token::HeadingH2(t)
+        | Token::HeadingH3(t)
+        | Token::HeadingH4(t)
+        | Token::HeadingH5(t)
+        | Token::HeadingH6(t) => t,
+        _ => return None,
+    };
+    let title = title
+        .iter()
+        .map(|t| t.to_markdown_string())
+        .collect::<String>();
+    match title.trim().chars().all(|c| c == '#') {
+        true => Some("the heading has no title".to_string()),
+        false => None,
+    }
+}
+
+fn markup_near_miss(tokens: &[Token]) -> Option<String> {
+    tokens.iter().find_map(|token| match token {
+        Token::Text(text) => MARKUP_DELIMITERS
+            .iter()
+            .find(|d| text.contains(*d))
+            .map(|d| format!("`{}` is not closed", d)),
+        // E.g. an unclosed `**` is parsed as empty italic text
+        Token::Bold(t) | Token::Italic(t) | Token::Strike(t) | Token::Highlight(t)
+            if t.is_empty() =>
+        {
+            Some(format!(
+                "`{}` is empty or not closed",
+                token.to_markdown_string()
+            ))
+        }
+        t => markup_near_miss(t.children()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{markdown::MDPMarkdownTokenizer, models::MarkdownTokenizer};
+
+    fn check(line: &str) -> Option<String> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize(line).unwrap();
+        near_miss(line, &tokens)
+    }
+
+    #[test]
+    fn test_near_miss() {
+        assert_eq!(
+            check("TODO : Buy milk").as_deref(),
+            Some("`TODO` is not followed directly by `:`, so it is no task")
+        );
+        assert!(check("- DONE  : Call").is_some());
+        assert!(check("TODO UNTIL someday: Call").is_some());
+        assert_eq!(check("TODO UNTIL tomorrow: Call"), None);
+        assert!(check("WAITING: Answer").is_some());
+        assert_eq!(check("#### #").as_deref(), Some("the heading has no title"));
+        assert_eq!(
+            check("Some **bold text").as_deref(),
+            Some("`**` is empty or not closed")
+        );
+        assert!(check("- A ~~strike").is_some());
+
+        assert_eq!(check("TODO: Buy milk"), None);
+        assert_eq!(check("- [ ] TODO : Buy milk"), None);
+        assert_eq!(check("TODOs are tracked below"), None);
+        assert_eq!(check("## Meeting"), None);
+        assert_eq!(check("Some **bold** text, `**` in code"), None);
+    }
+
+    #[test]
+    fn test_misspelled_task_keyword() {
+        assert_eq!(
+            misspelled_task_keyword("todo: Buy milk"),
+            Some(("todo:", "TODO"))
+        );
+        assert_eq!(
+            misspelled_task_keyword("  - Done: Call"),
+            Some(("Done:", "DONE"))
+        );
+        assert_eq!(
+            misspelled_task_keyword("TODO：Call"),
+            Some(("TODO：", "TODO"))
+        );
+        assert_eq!(
+            misspelled_task_keyword("Todo until 2024-06-01: Pay"),
+            Some(("Todo until 2024-06-01:", "TODO UNTIL"))
+        );
+        assert_eq!(
+            misspelled_task_keyword("waiting(@roger): Keys"),
+            Some(("waiting(@roger):", "WAITING"))
+        );
+
+        assert_eq!(misspelled_task_keyword("TODO: Buy milk"), None);
+        assert_eq!(misspelled_task_keyword("WAITING(@roger): Keys"), None);
+        assert_eq!(misspelled_task_keyword("Today: nothing to do"), None);
+        assert_eq!(misspelled_task_keyword("- [ ] todo: Buy milk"), None);
+    }
+}
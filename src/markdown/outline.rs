@@ -0,0 +1,229 @@
+use super::sections::{section_dates, DatePolicy, SectionDates, TagStyle};
+use crate::models::{MDPError, Origin, Section, SectionBuilder, SectionType, Token};
+
+/// Builds sections from the list items of an outline (Logseq/Roam style notes)
+/// instead of the headings.
+///
+/// Every list item becomes a section, the items nested below it become its subsections.
+/// Other lines are added to the content of the preceding list item, lines before the
+/// first list item are ignored. Like headings, the top level items need to contain a
+/// date unless the date policy dates them otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct OutlineSectionBuilder {
+    /// How top level items without a date are dated
+    pub date_policy: DatePolicy,
+    /// Which tokens are the tags of a section
+    pub tag_style: TagStyle,
+}
+
+impl SectionBuilder for OutlineSectionBuilder {
+    fn sections_from_tokens_with_origins<'a>(
+        &self,
+        tokens: Vec<(Token<'a>, Origin)>,
+    ) -> Result<Vec<Section<'a>>, MDPError> {
+        sections_from_blocks(
+            blocks_from_tokens(tokens),
+            None,
+            1,
+            self.date_policy,
+            self.tag_style,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Block<'a> {
+    level: usize,
+    title: Token<'a>,
+    origin: Origin,
+    content: Vec<Token<'a>>,
+    children: Vec<Block<'a>>,
+}
+
+fn blocks_from_tokens(tokens: Vec<(Token, Origin)>) -> Vec<Block> {
+    let mut roots: Vec<Block> = vec![];
+    let mut stack: Vec<Block> = vec![];
+
+    for (line, origin) in split_into_lines(tokens) {
+        match line.as_slice() {
+            [title @ Token::ListItem { level, .. }] => {
+                while stack.last().is_some_and(|b| b.level >= *level) {
+                    close_block(&mut stack, &mut roots);
+                }
+                stack.push(Block {
+                    level: *level,
+                    title: title.to_owned(),
+                    origin,
+                    content: vec![Token::Newline],
+                    children: vec![],
+                });
+            }
+            _ => {
+                if let Some(block) = stack.last_mut() {
+                    block.content.extend(line);
+                    block.content.push(Token::Newline);
+                }
+            }
+        }
+    }
+    while !stack.is_empty() {
+        close_block(&mut stack, &mut roots);
+    }
+
+    roots
+}
+
+fn close_block<'a>(stack: &mut Vec<Block<'a>>, roots: &mut Vec<Block<'a>>) {
+    if let Some(block) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(block),
+            None => roots.push(block),
+        }
+    }
+}
+
+/// Splits the tokens at `Token::Newline`, the origin is the one of the line's first token.
+fn split_into_lines(tokens: Vec<(Token, Origin)>) -> Vec<(Vec<Token>, Origin)> {
+    let mut lines = vec![];
+    let mut line = vec![];
+    let mut line_origin = None;
+
+    for (token, origin) in tokens {
+        if token == Token::Newline {
+            lines.push((
+                std::mem::take(&mut line),
+                line_origin.take().unwrap_or(origin),
+            ));
+        } else {
+            line_origin.get_or_insert(origin);
+            line.push(token);
+        }
+    }
+    if !line.is_empty() {
+        lines.push((line, line_origin.unwrap_or_default()));
+    }
+    lines
+}
+
+fn sections_from_blocks(
+    blocks: Vec<Block>,
+    parent_dates: Option<SectionDates>,
+    level: usize,
+    date_policy: DatePolicy,
+    tag_style: TagStyle,
+) -> Result<Vec<Section>, MDPError> {
+    let mut sections = vec![];
+
+    for block in blocks {
+        let title_elements = match &block.title {
+            Token::ListItem { content, .. } => content,
+            _ => continue,
+        };
+
+        let tags = title_elements
+            .iter()
+            .chain(block.content.iter())
+            .flat_map(|t| tag_style.tags(t))
+            .map(str::to_string)
+            .collect();
+
+        let (date, date_end) = match parent_dates {
+            Some(dates) => dates,
+            None => section_dates(&block.title, title_elements, &block.origin, date_policy)?,
+        };
+
+        let content = block
+            .content
+            .into_iter()
+            .filter(|t| !matches!(t, Token::HRule | Token::Blank))
+            .collect();
+
+        sections.push(Section {
+            section_type: SectionType::Block(level),
+            title: block.title,
+            tags,
+            date,
+            date_end,
+            content,
+            subsections: sections_from_blocks(
+                block.children,
+                Some((date, date_end)),
+                level + 1,
+                date_policy,
+                tag_style,
+            )?,
+            origin: block.origin,
+        });
+    }
+
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{markdown::MDPMarkdownTokenizer, models::MarkdownTokenizer};
+
+    const OUTLINE: &str = "\
+Preamble
+- 2024-01-01 Journal
+  - Meeting @work
+    - Discussed the budget
+    continued on the next line
+  - Notes
+- 2024-01-02
+\t- Tab indented
+  * Other bullet
+";
+
+    fn titles(sections: &[Section]) -> Vec<String> {
+        sections.iter().map(|s| s.title_string()).collect()
+    }
+
+    #[test]
+    fn test_outline_section_builder() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize_with_origins(OUTLINE, None)?;
+        let sections =
+            OutlineSectionBuilder::default().sections_from_tokens_with_origins(tokens)?;
+
+        assert_eq!(titles(&sections), vec!["2024-01-01 Journal", "2024-01-02"]);
+        assert_eq!(sections[0].section_type, SectionType::Block(1));
+        assert_eq!(sections[0].origin.line, 2);
+
+        let first_day = &sections[0].subsections;
+        assert_eq!(titles(first_day), vec!["Meeting @work", "Notes"]);
+        assert_eq!(first_day[0].tags, vec!["work".to_string()]);
+        assert_eq!(first_day[0].date, NaiveDate::from_ymd_opt(2024, 1, 1));
+
+        let meeting = &first_day[0].subsections[0];
+        assert_eq!(meeting.section_type, SectionType::Block(3));
+        assert_eq!(
+            meeting.content,
+            vec![
+                Token::Newline,
+                Token::Text("    continued on the next line"),
+                Token::Newline
+            ]
+        );
+
+        assert_eq!(
+            titles(&sections[1].subsections),
+            vec!["Tab indented", "Other bullet"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_outline_section_builder_requires_date() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize_with_origins("- No date\n", None)?;
+
+        assert!(OutlineSectionBuilder::default()
+            .sections_from_tokens_with_origins(tokens)
+            .is_err());
+        Ok(())
+    }
+}
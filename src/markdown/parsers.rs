@@ -1,17 +1,17 @@
 use core::str;
 use std::vec;
 
-use chrono::{NaiveDate, Weekday};
+use chrono::{Datelike, Days, NaiveDate, Weekday};
 
 use email_address_parser::EmailAddress;
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, tag, take_until, take_while1},
+    bytes::complete::{is_not, tag, tag_no_case, take_until, take_while, take_while1},
     character::{
-        complete::{char, multispace1},
+        complete::{char, digit1, multispace1},
         is_newline,
     },
-    combinator::{map, map_parser},
+    combinator::{consumed, map, map_parser, peek, recognize, verify},
     multi::many1_count,
     sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
     IResult,
@@ -20,7 +20,7 @@ use nom::{
 use urlocator::{UrlLocation, UrlLocator};
 
 use super::errors::MarkdownParseError;
-use crate::models::{TaskStatus, Token};
+use crate::models::{Due, TaskStatus, Token};
 
 /// Take a string delimited by some characters, but track how many times the delimiter pairs
 /// themselves also appear in the string.
@@ -36,7 +36,7 @@ fn take_until_unbalanced(
             index += n;
             let mut it = i[index..].chars();
             match it.next().unwrap_or_default() {
-                c if c == '\\' => {
+                '\\' => {
                     // Skip the escape char `\`.
                     index += '\\'.len_utf8();
                     // Skip also the following char.
@@ -76,10 +76,7 @@ fn nonws_char(c: char) -> bool {
 }
 
 fn is_word_finish_char(c: char) -> bool {
-    match c {
-        ',' | '.' | ':' | ';' | ')' | ']' => true,
-        _ => false,
-    }
+    matches!(c, ',' | '.' | ':' | ';' | ')' | ']')
 }
 
 fn word(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
@@ -89,13 +86,13 @@ fn word(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
 fn fenced<'a>(
     start: &'a str,
     end: &'a str,
-) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, MarkdownParseError<&str>> {
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, MarkdownParseError<&'a str>> {
     map(tuple((tag(start), take_until(end), tag(end))), |x| x.1)
 }
 
 fn style<'a>(
     boundary: &'a str,
-) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<Token<'a>>, MarkdownParseError<&str>> {
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<Token<'a>>, MarkdownParseError<&'a str>> {
     map_parser(fenced(boundary, boundary), parse_inline)
 }
 
@@ -110,6 +107,11 @@ fn markdown_link(input: &str) -> IResult<&str, (&str, &str), MarkdownParseError<
     )(input)
 }
 
+/// Parse `![[Note#Heading]]`
+fn embed(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
+    preceded(char('!'), link)(input)
+}
+
 fn link_or_word(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
     alt((link, word))(input)
 }
@@ -131,19 +133,28 @@ fn block_ref(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
     fenced("((", "))")(input)
 }
 
-fn bold(input: &str) -> IResult<&str, Vec<Token>, MarkdownParseError<&str>> {
+/// Parses a footnote reference (`[^1]` or `[^note]`) into its label
+fn footnote_ref(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
+    delimited(
+        tag("[^"),
+        take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_'),
+        char(']'),
+    )(input)
+}
+
+fn bold(input: &str) -> IResult<&str, Vec<Token<'_>>, MarkdownParseError<&str>> {
     style("**")(input)
 }
 
-fn italic(input: &str) -> IResult<&str, Vec<Token>, MarkdownParseError<&str>> {
+fn italic(input: &str) -> IResult<&str, Vec<Token<'_>>, MarkdownParseError<&str>> {
     style("*")(input)
 }
 
-fn strike(input: &str) -> IResult<&str, Vec<Token>, MarkdownParseError<&str>> {
+fn strike(input: &str) -> IResult<&str, Vec<Token<'_>>, MarkdownParseError<&str>> {
     style("~~")(input)
 }
 
-fn highlight(input: &str) -> IResult<&str, Vec<Token>, MarkdownParseError<&str>> {
+fn highlight(input: &str) -> IResult<&str, Vec<Token<'_>>, MarkdownParseError<&str>> {
     style("^^")(input)
 }
 
@@ -192,6 +203,14 @@ fn tag_token(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
     preceded(char('@'), word)(input)
 }
 
+/// Parses a snooze annotation of a task: `SNOOZED UNTIL 2024-06-10` or `@snooze(2024-06-10)`
+fn snooze(input: &str) -> IResult<&str, (&str, NaiveDate), MarkdownParseError<&str>> {
+    consumed(alt((
+        preceded(tag("SNOOZED UNTIL "), date),
+        delimited(tag("@snooze("), date, char(')')),
+    )))(input)
+}
+
 fn raw_url(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
     let mut locator = UrlLocator::new();
     let mut end = 0;
@@ -212,7 +231,7 @@ fn raw_url(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
     }
 }
 
-fn directive(input: &str) -> IResult<&str, Token, MarkdownParseError<&str>> {
+fn directive(input: &str) -> IResult<&str, Token<'_>, MarkdownParseError<&str>> {
     alt((
         map(markdown_link, |(title, url)| {
             if url.starts_with('#') {
@@ -224,6 +243,7 @@ fn directive(input: &str) -> IResult<&str, Token, MarkdownParseError<&str>> {
                 Token::MarkdownExternalLink { title, url }
             }
         }),
+        map(snooze, |(markup, date)| Token::Snooze { markup, date }),
         map(date, Token::Date),
         map(email, Token::Email),
         map(tag_token, Token::Tag),
@@ -231,6 +251,8 @@ fn directive(input: &str) -> IResult<&str, Token, MarkdownParseError<&str>> {
         map(single_backtick, Token::SingleBacktick),
         map(hashtag, Token::Hashtag),
         map(block_ref, Token::BlockRef),
+        map(footnote_ref, Token::FootnoteRef),
+        map(embed, Token::Embed),
         map(image, |(alt, url)| Token::Image { alt, url }),
         map(link, Token::Link),
         map(bold, Token::Bold),
@@ -243,7 +265,7 @@ fn directive(input: &str) -> IResult<&str, Token, MarkdownParseError<&str>> {
 }
 
 /// Parse a line of text, counting anything that doesn't match a directive as plain text.
-pub(super) fn parse_inline(input: &str) -> IResult<&str, Vec<Token>, MarkdownParseError<&str>> {
+pub(super) fn parse_inline(input: &str) -> IResult<&str, Vec<Token<'_>>, MarkdownParseError<&str>> {
     let mut output = Vec::with_capacity(4);
 
     let mut current_input = input;
@@ -283,38 +305,104 @@ pub(super) fn parse_inline(input: &str) -> IResult<&str, Vec<Token>, MarkdownPar
     Ok(("", output))
 }
 
-/// Parses `Name:: Arbitrary [[text]]`
+/// Parses the definition of a footnote: `[^1]: Arbitrary [[text]]`
+pub(super) fn footnote_definition(
+    input: &str,
+) -> IResult<&str, (&str, Vec<Token<'_>>), MarkdownParseError<&str>> {
+    separated_pair(footnote_ref, pair(char(':'), char(' ')), parse_inline)(input)
+}
+
+/// Parses `Name:: Arbitrary [[text]]` into the name, the separator and the value
 pub(super) fn attribute(
     input: &str,
-) -> IResult<&str, (&str, Vec<Token>), MarkdownParseError<&str>> {
-    separated_pair(is_not(":`"), tag("::"), parse_inline)(input)
+) -> IResult<&str, (&str, &str, Vec<Token<'_>>), MarkdownParseError<&str>> {
+    tuple((is_not(":`"), tag("::"), parse_inline))(input)
 }
 
-pub(super) fn task(input: &str) -> IResult<&str, Token, MarkdownParseError<&str>> {
+/// Maximum number of words of the name of a `Name: value` metadata attribute
+const MAX_METADATA_NAME_WORDS: usize = 3;
+
+/// Parses `Name: Arbitrary [[text]]` (with a single colon, like the metadata written by
+/// other tools) into the name, the separator and the value. To tell it apart from prose
+/// containing a colon, the name has to start with a letter and consist of at most three
+/// words (letters, digits, `-` and `_`), the value must not be empty.
+pub(super) fn metadata_attribute(
+    input: &str,
+) -> IResult<&str, (&str, &str, Vec<Token<'_>>), MarkdownParseError<&str>> {
+    let is_name = |name: &str| {
+        name.starts_with(char::is_alphabetic)
+            && !name.ends_with(' ')
+            && !name.contains("  ")
+            && name.split(' ').count() <= MAX_METADATA_NAME_WORDS
+    };
+    let is_value =
+        |value: &Vec<Token>| value.iter().any(|t| !t.to_plain_string().trim().is_empty());
+    tuple((
+        verify(
+            take_while1(|c: char| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_')),
+            is_name,
+        ),
+        tag(":"),
+        preceded(peek(char(' ')), verify(parse_inline, is_value)),
+    ))(input)
+}
+
+/// Parses a task keyword (e.g. `TODO:`), in any case (`todo:`, `Todo:`) if `any_case`
+fn keyword<'a>(
+    keyword: &'static str,
+    any_case: bool,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, MarkdownParseError<&'a str>> {
+    move |input| match any_case {
+        true => tag_no_case(keyword)(input),
+        false => tag(keyword)(input),
+    }
+}
+
+/// Parses a task (`TODO: Task`), the keywords are only recognized in other cases if
+/// `any_case`. Relative deadlines (`TODO UNTIL tomorrow:`) are resolved against `due_base`.
+pub(super) fn task(
+    input: &str,
+    any_case: bool,
+    due_base: NaiveDate,
+) -> IResult<&str, Token<'_>, MarkdownParseError<&str>> {
     let (task_description, task) = terminated(
         alt((
-            map(tag("TODO:"), |_| Token::Task {
+            map(keyword("TODO:", any_case), |_| Token::Task {
                 content: vec![],
                 status: TaskStatus::Todo,
+                checkbox: None,
             }),
-            map(tag("DOING:"), |_| Token::Task {
+            map(keyword("DOING:", any_case), |_| Token::Task {
                 content: vec![],
                 status: TaskStatus::Doing,
+                checkbox: None,
             }),
-            map(tag("REVIEW:"), |_| Token::Task {
+            map(keyword("REVIEW:", any_case), |_| Token::Task {
                 content: vec![],
                 status: TaskStatus::Review,
+                checkbox: None,
             }),
-            map(tag("DONE:"), |_| Token::Task {
+            map(keyword("DONE:", any_case), |_| Token::Task {
                 content: vec![],
                 status: TaskStatus::Done,
+                checkbox: None,
             }),
-            map(tuple((tag("TODO UNTIL "), date, tag(":"))), |(_, d, _)| {
-                Token::Task {
+            map(
+                tuple((keyword("TODO UNTIL ", any_case), due(due_base), tag(":"))),
+                |(_, due, _)| Token::Task {
                     content: vec![],
-                    status: TaskStatus::TodoUntil(d),
-                }
-            }),
+                    status: TaskStatus::TodoUntil(due),
+                    checkbox: None,
+                },
+            ),
+            map(
+                delimited(keyword("WAITING(", any_case), tag_token, tag("):")),
+                |person| Token::Task {
+                    content: vec![],
+                    status: TaskStatus::Waiting(person.to_string()),
+                    checkbox: None,
+                },
+            ),
         )),
         multispace1,
     )(input)?;
@@ -322,13 +410,152 @@ pub(super) fn task(input: &str) -> IResult<&str, Token, MarkdownParseError<&str>
     match task {
         Token::Task { status, .. } => {
             let (_, content) = parse_inline(task_description)?;
-            Ok(("", Token::Task { content, status }))
+            Ok((
+                "",
+                Token::Task {
+                    content,
+                    status,
+                    checkbox: None,
+                },
+            ))
         }
         _ => unreachable!(),
     }
 }
 
-pub(super) fn heading(input: &str) -> IResult<&str, Token, MarkdownParseError<&str>> {
+/// Parses the deadline of a `TODO UNTIL` task: a date or a day relative to `base`, see
+/// [`relative_due_date`]
+fn due(base: NaiveDate) -> impl FnMut(&str) -> IResult<&str, Due, MarkdownParseError<&str>> {
+    move |input| {
+        if let Ok((i, date)) = date(input) {
+            return Ok((i, Due::from(date)));
+        }
+        let (i, expression) = take_while1(|c: char| c.is_alphanumeric() || c == '+')(input)?;
+        match relative_due_date(expression, base) {
+            Some(date) => Ok((
+                i,
+                Due {
+                    date,
+                    expression: Some(expression.to_string()),
+                },
+            )),
+            None => Err(nom::Err::Error(MarkdownParseError::InvalidISO8601Date)),
+        }
+    }
+}
+
+/// The day a relative deadline refers to: `today`, `tomorrow`, the next weekday after
+/// `base` (e.g. `friday` or `fri`) or a number of days or weeks after it (`+3d`, `+1w`),
+/// `None` for anything else
+fn relative_due_date(expression: &str, base: NaiveDate) -> Option<NaiveDate> {
+    let expression = expression.to_lowercase();
+    let days = match expression.as_str() {
+        "today" => 0,
+        "tomorrow" => 1,
+        e => match (e.parse::<Weekday>(), e.strip_prefix('+')) {
+            (Ok(weekday), _) => {
+                let days = weekday.num_days_from_monday() as i64
+                    - base.weekday().num_days_from_monday() as i64;
+                (days + 6).rem_euclid(7) + 1
+            }
+            (_, Some(_)) if e.ends_with(['d', 'w']) => day_offset(e)?,
+            _ => return None,
+        },
+    };
+    base.checked_add_days(Days::new(days as u64))
+}
+
+/// Parses a number of days (`7d`, `-3d`) or weeks (`+1w`), a missing unit means days.
+pub fn day_offset(s: &str) -> Option<i64> {
+    let (number, factor) = match s.strip_suffix('w') {
+        Some(number) => (number, 7),
+        None => (s.strip_suffix('d').unwrap_or(s), 1),
+    };
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, number.strip_prefix('+').unwrap_or(number)),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse::<i64>().ok()?.checked_mul(sign * factor)
+}
+
+/// Parses a task written as list item with a checkbox: `- [ ] Task` (todo), `- [/] Task`
+/// (doing) or `- [x] Task` (done).
+pub(super) fn checkbox_task(input: &str) -> IResult<&str, Token<'_>, MarkdownParseError<&str>> {
+    let (content_raw, (prefix, status)) = pair(
+        list_prefix,
+        terminated(
+            alt((
+                map(tag("[ ]"), |_| TaskStatus::Todo),
+                map(tag("[/]"), |_| TaskStatus::Doing),
+                map(alt((tag("[x]"), tag("[X]"))), |_| TaskStatus::Done),
+            )),
+            char(' '),
+        ),
+    )(input)?;
+
+    let (i, content) = parse_inline(content_raw)?;
+    Ok((
+        i,
+        Token::Task {
+            content,
+            status,
+            checkbox: Some(prefix),
+        },
+    ))
+}
+
+/// Parses a task written as list item with a keyword (`- TODO: Task`) into an empty list
+/// item followed by the task, so the task is found like any other one.
+pub(super) fn list_task(
+    input: &str,
+    any_case: bool,
+    due_base: NaiveDate,
+) -> IResult<&str, (Token<'_>, Token<'_>), MarkdownParseError<&str>> {
+    let (task_raw, prefix) = list_prefix(input)?;
+    let (i, task) = task(task_raw, any_case, due_base)?;
+    let item = Token::ListItem {
+        prefix,
+        level: 0,
+        content: vec![],
+    };
+    Ok((i, (item, task)))
+}
+
+/// Parses a list item (`- `, `* `, `+ `, `1. ` or `1) `, possibly indented).
+///
+/// The nesting level depends on the preceding lines and is therefore set by the tokenizer.
+pub(super) fn list_item(input: &str) -> IResult<&str, Token<'_>, MarkdownParseError<&str>> {
+    let (content_raw, prefix) = list_prefix(input)?;
+
+    let (i, content) = parse_inline(content_raw)?;
+    Ok((
+        i,
+        Token::ListItem {
+            prefix,
+            level: 0,
+            content,
+        },
+    ))
+}
+
+/// Recognizes the (possibly indented) prefix of a list item including the space after it
+fn list_prefix(input: &str) -> IResult<&str, &str, MarkdownParseError<&str>> {
+    recognize(tuple((
+        take_while(|c| c == ' ' || c == '\t'),
+        alt((
+            tag("-"),
+            tag("*"),
+            tag("+"),
+            recognize(pair(digit1, alt((tag("."), tag(")"))))),
+        )),
+        char(' '),
+    )))(input)
+}
+
+pub(super) fn heading(input: &str) -> IResult<&str, Token<'_>, MarkdownParseError<&str>> {
     let (content_raw, hashtag_count) = terminated(many1_count(tag("#")), multispace1)(input)?;
 
     let (i, content) = parse_inline(content_raw)?;
@@ -337,6 +564,8 @@ pub(super) fn heading(input: &str) -> IResult<&str, Token, MarkdownParseError<&s
         2 => Ok((i, Token::HeadingH2(content))),
         3 => Ok((i, Token::HeadingH3(content))),
         4 => Ok((i, Token::HeadingH4(content))),
+        5 => Ok((i, Token::HeadingH5(content))),
+        6 => Ok((i, Token::HeadingH6(content))),
         _ => Err(nom::Err::Error(MarkdownParseError::InvalidMarkdownHeading)),
     }
 }
@@ -367,6 +596,11 @@ mod tests {
     use chrono::NaiveDate;
     use pretty_assertions::assert_eq;
 
+    /// The day relative deadlines are resolved against (a Wednesday)
+    fn base() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 10, 11).unwrap()
+    }
+
     #[test]
     fn test_parse_inline_markdown_internal_link() {
         let (remaining_input, tokens) = parse_inline("[link123](#section123)").unwrap();
@@ -416,6 +650,34 @@ mod tests {
         assert_eq!(remaining_input, "");
     }
 
+    #[test]
+    fn test_parse_inline_snooze() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let (_, tokens) = parse_inline("Pay SNOOZED UNTIL 2024-06-10").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text("Pay "),
+                Token::Snooze {
+                    markup: "SNOOZED UNTIL 2024-06-10",
+                    date
+                }
+            ]
+        );
+        let (_, tokens) = parse_inline("@snooze(2024-06-10) @snooze").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Snooze {
+                    markup: "@snooze(2024-06-10)",
+                    date
+                },
+                Token::Text(" "),
+                Token::Tag("snooze")
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_inline_email() {
         let (remaining_input, tokens) = parse_inline("mathias.aschwanden@gmail.com").unwrap();
@@ -461,6 +723,20 @@ mod tests {
         assert_eq!(remaining_input, "");
     }
 
+    #[test]
+    fn test_parse_inline_footnote_ref() {
+        let (remaining_input, tokens) = parse_inline("Claim[^1] [^ x]").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text("Claim"),
+                Token::FootnoteRef("1"),
+                Token::Text(" [^ x]")
+            ]
+        );
+        assert_eq!(remaining_input, "");
+    }
+
     #[test]
     fn test_parse_inline_image() {
         let (remaining_input, tokens) = parse_inline("![alt](url)").unwrap();
@@ -481,6 +757,16 @@ mod tests {
         assert_eq!(remaining_input, "");
     }
 
+    #[test]
+    fn test_parse_inline_embed() {
+        let (remaining_input, tokens) = parse_inline("see ![[Note#Heading]]").unwrap();
+        assert_eq!(
+            tokens,
+            vec![Token::Text("see "), Token::Embed("Note#Heading")]
+        );
+        assert_eq!(remaining_input, "");
+    }
+
     #[test]
     fn test_parse_inline_bold() {
         let (remaining_input, tokens) = parse_inline("**Haha**").unwrap();
@@ -531,9 +817,10 @@ mod tests {
 
     #[test]
     fn test_attribute() {
-        let (remaining_input, (attribute_name, tokens)) =
+        let (remaining_input, (attribute_name, separator, tokens)) =
             attribute("attr123:: https://google.com").unwrap();
         assert_eq!(attribute_name, "attr123",);
+        assert_eq!(separator, "::");
         assert_eq!(
             tokens,
             vec![Token::Text(" "), Token::RawHyperlink("https://google.com")]
@@ -541,14 +828,42 @@ mod tests {
         assert_eq!(remaining_input, "");
     }
 
+    #[test]
+    fn test_metadata_attribute() {
+        let (remaining_input, (name, separator, tokens)) =
+            metadata_attribute("Created at: 2024-05-01 by @roger").unwrap();
+        assert_eq!((name, separator, remaining_input), ("Created at", ":", ""));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text(" "),
+                Token::Date(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()),
+                Token::Text(" by "),
+                Token::Tag("roger"),
+            ]
+        );
+        for prose in [
+            "Today I met Roger: he told me",
+            "Source:",
+            "Source:  ",
+            "Source:blog",
+            "10:30 meeting",
+            "https://example.org",
+            "Note : text",
+        ] {
+            assert!(metadata_attribute(prose).is_err(), "{}", prose);
+        }
+    }
+
     #[test]
     fn test_task_todo() {
-        let (remaining_input, tokens) = task("TODO: here comes the task").unwrap();
+        let (remaining_input, tokens) = task("TODO: here comes the task", false, base()).unwrap();
         assert_eq!(
             tokens,
             Token::Task {
                 content: vec![Token::Text("here comes the task")],
-                status: TaskStatus::Todo
+                status: TaskStatus::Todo,
+                checkbox: None,
             },
         );
         assert_eq!(remaining_input, "");
@@ -556,25 +871,69 @@ mod tests {
 
     #[test]
     fn test_task_todo_until() {
-        let (remaining_input, tokens) = task("TODO UNTIL 2023-10-10: here comes the task").unwrap();
+        let (remaining_input, tokens) =
+            task("TODO UNTIL 2023-10-10: here comes the task", false, base()).unwrap();
         assert_eq!(
             tokens,
             Token::Task {
                 content: vec![Token::Text("here comes the task")],
-                status: TaskStatus::TodoUntil(NaiveDate::from_ymd_opt(2023, 10, 10).unwrap())
+                status: TaskStatus::TodoUntil(
+                    NaiveDate::from_ymd_opt(2023, 10, 10).unwrap().into()
+                ),
+                checkbox: None,
             },
         );
         assert_eq!(remaining_input, "");
     }
 
+    #[test]
+    fn test_task_todo_until_relative() {
+        let due = |expression: &str| match task(
+            &format!("TODO UNTIL {}: here comes the task", expression),
+            false,
+            base(),
+        ) {
+            Ok((_, Token::Task { status, .. })) => Some(status),
+            _ => None,
+        };
+        let date = |d| NaiveDate::from_ymd_opt(2023, 10, d).unwrap();
+        for (expression, day) in [
+            ("today", 11),
+            ("tomorrow", 12),
+            ("friday", 13),
+            ("Fri", 13),
+            ("wednesday", 18),
+            ("+3d", 14),
+            ("+1w", 18),
+        ] {
+            assert_eq!(
+                due(expression),
+                Some(TaskStatus::TodoUntil(Due {
+                    date: date(day),
+                    expression: Some(expression.to_string()),
+                })),
+            );
+        }
+        for expression in ["someday", "+3", "+-3d", "3d"] {
+            assert_eq!(due(expression), None);
+        }
+
+        let (_, tokens) = task("TODO UNTIL tomorrow: here comes the task", false, base()).unwrap();
+        assert_eq!(
+            tokens.to_markdown_string(),
+            "TODO UNTIL tomorrow: here comes the task"
+        );
+    }
+
     #[test]
     fn test_task_doing() {
-        let (remaining_input, tokens) = task("DOING: here comes the task").unwrap();
+        let (remaining_input, tokens) = task("DOING: here comes the task", false, base()).unwrap();
         assert_eq!(
             tokens,
             Token::Task {
                 content: vec![Token::Text("here comes the task")],
-                status: TaskStatus::Doing
+                status: TaskStatus::Doing,
+                checkbox: None,
             },
         );
         assert_eq!(remaining_input, "");
@@ -582,30 +941,110 @@ mod tests {
 
     #[test]
     fn test_task_review() {
-        let (remaining_input, tokens) = task("REVIEW: here comes the task").unwrap();
+        let (remaining_input, tokens) = task("REVIEW: here comes the task", false, base()).unwrap();
+        assert_eq!(
+            tokens,
+            Token::Task {
+                content: vec![Token::Text("here comes the task")],
+                status: TaskStatus::Review,
+                checkbox: None,
+            },
+        );
+        assert_eq!(remaining_input, "");
+    }
+
+    #[test]
+    fn test_task_waiting() {
+        let (remaining_input, tokens) =
+            task("WAITING(@roger): here comes the task", false, base()).unwrap();
         assert_eq!(
             tokens,
             Token::Task {
                 content: vec![Token::Text("here comes the task")],
-                status: TaskStatus::Review
+                status: TaskStatus::Waiting("roger".to_string()),
+                checkbox: None,
             },
         );
         assert_eq!(remaining_input, "");
+        assert_eq!(
+            tokens.to_markdown_string(),
+            "WAITING(@roger): here comes the task"
+        );
+        assert!(task("WAITING(roger): here comes the task", false, base()).is_err());
     }
 
     #[test]
     fn test_task_done() {
-        let (remaining_input, tokens) = task("DONE: here comes the task").unwrap();
+        let (remaining_input, tokens) = task("DONE: here comes the task", false, base()).unwrap();
         assert_eq!(
             tokens,
             Token::Task {
                 content: vec![Token::Text("here comes the task")],
-                status: TaskStatus::Done
+                status: TaskStatus::Done,
+                checkbox: None,
             },
         );
         assert_eq!(remaining_input, "");
     }
 
+    #[test]
+    fn test_checkbox_task() {
+        let checkbox = |status, prefix| Token::Task {
+            content: vec![Token::Text("here comes the task")],
+            status,
+            checkbox: Some(prefix),
+        };
+        for (input, expected) in [
+            (
+                "- [ ] here comes the task",
+                checkbox(TaskStatus::Todo, "- "),
+            ),
+            (
+                "  * [/] here comes the task",
+                checkbox(TaskStatus::Doing, "  * "),
+            ),
+            (
+                "1. [x] here comes the task",
+                checkbox(TaskStatus::Done, "1. "),
+            ),
+            (
+                "- [X] here comes the task",
+                checkbox(TaskStatus::Done, "- "),
+            ),
+        ] {
+            assert_eq!(checkbox_task(input).unwrap(), ("", expected));
+        }
+        assert!(checkbox_task("- [?] here comes the task").is_err());
+        assert!(checkbox_task("[ ] here comes the task").is_err());
+    }
+
+    #[test]
+    fn test_list_item() {
+        let (remaining_input, token) = list_item("  - Meeting @work").unwrap();
+        assert_eq!(
+            token,
+            Token::ListItem {
+                prefix: "  - ",
+                level: 0,
+                content: vec![Token::Text("Meeting "), Token::Tag("work")],
+            }
+        );
+        assert_eq!(remaining_input, "");
+
+        let (_, token) = list_item("12. Twelfth").unwrap();
+        assert_eq!(
+            token,
+            Token::ListItem {
+                prefix: "12. ",
+                level: 0,
+                content: vec![Token::Text("Twelfth")],
+            }
+        );
+
+        assert!(list_item("-5 degrees").is_err());
+        assert!(list_item("**bold**").is_err());
+    }
+
     #[test]
     fn test_heading_h1() {
         let (remaining_input, tokens) = heading("# Titel").unwrap();
@@ -634,9 +1073,23 @@ mod tests {
         assert_eq!(remaining_input, "");
     }
 
+    #[test]
+    fn test_heading_h5() {
+        let (remaining_input, tokens) = heading("##### Titel").unwrap();
+        assert_eq!(tokens, Token::HeadingH5(vec![Token::Text("Titel")]),);
+        assert_eq!(remaining_input, "");
+    }
+
+    #[test]
+    fn test_heading_h6() {
+        let (remaining_input, tokens) = heading("###### Titel").unwrap();
+        assert_eq!(tokens, Token::HeadingH6(vec![Token::Text("Titel")]),);
+        assert_eq!(remaining_input, "");
+    }
+
     #[test]
     fn test_heading_invalid() {
-        let res = heading("##### Titel");
+        let res = heading("####### Titel");
         assert!(res.is_err());
     }
 
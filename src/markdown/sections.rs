@@ -1,26 +1,109 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, fs};
 
-use crate::models::{MDPError, Section, SectionBuilder, SectionType, Token, TokenType};
+use crate::models::{MDPError, Origin, Section, SectionBuilder, SectionType, Token, TokenType};
 
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate};
+use regex::Regex;
+use serde::Deserialize;
 use std::vec;
 
+/// Default for [`MDPSectionBuilder::max_recursion_depth`]
+pub const DEFAULT_MAX_RECURSION_DEPTH: usize = 10;
+
+/// How top level sections without a date in their title are dated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DatePolicy {
+    /// Sections without a date are an error
+    #[default]
+    Require,
+    /// The day the file was last modified
+    FileMtime,
+    /// The first date (YYYY-MM-DD) in the file name, e.g. `2024-05-20 Meetup.md`
+    Filename,
+    /// The sections are kept without a date
+    None,
+}
+
+/// Which tokens are the tags of the sections, e.g. `#tag` for notes written in Obsidian
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagStyle {
+    /// `@tag`, while `#tag` is a hashtag (the mdp style)
+    #[default]
+    At,
+    /// `#tag` (the Obsidian style), `@tag` is no tag
+    Hash,
+    /// `@tag` as well as `#tag`
+    Both,
+}
+
+impl TagStyle {
+    /// The name of the tag if the token is a tag in this style
+    pub fn tag<'a>(&self, token: &Token<'a>) -> Option<&'a str> {
+        match (self, token) {
+            (TagStyle::At | TagStyle::Both, Token::Tag(s))
+            | (TagStyle::Hash | TagStyle::Both, Token::Hashtag(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The names of the tags in this style within the token, including the ones nested in
+    /// list items, tasks, tables and styled text, but not the ones of headings (which
+    /// belong to the title of their section)
+    pub fn tags<'a>(&self, token: &Token<'a>) -> Vec<&'a str> {
+        match self.tag(token) {
+            Some(tag) => vec![tag],
+            None if token.is_heading() => vec![],
+            None => token.children().iter().flat_map(|t| self.tags(t)).collect(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
-pub struct MDPSectionBuilder {}
+pub struct MDPSectionBuilder {
+    /// Maximum depth of the section tree. Headings nested deeper are not hierarchized
+    /// anymore (a warning is logged if this happens).
+    pub max_recursion_depth: usize,
+    /// How H1 sections without a date are dated
+    pub date_policy: DatePolicy,
+    /// Which tokens are the tags of a section
+    pub tag_style: TagStyle,
+}
+
+impl Default for MDPSectionBuilder {
+    fn default() -> Self {
+        Self {
+            max_recursion_depth: DEFAULT_MAX_RECURSION_DEPTH,
+            date_policy: DatePolicy::default(),
+            tag_style: TagStyle::default(),
+        }
+    }
+}
 
 impl SectionBuilder for MDPSectionBuilder {
-    fn sections_from_tokens<'a>(
+    fn sections_from_tokens_with_origins<'a>(
         &self,
-        tokens: Vec<Token<'a>>,
+        tokens: Vec<(Token<'a>, Origin)>,
     ) -> Result<Vec<Section<'a>>, MDPError> {
-        let hierarchized_tokens = hierarchize_tokens_using_headings(tokens);
-        sections_from_hierarchized_tokens(hierarchized_tokens, None)
+        let hierarchized_tokens =
+            hierarchize_tokens_using_headings(tokens, self.max_recursion_depth);
+        sections_from_hierarchized_tokens(
+            hierarchized_tokens,
+            None,
+            self.date_policy,
+            self.tag_style,
+        )
     }
 }
 
+/// The first and the last day of a section (the latter only if it spans several days)
+pub(super) type SectionDates = (Option<NaiveDate>, Option<NaiveDate>);
+
 fn sections_from_hierarchized_tokens(
     hierachical_tokens: Vec<HierarchicalToken>,
-    parent_date: Option<NaiveDate>,
+    parent_dates: Option<SectionDates>,
+    date_policy: DatePolicy,
+    tag_style: TagStyle,
 ) -> Result<Vec<Section>, MDPError> {
     let mut sections: Vec<Section> = vec![];
 
@@ -30,6 +113,8 @@ fn sections_from_hierarchized_tokens(
             Token::HeadingH2(t) => (SectionType::H2, t),
             Token::HeadingH3(t) => (SectionType::H3, t),
             Token::HeadingH4(t) => (SectionType::H4, t),
+            Token::HeadingH5(t) => (SectionType::H5, t),
+            Token::HeadingH6(t) => (SectionType::H6, t),
             _ => continue,
         };
         let title_element = token.token.clone();
@@ -37,38 +122,13 @@ fn sections_from_hierarchized_tokens(
         let tags = token
             .children
             .iter()
-            .filter_map(|t| match t.token {
-                Token::Tag(s) => Some(s.to_owned()),
-                _ => None,
-            })
+            .flat_map(|t| tag_style.tags(&t.token))
+            .map(str::to_owned)
             .collect();
 
-        let date = if let Some(d) = parent_date {
-            d
-        } else {
-            let dates = title_elements
-                .iter()
-                .filter_map(|t| match t {
-                    Token::Date(d) => Some(d.to_owned()),
-                    _ => None,
-                })
-                .collect::<Vec<NaiveDate>>();
-
-            match dates.len() {
-                0 => {
-                    return Err(MDPError::MDPSyntaxError(format!(
-                        "The section title {} doesn't contain a date.",
-                        title_element.to_markdown_string()
-                    )))
-                }
-                1 => dates.first().unwrap().to_owned(),
-                _ => {
-                    return Err(MDPError::MDPSyntaxError(format!(
-                        "The section title {} does contain more than one date.",
-                        title_element.to_markdown_string()
-                    )))
-                }
-            }
+        let (date, date_end) = match parent_dates {
+            Some(dates) => dates,
+            None => section_dates(&title_element, title_elements, &token.origin, date_policy)?,
         };
 
         let mut content = vec![];
@@ -77,32 +137,136 @@ fn sections_from_hierarchized_tokens(
                 Token::HeadingH1(_)
                 | Token::HeadingH2(_)
                 | Token::HeadingH3(_)
-                | Token::HeadingH4(_) => break,
+                | Token::HeadingH4(_)
+                | Token::HeadingH5(_)
+                | Token::HeadingH6(_) => break,
                 Token::HRule | Token::Blank => continue,
                 _ => content.push(t.token.to_owned()),
             }
         }
 
-        let subsections = sections_from_hierarchized_tokens(token.children, Some(date))?;
+        let subsections = sections_from_hierarchized_tokens(
+            token.children,
+            Some((date, date_end)),
+            date_policy,
+            tag_style,
+        )?;
 
         sections.push(Section {
             section_type,
             title: token.token,
             tags,
             date,
+            date_end,
             content,
             subsections,
+            origin: token.origin,
         });
     }
 
     Ok(sections)
 }
 
-fn hierarchize_tokens_using_headings(tokens: Vec<Token>) -> Vec<HierarchicalToken> {
-    let mut hierarchical_tokens = tokens
+/// The dates of a top level section: from its title if it contains a date, otherwise as
+/// given by the date policy
+pub(super) fn section_dates(
+    title: &Token,
+    title_elements: &[Token],
+    origin: &Origin,
+    date_policy: DatePolicy,
+) -> Result<SectionDates, MDPError> {
+    let has_date = title_elements.iter().any(|t| matches!(t, Token::Date(_)));
+    if has_date || date_policy == DatePolicy::Require {
+        let (date, date_end) = dates_from_title(title, title_elements)?;
+        return Ok((Some(date), date_end));
+    }
+    let date = match date_policy {
+        DatePolicy::Require | DatePolicy::None => return Ok((None, None)),
+        DatePolicy::FileMtime => origin.file.as_ref().and_then(|f| {
+            let modified = fs::metadata(f).and_then(|m| m.modified()).ok()?;
+            Some(DateTime::<Local>::from(modified).date_naive())
+        }),
+        DatePolicy::Filename => origin
+            .file
+            .as_ref()
+            .and_then(|f| date_from_file_name(&f.file_stem()?.to_string_lossy())),
+    };
+    match date {
+        Some(date) => Ok((Some(date), None)),
+        None => Err(MDPError::MDPSyntaxError(format!(
+            "The section title {} doesn't contain a date and no date could be taken from \
+             its file ({}).",
+            title.to_markdown_string(),
+            origin
+        ))),
+    }
+}
+
+/// The first date (YYYY-MM-DD) in a file name
+fn date_from_file_name(name: &str) -> Option<NaiveDate> {
+    let re = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
+    let date = re
+        .find_iter(name)
+        .find_map(|m| NaiveDate::parse_from_str(m.as_str(), "%Y-%m-%d").ok());
+    date
+}
+
+/// Words and dashes separating the first and the last day of a range
+const DATE_RANGE_SEPARATORS: &[&str] = &["-", "–", "—", "..", "to", "bis"];
+
+/// Returns the date contained in the title of a top level section, and the last day if the
+/// title contains a range of dates (e.g. `2023-10-10 - 2023-10-12`).
+pub fn dates_from_title(
+    title: &Token,
+    title_elements: &[Token],
+) -> Result<(NaiveDate, Option<NaiveDate>), MDPError> {
+    let dates = title_elements
         .iter()
-        .cloned()
-        .map(HierarchicalToken::from_token)
+        .enumerate()
+        .filter_map(|(i, t)| match t {
+            Token::Date(d) => Some((i, d.to_owned())),
+            _ => None,
+        })
+        .collect::<Vec<(usize, NaiveDate)>>();
+
+    let error = |details: &str| {
+        Err(MDPError::MDPSyntaxError(format!(
+            "The section title {} {}",
+            title.to_markdown_string(),
+            details
+        )))
+    };
+    match dates[..] {
+        [] => error("doesn't contain a date."),
+        [(_, date)] => Ok((date, None)),
+        [(i, first), (j, last)] if is_date_range_separator(&title_elements[i + 1..j]) => {
+            match first <= last {
+                true => Ok((first, Some(last))),
+                false => error("contains a range of dates ending before it starts."),
+            }
+        }
+        _ => error(
+            "does contain more than one date (write a range of dates like \
+             `2023-10-10 - 2023-10-12`).",
+        ),
+    }
+}
+
+/// Whether the tokens between two dates make them a range
+fn is_date_range_separator(tokens: &[Token]) -> bool {
+    match tokens {
+        [Token::Text(text)] => DATE_RANGE_SEPARATORS.contains(&text.trim()),
+        _ => false,
+    }
+}
+
+fn hierarchize_tokens_using_headings(
+    tokens: Vec<(Token, Origin)>,
+    max_recursion_depth: usize,
+) -> Vec<HierarchicalToken> {
+    let mut hierarchical_tokens = tokens
+        .into_iter()
+        .map(|(token, origin)| HierarchicalToken::from_token(token, origin))
         .collect::<Vec<HierarchicalToken>>();
 
     let hierarchy = TokenHierarchy::from_token_types(vec![
@@ -110,9 +274,12 @@ fn hierarchize_tokens_using_headings(tokens: Vec<Token>) -> Vec<HierarchicalToke
         TokenType::HeadingH2,
         TokenType::HeadingH3,
         TokenType::HeadingH4,
+        TokenType::HeadingH5,
+        TokenType::HeadingH6,
     ]);
 
-    let mut status = HierarchizeStatus::new(hierarchy.levels.len(), 10);
+    let mut status = HierarchizeStatus::new(hierarchy.levels.len(), max_recursion_depth);
+    let mut unnested = vec![];
 
     loop {
         hierarchical_tokens = hierarchize_recursive_one_hierarchy_level(
@@ -120,12 +287,23 @@ fn hierarchize_tokens_using_headings(tokens: Vec<Token>) -> Vec<HierarchicalToke
             hierarchical_tokens,
             status.clone(),
             status.is_hierarchy_root(),
+            &mut unnested,
         );
         status = match status.one_hierarchy_level_deeper() {
             Some(x) => x,
             None => break,
         };
     }
+
+    for h in unnested {
+        log::warn!(
+            "Maximum section depth ({}) reached: The heading '{}' ({}) and its \
+            content are not nested correctly.",
+            max_recursion_depth,
+            h.token.to_markdown_string(),
+            h.origin,
+        );
+    }
     hierarchical_tokens
 }
 
@@ -134,6 +312,7 @@ fn hierarchize_recursive_one_hierarchy_level<'a>(
     hierachical_tokens: Vec<HierarchicalToken<'a>>,
     status: HierarchizeStatus,
     insert_blank_root_token: bool,
+    unnested: &mut Vec<HierarchicalToken<'a>>,
 ) -> Vec<HierarchicalToken<'a>> {
     if hierachical_tokens.is_empty() {
         return vec![];
@@ -159,6 +338,15 @@ fn hierarchize_recursive_one_hierarchy_level<'a>(
         let next_status = match status.one_recursion_level_deeper() {
             Some(x) => x,
             None => {
+                if let Some(h) = t
+                    .children
+                    .iter()
+                    .find(|c| hierarchy.position(&c.token.token_type()) != usize::MAX)
+                {
+                    if !unnested.iter().any(|u| u.origin == h.origin) {
+                        unnested.push(h.to_owned());
+                    }
+                }
                 final_result.push(t.to_owned());
                 continue;
             }
@@ -168,6 +356,7 @@ fn hierarchize_recursive_one_hierarchy_level<'a>(
             t.children.clone(),
             next_status,
             false,
+            unnested,
         );
         final_result.push(t.to_owned());
     }
@@ -208,10 +397,12 @@ fn hierarchize_one_group<'a>(
                 root_tokens.push(token);
                 root_tokens.extend(higher_hierarchy_tokens);
             } else if insert_blank_root_token {
+                let origin = token.origin.clone();
                 let mut children = vec![token];
                 children.extend(tokens);
                 let fake_root = HierarchicalToken {
                     token: Token::Blank,
+                    origin,
                     children,
                 };
                 root_tokens.push(fake_root);
@@ -306,13 +497,15 @@ impl HierarchizeStatus {
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct HierarchicalToken<'a> {
     token: Token<'a>,
+    origin: Origin,
     children: Vec<HierarchicalToken<'a>>,
 }
 
 impl<'a> HierarchicalToken<'a> {
-    fn from_token(token: Token<'a>) -> Self {
+    fn from_token(token: Token<'a>, origin: Origin) -> Self {
         Self {
             token,
+            origin,
             children: vec![],
         }
     }
@@ -352,13 +545,16 @@ mod tests {
     use chrono::NaiveDate;
     use pretty_assertions::assert_eq;
 
-    use crate::models::TaskStatus;
+    use crate::{
+        markdown::MDPMarkdownTokenizer,
+        models::{MarkdownTokenizer, TaskStatus},
+    };
 
     use super::*;
 
     #[test]
     fn test_mdp_section_builder() -> Result<()> {
-        let mdp_section_builder = MDPSectionBuilder {};
+        let mdp_section_builder = MDPSectionBuilder::default();
 
         let tokens = vec![
             Token::Blank,
@@ -392,6 +588,7 @@ mod tests {
             Token::Task {
                 content: vec![Token::Text("Clean room")],
                 status: TaskStatus::Done,
+                checkbox: None,
             },
             Token::Newline,
             Token::Blank,
@@ -421,6 +618,7 @@ mod tests {
             Token::Task {
                 content: vec![Token::Text("Inform roger about the state of the project")],
                 status: TaskStatus::Todo,
+                checkbox: None,
             },
             Token::Newline,
             Token::Blank,
@@ -433,7 +631,8 @@ mod tests {
                 )]),
                 tags: vec![],
                 content: vec![Token::Newline, Token::Newline],
-                date: NaiveDate::from_ymd_opt(2022, 11, 2).unwrap(),
+                date: NaiveDate::from_ymd_opt(2022, 11, 2),
+                date_end: None,
                 section_type: SectionType::H1,
                 subsections: vec![
                     Section {
@@ -449,9 +648,11 @@ mod tests {
                             Token::Newline,
                             Token::Newline,
                         ],
-                        date: NaiveDate::from_ymd_opt(2022, 11, 2).unwrap(),
+                        date: NaiveDate::from_ymd_opt(2022, 11, 2),
+                        date_end: None,
                         section_type: SectionType::H2,
                         subsections: vec![],
+                        origin: Origin::new(None, 4, 1),
                     },
                     Section {
                         title: Token::HeadingH2(vec![Token::Text("Freetime")]),
@@ -465,17 +666,21 @@ mod tests {
                             Token::Task {
                                 content: vec![Token::Text("Clean room")],
                                 status: TaskStatus::Done,
+                                checkbox: None,
                             },
                             Token::Newline,
                             Token::Newline,
                             Token::Newline,
                             Token::Newline,
                         ],
-                        date: NaiveDate::from_ymd_opt(2022, 11, 2).unwrap(),
+                        date: NaiveDate::from_ymd_opt(2022, 11, 2),
+                        date_end: None,
                         section_type: SectionType::H2,
                         subsections: vec![],
+                        origin: Origin::new(None, 10, 1),
                     },
                 ],
+                origin: Origin::new(None, 2, 1),
             },
             Section {
                 title: Token::HeadingH1(vec![Token::Date(
@@ -483,7 +688,8 @@ mod tests {
                 )]),
                 tags: vec![],
                 content: vec![Token::Newline, Token::Newline],
-                date: NaiveDate::from_ymd_opt(2022, 11, 3).unwrap(),
+                date: NaiveDate::from_ymd_opt(2022, 11, 3),
+                date_end: None,
                 section_type: SectionType::H1,
                 subsections: vec![Section {
                     title: Token::HeadingH2(vec![Token::Text("Meeting")]),
@@ -503,14 +709,18 @@ mod tests {
                                 "Inform roger about the state of the project",
                             )],
                             status: TaskStatus::Todo,
+                            checkbox: None,
                         },
                         Token::Newline,
                         Token::Newline,
                     ],
-                    date: NaiveDate::from_ymd_opt(2022, 11, 3).unwrap(),
+                    date: NaiveDate::from_ymd_opt(2022, 11, 3),
+                    date_end: None,
                     section_type: SectionType::H2,
                     subsections: vec![],
+                    origin: Origin::new(None, 20, 1),
                 }],
+                origin: Origin::new(None, 18, 1),
             },
         ];
 
@@ -520,4 +730,199 @@ mod tests {
         );
         Ok(())
     }
+
+    fn nested_document(days: u32) -> Vec<Token<'static>> {
+        let mut tokens = vec![];
+        for day in 0..days {
+            let date = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap() + chrono::Days::new(day as u64);
+            tokens.extend([
+                Token::HeadingH1(vec![Token::Date(date)]),
+                Token::Newline,
+                Token::HeadingH2(vec![Token::Text("Level 2")]),
+                Token::Newline,
+                Token::HeadingH3(vec![Token::Text("Level 3")]),
+                Token::Newline,
+                Token::HeadingH4(vec![Token::Text("Level 4")]),
+                Token::Newline,
+                Token::Text("Deepest content"),
+                Token::Newline,
+            ]);
+        }
+        tokens
+    }
+
+    fn depth(section: &Section) -> usize {
+        1 + section.subsections.iter().map(depth).max().unwrap_or(0)
+    }
+
+    #[test]
+    fn test_mdp_section_builder_deeply_nested_stress() -> Result<()> {
+        let sections = MDPSectionBuilder::default().sections_from_tokens(nested_document(500))?;
+
+        assert_eq!(sections.len(), 500);
+        for section in &sections {
+            assert_eq!(depth(section), 4);
+            let h4 = &section.subsections[0].subsections[0].subsections[0];
+            assert_eq!(h4.section_type, SectionType::H4);
+            assert!(h4.content.contains(&Token::Text("Deepest content")));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_section_builder_max_recursion_depth() -> Result<()> {
+        let builder = MDPSectionBuilder {
+            max_recursion_depth: 2,
+            ..Default::default()
+        };
+        let sections = builder.sections_from_tokens(nested_document(1))?;
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(depth(&sections[0]), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_section_builder_h5_h6() -> Result<()> {
+        let tokens = vec![
+            Token::HeadingH1(vec![Token::Date(
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )]),
+            Token::Newline,
+            Token::HeadingH5(vec![Token::Text("Level 5")]),
+            Token::Newline,
+            Token::HeadingH6(vec![Token::Text("Level 6")]),
+            Token::Newline,
+            Token::Text("Content"),
+            Token::Newline,
+            Token::HeadingH5(vec![Token::Text("Another level 5")]),
+            Token::Newline,
+        ];
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+
+        let h5s = &sections[0].subsections;
+        assert_eq!(h5s.len(), 2);
+        assert_eq!(h5s[0].section_type, SectionType::H5);
+        assert_eq!(h5s[0].subsections[0].section_type, SectionType::H6);
+        assert_eq!(
+            h5s[0].subsections[0].content,
+            vec![Token::Newline, Token::Text("Content"), Token::Newline]
+        );
+        assert_eq!(h5s[1].title_string(), "Another level 5");
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_section_builder_date_ranges() -> Result<()> {
+        let date = |d| NaiveDate::from_ymd_opt(2023, 10, d).unwrap();
+        let heading = |separator| {
+            vec![
+                Token::HeadingH1(vec![
+                    Token::Date(date(10)),
+                    Token::Text(separator),
+                    Token::Date(date(12)),
+                    Token::Text(" Vacation"),
+                ]),
+                Token::Newline,
+                Token::HeadingH2(vec![Token::Text("Hike")]),
+                Token::Newline,
+            ]
+        };
+        let builder = MDPSectionBuilder::default();
+
+        let sections = builder.sections_from_tokens(heading(" - "))?;
+        assert_eq!(sections[0].date, Some(date(10)));
+        assert_eq!(sections[0].date_end, Some(date(12)));
+        assert_eq!(sections[0].subsections[0].last_date(), Some(date(12)));
+        assert_eq!(
+            builder.sections_from_tokens(heading(" to "))?[0].date_end,
+            Some(date(12))
+        );
+        assert!(builder.sections_from_tokens(heading(" and ")).is_err());
+
+        let reversed = vec![Token::HeadingH1(vec![
+            Token::Date(date(12)),
+            Token::Text(" - "),
+            Token::Date(date(10)),
+        ])];
+        assert!(builder.sections_from_tokens(reversed).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_section_builder_tag_style() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize("# 2024-05-20\n\n@work #rust\n\n- Talk #meetup\n")?;
+        let tags = |tag_style| -> Result<Vec<String>> {
+            let builder = MDPSectionBuilder {
+                tag_style,
+                ..Default::default()
+            };
+            let sections = builder.sections_from_tokens(tokens.clone())?;
+            Ok(sections[0].tags.clone())
+        };
+
+        assert_eq!(tags(TagStyle::At)?, vec!["work"]);
+        assert_eq!(tags(TagStyle::Hash)?, vec!["rust", "meetup"]);
+        assert_eq!(tags(TagStyle::Both)?, vec!["work", "rust", "meetup"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_section_builder_list_tags() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default().tokenize(
+            "# 2024-05-20\n\n- item @b\n  - nested @c\n- [ ] task @d\n\n## Sub @e\n\n- @f\n",
+        )?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+
+        assert_eq!(sections[0].tags, vec!["b", "c", "d"]);
+        assert_eq!(sections[0].subsections[0].tags, vec!["f"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_section_builder_table_tags() -> Result<()> {
+        let tokens = MDPMarkdownTokenizer::default()
+            .tokenize("# 2024-05-20\n\n| @x | y |\n|---|---|\n| 1 | @z |\n")?;
+        let sections = MDPSectionBuilder::default().sections_from_tokens(tokens)?;
+
+        assert_eq!(sections[0].tags, vec!["x", "z"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_section_builder_date_policy() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("2024-05-20 Meetup.md");
+        let content = "# Notes\n\n## Talk\n\n# 2024-05-21\n";
+        std::fs::write(&path, content)?;
+        let sections = |date_policy| {
+            let tokens =
+                MDPMarkdownTokenizer::default().tokenize_with_origins(content, Some(&path))?;
+            MDPSectionBuilder {
+                date_policy,
+                ..Default::default()
+            }
+            .sections_from_tokens_with_origins(tokens)
+        };
+        let may_20 = NaiveDate::from_ymd_opt(2024, 5, 20);
+
+        assert!(sections(DatePolicy::Require).is_err());
+        let by_name = sections(DatePolicy::Filename)?;
+        assert_eq!(by_name[0].date, may_20);
+        assert_eq!(by_name[0].subsections[0].date, may_20);
+        assert_eq!(by_name[1].date, NaiveDate::from_ymd_opt(2024, 5, 21));
+
+        let undated = sections(DatePolicy::None)?;
+        assert_eq!(undated[0].date, None);
+        assert_eq!(undated[0].subsections[0].last_date(), None);
+        assert_eq!(undated[0].id(), "2024-05-20 Meetup.md:Notes");
+
+        let modified = DateTime::<Local>::from(std::fs::metadata(&path)?.modified()?);
+        assert_eq!(
+            sections(DatePolicy::FileMtime)?[0].date,
+            Some(modified.date_naive())
+        );
+        Ok(())
+    }
 }
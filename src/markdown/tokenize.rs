@@ -1,5 +1,6 @@
-use std::vec;
+use std::{collections::VecDeque, vec};
 
+use chrono::{Local, NaiveDate};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -10,24 +11,52 @@ use nom::{
 
 use super::{
     errors::MarkdownParseError,
-    parsers::{attribute, heading, parse_inline, task},
+    near_miss::{misspelled_task_keyword, near_miss},
+    parsers::{
+        attribute, checkbox_task, footnote_definition, heading, list_item, list_task,
+        metadata_attribute, parse_inline, task,
+    },
 };
 use crate::models::{MDPError, MarkdownTokenizer, Token};
 
-pub struct MDPMarkdownTokenizer {}
+#[derive(Clone, Debug, Default)]
+pub struct MDPMarkdownTokenizer {
+    /// Read task keywords written in any case (e.g. `todo:` or `Done:`) as tasks
+    pub any_case_task_keywords: bool,
+    /// The day relative deadlines (e.g. `TODO UNTIL tomorrow:`) outside of a dated H1
+    /// section are resolved against, the current day if `None`
+    pub today: Option<NaiveDate>,
+    /// Read `Name: value` lines (with a single colon) directly below a heading as
+    /// attributes, like the metadata of notes imported from other tools
+    pub single_colon_attributes: bool,
+}
 
 impl MarkdownTokenizer for MDPMarkdownTokenizer {
     fn tokenize<'a>(&self, markdown_string: &'a str) -> Result<Vec<Token<'a>>, MDPError> {
-        let mut errors: Vec<MDPError> = vec![];
-        let mut markdown_elements: Vec<Token> = vec![];
+        let (markdown_elements, errors) = self.tokenize_best_effort(markdown_string);
 
-        for (line_number, line) in split_into_lines(markdown_string).drain(..).enumerate() {
-            match parse_line(line).map_err(|e| e.into_mdp_error(line_number)) {
-                Ok(elements) => markdown_elements.extend(elements),
-                Err(e) => errors.push(e),
-            }
-            markdown_elements.push(Token::Newline)
+        if errors.is_empty() {
+            Ok(markdown_elements)
+        } else {
+            Err(MDPError::MultiError(errors))
         }
+    }
+
+    /// Lines which can not be parsed are skipped (only their `Token::Newline` is kept
+    /// so that line numbers stay intact).
+    fn tokenize_best_effort<'a>(
+        &self,
+        markdown_string: &'a str,
+    ) -> (Vec<Token<'a>>, Vec<MDPError>) {
+        self.tokenize_recovering(markdown_string, false, false)
+    }
+
+    fn tokenize_lenient<'a>(&self, markdown_string: &'a str) -> (Vec<Token<'a>>, Vec<MDPError>) {
+        self.tokenize_recovering(markdown_string, true, false)
+    }
+
+    fn tokenize_strict<'a>(&self, markdown_string: &'a str) -> Result<Vec<Token<'a>>, MDPError> {
+        let (markdown_elements, errors) = self.tokenize_recovering(markdown_string, false, true);
 
         if errors.is_empty() {
             Ok(markdown_elements)
@@ -35,10 +64,272 @@ impl MarkdownTokenizer for MDPMarkdownTokenizer {
             Err(MDPError::MultiError(errors))
         }
     }
+
+    fn cache_key(&self) -> Option<String> {
+        let today = self.today.unwrap_or_else(|| Local::now().date_naive());
+        Some(format!(
+            "mdp any_case_task_keywords={} single_colon_attributes={} today={}",
+            self.any_case_task_keywords, self.single_colon_attributes, today
+        ))
+    }
+
+    /// Only the tokens of the current line (or table or code block) are kept in memory.
+    fn tokenize_iter<'a>(
+        &'a self,
+        markdown_string: &'a str,
+    ) -> Box<dyn Iterator<Item = Result<Token<'a>, MDPError>> + 'a> {
+        Box::new(self.stream(markdown_string, false, false))
+    }
+}
+
+impl MDPMarkdownTokenizer {
+    /// Tokenize line by line, lines which can not be parsed are skipped or (if
+    /// `keep_as_text`) kept as `Token::Text`. Returns the errors of these lines, if `strict`
+    /// also the ones of lines which look like malformed markup (see [`near_miss`]).
+    fn tokenize_recovering<'a>(
+        &self,
+        markdown_string: &'a str,
+        keep_as_text: bool,
+        strict: bool,
+    ) -> (Vec<Token<'a>>, Vec<MDPError>) {
+        let mut errors: Vec<MDPError> = vec![];
+        let mut markdown_elements: Vec<Token> = vec![];
+        for element in self.stream(markdown_string, keep_as_text, strict) {
+            match element {
+                Ok(token) => markdown_elements.push(token),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (markdown_elements, errors)
+    }
+
+    fn stream<'a>(
+        &self,
+        markdown_string: &'a str,
+        keep_as_text: bool,
+        strict: bool,
+    ) -> TokenStream<'a> {
+        let today = self.today.unwrap_or_else(|| Local::now().date_naive());
+        TokenStream {
+            markdown_string,
+            lines: split_into_lines(markdown_string),
+            line_index: 0,
+            any_case_task_keywords: self.any_case_task_keywords,
+            single_colon_attributes: self.single_colon_attributes,
+            today,
+            due_base: today,
+            keep_as_text,
+            strict,
+            setext_underline_index: None,
+            in_metadata: false,
+            block_end: 0,
+            list_levels: ListLevels::default(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// The tokens of a Markdown string, tokenized line by line when they are consumed. The
+/// errors of lines which can not be parsed are yielded in place of their tokens.
+struct TokenStream<'a> {
+    markdown_string: &'a str,
+    lines: Vec<Line<'a>>,
+    /// The next line to tokenize
+    line_index: usize,
+    any_case_task_keywords: bool,
+    single_colon_attributes: bool,
+    today: NaiveDate,
+    /// The day relative deadlines are resolved against: the date of the current H1 section
+    /// or today
+    due_base: NaiveDate,
+    keep_as_text: bool,
+    strict: bool,
+    setext_underline_index: Option<usize>,
+    /// Whether the line is directly below a heading or its attributes, where `Name: value`
+    /// lines are attributes (if `single_colon_attributes`)
+    in_metadata: bool,
+    /// First line after the current table or code block
+    block_end: usize,
+    list_levels: ListLevels,
+    /// The tokens of the last tokenized line which were not yielded yet
+    pending: VecDeque<Result<Token<'a>, MDPError>>,
+}
+
+impl<'a> Iterator for TokenStream<'a> {
+    type Item = Result<Token<'a>, MDPError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && self.line_index < self.lines.len() {
+            self.tokenize_line();
+            self.line_index += 1;
+        }
+        self.pending.pop_front()
+    }
+}
+
+impl<'a> TokenStream<'a> {
+    fn tokenize_line(&mut self) {
+        let line_index = self.line_index;
+        let line = self.lines[line_index];
+        // The rows of a table and the lines of a code block are part of the token on
+        // their first line
+        if line_index < self.block_end {
+            return;
+        }
+        let in_metadata = std::mem::take(&mut self.in_metadata);
+        if let Some((code_tokens, code_lines)) =
+            fenced_code_block(self.markdown_string, &self.lines[line_index..])
+        {
+            self.block_end = line_index + code_lines;
+            self.pending.extend(code_tokens.into_iter().map(Ok));
+            self.pending.push_back(Ok(Token::Newline));
+            return;
+        }
+        if let Some((table_token, rows)) = table(&self.lines[line_index..]) {
+            self.block_end = line_index + rows;
+            let mut elements = vec![table_token];
+            merge_text(self.markdown_string, &mut elements);
+            self.list_levels.update(line, &mut elements);
+            self.pending.extend(elements.into_iter().map(Ok));
+            self.pending.push_back(Ok(Token::Newline));
+            return;
+        }
+
+        let parsed = if self.setext_underline_index == Some(line_index) {
+            Ok(vec![])
+        } else {
+            let next_line = self.lines.get(line_index + 1);
+            let any_case = self.any_case_task_keywords;
+            match next_line.and_then(|next| setext_heading(line, *next, any_case, self.due_base)) {
+                Some(heading) => {
+                    self.setext_underline_index = Some(line_index + 1);
+                    Ok(vec![heading])
+                }
+                None => parse_line(line, any_case, self.due_base),
+            }
+        };
+        let parsed = match parsed {
+            Ok(elements) if in_metadata && self.single_colon_attributes => {
+                Ok(metadata_attribute_line(line, elements))
+            }
+            parsed => parsed,
+        };
+        let parsed = match parsed {
+            Ok(elements) if self.strict => match near_miss(line.0, &elements) {
+                Some(reason) => Err(MarkdownParseError::MalformedMarkup(reason)),
+                None => Ok(elements),
+            },
+            parsed => parsed,
+        };
+        let parsed = match parsed.map_err(|e| e.into_mdp_error(line_index + 1)) {
+            Ok(elements) => Some(elements),
+            Err(e) => {
+                self.pending.push_back(Err(e));
+                self.keep_as_text.then(|| vec![Token::Text(line.0)])
+            }
+        };
+        if let Some(mut elements) = parsed {
+            self.in_metadata = match elements.first() {
+                // The underline of a setext heading
+                None => in_metadata,
+                Some(first) => first.is_heading() || matches!(first, Token::Attribute { .. }),
+            };
+            if let Some(Token::HeadingH1(title)) = elements.first() {
+                self.due_base = title
+                    .iter()
+                    .find_map(|t| match t {
+                        Token::Date(date) => Some(*date),
+                        _ => None,
+                    })
+                    .unwrap_or(self.today);
+            }
+            merge_text(self.markdown_string, &mut elements);
+            self.list_levels.update(line, &mut elements);
+            self.pending.extend(elements.into_iter().map(Ok));
+        }
+        self.pending.push_back(Ok(Token::Newline))
+    }
+}
+
+/// Merges adjacent `Token::Text`s (also within other tokens) which are consecutive
+/// slices of `input` into a single one, so that a run of text is never split into
+/// fragments.
+fn merge_text<'a>(input: &'a str, tokens: &mut Vec<Token<'a>>) {
+    let offset = |s: &str| (s.as_ptr() as usize).checked_sub(input.as_ptr() as usize);
+    let mut merged: Vec<Token<'a>> = Vec::with_capacity(tokens.len());
+    for mut token in tokens.drain(..) {
+        if let Some(children) = token.children_mut() {
+            merge_text(input, children);
+        }
+        if let (Some(Token::Text(previous)), Token::Text(text)) = (merged.last_mut(), &token) {
+            if let (Some(start), Some(end)) = (offset(previous), offset(text)) {
+                if start + previous.len() == end && end + text.len() <= input.len() {
+                    *previous = &input[start..end + text.len()];
+                    continue;
+                }
+            }
+        }
+        merged.push(token);
+    }
+    *tokens = merged;
 }
 
+/// Number of columns a tab counts for when comparing indentations
+const TAB_WIDTH: usize = 4;
+
+/// Start and end of a code block spanning multiple lines
+const CODE_FENCE: &str = "```";
+
+#[derive(Clone, Copy)]
 struct Line<'a>(&'a str);
 
+/// Indentations of the list items enclosing the current line, used to set the nesting
+/// level of `Token::ListItem`s.
+#[derive(Default)]
+struct ListLevels(Vec<usize>);
+
+impl ListLevels {
+    fn update(&mut self, line: Line<'_>, tokens: &mut [Token<'_>]) {
+        match tokens.first_mut() {
+            Some(Token::ListItem { prefix, level, .. }) => {
+                *level = self.push(prefix);
+            }
+            // Checkbox tasks are list items as well (without a level of their own)
+            Some(Token::Task {
+                checkbox: Some(prefix),
+                ..
+            }) => {
+                self.push(prefix);
+            }
+            Some(Token::Blank) => {}
+            // An unindented line which is no list item ends the list
+            _ if !line.0.starts_with([' ', '\t']) => self.0.clear(),
+            _ => {}
+        }
+    }
+
+    /// Add a list item with the given prefix, returns its nesting level
+    fn push(&mut self, prefix: &str) -> usize {
+        let indentation = indentation_width(prefix);
+        while self.0.last().is_some_and(|i| *i > indentation) {
+            self.0.pop();
+        }
+        if self.0.last() != Some(&indentation) {
+            self.0.push(indentation);
+        }
+        self.0.len() - 1
+    }
+}
+
+/// Width of the whitespace at the start of the string (e.g. the prefix of a list item)
+pub(crate) fn indentation_width(s: &str) -> usize {
+    s.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
 impl<'a> From<Line<'a>> for &'a str {
     fn from(value: Line<'a>) -> Self {
         value.0
@@ -49,15 +340,201 @@ fn split_into_lines(input: &str) -> Vec<Line<'_>> {
     input.split('\n').map(Line).collect()
 }
 
-fn parse_line(input: Line<'_>) -> Result<Vec<Token<'_>>, MarkdownParseError<&str>> {
+/// Parses a setext heading, i.e. a paragraph line underlined by `=` (H1) or `-` (H2).
+///
+/// A single `-` is not considered an underline since it is more likely an empty list item.
+fn setext_heading<'a>(
+    line: Line<'a>,
+    underline: Line<'_>,
+    any_case_task_keywords: bool,
+    due_base: NaiveDate,
+) -> Option<Token<'a>> {
+    let underline = underline.0.trim_end();
+    let is_underline = |c: char, min_length: usize| {
+        underline.len() >= min_length && underline.chars().all(|u| u == c)
+    };
+    let is_h1 = is_underline('=', 1);
+    if !is_h1 && !is_underline('-', 2) {
+        return None;
+    }
+
+    match parse_line(line, any_case_task_keywords, due_base)
+        .ok()?
+        .first()?
+    {
+        Token::Blank
+        | Token::HRule
+        | Token::BlockQuote(_)
+        | Token::Attribute { .. }
+        | Token::FootnoteDefinition { .. }
+        | Token::Task { .. }
+        | Token::ListItem { .. }
+        | Token::HeadingH1(_)
+        | Token::HeadingH2(_)
+        | Token::HeadingH3(_)
+        | Token::HeadingH4(_)
+        | Token::HeadingH5(_)
+        | Token::HeadingH6(_) => return None,
+        _ => {}
+    }
+
+    let (_, content) = parse_inline(line.0.trim()).ok()?;
+    match is_h1 {
+        true => Some(Token::HeadingH1(content)),
+        false => Some(Token::HeadingH2(content)),
+    }
+}
+
+/// Parses a code block fenced by lines starting with ` ``` ` at the start of `lines`, e.g.
+///
+/// ````markdown
+/// ```rust
+/// let x = 1;
+/// ```
+/// ````
+///
+/// The block becomes a single `Token::TripleBacktick` containing everything between the
+/// fences (including the info string with the language), so that its lines are not parsed
+/// as markdown. The indentation of the opening fence and any text after the closing fence
+/// are kept as `Token::Text`. Returns the tokens and the number of lines the block spans,
+/// `None` if the fence is not closed (or already closed on the same line).
+///
+/// The lines have to be slices of `input`.
+fn fenced_code_block<'a>(input: &'a str, lines: &[Line<'a>]) -> Option<(Vec<Token<'a>>, usize)> {
+    let first = lines.first()?.0;
+    let indentation = &first[..first.len() - first.trim_start().len()];
+    let info = first.trim_start().strip_prefix(CODE_FENCE)?;
+    if info.contains(CODE_FENCE) {
+        return None;
+    }
+    let (closing_index, closing) = lines
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, l)| l.0.trim_start().starts_with(CODE_FENCE))?;
+
+    let offset = |s: &str| s.as_ptr() as usize - input.as_ptr() as usize;
+    let content_start = offset(info);
+    let content_end = offset(closing.0) + closing.0.find(CODE_FENCE)?;
+    let rest = &closing.0[closing.0.find(CODE_FENCE)? + CODE_FENCE.len()..];
+
+    let mut tokens = vec![];
+    if !indentation.is_empty() {
+        tokens.push(Token::Text(indentation));
+    }
+    tokens.push(Token::TripleBacktick(&input[content_start..content_end]));
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Some((tokens, closing_index + 1))
+}
+
+/// Parses a table at the start of `lines`, i.e. a header row followed by a delimiter row
+/// (`|---|:-:|`) and any number of further rows. Returns the table and the number of lines
+/// it spans.
+///
+/// Only rows starting and ending with `|` are recognized, the table ends at the first line
+/// which is not a row.
+fn table<'a>(lines: &[Line<'a>]) -> Option<(Token<'a>, usize)> {
+    let header = table_row_cells(*lines.first()?)?;
+    let delimiter = table_row_cells(*lines.get(1)?)?;
+    let is_delimiter = |cell: &str| {
+        let cell = cell.trim();
+        let dashes = cell.strip_prefix(':').unwrap_or(cell);
+        let dashes = dashes.strip_suffix(':').unwrap_or(dashes);
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    };
+    if header.len() != delimiter.len() || !delimiter.iter().all(|c| is_delimiter(c)) {
+        return None;
+    }
+
+    let mut rows = vec![];
+    for line in lines {
+        let Some(cells) = table_row_cells(*line) else {
+            break;
+        };
+        let cells = cells
+            .into_iter()
+            .map(|c| {
+                parse_inline(c)
+                    .ok()
+                    .map(|(_, tokens)| Token::TableCell(tokens))
+            })
+            .collect::<Option<Vec<Token>>>()?;
+        rows.push(Token::TableRow(cells));
+    }
+    let row_count = rows.len();
+    Some((Token::Table(rows), row_count))
+}
+
+/// The (untrimmed) cells of a table row, `|` within code spans or escaped as `\|` do not
+/// separate cells.
+fn table_row_cells(line: Line<'_>) -> Option<Vec<&str>> {
+    let inner = line.0.trim_end().strip_prefix('|')?.strip_suffix('|')?;
+
+    let mut cells = vec![];
+    let mut start = 0;
+    let mut in_code = false;
+    let mut escaped = false;
+    for (i, c) in inner.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '`' => in_code = !in_code,
+            '|' if !in_code => {
+                cells.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    cells.push(&inner[start..]);
+    Some(cells)
+}
+
+/// The tokens of a line below a heading: a `Name: value` attribute if the line was parsed
+/// as text, unless its name is a task keyword in another case (e.g. `Todo: Call`)
+fn metadata_attribute_line<'a>(line: Line<'a>, elements: Vec<Token<'a>>) -> Vec<Token<'a>> {
+    if !matches!(elements.first(), Some(Token::Text(_)))
+        || misspelled_task_keyword(line.0).is_some()
+    {
+        return elements;
+    }
+    match all_consuming(metadata_attribute)(line.0) {
+        Ok((_, (name, separator, value))) => vec![Token::Attribute {
+            name,
+            separator,
+            value,
+        }],
+        Err(_) => elements,
+    }
+}
+
+fn parse_line(
+    input: Line<'_>,
+    any_case_task_keywords: bool,
+    due_base: NaiveDate,
+) -> Result<Vec<Token<'_>>, MarkdownParseError<&str>> {
+    let task = |i| task(i, any_case_task_keywords, due_base);
+    let list_task = |i| list_task(i, any_case_task_keywords, due_base);
     let r = alt((
         map(all_consuming(multispace0), |_| vec![Token::Blank]),
         map(all_consuming(tag("---")), |_| vec![Token::HRule]),
         map(all_consuming(preceded(tag("> "), parse_inline)), |values| {
             vec![Token::BlockQuote(values)]
         }),
-        map(all_consuming(attribute), |(name, value)| {
-            vec![Token::Attribute { name, value }]
+        all_consuming(map(checkbox_task, |t| vec![t])),
+        all_consuming(map(list_task, |(item, task)| vec![item, task])),
+        all_consuming(map(list_item, |l| vec![l])),
+        map(all_consuming(footnote_definition), |(label, content)| {
+            vec![Token::FootnoteDefinition { label, content }]
+        }),
+        map(all_consuming(attribute), |(name, separator, value)| {
+            vec![Token::Attribute {
+                name,
+                separator,
+                value,
+            }]
         }),
         all_consuming(map(pair(task, parse_inline), |(todo_token, mut tokens)| {
             tokens.insert(0, todo_token);
@@ -112,7 +589,7 @@ In the morning i had a meeting with @roger (roger.example@gmail.com).
 TODO: Inform roger about the state of the project
         "#;
 
-        let mdp_tokenizer = MDPMarkdownTokenizer {};
+        let mdp_tokenizer = MDPMarkdownTokenizer::default();
         let should_tokens = vec![
             Token::Blank,
             Token::Newline,
@@ -145,6 +622,7 @@ TODO: Inform roger about the state of the project
             Token::Task {
                 content: vec![Token::Text("Clean room")],
                 status: TaskStatus::Done,
+                checkbox: None,
             },
             Token::Newline,
             Token::Blank,
@@ -174,13 +652,360 @@ TODO: Inform roger about the state of the project
             Token::Task {
                 content: vec![Token::Text("Inform roger about the state of the project")],
                 status: TaskStatus::Todo,
+                checkbox: None,
             },
             Token::Newline,
             Token::Blank,
             Token::Newline,
         ];
 
-        assert_eq!(mdp_tokenizer.tokenize(&markdown_string), Ok(should_tokens),);
+        assert_eq!(mdp_tokenizer.tokenize(markdown_string), Ok(should_tokens),);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_setext_headings() -> Result<()> {
+        let markdown_string = "2022-11-02\n==========\nSchool\n---\n\n---\n- item\n--";
+
+        let should_tokens = vec![
+            Token::HeadingH1(vec![Token::Date(
+                NaiveDate::from_ymd_opt(2022, 11, 2).unwrap(),
+            )]),
+            Token::Newline,
+            Token::Newline,
+            Token::HeadingH2(vec![Token::Text("School")]),
+            Token::Newline,
+            Token::Newline,
+            Token::Blank,
+            Token::Newline,
+            Token::HRule,
+            Token::Newline,
+            Token::ListItem {
+                prefix: "- ",
+                level: 0,
+                content: vec![Token::Text("item")],
+            },
+            Token::Newline,
+            Token::Text("--"),
+            Token::Newline,
+        ];
+
+        assert_eq!(
+            MDPMarkdownTokenizer::default().tokenize(markdown_string),
+            Ok(should_tokens)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_list_levels() -> Result<()> {
+        let markdown_string = "- a\n  - b\n\t- c\n\n  - d\n1. e\nText\n  - f";
+
+        let levels = MDPMarkdownTokenizer::default()
+            .tokenize(markdown_string)?
+            .iter()
+            .filter_map(|t| match t {
+                Token::ListItem { level, .. } => Some(*level),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(levels, vec![0, 1, 2, 1, 0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_checkbox_tasks() -> Result<()> {
+        let markdown_string = "- [ ] Plan trip\n  - [x] Book flight\n    - Window seat\n- [] a";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown_string)?;
+
+        assert_eq!(
+            tokens[2],
+            Token::Task {
+                content: vec![Token::Text("Book flight")],
+                status: TaskStatus::Done,
+                checkbox: Some("  - "),
+            }
+        );
+        // Nested below the checkbox task
+        assert!(matches!(tokens[4], Token::ListItem { level: 2, .. }));
+        assert!(matches!(tokens[6], Token::ListItem { level: 0, .. }));
+        let rendered: String = tokens.iter().map(|t| t.to_string()).collect();
+        assert_eq!(rendered, format!("{}\n", markdown_string));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_any_case_task_keywords() -> Result<()> {
+        let markdown_string = "todo: Call\n- Done: Book flight\nWaiting(@roger): Keys\n";
+        let is_task = |t: &Token| matches!(t, Token::Task { .. });
+
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown_string)?;
+        assert!(!tokens.iter().any(is_task));
+
+        let tokenizer = MDPMarkdownTokenizer {
+            any_case_task_keywords: true,
+            ..Default::default()
+        };
+        let tasks = tokenizer
+            .tokenize(markdown_string)?
+            .into_iter()
+            .filter(is_task)
+            .collect::<Vec<_>>();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(
+            tasks[1],
+            Token::Task {
+                content: vec![Token::Text("Book flight")],
+                status: TaskStatus::Done,
+                checkbox: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_single_colon_attributes() -> Result<()> {
+        let markdown_string = "# 2024-05-01 Book\nAuthor: Ursula K. Le Guin\nRating:: 5\n\
+                               Todo: Read\nRead it in: two days\n\nSource: Library\n\
+                               Imported\n========\nCreated: 2024-04-30\n";
+        let attributes = |tokenizer: MDPMarkdownTokenizer| -> Result<Vec<String>> {
+            Ok(tokenizer
+                .tokenize(markdown_string)?
+                .into_iter()
+                .filter(|t| matches!(t, Token::Attribute { .. }))
+                .map(|t| t.to_markdown_string())
+                .collect())
+        };
+
+        assert_eq!(
+            attributes(MDPMarkdownTokenizer::default())?,
+            vec!["Rating:: 5"]
+        );
+        let tokenizer = MDPMarkdownTokenizer {
+            single_colon_attributes: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            attributes(tokenizer)?,
+            vec![
+                "Author: Ursula K. Le Guin",
+                "Rating:: 5",
+                "Created: 2024-04-30"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_relative_due_dates() -> Result<()> {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let tokenizer = MDPMarkdownTokenizer {
+            today: Some(date(20)),
+            ..Default::default()
+        };
+        let markdown_string = "TODO UNTIL tomorrow: A\n# 2024-05-03 Friday\n\n\
+                               TODO UNTIL tomorrow: B\n## Plans\n- TODO UNTIL monday: C\n\
+                               # Ideas\nTODO UNTIL +1w: D\n";
+        let dues = tokenizer
+            .tokenize(markdown_string)?
+            .into_iter()
+            .filter_map(|t| match t {
+                Token::Task {
+                    status: TaskStatus::TodoUntil(due),
+                    ..
+                } => Some(due.date),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(dues, vec![date(21), date(4), date(6), date(27)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_list_tasks() -> Result<()> {
+        let markdown_string = "- TODO: Plan trip\n  1. DONE: Book flight\n- TODO:";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown_string)?;
+
+        assert!(matches!(tokens[3], Token::ListItem { level: 1, .. }));
+        assert_eq!(
+            tokens[4],
+            Token::Task {
+                content: vec![Token::Text("Book flight")],
+                status: TaskStatus::Done,
+                checkbox: None,
+            }
+        );
+        // Without a description it is no task
+        assert!(matches!(&tokens[6], Token::ListItem { content, .. } if !content.is_empty()));
+        let rendered: String = tokens.iter().map(|t| t.to_string()).collect();
+        assert_eq!(rendered, format!("{}\n", markdown_string));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_footnotes() -> Result<()> {
+        let markdown_string = "As shown[^1] before[^note].\n\n[^1]: The source, see [[Paper]].\n\
+                               [^note]: Only once\n---";
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown_string)?;
+
+        assert_eq!(tokens[1], Token::FootnoteRef("1"));
+        assert_eq!(tokens[3], Token::FootnoteRef("note"));
+        assert_eq!(
+            tokens[8],
+            Token::FootnoteDefinition {
+                label: "1",
+                content: vec![
+                    Token::Text("The source, see "),
+                    Token::Link("Paper"),
+                    Token::Text("."),
+                ],
+            }
+        );
+        // A definition is no setext heading
+        assert!(matches!(tokens[10], Token::FootnoteDefinition { .. }));
+        assert_eq!(tokens[12], Token::HRule);
+        let rendered: String = tokens.iter().map(|t| t.to_string()).collect();
+        assert_eq!(rendered, format!("{}\n", markdown_string));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_table() -> Result<()> {
+        let markdown_string =
+            "| Name | Code |\n|:-----|------|\n| @roger | `a|b` |\nAfter\n| no | table |";
+        let cell = |tokens: Vec<Token<'static>>| Token::TableCell(tokens);
+
+        let should_tokens = vec![
+            Token::Table(vec![
+                Token::TableRow(vec![
+                    cell(vec![Token::Text(" Name ")]),
+                    cell(vec![Token::Text(" Code ")]),
+                ]),
+                Token::TableRow(vec![
+                    cell(vec![Token::Text(":-----")]),
+                    cell(vec![Token::Text("------")]),
+                ]),
+                Token::TableRow(vec![
+                    cell(vec![
+                        Token::Text(" "),
+                        Token::Tag("roger"),
+                        Token::Text(" "),
+                    ]),
+                    cell(vec![
+                        Token::Text(" "),
+                        Token::SingleBacktick("a|b"),
+                        Token::Text(" "),
+                    ]),
+                ]),
+            ]),
+            Token::Newline,
+            Token::Text("After"),
+            Token::Newline,
+            Token::Text("| no | table |"),
+            Token::Newline,
+        ];
+
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown_string)?;
+        assert_eq!(tokens, should_tokens);
+        assert_eq!(
+            tokens[0].to_markdown_string(),
+            "| Name | Code |\n|:-----|------|\n| @roger | `a|b` |"
+        );
+
+        let lines = crate::models::annotate_origins(tokens, None)
+            .into_iter()
+            .map(|(_, o)| o.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![1, 3, 4, 4, 5, 5]);
         Ok(())
     }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_code_block() -> Result<()> {
+        let markdown_string = "```rust\n# Not a heading\nTODO: No task\n```\nTODO: Task\n\
+                               - a\n  ```\n  b\n  ``` \n```unclosed\n# Heading";
+
+        let should_tokens = vec![
+            Token::TripleBacktick("rust\n# Not a heading\nTODO: No task\n"),
+            Token::Newline,
+            Token::Task {
+                content: vec![Token::Text("Task")],
+                status: TaskStatus::Todo,
+                checkbox: None,
+            },
+            Token::Newline,
+            Token::ListItem {
+                prefix: "- ",
+                level: 0,
+                content: vec![Token::Text("a")],
+            },
+            Token::Newline,
+            Token::Text("  "),
+            Token::TripleBacktick("\n  b\n  "),
+            Token::Text(" "),
+            Token::Newline,
+            Token::Text("```unclosed"),
+            Token::Newline,
+            Token::HeadingH1(vec![Token::Text("Heading")]),
+            Token::Newline,
+        ];
+
+        let tokens = MDPMarkdownTokenizer::default().tokenize(markdown_string)?;
+        assert_eq!(tokens, should_tokens);
+        assert_eq!(tokens[0].code_language(), Some("rust"));
+        assert_eq!(tokens[7].code_language(), None);
+        let rendered: String = tokens.iter().map(|t| t.to_string()).collect();
+        assert_eq!(rendered, format!("{}\n", markdown_string));
+
+        let lines = crate::models::annotate_origins(tokens, None)
+            .into_iter()
+            .map(|(_, o)| o.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![1, 4, 5, 5, 6, 6, 7, 7, 9, 9, 10, 10, 11, 11]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mdp_markdown_tokenizer_iter() -> Result<()> {
+        let markdown_string = "Title\n=====\n| a | b |\n|---|---|\n```\n# No heading\n```\n\
+                               - TODO: Task\n  - [x] Done";
+        let tokenizer = MDPMarkdownTokenizer::default();
+
+        let tokens = tokenizer
+            .tokenize_iter(markdown_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(tokens, tokenizer.tokenize(markdown_string)?);
+        assert_eq!(
+            tokenizer.tokenize_iter("").count(),
+            tokenizer.tokenize("")?.len()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_text() {
+        let input = "Call (Bob) *now* or later";
+        let mut tokens = vec![
+            Token::Text(&input[0..5]),
+            Token::Text(&input[5..10]),
+            Token::Text(&input[10..11]),
+            Token::Italic(vec![Token::Text(&input[12..13]), Token::Text(&input[13..15])]),
+            Token::Text(&input[16..19]),
+            // Not consecutive in the input
+            Token::Text(&input[20..25]),
+            Token::Text("!"),
+        ];
+        merge_text(input, &mut tokens);
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text("Call (Bob) "),
+                Token::Italic(vec![Token::Text("now")]),
+                Token::Text(" or"),
+                Token::Text("later"),
+                Token::Text("!"),
+            ]
+        );
+    }
 }
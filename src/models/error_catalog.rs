@@ -0,0 +1,238 @@
+/// Detailed description of an error code
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorExplanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub suggestions: &'static [&'static str],
+}
+
+impl ErrorExplanation {
+    pub fn to_text(&self) -> String {
+        let mut s = format!("{}: {}\n\n{}\n", self.code, self.title, self.description);
+        if !self.suggestions.is_empty() {
+            s += "\nPossible fixes:\n";
+            for suggestion in self.suggestions {
+                s += &format!("- {}\n", suggestion);
+            }
+        }
+        s
+    }
+}
+
+const ERROR_CATALOG: &[ErrorExplanation] = &[
+    ErrorExplanation {
+        code: "MDP001",
+        title: "Markdown parse error",
+        description: "A line of the input could not be tokenized. This usually happens if \
+            inline markup is not closed properly, e.g. a link with unbalanced brackets.",
+        suggestions: &[
+            "Check the reported line for unbalanced brackets or parentheses.",
+            "Escape brackets which are not part of a link with a backslash.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP002",
+        title: "MDP syntax error",
+        description: "The input is valid Markdown but violates the conventions MDP relies \
+            on, e.g. a H1 heading without a date or with more than one date.",
+        suggestions: &[
+            "Make sure every H1 heading contains exactly one date in YYYY-MM-DD format.",
+            "Write the days of a section spanning several days as a range, e.g. \
+             `# 2023-10-10 - 2023-10-12`.",
+            "Notes without dates can be dated by their file with `--date-policy filename` or \
+             `--date-policy file-mtime`, or kept undated with `--date-policy none`.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP003",
+        title: "File could not be read",
+        description: "One of the input files could not be read.",
+        suggestions: &[
+            "Check that the path exists and that you are allowed to read it.",
+            "Make sure the file is UTF-8 encoded.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP004",
+        title: "File could not be written",
+        description: "An output file could not be written.",
+        suggestions: &[
+            "Check that the directory of the output file exists and is writable.",
+            "Make sure the output file is not opened exclusively by another program.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP005",
+        title: "IO error",
+        description: "A filesystem operation (e.g. traversing an input directory or \
+            changing file permissions) failed.",
+        suggestions: &["Check the permissions of the involved files and directories."],
+    },
+    ErrorExplanation {
+        code: "MDP006",
+        title: "Multiple errors",
+        description: "Several errors occured at once, e.g. multiple lines could not be \
+            parsed. Each of them is listed with its own error code.",
+        suggestions: &["Look up the error codes of the listed errors."],
+    },
+    ErrorExplanation {
+        code: "MDP007",
+        title: "Post-write hook failed",
+        description: "A command configured to run after an output file was written (see \
+            `--post-hook`) could not be started or exited with a non-zero status.",
+        suggestions: &[
+            "Run the hook command manually with the output file as last argument.",
+            "Make sure the command is installed and in your PATH.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP008",
+        title: "Execution not permitted",
+        description: "An external command (e.g. a post-write hook) was not run because \
+            execution is disabled with `--no-exec` or its executable is not part of the \
+            `allowed_executables` in the configuration file.",
+        suggestions: &[
+            "Add the executable to `allowed_executables` in the `[exec]` table of the \
+                configuration file.",
+            "Don't use shell operators (e.g. `;`, `|`, `$`) in commands when an allowlist \
+                is configured.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP009",
+        title: "Limit exceeded",
+        description: "The input is larger than the configured limits allow: too many input \
+            files, too many tokens, or a file which is too large (such files are skipped). \
+            The limits protect against accidentally reading e.g. a whole disk or an exported \
+            log file.",
+        suggestions: &[
+            "Check the input paths, e.g. for a `-i` pointing to the root directory.",
+            "Raise `max_files`, `max_tokens` or `max_file_size` in the `[limits]` table of \
+                the configuration file.",
+            "Use `--force` to ignore the limits.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP010",
+        title: "Binary file",
+        description: "An input file contains binary data instead of text, e.g. an image or \
+            a sync conflict which was saved with a markdown extension. Such files are skipped.",
+        suggestions: &[
+            "Rename or remove the file if it is no markdown file.",
+            "Restore the file from a backup if it is a corrupted markdown file.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP101",
+        title: "Configuration IO error",
+        description: "An IO error occured while processing the configuration.",
+        suggestions: &["Check that all files referenced by the configuration exist."],
+    },
+    ErrorExplanation {
+        code: "MDP102",
+        title: "Invalid search term",
+        description: "A search term is invalid. Search terms are tags (`@tag`), hashtags \
+            (`#topic`) or words and must neither be empty nor contain whitespace.",
+        suggestions: &[
+            "Separate multiple search terms with commas, e.g. `mdp search @work,meeting`.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP103",
+        title: "Incompatible configuration",
+        description: "The provided options can not be combined for this command.",
+        suggestions: &["Check the help of the command (`mdp <COMMAND> --help`)."],
+    },
+    ErrorExplanation {
+        code: "MDP104",
+        title: "Invalid date",
+        description: "A date is invalid. Dates are given as YYYY-MM-DD or relative to today \
+            as a number of days or weeks, e.g. `+7d`, `-1w` or `2w`.",
+        suggestions: &["Check that the day exists, e.g. there is no 2023-02-30."],
+    },
+    ErrorExplanation {
+        code: "MDP105",
+        title: "Invalid combination of options",
+        description: "The options are valid on their own, but together they would produce \
+            confusing results, e.g. `--until` before `--from` or an output file which would be \
+            read as input by later runs.",
+        suggestions: &[
+            "Follow the suggestion of the error message.",
+            "Run the command with `--check-args` to validate the options without running it.",
+        ],
+    },
+    ErrorExplanation {
+        code: "MDP199",
+        title: "Unknown error",
+        description: "An unexpected error occured.",
+        suggestions: &["Please open an issue including the command you ran."],
+    },
+];
+
+/// Returns the detailed explanation of an error code (e.g. `MDP001`), if it exists.
+pub fn explain_error(code: &str) -> Option<&'static ErrorExplanation> {
+    ERROR_CATALOG
+        .iter()
+        .find(|e| e.code.eq_ignore_ascii_case(code.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::models::{ConfigError, MDPError};
+
+    #[test]
+    fn test_all_error_codes_are_explained() {
+        let errors = vec![
+            MDPError::MarkdownParseError {
+                msg: String::new(),
+                line_number: 0,
+            },
+            MDPError::MDPSyntaxError(String::new()),
+            MDPError::IOReadError {
+                path: Default::default(),
+                details: String::new(),
+            },
+            MDPError::IOWriteError {
+                path: Default::default(),
+                details: String::new(),
+            },
+            MDPError::IOError(String::new()),
+            MDPError::MultiError(vec![]),
+            MDPError::HookError {
+                command: String::new(),
+                details: String::new(),
+            },
+            MDPError::ExecNotPermittedError {
+                command: String::new(),
+                reason: String::new(),
+            },
+            MDPError::LimitExceededError(String::new()),
+            MDPError::BinaryFileError(PathBuf::new()),
+            MDPError::ConfigError(ConfigError::IOError),
+            MDPError::ConfigError(ConfigError::InvalidSearchTermError(String::new())),
+            MDPError::ConfigError(ConfigError::IncompatibleConfigError),
+            MDPError::ConfigError(ConfigError::InvalidDateError(String::new())),
+            MDPError::ConfigError(ConfigError::InvalidOptionsError(String::new())),
+            MDPError::ConfigError(ConfigError::UnkownError),
+        ];
+        for e in errors {
+            assert!(explain_error(e.code()).is_some(), "{} is not explained", e.code());
+        }
+    }
+
+    #[test]
+    fn test_error_code_in_message() {
+        let e = MDPError::MDPSyntaxError("The section title # abc doesn't contain a date.".into());
+        assert_eq!(
+            e.to_string(),
+            "[MDP002] The section title # abc doesn't contain a date."
+        );
+        assert_eq!(explain_error("mdp002").unwrap().code, "MDP002");
+    }
+}
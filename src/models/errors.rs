@@ -8,13 +8,62 @@ pub enum MDPError {
         path: PathBuf,
         details: String,
     },
-    IOWriteError(PathBuf),
+    IOWriteError {
+        path: PathBuf,
+        details: String,
+    },
     IOError(String),
+    HookError {
+        command: String,
+        details: String,
+    },
+    ExecNotPermittedError {
+        command: String,
+        reason: String,
+    },
     ConfigError(ConfigError),
+    /// The input exceeds one of the configured limits
+    LimitExceededError(String),
+    /// The file is no text file (e.g. an image or a sync conflict named `.md`)
+    BinaryFileError(PathBuf),
 
     MultiError(Vec<MDPError>),
 }
 
+impl MDPError {
+    /// Adds `offset` to the line numbers of parse errors (e.g. for input which was parsed
+    /// without the preceding lines)
+    pub fn offset_lines(self, offset: usize) -> Self {
+        match self {
+            Self::MarkdownParseError { msg, line_number } => Self::MarkdownParseError {
+                msg,
+                line_number: line_number + offset,
+            },
+            Self::MultiError(errors) => {
+                Self::MultiError(errors.into_iter().map(|e| e.offset_lines(offset)).collect())
+            }
+            e => e,
+        }
+    }
+
+    /// Stable error code, see [`super::explain_error`]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MarkdownParseError { .. } => "MDP001",
+            Self::MDPSyntaxError(_) => "MDP002",
+            Self::IOReadError { .. } => "MDP003",
+            Self::IOWriteError { .. } => "MDP004",
+            Self::IOError(_) => "MDP005",
+            Self::ConfigError(e) => e.code(),
+            Self::MultiError(_) => "MDP006",
+            Self::HookError { .. } => "MDP007",
+            Self::ExecNotPermittedError { .. } => "MDP008",
+            Self::LimitExceededError(_) => "MDP009",
+            Self::BinaryFileError(_) => "MDP010",
+        }
+    }
+}
+
 impl Display for MDPError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let msg = match self {
@@ -28,12 +77,25 @@ impl Display for MDPError {
                 Some(f) => format!("An error occured while reading the file {}: {}", f, details),
                 None => format!("An error occured while reading a file: {}", details),
             },
-            Self::IOWriteError(f) => match f.to_str() {
-                Some(ff) => format!("An error occured while writing the following file: {}", ff),
-                None => "An error occured while writing a file".to_string(),
+            Self::IOWriteError { path, details } => match path.to_str() {
+                Some(f) => format!("An error occured while writing the file {}: {}", f, details),
+                None => format!("An error occured while writing a file: {}", details),
             },
             Self::IOError(s) => s.to_string(),
-            Self::ConfigError(e) => e.to_string(),
+            Self::HookError { command, details } => {
+                format!("The post-write hook '{}' failed: {}", command, details)
+            }
+            Self::ExecNotPermittedError { command, reason } => {
+                format!("Running '{}' is not permitted: {}", command, reason)
+            }
+            Self::LimitExceededError(s) => {
+                format!("{} (raise the limit in config.toml or use --force)", s)
+            }
+            Self::BinaryFileError(path) => {
+                format!("The file {} is no text file", path.display())
+            }
+            // The config error adds its own error code
+            Self::ConfigError(e) => return write!(f, "{}", e),
             Self::MultiError(errors) => format!(
                 "Multiple errors occured:\n{}",
                 errors
@@ -43,7 +105,7 @@ impl Display for MDPError {
                     .join("\n")
             ),
         };
-        write!(f, "{}", msg)
+        write!(f, "[{}] {}", self.code(), msg)
     }
 }
 
@@ -52,22 +114,43 @@ impl Error for MDPError {}
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConfigError {
     IOError,
-    InvalidSearchTermError,
+    InvalidSearchTermError(String),
     IncompatibleConfigError,
+    InvalidDateError(String),
+    /// Options which can not be combined, with a message how to resolve the conflict
+    InvalidOptionsError(String),
     UnkownError,
 }
 
+impl ConfigError {
+    /// Stable error code, see [`super::explain_error`]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::IOError => "MDP101",
+            Self::InvalidSearchTermError(_) => "MDP102",
+            Self::IncompatibleConfigError => "MDP103",
+            Self::InvalidDateError(_) => "MDP104",
+            Self::InvalidOptionsError(_) => "MDP105",
+            Self::UnkownError => "MDP199",
+        }
+    }
+}
+
 impl fmt::Display for ConfigError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match self {
-            Self::IOError => "An IO error occured while processing the configuration",
-            Self::InvalidSearchTermError => "One of the provided search terms is invalid",
+            Self::IOError => "An IO error occured while processing the configuration".to_string(),
+            Self::InvalidSearchTermError(term) => {
+                format!("The provided search term '{}' is invalid", term)
+            }
             Self::IncompatibleConfigError => {
-                "The provided configuration is incompatible with the command"
+                "The provided configuration is incompatible with the command".to_string()
             }
-            Self::UnkownError => "An unknown error occured",
+            Self::InvalidDateError(date) => format!("The provided date '{}' is invalid", date),
+            Self::InvalidOptionsError(msg) => msg.to_owned(),
+            Self::UnkownError => "An unknown error occured".to_string(),
         };
-        write!(f, "{}", msg)
+        write!(f, "[{}] {}", self.code(), msg)
     }
 }
 
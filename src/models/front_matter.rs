@@ -0,0 +1,50 @@
+use chrono::NaiveDate;
+
+use super::Section;
+
+/// Metadata of a file given in a leading YAML block:
+///
+/// ```markdown
+/// ---
+/// title: Project X
+/// date: 2024-03-01
+/// tags: [work, projectX]
+/// aliases: [px]
+/// ---
+///
+/// # 2024-03-01
+/// ```
+///
+/// The tags apply to all top level sections of the file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub date: Option<NaiveDate>,
+    /// Tag names (without the leading `@`)
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    /// Number of lines of the block including the delimiters
+    pub line_count: usize,
+}
+
+impl FrontMatter {
+    /// Returns the content following the front matter block
+    pub fn body<'c>(&self, content: &'c str) -> &'c str {
+        let mut rest = content;
+        for _ in 0..self.line_count {
+            rest = rest.split_once('\n').map_or("", |(_, r)| r);
+        }
+        rest
+    }
+
+    /// Adds the tags to the given (top level) sections, unless they are tagged already
+    pub fn tag_sections(&self, sections: &mut [Section]) {
+        for section in sections {
+            for tag in &self.tags {
+                if !section.tags.contains(tag) {
+                    section.tags.push(tag.to_owned());
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,17 @@
+mod error_catalog;
 mod errors;
+mod front_matter;
+mod origin;
 mod sections;
+mod span;
 mod token;
 mod traits;
 
+pub use error_catalog::*;
 pub use errors::*;
+pub use front_matter::*;
+pub use origin::*;
 pub use sections::*;
+pub use span::*;
 pub use token::*;
 pub use traits::*;
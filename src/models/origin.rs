@@ -0,0 +1,118 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use super::Token;
+
+/// Location of a token or section in the Markdown input
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Origin {
+    pub file: Option<PathBuf>,
+    /// Line number (starting at 1)
+    pub line: usize,
+    /// Column (starting at 1), counted in characters
+    pub column: usize,
+}
+
+impl Origin {
+    pub fn new(file: Option<&Path>, line: usize, column: usize) -> Self {
+        Self {
+            file: file.map(Path::to_path_buf),
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}", file.display(), self.line, self.column),
+            None => write!(f, "{}:{}", self.line, self.column),
+        }
+    }
+}
+
+/// Annotate a line based token stream (one `Token::Newline` per line) with the origin of
+/// every token. Tokens spanning multiple lines (e.g. tables) contain the line breaks
+/// between them in their Markdown representation.
+///
+/// Note: The column is computed from the Markdown representation of the preceding tokens
+/// on the same line, i.e. it may be slightly off if the input contained redundant
+/// whitespace (e.g. `TODO:   task`).
+pub fn annotate_origins<'a>(tokens: Vec<Token<'a>>, file: Option<&Path>) -> Vec<(Token<'a>, Origin)> {
+    let mut origins = OriginTracker::new(file);
+    tokens.into_iter().map(|t| origins.annotate(t)).collect()
+}
+
+/// Annotates the tokens of a line based token stream one after another, like
+/// [`annotate_origins`] (e.g. while they are tokenized).
+#[derive(Clone, Debug)]
+pub struct OriginTracker {
+    file: Option<PathBuf>,
+    line: usize,
+    column: usize,
+}
+
+impl OriginTracker {
+    pub fn new(file: Option<&Path>) -> Self {
+        Self {
+            file: file.map(Path::to_path_buf),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// The token with its origin, tokens have to be passed in the order of the input
+    pub fn annotate<'a>(&mut self, token: Token<'a>) -> (Token<'a>, Origin) {
+        let origin = Origin {
+            file: self.file.clone(),
+            line: self.line,
+            column: self.column,
+        };
+        let markdown = token.to_markdown_string();
+        match markdown.rsplit_once('\n') {
+            Some((before, last_line)) => {
+                self.line += before.matches('\n').count() + 1;
+                self.column = last_line.chars().count() + 1;
+            }
+            None => self.column += markdown.chars().count(),
+        }
+        (token, origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_annotate_origins() {
+        let tokens = vec![
+            Token::Blank,
+            Token::Newline,
+            Token::Text("Meeting with "),
+            Token::Tag("roger"),
+            Token::Newline,
+        ];
+        let file = Path::new("diary.md");
+
+        let origins = annotate_origins(tokens, Some(file))
+            .into_iter()
+            .map(|(_, o)| (o.line, o.column))
+            .collect::<Vec<_>>();
+        assert_eq!(origins, vec![(1, 1), (1, 1), (2, 1), (2, 14), (2, 20)]);
+    }
+
+    #[test]
+    fn test_display_origin() {
+        assert_eq!(
+            Origin::new(Some(Path::new("diary.md")), 3, 7).to_string(),
+            "diary.md:3:7"
+        );
+        assert_eq!(Origin::new(None, 3, 7).to_string(), "3:7");
+    }
+}
@@ -2,19 +2,211 @@ use std::fmt::Display;
 
 use chrono::NaiveDate;
 
-use super::Token;
+use super::{Origin, Token};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Section<'a> {
     pub title: Token<'a>,
     pub section_type: SectionType,
     pub tags: Vec<String>,
-    pub date: NaiveDate,
+    /// The day of the section, the first one if it spans several days. `None` for sections
+    /// kept without a date (see [`DatePolicy::None`])
+    ///
+    /// [`DatePolicy::None`]: crate::markdown::DatePolicy::None
+    pub date: Option<NaiveDate>,
+    /// The last day of a section spanning several days (e.g. `# 2023-10-10 - 2023-10-12`)
+    pub date_end: Option<NaiveDate>,
     pub content: Vec<Token<'a>>,
     pub subsections: Vec<Section<'a>>,
+    /// Location of the section title
+    pub origin: Origin,
 }
 
 impl<'a> Section<'a> {
+    /// The last day of the section, its date unless it spans several days
+    pub fn last_date(&self) -> Option<NaiveDate> {
+        self.date_end.or(self.date)
+    }
+
+    /// Identifies the section across runs (as long as its file name, date and title stay
+    /// the same), e.g. `diary.md:2024-05-20 Meetup`
+    pub fn id(&self) -> String {
+        let file_name = self
+            .origin
+            .file
+            .as_ref()
+            .and_then(|f| f.file_name())
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match (self.section_type.is_top_level(), self.date) {
+            (true, Some(_)) => format!("{}:{}", file_name, self.date_string()),
+            (false, Some(_)) => format!(
+                "{}:{} {}",
+                file_name,
+                self.date_string(),
+                self.title_string()
+            ),
+            (_, None) => format!("{}:{}", file_name, self.title_string()),
+        }
+    }
+
+    /// The day of the section, or the first and last day like in the heading of a section
+    /// spanning several days (e.g. `2023-10-10 - 2023-10-12`), empty if it has no date
+    pub fn date_string(&self) -> String {
+        match (self.date, self.date_end) {
+            (Some(date), Some(end)) => format!("{} - {}", date, end),
+            (Some(date), None) => date.to_string(),
+            (None, _) => String::new(),
+        }
+    }
+
+    /// Returns the title without the heading markup (e.g. `Meeting` for `## Meeting`)
+    pub fn title_string(&self) -> String {
+        match &self.title {
+            Token::HeadingH1(tokens)
+            | Token::HeadingH2(tokens)
+            | Token::HeadingH3(tokens)
+            | Token::HeadingH4(tokens)
+            | Token::HeadingH5(tokens)
+            | Token::HeadingH6(tokens)
+            | Token::ListItem {
+                content: tokens, ..
+            } => tokens
+                .iter()
+                .map(|t| t.to_markdown_string())
+                .collect::<String>()
+                .trim()
+                .to_string(),
+            t => t.to_markdown_string().trim().to_string(),
+        }
+    }
+
+    /// Returns the title as plain text on a single line, without its dates (the separator of
+    /// a range of dates included) and tags (e.g. `Trip` for `# 2024-05-03 - 2024-05-04 Trip
+    /// @travel`).
+    pub fn plain_title(&self) -> String {
+        let tokens = match self.title.children() {
+            [] => std::slice::from_ref(&self.title),
+            children => children,
+        };
+        let is_date = |i: usize| matches!(tokens.get(i), Some(Token::Date(_)));
+        tokens
+            .iter()
+            .enumerate()
+            .filter(|(i, t)| match t {
+                Token::Date(_) | Token::Tag(_) => false,
+                Token::Text(_) => !(*i > 0 && is_date(i - 1) && is_date(i + 1)),
+                _ => true,
+            })
+            .map(|(_, t)| t.to_plain_string())
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the content tokens together with their origin.
+    ///
+    /// The line numbers are derived from the origin of the section title and the line
+    /// breaks in the content (`Token::Newline`s and tokens spanning multiple lines).
+    pub fn content_with_origins(&self) -> Vec<(&Token<'a>, Origin)> {
+        let mut line = self.origin.line;
+        let mut column = self.title.to_markdown_string().chars().count() + 1;
+
+        self.content
+            .iter()
+            .map(|token| {
+                let origin = Origin {
+                    file: self.origin.file.clone(),
+                    line,
+                    column,
+                };
+                let markdown = token.to_markdown_string();
+                match markdown.rsplit_once('\n') {
+                    Some((before, last_line)) => {
+                        line += before.matches('\n').count() + 1;
+                        column = last_line.chars().count() + 1;
+                    }
+                    None => column += markdown.chars().count(),
+                }
+                (token, origin)
+            })
+            .collect()
+    }
+
+    /// Returns the paragraphs of the content (without subsections) as plain text.
+    ///
+    /// Paragraphs are separated by blank lines, the lines of a paragraph are joined and
+    /// whitespace is collapsed so that the fragments of the inline tokens (e.g. `" ("`
+    /// and `")."` around a link) are merged into clean strings.
+    pub fn paragraphs(&self) -> Vec<String> {
+        let mut paragraphs = vec![];
+        let mut current = String::new();
+        let mut previous_was_newline = false;
+
+        for token in &self.content {
+            if token == &Token::Newline {
+                if previous_was_newline {
+                    paragraphs.push(std::mem::take(&mut current));
+                } else {
+                    current.push(' ');
+                }
+                previous_was_newline = true;
+            } else {
+                current += &token.to_plain_string();
+                previous_was_newline = false;
+            }
+        }
+        paragraphs.push(current);
+
+        paragraphs
+            .iter()
+            .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|p| !p.is_empty())
+            .collect()
+    }
+
+    /// Returns the title and the paragraphs of the content (without subsections) as plain
+    /// text, see [`Section::paragraphs`].
+    pub fn plain_text(&self) -> String {
+        let mut parts = vec![self.title.to_plain_string().trim().to_string()];
+        parts.extend(self.paragraphs());
+        parts.retain(|p| !p.is_empty());
+        parts.join("\n\n")
+    }
+
+    /// Returns the hashtags (`#topic`) in the title and the content (without subsections).
+    pub fn hashtags(&self) -> Vec<String> {
+        fn collect(tokens: &[Token], hashtags: &mut Vec<String>) {
+            for token in tokens {
+                match token {
+                    Token::Hashtag(s) => hashtags.push(s.to_string()),
+                    t => collect(t.children(), hashtags),
+                }
+            }
+        }
+
+        let mut hashtags = vec![];
+        collect(std::slice::from_ref(&self.title), &mut hashtags);
+        collect(&self.content, &mut hashtags);
+        hashtags
+    }
+
+    /// Returns the attributes (`Key:: value` or `Key: value`) of the content (without subsections) as
+    /// trimmed plain text names and values.
+    pub fn attributes(&self) -> Vec<(String, String)> {
+        self.content
+            .iter()
+            .filter_map(|t| match t {
+                Token::Attribute { name, value, .. } => {
+                    let value: String = value.iter().map(|t| t.to_plain_string()).collect();
+                    Some((name.trim().to_string(), value.trim().to_string()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn contains_tag(&self, tag: String) -> bool {
         if self.tags.contains(&tag) {
             return true;
@@ -43,10 +235,171 @@ impl<'a> Display for Section<'a> {
     }
 }
 
+/// Merges top level sections with the same date (e.g. of different files) into the first
+/// one of them.
+///
+/// The subsections of the merged sections are appended in order and keep their origin,
+/// the tags are combined. The content directly below the date is appended as well, but
+/// its origin is only known for the first section.
+pub fn merge_days(sections: Vec<Section>) -> Vec<Section> {
+    let mut merged: Vec<Section> = vec![];
+
+    for section in sections {
+        let day = match section.section_type.is_top_level() {
+            true => merged.iter_mut().find(|s| {
+                s.section_type.is_top_level()
+                    && s.date == section.date
+                    && s.date_end == section.date_end
+            }),
+            false => None,
+        };
+        let Some(day) = day else {
+            merged.push(section);
+            continue;
+        };
+
+        if section
+            .content
+            .iter()
+            .any(|t| !matches!(t, Token::Newline | Token::Blank))
+        {
+            day.content.extend(section.content);
+        }
+        for tag in section.tags {
+            if !day.tags.contains(&tag) {
+                day.tags.push(tag);
+            }
+        }
+        day.subsections.extend(section.subsections);
+    }
+
+    merged
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SectionType {
     H1,
     H2,
     H3,
     H4,
+    H5,
+    H6,
+    /// Outline block at the given nesting level (starting at 1)
+    Block(usize),
+}
+
+impl SectionType {
+    /// Whether the section is a root section (i.e. the one containing the date)
+    pub fn is_top_level(&self) -> bool {
+        matches!(self, SectionType::H1 | SectionType::Block(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        let section = Section {
+            title: Token::HeadingH2(vec![Token::Text("Meeting")]),
+            section_type: SectionType::H2,
+            tags: vec![],
+            date: NaiveDate::from_ymd_opt(2022, 11, 3),
+            date_end: None,
+            content: vec![
+                Token::Newline,
+                Token::Newline,
+                Token::Text("In the morning i had a meeting with "),
+                Token::Tag("roger"),
+                Token::Text(" ("),
+                Token::Email("roger.example@gmail.com"),
+                Token::Text(")."),
+                Token::Newline,
+                Token::Text("It took  long."),
+                Token::Newline,
+                Token::Newline,
+                Token::Newline,
+                Token::Bold(vec![Token::Text("Next")]),
+                Token::Text(" paragraph"),
+                Token::Newline,
+            ],
+            subsections: vec![],
+            origin: Origin::default(),
+        };
+
+        assert_eq!(
+            section.paragraphs(),
+            vec![
+                "In the morning i had a meeting with roger (roger.example@gmail.com). It took long.",
+                "Next paragraph",
+            ]
+        );
+        assert_eq!(
+            section.plain_text(),
+            "Meeting\n\nIn the morning i had a meeting with roger (roger.example@gmail.com). \
+            It took long.\n\nNext paragraph"
+        );
+    }
+
+    #[test]
+    fn test_plain_title() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+        let section = Section {
+            title: Token::HeadingH1(vec![
+                Token::Date(date(3)),
+                Token::Text(" - "),
+                Token::Date(date(4)),
+                Token::Text(" Trip to "),
+                Token::Bold(vec![Token::Text("Rome")]),
+                Token::Text("\t"),
+                Token::Tag("travel"),
+            ]),
+            section_type: SectionType::H1,
+            tags: vec!["travel".to_string()],
+            date: Some(date(3)),
+            date_end: Some(date(4)),
+            content: vec![],
+            subsections: vec![],
+            origin: Origin::default(),
+        };
+        assert_eq!(section.plain_title(), "Trip to Rome");
+    }
+
+    #[test]
+    fn test_merge_days() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        let section = |day, section_type, file: &str, tags: &[&str], subsections| Section {
+            title: Token::Blank,
+            section_type,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            date: Some(date(day)),
+            date_end: None,
+            content: vec![Token::Newline],
+            subsections,
+            origin: Origin::new(Some(std::path::Path::new(file)), 1, 1),
+        };
+        let meeting = |file| section(1, SectionType::H2, file, &[], vec![]);
+
+        let merged = merge_days(vec![
+            section(1, SectionType::H1, "a.md", &["work"], vec![meeting("a.md")]),
+            section(2, SectionType::H1, "a.md", &[], vec![]),
+            section(1, SectionType::H1, "b.md", &["work", "home"], vec![meeting("b.md")]),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].tags, vec!["work", "home"]);
+        assert_eq!(merged[0].content, vec![Token::Newline]);
+        assert_eq!(
+            merged[0]
+                .subsections
+                .iter()
+                .map(|s| s.origin.to_string())
+                .collect::<Vec<_>>(),
+            vec!["a.md:1:1", "b.md:1:1"]
+        );
+        assert_eq!(merged[1].date, Some(date(2)));
+    }
 }
@@ -0,0 +1,178 @@
+use std::ops::Range;
+
+use super::{Token, TokenType};
+
+/// The kind of a token and the bytes of the Markdown input it was parsed from, e.g. to
+/// highlight the syntax of mdp (tags, tasks, dates, ...) in an editor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenSpan {
+    pub kind: TokenType,
+    /// Byte range within the input, including the markup (e.g. the `@` of a tag or the
+    /// `# ` of a heading)
+    pub range: Range<usize>,
+}
+
+/// The spans of the tokens parsed from `markdown` and of all tokens nested within them, in
+/// the order of the input (a token before the tokens nested within it). Blank lines and
+/// line breaks have no span.
+///
+/// The tokens are rendered back to Markdown and aligned with the input, so whitespace
+/// normalized by the tokenizer (e.g. `TODO:   Task`), keywords in another case and setext
+/// headings are mapped to the bytes they were parsed from as well.
+pub fn token_spans(markdown: &str, tokens: &[Token<'_>]) -> Vec<TokenSpan> {
+    let mut rendered = vec![];
+    let mut rendered_markdown = String::new();
+    for token in tokens {
+        collect_rendered(token, rendered_markdown.len(), &mut rendered);
+        rendered_markdown += &token.to_markdown_string();
+    }
+
+    let alignment = Alignment::new(&rendered_markdown, markdown);
+    rendered
+        .into_iter()
+        .map(|(kind, range)| TokenSpan {
+            kind,
+            range: alignment.source_range(range),
+        })
+        .collect()
+}
+
+/// Collects the kind of the token and the tokens nested within it together with their
+/// byte range in the rendered Markdown, the token starting at `offset`
+fn collect_rendered(token: &Token, offset: usize, rendered: &mut Vec<(TokenType, Range<usize>)>) {
+    if matches!(token, Token::Blank | Token::Newline) {
+        return;
+    }
+    let markdown = token.to_markdown_string();
+    rendered.push((token.token_type(), offset..offset + markdown.len()));
+    // The children are rendered in order within the markup of the token
+    let mut position = 0;
+    for child in token.children() {
+        let child_markdown = child.to_markdown_string();
+        let start = markdown[position..]
+            .find(&child_markdown)
+            .map_or(position, |i| position + i);
+        collect_rendered(child, offset + start, rendered);
+        position = start + child_markdown.len();
+    }
+}
+
+/// Maps the bytes of the rendered Markdown to the bytes of the input it was parsed from
+struct Alignment {
+    /// Input position of the start of each rendered byte
+    starts: Vec<usize>,
+    /// Input position of the end of each rendered byte
+    ends: Vec<usize>,
+    source_len: usize,
+}
+
+impl Alignment {
+    fn new(rendered: &str, source: &str) -> Self {
+        let mut starts = vec![0; rendered.len()];
+        let mut ends = vec![0; rendered.len()];
+        let mut map = |range: Range<usize>, start: usize, end: usize| {
+            starts[range.clone()].fill(start);
+            ends[range].fill(end);
+        };
+        let source_char = |i: usize| source[i..].chars().next();
+        let rendered_char = |i: usize| rendered[i..].chars().next();
+        let is_space = |c: char| c.is_whitespace() && c != '\n';
+
+        let (mut r, mut s) = (0, 0);
+        while let Some(rc) = rendered_char(r) {
+            let r_next = r + rc.len_utf8();
+            match source_char(s) {
+                Some(sc) if sc == rc || sc.to_lowercase().eq(rc.to_lowercase()) => {
+                    map(r..r_next, s, s + sc.len_utf8());
+                    s += sc.len_utf8();
+                    r = r_next;
+                }
+                // Whitespace (or a `\r`) removed by the tokenizer
+                Some(sc) if is_space(sc) => s += sc.len_utf8(),
+                // Whitespace added by the tokenizer
+                _ if is_space(rc) => {
+                    map(r..r_next, s, s);
+                    r = r_next;
+                }
+                // Markup written differently (e.g. a setext heading), the rest of the line
+                // of both is mapped to the rest of the line of the input
+                Some(_) => {
+                    let r_end = rendered[r..].find('\n').map_or(rendered.len(), |i| r + i);
+                    let s_end = source[s..].find('\n').map_or(source.len(), |i| s + i);
+                    map(r..r_end, s, s_end);
+                    r = r_end;
+                    s = s_end;
+                }
+                None => {
+                    map(r..rendered.len(), s, s);
+                    break;
+                }
+            }
+        }
+        Self {
+            starts,
+            ends,
+            source_len: source.len(),
+        }
+    }
+
+    fn source_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self
+            .starts
+            .get(range.start)
+            .copied()
+            .unwrap_or(self.source_len);
+        match range.is_empty() {
+            true => start..start,
+            false => start..self.ends[range.end - 1].max(start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{markdown::MDPMarkdownTokenizer, models::MarkdownTokenizer};
+
+    fn spans(markdown: &str) -> Vec<(TokenType, &str)> {
+        let tokenizer = MDPMarkdownTokenizer {
+            any_case_task_keywords: true,
+            ..Default::default()
+        };
+        tokenizer
+            .tokenize_with_spans(markdown)
+            .into_iter()
+            .map(|s| (s.kind, &markdown[s.range]))
+            .collect()
+    }
+
+    #[test]
+    fn test_token_spans() {
+        assert_eq!(
+            spans("#   2024-05-01 Trip @travel\r\ntodo:  Pack **[[List]]**\n"),
+            vec![
+                (TokenType::HeadingH1, "#   2024-05-01 Trip @travel\r"),
+                (TokenType::Date, "2024-05-01"),
+                (TokenType::Text, " Trip "),
+                (TokenType::Tag, "@travel"),
+                (TokenType::Text, "\r"),
+                (TokenType::Task, "todo:  Pack **[[List]]**"),
+                (TokenType::Text, "Pack "),
+                (TokenType::Bold, "**[[List]]**"),
+                (TokenType::Link, "[[List]]"),
+            ]
+        );
+        assert_eq!(
+            spans("Trip\n====\n- [ ] See [docs](https://x.org)\n"),
+            vec![
+                (TokenType::HeadingH1, "Trip"),
+                (TokenType::Text, "Trip"),
+                (TokenType::Task, "- [ ] See [docs](https://x.org)"),
+                (TokenType::Text, "See "),
+                (TokenType::MarkdownExternalLink, "[docs](https://x.org)"),
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Token<'a> {
@@ -10,6 +11,10 @@ pub enum Token<'a> {
 
     BlockRef(&'a str),
     Email(&'a str),
+    /// `![[Note#Heading]]`: the referenced section is inlined when rendered
+    Embed(&'a str),
+    /// `[^1]`: reference to the footnote with the label (without brackets and caret)
+    FootnoteRef(&'a str),
     Hashtag(&'a str),
     Latex(&'a str),
     Link(&'a str),
@@ -30,11 +35,41 @@ pub enum Token<'a> {
     HeadingH2(Vec<Token<'a>>),
     HeadingH3(Vec<Token<'a>>),
     HeadingH4(Vec<Token<'a>>),
+    HeadingH5(Vec<Token<'a>>),
+    HeadingH6(Vec<Token<'a>>),
+    /// Table spanning multiple lines, consists of `TableRow`s (including the delimiter row
+    /// below the header)
+    Table(Vec<Token<'a>>),
+    /// Row of a table (`| a | b |`), consists of `TableCell`s
+    TableRow(Vec<Token<'a>>),
+    /// Content of a table cell including the whitespace around it
+    TableCell(Vec<Token<'a>>),
 
+    /// Item of a (nested) list; `prefix` is the indentation and the list marker (e.g.
+    /// `"  - "` or `"1. "`), `level` the nesting depth starting at 0
+    ListItem {
+        prefix: &'a str,
+        level: usize,
+        content: Vec<Token<'a>>,
+    },
+    /// `Name:: value` or (if enabled, directly below a heading) `Name: value`; `separator`
+    /// is the colon(s) as written
     Attribute {
         name: &'a str,
+        separator: &'a str,
         value: Vec<Token<'a>>,
     },
+    /// `[^1]: Text` at the start of a line, defining the footnote with the label
+    FootnoteDefinition {
+        label: &'a str,
+        content: Vec<Token<'a>>,
+    },
+    /// `SNOOZED UNTIL 2024-06-10` or `@snooze(2024-06-10)`, hiding the task containing it
+    /// until the date; `markup` is the annotation as written
+    Snooze {
+        markup: &'a str,
+        date: NaiveDate,
+    },
     Image {
         alt: &'a str,
         url: &'a str,
@@ -50,6 +85,9 @@ pub enum Token<'a> {
     Task {
         content: Vec<Token<'a>>,
         status: TaskStatus,
+        /// List item prefix (e.g. `- `) of a task written as checkbox (`- [ ] Task`), `None`
+        /// for tasks written with a keyword (`TODO: Task`)
+        checkbox: Option<&'a str>,
     },
 }
 
@@ -62,6 +100,8 @@ impl<'a> Token<'a> {
 
             Token::BlockRef(s) => format!("<BlockRef: '{}'>", s),
             Token::Email(s) => format!("<Email: '{}'>", s),
+            Token::Embed(s) => format!("<Embed: '{}'>", s),
+            Token::FootnoteRef(s) => format!("<FootnoteRef: '{}'>", s),
             Token::Hashtag(s) => format!("<Hashtag: '{}'>", s),
             Token::Latex(s) => format!("<Latex: '{}'>", s),
             Token::Link(s) => format!("<Link: '{}'>", s),
@@ -72,6 +112,7 @@ impl<'a> Token<'a> {
             Token::TripleBacktick(s) => format!("<TripleBacktick: '{}'>", s),
 
             Token::Date(date) => format!("<Date: '{}'>", date.format("%Y-%m-%d")),
+            Token::Snooze { date, .. } => format!("<Snooze: '{}'>", date.format("%Y-%m-%d")),
 
             Token::BlockQuote(tokens) => {
                 format!(
@@ -118,14 +159,65 @@ impl<'a> Token<'a> {
                     Self::child_tokens_as_debug_string(tokens)
                 )
             }
+            Token::HeadingH5(tokens) => {
+                format!(
+                    "<HeadingH5: '{}'>",
+                    Self::child_tokens_as_debug_string(tokens)
+                )
+            }
+            Token::HeadingH6(tokens) => {
+                format!(
+                    "<HeadingH6: '{}'>",
+                    Self::child_tokens_as_debug_string(tokens)
+                )
+            }
+            Token::Table(tokens) => {
+                format!("<Table: '{}'>", Self::child_tokens_as_debug_string(tokens))
+            }
+            Token::TableRow(tokens) => {
+                format!(
+                    "<TableRow: '{}'>",
+                    Self::child_tokens_as_debug_string(tokens)
+                )
+            }
+            Token::TableCell(tokens) => {
+                format!(
+                    "<TableCell: '{}'>",
+                    Self::child_tokens_as_debug_string(tokens)
+                )
+            }
 
-            Token::Attribute { name, value } => {
+            Token::ListItem {
+                prefix,
+                level,
+                content,
+            } => {
                 format!(
-                    "<Attribute: '{}::{}'>",
+                    "<ListItem({}, {}): '{}'>",
+                    prefix.trim(),
+                    level,
+                    Self::child_tokens_as_debug_string(content)
+                )
+            }
+            Token::Attribute {
+                name,
+                separator,
+                value,
+            } => {
+                format!(
+                    "<Attribute: '{}{}{}'>",
                     name,
+                    separator,
                     Self::child_tokens_as_debug_string(value)
                 )
             }
+            Token::FootnoteDefinition { label, content } => {
+                format!(
+                    "<FootnoteDefinition({}): '{}'>",
+                    label,
+                    Self::child_tokens_as_debug_string(content)
+                )
+            }
             Token::Image { alt, url } => format!("<Image: '[{}]({})'>", alt, url),
             Token::MarkdownExternalLink { title, url } => {
                 format!("<MarkdownExternalLink: '[{}]({})'>", title, url)
@@ -133,7 +225,9 @@ impl<'a> Token<'a> {
             Token::MarkdownInternalLink { label, link } => {
                 format!("<MarkdownInternalLink: '[{}]({})'>", label, link)
             }
-            Token::Task { content, status } => format!(
+            Token::Task {
+                content, status, ..
+            } => format!(
                 "<Task({}): {}>",
                 status,
                 Self::child_tokens_as_debug_string(content),
@@ -149,13 +243,21 @@ impl<'a> Token<'a> {
     }
 
     pub fn to_markdown_string(&self) -> String {
-        match self {
+        self.render(&PlainStyler)
+    }
+
+    /// Renders the token as Markdown like [`Token::to_markdown_string`], decorating it and
+    /// all nested tokens with `styler`.
+    pub fn render(&self, styler: &dyn TokenStyler) -> String {
+        let markdown = match self {
             Token::Blank => "".to_string(),
             Token::HRule => "---".to_string(),
             Token::Newline => "\n".to_string(),
 
             Token::BlockRef(s) => format!("(({}))", s),
             Token::Email(s) => s.to_string(),
+            Token::Embed(s) => format!("![[{}]]", s),
+            Token::FootnoteRef(s) => format!("[^{}]", s),
             Token::Hashtag(s) => format!("#{}", s),
             Token::Latex(s) => format!("$${}$$", s),
             Token::Link(s) => format!("[[{}]]", s),
@@ -166,58 +268,180 @@ impl<'a> Token<'a> {
             Token::TripleBacktick(s) => format!("```{}```", s),
 
             Token::Date(date) => format!("{}", date.format("%Y-%m-%d")),
+            Token::Snooze { markup, .. } => markup.to_string(),
 
             Token::BlockQuote(tokens) => {
-                format!("> {}", Self::child_tokens_as_markdown_string(tokens),)
+                format!("> {}", Self::render_children(tokens, styler),)
             }
             Token::Bold(tokens) => {
-                format!("**{}**", Self::child_tokens_as_markdown_string(tokens),)
+                format!("**{}**", Self::render_children(tokens, styler),)
             }
             Token::Highlight(tokens) => {
-                format!("^^{}^^", Self::child_tokens_as_markdown_string(tokens),)
+                format!("^^{}^^", Self::render_children(tokens, styler),)
             }
             Token::Italic(tokens) => {
-                format!("*{}*", Self::child_tokens_as_markdown_string(tokens),)
+                format!("*{}*", Self::render_children(tokens, styler),)
             }
             Token::Strike(tokens) => {
-                format!("~~{}~~", Self::child_tokens_as_markdown_string(tokens),)
+                format!("~~{}~~", Self::render_children(tokens, styler),)
             }
             Token::HeadingH1(tokens) => {
-                format!("# {}", Self::child_tokens_as_markdown_string(tokens))
+                format!("# {}", Self::render_children(tokens, styler))
             }
             Token::HeadingH2(tokens) => {
-                format!("## {}", Self::child_tokens_as_markdown_string(tokens))
+                format!("## {}", Self::render_children(tokens, styler))
             }
             Token::HeadingH3(tokens) => {
-                format!("### {}", Self::child_tokens_as_markdown_string(tokens))
+                format!("### {}", Self::render_children(tokens, styler))
             }
             Token::HeadingH4(tokens) => {
-                format!("#### {}", Self::child_tokens_as_markdown_string(tokens))
+                format!("#### {}", Self::render_children(tokens, styler))
             }
+            Token::HeadingH5(tokens) => {
+                format!("##### {}", Self::render_children(tokens, styler))
+            }
+            Token::HeadingH6(tokens) => {
+                format!("###### {}", Self::render_children(tokens, styler))
+            }
+            Token::Table(rows) => rows
+                .iter()
+                .map(|r| r.render(styler))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Token::TableRow(cells) => {
+                let cells = cells
+                    .iter()
+                    .map(|c| c.render(styler))
+                    .collect::<Vec<String>>();
+                format!("|{}|", cells.join("|"))
+            }
+            Token::TableCell(tokens) => Self::render_children(tokens, styler),
 
-            Token::Attribute { name, value } => {
-                format!("{}::{}", name, Self::child_tokens_as_markdown_string(value))
+            Token::ListItem {
+                prefix, content, ..
+            } => {
+                format!("{}{}", prefix, Self::render_children(content, styler))
+            }
+            Token::Attribute {
+                name,
+                separator,
+                value,
+            } => {
+                format!(
+                    "{}{}{}",
+                    name,
+                    separator,
+                    Self::render_children(value, styler)
+                )
+            }
+            Token::FootnoteDefinition { label, content } => {
+                format!("[^{}]: {}", label, Self::render_children(content, styler))
             }
             Token::Image { alt, url } => format!("![{}]({})", alt, url),
             Token::MarkdownExternalLink { title, url } => format!("[{}]({})", title, url),
             Token::MarkdownInternalLink { label, link } => {
                 format!("[{}]({})", label, link)
             }
-            Token::Task { content, status } => format!(
-                "{}: {}",
+            Token::Task {
+                content,
                 status,
-                Self::child_tokens_as_markdown_string(content),
+                checkbox: Some(prefix),
+            } if status.checkbox_mark().is_some() => format!(
+                "{}{} {}",
+                prefix,
+                styler.style_task_status(status, status.checkbox()),
+                Self::render_children(content, styler),
             ),
+            Token::Task {
+                content,
+                status,
+                checkbox,
+            } => format!(
+                "{}{}: {}",
+                checkbox.unwrap_or_default(),
+                styler.style_task_status(status, status.to_string()),
+                Self::render_children(content, styler),
+            ),
+        };
+        styler.style_token(self, markdown)
+    }
+
+    /// Returns the text without any markup (e.g. `roger` for `@roger`, `Title` for
+    /// `[Title](url)`).
+    pub fn to_plain_string(&self) -> String {
+        match self {
+            Token::Blank | Token::HRule => "".to_string(),
+            Token::Newline => "\n".to_string(),
+
+            Token::BlockRef(s)
+            | Token::Email(s)
+            | Token::Embed(s)
+            | Token::FootnoteRef(s)
+            | Token::Hashtag(s)
+            | Token::Latex(s)
+            | Token::Link(s)
+            | Token::RawHyperlink(s)
+            | Token::SingleBacktick(s)
+            | Token::Tag(s)
+            | Token::Text(s)
+            | Token::TripleBacktick(s) => s.to_string(),
+
+            Token::Date(date) => format!("{}", date.format("%Y-%m-%d")),
+            Token::Snooze { markup, .. } => markup.to_string(),
+
+            Token::BlockQuote(tokens)
+            | Token::Bold(tokens)
+            | Token::Highlight(tokens)
+            | Token::Italic(tokens)
+            | Token::Strike(tokens)
+            | Token::HeadingH1(tokens)
+            | Token::HeadingH2(tokens)
+            | Token::HeadingH3(tokens)
+            | Token::HeadingH4(tokens)
+            | Token::HeadingH5(tokens)
+            | Token::HeadingH6(tokens)
+            | Token::ListItem {
+                content: tokens, ..
+            }
+            | Token::TableCell(tokens)
+            | Token::Task {
+                content: tokens, ..
+            } => Self::child_tokens_as_plain_string(tokens),
+
+            Token::Table(rows) => rows
+                .iter()
+                .map(|r| r.to_plain_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Token::TableRow(cells) => cells
+                .iter()
+                .map(|c| c.to_plain_string().trim().to_string())
+                .collect::<Vec<String>>()
+                .join(" "),
+
+            Token::Attribute { name, value, .. } => {
+                format!("{}: {}", name, Self::child_tokens_as_plain_string(value))
+            }
+            Token::FootnoteDefinition { label, content } => {
+                format!("{}: {}", label, Self::child_tokens_as_plain_string(content))
+            }
+            Token::Image { alt, .. } => alt.to_string(),
+            Token::MarkdownExternalLink { title, .. } => title.to_string(),
+            Token::MarkdownInternalLink { label, .. } => label.to_string(),
         }
     }
 
-    fn child_tokens_as_markdown_string(tokens: &[Token<'a>]) -> String {
+    fn child_tokens_as_plain_string(tokens: &[Token<'a>]) -> String {
         tokens
             .iter()
-            .map(|t| t.to_markdown_string())
+            .map(|t| t.to_plain_string())
             .collect::<String>()
     }
 
+    fn render_children(tokens: &[Token<'a>], styler: &dyn TokenStyler) -> String {
+        tokens.iter().map(|t| t.render(styler)).collect::<String>()
+    }
+
     pub fn token_type(&self) -> TokenType {
         match self {
             Token::Blank => TokenType::Blankline,
@@ -226,6 +450,8 @@ impl<'a> Token<'a> {
 
             Token::BlockRef(_) => TokenType::BlockRef,
             Token::Email(_) => TokenType::Email,
+            Token::Embed(_) => TokenType::Embed,
+            Token::FootnoteRef(_) => TokenType::FootnoteRef,
             Token::Hashtag(_) => TokenType::Hashtag,
             Token::Latex(_) => TokenType::Latex,
             Token::Link(_) => TokenType::Link,
@@ -236,6 +462,7 @@ impl<'a> Token<'a> {
             Token::TripleBacktick(_) => TokenType::TripleBacktick,
 
             Token::Date(_) => TokenType::Date,
+            Token::Snooze { .. } => TokenType::Snooze,
 
             Token::BlockQuote(_) => TokenType::BlockQuote,
             Token::Bold(_) => TokenType::Bold,
@@ -246,15 +473,84 @@ impl<'a> Token<'a> {
             Token::HeadingH2(_) => TokenType::HeadingH2,
             Token::HeadingH3(_) => TokenType::HeadingH3,
             Token::HeadingH4(_) => TokenType::HeadingH4,
+            Token::HeadingH5(_) => TokenType::HeadingH5,
+            Token::HeadingH6(_) => TokenType::HeadingH6,
+            Token::Table(_) => TokenType::Table,
+            Token::TableRow(_) => TokenType::TableRow,
+            Token::TableCell(_) => TokenType::TableCell,
 
+            Token::ListItem { .. } => TokenType::ListItem,
             Token::Attribute { .. } => TokenType::Attribute,
+            Token::FootnoteDefinition { .. } => TokenType::FootnoteDefinition,
             Token::Image { .. } => TokenType::Image,
-            Token::MarkdownExternalLink { .. } => TokenType::MarkdownInternalLink,
+            Token::MarkdownExternalLink { .. } => TokenType::MarkdownExternalLink,
             Token::MarkdownInternalLink { .. } => TokenType::MarkdownInternalLink,
             Token::Task { .. } => TokenType::Task,
         }
     }
 
+    /// Returns the tokens nested within this token (empty for leaf tokens).
+    pub fn children(&self) -> &[Token<'a>] {
+        match self {
+            Token::BlockQuote(tokens)
+            | Token::Bold(tokens)
+            | Token::Highlight(tokens)
+            | Token::Italic(tokens)
+            | Token::Strike(tokens)
+            | Token::HeadingH1(tokens)
+            | Token::HeadingH2(tokens)
+            | Token::HeadingH3(tokens)
+            | Token::HeadingH4(tokens)
+            | Token::HeadingH5(tokens)
+            | Token::HeadingH6(tokens)
+            | Token::Table(tokens)
+            | Token::TableRow(tokens)
+            | Token::TableCell(tokens)
+            | Token::ListItem {
+                content: tokens, ..
+            }
+            | Token::Attribute { value: tokens, .. }
+            | Token::FootnoteDefinition {
+                content: tokens, ..
+            }
+            | Token::Task {
+                content: tokens, ..
+            } => tokens,
+            _ => &[],
+        }
+    }
+
+    /// Returns the tokens nested within this token (`None` for leaf tokens) for modification.
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Token<'a>>> {
+        match self {
+            Token::BlockQuote(tokens)
+            | Token::Bold(tokens)
+            | Token::Highlight(tokens)
+            | Token::Italic(tokens)
+            | Token::Strike(tokens)
+            | Token::HeadingH1(tokens)
+            | Token::HeadingH2(tokens)
+            | Token::HeadingH3(tokens)
+            | Token::HeadingH4(tokens)
+            | Token::HeadingH5(tokens)
+            | Token::HeadingH6(tokens)
+            | Token::Table(tokens)
+            | Token::TableRow(tokens)
+            | Token::TableCell(tokens)
+            | Token::ListItem {
+                content: tokens, ..
+            }
+            | Token::Attribute { value: tokens, .. }
+            | Token::FootnoteDefinition {
+                content: tokens, ..
+            }
+            | Token::Task {
+                content: tokens, ..
+            } => Some(tokens),
+            _ => None,
+        }
+    }
+
     pub fn contains(&self, token: &Self) -> bool {
         match self {
             Token::BlockQuote(tokens)
@@ -266,7 +562,18 @@ impl<'a> Token<'a> {
             | Token::HeadingH2(tokens)
             | Token::HeadingH3(tokens)
             | Token::HeadingH4(tokens)
+            | Token::HeadingH5(tokens)
+            | Token::HeadingH6(tokens)
+            | Token::Table(tokens)
+            | Token::TableRow(tokens)
+            | Token::TableCell(tokens)
+            | Token::ListItem {
+                content: tokens, ..
+            }
             | Token::Attribute { value: tokens, .. }
+            | Token::FootnoteDefinition {
+                content: tokens, ..
+            }
             | Token::Task {
                 content: tokens, ..
             } => {
@@ -281,6 +588,39 @@ impl<'a> Token<'a> {
             t => t == token,
         }
     }
+
+    /// Whether the token is a heading (of any level)
+    pub fn is_heading(&self) -> bool {
+        matches!(
+            self,
+            Token::HeadingH1(_)
+                | Token::HeadingH2(_)
+                | Token::HeadingH3(_)
+                | Token::HeadingH4(_)
+                | Token::HeadingH5(_)
+                | Token::HeadingH6(_)
+        )
+    }
+
+    /// The date of the (last) snooze annotation within the token, e.g. of a task
+    pub fn snoozed_until(&self) -> Option<NaiveDate> {
+        self.children().iter().rev().find_map(|t| match t {
+            Token::Snooze { date, .. } => Some(*date),
+            t => t.snoozed_until(),
+        })
+    }
+
+    /// The language of a code block spanning multiple lines (the info string after the
+    /// opening fence, e.g. `rust` for ` ```rust `), `None` if it has none
+    pub fn code_language(&self) -> Option<&'a str> {
+        match self {
+            Token::TripleBacktick(s) => {
+                let (info, _) = s.split_once('\n')?;
+                info.split_whitespace().next()
+            }
+            _ => None,
+        }
+    }
 }
 
 impl<'a> Display for Token<'a> {
@@ -289,12 +629,33 @@ impl<'a> Display for Token<'a> {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Decorates the Markdown representation of tokens (e.g. with colors), see
+/// [`Token::render`]. Nothing is decorated by default.
+pub trait TokenStyler {
+    /// Decorate a token, `markdown` already contains its decorated children
+    fn style_token(&self, _token: &Token, markdown: String) -> String {
+        markdown
+    }
+
+    /// Decorate the status of a task (e.g. `TODO`)
+    fn style_task_status(&self, _status: &TaskStatus, markdown: String) -> String {
+        markdown
+    }
+}
+
+/// Renders tokens as they are
+pub struct PlainStyler;
+
+impl TokenStyler for PlainStyler {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskStatus {
     Todo,
-    TodoUntil(NaiveDate),
+    TodoUntil(Due),
     Doing,
     Review,
+    /// Delegated to the person (given without `@`) and waiting for them
+    Waiting(String),
     Done,
 }
 
@@ -302,20 +663,67 @@ impl From<&TaskStatus> for String {
     fn from(task_status: &TaskStatus) -> String {
         match task_status {
             TaskStatus::Todo => "TODO".to_owned(),
-            TaskStatus::TodoUntil(d) => format!("TODO UNTIL {}", d),
+            TaskStatus::TodoUntil(due) => format!("TODO UNTIL {}", due),
             TaskStatus::Doing => "DOING".to_owned(),
             TaskStatus::Review => "REVIEW".to_owned(),
+            TaskStatus::Waiting(person) => format!("WAITING(@{})", person),
             TaskStatus::Done => "DONE".to_owned(),
         }
     }
 }
 
+impl TaskStatus {
+    /// The character within the brackets of a checkbox (`- [x] Task`) with this status,
+    /// `None` if the status can not be written as checkbox
+    pub fn checkbox_mark(&self) -> Option<char> {
+        match self {
+            TaskStatus::Todo => Some(' '),
+            TaskStatus::Doing => Some('/'),
+            TaskStatus::Done => Some('x'),
+            TaskStatus::TodoUntil(_) | TaskStatus::Review | TaskStatus::Waiting(_) => None,
+        }
+    }
+
+    /// The checkbox (e.g. `[x]`) with this status
+    pub fn checkbox(&self) -> String {
+        format!("[{}]", self.checkbox_mark().unwrap_or(' '))
+    }
+}
+
 impl Display for TaskStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", String::from(self))
     }
 }
 
+/// The deadline of a `TODO UNTIL` task
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Due {
+    /// The day the task is due
+    pub date: NaiveDate,
+    /// The deadline as written if it is given relative to the date of the section of the
+    /// task (or to today), e.g. `tomorrow`, `friday` or `+3d`
+    pub expression: Option<String>,
+}
+
+impl From<NaiveDate> for Due {
+    fn from(date: NaiveDate) -> Self {
+        Self {
+            date,
+            expression: None,
+        }
+    }
+}
+
+impl Display for Due {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.expression {
+            Some(expression) => write!(f, "{}", expression),
+            None => write!(f, "{}", self.date),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TokenType {
     Blankline,
@@ -324,6 +732,8 @@ pub enum TokenType {
 
     BlockRef,
     Email,
+    Embed,
+    FootnoteRef,
     Hashtag,
     Latex,
     Link,
@@ -344,8 +754,16 @@ pub enum TokenType {
     HeadingH2,
     HeadingH3,
     HeadingH4,
+    HeadingH5,
+    HeadingH6,
+    Table,
+    TableRow,
+    TableCell,
 
+    ListItem,
     Attribute,
+    FootnoteDefinition,
+    Snooze,
     Image,
     MarkdownInternalLink,
     MarkdownExternalLink,
@@ -427,6 +845,68 @@ mod tests {
         ).to_string(), input);
     }
 
+    #[test]
+    fn test_plain_string() {
+        let token = Token::Task {
+            content: vec![
+                Token::Text("Call "),
+                Token::Tag("roger"),
+                Token::Text(" about "),
+                Token::MarkdownExternalLink {
+                    title: "the project",
+                    url: "https://example.com",
+                },
+                Token::Bold(vec![Token::Text(" today")]),
+            ],
+            status: TaskStatus::Todo,
+            checkbox: None,
+        };
+        assert_eq!(
+            token.to_plain_string(),
+            "Call roger about the project today"
+        );
+    }
+
+    #[test]
+    fn test_display_table() {
+        let row = |cells: Vec<&'static str>| {
+            Token::TableRow(
+                cells
+                    .into_iter()
+                    .map(|c| Token::TableCell(vec![Token::Text(c)]))
+                    .collect(),
+            )
+        };
+        let table = Token::Table(vec![
+            row(vec![" Name ", " Tag "]),
+            row(vec!["------", "-----"]),
+            row(vec![" Roger ", " @work "]),
+        ]);
+
+        assert_eq!(
+            table.to_string(),
+            "| Name | Tag |\n|------|-----|\n| Roger | @work |"
+        );
+        assert_eq!(
+            table.to_plain_string(),
+            "Name Tag\n------ -----\nRoger @work"
+        );
+    }
+
+    #[test]
+    fn test_display_list_item() {
+        let input = "  - Meeting @work";
+        assert_eq!(
+            Token::ListItem {
+                prefix: "  - ",
+                level: 1,
+                content: vec![Token::Text("Meeting "), Token::Tag("work")]
+            }
+            .to_string(),
+            input
+        );
+    }
+
     #[test]
     fn test_display_block_quote() {
         let input = "> This is a block quote [[link123]]";
@@ -489,6 +969,7 @@ mod tests {
         assert_eq!(
             Token::Attribute {
                 name: "Source",
+                separator: "::",
                 value: vec![Token::Text("some blog")],
             }
             .to_string(),
@@ -544,6 +1025,7 @@ mod tests {
             Token::Task {
                 content: vec![Token::Text("Get things done")],
                 status: TaskStatus::Todo,
+                checkbox: None,
             }
             .to_string(),
             input
@@ -554,9 +1036,32 @@ mod tests {
             Token::Task {
                 content: vec![Token::Text("Get things done")],
                 status: TaskStatus::Done,
+                checkbox: None,
             }
             .to_string(),
             input
         );
+
+        let checkbox = |status| Token::Task {
+            content: vec![Token::Text("Get things done")],
+            status,
+            checkbox: Some("  - "),
+        };
+        assert_eq!(
+            checkbox(TaskStatus::Todo).to_string(),
+            "  - [ ] Get things done"
+        );
+        assert_eq!(
+            checkbox(TaskStatus::Doing).to_string(),
+            "  - [/] Get things done"
+        );
+        assert_eq!(
+            checkbox(TaskStatus::Done).to_string(),
+            "  - [x] Get things done"
+        );
+        assert_eq!(
+            checkbox(TaskStatus::Review).to_string(),
+            "  - REVIEW: Get things done"
+        );
     }
 }
@@ -1,14 +1,89 @@
-use super::{MDPError, Section, Token};
+use std::path::Path;
 
-pub trait MarkdownTokenizer {
+use super::{annotate_origins, token_spans, MDPError, Origin, Section, Token, TokenSpan};
+
+/// Tokenizers are shared between the threads parsing the input files.
+pub trait MarkdownTokenizer: Send + Sync {
     /// Tokenize Markdown string into Markdown tokens
     fn tokenize<'a>(&self, markdown_string: &'a str) -> Result<Vec<Token<'a>>, MDPError>;
+
+    /// Tokenize Markdown string into Markdown tokens, skipping what can not be parsed.
+    ///
+    /// Returns the tokens which could be parsed together with the errors of the skipped
+    /// parts of the input.
+    fn tokenize_best_effort<'a>(&self, markdown_string: &'a str) -> (Vec<Token<'a>>, Vec<MDPError>) {
+        match self.tokenize(markdown_string) {
+            Ok(tokens) => (tokens, vec![]),
+            Err(MDPError::MultiError(errors)) => (vec![], errors),
+            Err(e) => (vec![], vec![e]),
+        }
+    }
+
+    /// Tokenize Markdown string into Markdown tokens, keeping what can not be parsed as
+    /// `Token::Text`.
+    ///
+    /// Returns the tokens together with the errors of the parts of the input kept as text.
+    fn tokenize_lenient<'a>(&self, markdown_string: &'a str) -> (Vec<Token<'a>>, Vec<MDPError>) {
+        self.tokenize_best_effort(markdown_string)
+    }
+
+    /// Tokenize Markdown string into Markdown tokens, failing on lines which can be parsed
+    /// but look like mistyped markup as well (e.g. `TODO : Task`, which is no task).
+    fn tokenize_strict<'a>(&self, markdown_string: &'a str) -> Result<Vec<Token<'a>>, MDPError> {
+        self.tokenize(markdown_string)
+    }
+
+    /// Tokenize Markdown string lazily into Markdown tokens, so the tokens of a large file
+    /// don't have to be kept in memory at once.
+    ///
+    /// Lines which can not be parsed are skipped like by [`Self::tokenize_best_effort`],
+    /// their errors are yielded in place of their tokens.
+    fn tokenize_iter<'a>(
+        &'a self,
+        markdown_string: &'a str,
+    ) -> Box<dyn Iterator<Item = Result<Token<'a>, MDPError>> + 'a> {
+        let (tokens, errors) = self.tokenize_best_effort(markdown_string);
+        let errors = errors.into_iter().map(Err);
+        Box::new(errors.chain(tokens.into_iter().map(Ok)))
+    }
+
+    /// Identifies the settings of the tokenizer which change the tokens, so that tokens
+    /// cached with other settings are not reused (see [`crate::commands::cache::TokenCache`]).
+    /// `None` if the tokens must not be cached.
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
+
+    /// Tokenize Markdown string into Markdown tokens and annotate each token with its origin
+    fn tokenize_with_origins<'a>(
+        &self,
+        markdown_string: &'a str,
+        file: Option<&Path>,
+    ) -> Result<Vec<(Token<'a>, Origin)>, MDPError> {
+        Ok(annotate_origins(self.tokenize(markdown_string)?, file))
+    }
+
+    /// Tokenize Markdown string leniently (see [`Self::tokenize_lenient`]) and return the
+    /// kind and byte range of every token, including nested ones (see [`token_spans`])
+    fn tokenize_with_spans(&self, markdown_string: &str) -> Vec<TokenSpan> {
+        let (tokens, _) = self.tokenize_lenient(markdown_string);
+        token_spans(markdown_string, &tokens)
+    }
 }
 
-pub trait SectionBuilder {
+/// Section builders are shared between the threads parsing the input files.
+pub trait SectionBuilder: Send + Sync {
     /// Create sections from Markdown tokens
     fn sections_from_tokens<'a>(
         &self,
         tokens: Vec<Token<'a>>,
+    ) -> Result<Vec<Section<'a>>, MDPError> {
+        self.sections_from_tokens_with_origins(annotate_origins(tokens, None))
+    }
+
+    /// Create sections from Markdown tokens annotated with their origin
+    fn sections_from_tokens_with_origins<'a>(
+        &self,
+        tokens: Vec<(Token<'a>, Origin)>,
     ) -> Result<Vec<Section<'a>>, MDPError>;
 }